@@ -0,0 +1,72 @@
+//! Timings for the calls the app makes most often, in bulk. Run with
+//! `cargo bench --bench hot_paths`; compare against a saved run with
+//! `-- --save-baseline main` and `-- --baseline main`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use volt_wasm::{find_variables, run_assertions, substitute_variables, substitute_variables_batch};
+
+fn substitution(c: &mut Criterion) {
+    let variables = r#"{"baseUrl": "https://api.example.com", "version": "v2",
+        "api": "{{baseUrl}}/{{version}}", "token": "abc123", "userId": "42"}"#;
+    let texts: Vec<String> = (0..5000)
+        .map(|i| {
+            format!(
+                "{{{{api}}}}/users/{{{{userId}}}}/items/{} ?auth={{{{token}}}}",
+                i
+            )
+        })
+        .collect();
+    let texts_json = serde_json::to_string(&texts).unwrap();
+
+    c.bench_function("substitute_variables_batch x5000", |b| {
+        b.iter(|| substitute_variables_batch(black_box(&texts_json), variables, None))
+    });
+    c.bench_function("substitute_variables x5000", |b| {
+        b.iter(|| {
+            for text in &texts {
+                black_box(substitute_variables(text, variables));
+            }
+        })
+    });
+    c.bench_function("find_variables x5000", |b| {
+        b.iter(|| {
+            for text in &texts {
+                black_box(find_variables(text));
+            }
+        })
+    });
+}
+
+fn assertions(c: &mut Criterion) {
+    let assertions: Vec<serde_json::Value> = (0..500)
+        .map(|i| {
+            serde_json::json!({
+                "id": i.to_string(),
+                "type": if i % 2 == 0 { "bodyJson" } else { "bodyContains" },
+                "property": "data.items[3].name",
+                "operator": if i % 2 == 0 { "exists" } else { "matches" },
+                "expected": "item-[0-9]+",
+                "enabled": true,
+            })
+        })
+        .collect();
+    let items: Vec<serde_json::Value> = (0..10)
+        .map(|i| serde_json::json!({ "name": format!("item-{}", i) }))
+        .collect();
+    let response = serde_json::json!({
+        "statusCode": 200,
+        "headers": {},
+        "body": serde_json::json!({ "data": { "items": items } }).to_string(),
+        "timingMs": 12,
+    })
+    .to_string();
+    let assertions_json = serde_json::Value::from(assertions).to_string();
+    c.bench_function("run_assertions x500 (path + regex)", |b| {
+        b.iter(|| run_assertions(black_box(&assertions_json), &response, None))
+    });
+}
+
+criterion_group!(benches, substitution, assertions);
+criterion_main!(benches);
@@ -0,0 +1,465 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::environments::is_secret;
+use crate::model::Variable;
+use crate::zip;
+
+const FORMAT: &str = "volt-bundle";
+const VERSION: u64 = 1;
+const MANIFEST: &str = "manifest.json";
+/// Holds every other file when the bundle is encrypted.
+const PAYLOAD: &str = "payload.vault";
+
+/// Credential fields of a request's `auth` object.
+const AUTH_SECRETS: &[&str] = &["password", "token", "apiKeyValue"];
+/// Headers that carry credentials, lower-cased.
+pub(crate) const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct BundleOptions {
+    name: String,
+    /// Blank secret variables, auth credentials and credential headers.
+    #[serde(rename = "stripSecrets")]
+    strip_secrets: bool,
+    /// Encrypt the contents (vault format); empty for a plain bundle.
+    passphrase: String,
+    #[serde(rename = "createdAt")]
+    created_at: Option<f64>,
+}
+
+/// Package a whole workspace into one portable ZIP archive.
+/// collections_json / environments_json: JSON arrays; history_json: optional JSON array.
+/// options_json: optional {name, stripSecrets, passphrase, createdAt}.
+/// The archive holds manifest.json plus collections/*.json, environments/*.json and
+/// history.json; with a passphrase those files are sealed into payload.vault and the
+/// manifest only says the bundle is encrypted.
+/// Returns the archive bytes, or an empty array on invalid input (the reason is logged).
+#[wasm_bindgen]
+pub fn bundle_create(
+    collections_json: &str,
+    environments_json: &str,
+    history_json: Option<String>,
+    options_json: &str,
+) -> Vec<u8> {
+    let options: BundleOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let _timer = crate::logging::timer("bundle_create");
+    match create(
+        collections_json,
+        environments_json,
+        history_json.as_deref(),
+        options,
+    ) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            crate::logging::warn("bundle", || e);
+            Vec::new()
+        }
+    }
+}
+
+/// Unpack an archive written by `bundle_create`.
+/// passphrase: required for encrypted bundles.
+/// Returns JSON {manifest, collections, environments, history}, or {error, encrypted}
+/// where `encrypted` tells the UI to prompt for a passphrase.
+#[wasm_bindgen]
+pub fn bundle_extract(bytes: &[u8], passphrase: Option<String>) -> String {
+    let _timer = crate::logging::timer("bundle_extract");
+    match extract(bytes, passphrase.as_deref().unwrap_or("")) {
+        Ok(result) => serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string()),
+        Err((message, encrypted)) => {
+            serde_json::json!({ "error": message, "encrypted": encrypted }).to_string()
+        }
+    }
+}
+
+fn parse_list(label: &str, json: &str) -> Result<Vec<Value>, String> {
+    match serde_json::from_str(json) {
+        Ok(Value::Array(items)) => Ok(items),
+        _ => Err(format!("{} must be a JSON array", label)),
+    }
+}
+
+fn create(
+    collections_json: &str,
+    environments_json: &str,
+    history_json: Option<&str>,
+    options: BundleOptions,
+) -> Result<Vec<u8>, String> {
+    let mut collections = parse_list("Collections", collections_json)?;
+    let mut environments = parse_list("Environments", environments_json)?;
+    let mut history = history_json
+        .map(|json| parse_list("History", json))
+        .transpose()?;
+    if options.strip_secrets {
+        collections.iter_mut().for_each(strip_requests);
+        environments.iter_mut().for_each(strip_environment);
+        history.iter_mut().flatten().for_each(strip_requests);
+    }
+
+    let created_at = options.created_at.unwrap_or_else(crate::now_ms);
+    let mut files: Vec<(String, Value)> = Vec::new();
+    let listing = |dir: &str, items: Vec<Value>, files: &mut Vec<(String, Value)>| {
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let name = item.get("name").and_then(Value::as_str).unwrap_or("");
+                let file = format!("{}/{:03}-{}.json", dir, i + 1, slug(name));
+                let entry = serde_json::json!({ "name": name, "file": file });
+                files.push((file, item));
+                entry
+            })
+            .collect::<Vec<_>>()
+    };
+    let collection_list = listing("collections", collections, &mut files);
+    let environment_list = listing("environments", environments, &mut files);
+    let history_file = history.map(|h| {
+        files.push(("history.json".to_string(), Value::Array(h)));
+        "history.json"
+    });
+
+    let encrypted = !options.passphrase.is_empty();
+    let manifest = serde_json::json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "name": options.name,
+        "createdAt": created_at,
+        "encrypted": encrypted,
+        "secretsStripped": options.strip_secrets,
+        "collections": collection_list,
+        "environments": environment_list,
+        "history": history_file,
+    });
+
+    let entries: Vec<(String, Vec<u8>)> = if encrypted {
+        let mut payload = Map::new();
+        payload.insert(MANIFEST.to_string(), manifest);
+        payload.extend(files);
+        let sealed = seal(&Value::Object(payload).to_string(), &options.passphrase)?;
+        let public = serde_json::json!({
+            "format": FORMAT,
+            "version": VERSION,
+            "createdAt": created_at,
+            "encrypted": true,
+        });
+        vec![
+            (MANIFEST.to_string(), pretty(&public)),
+            (PAYLOAD.to_string(), sealed.into_bytes()),
+        ]
+    } else {
+        std::iter::once((MANIFEST.to_string(), pretty(&manifest)))
+            .chain(
+                files
+                    .into_iter()
+                    .map(|(name, value)| (name, pretty(&value))),
+            )
+            .collect()
+    };
+    Ok(zip::write(&entries, created_at))
+}
+
+fn extract(bytes: &[u8], passphrase: &str) -> Result<Value, (String, bool)> {
+    let plain_err = |e: String| (e, false);
+    let entries = zip::read(bytes).map_err(plain_err)?;
+    let file = |name: &str| entries.iter().find(|(n, _)| n == name).map(|(_, d)| d);
+    let parse = |name: &str, data: &[u8]| {
+        serde_json::from_slice::<Value>(data)
+            .map_err(|e| (format!("Invalid {}: {}", name, e), false))
+    };
+
+    let manifest = parse(
+        MANIFEST,
+        file(MANIFEST).ok_or_else(|| plain_err("Bundle has no manifest".to_string()))?,
+    )?;
+    if manifest["format"] != FORMAT {
+        return Err(plain_err("Not a Volt workspace bundle".to_string()));
+    }
+    if manifest["version"].as_u64().is_none_or(|v| v > VERSION) {
+        return Err(plain_err(format!(
+            "Unsupported bundle version {}",
+            manifest["version"]
+        )));
+    }
+
+    // Encrypted bundles carry the real manifest and files inside the payload.
+    let mut files: Map<String, Value> = Map::new();
+    let manifest = if manifest["encrypted"] == true {
+        if passphrase.is_empty() {
+            return Err((
+                "Bundle is encrypted; a passphrase is required".to_string(),
+                true,
+            ));
+        }
+        let sealed =
+            file(PAYLOAD).ok_or_else(|| plain_err("Bundle payload is missing".to_string()))?;
+        let plain = open(&String::from_utf8_lossy(sealed), passphrase).map_err(|e| (e, true))?;
+        let Ok(Value::Object(payload)) = serde_json::from_str::<Value>(&plain) else {
+            return Err(plain_err("Bundle payload is corrupted".to_string()));
+        };
+        files = payload;
+        files.remove(MANIFEST).unwrap_or(Value::Null)
+    } else {
+        for (name, data) in &entries {
+            if name != MANIFEST && name.ends_with(".json") {
+                files.insert(name.clone(), parse(name, data)?);
+            }
+        }
+        manifest
+    };
+
+    let mut take = |list: &Value| -> Result<Vec<Value>, (String, bool)> {
+        list.as_array()
+            .into_iter()
+            .flatten()
+            .map(|entry| {
+                let name = entry["file"].as_str().unwrap_or("");
+                files
+                    .remove(name)
+                    .ok_or_else(|| plain_err(format!("Bundle is missing \"{}\"", name)))
+            })
+            .collect()
+    };
+    let collections = take(&manifest["collections"])?;
+    let environments = take(&manifest["environments"])?;
+    let history = match manifest["history"].as_str() {
+        Some(name) => files
+            .remove(name)
+            .ok_or_else(|| plain_err(format!("Bundle is missing \"{}\"", name)))?,
+        None => Value::Null,
+    };
+    Ok(serde_json::json!({
+        "manifest": manifest,
+        "collections": collections,
+        "environments": environments,
+        "history": history,
+    }))
+}
+
+#[cfg(feature = "vault")]
+fn seal(plain: &str, passphrase: &str) -> Result<String, String> {
+    crate::vault::encrypt_with(plain, passphrase, crate::vault::DEFAULT_COST)
+}
+
+#[cfg(feature = "vault")]
+fn open(sealed: &str, passphrase: &str) -> Result<String, String> {
+    crate::vault::decrypt(sealed, passphrase)
+}
+
+#[cfg(not(feature = "vault"))]
+fn seal(_plain: &str, _passphrase: &str) -> Result<String, String> {
+    Err("Encrypted bundles require the vault feature".to_string())
+}
+
+#[cfg(not(feature = "vault"))]
+fn open(_sealed: &str, _passphrase: &str) -> Result<String, String> {
+    Err("Encrypted bundles require the vault feature".to_string())
+}
+
+fn pretty(value: &Value) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).unwrap_or_default()
+}
+
+/// File-name-safe form of a display name.
+fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out.trim_end_matches('-');
+    if out.is_empty() {
+        "untitled".to_string()
+    } else {
+        out.chars().take(48).collect()
+    }
+}
+
+/// Blank the values of secret variables (list or map form).
+fn strip_environment(env: &mut Value) {
+    match env.get_mut("variables") {
+        Some(Value::Array(vars)) => {
+            for var in vars {
+                let Ok(parsed) = serde_json::from_value::<Variable>(var.clone()) else {
+                    continue;
+                };
+                if is_secret(&parsed) {
+                    var["value"] = Value::from("");
+                }
+            }
+        }
+        Some(Value::Object(vars)) => {
+            for (key, value) in vars.iter_mut() {
+                if is_secret(&Variable::new(key, "")) {
+                    *value = Value::from("");
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Blank auth credentials and credential headers anywhere in a collection or
+/// history entry.
+fn strip_requests(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Object(auth)) = map.get_mut("auth") {
+                for field in AUTH_SECRETS {
+                    if let Some(v) = auth.get_mut(*field) {
+                        *v = Value::from("");
+                    }
+                }
+            }
+            match map.get_mut("headers") {
+                Some(Value::Object(headers)) => {
+                    for (name, v) in headers.iter_mut() {
+                        if SECRET_HEADERS.contains(&name.to_lowercase().as_str()) {
+                            *v = Value::from("");
+                        }
+                    }
+                }
+                Some(Value::Array(headers)) => {
+                    for h in headers {
+                        let secret = h["key"]
+                            .as_str()
+                            .is_some_and(|k| SECRET_HEADERS.contains(&k.to_lowercase().as_str()));
+                        if secret {
+                            h["value"] = Value::from("");
+                        }
+                    }
+                }
+                _ => {}
+            }
+            map.values_mut().for_each(strip_requests);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_requests),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collections() -> String {
+        serde_json::json!([{
+            "name": "Users API",
+            "requests": [{"id": "a", "name": "Me", "url": "/me",
+                "headers": {"Authorization": "Bearer abc", "Accept": "*/*"},
+                "auth": {"type": "basic", "username": "u", "password": "p"}}]
+        }])
+        .to_string()
+    }
+
+    fn environments() -> String {
+        serde_json::json!([
+            {"name": "Prod", "variables": [{"key": "host", "value": "x.io"}, {"key": "apiToken", "value": "t0k"}]},
+            {"name": "Dev", "variables": {"password": "pw", "host": "localhost"}}
+        ])
+        .to_string()
+    }
+
+    fn extract_json(bytes: &[u8], passphrase: Option<&str>) -> Value {
+        serde_json::from_str(&bundle_extract(bytes, passphrase.map(str::to_string))).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let history = r#"[{"request":{"method":"GET","url":"/me","headers":[{"key":"Cookie","value":"s=1"}]}}]"#;
+        let bytes = bundle_create(
+            &collections(),
+            &environments(),
+            Some(history.to_string()),
+            r#"{"name":"Workspace","createdAt":1700000000000}"#,
+        );
+        let names: Vec<String> = zip::read(&bytes)
+            .unwrap()
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "manifest.json",
+                "collections/001-users-api.json",
+                "environments/001-prod.json",
+                "environments/002-dev.json",
+                "history.json"
+            ]
+        );
+        let out = extract_json(&bytes, None);
+        assert_eq!(out["manifest"]["name"], "Workspace");
+        assert_eq!(out["manifest"]["secretsStripped"], false);
+        assert_eq!(
+            out["collections"],
+            serde_json::from_str::<Value>(&collections()).unwrap()
+        );
+        assert_eq!(out["environments"][1]["variables"]["password"], "pw");
+        assert_eq!(out["history"][0]["request"]["url"], "/me");
+    }
+
+    #[test]
+    fn test_bundle_strips_secrets() {
+        let history = r#"[{"request":{"headers":[{"key":"Cookie","value":"s=1"}]}}]"#;
+        let bytes = bundle_create(
+            &collections(),
+            &environments(),
+            Some(history.to_string()),
+            r#"{"stripSecrets":true}"#,
+        );
+        let out = extract_json(&bytes, None);
+        let request = &out["collections"][0]["requests"][0];
+        assert_eq!(request["headers"]["Authorization"], "");
+        assert_eq!(request["headers"]["Accept"], "*/*");
+        assert_eq!(request["auth"]["password"], "");
+        assert_eq!(request["auth"]["username"], "u");
+        assert_eq!(out["environments"][0]["variables"][0]["value"], "x.io");
+        assert_eq!(out["environments"][0]["variables"][1]["value"], "");
+        assert_eq!(out["environments"][1]["variables"]["password"], "");
+        assert_eq!(out["history"][0]["request"]["headers"][0]["value"], "");
+    }
+
+    #[cfg(feature = "vault")]
+    #[test]
+    fn test_bundle_encrypted() {
+        let options = BundleOptions {
+            passphrase: "hunter2".to_string(),
+            ..Default::default()
+        };
+        // Uses the real KDF cost, so keep this to a single round trip.
+        let bytes = create(&collections(), "[]", None, options).unwrap();
+        let raw = String::from_utf8_lossy(&bytes);
+        assert!(!raw.contains("Users API") && !raw.contains("Bearer abc"));
+
+        let locked = extract_json(&bytes, None);
+        assert_eq!(locked["encrypted"], true);
+        assert!(locked["error"].as_str().unwrap().contains("passphrase"));
+        let out = extract_json(&bytes, Some("hunter2"));
+        assert_eq!(out["collections"][0]["name"], "Users API");
+        assert_eq!(out["manifest"]["encrypted"], true);
+    }
+
+    #[test]
+    fn test_bundle_invalid_input() {
+        assert!(bundle_create("{}", "[]", None, "").is_empty());
+        let out = extract_json(b"not a zip", None);
+        assert_eq!(out["error"], "Not a ZIP archive");
+        let foreign = zip::write(&[(MANIFEST.to_string(), b"{}".to_vec())], 0.0);
+        assert_eq!(
+            extract_json(&foreign, None)["error"],
+            "Not a Volt workspace bundle"
+        );
+    }
+}
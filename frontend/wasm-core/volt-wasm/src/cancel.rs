@@ -0,0 +1,191 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// How many checkpoints pass between evaluations of the (comparatively
+/// expensive) deadline and JS poll callback.
+const SLOW_CHECK_INTERVAL: u32 = 256;
+
+#[derive(Default)]
+struct AbortState {
+    aborted: Cell<bool>,
+    deadline_ms: Cell<Option<f64>>,
+    poll: RefCell<Option<js_sys::Function>>,
+    checks: Cell<u32>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<AbortState>>> = const { RefCell::new(None) };
+}
+
+/// Cooperative cancellation token for long-running operations.
+///
+/// Activate a handle around a call; operations check it at safe points and
+/// return `{"cancelled":true}` once it fires. Because WASM runs on the calling
+/// thread, a handle can fire through `abort()` (between chunks of work), a
+/// timeout, or a poll callback (e.g. reading an `Atomics` flag from a worker).
+#[wasm_bindgen]
+pub struct AbortHandle {
+    state: Rc<AbortState>,
+}
+
+impl Default for AbortHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl AbortHandle {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AbortHandle {
+        AbortHandle {
+            state: Rc::new(AbortState::default()),
+        }
+    }
+
+    pub fn abort(&self) {
+        self.state.aborted.set(true);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn aborted(&self) -> bool {
+        self.state.aborted.get()
+    }
+
+    /// Clear the aborted flag and any deadline so the handle can be reused.
+    pub fn reset(&self) {
+        self.state.aborted.set(false);
+        self.state.deadline_ms.set(None);
+        self.state.checks.set(0);
+    }
+
+    /// Abort automatically once `ms` milliseconds have elapsed from now.
+    pub fn set_timeout_ms(&self, ms: f64) {
+        self.state.deadline_ms.set(Some(crate::now_ms() + ms));
+    }
+
+    /// Register a callback polled at safe points; a truthy return aborts.
+    pub fn set_poll(&self, poll: js_sys::Function) {
+        *self.state.poll.borrow_mut() = Some(poll);
+    }
+
+    /// Make this the handle checked by operations started from now on.
+    pub fn activate(&self) {
+        self.state.checks.set(0);
+        CURRENT.with(|c| *c.borrow_mut() = Some(self.state.clone()));
+    }
+
+    /// Stop checking this handle (no-op if another handle is active).
+    pub fn deactivate(&self) {
+        CURRENT.with(|c| {
+            let mut current = c.borrow_mut();
+            if current.as_ref().is_some_and(|s| Rc::ptr_eq(s, &self.state)) {
+                *current = None;
+            }
+        });
+    }
+}
+
+/// Marker error returned from a checkpoint once the active handle has fired.
+pub(crate) struct Cancelled;
+
+/// Safe point for long-running work. Cheap when no handle is active.
+pub(crate) fn checkpoint() -> Result<(), Cancelled> {
+    let state = CURRENT.with(|c| c.borrow().clone());
+    let Some(state) = state else {
+        return Ok(());
+    };
+    if state.aborted.get() {
+        return Err(Cancelled);
+    }
+
+    let n = state.checks.get();
+    state.checks.set(n.wrapping_add(1));
+    if n % SLOW_CHECK_INTERVAL != 0 {
+        return Ok(());
+    }
+
+    if state
+        .deadline_ms
+        .get()
+        .is_some_and(|deadline| crate::now_ms() >= deadline)
+    {
+        state.aborted.set(true);
+        return Err(Cancelled);
+    }
+    let fired = state
+        .poll
+        .borrow()
+        .as_ref()
+        .is_some_and(|f| f.call0(&JsValue::NULL).is_ok_and(|v| v.is_truthy()));
+    if fired {
+        state.aborted.set(true);
+        return Err(Cancelled);
+    }
+    Ok(())
+}
+
+/// Standard result body for an operation stopped by its abort handle.
+pub(crate) fn cancelled_json() -> String {
+    r#"{"cancelled":true}"#.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_without_handle() {
+        assert!(checkpoint().is_ok());
+    }
+
+    #[test]
+    fn test_abort_handle_lifecycle() {
+        let handle = AbortHandle::new();
+        handle.activate();
+        assert!(checkpoint().is_ok());
+        handle.abort();
+        assert!(checkpoint().is_err());
+        assert!(handle.aborted());
+        handle.reset();
+        assert!(checkpoint().is_ok());
+        handle.deactivate();
+        handle.abort();
+        assert!(checkpoint().is_ok());
+    }
+
+    #[test]
+    fn test_abort_handle_timeout() {
+        let handle = AbortHandle::new();
+        handle.set_timeout_ms(-1.0);
+        handle.activate();
+        assert!(checkpoint().is_err());
+        assert!(handle.aborted());
+        handle.deactivate();
+    }
+
+    #[test]
+    fn test_operations_stop_when_cancelled() {
+        let handle = AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(
+            crate::substitute_variables_batch(r#"["{{a}}"]"#, r#"{"a":"1"}"#, None),
+            cancelled_json()
+        );
+        assert_eq!(
+            crate::collection_diff::diff_collections(
+                r#"{"requests":[{"id":"1"}]}"#,
+                r#"{"requests":[]}"#,
+                None
+            ),
+            cancelled_json()
+        );
+        handle.deactivate();
+        assert_eq!(
+            crate::substitute_variables_batch(r#"["{{a}}"]"#, r#"{"a":"1"}"#, None),
+            r#"["1"]"#
+        );
+    }
+}
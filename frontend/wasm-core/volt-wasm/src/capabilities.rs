@@ -0,0 +1,139 @@
+use wasm_bindgen::prelude::*;
+
+/// Assertion types understood by `run_assertions`, with their operators.
+const ASSERTIONS: &[(&str, &[&str])] = &[
+    (
+        "status",
+        &["equals", "notEquals", "lessThan", "greaterThan"],
+    ),
+    ("responseTime", &["lessThan", "greaterThan"]),
+    ("bodyContains", &["contains", "notContains", "matches"]),
+    (
+        "bodyJson",
+        &["exists", "notExists", "equals", "notEquals", "contains"],
+    ),
+    ("bodySchema", &[]),
+    ("noDuplicateKeys", &[]),
+    ("headerExists", &["exists", "notExists"]),
+    ("headerEquals", &["equals", "notEquals", "contains"]),
+];
+
+/// Inputs the importers accept.
+const IMPORT_FORMATS: &[&str] = &[
+    "fetch",
+    "powershell",
+    "rawHttp",
+    "urlList",
+    "sitemap",
+    "voltBinary",
+    "voltBundle",
+];
+
+/// Values of an assertion's `queryLanguage`.
+const QUERY_LANGUAGES: &[&str] = &["path", "jmespath"];
+
+const LOAD_SCRIPT_TARGETS: &[&str] = &["k6", "jmeter", "gatling"];
+const TEST_CODE_FRAMEWORKS: &[&str] = &["playwright", "cypress"];
+const RUN_RESULT_FORMATS: &[&str] = &["newman"];
+
+/// Describe this build of the core so the frontend can feature-detect instead
+/// of assuming which wasm binary it loaded.
+/// Returns JSON {version, features: {vault, preciseNumbers, consoleErrorPanicHook},
+/// assertions: [{type, operators}], queryLanguages, formats: {import, loadScript,
+/// testCode, runResults, collection}}. `version` is the crate's semantic version.
+#[wasm_bindgen]
+pub fn wasm_capabilities() -> String {
+    let mut collection = vec!["voltBinary"];
+    if cfg!(feature = "vault") {
+        collection.push("voltBundle");
+    }
+    let import: Vec<&str> = IMPORT_FORMATS
+        .iter()
+        .copied()
+        .filter(|f| *f != "voltBundle" || cfg!(feature = "vault"))
+        .collect();
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": {
+            "vault": cfg!(feature = "vault"),
+            "preciseNumbers": cfg!(feature = "precise_numbers"),
+            "consoleErrorPanicHook": cfg!(feature = "console_error_panic_hook"),
+        },
+        "assertions": ASSERTIONS
+            .iter()
+            .map(|(kind, operators)| serde_json::json!({ "type": kind, "operators": operators }))
+            .collect::<Vec<_>>(),
+        "queryLanguages": QUERY_LANGUAGES,
+        "formats": {
+            "import": import,
+            "loadScript": LOAD_SCRIPT_TARGETS,
+            "testCode": TEST_CODE_FRAMEWORKS,
+            "runResults": RUN_RESULT_FORMATS,
+            "collection": collection,
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_wasm_capabilities() {
+        let caps: Value = serde_json::from_str(&wasm_capabilities()).unwrap();
+        assert_eq!(caps["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(caps["features"]["vault"], cfg!(feature = "vault"));
+        assert_eq!(caps["assertions"][0]["type"], "status");
+        assert_eq!(caps["formats"]["loadScript"][0], "k6");
+    }
+
+    #[test]
+    fn test_capabilities_match_implementation() {
+        let response =
+            r#"{"statusCode": 200, "headers": {"A": "1"}, "body": "{\"a\": 1}", "timingMs": 5}"#;
+        for (kind, operators) in ASSERTIONS {
+            for operator in operators
+                .iter()
+                .copied()
+                .chain(operators.is_empty().then_some(""))
+            {
+                let assertion = serde_json::json!([{
+                    "id": "1", "type": kind, "property": "a", "operator": operator,
+                    "expected": "1", "enabled": true,
+                }]);
+                let results: Value = serde_json::from_str(&crate::run_assertions(
+                    &assertion.to_string(),
+                    response,
+                    None,
+                ))
+                .unwrap();
+                let code = results[0]["code"].as_str().unwrap();
+                assert!(
+                    !code.starts_with("assertion.unknown"),
+                    "{} {}: {}",
+                    kind,
+                    operator,
+                    code
+                );
+            }
+        }
+
+        let request = r#"{"name": "R", "method": "GET", "url": "https://x.io"}"#;
+        for target in LOAD_SCRIPT_TARGETS {
+            assert!(!crate::codegen::export_load_script(request, target).is_empty());
+        }
+        for framework in TEST_CODE_FRAMEWORKS {
+            assert!(!crate::codegen::export_test_code(request, framework).is_empty());
+        }
+        for format in RUN_RESULT_FORMATS {
+            let out: Value = serde_json::from_str(&crate::run_results::export_run_results(
+                r#"{"results": []}"#,
+                format,
+            ))
+            .unwrap();
+            assert!(out["error"].is_null(), "{}", out);
+        }
+    }
+}
@@ -0,0 +1,640 @@
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, civil_from_days, days_from_civil};
+
+const DAY_MS: f64 = 86_400_000.0;
+/// Certificates expiring within this many days get an "expiringSoon" warning.
+const EXPIRY_WARNING_DAYS: f64 = 30.0;
+const MIN_RSA_BITS: u32 = 2048;
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_VERSION: u8 = 0xA0;
+const TAG_EXTENSIONS: u8 = 0xA3;
+const SAN_DNS: u8 = 0x82;
+const SAN_IP: u8 = 0x87;
+const SAN_URI: u8 = 0x86;
+const SAN_EMAIL: u8 = 0x81;
+
+#[derive(Serialize)]
+struct Warning {
+    index: usize,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ParseError {
+    index: usize,
+    message: String,
+}
+
+struct Certificate {
+    subject: String,
+    issuer: String,
+    /// Raw DER of the names, compared when checking chain links.
+    subject_der: Vec<u8>,
+    issuer_der: Vec<u8>,
+    serial: String,
+    not_before: f64,
+    not_after: f64,
+    is_ca: bool,
+    sans: Vec<String>,
+    key_algorithm: String,
+    key_bits: Option<u32>,
+    curve: Option<&'static str>,
+    signature_algorithm: String,
+    fingerprint: String,
+}
+
+/// Inspect a PEM certificate chain, leaf first.
+/// pems: one or more "-----BEGIN CERTIFICATE-----" blocks; other PEM blocks are ignored.
+/// now_ms: reference time for expiry checks; defaults to the current time.
+/// Returns JSON {certificates: [{index, subject, issuer, serialNumber, notBefore,
+/// notAfter, daysUntilExpiry, expired, notYetValid, selfSigned, isCa, sans,
+/// keyAlgorithm, keyBits, curve, signatureAlgorithm, fingerprintSha256}],
+/// chain: {ordered, complete, issues}, warnings: [{index, code, message}],
+/// errors: [{index, message}]}.
+/// Warning codes: "expired", "expiringSoon", "notYetValid", "weakKey", "weakSignature",
+/// "missingSans". Signatures are not verified; the chain check compares names only.
+#[wasm_bindgen]
+pub fn parse_cert_chain(pems: &str, now_ms: Option<f64>) -> String {
+    let now = now_ms.unwrap_or_else(crate::now_ms);
+    let mut certs = Vec::new();
+    let mut errors = Vec::new();
+    for (index, block) in pem_blocks(pems).into_iter().enumerate() {
+        let parsed = base64_decode(&block)
+            .ok_or_else(|| "Invalid base64 in PEM block".to_string())
+            .and_then(|der| {
+                parse_certificate(&der).ok_or_else(|| "Invalid DER certificate".to_string())
+            });
+        match parsed {
+            Ok(cert) => certs.push((index, cert)),
+            Err(message) => errors.push(ParseError { index, message }),
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut out = Vec::new();
+    for (index, cert) in &certs {
+        let index = *index;
+        let days = ((cert.not_after - now) / DAY_MS).floor();
+        let expired = now > cert.not_after;
+        let not_yet_valid = now < cert.not_before;
+        let mut warn = |code, message: String| {
+            warnings.push(Warning {
+                index,
+                code,
+                message,
+            })
+        };
+        if expired {
+            warn(
+                "expired",
+                format!("{} expired on {}", cert.subject, iso(cert.not_after)),
+            );
+        } else if days < EXPIRY_WARNING_DAYS {
+            warn(
+                "expiringSoon",
+                format!("{} expires in {} day(s)", cert.subject, days),
+            );
+        }
+        if not_yet_valid {
+            warn(
+                "notYetValid",
+                format!(
+                    "{} is not valid until {}",
+                    cert.subject,
+                    iso(cert.not_before)
+                ),
+            );
+        }
+        if cert.key_algorithm == "RSA" && cert.key_bits.is_some_and(|b| b < MIN_RSA_BITS) {
+            warn(
+                "weakKey",
+                format!(
+                    "{} uses a {}-bit RSA key",
+                    cert.subject,
+                    cert.key_bits.unwrap_or(0)
+                ),
+            );
+        }
+        let sig = cert.signature_algorithm.to_lowercase();
+        if sig.contains("sha1") || sig.contains("md5") {
+            warn(
+                "weakSignature",
+                format!(
+                    "{} is signed with {}",
+                    cert.subject, cert.signature_algorithm
+                ),
+            );
+        }
+        if index == 0 && !cert.is_ca && cert.sans.is_empty() {
+            warn(
+                "missingSans",
+                format!(
+                    "{} has no subject alternative names; clients ignore the common name",
+                    cert.subject
+                ),
+            );
+        }
+
+        out.push(serde_json::json!({
+            "index": index,
+            "subject": cert.subject,
+            "issuer": cert.issuer,
+            "serialNumber": cert.serial,
+            "notBefore": iso(cert.not_before),
+            "notAfter": iso(cert.not_after),
+            "daysUntilExpiry": days,
+            "expired": expired,
+            "notYetValid": not_yet_valid,
+            "selfSigned": cert.subject_der == cert.issuer_der,
+            "isCa": cert.is_ca,
+            "sans": cert.sans,
+            "keyAlgorithm": cert.key_algorithm,
+            "keyBits": cert.key_bits,
+            "curve": cert.curve,
+            "signatureAlgorithm": cert.signature_algorithm,
+            "fingerprintSha256": cert.fingerprint,
+        }));
+    }
+
+    let mut issues = Vec::new();
+    for pair in certs.windows(2) {
+        let ((i, child), (j, parent)) = (&pair[0], &pair[1]);
+        if child.issuer_der != parent.subject_der {
+            issues.push(format!(
+                "Certificate {} ({}) is not issued by certificate {} ({})",
+                i, child.subject, j, parent.subject
+            ));
+        } else if !parent.is_ca {
+            issues.push(format!(
+                "Certificate {} ({}) issues certificate {} but is not a CA",
+                j, parent.subject, i
+            ));
+        }
+    }
+    let complete = certs
+        .last()
+        .is_some_and(|(_, c)| c.subject_der == c.issuer_der);
+
+    serde_json::to_string(&serde_json::json!({
+        "certificates": out,
+        "chain": {
+            "ordered": issues.is_empty(),
+            "complete": complete,
+            "issues": issues,
+        },
+        "warnings": warnings,
+        "errors": errors,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Base64 bodies of the CERTIFICATE blocks in a PEM bundle.
+fn pem_blocks(pems: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for line in pems.lines().map(str::trim) {
+        if line == "-----BEGIN CERTIFICATE-----" {
+            current = Some(String::new());
+        } else if line == "-----END CERTIFICATE-----" {
+            blocks.extend(current.take());
+        } else if let Some(body) = current.as_mut() {
+            body.push_str(line);
+        }
+    }
+    blocks
+}
+
+/// A DER reader over one level of TLVs.
+struct Der<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Der<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Der { bytes, pos: 0 }
+    }
+
+    fn peek_tag(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// Next (tag, contents, whole TLV).
+    fn next(&mut self) -> Option<(u8, &'a [u8], &'a [u8])> {
+        let start = self.pos;
+        let tag = *self.bytes.get(start)?;
+        let first = *self.bytes.get(start + 1)?;
+        let (len, header) = if first < 0x80 {
+            (first as usize, 2)
+        } else {
+            let n = (first & 0x7F) as usize;
+            if n == 0 || n > 4 {
+                return None;
+            }
+            let len = self
+                .bytes
+                .get(start + 2..start + 2 + n)?
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            (len, 2 + n)
+        };
+        let end = start.checked_add(header)?.checked_add(len)?;
+        let contents = self.bytes.get(start + header..end)?;
+        self.pos = end;
+        Some((tag, contents, &self.bytes[start..end]))
+    }
+
+    fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        match self.next()? {
+            (t, contents, _) if t == tag => Some(contents),
+            _ => None,
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+fn parse_certificate(der: &[u8]) -> Option<Certificate> {
+    let mut outer = Der::new(Der::new(der).expect(TAG_SEQUENCE)?);
+    let mut tbs = Der::new(outer.expect(TAG_SEQUENCE)?);
+    let signature_algorithm = algorithm(outer.expect(TAG_SEQUENCE)?)?.0;
+
+    if tbs.peek_tag() == Some(TAG_VERSION) {
+        tbs.next()?;
+    }
+    let serial = tbs.expect(TAG_INTEGER)?;
+    let serial = match serial {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        s => s,
+    };
+    tbs.expect(TAG_SEQUENCE)?;
+    let (_, issuer, issuer_der) = tbs.next()?;
+    let mut validity = Der::new(tbs.expect(TAG_SEQUENCE)?);
+    let not_before = time(validity.next()?)?;
+    let not_after = time(validity.next()?)?;
+    let (_, subject, subject_der) = tbs.next()?;
+    let (key_algorithm, key_bits, curve) = public_key(tbs.expect(TAG_SEQUENCE)?)?;
+
+    let mut cert = Certificate {
+        subject: name(subject)?,
+        issuer: name(issuer)?,
+        subject_der: subject_der.to_vec(),
+        issuer_der: issuer_der.to_vec(),
+        serial: colon_hex(serial),
+        not_before,
+        not_after,
+        is_ca: false,
+        sans: Vec::new(),
+        key_algorithm,
+        key_bits,
+        curve,
+        signature_algorithm: signature_name(&signature_algorithm),
+        fingerprint: colon_hex(&Sha256::digest(der)),
+    };
+
+    while !tbs.at_end() {
+        let (tag, contents, _) = tbs.next()?;
+        if tag != TAG_EXTENSIONS {
+            continue;
+        }
+        let mut list = Der::new(Der::new(contents).expect(TAG_SEQUENCE)?);
+        while !list.at_end() {
+            let mut ext = Der::new(list.expect(TAG_SEQUENCE)?);
+            let id = oid(ext.expect(TAG_OID)?);
+            if ext.peek_tag() == Some(TAG_BOOLEAN) {
+                ext.next()?;
+            }
+            let value = ext.expect(TAG_OCTET_STRING)?;
+            match id.as_str() {
+                "2.5.29.17" => cert.sans = subject_alt_names(value)?,
+                "2.5.29.19" => {
+                    let mut constraints = Der::new(Der::new(value).expect(TAG_SEQUENCE)?);
+                    cert.is_ca = constraints.peek_tag() == Some(TAG_BOOLEAN)
+                        && constraints.expect(TAG_BOOLEAN)? != [0];
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(cert)
+}
+
+/// An AlgorithmIdentifier's OID and optional parameters (tag, contents).
+type Algorithm<'a> = (String, Option<(u8, &'a [u8])>);
+
+fn algorithm(contents: &[u8]) -> Option<Algorithm<'_>> {
+    let mut der = Der::new(contents);
+    let id = oid(der.expect(TAG_OID)?);
+    let params = der.next().map(|(tag, contents, _)| (tag, contents));
+    Some((id, params))
+}
+
+/// (algorithm, key size in bits, curve) from a SubjectPublicKeyInfo.
+fn public_key(contents: &[u8]) -> Option<(String, Option<u32>, Option<&'static str>)> {
+    let mut der = Der::new(contents);
+    let (id, params) = algorithm(der.expect(TAG_SEQUENCE)?)?;
+    let key = der.expect(TAG_BIT_STRING)?;
+    Some(match id.as_str() {
+        "1.2.840.113549.1.1.1" => {
+            // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+            let mut rsa = Der::new(Der::new(key.get(1..)?).expect(TAG_SEQUENCE)?);
+            let modulus = rsa.expect(TAG_INTEGER)?;
+            let modulus = match modulus.iter().position(|&b| b != 0) {
+                Some(i) => &modulus[i..],
+                None => &[],
+            };
+            let bits = modulus
+                .first()
+                .map(|b| (modulus.len() as u32 - 1) * 8 + (8 - b.leading_zeros()));
+            ("RSA".to_string(), bits, None)
+        }
+        "1.2.840.10045.2.1" => {
+            let curve = params
+                .filter(|(tag, _)| *tag == TAG_OID)
+                .map(|(_, c)| oid(c));
+            let (name, bits) = match curve.as_deref() {
+                Some("1.2.840.10045.3.1.7") => (Some("P-256"), Some(256)),
+                Some("1.3.132.0.34") => (Some("P-384"), Some(384)),
+                Some("1.3.132.0.35") => (Some("P-521"), Some(521)),
+                Some("1.3.132.0.10") => (Some("secp256k1"), Some(256)),
+                _ => (None, None),
+            };
+            ("EC".to_string(), bits, name)
+        }
+        "1.3.101.112" => ("Ed25519".to_string(), Some(256), None),
+        "1.3.101.113" => ("Ed448".to_string(), Some(456), None),
+        other => (other.to_string(), None, None),
+    })
+}
+
+fn signature_name(id: &str) -> String {
+    match id {
+        "1.2.840.113549.1.1.4" => "md5WithRSAEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "rsassaPss",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.10045.4.1" => "ecdsa-with-SHA1",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        "1.2.840.10045.4.3.4" => "ecdsa-with-SHA512",
+        "1.3.101.112" => "Ed25519",
+        "1.3.101.113" => "Ed448",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Format a Name as "C=US, O=Example, CN=host", in certificate order.
+fn name(contents: &[u8]) -> Option<String> {
+    let mut parts = Vec::new();
+    let mut rdns = Der::new(contents);
+    while !rdns.at_end() {
+        let (_, set, _) = rdns.next()?;
+        let mut attrs = Der::new(set);
+        while !attrs.at_end() {
+            let mut attr = Der::new(attrs.expect(TAG_SEQUENCE)?);
+            let id = oid(attr.expect(TAG_OID)?);
+            let (_, value, _) = attr.next()?;
+            let label = match id.as_str() {
+                "2.5.4.3" => "CN",
+                "2.5.4.5" => "serialNumber",
+                "2.5.4.6" => "C",
+                "2.5.4.7" => "L",
+                "2.5.4.8" => "ST",
+                "2.5.4.10" => "O",
+                "2.5.4.11" => "OU",
+                "1.2.840.113549.1.9.1" => "emailAddress",
+                "0.9.2342.19200300.100.1.25" => "DC",
+                other => other,
+            };
+            parts.push(format!("{}={}", label, String::from_utf8_lossy(value)));
+        }
+    }
+    Some(parts.join(", "))
+}
+
+fn subject_alt_names(value: &[u8]) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    let mut der = Der::new(Der::new(value).expect(TAG_SEQUENCE)?);
+    while !der.at_end() {
+        let (tag, contents, _) = der.next()?;
+        let text = String::from_utf8_lossy(contents);
+        match tag {
+            SAN_DNS => names.push(format!("DNS:{}", text)),
+            SAN_EMAIL => names.push(format!("email:{}", text)),
+            SAN_URI => names.push(format!("URI:{}", text)),
+            SAN_IP => names.push(format!("IP:{}", ip(contents))),
+            _ => {}
+        }
+    }
+    Some(names)
+}
+
+fn ip(bytes: &[u8]) -> String {
+    match bytes.len() {
+        4 => std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string(),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().unwrap_or_default();
+            std::net::Ipv6Addr::from(octets).to_string()
+        }
+        _ => colon_hex(bytes),
+    }
+}
+
+fn oid(bytes: &[u8]) -> String {
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut value: u64 = 0;
+    for &b in bytes {
+        value = (value << 7) | u64::from(b & 0x7F);
+        if b & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.extend([first, value - first * 40]);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// UTCTime or GeneralizedTime (UTC, "Z" suffix) to ms since the epoch.
+fn time((tag, contents, _): (u8, &[u8], &[u8])) -> Option<f64> {
+    let text = std::str::from_utf8(contents).ok()?;
+    let text = text.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        TAG_UTC_TIME => {
+            let yy: i64 = text.get(..2)?.parse().ok()?;
+            (if yy < 50 { 2000 + yy } else { 1900 + yy }, text.get(2..)?)
+        }
+        TAG_GENERALIZED_TIME => (text.get(..4)?.parse().ok()?, text.get(4..)?),
+        _ => return None,
+    };
+    let field = |at: usize| -> Option<i64> { rest.get(at..at + 2)?.parse().ok() };
+    let days = days_from_civil(year, field(0)?, field(2)?);
+    let secs = days * 86_400 + field(4)? * 3600 + field(6)? * 60 + field(8).unwrap_or(0);
+    Some(secs as f64 * 1000.0)
+}
+
+fn iso(ms: f64) -> Value {
+    let secs = (ms / 1000.0) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    Value::from(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    ))
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &str = "-----BEGIN CERTIFICATE-----
+MIIBtTCCAVygAwIBAgIBATAKBggqhkjOPQQDAjA6MQswCQYDVQQGEwJVUzESMBAG
+A1UECgwJVm9sdCBUZXN0MRcwFQYDVQQDDA5Wb2x0IFRlc3QgUm9vdDAeFw0yNjEw
+MTYwMDI4MjlaFw0zNjEwMTMwMDI4MjlaMDoxCzAJBgNVBAYTAlVTMRIwEAYDVQQK
+DAlWb2x0IFRlc3QxFzAVBgNVBAMMDlZvbHQgVGVzdCBSb290MFkwEwYHKoZIzj0C
+AQYIKoZIzj0DAQcDQgAEis5lGOytr9j+nTjBliDc77rDoEqUULa2KMrCKB/3FKNP
+6e+H3q3b3mjhRYvJa7/atr8sdxazota/PQnAyhcxyqNTMFEwHQYDVR0OBBYEFJTq
+nIWxI9SUf/7lSIQm9IW4ChrjMB8GA1UdIwQYMBaAFJTqnIWxI9SUf/7lSIQm9IW4
+ChrjMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDRwAwRAIgbNC56XFyvDAq
+afbHeTVZjM//x0ufHgbokbhCy6YJ6tMCIHYcWqKix9EUm5FEbIXETrn9l6RtfCh0
+J5rSi7kyRNRD
+-----END CERTIFICATE-----
+";
+
+    const LEAF: &str = "-----BEGIN CERTIFICATE-----
+MIIBwjCCAWigAwIBAgICEjQwCgYIKoZIzj0EAwIwOjELMAkGA1UEBhMCVVMxEjAQ
+BgNVBAoMCVZvbHQgVGVzdDEXMBUGA1UEAwwOVm9sdCBUZXN0IFJvb3QwHhcNMjYx
+MDE2MDAyODI5WhcNMjcwMTE0MDAyODI5WjAaMRgwFgYDVQQDDA9hcGkuZXhhbXBs
+ZS5jb20wWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARc971fwEsDhSmkrxSHho+a
+vg5bPPhKRnp3rwpT3W56xFthWHniTIQKR+LujJzbY/VGNEz+vv4ScwfUYZCsETPz
+o34wfDAvBgNVHREEKDAmgg9hcGkuZXhhbXBsZS5jb22CDSouZXhhbXBsZS5jb22H
+BMAAAgEwCQYDVR0TBAIwADAdBgNVHQ4EFgQUnfYQqpWE1hJHuPYxSunFlFZbWncw
+HwYDVR0jBBgwFoAUlOqchbEj1JR//uVIhCb0hbgKGuMwCgYIKoZIzj0EAwIDSAAw
+RQIgIgp3R1NHo+eof6vDvfcO8CxT+cjAdoFhGg7oWl6PHNUCIQCbXmqMxIj7gNoN
+HX7TM6P1zQsnWtuDL2c9585pC4QShw==
+-----END CERTIFICATE-----
+";
+
+    const WEAK: &str = "-----BEGIN CERTIFICATE-----
+MIICHzCCAYigAwIBAgIBBzANBgkqhkiG9w0BAQUFADArMRgwFgYDVQQDDA9vbGQu
+ZXhhbXBsZS5jb20xDzANBgNVBAoMBkxlZ2FjeTAeFw0yNjEwMTYwMDI4MzNaFw0y
+NjEwMTcwMDI4MzNaMCsxGDAWBgNVBAMMD29sZC5leGFtcGxlLmNvbTEPMA0GA1UE
+CgwGTGVnYWN5MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDVuGWSHy80Acng
+y5usWmVPyOx5EtU/fBIhzmU7mMmDW+NDtvzDeEBGBCJjVoIq9sypC2maXMKVIkWx
+79/6hAkQfbXjac8EYLfFLL/gSx59dH4FgDMOtlcGFgPV7ZkS4BaABLzhhIwY67Ne
+YGgthqqjg6VgL886VXblbrwmOWnVsQIDAQABo1MwUTAdBgNVHQ4EFgQUWBUjP5ao
+fqSDMpkaJKOBYdiGYR8wHwYDVR0jBBgwFoAUWBUjP5aofqSDMpkaJKOBYdiGYR8w
+DwYDVR0TAQH/BAUwAwEB/zANBgkqhkiG9w0BAQUFAAOBgQC9Xbo49T3MqP8CyIBR
+618nsyLqnv5If7ySD/I0VCiCRGqd/3hujGFLswb0V9GJp4mdPGcLdDi56LsASHyj
+00LnYp/d+wJRi4W6A4VeEQgyxa+3/l78cw9sSOBxA81XEnWOlAxi4T32F2DPJMIb
+/ufWwxwflhTKo7eNrClpKECndg==
+-----END CERTIFICATE-----
+";
+
+    // 2026-12-01T00:00:00Z
+    const NOW: f64 = 1_796_083_200_000.0;
+
+    fn parse(pems: &str) -> Value {
+        serde_json::from_str(&parse_cert_chain(pems, Some(NOW))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cert_chain_fields() {
+        let result = parse(&format!("{}{}", LEAF, ROOT));
+        let leaf = &result["certificates"][0];
+        assert_eq!(leaf["subject"], "CN=api.example.com");
+        assert_eq!(leaf["issuer"], "C=US, O=Volt Test, CN=Volt Test Root");
+        assert_eq!(leaf["serialNumber"], "12:34");
+        assert_eq!(leaf["notBefore"], "2026-10-16T00:28:29Z");
+        assert_eq!(leaf["notAfter"], "2027-01-14T00:28:29Z");
+        assert_eq!(leaf["daysUntilExpiry"], 44.0);
+        assert_eq!(
+            leaf["sans"],
+            serde_json::json!(["DNS:api.example.com", "DNS:*.example.com", "IP:192.0.2.1"])
+        );
+        assert_eq!(leaf["keyAlgorithm"], "EC");
+        assert_eq!(leaf["keyBits"], 256);
+        assert_eq!(leaf["curve"], "P-256");
+        assert_eq!(leaf["signatureAlgorithm"], "ecdsa-with-SHA256");
+        assert_eq!(leaf["isCa"], false);
+        assert_eq!(
+            leaf["fingerprintSha256"],
+            "5D:30:68:E2:1E:D7:3B:89:52:42:9E:9C:3B:0F:04:DC:BB:D0:99:FF:BB:44:01:DF:E9:43:4B:A3:57:86:35:2E"
+        );
+        let root = &result["certificates"][1];
+        assert_eq!(root["isCa"], true);
+        assert_eq!(root["selfSigned"], true);
+        assert_eq!(
+            result["chain"],
+            serde_json::json!({"ordered": true, "complete": true, "issues": []})
+        );
+        assert_eq!(result["warnings"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_parse_cert_chain_order_and_warnings() {
+        let result = parse(&format!("{}{}", ROOT, LEAF));
+        assert_eq!(result["chain"]["ordered"], false);
+        assert_eq!(result["chain"]["complete"], false);
+        assert!(
+            result["chain"]["issues"][0]
+                .as_str()
+                .unwrap()
+                .contains("not issued by certificate 1")
+        );
+
+        let result = parse(&format!(
+            "{}-----BEGIN CERTIFICATE-----\n!!\n-----END CERTIFICATE-----\n",
+            WEAK
+        ));
+        let weak = &result["certificates"][0];
+        assert_eq!(weak["keyAlgorithm"], "RSA");
+        assert_eq!(weak["keyBits"], 1024);
+        assert_eq!(weak["expired"], true);
+        let codes: Vec<&str> = result["warnings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| w["code"].as_str().unwrap())
+            .collect();
+        assert_eq!(codes, vec!["expired", "weakKey", "weakSignature"]);
+        assert_eq!(result["errors"][0]["index"], 1);
+    }
+}
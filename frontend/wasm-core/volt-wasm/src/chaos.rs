@@ -0,0 +1,272 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::model::Headers;
+use crate::regex_tester::cached_regex;
+use crate::rng::Rng;
+
+#[derive(Deserialize)]
+struct ChaosConfig {
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    rules: Vec<ChaosRule>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ChaosRule {
+    name: String,
+    #[serde(rename = "match")]
+    matcher: RuleMatch,
+    latency: Option<LatencyFault>,
+    error: Option<ErrorFault>,
+    truncate: Option<TruncateFault>,
+    drop: Option<DropFault>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RuleMatch {
+    methods: Vec<String>,
+    #[serde(rename = "urlContains")]
+    url_contains: Option<String>,
+    #[serde(rename = "urlPattern")]
+    url_pattern: Option<String>,
+    headers: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct LatencyFault {
+    #[serde(default = "default_one")]
+    probability: f64,
+    #[serde(rename = "minMs", default)]
+    min_ms: i64,
+    #[serde(rename = "maxMs", default)]
+    max_ms: i64,
+}
+
+#[derive(Deserialize)]
+struct ErrorFault {
+    #[serde(default = "default_one")]
+    probability: f64,
+    #[serde(rename = "statusCodes", default = "default_error_statuses")]
+    status_codes: Vec<i32>,
+    #[serde(default)]
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct TruncateFault {
+    #[serde(default = "default_one")]
+    probability: f64,
+    /// Fraction of the body to keep, 0.0..1.0.
+    #[serde(default = "default_half")]
+    ratio: f64,
+}
+
+#[derive(Deserialize)]
+struct DropFault {
+    #[serde(default = "default_one")]
+    probability: f64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ChaosRequest {
+    method: String,
+    url: String,
+    headers: Headers,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_one() -> f64 {
+    1.0
+}
+
+fn default_half() -> f64 {
+    0.5
+}
+
+fn default_error_statuses() -> Vec<i32> {
+    vec![500]
+}
+
+/// Decide which faults to inject for a single request.
+/// config_json: {enabled, rules: [{name, match: {methods, urlContains, urlPattern, headers},
+///   latency: {probability, minMs, maxMs}, error: {probability, statusCodes, body},
+///   truncate: {probability, ratio}, drop: {probability}}]}
+/// request_json: {method, url, headers}
+/// The first matching rule applies. The same seed always yields the same decision.
+/// Returns JSON {matched, rule, delayMs, errorStatus, errorBody, truncateRatio, drop}.
+#[wasm_bindgen]
+pub fn evaluate_chaos(config_json: &str, request_json: &str, rng_seed: u32) -> String {
+    let no_fault = |error: Option<String>| {
+        let mut decision = serde_json::json!({
+            "matched": false,
+            "rule": null,
+            "delayMs": 0,
+            "errorStatus": null,
+            "errorBody": null,
+            "truncateRatio": null,
+            "drop": false,
+        });
+        if let Some(e) = error {
+            decision["error"] = serde_json::Value::String(e);
+        }
+        decision.to_string()
+    };
+
+    let config: ChaosConfig = match serde_json::from_str(config_json) {
+        Ok(c) => c,
+        Err(e) => return no_fault(Some(format!("Invalid chaos config: {}", e))),
+    };
+    let request: ChaosRequest = serde_json::from_str(request_json).unwrap_or_default();
+
+    if !config.enabled {
+        return no_fault(None);
+    }
+
+    let rule = match config
+        .rules
+        .iter()
+        .find(|r| rule_matches(&r.matcher, &request))
+    {
+        Some(r) => r,
+        None => return no_fault(None),
+    };
+
+    // Every fault rolls in a fixed order whether or not it fires, so adding a
+    // fault to a rule never changes the outcome of the ones before it.
+    let mut rng = Rng::new(rng_seed as u64);
+
+    let drop = rule
+        .drop
+        .as_ref()
+        .is_some_and(|f| rng.chance(f.probability));
+
+    let error = rule.error.as_ref().and_then(|f| {
+        let fire = rng.chance(f.probability);
+        let idx = rng.range(0, f.status_codes.len() as i64 - 1) as usize;
+        match (fire, f.status_codes.get(idx)) {
+            (true, Some(status)) => Some((*status, f.body.clone())),
+            _ => None,
+        }
+    });
+
+    let truncate = rule.truncate.as_ref().and_then(|f| {
+        if rng.chance(f.probability) {
+            Some(f.ratio.clamp(0.0, 1.0))
+        } else {
+            None
+        }
+    });
+
+    let delay_ms = rule.latency.as_ref().map_or(0, |f| {
+        let fire = rng.chance(f.probability);
+        let delay = rng.range(f.min_ms.max(0), f.max_ms.max(f.min_ms).max(0));
+        if fire { delay } else { 0 }
+    });
+
+    // A dropped connection never produces a response to corrupt.
+    let (error, truncate) = if drop {
+        (None, None)
+    } else {
+        (error, truncate)
+    };
+
+    serde_json::json!({
+        "matched": true,
+        "rule": rule.name,
+        "delayMs": delay_ms,
+        "errorStatus": error.as_ref().map(|e| e.0),
+        "errorBody": error.map(|e| e.1),
+        "truncateRatio": truncate,
+        "drop": drop,
+    })
+    .to_string()
+}
+
+fn rule_matches(matcher: &RuleMatch, request: &ChaosRequest) -> bool {
+    if !matcher.methods.is_empty()
+        && !matcher
+            .methods
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(&request.method))
+    {
+        return false;
+    }
+    if let Some(needle) = &matcher.url_contains
+        && !request.url.contains(needle.as_str())
+    {
+        return false;
+    }
+    if let Some(pattern) = &matcher.url_pattern {
+        match cached_regex(pattern) {
+            Ok(re) if re.is_match(&request.url) => {}
+            _ => return false,
+        }
+    }
+    matcher
+        .headers
+        .iter()
+        .all(|(name, expected)| request.headers.get_all(name).any(|v| v == expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decide(config: &str, request: &str, seed: u32) -> serde_json::Value {
+        serde_json::from_str(&evaluate_chaos(config, request, seed)).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_chaos_is_deterministic() {
+        let config = r#"{"rules":[{"name":"flaky","latency":{"probability":0.5,"minMs":10,"maxMs":500},"error":{"probability":0.3,"statusCodes":[500,503]}}]}"#;
+        let request = r#"{"method":"GET","url":"https://api.example.com/users"}"#;
+        for seed in 0..20 {
+            assert_eq!(decide(config, request, seed), decide(config, request, seed));
+        }
+        let delays: Vec<i64> = (0..50)
+            .map(|s| decide(config, request, s)["delayMs"].as_i64().unwrap())
+            .collect();
+        assert!(delays.contains(&0));
+        assert!(delays.iter().any(|d| (10..=500).contains(d)));
+    }
+
+    #[test]
+    fn test_evaluate_chaos_first_matching_rule() {
+        let config = r#"{"rules":[
+            {"name":"posts","match":{"methods":["POST"]},"error":{"statusCodes":[429]}},
+            {"name":"users","match":{"urlPattern":"/users/\\d+$"},"drop":{}}
+        ]}"#;
+        let post = decide(config, r#"{"method":"post","url":"/users/1"}"#, 1);
+        assert_eq!(post["rule"], "posts");
+        assert_eq!(post["errorStatus"], 429);
+
+        let get = decide(config, r#"{"method":"GET","url":"/users/1"}"#, 1);
+        assert_eq!(get["rule"], "users");
+        assert_eq!(get["drop"], true);
+
+        let other = decide(config, r#"{"method":"GET","url":"/orders"}"#, 1);
+        assert_eq!(other["matched"], false);
+    }
+
+    #[test]
+    fn test_evaluate_chaos_disabled_and_invalid() {
+        let disabled = decide(r#"{"enabled":false,"rules":[{"drop":{}}]}"#, "{}", 0);
+        assert_eq!(disabled["matched"], false);
+        let invalid = decide("nope", "{}", 0);
+        assert!(
+            invalid["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid chaos config")
+        );
+    }
+}
@@ -0,0 +1,944 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::docs::request_title;
+use crate::model::{Collection, Request};
+use crate::variables::placeholder_regex;
+use crate::xml::xml_escape;
+use crate::{Assertion, QueryLanguage, base64_encode, has_variables, percent_encode};
+
+/// Generate a load-testing script from a collection or a single request.
+/// target: "k6" (JavaScript), "jmeter" (JMX test plan) or "gatling" (Scala simulation).
+/// Requests run once each, in collection order, by a single virtual user; scale
+/// them up in the generated file. {{variables}} map to each tool's own parameters:
+/// k6 reads `__ENV` (`k6 run -e name=value`), JMeter reads properties through
+/// User Defined Variables (`-Jname=value`), Gatling reads JVM system properties
+/// (`-Dname=value`) into the session.
+/// Returns the script text, or an empty string for invalid input or an unknown target.
+#[wasm_bindgen]
+pub fn export_load_script(collection_or_request_json: &str, target: &str) -> String {
+    let Some((name, requests)) = load(collection_or_request_json) else {
+        return String::new();
+    };
+    let prepared: Vec<Prepared> = requests.iter().map(|(path, r)| prepare(path, r)).collect();
+    match target {
+        "k6" => k6_script(&prepared),
+        "jmeter" => jmeter_plan(&name, &prepared),
+        "gatling" => gatling_simulation(&name, &prepared),
+        other => {
+            crate::logging::warn("export_load_script", || {
+                format!("unknown target \"{}\"", other)
+            });
+            String::new()
+        }
+    }
+}
+
+/// Generate an API test file from a collection or a single request.
+/// framework: "playwright" (`@playwright/test` `request` fixture) or "cypress" (`cy.request`).
+/// Each request becomes a test, with its enabled assertions translated into
+/// `expect()` calls; {{variables}} read `process.env` (Playwright) or `Cypress.env()`.
+/// Assertions with no equivalent are kept as comments.
+/// Returns the test file, or an empty string for invalid input or an unknown framework.
+#[wasm_bindgen]
+pub fn export_test_code(collection_or_request_json: &str, framework: &str) -> String {
+    let framework = match framework {
+        "playwright" => Framework::Playwright,
+        "cypress" => Framework::Cypress,
+        other => {
+            crate::logging::warn("export_test_code", || {
+                format!("unknown framework \"{}\"", other)
+            });
+            return String::new();
+        }
+    };
+    let Some((name, requests)) = load(collection_or_request_json) else {
+        return String::new();
+    };
+    let prepared: Vec<Prepared> = requests.iter().map(|(path, r)| prepare(path, r)).collect();
+    test_file(framework, &name, &prepared)
+}
+
+/// A collection name and its requests, each with its folder path.
+type Loaded = (String, Vec<(Vec<String>, Request)>);
+
+/// A bare request becomes a one-request collection named after it.
+fn load(json: &str) -> Option<Loaded> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    if value.get("requests").is_some() || value.get("folders").is_some() {
+        let collection: Collection = serde_json::from_value(value).ok()?;
+        let requests = collection
+            .all_requests()
+            .into_iter()
+            .map(|(path, r)| (path.iter().map(|s| s.to_string()).collect(), r.clone()))
+            .collect();
+        return Some((collection.name, requests));
+    }
+    let request: Request = serde_json::from_value(value).ok()?;
+    Some((request_title(&request), vec![(Vec::new(), request)]))
+}
+
+/// A request flattened into what every target needs; texts keep their {{variables}}.
+struct Prepared {
+    title: String,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Body,
+    /// Basic credentials, left to each tool when they contain variables.
+    basic: Option<(String, String)>,
+    assertions: Vec<Assertion>,
+}
+
+enum Body {
+    None,
+    Raw(String),
+    Form(Vec<(String, String)>),
+}
+
+fn prepare(path: &[String], request: &Request) -> Prepared {
+    let mut title = path.join(" / ");
+    if !title.is_empty() {
+        title.push_str(" / ");
+    }
+    title.push_str(&request_title(request));
+
+    let mut url = request.url.trim().to_string();
+    let mut params: Vec<(String, String)> = request
+        .query_params
+        .iter()
+        .filter(|p| p.enabled && !p.key.trim().is_empty())
+        .map(|p| (p.key.clone(), p.value.clone()))
+        .collect();
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .filter(|h| h.enabled && !h.key.trim().is_empty())
+        .map(|h| (h.key.clone(), h.value.clone()))
+        .collect();
+
+    let mut basic = None;
+    if let Some(auth) = &request.auth {
+        let get = |v: &Option<String>| v.clone().unwrap_or_default();
+        match auth.auth_type.as_str() {
+            "bearer" => headers.push((
+                "Authorization".into(),
+                format!("Bearer {}", get(&auth.token)),
+            )),
+            "basic" => {
+                let (user, pass) = (get(&auth.username), get(&auth.password));
+                if has_variables(&user) || has_variables(&pass) {
+                    basic = Some((user, pass));
+                } else {
+                    let encoded = base64_encode(format!("{}:{}", user, pass).as_bytes());
+                    headers.push(("Authorization".into(), format!("Basic {}", encoded)));
+                }
+            }
+            "apikey" => {
+                let pair = (get(&auth.api_key_name), get(&auth.api_key_value));
+                if auth.api_key_location.as_deref() == Some("query") {
+                    params.push(pair);
+                } else {
+                    headers.push(pair);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Parameters already written into the URL are not repeated.
+    let (_, _, existing) = crate::split_url(&url);
+    let existing = existing.to_string();
+    for (key, value) in params {
+        let pair = format!(
+            "{}={}",
+            encode_keeping_variables(&key),
+            encode_keeping_variables(&value)
+        );
+        if existing.split('&').any(|p| p == pair) {
+            continue;
+        }
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&pair);
+    }
+
+    let form: Vec<(String, String)> = request
+        .form_data
+        .iter()
+        .filter(|f| f.enabled && !f.key.trim().is_empty())
+        .map(|f| (f.key.clone(), f.value.clone()))
+        .collect();
+    let body = if request.body_type == "form-data" && !form.is_empty() {
+        Body::Form(form)
+    } else if request.body_type == "none" || request.body.is_empty() {
+        Body::None
+    } else {
+        if request.body_type == "json"
+            && !headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        {
+            headers.push(("Content-Type".into(), "application/json".into()));
+        }
+        Body::Raw(request.body.clone())
+    };
+
+    Prepared {
+        title,
+        method: if request.method.trim().is_empty() {
+            "GET".to_string()
+        } else {
+            request.method.trim().to_uppercase()
+        },
+        url,
+        headers,
+        body,
+        basic,
+        assertions: request
+            .assertions
+            .iter()
+            .filter(|a| a.enabled)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Percent-encode a query component while leaving {{variables}} intact.
+fn encode_keeping_variables(text: &str) -> String {
+    parts(text)
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(t) => percent_encode(t),
+            Part::Var(name) => format!("{{{{{}}}}}", name),
+        })
+        .collect()
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+/// Split a text into literal runs and {{variable}} references.
+fn parts(text: &str) -> Vec<Part<'_>> {
+    let mut out = Vec::new();
+    let mut last = 0;
+    for caps in placeholder_regex().captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        if whole.start() > last {
+            out.push(Part::Text(&text[last..whole.start()]));
+        }
+        out.push(Part::Var(caps.get(1).unwrap().as_str().trim()));
+        last = whole.end();
+    }
+    if last < text.len() {
+        out.push(Part::Text(&text[last..]));
+    }
+    out
+}
+
+/// Every variable referenced by the requests, in first-use order.
+fn variables(requests: &[Prepared]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for r in requests {
+        let mut texts = vec![r.url.as_str()];
+        for (k, v) in &r.headers {
+            texts.extend([k.as_str(), v.as_str()]);
+        }
+        match &r.body {
+            Body::Raw(body) => texts.push(body),
+            Body::Form(fields) => {
+                for (k, v) in fields {
+                    texts.extend([k.as_str(), v.as_str()]);
+                }
+            }
+            Body::None => {}
+        }
+        if let Some((user, pass)) = &r.basic {
+            texts.extend([user.as_str(), pass.as_str()]);
+        }
+        for text in texts {
+            for part in parts(text) {
+                if let Part::Var(name) = part
+                    && !names.iter().any(|n| n == name)
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+fn js_string(text: &str) -> String {
+    Value::from(text).to_string()
+}
+
+/// A JS string, as a template literal reading `vars` when it has variables.
+fn js_template(text: &str) -> String {
+    if !has_variables(text) {
+        return js_string(text);
+    }
+    let mut out = String::from("`");
+    for part in parts(text) {
+        match part {
+            Part::Text(t) => out.push_str(
+                &t.replace('\\', "\\\\")
+                    .replace('`', "\\`")
+                    .replace("${", "\\${"),
+            ),
+            Part::Var(name) => out.push_str(&format!("${{vars[{}]}}", js_string(name))),
+        }
+    }
+    out.push('`');
+    out
+}
+
+/// `const vars = {...};` with each variable read through `source`.
+fn js_vars(requests: &[Prepared], source: fn(&str) -> String) -> String {
+    let mut out = String::from("const vars = {\n");
+    for name in variables(requests) {
+        out.push_str(&format!("  {}: {},\n", js_string(&name), source(&name)));
+    }
+    out.push_str("};\n");
+    out
+}
+
+/// Object entries for a request's headers, one per line at `indent`; basic
+/// credentials with variables are encoded at run time by `base64`.
+fn js_headers(r: &Prepared, indent: &str, base64: fn(&str) -> String) -> String {
+    let mut out: String = r
+        .headers
+        .iter()
+        .map(|(k, v)| format!("{}{}: {},\n", indent, js_template(k), js_template(v)))
+        .collect();
+    if let Some((user, pass)) = &r.basic {
+        let credentials = js_template(&format!("{}:{}", user, pass));
+        out.push_str(&format!(
+            "{}\"Authorization\": `Basic ${{{}}}`,\n",
+            indent,
+            base64(&credentials)
+        ));
+    }
+    out
+}
+
+fn js_object(fields: &[(String, String)]) -> String {
+    let entries: Vec<String> = fields
+        .iter()
+        .map(|(k, v)| format!("{}: {}", js_template(k), js_template(v)))
+        .collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+fn k6_script(requests: &[Prepared]) -> String {
+    let needs_encoding = requests.iter().any(|r| r.basic.is_some());
+    let mut out =
+        String::from("import http from \"k6/http\";\nimport { check, group } from \"k6\";\n");
+    if needs_encoding {
+        out.push_str("import encoding from \"k6/encoding\";\n");
+    }
+    out.push_str("\nexport const options = {\n  vus: 1,\n  iterations: 1,\n};\n\n");
+    out.push_str("// Set with: k6 run -e name=value script.js\n");
+    out.push_str(&js_vars(requests, |name| {
+        format!("__ENV[{}] || \"\"", js_string(name))
+    }));
+    out.push_str("\nexport default function () {\n");
+
+    for r in requests {
+        out.push_str(&format!("  group({}, () => {{\n", js_string(&r.title)));
+        let body = match &r.body {
+            Body::None => "null".to_string(),
+            Body::Raw(body) => js_template(body),
+            Body::Form(fields) => js_object(fields),
+        };
+        out.push_str(&format!(
+            "    const res = http.request({}, {}, {}, {{\n      headers: {{\n{}      }},\n    }});\n",
+            js_string(&r.method),
+            js_template(&r.url),
+            body,
+            js_headers(r, "        ", |c| format!("encoding.b64encode({})", c))
+        ));
+        out.push_str("    check(res, { \"status is not 5xx\": (r) => r.status < 500 });\n  });\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Framework {
+    Playwright,
+    Cypress,
+}
+
+fn test_file(framework: Framework, name: &str, requests: &[Prepared]) -> String {
+    let suite = if name.trim().is_empty() {
+        "API tests"
+    } else {
+        name.trim()
+    };
+    let mut out = String::new();
+    if framework == Framework::Playwright {
+        out.push_str("import { test, expect } from \"@playwright/test\";\n\n");
+        out.push_str(&js_vars(requests, |name| {
+            format!("process.env[{}] ?? \"\"", js_string(name))
+        }));
+    } else {
+        out.push_str("/// <reference types=\"cypress\" />\n\n");
+        out.push_str(&js_vars(requests, |name| {
+            format!("Cypress.env({}) ?? \"\"", js_string(name))
+        }));
+    }
+    let describe = match framework {
+        Framework::Playwright => "test.describe",
+        Framework::Cypress => "describe",
+    };
+    out.push_str(&format!("\n{}({}, () => {{\n", describe, js_string(suite)));
+
+    for (i, r) in requests.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        match framework {
+            Framework::Playwright => playwright_test(&mut out, r),
+            Framework::Cypress => cypress_test(&mut out, r),
+        }
+    }
+    out.push_str("});\n");
+    out
+}
+
+fn playwright_test(out: &mut String, r: &Prepared) {
+    let uses = |t: &str| r.assertions.iter().any(|a| a.assertion_type == t);
+    out.push_str(&format!(
+        "  test({}, async ({{ request }}) => {{\n",
+        js_string(&r.title)
+    ));
+    if uses("responseTime") {
+        out.push_str("    const started = Date.now();\n");
+    }
+    out.push_str(&format!(
+        "    const response = await request.fetch({}, {{\n      method: {},\n",
+        js_template(&r.url),
+        js_string(&r.method)
+    ));
+    let headers = js_headers(r, "        ", |c| {
+        format!("Buffer.from({}).toString(\"base64\")", c)
+    });
+    if !headers.is_empty() {
+        out.push_str(&format!("      headers: {{\n{}      }},\n", headers));
+    }
+    match &r.body {
+        Body::None => {}
+        Body::Raw(body) => out.push_str(&format!("      data: {},\n", js_template(body))),
+        Body::Form(fields) => out.push_str(&format!("      multipart: {},\n", js_object(fields))),
+    }
+    out.push_str("    });\n");
+    if uses("responseTime") {
+        out.push_str("    const elapsed = Date.now() - started;\n");
+    }
+    if uses("bodyContains") || uses("bodyJson") {
+        out.push_str("    const text = await response.text();\n");
+    }
+    if uses("bodyJson") {
+        out.push_str("    const json = JSON.parse(text);\n");
+    }
+    if r.assertions.is_empty() {
+        out.push_str("    expect(response.status()).toBeLessThan(500);\n");
+    }
+    for a in &r.assertions {
+        let actual = match a.assertion_type.as_str() {
+            "status" => "response.status()".to_string(),
+            "responseTime" => "elapsed".to_string(),
+            "bodyContains" => "text".to_string(),
+            // JMESPath has no JavaScript equivalent; left as a comment.
+            "bodyJson" if a.query_language == QueryLanguage::JmesPath => String::new(),
+            "bodyJson" => json_path_expr("json", &a.property),
+            "headerExists" | "headerEquals" => format!(
+                "response.headers()[{}]",
+                js_string(&a.property.to_lowercase())
+            ),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "    {}\n",
+            expectation(Framework::Playwright, a, &actual)
+        ));
+    }
+    out.push_str("  });\n");
+}
+
+fn cypress_test(out: &mut String, r: &Prepared) {
+    out.push_str(&format!(
+        "  it({}, () => {{\n    cy.request({{\n      method: {},\n      url: {},\n",
+        js_string(&r.title),
+        js_string(&r.method),
+        js_template(&r.url)
+    ));
+    let headers = js_headers(r, "        ", |c| format!("btoa({})", c));
+    if !headers.is_empty() {
+        out.push_str(&format!("      headers: {{\n{}      }},\n", headers));
+    }
+    match &r.body {
+        Body::None => {}
+        Body::Raw(body) => out.push_str(&format!("      body: {},\n", js_template(body))),
+        Body::Form(fields) => out.push_str(&format!(
+            "      form: true,\n      body: {},\n",
+            js_object(fields)
+        )),
+    }
+    out.push_str("      failOnStatusCode: false,\n    }).then((response) => {\n");
+    if r.assertions
+        .iter()
+        .any(|a| a.assertion_type == "bodyContains")
+    {
+        out.push_str(
+            "      const text = typeof response.body === \"string\" ? response.body : JSON.stringify(response.body);\n",
+        );
+    }
+    if r.assertions.is_empty() {
+        out.push_str("      expect(response.status).to.be.lessThan(500);\n");
+    }
+    for a in &r.assertions {
+        let actual = match a.assertion_type.as_str() {
+            "status" => "response.status".to_string(),
+            "responseTime" => "response.duration".to_string(),
+            "bodyContains" => "text".to_string(),
+            // JMESPath has no JavaScript equivalent; left as a comment.
+            "bodyJson" if a.query_language == QueryLanguage::JmesPath => String::new(),
+            "bodyJson" => json_path_expr("response.body", &a.property),
+            "headerExists" | "headerEquals" => format!(
+                "response.headers[{}]",
+                js_string(&a.property.to_lowercase())
+            ),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "      {}\n",
+            expectation(Framework::Cypress, a, &actual)
+        ));
+    }
+    out.push_str("    });\n  });\n");
+}
+
+/// Optional-chained access for an assertion path such as "data.items[0].id".
+fn json_path_expr(root: &str, path: &str) -> String {
+    let mut out = root.to_string();
+    let path = path.trim().trim_start_matches('$').trim_start_matches('.');
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        let mut pieces = part.split('[');
+        if let Some(key) = pieces.next().filter(|k| !k.is_empty()) {
+            out.push_str(&format!("?.[{}]", js_string(key)));
+        }
+        for index in pieces {
+            out.push_str(&format!("?.[{}]", index.trim_end_matches(']')));
+        }
+    }
+    out
+}
+
+/// One `expect()` statement for an assertion, or a comment when it has no equivalent.
+fn expectation(framework: Framework, a: &Assertion, actual: &str) -> String {
+    // A multi-line expected value (a bodySchema schema) must stay on the comment line.
+    let unsupported = || {
+        let expected: Vec<&str> = a.expected.split_whitespace().collect();
+        format!(
+            "// Not translated: {} {} {}",
+            a.assertion_type,
+            a.operator,
+            expected.join(" ")
+        )
+        .trim_end()
+        .to_string()
+    };
+    if actual.is_empty() {
+        return unsupported();
+    }
+    let numeric = matches!(a.assertion_type.as_str(), "status" | "responseTime")
+        || matches!(a.operator.as_str(), "lessThan" | "greaterThan");
+    let expected = match a.expected.trim().parse::<f64>() {
+        Ok(_) if numeric => a.expected.trim().to_string(),
+        _ if a.assertion_type == "bodyJson"
+            && matches!(a.operator.as_str(), "equals" | "notEquals") =>
+        {
+            serde_json::from_str::<Value>(&a.expected)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| js_template(&a.expected))
+        }
+        _ => js_template(&a.expected),
+    };
+    // bodyJson "contains" matches against the value's JSON text, as the runner does.
+    let actual = if a.assertion_type == "bodyJson" && a.operator == "contains" {
+        format!("JSON.stringify({})", actual)
+    } else {
+        actual.to_string()
+    };
+    let matcher = match (framework, a.operator.as_str()) {
+        (Framework::Playwright, "equals") => format!("toEqual({})", expected),
+        (Framework::Playwright, "notEquals") => format!("not.toEqual({})", expected),
+        (Framework::Playwright, "lessThan") => format!("toBeLessThan({})", expected),
+        (Framework::Playwright, "greaterThan") => format!("toBeGreaterThan({})", expected),
+        (Framework::Playwright, "contains") => format!("toContain({})", expected),
+        (Framework::Playwright, "notContains") => format!("not.toContain({})", expected),
+        (Framework::Playwright, "matches") => format!("toMatch(new RegExp({}))", expected),
+        (Framework::Playwright, "exists") => "toBeDefined()".to_string(),
+        (Framework::Playwright, "notExists") => "toBeUndefined()".to_string(),
+        (Framework::Cypress, "equals") => format!("to.deep.equal({})", expected),
+        (Framework::Cypress, "notEquals") => format!("to.not.deep.equal({})", expected),
+        (Framework::Cypress, "lessThan") => format!("to.be.lessThan({})", expected),
+        (Framework::Cypress, "greaterThan") => format!("to.be.greaterThan({})", expected),
+        (Framework::Cypress, "contains") => format!("to.include({})", expected),
+        (Framework::Cypress, "notContains") => format!("to.not.include({})", expected),
+        (Framework::Cypress, "matches") => format!("to.match(new RegExp({}))", expected),
+        (Framework::Cypress, "exists") => "to.not.be.undefined".to_string(),
+        (Framework::Cypress, "notExists") => "to.be.undefined".to_string(),
+        _ => return unsupported(),
+    };
+    format!("expect({}).{};", actual, matcher)
+}
+
+/// XML text with {{name}} rewritten to JMeter's ${name}.
+fn jmeter_text(text: &str) -> String {
+    parts(text)
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(t) => xml_escape(t),
+            Part::Var(name) => format!("${{{}}}", xml_escape(name)),
+        })
+        .collect()
+}
+
+fn jmeter_plan(name: &str, requests: &[Prepared]) -> String {
+    let name = xml_escape(if name.trim().is_empty() {
+        "Volt Test Plan"
+    } else {
+        name.trim()
+    });
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<jmeterTestPlan version=\"1.2\" properties=\"5.0\" jmeter=\"5.6.3\">\n  <hashTree>\n",
+    );
+    out.push_str(&format!(
+        "    <TestPlan guiclass=\"TestPlanGui\" testclass=\"TestPlan\" testname=\"{}\">\n",
+        name
+    ));
+    out.push_str("      <elementProp name=\"TestPlan.user_defined_variables\" elementType=\"Arguments\" guiclass=\"ArgumentsPanel\" testclass=\"Arguments\" testname=\"User Defined Variables\">\n        <collectionProp name=\"Arguments.arguments\">\n");
+    for var in variables(requests) {
+        let var = xml_escape(&var);
+        out.push_str(&format!(
+            "          <elementProp name=\"{0}\" elementType=\"Argument\">\n            <stringProp name=\"Argument.name\">{0}</stringProp>\n            <stringProp name=\"Argument.value\">${{__P({0},)}}</stringProp>\n            <stringProp name=\"Argument.metadata\">=</stringProp>\n          </elementProp>\n",
+            var
+        ));
+    }
+    out.push_str(
+        "        </collectionProp>\n      </elementProp>\n    </TestPlan>\n    <hashTree>\n",
+    );
+    out.push_str("      <ThreadGroup guiclass=\"ThreadGroupGui\" testclass=\"ThreadGroup\" testname=\"Thread Group\">\n        <intProp name=\"ThreadGroup.num_threads\">1</intProp>\n        <intProp name=\"ThreadGroup.ramp_time\">1</intProp>\n        <stringProp name=\"ThreadGroup.on_sample_error\">continue</stringProp>\n        <elementProp name=\"ThreadGroup.main_controller\" elementType=\"LoopController\" guiclass=\"LoopControlPanel\" testclass=\"LoopController\">\n          <stringProp name=\"LoopController.loops\">1</stringProp>\n          <boolProp name=\"LoopController.continue_forever\">false</boolProp>\n        </elementProp>\n      </ThreadGroup>\n      <hashTree>\n");
+
+    for r in requests {
+        out.push_str(&format!(
+            "        <HTTPSamplerProxy guiclass=\"HttpTestSampleGui\" testclass=\"HTTPSamplerProxy\" testname=\"{}\">\n",
+            xml_escape(&r.title)
+        ));
+        out.push_str(&format!(
+            "          <stringProp name=\"HTTPSampler.path\">{}</stringProp>\n          <stringProp name=\"HTTPSampler.method\">{}</stringProp>\n          <boolProp name=\"HTTPSampler.follow_redirects\">true</boolProp>\n          <boolProp name=\"HTTPSampler.use_keepalive\">true</boolProp>\n",
+            jmeter_text(&r.url),
+            xml_escape(&r.method)
+        ));
+        let arguments: Vec<String> = match &r.body {
+            Body::None => Vec::new(),
+            Body::Raw(body) => {
+                out.push_str(
+                    "          <boolProp name=\"HTTPSampler.postBodyRaw\">true</boolProp>\n",
+                );
+                vec![format!(
+                    "              <elementProp name=\"\" elementType=\"HTTPArgument\">\n                <boolProp name=\"HTTPArgument.always_encode\">false</boolProp>\n                <stringProp name=\"Argument.value\">{}</stringProp>\n                <stringProp name=\"Argument.metadata\">=</stringProp>\n              </elementProp>\n",
+                    jmeter_text(body)
+                )]
+            }
+            Body::Form(fields) => {
+                out.push_str(
+                    "          <boolProp name=\"HTTPSampler.DO_MULTIPART_POST\">true</boolProp>\n",
+                );
+                fields
+                    .iter()
+                    .map(|(k, v)| {
+                        format!(
+                            "              <elementProp name=\"{0}\" elementType=\"HTTPArgument\">\n                <boolProp name=\"HTTPArgument.always_encode\">true</boolProp>\n                <stringProp name=\"Argument.name\">{0}</stringProp>\n                <stringProp name=\"Argument.value\">{1}</stringProp>\n                <stringProp name=\"Argument.metadata\">=</stringProp>\n              </elementProp>\n",
+                            jmeter_text(k),
+                            jmeter_text(v)
+                        )
+                    })
+                    .collect()
+            }
+        };
+        out.push_str(&format!(
+            "          <elementProp name=\"HTTPsampler.Arguments\" elementType=\"Arguments\">\n            <collectionProp name=\"Arguments.arguments\">\n{}            </collectionProp>\n          </elementProp>\n        </HTTPSamplerProxy>\n        <hashTree>\n",
+            arguments.concat()
+        ));
+
+        if !r.headers.is_empty() {
+            out.push_str("          <HeaderManager guiclass=\"HeaderPanel\" testclass=\"HeaderManager\" testname=\"HTTP Header Manager\">\n            <collectionProp name=\"HeaderManager.headers\">\n");
+            for (k, v) in &r.headers {
+                out.push_str(&format!(
+                    "              <elementProp name=\"\" elementType=\"Header\">\n                <stringProp name=\"Header.name\">{}</stringProp>\n                <stringProp name=\"Header.value\">{}</stringProp>\n              </elementProp>\n",
+                    jmeter_text(k),
+                    jmeter_text(v)
+                ));
+            }
+            out.push_str("            </collectionProp>\n          </HeaderManager>\n          <hashTree/>\n");
+        }
+        if let Some((user, pass)) = &r.basic {
+            out.push_str(&format!(
+                "          <AuthManager guiclass=\"AuthPanel\" testclass=\"AuthManager\" testname=\"HTTP Authorization Manager\">\n            <collectionProp name=\"AuthManager.auth_list\">\n              <elementProp name=\"\" elementType=\"Authorization\">\n                <stringProp name=\"Authorization.url\"></stringProp>\n                <stringProp name=\"Authorization.username\">{}</stringProp>\n                <stringProp name=\"Authorization.password\">{}</stringProp>\n                <stringProp name=\"Authorization.mechanism\">BASIC</stringProp>\n              </elementProp>\n            </collectionProp>\n          </AuthManager>\n          <hashTree/>\n",
+                jmeter_text(user),
+                jmeter_text(pass)
+            ));
+        }
+        out.push_str("        </hashTree>\n");
+    }
+    out.push_str("      </hashTree>\n    </hashTree>\n  </hashTree>\n</jmeterTestPlan>\n");
+    out
+}
+
+/// A Scala string literal with {{name}} rewritten to Gatling's #{name}.
+fn scala_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for part in parts(text) {
+        match part {
+            Part::Text(t) => {
+                for c in t.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        '\t' => out.push_str("\\t"),
+                        c => out.push(c),
+                    }
+                }
+            }
+            Part::Var(name) => out.push_str(&format!("#{{{}}}", name)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn gatling_simulation(name: &str, requests: &[Prepared]) -> String {
+    let class: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let class = if class.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        format!("{}Simulation", class)
+    } else {
+        format!("Volt{}Simulation", class)
+    };
+
+    let mut out =
+        String::from("import io.gatling.core.Predef._\nimport io.gatling.http.Predef._\n\n");
+    out.push_str(&format!("class {} extends Simulation {{\n\n", class));
+    out.push_str("  // Set with: -Dname=value\n  val vars: Map[String, Any] = Map(\n");
+    let vars: Vec<String> = variables(requests)
+        .iter()
+        .map(|v| {
+            format!(
+                "    {} -> System.getProperty({}, \"\")",
+                scala_string(v),
+                scala_string(v)
+            )
+        })
+        .collect();
+    out.push_str(&vars.join(",\n"));
+    if !vars.is_empty() {
+        out.push('\n');
+    }
+    out.push_str("  )\n\n  val httpProtocol = http\n\n");
+    out.push_str(&format!(
+        "  val scn = scenario({})\n    .exec(session => session.setAll(vars))\n",
+        scala_string(if name.trim().is_empty() {
+            "Volt"
+        } else {
+            name.trim()
+        })
+    ));
+
+    for r in requests {
+        let call = match r.method.as_str() {
+            "GET" | "POST" | "PUT" | "DELETE" | "PATCH" | "HEAD" | "OPTIONS" => {
+                format!(".{}({})", r.method.to_lowercase(), scala_string(&r.url))
+            }
+            other => format!(
+                ".httpRequest({}, {})",
+                scala_string(other),
+                scala_string(&r.url)
+            ),
+        };
+        out.push_str(&format!(
+            "    .exec(\n      http({})\n        {}\n",
+            scala_string(&r.title),
+            call
+        ));
+        for (k, v) in &r.headers {
+            out.push_str(&format!(
+                "        .header({}, {})\n",
+                scala_string(k),
+                scala_string(v)
+            ));
+        }
+        if let Some((user, pass)) = &r.basic {
+            out.push_str(&format!(
+                "        .basicAuth({}, {})\n",
+                scala_string(user),
+                scala_string(pass)
+            ));
+        }
+        match &r.body {
+            Body::None => {}
+            Body::Raw(body) => out.push_str(&format!(
+                "        .body(StringBody({}))\n",
+                scala_string(body)
+            )),
+            Body::Form(fields) => {
+                for (k, v) in fields {
+                    out.push_str(&format!(
+                        "        .formParam({}, {})\n",
+                        scala_string(k),
+                        scala_string(v)
+                    ));
+                }
+            }
+        }
+        out.push_str("    )\n");
+    }
+    out.push_str("\n  setUp(scn.inject(atOnceUsers(1))).protocols(httpProtocol)\n}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"{
+        "name": "Users API",
+        "requests": [{
+            "name": "Create user",
+            "method": "post",
+            "url": "{{baseUrl}}/users",
+            "queryParams": [{"key": "notify", "value": "{{notify}}", "enabled": true}],
+            "headers": {"X-Trace": "a&b"},
+            "body": "{\"name\": \"{{userName}}\"}",
+            "bodyType": "json",
+            "auth": {"type": "basic", "username": "{{user}}", "password": "s3cret"}
+        }],
+        "folders": [{
+            "name": "Admin",
+            "requests": [{
+                "name": "Health",
+                "method": "GET",
+                "url": "https://api.example.com/health",
+                "auth": {"type": "bearer", "token": "abc"}
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn test_export_load_script_k6() {
+        let script = export_load_script(COLLECTION, "k6");
+        assert!(script.contains("import encoding from \"k6/encoding\";"));
+        assert!(script.contains("  \"baseUrl\": __ENV[\"baseUrl\"] || \"\",\n"));
+        assert!(script.contains("  \"userName\": __ENV[\"userName\"] || \"\",\n"));
+        assert!(script.contains(
+            "http.request(\"POST\", `${vars[\"baseUrl\"]}/users?notify=${vars[\"notify\"]}`, `{\"name\": \"${vars[\"userName\"]}\"}`"
+        ));
+        assert!(script.contains("`Basic ${encoding.b64encode(`${vars[\"user\"]}:s3cret`)}`"));
+        assert!(script.contains("group(\"Admin / Health\""));
+        assert!(script.contains("\"Authorization\": \"Bearer abc\""));
+        assert!(script.contains("\"Content-Type\": \"application/json\""));
+        assert_eq!(export_load_script(COLLECTION, "locust"), "");
+        assert_eq!(export_load_script("nope", "k6"), "");
+    }
+
+    #[test]
+    fn test_export_load_script_jmeter_and_gatling() {
+        let plan = export_load_script(COLLECTION, "jmeter");
+        assert!(plan.starts_with("<?xml"));
+        assert!(plan.contains("testname=\"Users API\""));
+        assert!(plan.contains("<stringProp name=\"Argument.value\">${__P(baseUrl,)}</stringProp>"));
+        assert!(plan.contains(
+            "<stringProp name=\"HTTPSampler.path\">${baseUrl}/users?notify=${notify}</stringProp>"
+        ));
+        assert!(plan.contains("<stringProp name=\"Header.value\">a&amp;b</stringProp>"));
+        assert!(plan.contains("<stringProp name=\"Authorization.username\">${user}</stringProp>"));
+        assert_eq!(plan.matches("<HTTPSamplerProxy ").count(), 2);
+        assert_eq!(
+            plan.matches("<hashTree>").count(),
+            plan.matches("</hashTree>").count()
+        );
+
+        let sim = export_load_script(COLLECTION, "gatling");
+        assert!(sim.contains("class UsersAPISimulation extends Simulation {"));
+        assert!(sim.contains("    \"baseUrl\" -> System.getProperty(\"baseUrl\", \"\"),\n"));
+        assert!(sim.contains(".post(\"#{baseUrl}/users?notify=#{notify}\")"));
+        assert!(sim.contains(".basicAuth(\"#{user}\", \"s3cret\")"));
+        assert!(sim.contains(".body(StringBody(\"{\\\"name\\\": \\\"#{userName}\\\"}\"))"));
+        assert!(sim.contains(
+            "http(\"Admin / Health\")\n        .get(\"https://api.example.com/health\")"
+        ));
+
+        let single = export_load_script(r#"{"method": "DELETE", "url": "/a"}"#, "gatling");
+        assert!(single.contains("class DELETEASimulation"));
+    }
+
+    #[test]
+    fn test_export_test_code() {
+        let request = r#"{
+            "name": "Get user",
+            "method": "GET",
+            "url": "{{baseUrl}}/users/1",
+            "headers": {"Accept": "application/json"},
+            "assertions": [
+                {"id": "1", "type": "status", "property": "", "operator": "equals", "expected": "200", "enabled": true},
+                {"id": "2", "type": "bodyJson", "property": "data.tags[0]", "operator": "equals", "expected": "\"admin\"", "enabled": true},
+                {"id": "3", "type": "headerEquals", "property": "Content-Type", "operator": "contains", "expected": "json", "enabled": true},
+                {"id": "4", "type": "responseTime", "property": "", "operator": "lessThan", "expected": "500", "enabled": true},
+                {"id": "5", "type": "noDuplicateKeys", "property": "", "operator": "equals", "expected": "", "enabled": true},
+                {"id": "6", "type": "status", "property": "", "operator": "equals", "expected": "500", "enabled": false}
+            ]
+        }"#;
+        let pw = export_test_code(request, "playwright");
+        assert!(pw.starts_with("import { test, expect } from \"@playwright/test\";"));
+        assert!(pw.contains("  \"baseUrl\": process.env[\"baseUrl\"] ?? \"\",\n"));
+        assert!(pw.contains("test.describe(\"Get user\", () => {"));
+        assert!(pw.contains("await request.fetch(`${vars[\"baseUrl\"]}/users/1`, {"));
+        assert!(pw.contains("expect(response.status()).toEqual(200);"));
+        assert!(pw.contains("expect(json?.[\"data\"]?.[\"tags\"]?.[0]).toEqual(\"admin\");"));
+        assert!(pw.contains("expect(response.headers()[\"content-type\"]).toContain(\"json\");"));
+        assert!(pw.contains("const elapsed = Date.now() - started;"));
+        assert!(pw.contains("expect(elapsed).toBeLessThan(500);"));
+        assert!(pw.contains("// Not translated: noDuplicateKeys equals"));
+        assert!(!pw.contains("500);\n    expect(response.status()).toEqual(500)"));
+
+        let cy = export_test_code(request, "cypress");
+        assert!(cy.contains("  \"baseUrl\": Cypress.env(\"baseUrl\") ?? \"\",\n"));
+        assert!(cy.contains(
+            "describe(\"Get user\", () => {\n  it(\"Get user\", () => {\n    cy.request({"
+        ));
+        assert!(cy.contains("failOnStatusCode: false,"));
+        assert!(cy.contains("expect(response.status).to.deep.equal(200);"));
+        assert!(cy.contains("expect(response.duration).to.be.lessThan(500);"));
+        assert!(cy.contains(
+            "expect(response.body?.[\"data\"]?.[\"tags\"]?.[0]).to.deep.equal(\"admin\");"
+        ));
+        assert_eq!(cy.matches("expect(").count(), 4);
+        assert_eq!(export_test_code(request, "jest"), "");
+    }
+}
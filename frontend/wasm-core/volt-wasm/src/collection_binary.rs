@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::prelude::*;
+
+const MAGIC: &[u8; 4] = b"VOLT";
+/// Format written by `collection_serialize`.
+const CURRENT_VERSION: u8 = 2;
+/// Reads the payload that follows the header of one format version.
+type Decoder = fn(&[u8]) -> Result<Value, String>;
+/// Decoders for every format version still readable, oldest first.
+const DECODERS: &[(u8, Decoder)] = &[(1, decode_v1), (CURRENT_VERSION, decode_v2)];
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UINT: u8 = 3;
+const TAG_NEGINT: u8 = 4;
+/// Non-integer numbers keep their exact JSON text (via the string table).
+const TAG_NUMBER_TEXT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_OBJECT: u8 = 8;
+
+/// Serialize a collection (any JSON document) into the compact binary storage format.
+/// Layout: "VOLT", version byte, flags byte, then a deduplicated string table
+/// and a tagged value tree using LEB128 varints. Keys and repeated values such
+/// as methods and header names are stored once.
+/// Returns an empty array when collection_json is not valid JSON.
+#[wasm_bindgen]
+pub fn collection_serialize(collection_json: &str) -> Vec<u8> {
+    let Ok(value) = serde_json::from_str::<Value>(collection_json) else {
+        return Vec::new();
+    };
+    let _timer = crate::logging::timer("collection_serialize");
+    encode(&value)
+}
+
+/// Deserialize bytes written by `collection_serialize`, including older format
+/// versions, which are migrated on read.
+/// Returns the collection JSON, or JSON {error} for foreign or corrupted data.
+#[wasm_bindgen]
+pub fn collection_deserialize(bytes: &[u8]) -> String {
+    let _timer = crate::logging::timer("collection_deserialize");
+    match decode(bytes) {
+        Ok(value) => serde_json::to_string(&value).unwrap_or_else(|_| "{}".to_string()),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut strings = StringTable::default();
+    let mut body = Vec::new();
+    encode_value(value, &mut strings, &mut body);
+
+    let mut out = Vec::with_capacity(body.len() + strings.bytes + 16);
+    out.extend_from_slice(MAGIC);
+    out.push(CURRENT_VERSION);
+    out.push(0);
+    write_varint(&mut out, strings.list.len() as u64);
+    for s in &strings.list {
+        write_varint(&mut out, s.len() as u64);
+        out.extend_from_slice(s.as_bytes());
+    }
+    out.extend_from_slice(&body);
+    out
+}
+
+fn decode(bytes: &[u8]) -> Result<Value, String> {
+    if bytes.len() < 6 || &bytes[..4] != MAGIC {
+        return Err("Not a Volt collection file".to_string());
+    }
+    let version = bytes[4];
+    let (_, decoder) = DECODERS
+        .iter()
+        .find(|(v, _)| *v == version)
+        .ok_or_else(|| format!("Unsupported format version {}", version))?;
+    decoder(&bytes[6..])
+}
+
+/// Version 1 stored the collection as UTF-8 JSON after the header.
+fn decode_v1(payload: &[u8]) -> Result<Value, String> {
+    let text = std::str::from_utf8(payload).map_err(|_| "Corrupted data".to_string())?;
+    serde_json::from_str(text).map_err(|e| format!("Corrupted data: {}", e))
+}
+
+fn decode_v2(payload: &[u8]) -> Result<Value, String> {
+    let mut r = Reader {
+        bytes: payload,
+        pos: 0,
+    };
+    let count = r.len()?;
+    let mut strings = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = r.len()?;
+        let raw = r.take(len)?;
+        strings.push(
+            std::str::from_utf8(raw)
+                .map_err(|_| "Corrupted data".to_string())?
+                .to_string(),
+        );
+    }
+    let value = decode_value(&mut r, &strings, 0)?;
+    if r.pos != payload.len() {
+        return Err("Corrupted data: trailing bytes".to_string());
+    }
+    Ok(value)
+}
+
+#[derive(Default)]
+struct StringTable {
+    list: Vec<String>,
+    index: HashMap<String, u64>,
+    bytes: usize,
+}
+
+impl StringTable {
+    fn intern(&mut self, s: &str) -> u64 {
+        if let Some(&i) = self.index.get(s) {
+            return i;
+        }
+        let i = self.list.len() as u64;
+        self.bytes += s.len() + 2;
+        self.list.push(s.to_string());
+        self.index.insert(s.to_string(), i);
+        i
+    }
+}
+
+fn encode_value(value: &Value, strings: &mut StringTable, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(TAG_NULL),
+        Value::Bool(false) => out.push(TAG_FALSE),
+        Value::Bool(true) => out.push(TAG_TRUE),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                out.push(TAG_UINT);
+                write_varint(out, u);
+            } else if let Some(i) = n.as_i64() {
+                out.push(TAG_NEGINT);
+                write_varint(out, !(i as u64));
+            } else {
+                out.push(TAG_NUMBER_TEXT);
+                write_varint(out, strings.intern(&n.to_string()));
+            }
+        }
+        Value::String(s) => {
+            out.push(TAG_STRING);
+            write_varint(out, strings.intern(s));
+        }
+        Value::Array(items) => {
+            out.push(TAG_ARRAY);
+            write_varint(out, items.len() as u64);
+            for item in items {
+                encode_value(item, strings, out);
+            }
+        }
+        Value::Object(map) => {
+            out.push(TAG_OBJECT);
+            write_varint(out, map.len() as u64);
+            for (k, v) in map {
+                write_varint(out, strings.intern(k));
+                encode_value(v, strings, out);
+            }
+        }
+    }
+}
+
+/// Nesting deeper than this is treated as corruption rather than recursed into.
+const MAX_DEPTH: usize = 256;
+
+fn decode_value(r: &mut Reader, strings: &[String], depth: usize) -> Result<Value, String> {
+    if depth > MAX_DEPTH {
+        return Err("Corrupted data: nesting too deep".to_string());
+    }
+    let string = |r: &mut Reader| -> Result<String, String> {
+        let i = r.varint()? as usize;
+        strings
+            .get(i)
+            .cloned()
+            .ok_or_else(|| "Corrupted data: bad string index".to_string())
+    };
+    Ok(match r.byte()? {
+        TAG_NULL => Value::Null,
+        TAG_FALSE => Value::Bool(false),
+        TAG_TRUE => Value::Bool(true),
+        TAG_UINT => Value::from(r.varint()?),
+        TAG_NEGINT => Value::from(!r.varint()? as i64),
+        TAG_NUMBER_TEXT => {
+            let text = string(r)?;
+            Value::Number(
+                text.parse::<Number>()
+                    .map_err(|_| "Corrupted data: bad number".to_string())?,
+            )
+        }
+        TAG_STRING => Value::String(string(r)?),
+        TAG_ARRAY => {
+            let len = r.len()?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(decode_value(r, strings, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        TAG_OBJECT => {
+            let len = r.len()?;
+            let mut map = Map::with_capacity(len);
+            for _ in 0..len {
+                let key = string(r)?;
+                map.insert(key, decode_value(r, strings, depth + 1)?);
+            }
+            Value::Object(map)
+        }
+        tag => return Err(format!("Corrupted data: unknown tag {}", tag)),
+    })
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| "Corrupted data: unexpected end".to_string())?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| "Corrupted data: unexpected end".to_string())?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err("Corrupted data: bad varint".to_string())
+    }
+
+    /// A length or count, bounded by the remaining input so corrupt data can't
+    /// trigger huge allocations.
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.varint()?;
+        if n > (self.bytes.len() - self.pos) as u64 {
+            return Err("Corrupted data: length out of range".to_string());
+        }
+        Ok(n as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(requests: usize) -> Value {
+        let items: Vec<Value> = (0..requests)
+            .map(|i| {
+                serde_json::json!({
+                    "id": format!("req-{}", i),
+                    "name": format!("Get user {}", i),
+                    "method": "GET",
+                    "url": "{{baseUrl}}/users",
+                    "headers": [{"key": "Accept", "value": "application/json", "enabled": true}],
+                    "timeout": -1,
+                    "weight": 0.25,
+                    "body": null
+                })
+            })
+            .collect();
+        serde_json::json!({"name": "Users", "requests": items, "version": 3})
+    }
+
+    #[test]
+    fn test_collection_roundtrip_and_size() {
+        let value = collection(200);
+        let json = value.to_string();
+        let bytes = collection_serialize(&json);
+        assert_eq!(&bytes[..5], b"VOLT\x02");
+        assert!(bytes.len() * 2 < json.len());
+        let back: Value = serde_json::from_str(&collection_deserialize(&bytes)).unwrap();
+        assert_eq!(back, value);
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    }
+
+    #[test]
+    fn test_collection_deserialize_migrates_v1() {
+        let mut bytes = b"VOLT\x01\x00".to_vec();
+        bytes.extend_from_slice(br#"{"name":"Old","requests":[]}"#);
+        assert_eq!(
+            collection_deserialize(&bytes),
+            r#"{"name":"Old","requests":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_collection_deserialize_rejects_bad_input() {
+        let error = |bytes: &[u8]| -> String {
+            let v: Value = serde_json::from_str(&collection_deserialize(bytes)).unwrap();
+            v["error"].as_str().unwrap().to_string()
+        };
+        assert_eq!(error(b"PK\x03\x04xx"), "Not a Volt collection file");
+        assert_eq!(error(b"VOLT\x09\x00"), "Unsupported format version 9");
+        let bytes = collection_serialize(&collection(3).to_string());
+        assert!(error(&bytes[..bytes.len() - 4]).starts_with("Corrupted data"));
+        // A huge declared count must fail cleanly instead of allocating.
+        assert!(error(b"VOLT\x02\x00\xff\xff\xff\xff\x0f").starts_with("Corrupted data"));
+        assert!(collection_serialize("not json").is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{Cancelled, cancelled_json, checkpoint};
+use crate::model::{Collection, Folder, KeyValue, Request};
+use crate::progress::Progress;
+
+#[derive(Serialize, Default)]
+struct CollectionDiff {
+    added: Vec<RequestRef>,
+    removed: Vec<RequestRef>,
+    renamed: Vec<Renamed>,
+    moved: Vec<Moved>,
+    modified: Vec<Modified>,
+    #[serde(rename = "foldersAdded")]
+    folders_added: Vec<String>,
+    #[serde(rename = "foldersRemoved")]
+    folders_removed: Vec<String>,
+    summary: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RequestRef {
+    id: String,
+    name: String,
+    folder: String,
+}
+
+#[derive(Serialize)]
+struct Renamed {
+    id: String,
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize)]
+struct Moved {
+    id: String,
+    name: String,
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize)]
+struct Modified {
+    id: String,
+    name: String,
+    changes: Vec<Value>,
+}
+
+/// Compare two versions of a collection, matching requests by `id`
+/// (falling back to folder path + name for requests without one).
+/// progress: optional callback invoked with {done, total, stage}.
+/// Returns JSON {added, removed, renamed, moved, modified, foldersAdded, foldersRemoved, summary}
+/// where `summary` is a list of human-readable change lines.
+#[wasm_bindgen]
+pub fn diff_collections(
+    old_json: &str,
+    new_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
+    let old: Collection = match serde_json::from_str(old_json) {
+        Ok(c) => c,
+        Err(e) => return error_json(&format!("Invalid old collection: {}", e)),
+    };
+    let new: Collection = match serde_json::from_str(new_json) {
+        Ok(c) => c,
+        Err(e) => return error_json(&format!("Invalid new collection: {}", e)),
+    };
+
+    let mut progress = Progress::new(progress, "diff", 0);
+    let _timer = crate::logging::timer("diff_collections");
+    match compute_diff(&old, &new, &mut progress) {
+        Ok(diff) => serde_json::to_string(&diff).unwrap_or_else(|_| "{}".to_string()),
+        Err(Cancelled) => cancelled_json(),
+    }
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn request_key(folder: &[&str], request: &Request) -> String {
+    if request.id.is_empty() {
+        format!("{}/{}", folder.join("/"), request.name)
+    } else {
+        request.id.clone()
+    }
+}
+
+fn folder_keys(folders: &[Folder], prefix: &str, out: &mut Vec<(String, String)>) {
+    for f in folders {
+        let path = if prefix.is_empty() {
+            f.name.clone()
+        } else {
+            format!("{}/{}", prefix, f.name)
+        };
+        let key = if f.id.is_empty() {
+            path.clone()
+        } else {
+            f.id.clone()
+        };
+        out.push((key, path.clone()));
+        folder_keys(&f.folders, &path, out);
+    }
+}
+
+fn compute_diff(
+    old: &Collection,
+    new: &Collection,
+    progress: &mut Progress,
+) -> Result<CollectionDiff, Cancelled> {
+    let mut diff = CollectionDiff::default();
+    let old_requests: Vec<(String, String, &Request)> = old
+        .all_requests()
+        .into_iter()
+        .map(|(f, r)| (request_key(&f, r), f.join("/"), r))
+        .collect();
+    let new_requests: Vec<(String, String, &Request)> = new
+        .all_requests()
+        .into_iter()
+        .map(|(f, r)| (request_key(&f, r), f.join("/"), r))
+        .collect();
+
+    let mut old_folders = Vec::new();
+    folder_keys(&old.folders, "", &mut old_folders);
+    let mut new_folders = Vec::new();
+    folder_keys(&new.folders, "", &mut new_folders);
+    for (key, path) in &new_folders {
+        if !old_folders.iter().any(|(k, _)| k == key) {
+            diff.folders_added.push(path.clone());
+            diff.summary.push(format!("Added folder \"{}\"", path));
+        }
+    }
+    for (key, path) in &old_folders {
+        if !new_folders.iter().any(|(k, _)| k == key) {
+            diff.folders_removed.push(path.clone());
+            diff.summary.push(format!("Removed folder \"{}\"", path));
+        }
+    }
+
+    for (key, folder, request) in &new_requests {
+        if !old_requests.iter().any(|(k, _, _)| k == key) {
+            diff.added.push(RequestRef {
+                id: key.clone(),
+                name: request.name.clone(),
+                folder: folder.clone(),
+            });
+            diff.summary
+                .push(format!("Added request \"{}\"", display_name(request)));
+        }
+    }
+
+    progress.stage("diff", old_requests.len());
+    for (i, (key, old_folder, old_req)) in old_requests.iter().enumerate() {
+        checkpoint()?;
+        progress.tick(i + 1);
+        let Some((_, new_folder, new_req)) = new_requests.iter().find(|(k, _, _)| k == key) else {
+            diff.removed.push(RequestRef {
+                id: key.clone(),
+                name: old_req.name.clone(),
+                folder: old_folder.clone(),
+            });
+            diff.summary
+                .push(format!("Removed request \"{}\"", display_name(old_req)));
+            continue;
+        };
+
+        if old_req.name != new_req.name {
+            diff.renamed.push(Renamed {
+                id: key.clone(),
+                from: old_req.name.clone(),
+                to: new_req.name.clone(),
+            });
+            diff.summary.push(format!(
+                "Renamed request \"{}\" to \"{}\"",
+                old_req.name, new_req.name
+            ));
+        }
+        if old_folder != new_folder {
+            diff.moved.push(Moved {
+                id: key.clone(),
+                name: new_req.name.clone(),
+                from: old_folder.clone(),
+                to: new_folder.clone(),
+            });
+            diff.summary.push(format!(
+                "Moved \"{}\" from \"{}\" to \"{}\"",
+                display_name(new_req),
+                folder_label(old_folder),
+                folder_label(new_folder)
+            ));
+        }
+
+        let changes = request_changes(old_req, new_req);
+        if !changes.is_empty() {
+            for change in &changes {
+                diff.summary.push(format!(
+                    "\"{}\": {}",
+                    display_name(new_req),
+                    describe_change(change)
+                ));
+            }
+            diff.modified.push(Modified {
+                id: key.clone(),
+                name: new_req.name.clone(),
+                changes,
+            });
+        }
+    }
+
+    Ok(diff)
+}
+
+fn display_name(request: &Request) -> String {
+    if request.name.is_empty() {
+        format!("{} {}", request.method.to_uppercase(), request.url)
+    } else {
+        request.name.clone()
+    }
+}
+
+fn folder_label(folder: &str) -> &str {
+    if folder.is_empty() { "(root)" } else { folder }
+}
+
+fn request_changes(old: &Request, new: &Request) -> Vec<Value> {
+    let mut changes = Vec::new();
+    let scalar = |field: &str, a: &str, b: &str, changes: &mut Vec<Value>| {
+        if a != b {
+            changes.push(serde_json::json!({ "field": field, "from": a, "to": b }));
+        }
+    };
+    scalar(
+        "method",
+        &old.method.to_uppercase(),
+        &new.method.to_uppercase(),
+        &mut changes,
+    );
+    scalar("url", &old.url, &new.url, &mut changes);
+    scalar(
+        "description",
+        &old.description,
+        &new.description,
+        &mut changes,
+    );
+    scalar("bodyType", &old.body_type, &new.body_type, &mut changes);
+    scalar("body", &old.body, &new.body, &mut changes);
+
+    for (field, a, b) in [
+        ("headers", &old.headers, &new.headers),
+        ("queryParams", &old.query_params, &new.query_params),
+        ("formData", &old.form_data, &new.form_data),
+    ] {
+        if let Some(change) = pair_changes(field, a, b) {
+            changes.push(change);
+        }
+    }
+
+    if old.auth != new.auth {
+        changes.push(serde_json::json!({
+            "field": "auth",
+            "from": old.auth.as_ref().map(|a| a.auth_type.clone()).unwrap_or_default(),
+            "to": new.auth.as_ref().map(|a| a.auth_type.clone()).unwrap_or_default(),
+        }));
+    }
+
+    let old_assertions: Vec<Value> = old
+        .assertions
+        .iter()
+        .filter_map(|a| serde_json::to_value(a).ok())
+        .collect();
+    let new_assertions: Vec<Value> = new
+        .assertions
+        .iter()
+        .filter_map(|a| serde_json::to_value(a).ok())
+        .collect();
+    let id_of = |v: &Value| v["id"].as_str().unwrap_or_default().to_string();
+    let added: Vec<String> = new_assertions
+        .iter()
+        .filter(|n| !old_assertions.iter().any(|o| id_of(o) == id_of(n)))
+        .map(id_of)
+        .collect();
+    let removed: Vec<String> = old_assertions
+        .iter()
+        .filter(|o| !new_assertions.iter().any(|n| id_of(o) == id_of(n)))
+        .map(id_of)
+        .collect();
+    let changed: Vec<String> = old_assertions
+        .iter()
+        .filter(|o| {
+            new_assertions
+                .iter()
+                .any(|n| id_of(o) == id_of(n) && o != &n)
+        })
+        .map(id_of)
+        .collect();
+    if !added.is_empty() || !removed.is_empty() || !changed.is_empty() {
+        changes.push(serde_json::json!({
+            "field": "assertions",
+            "added": added,
+            "removed": removed,
+            "changed": changed,
+        }));
+    }
+
+    changes
+}
+
+/// Compares key/value lists by key (first occurrence), ignoring row order.
+fn pair_changes(field: &str, old: &[KeyValue], new: &[KeyValue]) -> Option<Value> {
+    let find = |list: &[KeyValue], key: &str| list.iter().find(|p| p.key == key).cloned();
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for p in new {
+        if p.key.is_empty() {
+            continue;
+        }
+        match find(old, &p.key) {
+            None => added.push(serde_json::json!({ "key": p.key, "value": p.value })),
+            Some(o) if o.value != p.value || o.enabled != p.enabled => {
+                changed.push(serde_json::json!({
+                    "key": p.key,
+                    "from": o.value,
+                    "to": p.value,
+                    "enabled": p.enabled,
+                }))
+            }
+            _ => {}
+        }
+    }
+    for p in old {
+        if !p.key.is_empty() && find(new, &p.key).is_none() {
+            removed.push(serde_json::json!({ "key": p.key, "value": p.value }));
+        }
+    }
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        return None;
+    }
+    Some(serde_json::json!({
+        "field": field,
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    }))
+}
+
+fn describe_change(change: &Value) -> String {
+    let field = change["field"].as_str().unwrap_or_default();
+    match field {
+        "body" => "body changed".to_string(),
+        "headers" | "queryParams" | "formData" | "assertions" => {
+            let count = |k: &str| change[k].as_array().map_or(0, |a| a.len());
+            let mut parts = Vec::new();
+            for (k, label) in [
+                ("added", "added"),
+                ("removed", "removed"),
+                ("changed", "changed"),
+            ] {
+                if count(k) > 0 {
+                    parts.push(format!("{} {}", count(k), label));
+                }
+            }
+            format!("{} {}", field, parts.join(", "))
+        }
+        _ => format!(
+            "{} changed from \"{}\" to \"{}\"",
+            field,
+            change["from"].as_str().unwrap_or_default(),
+            change["to"].as_str().unwrap_or_default()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> Value {
+        serde_json::from_str(&diff_collections(old, new, None)).unwrap()
+    }
+
+    #[test]
+    fn test_diff_collections_added_removed_renamed() {
+        let old = r#"{"requests":[
+            {"id":"1","name":"List","method":"GET","url":"/users"},
+            {"id":"2","name":"Old","method":"GET","url":"/old"}]}"#;
+        let new = r#"{"requests":[
+            {"id":"1","name":"List users","method":"GET","url":"/users"},
+            {"id":"3","name":"New","method":"POST","url":"/new"}]}"#;
+        let d = diff(old, new);
+        assert_eq!(d["added"][0]["id"], "3");
+        assert_eq!(d["removed"][0]["id"], "2");
+        assert_eq!(d["renamed"][0]["to"], "List users");
+        assert!(d["modified"].as_array().unwrap().is_empty());
+        assert!(
+            d["summary"]
+                .as_array()
+                .unwrap()
+                .contains(&Value::from("Renamed request \"List\" to \"List users\""))
+        );
+    }
+
+    #[test]
+    fn test_diff_collections_field_changes() {
+        let old = r#"{"requests":[{"id":"1","name":"A","method":"GET","url":"/a",
+            "headers":{"Accept":"*/*","X-Old":"1"},
+            "assertions":[{"id":"s","type":"status","property":"","operator":"equals","expected":"200","enabled":true}]}]}"#;
+        let new = r#"{"folders":[{"id":"f","name":"Moved","requests":[{"id":"1","name":"A","method":"GET","url":"/b",
+            "headers":[{"key":"Accept","value":"application/json"},{"key":"X-New","value":"2"}],
+            "assertions":[{"id":"s","type":"status","property":"","operator":"equals","expected":"201","enabled":true}]}]}]}"#;
+        let d = diff(old, new);
+        assert_eq!(d["moved"][0]["to"], "Moved");
+        assert_eq!(d["foldersAdded"][0], "Moved");
+        let changes = d["modified"][0]["changes"].as_array().unwrap();
+        assert_eq!(changes[0]["field"], "url");
+        assert_eq!(changes[1]["field"], "headers");
+        assert_eq!(changes[1]["added"][0]["key"], "X-New");
+        assert_eq!(changes[1]["removed"][0]["key"], "X-Old");
+        assert_eq!(changes[1]["changed"][0]["to"], "application/json");
+        assert_eq!(changes[2]["field"], "assertions");
+        assert_eq!(changes[2]["changed"][0], "s");
+    }
+
+    #[test]
+    fn test_diff_collections_invalid_input() {
+        let d = diff("{}", "[1,2");
+        assert!(
+            d["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid new collection")
+        );
+    }
+}
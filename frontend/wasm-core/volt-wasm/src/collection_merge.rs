@@ -0,0 +1,568 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::openapi::escape_pointer;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Kind {
+    Request,
+    Folder,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Request => "request",
+            Kind::Folder => "folder",
+        }
+    }
+}
+
+/// A request or folder, detached from the tree. Folder `fields` exclude
+/// their `requests`/`folders` lists, which are rebuilt from `parent` links.
+#[derive(Clone, PartialEq)]
+struct Entity {
+    kind: Kind,
+    id: String,
+    parent: String,
+    fields: Map<String, Value>,
+}
+
+/// One version of a collection, flattened and keyed by "kind:id".
+#[derive(Default)]
+struct Side {
+    root: Map<String, Value>,
+    entities: IndexMap<String, Entity>,
+    /// Child keys in document order, per (parent key, kind).
+    order: HashMap<(String, Kind), Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct Conflict {
+    path: String,
+    entity: &'static str,
+    id: String,
+    field: String,
+    /// "edit" (both sides changed a field), "deleteModify" (one side deleted
+    /// what the other changed) or "move" (both sides moved it elsewhere).
+    kind: &'static str,
+    base: Value,
+    ours: Value,
+    theirs: Value,
+}
+
+/// Three-way merge of collection versions, matching requests and folders by `id`
+/// (falling back to folder path + name for items without one).
+/// Non-conflicting edits from both sides are applied field by field, recursing
+/// into objects such as `auth`; lists like `headers` merge as a whole.
+/// Returns JSON {merged, clean, conflicts: [{path, entity, id, field, kind, base, ours, theirs}]};
+/// `merged` takes "ours" wherever there is a conflict, so the UI can apply the user's choices on top.
+#[wasm_bindgen]
+pub fn merge_collections(base_json: &str, ours_json: &str, theirs_json: &str) -> String {
+    let parse = |label: &str, json: &str| -> Result<Value, String> {
+        serde_json::from_str::<Value>(json)
+            .ok()
+            .filter(Value::is_object)
+            .ok_or_else(|| format!("Invalid {} collection", label))
+    };
+    let (base, ours, theirs) = match (
+        parse("base", base_json),
+        parse("ours", ours_json),
+        parse("theirs", theirs_json),
+    ) {
+        (Ok(b), Ok(o), Ok(t)) => (b, o, t),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return serde_json::json!({ "error": e }).to_string();
+        }
+    };
+
+    let _timer = crate::logging::timer("merge_collections");
+    let (merged, conflicts) = merge(&flatten(&base), &flatten(&ours), &flatten(&theirs));
+    serde_json::to_string(&serde_json::json!({
+        "merged": merged,
+        "clean": conflicts.is_empty(),
+        "conflicts": conflicts,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+fn flatten(collection: &Value) -> Side {
+    fn walk(list: Option<&Value>, kind: Kind, parent: &str, path: &str, side: &mut Side) {
+        let Some(items) = list.and_then(Value::as_array) else {
+            return;
+        };
+        for item in items.iter().filter_map(Value::as_object) {
+            let name = item.get("name").and_then(Value::as_str).unwrap_or("");
+            let id = match item.get("id").and_then(Value::as_str) {
+                Some(id) if !id.is_empty() => id.to_string(),
+                _ => format!("{}/{}", path, name),
+            };
+            let key = format!("{}:{}", kind.name(), id);
+            if side.entities.contains_key(&key) {
+                continue;
+            }
+            let mut fields = item.clone();
+            if kind == Kind::Folder {
+                fields.remove("requests");
+                fields.remove("folders");
+            }
+            side.order
+                .entry((parent.to_string(), kind))
+                .or_default()
+                .push(key.clone());
+            side.entities.insert(
+                key.clone(),
+                Entity {
+                    kind,
+                    id,
+                    parent: parent.to_string(),
+                    fields,
+                },
+            );
+            if kind == Kind::Folder {
+                let path = format!("{}/{}", path, name);
+                walk(item.get("requests"), Kind::Request, &key, &path, side);
+                walk(item.get("folders"), Kind::Folder, &key, &path, side);
+            }
+        }
+    }
+
+    let mut side = Side::default();
+    let Some(map) = collection.as_object() else {
+        return side;
+    };
+    side.root = map.clone();
+    side.root.remove("requests");
+    side.root.remove("folders");
+    walk(map.get("requests"), Kind::Request, "", "", &mut side);
+    walk(map.get("folders"), Kind::Folder, "", "", &mut side);
+    side
+}
+
+struct Merger {
+    conflicts: Vec<Conflict>,
+}
+
+impl Merger {
+    /// Merge one value; objects recurse key by key, everything else is atomic.
+    fn value(
+        &mut self,
+        base: Option<&Value>,
+        ours: Option<&Value>,
+        theirs: Option<&Value>,
+        at: (&Entity, &str),
+    ) -> Option<Value> {
+        if ours == theirs || theirs == base {
+            return ours.cloned();
+        }
+        if ours == base {
+            return theirs.cloned();
+        }
+        if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+            let empty = Map::new();
+            let b = match base {
+                Some(Value::Object(b)) => Some(b),
+                None => Some(&empty),
+                _ => None,
+            };
+            if let Some(b) = b {
+                return Some(Value::Object(self.fields(b, o, t, at)));
+            }
+        }
+        self.conflict("edit", at, base, ours, theirs);
+        ours.cloned()
+    }
+
+    fn fields(
+        &mut self,
+        base: &Map<String, Value>,
+        ours: &Map<String, Value>,
+        theirs: &Map<String, Value>,
+        (entity, prefix): (&Entity, &str),
+    ) -> Map<String, Value> {
+        let mut out = Map::new();
+        let keys = ours
+            .keys()
+            .chain(theirs.keys().filter(|k| !ours.contains_key(*k)))
+            .chain(
+                base.keys()
+                    .filter(|k| !ours.contains_key(*k) && !theirs.contains_key(*k)),
+            );
+        for key in keys {
+            let field = if prefix.is_empty() {
+                escape_pointer(key)
+            } else {
+                format!("{}/{}", prefix, escape_pointer(key))
+            };
+            if let Some(v) = self.value(
+                base.get(key),
+                ours.get(key),
+                theirs.get(key),
+                (entity, &field),
+            ) {
+                out.insert(key.clone(), v);
+            }
+        }
+        out
+    }
+
+    fn conflict(
+        &mut self,
+        kind: &'static str,
+        (entity, field): (&Entity, &str),
+        base: Option<&Value>,
+        ours: Option<&Value>,
+        theirs: Option<&Value>,
+    ) {
+        let entity_name = if entity.id.is_empty() {
+            "collection"
+        } else {
+            entity.kind.name()
+        };
+        let mut path = if entity.id.is_empty() {
+            "collection".to_string()
+        } else {
+            format!("{}:{}", entity_name, entity.id)
+        };
+        if !field.is_empty() {
+            path.push('/');
+            path.push_str(field);
+        }
+        self.conflicts.push(Conflict {
+            path,
+            entity: entity_name,
+            id: entity.id.clone(),
+            field: field.to_string(),
+            kind,
+            base: base.cloned().unwrap_or(Value::Null),
+            ours: ours.cloned().unwrap_or(Value::Null),
+            theirs: theirs.cloned().unwrap_or(Value::Null),
+        });
+    }
+
+    fn entity(
+        &mut self,
+        base: Option<&Entity>,
+        ours: Option<&Entity>,
+        theirs: Option<&Entity>,
+    ) -> Option<Entity> {
+        match (base, ours, theirs) {
+            (_, Some(o), Some(t)) => {
+                let empty = Map::new();
+                let b_fields = base.map(|b| &b.fields).unwrap_or(&empty);
+                let fields = self.fields(b_fields, &o.fields, &t.fields, (o, ""));
+                let b_parent = base.map(|b| &b.parent);
+                let parent = if o.parent == t.parent || Some(&t.parent) == b_parent {
+                    o.parent.clone()
+                } else if Some(&o.parent) == b_parent {
+                    t.parent.clone()
+                } else {
+                    self.conflict(
+                        "move",
+                        (o, ""),
+                        b_parent.map(|p| parent_value(p)).as_ref(),
+                        Some(&parent_value(&o.parent)),
+                        Some(&parent_value(&t.parent)),
+                    );
+                    o.parent.clone()
+                };
+                Some(Entity {
+                    fields,
+                    parent,
+                    ..o.clone()
+                })
+            }
+            (Some(b), Some(kept), None) | (Some(b), None, Some(kept)) => {
+                if kept == b {
+                    return None;
+                }
+                let object = |e: &Entity| Value::Object(e.fields.clone());
+                let (o, t) = if ours.is_some() {
+                    (Some(object(kept)), None)
+                } else {
+                    (None, Some(object(kept)))
+                };
+                self.conflict(
+                    "deleteModify",
+                    (kept, ""),
+                    Some(&object(b)),
+                    o.as_ref(),
+                    t.as_ref(),
+                );
+                Some(kept.clone())
+            }
+            (None, Some(added), None) | (None, None, Some(added)) => Some(added.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Parent key ("folder:<id>" or "" for the root) as reported in conflicts.
+fn parent_value(parent: &str) -> Value {
+    Value::String(parent.strip_prefix("folder:").unwrap_or("").to_string())
+}
+
+fn merge(base: &Side, ours: &Side, theirs: &Side) -> (Value, Vec<Conflict>) {
+    let mut merger = Merger {
+        conflicts: Vec::new(),
+    };
+    let root_entity = Entity {
+        kind: Kind::Folder,
+        id: String::new(),
+        parent: String::new(),
+        fields: Map::new(),
+    };
+    let root = merger.fields(&base.root, &ours.root, &theirs.root, (&root_entity, ""));
+
+    let keys: Vec<&String> = ours
+        .entities
+        .keys()
+        .chain(theirs.entities.keys())
+        .chain(base.entities.keys())
+        .collect::<indexmap::IndexSet<_>>()
+        .into_iter()
+        .collect();
+    let mut result: IndexMap<String, Entity> = IndexMap::new();
+    for key in keys {
+        if let Some(e) = merger.entity(
+            base.entities.get(key),
+            ours.entities.get(key),
+            theirs.entities.get(key),
+        ) {
+            result.insert(key.clone(), e);
+        }
+    }
+
+    // Keep deleted folders that still hold surviving items.
+    loop {
+        let orphan = result.values().find_map(|e| {
+            (!e.parent.is_empty() && !result.contains_key(&e.parent)).then(|| e.parent.clone())
+        });
+        let Some(parent) = orphan else {
+            break;
+        };
+        let folder = [ours, theirs, base]
+            .iter()
+            .find_map(|side| side.entities.get(&parent))
+            .cloned()
+            .expect("every parent key comes from some side");
+        let object = |side: &Side| {
+            side.entities
+                .get(&parent)
+                .map(|e| Value::Object(e.fields.clone()))
+        };
+        merger.conflict(
+            "deleteModify",
+            (&folder, ""),
+            object(base).as_ref(),
+            object(ours).as_ref(),
+            object(theirs).as_ref(),
+        );
+        result.insert(parent, folder);
+    }
+
+    let mut tree = Tree {
+        result: &result,
+        sides: [ours, theirs, base],
+        placed: HashSet::new(),
+    };
+    let mut merged = root;
+    let (requests, mut folders) = tree.children("");
+    // Folders caught in a move cycle are unreachable from the root; re-home them there.
+    for key in result.keys() {
+        if result[key].kind == Kind::Folder && !tree.placed.contains(key.as_str()) {
+            tree.placed.insert(key);
+            folders.push(tree.folder(key));
+        }
+    }
+    merged.insert("requests".to_string(), Value::Array(requests));
+    merged.insert("folders".to_string(), Value::Array(folders));
+    (Value::Object(merged), merger.conflicts)
+}
+
+struct Tree<'a> {
+    result: &'a IndexMap<String, Entity>,
+    /// Order sources, by priority: ours, theirs, base.
+    sides: [&'a Side; 3],
+    placed: HashSet<&'a str>,
+}
+
+impl<'a> Tree<'a> {
+    fn children(&mut self, parent: &str) -> (Vec<Value>, Vec<Value>) {
+        let requests = self
+            .ordered(parent, Kind::Request)
+            .into_iter()
+            .map(|k| Value::Object(self.result[k].fields.clone()))
+            .collect();
+        let folders = self
+            .ordered(parent, Kind::Folder)
+            .into_iter()
+            .filter(|k| self.placed.insert(k))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|k| self.folder(k))
+            .collect();
+        (requests, folders)
+    }
+
+    fn folder(&mut self, key: &'a str) -> Value {
+        let mut fields = self.result[key].fields.clone();
+        let (requests, folders) = self.children(key);
+        fields.insert("requests".to_string(), Value::Array(requests));
+        fields.insert("folders".to_string(), Value::Array(folders));
+        Value::Object(fields)
+    }
+
+    /// Merged children of one parent: ours' order first, then items only theirs
+    /// places here inserted after their predecessor in theirs, then the rest in base order.
+    fn ordered(&self, parent: &str, kind: Kind) -> Vec<&'a str> {
+        let belongs = |k: &str| {
+            self.result
+                .get(k)
+                .is_some_and(|e| e.kind == kind && e.parent == parent)
+        };
+        let order = |side: &'a Side| -> Vec<&'a str> {
+            side.order
+                .get(&(parent.to_string(), kind))
+                .map(|keys| keys.iter().map(String::as_str).collect())
+                .unwrap_or_default()
+        };
+        let mut seq: Vec<&'a str> = order(self.sides[0])
+            .into_iter()
+            .filter(|k| belongs(k))
+            .collect();
+        let mut after: Option<&str> = None;
+        for key in order(self.sides[1]) {
+            if !belongs(key) {
+                continue;
+            }
+            if !seq.contains(&key) {
+                let at = after
+                    .and_then(|a| seq.iter().position(|k| *k == a))
+                    .map_or(0, |i| i + 1);
+                seq.insert(at, key);
+            }
+            after = Some(key);
+        }
+        // Items whose parent changed have no order entry under it in every side.
+        for (key, entity) in self.result {
+            if entity.kind == kind && entity.parent == parent && !seq.contains(&key.as_str()) {
+                seq.push(key);
+            }
+        }
+        seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(base: &Value, ours: &Value, theirs: &Value) -> Value {
+        serde_json::from_str(&merge_collections(
+            &base.to_string(),
+            &ours.to_string(),
+            &theirs.to_string(),
+        ))
+        .unwrap()
+    }
+
+    fn base() -> Value {
+        serde_json::json!({
+            "name": "API",
+            "requests": [
+                {"id": "a", "name": "List", "method": "GET", "url": "/users", "auth": {"type": "bearer", "token": "t"}},
+                {"id": "b", "name": "Create", "method": "POST", "url": "/users"}
+            ],
+            "folders": [{"id": "f", "name": "Admin", "requests": [{"id": "c", "name": "Stats", "url": "/stats"}]}]
+        })
+    }
+
+    #[test]
+    fn test_merge_collections_clean() {
+        let mut ours = base();
+        ours["requests"][0]["url"] = "/v2/users".into();
+        ours["requests"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"id": "d", "name": "Delete"}));
+        let mut theirs = base();
+        theirs["requests"][0]["auth"]["token"] = "{{token}}".into();
+        theirs["requests"].as_array_mut().unwrap().remove(1);
+        theirs["folders"][0]["requests"]
+            .as_array_mut()
+            .unwrap()
+            .insert(0, serde_json::json!({"id": "e", "name": "Health"}));
+        theirs["name"] = "API v2".into();
+
+        let result = run(&base(), &ours, &theirs);
+        assert_eq!(result["clean"], true);
+        let merged = &result["merged"];
+        assert_eq!(merged["name"], "API v2");
+        assert_eq!(merged["requests"][0]["url"], "/v2/users");
+        assert_eq!(merged["requests"][0]["auth"]["token"], "{{token}}");
+        let ids: Vec<&str> = merged["requests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["a", "d"]);
+        assert_eq!(merged["folders"][0]["requests"][0]["id"], "e");
+        assert_eq!(merged["folders"][0]["requests"][1]["id"], "c");
+    }
+
+    #[test]
+    fn test_merge_collections_conflicts() {
+        let mut ours = base();
+        ours["requests"][0]["url"] = "/ours".into();
+        ours["folders"].as_array_mut().unwrap().clear();
+        let mut theirs = base();
+        theirs["requests"][0]["url"] = "/theirs".into();
+        theirs["folders"][0]["requests"][0]["url"] = "/stats/v2".into();
+
+        let result = run(&base(), &ours, &theirs);
+        assert_eq!(result["clean"], false);
+        let conflicts = result["conflicts"].as_array().unwrap();
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0]["path"], "request:a/url");
+        assert_eq!(conflicts[0]["kind"], "edit");
+        assert_eq!(conflicts[0]["ours"], "/ours");
+        assert_eq!(conflicts[0]["theirs"], "/theirs");
+        assert_eq!(conflicts[1]["path"], "request:c");
+        assert_eq!(conflicts[1]["kind"], "deleteModify");
+        assert_eq!(conflicts[1]["ours"], Value::Null);
+        // The folder ours deleted comes back because it still holds request c.
+        assert_eq!(conflicts[2]["path"], "folder:f");
+        assert_eq!(result["merged"]["requests"][0]["url"], "/ours");
+        assert_eq!(
+            result["merged"]["folders"][0]["requests"][0]["url"],
+            "/stats/v2"
+        );
+    }
+
+    #[test]
+    fn test_merge_collections_move_and_invalid_input() {
+        let mut ours = base();
+        let moved = ours["requests"].as_array_mut().unwrap().remove(1);
+        ours["folders"][0]["requests"]
+            .as_array_mut()
+            .unwrap()
+            .push(moved);
+        let mut theirs = base();
+        theirs["requests"][1]["method"] = "PUT".into();
+
+        let result = run(&base(), &ours, &theirs);
+        assert_eq!(result["clean"], true);
+        let folder = &result["merged"]["folders"][0]["requests"];
+        assert_eq!(folder[1]["id"], "b");
+        assert_eq!(folder[1]["method"], "PUT");
+
+        let error: Value = serde_json::from_str(&merge_collections("{}", "[]", "{}")).unwrap();
+        assert_eq!(error["error"], "Invalid ours collection");
+    }
+}
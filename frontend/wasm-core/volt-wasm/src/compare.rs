@@ -0,0 +1,516 @@
+use indexmap::IndexMap;
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::regex_tester::cached_regex;
+use crate::{now_ms, parse_iso_datetime};
+
+/// How `bodyJson` equality (and `json_compare`) matches actual against expected.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct CompareOptions {
+    /// Arrays match as multisets: same elements in any order.
+    pub ignore_array_order: bool,
+    /// Objects may have keys that are not in the expected value.
+    pub ignore_extra_keys: bool,
+    /// Expected ⊆ actual: implies `ignore_extra_keys`, and arrays may also
+    /// contain elements that are not expected.
+    pub subset: bool,
+}
+
+impl CompareOptions {
+    pub(crate) fn is_exact(&self) -> bool {
+        *self == CompareOptions::default()
+    }
+}
+
+/// Compare two JSON documents.
+/// options_json: optional {ignoreArrayOrder, ignoreExtraKeys, subset}.
+/// Returns true if `actual_json` matches `expected_json` under the options.
+#[wasm_bindgen]
+pub fn json_compare(actual_json: &str, expected_json: &str, options_json: &str) -> bool {
+    let (Ok(actual), Ok(expected)) = (
+        serde_json::from_str::<Value>(actual_json),
+        serde_json::from_str::<Value>(expected_json),
+    ) else {
+        return false;
+    };
+    let options: CompareOptions = serde_json::from_str(options_json).unwrap_or_default();
+    json_matches(&actual, &expected, &options)
+}
+
+/// Numbers compare by value; see `CompareOptions` for arrays and objects.
+pub(crate) fn json_matches(actual: &Value, expected: &Value, options: &CompareOptions) -> bool {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => numbers_equal(a, e),
+        (Value::Object(a), Value::Object(e)) => {
+            let extra_ok = options.ignore_extra_keys || options.subset;
+            (extra_ok || a.len() == e.len())
+                && e.iter()
+                    .all(|(k, ev)| a.get(k).is_some_and(|av| json_matches(av, ev, options)))
+        }
+        (Value::Array(a), Value::Array(e)) => {
+            if !options.subset && a.len() != e.len() {
+                return false;
+            }
+            if options.ignore_array_order || options.subset && a.len() != e.len() {
+                unordered_match(a, e, options)
+            } else {
+                a.iter()
+                    .zip(e)
+                    .all(|(av, ev)| json_matches(av, ev, options))
+            }
+        }
+        _ => actual == expected,
+    }
+}
+
+/// Per-path leniency for `compare_to_golden`.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct FieldTolerance {
+    /// Numbers may differ by at most this much.
+    absolute: Option<f64>,
+    /// Numbers may differ by at most this fraction of the golden value.
+    relative: Option<f64>,
+    /// Both values are timestamps at most this many seconds apart.
+    within_seconds: Option<f64>,
+    /// "golden" (default) or "now": what `within_seconds` is measured from.
+    relative_to: String,
+    /// The actual value must match this regex; the golden value is a placeholder.
+    pattern: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct GoldenOptions {
+    /// Keyed by path, e.g. "total" or "items[*].price" for every element.
+    fields: IndexMap<String, FieldTolerance>,
+    ignore: Vec<String>,
+    ignore_extra_keys: bool,
+}
+
+/// Approval-style comparison of a response body against a stored golden file.
+/// tolerance_json: {fields: {path: {absolute, relative, withinSeconds, relativeTo, pattern}},
+/// ignore: [path], ignoreExtraKeys}. Paths use the `a.b[0].c` syntax, with `[*]`
+/// matching any index. Timestamps are ISO 8601 strings or epoch seconds/milliseconds.
+/// Returns JSON {passed, mismatches: [{path, reason, expected, actual}]} where reason
+/// is "missing", "unexpected", "type", "value", "length", "tolerance", "timestamp" or
+/// "pattern"; or {error} if either document or a pattern is invalid.
+#[wasm_bindgen]
+pub fn compare_to_golden(golden_json: &str, response_body: &str, tolerance_json: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let golden: Value = match serde_json::from_str(golden_json) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid golden JSON: {}", e)),
+    };
+    let actual: Value = match serde_json::from_str(response_body) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid response JSON: {}", e)),
+    };
+    let options: GoldenOptions = serde_json::from_str(tolerance_json).unwrap_or_default();
+    let mut patterns = IndexMap::new();
+    for (path, rule) in &options.fields {
+        if let Some(pattern) = &rule.pattern {
+            match cached_regex(pattern) {
+                Ok(re) => patterns.insert(path.as_str(), re),
+                Err(e) => return error(format!("Invalid pattern for {}: {}", path, e)),
+            };
+        }
+    }
+
+    let mut golden_cmp = Golden {
+        options: &options,
+        patterns,
+        mismatches: Vec::new(),
+    };
+    golden_cmp.compare(&golden, &actual, String::new());
+    serde_json::json!({
+        "passed": golden_cmp.mismatches.is_empty(),
+        "mismatches": golden_cmp.mismatches,
+    })
+    .to_string()
+}
+
+struct Golden<'a> {
+    options: &'a GoldenOptions,
+    patterns: IndexMap<&'a str, Regex>,
+    mismatches: Vec<Value>,
+}
+
+impl Golden<'_> {
+    fn compare(&mut self, golden: &Value, actual: &Value, path: String) {
+        if self.ignored(&path) {
+            return;
+        }
+        let generic = wildcard_path(&path);
+        let rule = self
+            .options
+            .fields
+            .get_key_value(path.as_str())
+            .or_else(|| self.options.fields.get_key_value(generic.as_str()));
+        if let Some((key, rule)) = rule {
+            self.apply(key, rule, golden, actual, &path);
+            return;
+        }
+
+        match (golden, actual) {
+            (Value::Object(g), Value::Object(a)) => {
+                for (k, gv) in g {
+                    let child = child_path(&path, k);
+                    match a.get(k) {
+                        Some(av) => self.compare(gv, av, child),
+                        None if !self.ignored(&child) => {
+                            self.mismatch(&child, "missing", gv, &Value::Null)
+                        }
+                        None => {}
+                    }
+                }
+                if !self.options.ignore_extra_keys {
+                    for (k, av) in a.iter().filter(|(k, _)| !g.contains_key(*k)) {
+                        let child = child_path(&path, k);
+                        if !self.ignored(&child) {
+                            self.mismatch(&child, "unexpected", &Value::Null, av);
+                        }
+                    }
+                }
+            }
+            (Value::Array(g), Value::Array(a)) => {
+                if g.len() != a.len() {
+                    self.mismatch(&path, "length", &g.len().into(), &a.len().into());
+                }
+                for (i, (gv, av)) in g.iter().zip(a).enumerate() {
+                    self.compare(gv, av, format!("{}[{}]", path, i));
+                }
+            }
+            (Value::Number(g), Value::Number(a)) if !numbers_equal(g, a) => {
+                self.mismatch(&path, "value", golden, actual);
+            }
+            (Value::Number(_), Value::Number(_)) => {}
+            _ if std::mem::discriminant(golden) != std::mem::discriminant(actual) => {
+                self.mismatch(&path, "type", golden, actual);
+            }
+            _ if golden != actual => self.mismatch(&path, "value", golden, actual),
+            _ => {}
+        }
+    }
+
+    fn apply(
+        &mut self,
+        key: &str,
+        rule: &FieldTolerance,
+        golden: &Value,
+        actual: &Value,
+        path: &str,
+    ) {
+        if let Some(re) = self.patterns.get(key) {
+            let text = match actual {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => actual.to_string(),
+                _ => {
+                    self.mismatch(path, "type", golden, actual);
+                    return;
+                }
+            };
+            if !re.is_match(&text) {
+                self.mismatch(path, "pattern", golden, actual);
+            }
+            return;
+        }
+        if let Some(seconds) = rule.within_seconds {
+            let reference = if rule.relative_to == "now" {
+                Some(now_ms())
+            } else {
+                timestamp_ms(golden)
+            };
+            match (reference, timestamp_ms(actual)) {
+                (Some(r), Some(a)) if (a - r).abs() <= seconds * 1000.0 => {}
+                _ => self.mismatch(path, "timestamp", golden, actual),
+            }
+            return;
+        }
+        match (golden.as_f64(), actual.as_f64()) {
+            (Some(g), Some(a)) => {
+                let diff = (a - g).abs();
+                let ok = diff == 0.0
+                    || rule.absolute.is_some_and(|t| diff <= t)
+                    || rule.relative.is_some_and(|t| diff <= t * g.abs());
+                if !ok {
+                    self.mismatch(path, "tolerance", golden, actual);
+                }
+            }
+            _ if golden != actual => self.mismatch(path, "type", golden, actual),
+            _ => {}
+        }
+    }
+
+    fn ignored(&self, path: &str) -> bool {
+        let generic = wildcard_path(path);
+        self.options
+            .ignore
+            .iter()
+            .any(|p| p == path || *p == generic)
+    }
+
+    fn mismatch(&mut self, path: &str, reason: &str, expected: &Value, actual: &Value) {
+        self.mismatches.push(serde_json::json!({
+            "path": path,
+            "reason": reason,
+            "expected": expected,
+            "actual": actual,
+        }));
+    }
+}
+
+pub(crate) fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// "items[2].tags[0]" → "items[*].tags[*]".
+pub(crate) fn wildcard_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut in_index = false;
+    for c in path.chars() {
+        match c {
+            '[' => {
+                in_index = true;
+                out.push_str("[*");
+            }
+            ']' => {
+                in_index = false;
+                out.push(']');
+            }
+            _ if in_index => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Epoch milliseconds for an ISO 8601 string or an epoch number in seconds or
+/// milliseconds.
+fn timestamp_ms(value: &Value) -> Option<f64> {
+    let epoch = |n: f64| if n.abs() >= 1e11 { n } else { n * 1000.0 };
+    match value {
+        Value::Number(n) => n.as_f64().map(epoch),
+        Value::String(s) => parse_iso_datetime(s).or_else(|| s.trim().parse().ok().map(epoch)),
+        _ => None,
+    }
+}
+
+/// Match every expected element to a distinct actual element (bipartite
+/// matching with augmenting paths, so greedy choices never cause false negatives).
+/// Order is still respected for subsets unless `ignore_array_order` is set.
+fn unordered_match(actual: &[Value], expected: &[Value], options: &CompareOptions) -> bool {
+    if !options.ignore_array_order {
+        // Ordered subset: expected must appear as a subsequence of actual.
+        let mut it = actual.iter();
+        return expected
+            .iter()
+            .all(|ev| it.any(|av| json_matches(av, ev, options)));
+    }
+
+    let candidates: Vec<Vec<usize>> = expected
+        .iter()
+        .map(|ev| {
+            (0..actual.len())
+                .filter(|&i| json_matches(&actual[i], ev, options))
+                .collect()
+        })
+        .collect();
+
+    fn augment(
+        e: usize,
+        candidates: &[Vec<usize>],
+        owner: &mut [Option<usize>],
+        visited: &mut [bool],
+    ) -> bool {
+        for &a in &candidates[e] {
+            if visited[a] {
+                continue;
+            }
+            visited[a] = true;
+            if owner[a].is_none_or(|other| augment(other, candidates, owner, visited)) {
+                owner[a] = Some(e);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut owner: Vec<Option<usize>> = vec![None; actual.len()];
+    (0..expected.len()).all(|e| {
+        let mut visited = vec![false; actual.len()];
+        augment(e, &candidates, &mut owner, &mut visited)
+    })
+}
+
+/// Numbers compare by value, so `1.0 == 1.00` and `100 == 1e2` even though
+/// arbitrary-precision numbers keep their original text.
+fn numbers_equal(x: &serde_json::Number, y: &serde_json::Number) -> bool {
+    // Integers compare exactly so ids beyond 2^53 are not confused.
+    if let (Some(a), Some(b)) = (x.as_i64(), y.as_i64()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (x.as_u64(), y.as_u64()) {
+        return a == b;
+    }
+    let (sx, sy) = (x.to_string(), y.to_string());
+    if sx == sy {
+        return true;
+    }
+    let integral = |s: &str| !s.contains(['.', 'e', 'E']);
+    if integral(&sx) && integral(&sy) {
+        // Both are integers wider than 64 bits: compare the digits.
+        return sx.trim_start_matches('-').trim_start_matches('0')
+            == sy.trim_start_matches('-').trim_start_matches('0')
+            && sx.starts_with('-') == sy.starts_with('-');
+    }
+    matches!((x.as_f64(), y.as_f64()), (Some(a), Some(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(actual: &str, expected: &str, options: &str) -> bool {
+        json_compare(actual, expected, options)
+    }
+
+    #[test]
+    fn test_json_compare_exact() {
+        assert!(matches(
+            r#"{"a":[1,2],"b":1.0}"#,
+            r#"{"b":1,"a":[1,2]}"#,
+            "{}"
+        ));
+        assert!(!matches(r#"[1,2]"#, r#"[2,1]"#, "{}"));
+        assert!(!matches(r#"{"a":1,"b":2}"#, r#"{"a":1}"#, "{}"));
+        assert!(!matches("nope", "{}", "{}"));
+    }
+
+    #[test]
+    fn test_json_compare_numbers_by_value() {
+        assert!(matches("1.0", "1.00", "{}"));
+        assert!(matches("100", "1e2", "{}"));
+        assert!(matches(r#"{"a":[1.50]}"#, r#"{"a":[1.5]}"#, "{}"));
+        assert!(!matches("9007199254740993", "9007199254740992", "{}"));
+    }
+
+    #[test]
+    fn test_json_compare_ignore_array_order() {
+        let opts = r#"{"ignoreArrayOrder":true}"#;
+        assert!(matches(r#"[1,2,2,3]"#, r#"[3,2,1,2]"#, opts));
+        assert!(!matches(r#"[1,1,2]"#, r#"[1,2,2]"#, opts));
+        assert!(matches(
+            r#"[{"id":1},{"id":2}]"#,
+            r#"[{"id":2},{"id":1}]"#,
+            opts
+        ));
+    }
+
+    #[test]
+    fn test_json_compare_extra_keys_and_subset() {
+        let extra = r#"{"ignoreExtraKeys":true}"#;
+        assert!(matches(
+            r#"{"a":1,"b":{"c":2,"d":3}}"#,
+            r#"{"b":{"c":2}}"#,
+            extra
+        ));
+        assert!(!matches(r#"{"a":[1,2,3]}"#, r#"{"a":[1,3]}"#, extra));
+
+        let subset = r#"{"subset":true}"#;
+        assert!(matches(r#"{"a":[1,2,3]}"#, r#"{"a":[1,3]}"#, subset));
+        assert!(!matches(r#"{"a":[1,2,3]}"#, r#"{"a":[3,1]}"#, subset));
+        assert!(matches(
+            r#"{"a":[1,2,3]}"#,
+            r#"{"a":[3,1]}"#,
+            r#"{"subset":true,"ignoreArrayOrder":true}"#
+        ));
+        // A greedy match of {"x":1} to the first element would wrongly fail here.
+        assert!(matches(
+            r#"[{"x":1,"y":1},{"x":1}]"#,
+            r#"[{"x":1},{"y":1}]"#,
+            r#"{"subset":true,"ignoreArrayOrder":true}"#
+        ));
+    }
+
+    #[test]
+    fn test_compare_to_golden_tolerances() {
+        let golden = r#"{
+            "id": "GOLDEN-ID",
+            "total": 100.0,
+            "items": [{"price": 10.0, "qty": 1}, {"price": 20.0, "qty": 2}],
+            "createdAt": "2024-01-01T10:00:00Z",
+            "seenAt": 1704103200
+        }"#;
+        let actual = r#"{
+            "id": "ord_8f2a91",
+            "total": 103,
+            "items": [{"price": 10.004, "qty": 1}, {"price": 19.999, "qty": 2}],
+            "createdAt": "2024-01-01T10:00:45+00:00",
+            "seenAt": 1704103230000
+        }"#;
+        let tolerance = r#"{"fields": {
+            "id": {"pattern": "^ord_[0-9a-f]{6}$"},
+            "total": {"relative": 0.05},
+            "items[*].price": {"absolute": 0.01},
+            "createdAt": {"withinSeconds": 60},
+            "seenAt": {"withinSeconds": 30}
+        }}"#;
+        let out: Value =
+            serde_json::from_str(&compare_to_golden(golden, actual, tolerance)).unwrap();
+        assert_eq!(out["passed"], true, "{}", out);
+
+        let strict = r#"{"fields": {
+            "id": {"pattern": "^ord_\\d+$"},
+            "total": {"absolute": 1},
+            "createdAt": {"withinSeconds": 10}
+        }, "ignore": ["items", "seenAt"]}"#;
+        let out: Value = serde_json::from_str(&compare_to_golden(golden, actual, strict)).unwrap();
+        let reasons: Vec<(&str, &str)> = out["mismatches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| (m["path"].as_str().unwrap(), m["reason"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                ("id", "pattern"),
+                ("total", "tolerance"),
+                ("createdAt", "timestamp")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_to_golden_structure() {
+        let out: Value = serde_json::from_str(&compare_to_golden(
+            r#"{"a": 1, "b": [1, 2], "c": "x"}"#,
+            r#"{"a": "1", "b": [1], "d": true, "e": 0}"#,
+            r#"{"ignore": ["e"]}"#,
+        ))
+        .unwrap();
+        assert_eq!(out["passed"], false);
+        let reasons: Vec<&str> = out["mismatches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["reason"].as_str().unwrap())
+            .collect();
+        assert_eq!(reasons, ["type", "length", "missing", "unexpected"]);
+
+        assert!(compare_to_golden("{", "{}", "").contains("Invalid golden JSON"));
+        assert!(
+            compare_to_golden("{}", "{}", r#"{"fields":{"a":{"pattern":"("}}}"#)
+                .contains("Invalid pattern")
+        );
+    }
+}
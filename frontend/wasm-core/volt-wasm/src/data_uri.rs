@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_encode_into, logging};
+
+/// Larger bodies are not turned into data URIs: the string would be a third
+/// bigger again, and some browsers refuse data URIs past a few tens of MB.
+const MAX_DATA_URI_BYTES: usize = 10 * 1024 * 1024;
+
+/// Leading bytes of formats the response viewer can preview, with their type.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"BM", "image/bmp"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+];
+
+/// Build a `data:` URI for previewing a binary response body inline.
+/// content_type: the response's Content-Type; parameters other than `charset` are
+/// dropped. When it is missing, invalid or `application/octet-stream`, the type is
+/// sniffed from the first bytes (PNG, JPEG, GIF, WebP, BMP, ICO, PDF, SVG).
+/// The base64 is written straight into the result, so the body is not copied again.
+/// Returns the URI, or an empty string for an empty body or one over 10 MiB.
+#[wasm_bindgen]
+pub fn to_data_uri(bytes: &[u8], content_type: &str) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+    if bytes.len() > MAX_DATA_URI_BYTES {
+        logging::warn("to_data_uri", || {
+            format!(
+                "body of {} bytes exceeds the {} byte preview limit",
+                bytes.len(),
+                MAX_DATA_URI_BYTES
+            )
+        });
+        return String::new();
+    }
+
+    let media_type = media_type(content_type).unwrap_or_else(|| sniff(bytes).to_string());
+    let mut uri = String::with_capacity(media_type.len() + 13 + bytes.len().div_ceil(3) * 4);
+    uri.push_str("data:");
+    uri.push_str(&media_type);
+    uri.push_str(";base64,");
+    base64_encode_into(&mut uri, bytes);
+    uri
+}
+
+/// "Image/PNG; name=x" → "image/png"; keeps a charset parameter. None when the
+/// type is generic or contains characters that would break the URI.
+fn media_type(content_type: &str) -> Option<String> {
+    let mut parts = content_type.split(';');
+    let essence = parts.next()?.trim().to_lowercase();
+    let token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    let (kind, subtype) = essence.split_once('/')?;
+    if !token(kind) || !token(subtype) || essence == "application/octet-stream" {
+        return None;
+    }
+    let charset = parts.find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        let v = v.trim().trim_matches('"');
+        (k.trim().eq_ignore_ascii_case("charset") && token(v)).then(|| v.to_lowercase())
+    });
+    Some(match charset {
+        Some(charset) => format!("{};charset={}", essence, charset),
+        None => essence,
+    })
+}
+
+pub(crate) fn sniff(bytes: &[u8]) -> &'static str {
+    if let Some((_, kind)) = SIGNATURES
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+    {
+        return kind;
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+        return "image/svg+xml";
+    }
+    "application/octet-stream"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+
+    #[test]
+    fn test_to_data_uri() {
+        assert_eq!(
+            to_data_uri(b"hello", "Text/Plain; charset=\"UTF-8\"; format=flowed"),
+            "data:text/plain;charset=utf-8;base64,aGVsbG8="
+        );
+        assert!(to_data_uri(PNG, "").starts_with("data:image/png;base64,iVBORw0KGgo"));
+        assert!(to_data_uri(PNG, "application/octet-stream").starts_with("data:image/png;"));
+        assert!(to_data_uri(b"%PDF-1.7", "bad type,x").starts_with("data:application/pdf;"));
+        assert!(
+            to_data_uri(b"<?xml version=\"1.0\"?><svg/>", "").starts_with("data:image/svg+xml;")
+        );
+        assert!(to_data_uri(b"\x00\x01", "").starts_with("data:application/octet-stream;"));
+    }
+
+    #[test]
+    fn test_to_data_uri_limits() {
+        assert_eq!(to_data_uri(b"", "image/png"), "");
+        let big = vec![0u8; MAX_DATA_URI_BYTES + 1];
+        assert_eq!(to_data_uri(&big, "image/png"), "");
+        let max = vec![0u8; MAX_DATA_URI_BYTES];
+        let uri = to_data_uri(&max, "image/png");
+        let prefix = "data:image/png;base64,";
+        assert!(uri.starts_with(prefix));
+        assert_eq!(uri.len(), prefix.len() + MAX_DATA_URI_BYTES.div_ceil(3) * 4);
+    }
+}
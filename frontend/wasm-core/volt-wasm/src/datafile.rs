@@ -0,0 +1,331 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::variables::substitute_request;
+
+/// Rows beyond this are not expanded; the result is marked truncated.
+const MAX_ROWS: usize = 10_000;
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct DataFileOptions {
+    /// CSV field separator; "," when not given, "\t" when the format is "tsv".
+    delimiter: Option<String>,
+    /// Variables (name → value) that every row starts from; columns override them.
+    variables: HashMap<String, String>,
+}
+
+/// Parse CSV text into records: fields may be quoted with `"` (doubled inside to
+/// escape it) and then contain the delimiter or line breaks. Blank lines are
+/// skipped; a leading byte order mark is ignored.
+pub(crate) fn parse_csv(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut was_quoted = false;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !was_quoted => {
+                quoted = true;
+                was_quoted = true;
+            }
+            '"' => return Err(format!("line {}: stray quote inside a field", line)),
+            c if c == delimiter => {
+                record.push(std::mem::take(&mut field));
+                was_quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                end_record(&mut records, &mut record, &mut field, was_quoted);
+                was_quoted = false;
+            }
+            _ if was_quoted => {
+                return Err(format!("line {}: text after a closing quote", line));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("line {}: unterminated quoted field", line));
+    }
+    end_record(&mut records, &mut record, &mut field, was_quoted);
+    Ok(records)
+}
+
+fn end_record(
+    records: &mut Vec<Vec<String>>,
+    record: &mut Vec<String>,
+    field: &mut String,
+    was_quoted: bool,
+) {
+    if record.is_empty() && field.trim().is_empty() && !was_quoted {
+        field.clear();
+        return;
+    }
+    record.push(std::mem::take(field));
+    records.push(std::mem::take(record));
+}
+
+/// One data row as variables, or why it could not be read.
+type Row = Result<Map<String, Value>, String>;
+
+fn csv_rows(text: &str, delimiter: char) -> Result<(Vec<String>, Vec<Row>), String> {
+    let mut records = parse_csv(text, delimiter)?.into_iter();
+    let columns: Vec<String> = match records.next() {
+        Some(header) => header.iter().map(|h| h.trim().to_string()).collect(),
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let rows = records
+        .map(|record| {
+            if record.len() != columns.len() {
+                return Err(format!(
+                    "has {} fields, expected {}",
+                    record.len(),
+                    columns.len()
+                ));
+            }
+            Ok(columns
+                .iter()
+                .zip(record)
+                .filter(|(column, _)| !column.is_empty())
+                .map(|(column, value)| (column.clone(), Value::String(value)))
+                .collect())
+        })
+        .collect();
+    Ok((columns, rows))
+}
+
+fn json_rows(text: &str) -> Result<(Vec<String>, Vec<Row>), String> {
+    let items = match serde_json::from_str(text) {
+        Ok(Value::Array(items)) => items,
+        Ok(_) => return Err("JSON data must be an array of objects".to_string()),
+        Err(e) => return Err(format!("Invalid JSON data: {}", e)),
+    };
+    let mut columns: Vec<String> = Vec::new();
+    let rows = items
+        .into_iter()
+        .map(|item| match item {
+            Value::Object(map) => {
+                for key in map.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+                // Variables are text: numbers and booleans as written, objects as JSON.
+                Ok(map
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let text = match v {
+                            Value::String(s) => s,
+                            Value::Null => String::new(),
+                            other => other.to_string(),
+                        };
+                        (k, Value::String(text))
+                    })
+                    .collect())
+            }
+            _ => Err("is not an object".to_string()),
+        })
+        .collect();
+    Ok((columns, rows))
+}
+
+/// Expand a request into one fully substituted request per row of a data file,
+/// for data-driven runs: each column (CSV) or key (JSON) becomes a variable for
+/// its row, overriding options.variables.
+/// format: "csv", "tsv", "json", or "" to detect (JSON when the data starts with `[`).
+/// options_json: {delimiter, variables: {name: value}}; may be empty.
+/// Returns JSON {columns, rows: [{iteration, data, request, unresolved, errors}],
+/// count, truncated} where iteration counts from 1, unresolved is as in
+/// `substitute_request`, and errors lists problems with the row (wrong number of
+/// fields, unresolved variables, loops), empty when it expanded cleanly; or {error}
+/// when the request or data cannot be read at all.
+#[wasm_bindgen]
+pub fn expand_with_data_file(
+    request_json: &str,
+    data: &str,
+    format: &str,
+    options_json: &str,
+) -> String {
+    let options: DataFileOptions = serde_json::from_str(options_json).unwrap_or_default();
+    if !matches!(serde_json::from_str(request_json), Ok(Value::Object(_))) {
+        return serde_json::json!({ "error": "Request must be a JSON object" }).to_string();
+    }
+    let format = match format.trim().to_lowercase().as_str() {
+        "" if data.trim_start().starts_with('[') => "json".to_string(),
+        "" => "csv".to_string(),
+        other => other.to_string(),
+    };
+    let parsed = match format.as_str() {
+        "json" => json_rows(data),
+        "csv" | "tsv" => {
+            let default = if format == "tsv" { '\t' } else { ',' };
+            let delimiter = match options.delimiter.as_deref() {
+                None | Some("") => default,
+                Some("\\t") => '\t',
+                Some(d) if d.chars().count() == 1 && !matches!(d, "\"" | "\n" | "\r") => {
+                    d.chars().next().unwrap_or(default)
+                }
+                Some(d) => {
+                    return serde_json::json!({ "error": format!("Invalid delimiter \"{}\"", d) })
+                        .to_string();
+                }
+            };
+            csv_rows(data, delimiter)
+        }
+        other => Err(format!("Unknown data format \"{}\"", other)),
+    };
+    let (columns, rows) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => return serde_json::json!({ "error": e }).to_string(),
+    };
+
+    let _timer = crate::logging::timer("expand_with_data_file");
+    let total = rows.len();
+    let mut out = Vec::with_capacity(total.min(MAX_ROWS));
+    for (i, row) in rows.into_iter().take(MAX_ROWS).enumerate() {
+        let mut errors: Vec<String> = Vec::new();
+        let data = row.unwrap_or_else(|e| {
+            errors.push(format!("Row {} {}", i + 1, e));
+            Map::new()
+        });
+        let mut variables: Map<String, Value> = options
+            .variables
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        variables.extend(data.clone());
+        let expanded: Value = serde_json::from_str(&substitute_request(
+            request_json,
+            &Value::Object(variables).to_string(),
+        ))
+        .unwrap_or_default();
+        let unresolved = expanded["unresolved"].clone();
+        for entry in unresolved.as_array().into_iter().flatten() {
+            errors.push(format!(
+                "{{{{{}}}}} is not defined",
+                entry["name"].as_str().unwrap_or_default()
+            ));
+        }
+        for cycle in expanded["cycles"].as_array().into_iter().flatten() {
+            let names: Vec<&str> = cycle
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            errors.push(format!(
+                "variables refer to each other: {}",
+                names.join(" → ")
+            ));
+        }
+        out.push(serde_json::json!({
+            "iteration": i + 1,
+            "data": data,
+            "request": expanded["request"],
+            "unresolved": unresolved,
+            "errors": errors,
+        }));
+    }
+
+    serde_json::json!({
+        "columns": columns,
+        "rows": out,
+        "count": total,
+        "truncated": total > MAX_ROWS,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(data: &str, format: &str, options: &str) -> Value {
+        let request = r#"{"method": "POST", "url": "{{host}}/users/{{id}}",
+            "headers": {"X-Name": "{{name}}"}, "body": "{\"name\": \"{{name}}\"}"}"#;
+        serde_json::from_str(&expand_with_data_file(request, data, format, options)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let records = parse_csv(
+            "\u{feff}id,name,note\r\n1,\"Ann, Jr.\",\"said \"\"hi\"\"\"\n\n2,Bo,\"two\nlines\"\n",
+            ',',
+        )
+        .unwrap();
+        assert_eq!(
+            records,
+            [
+                vec!["id", "name", "note"],
+                vec!["1", "Ann, Jr.", "said \"hi\""],
+                vec!["2", "Bo", "two\nlines"],
+            ]
+        );
+        assert_eq!(parse_csv("a;\"\"\n", ';').unwrap(), [vec!["a", ""]]);
+        assert!(
+            parse_csv("a,\"b", ',')
+                .unwrap_err()
+                .contains("unterminated")
+        );
+        assert!(parse_csv("a,b\"c\"", ',').is_err());
+    }
+
+    #[test]
+    fn test_expand_csv_rows() {
+        let out = expand(
+            "id;name\n1;Ann\n2\n3;\"Bo; the 2nd\"\n",
+            "csv",
+            r#"{"delimiter": ";", "variables": {"host": "https://api", "name": "default"}}"#,
+        );
+        assert_eq!(out["columns"], serde_json::json!(["id", "name"]));
+        assert_eq!(out["count"], 3);
+        let rows = out["rows"].as_array().unwrap();
+        assert_eq!(rows[0]["request"]["url"], "https://api/users/1");
+        assert_eq!(rows[0]["request"]["headers"]["X-Name"], "Ann");
+        assert_eq!(rows[0]["request"]["body"], "{\"name\": \"Ann\"}");
+        assert_eq!(rows[0]["errors"], serde_json::json!([]));
+        assert_eq!(rows[1]["errors"][0], "Row 2 has 1 fields, expected 2");
+        assert_eq!(rows[1]["errors"][1], "{{id}} is not defined");
+        assert_eq!(rows[1]["request"]["headers"]["X-Name"], "default");
+        assert_eq!(rows[2]["iteration"], 3);
+        assert_eq!(rows[2]["data"]["name"], "Bo; the 2nd");
+    }
+
+    #[test]
+    fn test_expand_json_rows() {
+        let out = expand(
+            r#"[{"id": 7, "name": "Ann", "host": "http://x"}, "oops"]"#,
+            "",
+            "",
+        );
+        let rows = out["rows"].as_array().unwrap();
+        assert_eq!(rows[0]["request"]["url"], "http://x/users/7");
+        assert_eq!(rows[0]["data"]["id"], "7");
+        assert_eq!(rows[1]["errors"][0], "Row 2 is not an object");
+        assert!(expand("[", "json", "")["error"].is_string());
+        assert!(expand("a", "xml", "")["error"].is_string());
+    }
+}
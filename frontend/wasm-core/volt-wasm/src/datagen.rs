@@ -0,0 +1,417 @@
+use serde_json::Value;
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+
+use crate::idempotency::random_bytes;
+use crate::logging;
+use crate::rng::Rng;
+
+thread_local! {
+    /// Seed for generated values; None draws a fresh seed for every substitution.
+    static SEED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+struct FakeKind {
+    name: &'static str,
+    description: &'static str,
+    generate: fn(&mut Rng) -> String,
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Ben", "Carlos", "Chloe", "Diego", "Elena", "Farah", "Grace", "Hiro",
+    "Ines", "Jamal", "Kai", "Lena", "Mateo", "Mei", "Nadia", "Omar", "Priya", "Quinn", "Rosa",
+    "Sven", "Tara", "Uma", "Viktor", "Wen", "Yusuf", "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adams", "Bauer", "Chen", "Dubois", "Evans", "Fischer", "Garcia", "Hansen", "Ito", "Jensen",
+    "Kowalski", "Lopez", "Martin", "Nguyen", "Okafor", "Patel", "Rossi", "Silva", "Tanaka",
+    "Varga", "Walker", "Yamamoto", "Zhang",
+];
+
+const CITIES: &[&str] = &[
+    "Amsterdam",
+    "Austin",
+    "Berlin",
+    "Bogotá",
+    "Cairo",
+    "Cape Town",
+    "Chicago",
+    "Dublin",
+    "Helsinki",
+    "Lagos",
+    "Lima",
+    "Lisbon",
+    "Madrid",
+    "Melbourne",
+    "Montreal",
+    "Mumbai",
+    "Nairobi",
+    "Osaka",
+    "Oslo",
+    "Prague",
+    "Seoul",
+    "Toronto",
+    "Vienna",
+    "Warsaw",
+];
+
+const COUNTRIES: &[&str] = &[
+    "Argentina",
+    "Australia",
+    "Brazil",
+    "Canada",
+    "Egypt",
+    "Finland",
+    "France",
+    "Germany",
+    "India",
+    "Ireland",
+    "Japan",
+    "Kenya",
+    "Mexico",
+    "Netherlands",
+    "Nigeria",
+    "Norway",
+    "Peru",
+    "Poland",
+    "Portugal",
+    "South Korea",
+    "Spain",
+    "United Kingdom",
+    "United States",
+];
+
+const STREETS: &[&str] = &[
+    "Maple", "Oak", "Pine", "Cedar", "Elm", "Station", "Church", "Mill", "Park", "River", "High",
+    "Lake",
+];
+
+const STREET_SUFFIXES: &[&str] = &["Street", "Avenue", "Road", "Lane", "Drive", "Way"];
+
+const COMPANY_WORDS: &[&str] = &[
+    "Acme", "Apex", "Blue", "Bright", "Cloud", "Delta", "Global", "Iron", "Nova", "Orbit",
+    "Summit", "Vertex",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "LLC", "Ltd", "Group", "Labs", "Systems"];
+
+const LOREM: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+    "commodo",
+    "consequat",
+];
+
+/// Card number prefixes (Visa, Mastercard, Amex) with their lengths.
+const CARD_PREFIXES: &[(&str, usize)] = &[("4", 16), ("51", 16), ("55", 16), ("37", 15)];
+
+/// Data that can be generated, available as `generate_fake(name)` and as
+/// `{{$randomName}}` in templates (name with its first letter capitalised).
+/// To add a kind, append an entry here.
+const FAKE_KINDS: &[FakeKind] = &[
+    FakeKind {
+        name: "firstName",
+        description: "First name",
+        generate: |rng| pick(rng, FIRST_NAMES).to_string(),
+    },
+    FakeKind {
+        name: "lastName",
+        description: "Last name",
+        generate: |rng| pick(rng, LAST_NAMES).to_string(),
+    },
+    FakeKind {
+        name: "fullName",
+        description: "First and last name",
+        generate: |rng| format!("{} {}", pick(rng, FIRST_NAMES), pick(rng, LAST_NAMES)),
+    },
+    FakeKind {
+        name: "userName",
+        description: "Lowercase user name with digits",
+        generate: |rng| {
+            let first = pick(rng, FIRST_NAMES).to_lowercase();
+            let last = pick(rng, LAST_NAMES).to_lowercase();
+            format!("{}.{}{}", first, last, rng.range(1, 99))
+        },
+    },
+    FakeKind {
+        name: "city",
+        description: "City name",
+        generate: |rng| pick(rng, CITIES).to_string(),
+    },
+    FakeKind {
+        name: "country",
+        description: "Country name",
+        generate: |rng| pick(rng, COUNTRIES).to_string(),
+    },
+    FakeKind {
+        name: "streetAddress",
+        description: "House number and street",
+        generate: |rng| {
+            format!(
+                "{} {} {}",
+                rng.range(1, 9999),
+                pick(rng, STREETS),
+                pick(rng, STREET_SUFFIXES)
+            )
+        },
+    },
+    FakeKind {
+        name: "zipCode",
+        description: "Five-digit postal code",
+        generate: |rng| format!("{:05}", rng.range(501, 99_950)),
+    },
+    FakeKind {
+        name: "phoneNumber",
+        description: "Phone number in 555-xxx-xxxx form",
+        generate: |rng| format!("555-{:03}-{:04}", rng.range(100, 999), rng.range(0, 9999)),
+    },
+    FakeKind {
+        name: "companyName",
+        description: "Company name",
+        generate: |rng| {
+            format!(
+                "{} {}",
+                pick(rng, COMPANY_WORDS),
+                pick(rng, COMPANY_SUFFIXES)
+            )
+        },
+    },
+    FakeKind {
+        name: "creditCard",
+        description: "Card number that passes the Luhn check (not a real card)",
+        generate: credit_card,
+    },
+    FakeKind {
+        name: "ipv4",
+        description: "IPv4 address",
+        generate: |rng| {
+            let octets: Vec<String> = (0..4).map(|_| rng.range(1, 254).to_string()).collect();
+            octets.join(".")
+        },
+    },
+    FakeKind {
+        name: "hexColor",
+        description: "Color as #rrggbb",
+        generate: |rng| format!("#{:06x}", rng.range(0, 0xFF_FFFF)),
+    },
+    FakeKind {
+        name: "price",
+        description: "Amount between 1.00 and 1000.00",
+        generate: |rng| format!("{:.2}", rng.range(100, 100_000) as f64 / 100.0),
+    },
+    FakeKind {
+        name: "loremWord",
+        description: "Lorem ipsum word",
+        generate: |rng| pick(rng, LOREM).to_string(),
+    },
+    FakeKind {
+        name: "loremSentence",
+        description: "Lorem ipsum sentence",
+        generate: sentence,
+    },
+    FakeKind {
+        name: "loremParagraph",
+        description: "Three to six lorem ipsum sentences",
+        generate: |rng| {
+            let count = rng.range(3, 6);
+            let sentences: Vec<String> = (0..count).map(|_| sentence(rng)).collect();
+            sentences.join(" ")
+        },
+    },
+];
+
+fn pick<'a>(rng: &mut Rng, list: &[&'a str]) -> &'a str {
+    list[rng.range(0, list.len() as i64 - 1) as usize]
+}
+
+fn sentence(rng: &mut Rng) -> String {
+    let count = rng.range(5, 12);
+    let words: Vec<&str> = (0..count).map(|_| pick(rng, LOREM)).collect();
+    let text = words.join(" ");
+    let mut chars = text.chars();
+    let first = chars
+        .next()
+        .map(|c| c.to_ascii_uppercase())
+        .unwrap_or_default();
+    format!("{}{}.", first, chars.as_str())
+}
+
+fn credit_card(rng: &mut Rng) -> String {
+    let (prefix, len) = CARD_PREFIXES[rng.range(0, CARD_PREFIXES.len() as i64 - 1) as usize];
+    let mut digits: Vec<u32> = prefix.chars().filter_map(|c| c.to_digit(10)).collect();
+    while digits.len() < len - 1 {
+        digits.push(rng.range(0, 9) as u32);
+    }
+    // Luhn: double every second digit from the right, counting the check digit.
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            0 if d * 2 > 9 => d * 2 - 9,
+            0 => d * 2,
+            _ => d,
+        })
+        .sum();
+    digits.push((10 - sum % 10) % 10);
+    digits
+        .iter()
+        .map(|d| char::from_digit(*d, 10).unwrap())
+        .collect()
+}
+
+/// Generate a value of the named kind, e.g. "firstName". None for an unknown kind.
+pub(crate) fn fake(kind: &str, rng: &mut Rng) -> Option<String> {
+    let kind = FAKE_KINDS.iter().find(|k| k.name == kind)?;
+    Some((kind.generate)(rng))
+}
+
+/// The kind behind a `$randomFirstName` template name.
+pub(crate) fn template_kind(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("random")?;
+    let mut chars = rest.chars();
+    let first = chars.next()?;
+    first
+        .is_ascii_uppercase()
+        .then(|| first.to_ascii_lowercase().to_string() + chars.as_str())
+}
+
+/// (template name, description) of every kind, for `list_dynamic_variables`.
+pub(crate) fn template_names() -> impl Iterator<Item = (String, &'static str)> {
+    FAKE_KINDS.iter().map(|k| {
+        let mut chars = k.name.chars();
+        let first = chars.next().unwrap().to_ascii_uppercase();
+        (format!("random{}{}", first, chars.as_str()), k.description)
+    })
+}
+
+/// A generator for one substitution or call: from the seed set with
+/// `set_random_seed`, so runs repeat exactly, or from a fresh random seed.
+pub(crate) fn seeded_rng() -> Rng {
+    let seed = SEED
+        .with(Cell::get)
+        .unwrap_or_else(|| u64::from_le_bytes(random_bytes()[..8].try_into().unwrap()));
+    Rng::new(seed)
+}
+
+/// Make generated values reproducible: with a seed, every substitution (and
+/// `generate_fake` call) starts from the same sequence, so a run produces the
+/// same `{{$randomInt}}`, `{{$randomFirstName}}` etc. each time. UUIDs stay
+/// random. Pass null/undefined to go back to fresh values.
+#[wasm_bindgen]
+pub fn set_random_seed(seed: Option<f64>) {
+    SEED.with(|s| s.set(seed.map(|n| n as u64)));
+}
+
+/// Generate fake data of the given kind, e.g. "firstName", "city",
+/// "creditCard" or "loremSentence" (see `list_fake_kinds`).
+/// seed: optional; the same seed always gives the same value. Without it the
+/// seed from `set_random_seed` applies, if any.
+/// Returns the value, or an empty string for an unknown kind.
+#[wasm_bindgen]
+pub fn generate_fake(kind: &str, seed: Option<f64>) -> String {
+    let mut rng = seed.map_or_else(seeded_rng, |n| Rng::new(n as u64));
+    fake(kind.trim(), &mut rng).unwrap_or_else(|| {
+        logging::warn("generate_fake", || format!("unknown kind \"{}\"", kind));
+        String::new()
+    })
+}
+
+/// List the kinds accepted by `generate_fake`.
+/// Returns JSON array of {name, template, description}, where template is the
+/// `{{$random...}}` placeholder producing the same kind.
+#[wasm_bindgen]
+pub fn list_fake_kinds() -> String {
+    let list: Vec<Value> = FAKE_KINDS
+        .iter()
+        .zip(template_names())
+        .map(|(k, (template, _))| {
+            serde_json::json!({
+                "name": k.name,
+                "template": format!("{{{{${}}}}}", template),
+                "description": k.description,
+            })
+        })
+        .collect();
+    Value::from(list).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn luhn_valid(number: &str) -> bool {
+        let sum: u32 = number
+            .chars()
+            .rev()
+            .filter_map(|c| c.to_digit(10))
+            .enumerate()
+            .map(|(i, d)| {
+                if i % 2 == 1 {
+                    if d * 2 > 9 { d * 2 - 9 } else { d * 2 }
+                } else {
+                    d
+                }
+            })
+            .sum();
+        sum.is_multiple_of(10)
+    }
+
+    #[test]
+    fn test_generate_fake() {
+        assert_eq!(
+            generate_fake("city", Some(7.0)),
+            generate_fake("city", Some(7.0))
+        );
+        assert!(CITIES.contains(&generate_fake("city", Some(7.0)).as_str()));
+        assert_eq!(generate_fake("nope", Some(1.0)), "");
+        for seed in 0..50 {
+            let card = generate_fake("creditCard", Some(seed as f64));
+            assert!(card.len() == 15 || card.len() == 16, "{}", card);
+            assert!(luhn_valid(&card), "{}", card);
+        }
+        let sentence = generate_fake("loremSentence", Some(3.0));
+        assert!(sentence.ends_with('.') && sentence.starts_with(char::is_uppercase));
+    }
+
+    #[test]
+    fn test_template_names() {
+        assert_eq!(
+            template_kind("randomFirstName").as_deref(),
+            Some("firstName")
+        );
+        assert_eq!(template_kind("randomness"), None);
+        assert!(template_names().any(|(name, _)| name == "randomCreditCard"));
+        let listed: Value = serde_json::from_str(&list_fake_kinds()).unwrap();
+        assert_eq!(listed[0]["template"], "{{$randomFirstName}}");
+    }
+}
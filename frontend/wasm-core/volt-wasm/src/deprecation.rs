@@ -0,0 +1,186 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::model::Headers;
+use crate::{now_ms, parse_http_date};
+
+const DAY_MS: f64 = 86_400_000.0;
+
+/// Link relations that point at deprecation notes or a replacement.
+const LINK_RELS: &[&str] = &[
+    "deprecation",
+    "sunset",
+    "successor-version",
+    "latest-version",
+];
+
+/// Read the deprecation state of an endpoint from its response headers.
+/// headers_json: JSON object of response headers (key → value or [values]).
+/// `Deprecation` may be an RFC 9745 date (`@1688169599`), an HTTP-date or a boolean
+/// (`true`, `?1`) as sent under earlier drafts; `Sunset` (RFC 8594) is an HTTP-date.
+/// Returns JSON {deprecated, deprecatedAt, sunsetAt, daysRemaining, expired,
+/// links: [{rel, url, type, title}]} with dates in ms since the epoch.
+/// deprecated is false for a `Deprecation` date still in the future;
+/// daysRemaining counts whole days until the sunset (negative once it passed).
+#[wasm_bindgen]
+pub fn parse_deprecation(headers_json: &str) -> String {
+    let headers: Headers = serde_json::from_str(headers_json).unwrap_or_default();
+    deprecation(&headers, now_ms()).to_string()
+}
+
+fn deprecation(headers: &Headers, now: f64) -> Value {
+    let header = |name: &str| headers.get(name).map(str::trim);
+
+    let (mut deprecated, deprecated_at) = match header("deprecation") {
+        None => (false, None),
+        Some(v) if v.eq_ignore_ascii_case("false") || v == "?0" => (false, None),
+        Some(v) => {
+            let at = match v.strip_prefix('@') {
+                Some(seconds) => seconds.parse::<f64>().ok().map(|s| s * 1000.0),
+                None => parse_http_date(v),
+            };
+            (at.is_none_or(|at| at <= now), at)
+        }
+    };
+    let sunset_at = header("sunset").and_then(parse_http_date);
+
+    let links: Vec<Value> = headers
+        .combined("link")
+        .map(|link| parse_links(&link))
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|(url, params)| {
+            let rels = params.get("rel").cloned().unwrap_or_default();
+            rels.split_whitespace()
+                .map(str::to_lowercase)
+                .filter(|rel| LINK_RELS.contains(&rel.as_str()))
+                .map(|rel| {
+                    serde_json::json!({
+                        "rel": rel,
+                        "url": url,
+                        "type": params.get("type"),
+                        "title": params.get("title"),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    // A deprecation link without the header still announces a deprecation.
+    deprecated |= deprecated_at.is_none() && links.iter().any(|l| l["rel"] == "deprecation");
+
+    serde_json::json!({
+        "deprecated": deprecated,
+        "deprecatedAt": deprecated_at,
+        "sunsetAt": sunset_at,
+        "daysRemaining": sunset_at.map(|at| ((at - now) / DAY_MS).floor()),
+        "expired": sunset_at.is_some_and(|at| at <= now),
+        "links": links,
+    })
+}
+
+/// `<url>; rel="a b"; type="text/html", <url2>; rel=c` → [(url, {param: value})].
+fn parse_links(value: &str) -> Vec<(String, IndexMap<String, String>)> {
+    let mut out = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let url = rest[start + 1..start + end].trim().to_string();
+        rest = &rest[start + end + 1..];
+        // Parameters run up to the next link; quoted values may contain ',' or '<'.
+        let mut params = IndexMap::new();
+        let mut in_quotes = false;
+        let stop = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                !in_quotes && c == ','
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        for param in split_params(&rest[..stop]) {
+            if let Some((k, v)) = param.split_once('=') {
+                params.insert(
+                    k.trim().to_lowercase(),
+                    v.trim().trim_matches('"').to_string(),
+                );
+            }
+        }
+        out.push((url, params));
+        rest = &rest[stop..];
+    }
+    out
+}
+
+/// Split on ';' outside double quotes.
+fn split_params(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01T00:00:00Z
+    const NOW: f64 = 1_704_067_200_000.0;
+
+    fn parse(headers: &str) -> Value {
+        deprecation(&serde_json::from_str(headers).unwrap(), NOW)
+    }
+
+    #[test]
+    fn test_parse_deprecation_headers() {
+        let out = parse(
+            r#"{
+                "Deprecation": "@1688169599",
+                "Sunset": "Wed, 31 Jan 2024 12:00:00 GMT",
+                "Link": "<https://api.x.io/docs/v1-deprecation>; rel=\"deprecation\"; type=\"text/html\", <https://api.x.io/v2/users>; rel=\"successor-version\", <https://api.x.io/users?page=2>; rel=\"next\""
+            }"#,
+        );
+        assert_eq!(out["deprecated"], true);
+        assert_eq!(out["deprecatedAt"], 1_688_169_599_000.0);
+        assert_eq!(out["daysRemaining"], 30.0);
+        assert_eq!(out["expired"], false);
+        let links = out["links"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0]["url"], "https://api.x.io/docs/v1-deprecation");
+        assert_eq!(links[0]["type"], "text/html");
+        assert_eq!(links[1]["rel"], "successor-version");
+        assert!(links[1]["title"].is_null());
+    }
+
+    #[test]
+    fn test_parse_deprecation_variants() {
+        let legacy = parse(r#"{"deprecation": "true", "sunset": "Sun, 31 Dec 2023 00:00:00 GMT"}"#);
+        assert_eq!(legacy["deprecated"], true);
+        assert!(legacy["deprecatedAt"].is_null());
+        assert_eq!(legacy["daysRemaining"], -1.0);
+        assert_eq!(legacy["expired"], true);
+
+        let future = parse(r#"{"Deprecation": "Mon, 01 Jul 2024 00:00:00 GMT"}"#);
+        assert_eq!(future["deprecated"], false);
+        assert!(future["deprecatedAt"].is_number());
+
+        let none: Value = serde_json::from_str(&parse_deprecation("{}")).unwrap();
+        assert_eq!(none["deprecated"], false);
+        assert!(none["sunsetAt"].is_null());
+        assert_eq!(none["links"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,345 @@
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::model::{Auth, Collection, KeyValue, Request};
+use crate::{Assertion, QueryLanguage, has_variables};
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct DocsOptions {
+    include_toc: bool,
+    include_examples: bool,
+    include_assertions: bool,
+    /// Replace literal credentials with "••••••"; {{variables}} are always shown.
+    mask_secrets: bool,
+}
+
+impl Default for DocsOptions {
+    fn default() -> Self {
+        DocsOptions {
+            include_toc: true,
+            include_examples: true,
+            include_assertions: true,
+            mask_secrets: true,
+        }
+    }
+}
+
+/// Generate Markdown documentation for a collection.
+/// collection_json: {name, description, requests, folders}.
+/// options_json: optional {includeToc, includeExamples, includeAssertions, maskSecrets}.
+/// Returns the Markdown document, or an empty string if the collection is invalid.
+#[wasm_bindgen]
+pub fn generate_docs_markdown(collection_json: &str, options_json: &str) -> String {
+    let collection: Collection = match serde_json::from_str(collection_json) {
+        Ok(c) => c,
+        Err(_) => return String::new(),
+    };
+    let options: DocsOptions = serde_json::from_str(options_json).unwrap_or_default();
+
+    let mut out = String::new();
+    let title = if collection.name.trim().is_empty() {
+        "API Documentation"
+    } else {
+        collection.name.trim()
+    };
+    out.push_str(&format!("# {}\n\n", title));
+    if !collection.description.trim().is_empty() {
+        out.push_str(&format!("{}\n\n", collection.description.trim()));
+    }
+
+    let requests = collection.all_requests();
+    let mut slugs: Vec<String> = Vec::new();
+    let anchors: Vec<String> = requests
+        .iter()
+        .map(|(_, r)| unique_slug(&request_title(r), &mut slugs))
+        .collect();
+
+    if options.include_toc && !requests.is_empty() {
+        out.push_str("## Contents\n\n");
+        for ((folders, request), anchor) in requests.iter().zip(&anchors) {
+            let indent = "  ".repeat(folders.len());
+            out.push_str(&format!(
+                "{}- [{}](#{})\n",
+                indent,
+                request_title(request),
+                anchor
+            ));
+        }
+        out.push('\n');
+    }
+
+    let mut current_folder: Vec<&str> = Vec::new();
+    for (folders, request) in &requests {
+        if *folders != current_folder && !folders.is_empty() {
+            out.push_str(&format!("## {}\n\n", folders.join(" / ")));
+        }
+        current_folder = folders.clone();
+        write_request(&mut out, request, &options);
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+/// A request's name, or "METHOD url" when it has none.
+pub(crate) fn request_title(request: &Request) -> String {
+    if request.name.trim().is_empty() {
+        format!("{} {}", request.method.to_uppercase(), request.url)
+    } else {
+        request.name.trim().to_string()
+    }
+}
+
+/// GitHub-style heading anchor, de-duplicated with numeric suffixes.
+fn unique_slug(title: &str, used: &mut Vec<String>) -> String {
+    let base: String = title
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect();
+    let mut slug = base.clone();
+    let mut n = 1;
+    while used.contains(&slug) {
+        slug = format!("{}-{}", base, n);
+        n += 1;
+    }
+    used.push(slug.clone());
+    slug
+}
+
+fn write_request(out: &mut String, request: &Request, options: &DocsOptions) {
+    let method = if request.method.is_empty() {
+        "GET".to_string()
+    } else {
+        request.method.to_uppercase()
+    };
+    out.push_str(&format!("### {}\n\n", request_title(request)));
+    out.push_str(&format!("`{} {}`\n\n", method, request.url));
+    if !request.description.trim().is_empty() {
+        out.push_str(&format!("{}\n\n", request.description.trim()));
+    }
+
+    let params: Vec<&KeyValue> = request
+        .query_params
+        .iter()
+        .filter(|p| p.enabled && !p.key.trim().is_empty())
+        .collect();
+    if !params.is_empty() {
+        out.push_str("**Query Parameters**\n\n");
+        write_table(out, &params);
+    }
+
+    let headers: Vec<&KeyValue> = request
+        .headers
+        .iter()
+        .filter(|h| h.enabled && !h.key.trim().is_empty())
+        .collect();
+    if !headers.is_empty() {
+        out.push_str("**Headers**\n\n");
+        write_table(out, &headers);
+    }
+
+    if let Some(auth) = &request.auth
+        && let Some(line) = describe_auth(auth, options.mask_secrets)
+    {
+        out.push_str(&format!("**Authentication:** {}\n\n", line));
+    }
+
+    let form: Vec<&KeyValue> = request
+        .form_data
+        .iter()
+        .filter(|f| f.enabled && !f.key.trim().is_empty())
+        .collect();
+    if request.body_type == "form-data" && !form.is_empty() {
+        out.push_str("**Form Data**\n\n");
+        write_table(out, &form);
+    } else if !request.body.trim().is_empty() && request.body_type != "none" {
+        out.push_str("**Request Body**\n\n");
+        write_code_block(out, &request.body);
+    }
+
+    if options.include_examples && !request.examples.is_empty() {
+        out.push_str("**Example Responses**\n\n");
+        for example in &request.examples {
+            let label = if example.name.trim().is_empty() {
+                format!("{}", example.status_code)
+            } else {
+                format!("{} — {}", example.status_code, example.name.trim())
+            };
+            out.push_str(&format!("#### {}\n\n", label));
+            if !example.request_body.trim().is_empty() {
+                out.push_str("Request:\n\n");
+                write_code_block(out, &example.request_body);
+                out.push_str("Response:\n\n");
+            }
+            if !example.body.trim().is_empty() {
+                write_code_block(out, &example.body);
+            }
+        }
+    }
+
+    let assertions: Vec<&Assertion> = request.assertions.iter().filter(|a| a.enabled).collect();
+    if options.include_assertions && !assertions.is_empty() {
+        out.push_str("**Assertions**\n\n");
+        for a in assertions {
+            out.push_str(&format!("- {}\n", describe_assertion(a)));
+        }
+        out.push('\n');
+    }
+}
+
+fn write_table(out: &mut String, rows: &[&KeyValue]) {
+    out.push_str("| Name | Value |\n| --- | --- |\n");
+    for row in rows {
+        out.push_str(&format!(
+            "| {} | {} |\n",
+            escape_cell(&row.key),
+            escape_cell(&row.value)
+        ));
+    }
+    out.push('\n');
+}
+
+fn escape_cell(s: &str) -> String {
+    let s = s
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>");
+    if s.is_empty() { " ".to_string() } else { s }
+}
+
+fn write_code_block(out: &mut String, body: &str) {
+    let (lang, text) = match serde_json::from_str::<Value>(body) {
+        Ok(v) => (
+            "json",
+            serde_json::to_string_pretty(&v).unwrap_or_else(|_| body.to_string()),
+        ),
+        Err(_) => ("", body.trim_end().to_string()),
+    };
+    // Use a fence longer than any backtick run inside the body.
+    let longest = text
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    out.push_str(&format!("{}{}\n{}\n{}\n\n", fence, lang, text, fence));
+}
+
+fn mask(value: &str, mask_secrets: bool) -> String {
+    if !mask_secrets || value.is_empty() || has_variables(value) {
+        value.to_string()
+    } else {
+        "••••••".to_string()
+    }
+}
+
+fn describe_auth(auth: &Auth, mask_secrets: bool) -> Option<String> {
+    let get = |v: &Option<String>| v.clone().unwrap_or_default();
+    match auth.auth_type.as_str() {
+        "basic" => Some(format!(
+            "Basic (username `{}`, password `{}`)",
+            get(&auth.username),
+            mask(&get(&auth.password), mask_secrets)
+        )),
+        "bearer" => Some(format!(
+            "Bearer token `{}`",
+            mask(&get(&auth.token), mask_secrets)
+        )),
+        "apikey" => Some(format!(
+            "API key `{}` = `{}` in {}",
+            get(&auth.api_key_name),
+            mask(&get(&auth.api_key_value), mask_secrets),
+            auth.api_key_location.as_deref().unwrap_or("header")
+        )),
+        _ => None,
+    }
+}
+
+fn describe_assertion(a: &Assertion) -> String {
+    let op = match a.operator.as_str() {
+        "equals" => "equals",
+        "notEquals" => "does not equal",
+        "lessThan" => "is less than",
+        "greaterThan" => "is greater than",
+        "contains" => "contains",
+        "notContains" => "does not contain",
+        "matches" => "matches",
+        "exists" => "exists",
+        "notExists" => "does not exist",
+        other => other,
+    };
+    let expected = if a.operator == "exists" || a.operator == "notExists" {
+        String::new()
+    } else {
+        format!(" `{}`", a.expected)
+    };
+    match a.assertion_type.as_str() {
+        "status" => format!("Status code {}{}", op, expected),
+        "responseTime" => format!("Response time {}{} ms", op, expected),
+        "bodyContains" => format!("Body {}{}", op, expected),
+        "bodyJson" if a.query_language == QueryLanguage::JmesPath => {
+            format!("JMESPath `{}` {}{}", a.property, op, expected)
+        }
+        "bodyJson" => format!("`{}` {}{}", a.property, op, expected),
+        "headerExists" | "headerEquals" => format!("Header `{}` {}{}", a.property, op, expected),
+        "bodySchema" => "Body matches the JSON Schema".to_string(),
+        "noDuplicateKeys" => "Body has no duplicate JSON keys".to_string(),
+        other => format!("{} {}{}", other, op, expected),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"{
+        "name": "Users API",
+        "description": "Manage users.",
+        "requests": [{
+            "name": "List users",
+            "method": "get",
+            "url": "{{baseUrl}}/users",
+            "queryParams": [{"key": "page", "value": "1", "enabled": true}],
+            "headers": {"Accept": "application/json"},
+            "auth": {"type": "bearer", "token": "abc123"},
+            "examples": [{"name": "OK", "statusCode": 200, "body": "{\"users\":[]}"}],
+            "assertions": [{"id": "a1", "type": "status", "property": "", "operator": "equals", "expected": "200", "enabled": true}]
+        }],
+        "folders": [{"name": "Admin", "requests": [{"name": "List users", "method": "DELETE", "url": "/users/1"}]}]
+    }"#;
+
+    #[test]
+    fn test_generate_docs_markdown_sections() {
+        let md = generate_docs_markdown(COLLECTION, "{}");
+        assert!(md.starts_with("# Users API\n\nManage users.\n"));
+        assert!(md.contains("- [List users](#list-users)\n  - [List users](#list-users-1)"));
+        assert!(md.contains("`GET {{baseUrl}}/users`"));
+        assert!(md.contains("| page | 1 |"));
+        assert!(md.contains("| Accept | application/json |"));
+        assert!(md.contains("**Authentication:** Bearer token `••••••`"));
+        assert!(md.contains("#### 200 — OK"));
+        assert!(md.contains("```json\n{\n  \"users\": []\n}\n```"));
+        assert!(md.contains("- Status code equals `200`"));
+        assert!(md.contains("## Admin\n\n### List users\n\n`DELETE /users/1`"));
+    }
+
+    #[test]
+    fn test_generate_docs_markdown_options() {
+        let md = generate_docs_markdown(
+            COLLECTION,
+            r#"{"includeToc":false,"includeExamples":false,"includeAssertions":false,"maskSecrets":false}"#,
+        );
+        assert!(!md.contains("## Contents"));
+        assert!(!md.contains("Example Responses"));
+        assert!(!md.contains("**Assertions**"));
+        assert!(md.contains("Bearer token `abc123`"));
+    }
+
+    #[test]
+    fn test_generate_docs_markdown_invalid() {
+        assert_eq!(generate_docs_markdown("not json", "{}"), "");
+    }
+}
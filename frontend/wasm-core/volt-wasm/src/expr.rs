@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Expressions nested deeper than this (parentheses, unary operators) are rejected.
+const MAX_DEPTH: usize = 64;
+
+/// A value while evaluating. Variable values that read as numbers are numbers,
+/// "true" and "false" are booleans, everything else is a string.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Val {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Null,
+}
+
+impl Val {
+    fn from_variable(text: String) -> Val {
+        match text.trim() {
+            "true" => return Val::Bool(true),
+            "false" => return Val::Bool(false),
+            _ => {}
+        }
+        match text.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() && !text.trim().is_empty() => Val::Num(n),
+            _ => Val::Str(text),
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Val::Num(n) => *n != 0.0 && !n.is_nan(),
+            Val::Str(s) => !s.is_empty(),
+            Val::Bool(b) => *b,
+            Val::Null => false,
+        }
+    }
+
+    fn number(&self) -> Result<f64, String> {
+        match self {
+            Val::Num(n) => Ok(*n),
+            Val::Bool(b) => Ok(*b as u8 as f64),
+            Val::Null => Ok(0.0),
+            Val::Str(s) => s
+                .trim()
+                .parse()
+                .map_err(|_| format!("\"{}\" is not a number", s)),
+        }
+    }
+
+    /// The text substituted for the placeholder. Whole numbers have no decimal point.
+    pub(crate) fn into_text(self) -> String {
+        match self {
+            Val::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => (n as i64).to_string(),
+            Val::Num(n) => n.to_string(),
+            Val::Str(s) => s,
+            Val::Bool(b) => b.to_string(),
+            Val::Null => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+/// Operators, longest first so `<=` is not read as `<`.
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "?", ":", "(", ")",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        if c.is_ascii_digit() || (c == '.' && rest[1..].starts_with(|d: char| d.is_ascii_digit())) {
+            let len = rest
+                .find(|d: char| !(d.is_ascii_digit() || d == '.'))
+                .unwrap_or(rest.len());
+            let n = rest[..len]
+                .parse()
+                .map_err(|_| format!("invalid number \"{}\"", &rest[..len]))?;
+            tokens.push(Token::Num(n));
+            rest = &rest[len..];
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, q)) if q == c => break i + 2,
+                    Some((_, '\\')) => value.extend(chars.next().map(|(_, e)| e)),
+                    Some((_, other)) => value.push(other),
+                    None => return Err("unterminated string".to_string()),
+                }
+            };
+            tokens.push(Token::Str(value));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|d: char| !(d.is_alphanumeric() || d == '_' || d == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            rest = &rest[len..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected \"{}\"", c));
+        }
+    }
+}
+
+/// Whether text reads as a variable name such as `x-api-key` or `user.id`.
+fn is_plain_name(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Syntax tree of an expression.
+#[derive(Debug)]
+enum Node {
+    Lit(Val),
+    Var(String),
+    Not(Box<Node>),
+    Neg(Box<Node>),
+    Binary(&'static str, Box<Node>, Box<Node>),
+    Ternary(Box<Node>, Box<Node>, Box<Node>),
+}
+
+/// Binary operators from loosest to tightest binding; all left-associative.
+const LEVELS: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected \"{}\"", op))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("expression nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    /// ternary := binary ("?" ternary ":" ternary)?
+    fn ternary(&mut self) -> Result<Node, String> {
+        self.enter()?;
+        let condition = self.binary(0)?;
+        let node = if self.peek_op() == Some("?") {
+            self.pos += 1;
+            let then = self.ternary()?;
+            self.expect(":")?;
+            let otherwise = self.ternary()?;
+            Node::Ternary(Box::new(condition), Box::new(then), Box::new(otherwise))
+        } else {
+            condition
+        };
+        self.depth -= 1;
+        Ok(node)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Node, String> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(op) = self.peek_op().filter(|op| LEVELS[level].contains(op)) {
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.peek_op() {
+            Some(op @ ("!" | "-")) => {
+                self.pos += 1;
+                self.enter()?;
+                let operand = Box::new(self.unary()?);
+                self.depth -= 1;
+                Ok(if op == "!" {
+                    Node::Not(operand)
+                } else {
+                    Node::Neg(operand)
+                })
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Num(n)) => Ok(Node::Lit(Val::Num(n))),
+            Some(Token::Str(s)) => Ok(Node::Lit(Val::Str(s))),
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "true" => Node::Lit(Val::Bool(true)),
+                "false" => Node::Lit(Val::Bool(false)),
+                "null" => Node::Lit(Val::Null),
+                _ => Node::Var(name),
+            }),
+            Some(Token::Op("(")) => {
+                let inner = self.ternary()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some(Token::Op(op)) => Err(format!("unexpected \"{}\"", op)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// A parsed expression, ready to evaluate against variables.
+#[derive(Debug)]
+pub(crate) struct Expr(Node);
+
+impl Expr {
+    pub(crate) fn parse(text: &str) -> Result<Expr, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let node = parser.ternary()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Expr(node)),
+            Some(_) => Err("unexpected text after expression".to_string()),
+        }
+    }
+
+    /// Parse text that is an expression rather than a plain name: it must use
+    /// at least one operator. None for a bare name or literal, or bad syntax.
+    /// Names made of letters, digits, `-`, `_` and `.` are always plain names,
+    /// so `x-api-key` is a variable; subtraction needs spaces, as in `a - b`.
+    pub(crate) fn parse_template(text: &str) -> Option<Expr> {
+        if is_plain_name(text.trim()) {
+            return None;
+        }
+        let expr = Expr::parse(text).ok()?;
+        (!matches!(expr.0, Node::Lit(_) | Node::Var(_))).then_some(expr)
+    }
+
+    /// Names of the variables used, in order of first use.
+    pub(crate) fn variables(&self) -> Vec<String> {
+        fn walk(node: &Node, names: &mut Vec<String>) {
+            match node {
+                Node::Lit(_) => {}
+                Node::Var(name) => {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+                Node::Not(operand) | Node::Neg(operand) => walk(operand, names),
+                Node::Binary(_, left, right) => {
+                    walk(left, names);
+                    walk(right, names);
+                }
+                Node::Ternary(condition, then, otherwise) => {
+                    walk(condition, names);
+                    walk(then, names);
+                    walk(otherwise, names);
+                }
+            }
+        }
+        let mut names = Vec::new();
+        walk(&self.0, &mut names);
+        names
+    }
+
+    /// Evaluate, looking variables up by name. Unknown variables are an error.
+    pub(crate) fn eval(
+        &self,
+        lookup: &mut dyn FnMut(&str) -> Option<String>,
+    ) -> Result<Val, String> {
+        eval(&self.0, lookup)
+    }
+}
+
+fn eval(node: &Node, lookup: &mut dyn FnMut(&str) -> Option<String>) -> Result<Val, String> {
+    Ok(match node {
+        Node::Lit(v) => v.clone(),
+        Node::Var(name) => lookup(name)
+            .map(Val::from_variable)
+            .ok_or_else(|| format!("unknown variable \"{}\"", name))?,
+        Node::Not(operand) => Val::Bool(!eval(operand, lookup)?.truthy()),
+        Node::Neg(operand) => Val::Num(-eval(operand, lookup)?.number()?),
+        Node::Ternary(condition, then, otherwise) => {
+            if eval(condition, lookup)?.truthy() {
+                eval(then, lookup)?
+            } else {
+                eval(otherwise, lookup)?
+            }
+        }
+        // Short-circuit, returning the deciding operand as JavaScript does.
+        Node::Binary("&&", left, right) => {
+            let left = eval(left, lookup)?;
+            if left.truthy() {
+                eval(right, lookup)?
+            } else {
+                left
+            }
+        }
+        Node::Binary("||", left, right) => {
+            let left = eval(left, lookup)?;
+            if left.truthy() {
+                left
+            } else {
+                eval(right, lookup)?
+            }
+        }
+        Node::Binary(op, left, right) => {
+            let (left, right) = (eval(left, lookup)?, eval(right, lookup)?);
+            binary(op, left, right)?
+        }
+    })
+}
+
+fn binary(op: &str, left: Val, right: Val) -> Result<Val, String> {
+    let strings = matches!(left, Val::Str(_)) || matches!(right, Val::Str(_));
+    Ok(match op {
+        "+" if strings => Val::Str(left.into_text() + &right.into_text()),
+        "==" => Val::Bool(equal(&left, &right)),
+        "!=" => Val::Bool(!equal(&left, &right)),
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = if strings {
+                left.into_text().partial_cmp(&right.into_text())
+            } else {
+                left.number()?.partial_cmp(&right.number()?)
+            };
+            Val::Bool(ordering.is_some_and(|o| match op {
+                "<" => o.is_lt(),
+                "<=" => o.is_le(),
+                ">" => o.is_gt(),
+                _ => o.is_ge(),
+            }))
+        }
+        _ => {
+            let (a, b) = (left.number()?, right.number()?);
+            if matches!(op, "/" | "%") && b == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            Val::Num(match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" => a / b,
+                _ => a % b,
+            })
+        }
+    })
+}
+
+fn equal(left: &Val, right: &Val) -> bool {
+    match (left, right) {
+        (Val::Num(a), Val::Num(b)) => a == b,
+        (Val::Null, Val::Null) => true,
+        (Val::Null, _) | (_, Val::Null) => false,
+        _ => left.clone().into_text() == right.clone().into_text(),
+    }
+}
+
+/// Evaluate an expression as used in `{{ ... }}` templates, e.g. `userId + 1`,
+/// `price * quantity`, `"Bearer " + token` or `count > 0 ? "some" : "none"`.
+/// Supports numbers, quoted strings, true/false/null, + - * / %, comparisons,
+/// && || !, ?: and parentheses. `+` concatenates when either side is a string;
+/// variable values that read as numbers are numbers, and "true"/"false" booleans.
+/// variables_json: JSON object of name → value (values are not substituted).
+/// Returns JSON {result} or {error}.
+#[wasm_bindgen]
+pub fn evaluate_expression(expression: &str, variables_json: &str) -> String {
+    let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid variables: {}", e) }).to_string();
+        }
+    };
+    let result = Expr::parse(expression)
+        .and_then(|expr| expr.eval(&mut |name| variables.get(name).cloned()));
+    match result {
+        Ok(value) => serde_json::json!({ "result": value.into_text() }).to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str) -> Result<String, String> {
+        let variables: HashMap<&str, &str> = [
+            ("userId", "41"),
+            ("price", "2.5"),
+            ("quantity", "4"),
+            ("name", "Ann"),
+            ("empty", ""),
+            ("user.id", "7"),
+        ]
+        .into_iter()
+        .collect();
+        Expr::parse(text)?
+            .eval(&mut |n| variables.get(n).map(|v| v.to_string()))
+            .map(Val::into_text)
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        assert_eq!(run("userId + 1").unwrap(), "42");
+        assert_eq!(run("price * quantity").unwrap(), "10");
+        assert_eq!(run("1 + 2 * 3").unwrap(), "7");
+        assert_eq!(run("(1 + 2) * 3").unwrap(), "9");
+        assert_eq!(run("10 - 4 - 3").unwrap(), "3");
+        assert_eq!(run("2 * 3 % 4").unwrap(), "2");
+        assert_eq!(run("-2 * -3").unwrap(), "6");
+        assert_eq!(run("7 / 2").unwrap(), "3.5");
+        assert_eq!(run("user.id * 2").unwrap(), "14");
+        assert!(run("1 / 0").unwrap_err().contains("division"));
+        assert!(run("name * 2").is_err());
+    }
+
+    #[test]
+    fn test_strings_and_logic() {
+        assert_eq!(run("\"Hi \" + name").unwrap(), "Hi Ann");
+        assert_eq!(run("'id-' + userId + 1").unwrap(), "id-411");
+        assert_eq!(run("userId + 1 + '!'").unwrap(), "42!");
+        assert_eq!(run("userId > 40 && name == 'Ann'").unwrap(), "true");
+        assert_eq!(run("1 < 2 == 2 < 3").unwrap(), "true");
+        assert_eq!(run("!empty || false").unwrap(), "true");
+        assert_eq!(run("empty || 'fallback'").unwrap(), "fallback");
+        assert_eq!(run("0 && missing").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_ternary() {
+        assert_eq!(run("quantity > 3 ? 'many' : 'few'").unwrap(), "many");
+        assert_eq!(run("1 + 1 == 3 ? 'a' : 2 > 1 ? 'b' : 'c'").unwrap(), "b");
+        assert_eq!(run("true ? 1 : 2 + 10").unwrap(), "1");
+        assert!(run("true ? 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert!(Expr::parse_template("a + b").is_some());
+        assert!(Expr::parse_template("host").is_none());
+        assert!(Expr::parse_template("x-api-key").is_none());
+        assert!(Expr::parse_template("api-version").is_none());
+        assert!(Expr::parse_template("a - b").is_some());
+        assert_eq!(
+            Expr::parse_template("a > 1 ? b : a + c")
+                .unwrap()
+                .variables(),
+            ["a", "b", "c"]
+        );
+        assert!(Expr::parse_template("name:-x").is_none());
+        assert!(Expr::parse_template("name | upper").is_none());
+        assert!(Expr::parse_template("$randomInt 1 2").is_none());
+        assert!(Expr::parse("a +").is_err());
+        assert!(Expr::parse("'open").is_err());
+        assert!(Expr::parse(&"(".repeat(100)).is_err());
+
+        let out: serde_json::Value =
+            serde_json::from_str(&evaluate_expression("a * 2", r#"{"a": "21"}"#)).unwrap();
+        assert_eq!(out["result"], "42");
+        let out: serde_json::Value =
+            serde_json::from_str(&evaluate_expression("b * 2", "{}")).unwrap();
+        assert_eq!(out["error"], "unknown variable \"b\"");
+    }
+}
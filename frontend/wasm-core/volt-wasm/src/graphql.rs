@@ -0,0 +1,118 @@
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::{hex_encode, percent_encode};
+
+/// The Automatic Persisted Queries extension for a query:
+/// {"persistedQuery": {"version": 1, "sha256Hash": "<hex>"}}.
+/// The hash covers the exact query text, so whitespace changes produce a new hash.
+#[wasm_bindgen]
+pub fn apq_hash(query: &str) -> String {
+    apq_extension(query).to_string()
+}
+
+fn apq_extension(query: &str) -> Value {
+    let hash = hex_encode(&Sha256::digest(query.as_bytes()));
+    serde_json::json!({
+        "persistedQuery": { "version": 1, "sha256Hash": hash }
+    })
+}
+
+/// Build both APQ request forms for a query.
+/// variables_json: JSON object (empty or invalid means no variables).
+/// Returns JSON {hash, hashOnly, withQuery, getParams}:
+/// - hashOnly: POST body sent first, without the query text
+/// - withQuery: fallback POST body sent after a PersistedQueryNotFound error
+/// - getParams: query string for the hash-only GET form
+#[wasm_bindgen]
+pub fn build_apq_request(query: &str, variables_json: &str, operation_name: &str) -> String {
+    let extensions = apq_extension(query);
+    let variables: Option<Map<String, Value>> = serde_json::from_str(variables_json)
+        .ok()
+        .filter(|m: &Map<String, Value>| !m.is_empty());
+
+    let mut hash_only = Map::new();
+    if !operation_name.is_empty() {
+        hash_only.insert("operationName".to_string(), Value::from(operation_name));
+    }
+    if let Some(vars) = &variables {
+        hash_only.insert("variables".to_string(), Value::Object(vars.clone()));
+    }
+    hash_only.insert("extensions".to_string(), extensions.clone());
+
+    let mut with_query = Map::new();
+    with_query.insert("query".to_string(), Value::from(query));
+    with_query.extend(hash_only.clone());
+
+    let mut get_params = Vec::new();
+    if !operation_name.is_empty() {
+        get_params.push(format!("operationName={}", percent_encode(operation_name)));
+    }
+    if let Some(vars) = &variables {
+        let vars = Value::Object(vars.clone()).to_string();
+        get_params.push(format!("variables={}", percent_encode(&vars)));
+    }
+    get_params.push(format!(
+        "extensions={}",
+        percent_encode(&extensions.to_string())
+    ));
+
+    serde_json::json!({
+        "hash": extensions["persistedQuery"]["sha256Hash"],
+        "hashOnly": hash_only,
+        "withQuery": with_query,
+        "getParams": get_params.join("&"),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apq_hash() {
+        let ext: Value = serde_json::from_str(&apq_hash("{ hello }")).unwrap();
+        assert_eq!(ext["persistedQuery"]["version"], 1);
+        // sha256("{ hello }")
+        assert_eq!(
+            ext["persistedQuery"]["sha256Hash"],
+            "001c3174e099bd72b729d0c0a529ba9f5a740c446e2a6e1d71b283cb84ec3065"
+        );
+    }
+
+    #[test]
+    fn test_build_apq_request() {
+        let req: Value = serde_json::from_str(&build_apq_request(
+            "query Q($id: ID!) { user(id: $id) { name } }",
+            r#"{"id":"1"}"#,
+            "Q",
+        ))
+        .unwrap();
+        assert!(req["hashOnly"].get("query").is_none());
+        assert_eq!(req["hashOnly"]["variables"]["id"], "1");
+        assert_eq!(req["hashOnly"]["operationName"], "Q");
+        assert_eq!(
+            req["withQuery"]["extensions"]["persistedQuery"]["sha256Hash"],
+            req["hash"]
+        );
+        assert!(
+            req["withQuery"]["query"]
+                .as_str()
+                .unwrap()
+                .starts_with("query Q")
+        );
+        let get = req["getParams"].as_str().unwrap();
+        assert!(get.starts_with("operationName=Q&variables=%7B%22id%22%3A%221%22%7D&extensions="));
+
+        let bare: Value = serde_json::from_str(&build_apq_request("{ a }", "", "")).unwrap();
+        assert!(bare["hashOnly"].get("variables").is_none());
+        assert!(
+            bare["getParams"]
+                .as_str()
+                .unwrap()
+                .starts_with("extensions=")
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+/// Message types of the graphql-transport-ws protocol (the subprotocol named
+/// in Sec-WebSocket-Protocol; the older subscriptions-transport-ws is not supported).
+const MESSAGE_TYPES: [&str; 8] = [
+    "connection_init",
+    "connection_ack",
+    "ping",
+    "pong",
+    "subscribe",
+    "next",
+    "error",
+    "complete",
+];
+
+/// Payloads kept per subscription for assertions; older ones are dropped.
+const MAX_KEPT_PAYLOADS: usize = 500;
+
+/// Parse one graphql-transport-ws message (a WebSocket text message).
+/// Checks what the protocol requires of each type: an id on subscribe, next,
+/// error and complete, a query in subscribe payloads, and an array of
+/// GraphQL errors in error payloads.
+/// Returns JSON {type, id, payload} or {error}.
+#[wasm_bindgen]
+pub fn graphql_ws_parse(text: &str) -> String {
+    match parse(text) {
+        Ok(v) => v.to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+fn parse(text: &str) -> Result<Value, String> {
+    let message: Map<String, Value> =
+        serde_json::from_str(text).map_err(|e| format!("Message is not a JSON object: {}", e))?;
+    let kind = message
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or("Message has no type")?;
+    if !MESSAGE_TYPES.contains(&kind) {
+        return Err(format!("Unknown message type \"{}\"", kind));
+    }
+    let id = message.get("id").cloned().unwrap_or(Value::Null);
+    let payload = message.get("payload").cloned().unwrap_or(Value::Null);
+    if matches!(kind, "subscribe" | "next" | "error" | "complete")
+        && id.as_str().is_none_or(str::is_empty)
+    {
+        return Err(format!("\"{}\" message has no id", kind));
+    }
+    match kind {
+        "subscribe" if !payload["query"].is_string() => {
+            return Err("Subscribe payload has no query".to_string());
+        }
+        "next" if !payload.is_object() => {
+            return Err("Next payload is not an execution result".to_string());
+        }
+        "error" if payload.as_array().is_none_or(Vec::is_empty) => {
+            return Err("Error payload is not a list of GraphQL errors".to_string());
+        }
+        _ => {}
+    }
+    Ok(serde_json::json!({ "type": kind, "id": id, "payload": payload }))
+}
+
+/// Build a graphql-transport-ws message.
+/// message_json: {type, id, payload}; a subscribe may give {query, variables,
+/// operationName, extensions} in place of payload.
+/// Returns the message text, or an empty string if it is not a valid message.
+#[wasm_bindgen]
+pub fn graphql_ws_build(message_json: &str) -> String {
+    let mut message: Map<String, Value> = match serde_json::from_str(message_json) {
+        Ok(m) => m,
+        Err(_) => return String::new(),
+    };
+    if message.get("type").and_then(Value::as_str) == Some("subscribe")
+        && !message.contains_key("payload")
+    {
+        let payload: Map<String, Value> = ["query", "variables", "operationName", "extensions"]
+            .iter()
+            .filter_map(|k| message.remove(*k).map(|v| (k.to_string(), v)))
+            .collect();
+        message.insert("payload".to_string(), Value::Object(payload));
+    }
+    if message.get("payload").is_some_and(Value::is_null) {
+        message.remove("payload");
+    }
+    let text = Value::Object(message).to_string();
+    if parse(&text).is_err() {
+        return String::new();
+    }
+    text
+}
+
+struct Subscription {
+    operation_name: Option<String>,
+    status: &'static str,
+    received: u64,
+    payloads: Vec<Value>,
+    errors: Vec<Value>,
+}
+
+/// Client side of a graphql-transport-ws connection: allocates subscription
+/// ids, builds outgoing messages and routes incoming ones to their
+/// subscription so each one's results can be shown and asserted on separately.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct GraphqlWsSession {
+    acknowledged: bool,
+    next_id: u64,
+    subscriptions: IndexMap<String, Subscription>,
+}
+
+#[wasm_bindgen]
+impl GraphqlWsSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GraphqlWsSession {
+        GraphqlWsSession::default()
+    }
+
+    /// The connection_init message. payload_json: connection params such as
+    /// auth headers (empty or invalid means none).
+    pub fn init(&mut self, payload_json: &str) -> String {
+        let mut message = serde_json::json!({ "type": "connection_init" });
+        if let Ok(payload @ Value::Object(_)) = serde_json::from_str(payload_json) {
+            message["payload"] = payload;
+        }
+        message.to_string()
+    }
+
+    /// Start a subscription. variables_json: JSON object (empty or invalid
+    /// means no variables).
+    /// Returns JSON {id, message} where message is the subscribe text to send.
+    pub fn subscribe(&mut self, query: &str, variables_json: &str, operation_name: &str) -> String {
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+        let mut payload = serde_json::json!({ "query": query });
+        if let Ok(variables @ Value::Object(_)) = serde_json::from_str(variables_json) {
+            payload["variables"] = variables;
+        }
+        if !operation_name.is_empty() {
+            payload["operationName"] = Value::from(operation_name);
+        }
+        self.subscriptions.insert(
+            id.clone(),
+            Subscription {
+                operation_name: (!operation_name.is_empty()).then(|| operation_name.to_string()),
+                status: "pending",
+                received: 0,
+                payloads: Vec::new(),
+                errors: Vec::new(),
+            },
+        );
+        let message = serde_json::json!({ "id": id, "type": "subscribe", "payload": payload });
+        serde_json::json!({ "id": id, "message": message.to_string() }).to_string()
+    }
+
+    /// Stop a subscription. Returns the complete message to send, or an empty
+    /// string if the id is unknown or the subscription already ended.
+    pub fn complete(&mut self, id: &str) -> String {
+        match self.subscriptions.get_mut(id) {
+            Some(sub) if matches!(sub.status, "pending" | "active") => {
+                sub.status = "cancelled";
+                serde_json::json!({ "id": id, "type": "complete" }).to_string()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Route a received message. Returns JSON {type, id, payload, routed, reply}
+    /// where routed is false for results of unknown or finished subscriptions
+    /// and reply is the message to send back (a pong for a ping), or {error}.
+    pub fn receive(&mut self, text: &str) -> String {
+        let message = match parse(text) {
+            Ok(m) => m,
+            Err(e) => return serde_json::json!({ "error": e }).to_string(),
+        };
+        let kind = message["type"].as_str().unwrap_or_default();
+        let id = message["id"].as_str().unwrap_or_default();
+        let mut reply = Value::Null;
+        let mut routed = true;
+        match kind {
+            "connection_ack" => self.acknowledged = true,
+            "ping" => reply = Value::from(r#"{"type":"pong"}"#),
+            "next" | "error" | "complete" => match self.subscriptions.get_mut(id) {
+                Some(sub) if matches!(sub.status, "pending" | "active") => {
+                    sub.received += 1;
+                    match kind {
+                        "next" => {
+                            sub.status = "active";
+                            if let Some(errors) = message["payload"]["errors"].as_array() {
+                                sub.errors.extend(errors.iter().cloned());
+                            }
+                            if sub.payloads.len() == MAX_KEPT_PAYLOADS {
+                                sub.payloads.remove(0);
+                            }
+                            sub.payloads.push(message["payload"].clone());
+                        }
+                        "error" => {
+                            sub.status = "error";
+                            sub.errors.extend(
+                                message["payload"].as_array().into_iter().flatten().cloned(),
+                            );
+                        }
+                        _ => sub.status = "complete",
+                    }
+                }
+                _ => routed = false,
+            },
+            _ => {}
+        }
+        let mut out = message;
+        out["routed"] = Value::Bool(routed);
+        out["reply"] = reply;
+        out.to_string()
+    }
+
+    /// Whether the server has sent connection_ack.
+    pub fn acknowledged(&self) -> bool {
+        self.acknowledged
+    }
+
+    /// State of every subscription as JSON [{id, operationName, status,
+    /// received, errors}]; status is "pending", "active", "complete", "error"
+    /// or "cancelled".
+    pub fn subscriptions(&self) -> String {
+        let list: Vec<Value> = self
+            .subscriptions
+            .iter()
+            .map(|(id, sub)| {
+                serde_json::json!({
+                    "id": id,
+                    "operationName": sub.operation_name,
+                    "status": sub.status,
+                    "received": sub.received,
+                    "errors": sub.errors,
+                })
+            })
+            .collect();
+        Value::Array(list).to_string()
+    }
+
+    /// Execution results received for a subscription, oldest first (the last
+    /// 500 are kept). Returns a JSON array, empty for an unknown id.
+    pub fn payloads(&self, id: &str) -> String {
+        self.subscriptions
+            .get(id)
+            .map(|sub| Value::from(sub.payloads.clone()).to_string())
+            .unwrap_or_else(|| "[]".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(s: &str) -> Value {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_graphql_ws_parse_and_build() {
+        let next = json(&graphql_ws_parse(
+            r#"{"id":"1","type":"next","payload":{"data":{"n":1}}}"#,
+        ));
+        assert_eq!(next["type"], "next");
+        assert_eq!(next["payload"]["data"]["n"], 1);
+        assert_eq!(
+            json(&graphql_ws_parse(r#"{"type":"ping"}"#))["type"],
+            "ping"
+        );
+        for bad in [
+            "nope",
+            r#"{"type":"start","id":"1"}"#,
+            r#"{"type":"next","payload":{}}"#,
+            r#"{"type":"error","id":"1","payload":{"message":"x"}}"#,
+            r#"{"type":"subscribe","id":"1","payload":{}}"#,
+        ] {
+            assert!(json(&graphql_ws_parse(bad))["error"].is_string(), "{}", bad);
+        }
+
+        let subscribe = graphql_ws_build(
+            r#"{"type":"subscribe","id":"a","query":"subscription { n }","variables":{"x":1}}"#,
+        );
+        assert_eq!(
+            json(&subscribe),
+            json(
+                r#"{"type":"subscribe","id":"a","payload":{"query":"subscription { n }","variables":{"x":1}}}"#
+            )
+        );
+        assert_eq!(
+            graphql_ws_build(r#"{"type":"connection_init","payload":null}"#),
+            r#"{"type":"connection_init"}"#
+        );
+        assert_eq!(graphql_ws_build(r#"{"type":"complete"}"#), "");
+    }
+
+    #[test]
+    fn test_graphql_ws_session_routing() {
+        let mut session = GraphqlWsSession::new();
+        assert_eq!(
+            json(&session.init(r#"{"token":"t"}"#))["payload"]["token"],
+            "t"
+        );
+        session.receive(r#"{"type":"connection_ack"}"#);
+        assert!(session.acknowledged());
+
+        let a = json(&session.subscribe("subscription { a }", "", "A"));
+        let b = json(&session.subscribe("subscription { b }", r#"{"x":1}"#, ""));
+        assert_eq!(a["id"], "1");
+        assert_eq!(
+            json(b["message"].as_str().unwrap())["payload"]["variables"]["x"],
+            1
+        );
+
+        let out = json(&session.receive(r#"{"id":"1","type":"next","payload":{"data":{"a":1}}}"#));
+        assert_eq!(out["routed"], true);
+        session.receive(r#"{"id":"1","type":"next","payload":{"data":{"a":2}}}"#);
+        session.receive(r#"{"id":"2","type":"error","payload":[{"message":"denied"}]}"#);
+        let late = json(&session.receive(r#"{"id":"2","type":"next","payload":{"data":{}}}"#));
+        assert_eq!(late["routed"], false);
+        let ping = json(&session.receive(r#"{"type":"ping"}"#));
+        assert_eq!(ping["reply"], r#"{"type":"pong"}"#);
+
+        assert_eq!(json(&session.payloads("1"))[1]["data"]["a"], 2);
+        let subs = json(&session.subscriptions());
+        assert_eq!(subs[0]["status"], "active");
+        assert_eq!(subs[0]["operationName"], "A");
+        assert_eq!(subs[1]["status"], "error");
+        assert_eq!(subs[1]["errors"][0]["message"], "denied");
+
+        assert_eq!(session.complete("1"), r#"{"id":"1","type":"complete"}"#);
+        assert_eq!(session.complete("1"), "");
+        assert_eq!(json(&session.subscriptions())[0]["status"], "cancelled");
+    }
+}
@@ -0,0 +1,198 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, base64_encode};
+
+/// Frame flag bits (gRPC-Web wire format).
+const FLAG_COMPRESSED: u8 = 0x01;
+const FLAG_TRAILERS: u8 = 0x80;
+
+/// Wrap a serialized protobuf message in a gRPC-Web data frame
+/// (1 flag byte + 4-byte big-endian length + message).
+#[wasm_bindgen]
+pub fn grpc_web_encode(message_bytes: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(message_bytes.len() + 5);
+    frame.push(0);
+    frame.extend_from_slice(&(message_bytes.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message_bytes);
+    frame
+}
+
+/// Same as `grpc_web_encode`, base64-encoded for `application/grpc-web-text`.
+#[wasm_bindgen]
+pub fn grpc_web_encode_text(message_bytes: &[u8]) -> String {
+    base64_encode(&grpc_web_encode(message_bytes))
+}
+
+/// Decode a gRPC-Web response body into its frames.
+/// Returns JSON {messages: [{base64, length, compressed}], trailers: {name: value},
+/// grpcStatus, grpcMessage, error}. `error` is set (and decoding stops) on a truncated frame.
+#[wasm_bindgen]
+pub fn grpc_web_decode(body_bytes: &[u8]) -> String {
+    decode_frames(body_bytes).to_string()
+}
+
+/// Decode an `application/grpc-web-text` body. The server may send several
+/// base64 chunks, each with its own padding, so each chunk is decoded separately.
+/// Returns the same JSON as `grpc_web_decode`, or {error} for invalid base64.
+#[wasm_bindgen]
+pub fn grpc_web_decode_text(body_text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut chunk = String::new();
+    let mut chars = body_text.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(c) = chars.next() {
+        chunk.push(c);
+        // A chunk ends after its padding.
+        if c == '=' && chars.peek() != Some(&'=') {
+            match base64_decode(&chunk) {
+                Some(b) => bytes.extend(b),
+                None => return error_json("Body is not valid base64"),
+            }
+            chunk.clear();
+        }
+    }
+    if !chunk.is_empty() {
+        match base64_decode(&chunk) {
+            Some(b) => bytes.extend(b),
+            None => return error_json("Body is not valid base64"),
+        }
+    }
+    grpc_web_decode(&bytes)
+}
+
+fn decode_frames(body: &[u8]) -> Value {
+    let mut messages = Vec::new();
+    let mut trailers = Map::new();
+    let mut error: Option<String> = None;
+    let mut rest = body;
+
+    while !rest.is_empty() {
+        if rest.len() < 5 {
+            error = Some(format!("Truncated frame header ({} bytes)", rest.len()));
+            break;
+        }
+        let flags = rest[0];
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let Some(payload) = rest.get(5..5 + len) else {
+            error = Some(format!(
+                "Truncated frame: expected {} bytes, got {}",
+                len,
+                rest.len() - 5
+            ));
+            break;
+        };
+        if flags & FLAG_TRAILERS != 0 {
+            parse_trailers(payload, &mut trailers);
+        } else {
+            messages.push(serde_json::json!({
+                "base64": base64_encode(payload),
+                "length": len,
+                "compressed": flags & FLAG_COMPRESSED != 0,
+            }));
+        }
+        rest = &rest[5 + len..];
+    }
+
+    let status = trailers
+        .get("grpc-status")
+        .and_then(Value::as_str)
+        .and_then(|s| s.trim().parse::<i64>().ok());
+    let message = trailers
+        .get("grpc-message")
+        .and_then(Value::as_str)
+        .map(grpc_message_decode);
+
+    serde_json::json!({
+        "messages": messages,
+        "trailers": trailers,
+        "grpcStatus": status,
+        "grpcMessage": message,
+        "error": error,
+    })
+}
+
+/// Trailers are an HTTP/1-style header block: "name: value\r\n" lines.
+fn parse_trailers(payload: &[u8], out: &mut Map<String, Value>) {
+    let text = String::from_utf8_lossy(payload);
+    for line in text.split('\n') {
+        let line = line.trim_end_matches('\r');
+        if let Some((name, value)) = line.split_once(':') {
+            out.insert(name.trim().to_ascii_lowercase(), Value::from(value.trim()));
+        }
+    }
+}
+
+/// grpc-message is percent-encoded UTF-8 (without '+' for space).
+fn grpc_message_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = s
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer_frame(text: &str) -> Vec<u8> {
+        let mut frame = vec![FLAG_TRAILERS];
+        frame.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        frame.extend_from_slice(text.as_bytes());
+        frame
+    }
+
+    #[test]
+    fn test_grpc_web_encode() {
+        assert_eq!(grpc_web_encode(&[8, 1]), vec![0, 0, 0, 0, 2, 8, 1]);
+        assert_eq!(grpc_web_encode_text(&[8, 1]), "AAAAAAIIAQ==");
+    }
+
+    #[test]
+    fn test_grpc_web_decode_messages_and_trailers() {
+        let mut body = grpc_web_encode(&[8, 1]);
+        body.extend(grpc_web_encode(&[]));
+        body.extend(trailer_frame(
+            "grpc-status: 5\r\nGrpc-Message: not%20found%20%E2%9C%93\r\n",
+        ));
+        let out: Value = serde_json::from_str(&grpc_web_decode(&body)).unwrap();
+        assert_eq!(out["messages"].as_array().unwrap().len(), 2);
+        assert_eq!(out["messages"][0]["base64"], "CAE=");
+        assert_eq!(out["grpcStatus"], 5);
+        assert_eq!(out["grpcMessage"], "not found ✓");
+        assert_eq!(out["error"], Value::Null);
+    }
+
+    #[test]
+    fn test_grpc_web_decode_text_chunks_and_truncation() {
+        // Two separately padded chunks, as streamed by grpc-web-text servers.
+        let text = format!(
+            "{}{}",
+            grpc_web_encode_text(&[8, 1]),
+            base64_encode(&trailer_frame("grpc-status:0\r\n"))
+        );
+        let out: Value = serde_json::from_str(&grpc_web_decode_text(&text)).unwrap();
+        assert_eq!(out["messages"][0]["length"], 2);
+        assert_eq!(out["grpcStatus"], 0);
+
+        let truncated: Value = serde_json::from_str(&grpc_web_decode(&[0, 0, 0, 0, 9, 1])).unwrap();
+        assert!(truncated["error"].as_str().unwrap().contains("Truncated"));
+        assert!(grpc_web_decode_text("***").contains("error"));
+    }
+}
@@ -0,0 +1,478 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::bundle::SECRET_HEADERS;
+use crate::progress::Progress;
+use crate::{percent_encode, query_pairs, split_url};
+
+/// Query and form parameters masked by default (compared lower-cased, without '-'/'_').
+const SECRET_PARAMS: &[&str] = &[
+    "token",
+    "accesstoken",
+    "refreshtoken",
+    "idtoken",
+    "apikey",
+    "key",
+    "code",
+    "secret",
+    "clientsecret",
+    "password",
+    "signature",
+    "sig",
+    "auth",
+];
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Rules {
+    /// "mask" replaces values with `mask`; "remove" drops the header, cookie or parameter.
+    mode: String,
+    mask: String,
+    /// Extra header names to treat as secret.
+    headers: Vec<String>,
+    /// Extra query/form parameter names to treat as secret.
+    #[serde(rename = "queryParams")]
+    query_params: Vec<String>,
+    cookies: bool,
+    /// Dot/`[n]` paths, with `*` wildcards, masked inside JSON request and response bodies.
+    #[serde(rename = "bodyPaths")]
+    body_paths: Vec<String>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            mode: "mask".to_string(),
+            mask: "[REDACTED]".to_string(),
+            headers: Vec::new(),
+            query_params: Vec::new(),
+            cookies: true,
+            body_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Redaction {
+    entry: usize,
+    location: &'static str,
+    name: String,
+}
+
+/// Scrub credentials from a HAR file before sharing it.
+/// rules_json: {mode: "mask"|"remove", mask: "[REDACTED]", headers: [name],
+/// queryParams: [name], cookies: true, bodyPaths: ["user.password", "items[*].token"]};
+/// every field is optional. Authorization, cookie and API-key headers and common token
+/// query parameters are always covered; `headers` and `queryParams` add to them.
+/// Returns JSON {har, redactions: [{entry, location, name}]} where location is one of
+/// "requestHeader", "responseHeader", "requestCookie", "responseCookie", "query",
+/// "formParam", "requestBody" or "responseBody"; or {error}.
+/// progress: optional callback invoked with {done, total, stage}.
+#[wasm_bindgen]
+pub fn sanitize_har(
+    har_json: &str,
+    rules_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
+    let mut har: Value = match serde_json::from_str(har_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid HAR: {}", e) }).to_string();
+        }
+    };
+    let rules: Rules = serde_json::from_str(rules_json).unwrap_or_default();
+    let entries = har
+        .get_mut("log")
+        .and_then(|log| log.get_mut("entries"))
+        .and_then(Value::as_array_mut);
+    let Some(entries) = entries else {
+        return serde_json::json!({ "error": "Invalid HAR: missing log.entries" }).to_string();
+    };
+
+    let mut progress = Progress::new(progress, "sanitize", entries.len());
+    let mut sanitizer = Sanitizer {
+        rules: &rules,
+        redactions: Vec::new(),
+        entry: 0,
+    };
+    for (index, entry) in entries.iter_mut().enumerate() {
+        sanitizer.entry = index;
+        sanitizer.sanitize(entry);
+        progress.tick(index + 1);
+    }
+    let redactions = sanitizer.redactions;
+
+    serde_json::to_string(&serde_json::json!({
+        "har": har,
+        "redactions": redactions,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+struct Sanitizer<'a> {
+    rules: &'a Rules,
+    redactions: Vec<Redaction>,
+    entry: usize,
+}
+
+impl Sanitizer<'_> {
+    /// Fields that are missing or of the wrong type are left as they are.
+    fn sanitize(&mut self, entry: &mut Value) {
+        if let Some(request) = entry.get_mut("request") {
+            self.request(request);
+        }
+        if let Some(response) = entry.get_mut("response") {
+            self.response(response);
+        }
+    }
+
+    fn request(&mut self, request: &mut Value) {
+        self.name_values(
+            request.get_mut("headers"),
+            "requestHeader",
+            Self::secret_header,
+        );
+        if self.rules.cookies {
+            self.name_values(request.get_mut("cookies"), "requestCookie", |_, _| true);
+        }
+        let found = self.redactions.len();
+        rewrite(request.get_mut("url"), |url| self.url(url));
+        let url_reported = self.redactions.len();
+        self.name_values(request.get_mut("queryString"), "query", Self::secret_param);
+        if url_reported > found {
+            // queryString mirrors the URL, whose parameters were already reported.
+            self.redactions.truncate(url_reported);
+        }
+        if let Some(post) = request.get_mut("postData") {
+            self.name_values(post.get_mut("params"), "formParam", Self::secret_param);
+            rewrite(post.get_mut("text"), |text| self.body(text, "requestBody"));
+        }
+    }
+
+    fn response(&mut self, response: &mut Value) {
+        self.name_values(
+            response.get_mut("headers"),
+            "responseHeader",
+            Self::secret_header,
+        );
+        if self.rules.cookies {
+            self.name_values(response.get_mut("cookies"), "responseCookie", |_, _| true);
+        }
+        if let Some(content) = response.get_mut("content")
+            && content["encoding"] != "base64"
+        {
+            rewrite(content.get_mut("text"), |text| {
+                self.body(text, "responseBody")
+            });
+        }
+    }
+
+    fn secret_header(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        if !self.rules.cookies && (name == "cookie" || name == "set-cookie") {
+            return false;
+        }
+        SECRET_HEADERS.contains(&name.as_str())
+            || self
+                .rules
+                .headers
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(&name))
+    }
+
+    fn secret_param(&self, name: &str) -> bool {
+        let normalized = normalize(name);
+        SECRET_PARAMS.contains(&normalized.as_str())
+            || self
+                .rules
+                .query_params
+                .iter()
+                .any(|p| normalize(p) == normalized)
+    }
+
+    /// Mask or remove the matching {name, value} items of a HAR list.
+    fn name_values(
+        &mut self,
+        list: Option<&mut Value>,
+        location: &'static str,
+        is_secret: fn(&Self, &str) -> bool,
+    ) {
+        let Some(items) = list.and_then(Value::as_array_mut) else {
+            return;
+        };
+        let remove = self.rules.mode == "remove";
+        let mut index = 0;
+        while index < items.len() {
+            let Some(item) = items[index].as_object_mut() else {
+                index += 1;
+                continue;
+            };
+            let name = item
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if !is_secret(self, &name) {
+                index += 1;
+                continue;
+            }
+            if remove {
+                items.remove(index);
+            } else {
+                item.insert("value".to_string(), Value::from(self.rules.mask.as_str()));
+                index += 1;
+            }
+            self.report(location, name);
+        }
+    }
+
+    /// Rewrite the query string of a URL, masking or dropping secret parameters.
+    fn url(&mut self, url: &str) -> String {
+        let (_, _, query) = split_url(url);
+        let pairs = query_pairs(query);
+        if !pairs.iter().any(|(k, _)| self.secret_param(k)) {
+            return url.to_string();
+        }
+        let fragment = url.find('#').map(|i| &url[i..]).unwrap_or_default();
+        let base = &url[..url.find('?').unwrap_or(url.len())];
+        let mut kept = Vec::new();
+        for (key, value) in pairs {
+            if !self.secret_param(&key) {
+                kept.push(format!(
+                    "{}={}",
+                    percent_encode(&key),
+                    percent_encode(&value)
+                ));
+                continue;
+            }
+            if self.rules.mode != "remove" {
+                kept.push(format!(
+                    "{}={}",
+                    percent_encode(&key),
+                    percent_encode(&self.rules.mask)
+                ));
+            }
+            self.report("query", key);
+        }
+        if kept.is_empty() {
+            format!("{}{}", base, fragment)
+        } else {
+            format!("{}?{}{}", base, kept.join("&"), fragment)
+        }
+    }
+
+    /// Mask the configured paths inside a JSON body; other bodies pass through.
+    fn body(&mut self, text: &str, location: &'static str) -> String {
+        if self.rules.body_paths.is_empty() {
+            return text.to_string();
+        }
+        let Ok(mut json) = serde_json::from_str::<Value>(text) else {
+            return text.to_string();
+        };
+        let mask = Value::from(self.rules.mask.as_str());
+        let mut changed = false;
+        for path in &self.rules.body_paths {
+            let mut hits = 0;
+            mask_path(&mut json, &segments(path), &mask, &mut hits);
+            if hits > 0 {
+                changed = true;
+                self.redactions.push(Redaction {
+                    entry: self.entry,
+                    location,
+                    name: path.clone(),
+                });
+            }
+        }
+        if changed {
+            json.to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn report(&mut self, location: &'static str, name: String) {
+        self.redactions.push(Redaction {
+            entry: self.entry,
+            location,
+            name,
+        });
+    }
+}
+
+/// Replace a string field with `f` of its text; other values are left alone.
+fn rewrite(field: Option<&mut Value>, f: impl FnOnce(&str) -> String) {
+    if let Some(field) = field
+        && let Some(text) = field.as_str()
+    {
+        *field = Value::from(f(text));
+    }
+}
+
+pub(crate) fn normalize(name: &str) -> String {
+    name.to_lowercase().replace(['-', '_'], "")
+}
+
+/// "items[*].token" → ["items", "*", "token"]; a leading "$" is ignored.
+pub(crate) fn segments(path: &str) -> Vec<String> {
+    path.split('.')
+        .flat_map(|part| {
+            part.split('[')
+                .map(|s| s.trim_end_matches(']').to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|s| !s.is_empty())
+        .skip_while(|s| s == "$")
+        .collect()
+}
+
+fn mask_path(value: &mut Value, segments: &[String], mask: &Value, hits: &mut usize) {
+    let Some((first, rest)) = segments.split_first() else {
+        *value = mask.clone();
+        *hits += 1;
+        return;
+    };
+    match value {
+        Value::Object(map) if first == "*" => map
+            .values_mut()
+            .for_each(|v| mask_path(v, rest, mask, hits)),
+        Value::Object(map) => {
+            if let Some(v) = map.get_mut(first) {
+                mask_path(v, rest, mask, hits);
+            }
+        }
+        Value::Array(items) if first == "*" => items
+            .iter_mut()
+            .for_each(|v| mask_path(v, rest, mask, hits)),
+        Value::Array(items) => {
+            if let Some(v) = first.parse::<usize>().ok().and_then(|i| items.get_mut(i)) {
+                mask_path(v, rest, mask, hits);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn har() -> String {
+        serde_json::json!({"log": {"version": "1.2", "entries": [{
+            "request": {
+                "method": "POST",
+                "url": "https://api.x.io/login?page=2&access_token=abc#top",
+                "headers": [
+                    {"name": "Authorization", "value": "Bearer abc"},
+                    {"name": "Accept", "value": "application/json"},
+                    {"name": "X-Tenant", "value": "acme"}
+                ],
+                "queryString": [
+                    {"name": "page", "value": "2"},
+                    {"name": "access_token", "value": "abc"}
+                ],
+                "cookies": [{"name": "sid", "value": "s3cr3t"}],
+                "postData": {
+                    "mimeType": "application/json",
+                    "text": "{\"user\":\"ann\",\"password\":\"hunter2\"}"
+                }
+            },
+            "response": {
+                "status": 200,
+                "headers": [{"name": "Set-Cookie", "value": "sid=new; HttpOnly"}],
+                "cookies": [{"name": "sid", "value": "new"}],
+                "content": {
+                    "mimeType": "application/json",
+                    "text": "{\"items\":[{\"token\":\"t1\"},{\"token\":\"t2\"}]}"
+                }
+            }
+        }]}})
+        .to_string()
+    }
+
+    #[test]
+    fn test_sanitize_har_mask() {
+        let rules = r#"{"headers": ["x-tenant"], "bodyPaths": ["password", "items[*].token"]}"#;
+        let result: Value = serde_json::from_str(&sanitize_har(&har(), rules, None)).unwrap();
+        let entry = &result["har"]["log"]["entries"][0];
+        let request = &entry["request"];
+        assert_eq!(
+            request["url"],
+            "https://api.x.io/login?page=2&access_token=%5BREDACTED%5D#top"
+        );
+        assert_eq!(request["headers"][0]["value"], "[REDACTED]");
+        assert_eq!(request["headers"][1]["value"], "application/json");
+        assert_eq!(request["headers"][2]["value"], "[REDACTED]");
+        assert_eq!(request["queryString"][1]["value"], "[REDACTED]");
+        assert_eq!(request["cookies"][0]["value"], "[REDACTED]");
+        assert_eq!(
+            request["postData"]["text"],
+            r#"{"user":"ann","password":"[REDACTED]"}"#
+        );
+        assert_eq!(
+            entry["response"]["content"]["text"],
+            r#"{"items":[{"token":"[REDACTED]"},{"token":"[REDACTED]"}]}"#
+        );
+        assert_eq!(entry["response"]["headers"][0]["value"], "[REDACTED]");
+        let locations: Vec<&str> = result["redactions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["location"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                "requestHeader",
+                "requestHeader",
+                "requestCookie",
+                "query",
+                "requestBody",
+                "responseHeader",
+                "responseCookie",
+                "responseBody"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_har_remove_and_errors() {
+        let result: Value = serde_json::from_str(&sanitize_har(
+            &har(),
+            r#"{"mode": "remove", "cookies": false}"#,
+            None,
+        ))
+        .unwrap();
+        let request = &result["har"]["log"]["entries"][0]["request"];
+        assert_eq!(request["url"], "https://api.x.io/login?page=2#top");
+        assert_eq!(request["headers"].as_array().unwrap().len(), 2);
+        assert_eq!(request["queryString"].as_array().unwrap().len(), 1);
+        assert_eq!(request["cookies"][0]["value"], "s3cr3t");
+        // Bodies are left alone without bodyPaths.
+        assert!(
+            request["postData"]["text"]
+                .as_str()
+                .unwrap()
+                .contains("hunter2")
+        );
+
+        for invalid in [
+            "{}",
+            "[]",
+            r#""str""#,
+            r#"{"log":5}"#,
+            r#"{"log":{"entries":{}}}"#,
+        ] {
+            assert!(sanitize_har(invalid, "{}", None).contains("missing log.entries"));
+        }
+        // Entries and fields of the wrong shape are passed through untouched.
+        let odd = r#"{"log":{"entries":[1,{"request":"x"},{"request":{"headers":[5,{"name":"Cookie"}],"postData":[]},"response":{"content":7,"cookies":["c"]}}]}}"#;
+        let result: Value = serde_json::from_str(&sanitize_har(odd, "{}", None)).unwrap();
+        let entries = &result["har"]["log"]["entries"];
+        assert_eq!(entries[0], 1);
+        assert_eq!(entries[1]["request"], "x");
+        assert_eq!(entries[2]["request"]["headers"][1]["value"], "[REDACTED]");
+        assert_eq!(entries[2]["response"]["cookies"], serde_json::json!(["c"]));
+        assert!(sanitize_har("nope", "{}", None).contains("Invalid HAR"));
+    }
+}
@@ -0,0 +1,760 @@
+use wasm_bindgen::prelude::*;
+
+use crate::expr::Expr;
+use crate::template::is_filter;
+use crate::variables::{Block, parse_block};
+
+/// Token kinds; the discriminant is what the tokenizers emit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Punctuation = 0,
+    Tag = 1,
+    Attribute = 2,
+    String = 3,
+    Comment = 4,
+    Entity = 5,
+    Meta = 6,
+    Keyword = 7,
+    Variable = 8,
+    Number = 9,
+    Property = 10,
+    Type = 11,
+    Directive = 12,
+    Literal = 13,
+    Text = 14,
+    Filter = 15,
+    Invalid = 16,
+}
+
+const KIND_NAMES: &[&str] = &[
+    "punctuation",
+    "tag",
+    "attribute",
+    "string",
+    "comment",
+    "entity",
+    "meta",
+    "keyword",
+    "variable",
+    "number",
+    "property",
+    "type",
+    "directive",
+    "literal",
+    "text",
+    "filter",
+    "invalid",
+];
+
+/// Names of the token kinds emitted by the tokenizers, indexed by kind.
+/// Returns a JSON array of strings.
+#[wasm_bindgen]
+pub fn highlight_token_kinds() -> String {
+    serde_json::to_string(KIND_NAMES).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Tokenize JSON for highlighting.
+/// Returns a flat array of [start, end, kind] triples; offsets are UTF-16 code
+/// units and kind indexes `highlight_token_kinds()`. Plain text gets no token.
+#[wasm_bindgen]
+pub fn json_tokenize(text: &str) -> Vec<u32> {
+    let mut s = Scanner::new(text);
+    while let Some(c) = s.peek() {
+        let start = s.pos;
+        match c {
+            '"' => {
+                s.string('"', false);
+                // A string followed by ':' is an object key.
+                let mut look = s.pos;
+                while s.chars.get(look).is_some_and(|c| c.is_whitespace()) {
+                    look += 1;
+                }
+                let kind = if s.chars.get(look) == Some(&':') {
+                    Kind::Property
+                } else {
+                    Kind::String
+                };
+                s.emit(start, kind);
+            }
+            '{' | '}' | '[' | ']' | ':' | ',' => {
+                s.pos += 1;
+                s.emit(start, Kind::Punctuation);
+            }
+            '-' | '0'..='9' => {
+                s.take_while(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+                s.emit(start, Kind::Number);
+            }
+            c if c.is_alphabetic() => {
+                s.take_while(char::is_alphanumeric);
+                s.emit(start, Kind::Literal);
+            }
+            _ => s.pos += 1,
+        }
+    }
+    s.finish()
+}
+
+/// Tokenize XML for highlighting: tags, attributes, values, comments, CDATA,
+/// processing instructions and entities.
+/// Returns [start, end, kind] triples like `json_tokenize`.
+#[wasm_bindgen]
+pub fn xml_tokenize(text: &str) -> Vec<u32> {
+    markup(text, false)
+}
+
+/// Tokenize HTML for highlighting. Like `xml_tokenize`, but also accepts
+/// unquoted attribute values and leaves <script>/<style> contents untokenized.
+#[wasm_bindgen]
+pub fn html_tokenize(text: &str) -> Vec<u32> {
+    markup(text, true)
+}
+
+fn markup(text: &str, html: bool) -> Vec<u32> {
+    let mut s = Scanner::new(text);
+    while let Some(c) = s.peek() {
+        let start = s.pos;
+        match c {
+            '<' if s.starts_with("<!--") => {
+                s.until("-->");
+                s.emit(start, Kind::Comment);
+            }
+            '<' if s.starts_with("<![CDATA[") => {
+                s.until("]]>");
+                s.emit(start, Kind::Meta);
+            }
+            '<' if s.starts_with("<?") => {
+                s.until("?>");
+                s.emit(start, Kind::Meta);
+            }
+            '<' if s.starts_with("<!") => {
+                s.until(">");
+                s.emit(start, Kind::Meta);
+            }
+            '<' if s
+                .chars
+                .get(s.pos + 1)
+                .is_some_and(|c| c.is_alphabetic() || *c == '/' || *c == '_') =>
+            {
+                let closing = s.chars[s.pos + 1] == '/';
+                let name = tag(&mut s);
+                if html
+                    && !closing
+                    && (name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style"))
+                    && s.chars.get(s.pos.wrapping_sub(2)) != Some(&'/')
+                {
+                    // Raw text element: skip to the matching close tag.
+                    let close = format!("</{}", name.to_lowercase());
+                    while s.pos < s.chars.len() && !s.starts_with_ignore_case(&close) {
+                        s.pos += 1;
+                    }
+                }
+            }
+            '&' => {
+                let end = s.chars[s.pos..]
+                    .iter()
+                    .take(32)
+                    .position(|c| *c == ';' || c.is_whitespace() || *c == '<');
+                match end {
+                    Some(n) if n > 1 && s.chars[s.pos + n] == ';' => {
+                        s.pos += n + 1;
+                        s.emit(start, Kind::Entity);
+                    }
+                    _ => s.pos += 1,
+                }
+            }
+            _ => s.pos += 1,
+        }
+    }
+
+    fn tag(s: &mut Scanner) -> String {
+        let start = s.pos;
+        s.pos += 1;
+        if s.peek() == Some('/') {
+            s.pos += 1;
+        }
+        s.emit(start, Kind::Punctuation);
+        let name_start = s.pos;
+        s.take_while(|c| !c.is_whitespace() && !"/>".contains(c));
+        s.emit(name_start, Kind::Tag);
+        let name: String = s.chars[name_start..s.pos].iter().collect();
+
+        while let Some(c) = s.peek() {
+            let start = s.pos;
+            match c {
+                '>' => {
+                    s.pos += 1;
+                    s.emit(start, Kind::Punctuation);
+                    break;
+                }
+                '/' if s.starts_with("/>") => {
+                    s.pos += 2;
+                    s.emit(start, Kind::Punctuation);
+                    break;
+                }
+                '=' => {
+                    s.pos += 1;
+                    s.emit(start, Kind::Punctuation);
+                    s.take_while(char::is_whitespace);
+                    let value_start = s.pos;
+                    match s.peek() {
+                        Some(q @ ('"' | '\'')) => s.string(q, true),
+                        _ => s.take_while(|c| !c.is_whitespace() && c != '>'),
+                    }
+                    s.emit(value_start, Kind::String);
+                }
+                '<' => break,
+                c if c.is_whitespace() => s.pos += 1,
+                _ => {
+                    s.take_while(|c| !c.is_whitespace() && !"=/>".contains(c));
+                    if s.pos == start {
+                        s.pos += 1;
+                    }
+                    s.emit(start, Kind::Attribute);
+                }
+            }
+        }
+        name
+    }
+
+    s.finish()
+}
+
+const GRAPHQL_KEYWORDS: &[&str] = &[
+    "query",
+    "mutation",
+    "subscription",
+    "fragment",
+    "on",
+    "type",
+    "interface",
+    "union",
+    "enum",
+    "input",
+    "scalar",
+    "schema",
+    "extend",
+    "directive",
+    "implements",
+    "repeatable",
+];
+
+/// Tokenize a GraphQL document for highlighting: keywords, fields, types,
+/// $variables, @directives, strings (including block strings) and comments.
+/// Returns [start, end, kind] triples like `json_tokenize`.
+#[wasm_bindgen]
+pub fn graphql_tokenize(text: &str) -> Vec<u32> {
+    let mut s = Scanner::new(text);
+    let mut braces = 0usize;
+    let mut parens = 0usize;
+    // Whether the next name is a type reference (after `on`, or after `:` or
+    // `[` in variable definitions and type-system fields).
+    let mut expect_type = false;
+    // Whether the current top-level definition is type-system SDL (`type`, `input`, ...).
+    let mut sdl = false;
+    while let Some(c) = s.peek() {
+        let start = s.pos;
+        match c {
+            '#' => {
+                s.take_while(|c| c != '\n');
+                s.emit(start, Kind::Comment);
+            }
+            '"' if s.starts_with("\"\"\"") => {
+                s.pos += 3;
+                while s.pos < s.chars.len() && !s.starts_with("\"\"\"") {
+                    s.pos += if s.starts_with("\\\"\"\"") { 4 } else { 1 };
+                }
+                s.pos = (s.pos + 3).min(s.chars.len());
+                s.emit(start, Kind::String);
+            }
+            '"' => {
+                s.string('"', false);
+                s.emit(start, Kind::String);
+            }
+            '$' | '@' => {
+                s.pos += 1;
+                s.take_while(is_name_char);
+                let kind = if c == '$' {
+                    Kind::Variable
+                } else {
+                    Kind::Directive
+                };
+                s.emit(start, kind);
+            }
+            '-' | '0'..='9' => {
+                s.pos += 1;
+                s.take_while(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+                s.emit(start, Kind::Number);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                s.take_while(is_name_char);
+                let word: String = s.chars[start..s.pos].iter().collect();
+                let in_selection = braces > 0 && parens == 0;
+                let kind = if expect_type {
+                    Kind::Type
+                } else if matches!(word.as_str(), "true" | "false" | "null") {
+                    Kind::Literal
+                } else if word == "on" || (braces == 0 && GRAPHQL_KEYWORDS.contains(&&*word)) {
+                    Kind::Keyword
+                } else if in_selection || parens > 0 {
+                    Kind::Property
+                } else {
+                    Kind::Type
+                };
+                if kind == Kind::Keyword && word != "on" {
+                    sdl = !matches!(
+                        word.as_str(),
+                        "query" | "mutation" | "subscription" | "fragment"
+                    );
+                }
+                expect_type = word == "on";
+                s.emit(start, kind);
+            }
+            '.' if s.starts_with("...") => {
+                s.pos += 3;
+                s.emit(start, Kind::Punctuation);
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | '=' | '!' | '|' | '&' => {
+                match c {
+                    '{' => braces += 1,
+                    '}' => braces = braces.saturating_sub(1),
+                    '(' => parens += 1,
+                    ')' => parens = parens.saturating_sub(1),
+                    _ => {}
+                }
+                // Type positions: `($id: ID!)`, `[Int]`, and `field(arg: A): B` in SDL.
+                expect_type = match c {
+                    ':' => (braces == 0 && parens > 0) || (braces > 0 && sdl),
+                    '[' => expect_type || (braces == 0 && parens > 0),
+                    '=' | '|' => braces == 0 && parens == 0,
+                    _ => false,
+                };
+                s.pos += 1;
+                s.emit(start, Kind::Punctuation);
+            }
+            _ => s.pos += 1,
+        }
+    }
+
+    s.finish()
+}
+
+/// Tokenize text that may contain `{{variable}}` placeholders (a URL, header or
+/// body) for highlighting: literal runs are "text", `\{{` escapes "entity", braces,
+/// pipes and `:-` "punctuation", variable names "variable", filters "filter" with
+/// "string" arguments and defaults, `#if`, `else` and `/if` "keyword", and
+/// expressions as numbers, strings, literals and operators. Unclosed or empty braces and unknown filters are "invalid".
+/// Returns [start, end, kind] triples like `json_tokenize`, covering all the text
+/// except whitespace inside placeholders.
+#[wasm_bindgen]
+pub fn tokenize_template(text: &str) -> Vec<u32> {
+    let mut s = Scanner::new(text);
+    let mut literal = 0;
+    while s.pos < s.chars.len() {
+        if !s.starts_with("{{") {
+            s.pos += 1;
+            continue;
+        }
+        let open = s.pos;
+        let backslashes = s.chars[..open]
+            .iter()
+            .rev()
+            .take_while(|c| **c == '\\')
+            .count();
+        if backslashes % 2 == 1 {
+            s.span(literal, open - 1, Kind::Text);
+            s.span(open - 1, open + 2, Kind::Entity);
+            s.pos = open + 2;
+            literal = s.pos;
+            continue;
+        }
+        s.span(literal, open, Kind::Text);
+        // Placeholders run to the first `}`, which must be doubled, as in substitution.
+        let mut close = open + 2;
+        while close < s.chars.len() && s.chars[close] != '}' {
+            s.pos = close;
+            if s.starts_with("{{") {
+                break;
+            }
+            close += 1;
+        }
+        let closed = s.chars.get(close) == Some(&'}') && s.chars.get(close + 1) == Some(&'}');
+        let inner: String = s.chars[open + 2..close.min(s.chars.len())].iter().collect();
+        if !closed || inner.trim().is_empty() {
+            // Through a lone `}`, or up to the next `{{` / the end.
+            let end = if s.chars.get(close) == Some(&'}') {
+                close + 1 + usize::from(closed)
+            } else {
+                close
+            };
+            s.span(open, end, Kind::Invalid);
+            s.pos = end;
+            literal = end;
+            continue;
+        }
+        s.span(open, open + 2, Kind::Punctuation);
+        let (a, b) = s.trimmed(open + 2, close);
+        match parse_block(&inner) {
+            Some(Block::If(condition)) => {
+                s.span(a, a + 3, Kind::Keyword);
+                if Expr::parse_template(condition).is_some() {
+                    expression_tokens(&mut s, a + 3, b);
+                } else {
+                    let (a, b) = s.trimmed(a + 3, b);
+                    s.span(a, b, Kind::Variable);
+                }
+            }
+            Some(_) => s.span(a, b, Kind::Keyword),
+            None if Expr::parse_template(&inner).is_some() => {
+                expression_tokens(&mut s, open + 2, close)
+            }
+            None => placeholder_tokens(&mut s, open + 2, close),
+        }
+        s.span(close, close + 2, Kind::Punctuation);
+        s.pos = close + 2;
+        literal = s.pos;
+    }
+    s.span(literal, s.chars.len(), Kind::Text);
+    s.finish()
+}
+
+/// `name`, `name:-default` or `name | filter:arg | ...` between start and end.
+fn placeholder_tokens(s: &mut Scanner, start: usize, end: usize) {
+    let pipe = (start..end).find(|&i| s.chars[i] == '|');
+    let head_end = pipe.unwrap_or(end);
+    let default = pipe
+        .is_none()
+        .then(|| {
+            (start..head_end.saturating_sub(1))
+                .find(|&i| s.chars[i] == ':' && s.chars[i + 1] == '-')
+        })
+        .flatten();
+    let (a, b) = s.trimmed(start, default.unwrap_or(head_end));
+    s.span(
+        a,
+        b,
+        if a == b {
+            Kind::Invalid
+        } else {
+            Kind::Variable
+        },
+    );
+    if let Some(colon) = default {
+        s.span(colon, colon + 2, Kind::Punctuation);
+        let (a, b) = s.trimmed(colon + 2, end);
+        s.span(a, b, Kind::String);
+    }
+    let mut at = head_end;
+    while at < end {
+        // `at` is a pipe; the filter runs to the next one.
+        let next = (at + 1..end).find(|&i| s.chars[i] == '|').unwrap_or(end);
+        let colon = (at + 1..next).find(|&i| s.chars[i] == ':');
+        let (a, b) = s.trimmed(at + 1, colon.unwrap_or(next));
+        let name: String = s.chars[a..b].iter().collect();
+        let known = name == "default" || is_filter(&name);
+        // A pipe with no filter after it is the mistake to point at.
+        let pipe = if name.is_empty() {
+            Kind::Invalid
+        } else {
+            Kind::Punctuation
+        };
+        s.span(at, at + 1, pipe);
+        s.span(a, b, if known { Kind::Filter } else { Kind::Invalid });
+        if let Some(colon) = colon {
+            s.span(colon, colon + 1, Kind::Punctuation);
+            let (a, b) = s.trimmed(colon + 1, next);
+            s.span(a, b, Kind::String);
+        }
+        at = next;
+    }
+}
+
+/// An expression such as `count + 1` or `n > 1 ? "s" : ""` between start and end.
+fn expression_tokens(s: &mut Scanner, start: usize, end: usize) {
+    s.pos = start;
+    while s.pos < end {
+        let at = s.pos;
+        let c = s.chars[at];
+        match c {
+            c if c.is_whitespace() => s.pos += 1,
+            '"' | '\'' => {
+                s.string(c, true);
+                s.pos = s.pos.min(end);
+                s.emit(at, Kind::String);
+            }
+            '0'..='9' | '.' => {
+                s.take_while(|c| c.is_ascii_digit() || c == '.');
+                s.emit(at, Kind::Number);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                s.take_while(|c| c.is_alphanumeric() || c == '_' || c == '.');
+                let word: String = s.chars[at..s.pos].iter().collect();
+                let kind = if matches!(word.as_str(), "true" | "false" | "null") {
+                    Kind::Literal
+                } else {
+                    Kind::Variable
+                };
+                s.emit(at, kind);
+            }
+            _ => {
+                let pair = ["==", "!=", "<=", ">=", "&&", "||"]
+                    .iter()
+                    .any(|op| s.starts_with(op));
+                s.pos += if pair { 2 } else { 1 };
+                s.emit(at, Kind::Punctuation);
+            }
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Character cursor that records [start, end, kind] tokens in char offsets
+/// and converts them to UTF-16 offsets at the end.
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+    tokens: Vec<u32>,
+}
+
+impl Scanner {
+    fn new(text: &str) -> Self {
+        Scanner {
+            chars: text.chars().collect(),
+            pos: 0,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        let mut i = self.pos;
+        prefix.chars().all(|p| {
+            let ok = self.chars.get(i) == Some(&p);
+            i += 1;
+            ok
+        })
+    }
+
+    fn starts_with_ignore_case(&self, prefix: &str) -> bool {
+        let mut i = self.pos;
+        prefix.chars().all(|p| {
+            let ok = self
+                .chars
+                .get(i)
+                .is_some_and(|c| c.eq_ignore_ascii_case(&p));
+            i += 1;
+            ok
+        })
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&f) {
+            self.pos += 1;
+        }
+    }
+
+    /// Advance past the terminator (or to the end of input).
+    fn until(&mut self, terminator: &str) {
+        let n = terminator.chars().count();
+        while self.pos < self.chars.len() && !self.starts_with(terminator) {
+            self.pos += 1;
+        }
+        self.pos = (self.pos + n).min(self.chars.len());
+    }
+
+    /// Advance past a quoted string starting at the current quote, honouring
+    /// backslash escapes. Unless `multiline`, an unterminated string stops at the line end.
+    fn string(&mut self, quote: char, multiline: bool) {
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            if c == '\n' && !multiline {
+                return;
+            }
+            self.pos += 1;
+            if c == '\\' {
+                self.pos = (self.pos + 1).min(self.chars.len());
+            } else if c == quote {
+                return;
+            }
+        }
+    }
+
+    /// Record [start, end) directly, if it is not empty.
+    fn span(&mut self, start: usize, end: usize, kind: Kind) {
+        if end > start {
+            self.tokens.extend([start as u32, end as u32, kind as u32]);
+        }
+    }
+
+    /// [start, end) without leading and trailing whitespace.
+    fn trimmed(&self, mut start: usize, mut end: usize) -> (usize, usize) {
+        while start < end && self.chars[start].is_whitespace() {
+            start += 1;
+        }
+        while end > start && self.chars[end - 1].is_whitespace() {
+            end -= 1;
+        }
+        (start, end)
+    }
+
+    fn emit(&mut self, start: usize, kind: Kind) {
+        if self.pos > start {
+            self.tokens
+                .extend([start as u32, self.pos as u32, kind as u32]);
+        }
+    }
+
+    fn finish(self) -> Vec<u32> {
+        if self.chars.iter().all(|c| c.len_utf16() == 1) {
+            return self.tokens;
+        }
+        let mut utf16 = Vec::with_capacity(self.chars.len() + 1);
+        let mut offset = 0u32;
+        for c in &self.chars {
+            utf16.push(offset);
+            offset += c.len_utf16() as u32;
+        }
+        utf16.push(offset);
+        self.tokens
+            .chunks(3)
+            .flat_map(|t| [utf16[t[0] as usize], utf16[t[1] as usize], t[2]])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render tokens as (text, kind name) pairs.
+    fn spans(text: &str, tokens: &[u32]) -> Vec<(String, &'static str)> {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        tokens
+            .chunks(3)
+            .map(|t| {
+                (
+                    String::from_utf16_lossy(&units[t[0] as usize..t[1] as usize]),
+                    KIND_NAMES[t[2] as usize],
+                )
+            })
+            .collect()
+    }
+
+    fn kinds_of<'a>(spans: &'a [(String, &'static str)], kind: &str) -> Vec<&'a str> {
+        spans
+            .iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|(t, _)| t.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_tokenize() {
+        let text = r#"{"név": "é😀", "n": -1.5e3, "ok": true}"#;
+        let s = spans(text, &json_tokenize(text));
+        assert_eq!(kinds_of(&s, "property"), vec!["\"név\"", "\"n\"", "\"ok\""]);
+        assert_eq!(kinds_of(&s, "string"), vec!["\"é😀\""]);
+        assert_eq!(kinds_of(&s, "number"), vec!["-1.5e3"]);
+        assert_eq!(kinds_of(&s, "literal"), vec!["true"]);
+    }
+
+    #[test]
+    fn test_xml_and_html_tokenize() {
+        let xml = r#"<?xml version="1.0"?><a:b x="1 &amp; 2"><!-- c --><![CDATA[<x>]]>&lt;</a:b>"#;
+        let s = spans(xml, &xml_tokenize(xml));
+        assert_eq!(kinds_of(&s, "tag"), vec!["a:b", "a:b"]);
+        assert_eq!(kinds_of(&s, "attribute"), vec!["x"]);
+        assert_eq!(kinds_of(&s, "string"), vec!["\"1 &amp; 2\""]);
+        assert_eq!(kinds_of(&s, "comment"), vec!["<!-- c -->"]);
+        assert_eq!(kinds_of(&s, "meta").len(), 2);
+        assert_eq!(kinds_of(&s, "entity"), vec!["&lt;"]);
+
+        let html = "<!DOCTYPE html><input disabled value=a><script>if (a<b) x='<p>'</script><br/>";
+        let s = spans(html, &html_tokenize(html));
+        assert_eq!(kinds_of(&s, "tag"), vec!["input", "script", "script", "br"]);
+        assert_eq!(kinds_of(&s, "attribute"), vec!["disabled", "value"]);
+        assert_eq!(kinds_of(&s, "string"), vec!["a"]);
+        assert_eq!(kinds_of(&s, "meta"), vec!["<!DOCTYPE html>"]);
+    }
+
+    #[test]
+    fn test_graphql_tokenize() {
+        let text = "# get\nquery Q($id: [ID!]! = \"x\") {\n  user(id: $id) @include(if: true) {\n    ...F\n    ... on Admin { level }\n  }\n}\n\"\"\"doc\"\"\"\ntype User { name: String }";
+        let s = spans(text, &graphql_tokenize(text));
+        assert_eq!(kinds_of(&s, "comment"), vec!["# get"]);
+        assert_eq!(kinds_of(&s, "keyword"), vec!["query", "on", "type"]);
+        assert_eq!(kinds_of(&s, "variable"), vec!["$id", "$id"]);
+        assert_eq!(kinds_of(&s, "directive"), vec!["@include"]);
+        assert_eq!(
+            kinds_of(&s, "type"),
+            vec!["Q", "ID", "Admin", "User", "String"]
+        );
+        assert_eq!(
+            kinds_of(&s, "property"),
+            vec!["user", "id", "if", "F", "level", "name"]
+        );
+        assert_eq!(kinds_of(&s, "string"), vec!["\"x\"", "\"\"\"doc\"\"\""]);
+    }
+
+    #[test]
+    fn test_tokenize_template() {
+        let text = "https://{{host}}/é/{{ id | upper | nope:1 }}?q={{name:-Ann}}&e=\\{{raw}}";
+        let s = spans(text, &tokenize_template(text));
+        assert_eq!(
+            s,
+            [
+                ("https://", "text"),
+                ("{{", "punctuation"),
+                ("host", "variable"),
+                ("}}", "punctuation"),
+                ("/é/", "text"),
+                ("{{", "punctuation"),
+                ("id", "variable"),
+                ("|", "punctuation"),
+                ("upper", "filter"),
+                ("|", "punctuation"),
+                ("nope", "invalid"),
+                (":", "punctuation"),
+                ("1", "string"),
+                ("}}", "punctuation"),
+                ("?q=", "text"),
+                ("{{", "punctuation"),
+                ("name", "variable"),
+                (":-", "punctuation"),
+                ("Ann", "string"),
+                ("}}", "punctuation"),
+                ("&e=", "text"),
+                ("\\{{", "entity"),
+                ("raw}}", "text"),
+            ]
+            .map(|(t, k)| (t.to_string(), k))
+        );
+
+        let text = "{{ n > 1 ? 'items' : count + 2 }}";
+        let s = spans(text, &tokenize_template(text));
+        assert_eq!(kinds_of(&s, "variable"), vec!["n", "count"]);
+        assert_eq!(kinds_of(&s, "number"), vec!["1", "2"]);
+        assert_eq!(kinds_of(&s, "string"), vec!["'items'"]);
+
+        let text = "a {{}} b {{open c {{ok}} {{x} d";
+        let s = spans(text, &tokenize_template(text));
+        assert_eq!(kinds_of(&s, "invalid"), vec!["{{}}", "{{open c ", "{{x}"]);
+        assert_eq!(kinds_of(&s, "variable"), vec!["ok"]);
+        assert_eq!(kinds_of(&s, "text"), vec!["a ", " b ", " ", " d"]);
+        let s = spans("{{a | }}", &tokenize_template("{{a | }}"));
+        assert_eq!(kinds_of(&s, "invalid"), vec!["|"]);
+
+        let text = "{{#if useAuth}}x{{ else }}{{#if !a}}y{{/if}}{{/if}}";
+        let s = spans(text, &tokenize_template(text));
+        assert_eq!(
+            kinds_of(&s, "keyword"),
+            vec!["#if", "else", "#if", "/if", "/if"]
+        );
+        assert_eq!(kinds_of(&s, "variable"), vec!["useAuth", "a"]);
+    }
+}
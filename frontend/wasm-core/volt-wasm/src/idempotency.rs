@@ -0,0 +1,315 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::model::{KeyValue, Request, deserialize_pairs};
+use crate::rng::Rng;
+use crate::{hex_encode, now_ms, split_url};
+
+/// Methods that are neither safe nor idempotent (RFC 9110 §9.2), so a repeated
+/// send may create or change something twice.
+const NON_IDEMPOTENT: &[&str] = &["POST", "PATCH"];
+
+/// Header names servers use to deduplicate retried requests.
+const KEY_HEADERS: &[&str] = &["idempotency-key", "x-idempotency-key", "x-request-id"];
+
+/// Mixed into the fallback RNG seed so keys made in the same millisecond differ.
+static KEY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Sent {
+    id: String,
+    timestamp: f64,
+    method: String,
+    url: String,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    headers: Vec<KeyValue>,
+    #[serde(alias = "requestBody")]
+    body: String,
+}
+
+/// Generate a value for an `Idempotency-Key` header.
+/// strategy:
+/// - "uuid" (default): a random UUID v4, different on every call;
+/// - "content-hash": SHA-256 (hex) of the method, URL, body and sorted headers,
+///   so resending the same request reuses its key;
+/// - "template:<pattern>": `<pattern>` with {method}, {name}, {path}, {hash} (first
+///   16 hex digits of the content hash), {uuid}, {timestamp} (ms) and {date}
+///   (YYYY-MM-DD) filled in; `{{variables}}` are left for substitution.
+///
+/// Returns JSON {key, strategy} or {error}.
+#[wasm_bindgen]
+pub fn idempotency_key(request_json: &str, strategy: &str) -> String {
+    let request: Request = match serde_json::from_str(request_json) {
+        Ok(r) => r,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid request: {}", e) }).to_string();
+        }
+    };
+    let key = match strategy {
+        "" | "uuid" => uuid_v4(),
+        "content-hash" => request_hash(&request),
+        _ => match strategy.strip_prefix("template:") {
+            Some(pattern) => fill_template(pattern, &request),
+            None => {
+                return serde_json::json!({ "error": format!("Unknown strategy: {}", strategy) })
+                    .to_string();
+            }
+        },
+    };
+    let name = match strategy.split(':').next() {
+        Some("") | None => "uuid",
+        Some(name) => name,
+    };
+    serde_json::json!({ "key": key, "strategy": name }).to_string()
+}
+
+/// Find identical POST/PATCH requests sent within `window_ms` of each other,
+/// typically double submits or retries that may have created duplicates.
+/// history_json: array of {id, timestamp (ms), method, url, headers, body}.
+/// Requests are identical when method, URL, body and headers match; a matching
+/// `Idempotency-Key` (or similar) header marks the pair as `protected`, since the
+/// server can deduplicate it.
+/// Returns JSON {duplicates: [{first, second, method, url, gapMs, protected}],
+/// unprotected} where first/second are entry ids (or indexes when ids are missing).
+#[wasm_bindgen]
+pub fn detect_duplicate_sends(history_json: &str, window_ms: f64) -> String {
+    let history: Vec<Sent> = serde_json::from_str(history_json).unwrap_or_default();
+    let mut order: Vec<(usize, &Sent)> = history
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| NON_IDEMPOTENT.contains(&s.method.to_uppercase().as_str()))
+        .collect();
+    order.sort_by(|a, b| a.1.timestamp.total_cmp(&b.1.timestamp));
+
+    let fingerprints: Vec<String> = order
+        .iter()
+        .map(|(_, s)| {
+            let headers: Vec<KeyValue> = s
+                .headers
+                .iter()
+                .filter(|h| !KEY_HEADERS.contains(&h.key.to_lowercase().as_str()))
+                .cloned()
+                .collect();
+            content_hash(&s.method, &s.url, &headers, &s.body)
+        })
+        .collect();
+
+    let label = |i: usize, s: &Sent| {
+        if s.id.is_empty() {
+            serde_json::json!(i)
+        } else {
+            serde_json::json!(s.id)
+        }
+    };
+    let mut duplicates = Vec::new();
+    for (pos, (i, sent)) in order.iter().enumerate() {
+        // Pair each send with the next identical one only, so a burst of N
+        // sends is reported as N - 1 pairs.
+        let next = order
+            .iter()
+            .zip(&fingerprints)
+            .skip(pos + 1)
+            .find(|(_, f)| **f == fingerprints[pos]);
+        let Some(((j, again), _)) = next else {
+            continue;
+        };
+        let gap = again.timestamp - sent.timestamp;
+        if gap > window_ms {
+            continue;
+        }
+        let protected = match (request_key(sent), request_key(again)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        };
+        duplicates.push(serde_json::json!({
+            "first": label(*i, sent),
+            "second": label(*j, again),
+            "method": sent.method.to_uppercase(),
+            "url": sent.url,
+            "gapMs": gap,
+            "protected": protected,
+        }));
+    }
+
+    let unprotected = duplicates
+        .iter()
+        .filter(|d| d["protected"] == false)
+        .count();
+    serde_json::json!({ "duplicates": duplicates, "unprotected": unprotected }).to_string()
+}
+
+fn request_key(sent: &Sent) -> Option<&str> {
+    sent.headers
+        .iter()
+        .find(|h| h.enabled && KEY_HEADERS.contains(&h.key.to_lowercase().as_str()))
+        .map(|h| h.value.as_str())
+}
+
+fn request_hash(request: &Request) -> String {
+    content_hash(
+        &request.method,
+        &request.url,
+        &request.headers,
+        &request.body,
+    )
+}
+
+/// SHA-256 over the request; header order and name case do not matter.
+fn content_hash(method: &str, url: &str, headers: &[KeyValue], body: &str) -> String {
+    let mut pairs: Vec<(String, &str)> = headers
+        .iter()
+        .filter(|h| h.enabled)
+        .map(|h| (h.key.to_lowercase(), h.value.as_str()))
+        .collect();
+    pairs.sort();
+    let mut hasher = Sha256::new();
+    for part in [method.to_uppercase().as_str(), url.trim()] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    for (k, v) in pairs {
+        hasher.update(format!("{}:{}\n", k, v).as_bytes());
+    }
+    hasher.update([0]);
+    hasher.update(body.as_bytes());
+    hex_encode(&hasher.finalize())
+}
+
+fn fill_template(pattern: &str, request: &Request) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // Leave {{variables}} for the usual substitution.
+        if let Some(var) = rest.strip_prefix("{{") {
+            let end = var.find("}}").map_or(var.len(), |e| e + 2);
+            out.push_str(&rest[..end + 2]);
+            rest = &var[end..];
+            continue;
+        }
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let value = match &rest[1..end] {
+            "method" => request.method.to_uppercase(),
+            "name" => request.name.clone(),
+            "path" => split_url(&request.url).1.to_string(),
+            "hash" => request_hash(request)[..16].to_string(),
+            "uuid" => uuid_v4(),
+            "timestamp" => format!("{}", now_ms() as u64),
+            "date" => {
+                let (y, m, d) = crate::civil_from_days((now_ms() / 86_400_000.0).floor() as i64);
+                format!("{:04}-{:02}-{:02}", y, m, d)
+            }
+            _ => rest[..=end].to_string(),
+        };
+        out.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+pub(crate) fn uuid_v4() -> String {
+    let mut bytes = random_bytes();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex_encode(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// 16 bytes from the system's secure source when available, otherwise from a
+/// PRNG seeded with the clock and a call counter.
+pub(crate) fn random_bytes() -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    #[cfg(feature = "vault")]
+    if getrandom::getrandom(&mut bytes).is_ok() {
+        return bytes;
+    }
+    let count = KEY_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut rng = Rng::new(now_ms().to_bits() ^ count.rotate_left(32));
+    for chunk in bytes.chunks_mut(8) {
+        chunk.copy_from_slice(&rng.next_u64().to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const REQUEST: &str = r#"{
+        "name": "Create charge",
+        "method": "post",
+        "url": "https://api.x.io/v1/charges?expand=1",
+        "headers": {"Content-Type": "application/json", "Accept": "*/*"},
+        "body": "{\"amount\": 100}"
+    }"#;
+
+    fn key(strategy: &str) -> String {
+        let out: Value = serde_json::from_str(&idempotency_key(REQUEST, strategy)).unwrap();
+        out["key"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_idempotency_key() {
+        let a = key("uuid");
+        assert_eq!(a.len(), 36);
+        assert_eq!(&a[14..15], "4");
+        assert_ne!(a, key(""));
+
+        let hash = key("content-hash");
+        assert_eq!(hash.len(), 64);
+        let reordered = REQUEST.replace(
+            r#"{"Content-Type": "application/json", "Accept": "*/*"}"#,
+            r#"{"accept": "*/*", "content-type": "application/json"}"#,
+        );
+        let again: Value =
+            serde_json::from_str(&idempotency_key(&reordered, "content-hash")).unwrap();
+        assert_eq!(again["key"], hash.as_str());
+
+        let templated = key("template:{method}-{path}-{hash}-{{runId}}-{unknown}");
+        assert_eq!(
+            templated,
+            format!("POST-/v1/charges-{}-{{{{runId}}}}-{{unknown}}", &hash[..16])
+        );
+        assert!(idempotency_key(REQUEST, "sequence").contains("Unknown strategy"));
+    }
+
+    #[test]
+    fn test_detect_duplicate_sends() {
+        let history = r#"[
+            {"id": "h1", "timestamp": 1000, "method": "POST", "url": "https://x.io/orders", "body": "{\"sku\":1}"},
+            {"id": "h2", "timestamp": 1400, "method": "post", "url": "https://x.io/orders", "body": "{\"sku\":1}"},
+            {"id": "h3", "timestamp": 1500, "method": "POST", "url": "https://x.io/orders", "body": "{\"sku\":2}"},
+            {"id": "h4", "timestamp": 1600, "method": "GET", "url": "https://x.io/orders"},
+            {"id": "h5", "timestamp": 1700, "method": "GET", "url": "https://x.io/orders"},
+            {"id": "h6", "timestamp": 2000, "method": "PATCH", "url": "https://x.io/a", "headers": {"Idempotency-Key": "k1"}},
+            {"id": "h7", "timestamp": 2100, "method": "PATCH", "url": "https://x.io/a", "headers": {"Idempotency-Key": "k1"}},
+            {"id": "h8", "timestamp": 9000, "method": "PATCH", "url": "https://x.io/a", "headers": {"Idempotency-Key": "k2"}}
+        ]"#;
+        let out: Value = serde_json::from_str(&detect_duplicate_sends(history, 1000.0)).unwrap();
+        let duplicates = out["duplicates"].as_array().unwrap();
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0]["first"], "h1");
+        assert_eq!(duplicates[0]["second"], "h2");
+        assert_eq!(duplicates[0]["gapMs"], 400.0);
+        assert_eq!(duplicates[0]["protected"], false);
+        assert_eq!(duplicates[1]["second"], "h7");
+        assert_eq!(duplicates[1]["protected"], true);
+        assert_eq!(out["unprotected"], 1);
+    }
+}
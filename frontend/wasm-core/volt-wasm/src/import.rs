@@ -0,0 +1,1052 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::model::{Folder, Headers, KeyValue, Request};
+use crate::progress::Progress;
+use crate::request_merge::{Strategy, apply};
+use crate::split_url;
+
+/// HTTP/2 pseudo-headers that Chrome copies into PowerShell snippets.
+const PSEUDO_HEADERS: &[&str] = &["authority", "method", "path", "scheme"];
+
+/// Import a DevTools "Copy as fetch" / "Copy as fetch (Node.js)" snippet.
+/// Understands `fetch(url, init)` with JS string, object and array literals
+/// (including `JSON.stringify(...)` bodies); `referrer` becomes a Referer header.
+/// Returns JSON request {method, url, headers: [{key, value, enabled}], body, bodyType}
+/// or {error}.
+/// progress: optional callback invoked with {done, total, stage}.
+#[wasm_bindgen]
+pub fn parse_fetch_snippet(js_text: &str, progress: Option<js_sys::Function>) -> String {
+    import_one(progress, || parse_fetch(js_text))
+}
+
+/// Import a DevTools "Copy as PowerShell" snippet (Invoke-WebRequest or
+/// Invoke-RestMethod), including the session's user agent and cookies.
+/// Returns the same shape as `parse_fetch_snippet`, or {error}.
+/// progress: optional callback invoked with {done, total, stage}.
+#[wasm_bindgen]
+pub fn parse_powershell_snippet(text: &str, progress: Option<js_sys::Function>) -> String {
+    import_one(progress, || parse_powershell(text))
+}
+
+/// Turn a pasted list of URLs, or a sitemap.xml, into a folder of GET requests.
+/// The list takes one URL per line (also comma or space separated); blank lines
+/// and `#` comments are ignored, and paths starting with "/" are resolved against
+/// `baseUrl`. Sitemap `<loc>` entries are used as is.
+/// defaults_json: {folderName, baseUrl, ...request fields}; the request fields
+/// (headers, queryParams, auth, assertions, ...) are applied to every request as
+/// with `merge_request(.., "defaults")`.
+/// Returns JSON {folder: {id, name, requests, folders}, skipped: [text]} where
+/// skipped lists entries that are not URLs, and duplicates; or {error}.
+/// progress: optional callback invoked with {done, total, stage}.
+#[wasm_bindgen]
+pub fn requests_from_urls(
+    text_or_sitemap_xml: &str,
+    defaults_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
+    let mut defaults: Map<String, Value> = match defaults_json.trim() {
+        "" => Map::new(),
+        json => match serde_json::from_str(json) {
+            Ok(Value::Object(map)) => map,
+            _ => {
+                return serde_json::json!({ "error": "Invalid defaults: expected an object" })
+                    .to_string();
+            }
+        },
+    };
+    let folder_name = defaults.remove("folderName");
+    let base_url = defaults
+        .remove("baseUrl")
+        .and_then(|v| v.as_str().map(|s| s.trim_end_matches('/').to_string()))
+        .unwrap_or_default();
+    let shared: Request =
+        serde_json::from_value(Value::Object(defaults.clone())).unwrap_or_default();
+
+    let sitemap = text_or_sitemap_xml.contains("<loc>");
+    let entries = if sitemap {
+        sitemap_locations(text_or_sitemap_xml)
+    } else {
+        text_or_sitemap_xml
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .flat_map(|l| l.split(|c: char| c == ',' || c.is_whitespace()))
+            .filter(|t| !t.is_empty() && !t.eq_ignore_ascii_case("GET"))
+            .map(str::to_string)
+            .collect()
+    };
+
+    let total = entries.len();
+    let mut progress = Progress::new(progress, "import", total);
+    let mut requests: Vec<Request> = Vec::new();
+    let mut skipped = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for (done, entry) in entries.into_iter().enumerate() {
+        progress.tick(done);
+        let url = if entry.starts_with('/') && !base_url.is_empty() {
+            format!("{}{}", base_url, entry)
+        } else {
+            entry.clone()
+        };
+        let valid = ["http://", "https://", "{{"]
+            .iter()
+            .any(|p| url.starts_with(p));
+        if !valid || requests.iter().any(|r| r.url == url) {
+            skipped.push(entry);
+            continue;
+        }
+        let mut request = build_request("GET", url, Vec::new(), String::new());
+        request.name = unique_name(url_name(&request.url), &mut names);
+        apply(&mut request, shared.clone(), &defaults, Strategy::Defaults);
+        requests.push(request);
+    }
+    progress.tick(total);
+
+    let name = folder_name
+        .and_then(|v| v.as_str().map(str::to_string))
+        .or_else(|| {
+            let host = split_url(requests.first()?.url.as_str()).0;
+            let host = host.split("://").nth(1).unwrap_or(host);
+            (sitemap && !host.is_empty()).then(|| host.to_string())
+        })
+        .unwrap_or_else(|| "Imported URLs".to_string());
+    let folder = Folder {
+        name,
+        requests,
+        ..Default::default()
+    };
+    serde_json::json!({ "folder": folder, "skipped": skipped }).to_string()
+}
+
+/// Import a raw HTTP/1.x message pasted from a proxy, a packet capture or a
+/// write-up. Line endings may be CRLF or LF, folded header lines are joined,
+/// `Transfer-Encoding: chunked` bodies are decoded and `Content-Length` cuts off
+/// anything pasted after the body.
+/// A request's URL comes from an absolute-form target, or from the Host header
+/// (http for localhost, IPs and ports other than 443, otherwise https); without
+/// a Host header the path is put under `{{baseUrl}}`. Host, Content-Length and
+/// Transfer-Encoding are dropped from the request headers since they are derived.
+/// Returns JSON {kind: "request", httpVersion, request} or {kind: "response",
+/// httpVersion, response: {statusCode, statusText, headers, body}}, or {error}.
+#[wasm_bindgen]
+pub fn parse_raw_http(text: &str) -> String {
+    match parse_raw(text) {
+        Ok(message) => message.to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+/// Run a single-request importer, reporting it as one step.
+fn import_one(
+    progress: Option<js_sys::Function>,
+    parse: impl FnOnce() -> Result<Request, String>,
+) -> String {
+    let mut progress = Progress::new(progress, "import", 1);
+    progress.tick(0);
+    let json = to_json(parse());
+    progress.tick(1);
+    json
+}
+
+fn to_json(result: Result<Request, String>) -> String {
+    match result {
+        Ok(request) => serde_json::to_string(&request)
+            .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }).to_string()),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+/// Fill in the request fields shared by every importer.
+fn build_request(method: &str, url: String, headers: Vec<KeyValue>, body: String) -> Request {
+    let content_type = headers
+        .iter()
+        .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.to_lowercase())
+        .unwrap_or_default();
+    let body_type = if body.is_empty() {
+        "none"
+    } else if content_type.contains("json")
+        || (content_type.is_empty()
+            && serde_json::from_str::<Value>(&body).is_ok_and(|v| v.is_object() || v.is_array()))
+    {
+        "json"
+    } else {
+        "raw"
+    };
+    let method = if method.is_empty() {
+        if body.is_empty() { "GET" } else { "POST" }
+    } else {
+        method
+    };
+    Request {
+        method: method.to_uppercase(),
+        url,
+        headers,
+        body,
+        body_type: body_type.to_string(),
+        ..Default::default()
+    }
+}
+
+/// `<loc>` values of a sitemap or sitemap index, with XML escapes decoded.
+fn sitemap_locations(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</loc>"))
+        .map(|(loc, _)| {
+            let loc = loc.trim();
+            let loc = loc
+                .strip_prefix("<![CDATA[")
+                .and_then(|l| l.strip_suffix("]]>"))
+                .unwrap_or(loc);
+            loc.trim()
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+/// "https://x.io/blog/post-1?p=2" → "blog/post-1"; the host for the root path.
+fn url_name(url: &str) -> String {
+    let (origin, path, _) = split_url(url);
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        origin.split("://").nth(1).unwrap_or(origin).to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Suffix repeated names with " (2)", " (3)", ...
+pub(crate) fn unique_name(name: String, used: &mut Vec<String>) -> String {
+    let mut unique = name.clone();
+    let mut n = 2;
+    while used.contains(&unique) {
+        unique = format!("{} ({})", name, n);
+        n += 1;
+    }
+    used.push(unique.clone());
+    unique
+}
+
+fn parse_fetch(text: &str) -> Result<Request, String> {
+    let start = text
+        .find("fetch(")
+        .ok_or_else(|| "No fetch(...) call found".to_string())?;
+    let mut js = JsParser::new(&text[start + "fetch(".len()..]);
+    let url = match js.value() {
+        Some(Value::String(url)) => url,
+        _ => return Err("fetch() must be called with a URL string".to_string()),
+    };
+    js.skip_space();
+    let init = if js.eat(',') {
+        js.value()
+            .ok_or_else(|| "Could not parse the fetch options".to_string())?
+    } else {
+        Value::Null
+    };
+
+    let mut headers: Vec<KeyValue> = match &init["headers"] {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| KeyValue::new(k, &scalar(v)))
+            .collect(),
+        Value::Array(pairs) => pairs
+            .iter()
+            .filter_map(|p| Some(KeyValue::new(p[0].as_str()?, &scalar(&p[1]))))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if let Some(referrer) = init["referrer"].as_str()
+        && !headers
+            .iter()
+            .any(|h| h.key.eq_ignore_ascii_case("referer"))
+    {
+        headers.push(KeyValue::new("Referer", referrer));
+    }
+    let body = match &init["body"] {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let method = init["method"].as_str().unwrap_or_default();
+    Ok(build_request(method, url, headers, body))
+}
+
+fn scalar(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// A forgiving parser for the JS literals DevTools emits.
+struct JsParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl JsParser {
+    fn new(text: &str) -> Self {
+        JsParser {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_space(&mut self) {
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            let rest = &self.chars[self.pos.min(self.chars.len())..];
+            if rest.starts_with(&['/', '/']) {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else if rest.starts_with(&['/', '*']) {
+                self.pos += 2;
+                while self.pos < self.chars.len()
+                    && !self.chars[self.pos..].starts_with(&['*', '/'])
+                {
+                    self.pos += 1;
+                }
+                self.pos += 2;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        self.skip_space();
+        match self.peek()? {
+            '"' | '\'' | '`' => self.string().map(Value::String),
+            '{' => self.object(),
+            '[' => self.array(),
+            c if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+                {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                serde_json::from_str::<serde_json::Number>(&text)
+                    .ok()
+                    .map(Value::Number)
+            }
+            _ => {
+                let word = self.identifier();
+                match word.as_str() {
+                    "null" | "undefined" => Some(Value::Null),
+                    "true" => Some(Value::Bool(true)),
+                    "false" => Some(Value::Bool(false)),
+                    "JSON.stringify" if self.eat('(') => {
+                        let inner = self.value()?;
+                        // Skip a replacer/indent argument, if any.
+                        while self.peek().is_some_and(|c| c != ')') {
+                            self.pos += 1;
+                        }
+                        self.eat(')');
+                        Some(Value::String(inner.to_string()))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let quote = self.peek()?;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self.peek()?;
+            self.pos += 1;
+            if c == quote {
+                return Some(out);
+            }
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            let escaped = self.peek()?;
+            self.pos += 1;
+            match escaped {
+                'n' => out.push('\n'),
+                'r' => out.push('\r'),
+                't' => out.push('\t'),
+                'b' => out.push('\u{8}'),
+                'f' => out.push('\u{c}'),
+                'v' => out.push('\u{b}'),
+                '0' => out.push('\0'),
+                '\n' => {}
+                'x' => out.push(self.hex_escape(2)?),
+                'u' if self.peek() == Some('{') => {
+                    self.pos += 1;
+                    let end = self.chars[self.pos..].iter().position(|&c| c == '}')?;
+                    let hex: String = self.chars[self.pos..self.pos + end].iter().collect();
+                    self.pos += end + 1;
+                    out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                'u' => {
+                    let unit = self.hex_unit(4)?;
+                    // Combine surrogate pairs written as two \u escapes.
+                    if (0xD800..0xDC00).contains(&unit)
+                        && self.chars[self.pos..].starts_with(&['\\', 'u'])
+                    {
+                        self.pos += 2;
+                        let low = self.hex_unit(4)?;
+                        let code =
+                            0x10000 + ((unit - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                        out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                    } else {
+                        out.push(char::from_u32(unit).unwrap_or('\u{FFFD}'));
+                    }
+                }
+                other => out.push(other),
+            }
+        }
+    }
+
+    fn hex_unit(&mut self, len: usize) -> Option<u32> {
+        let hex: String = self.chars.get(self.pos..self.pos + len)?.iter().collect();
+        self.pos += len;
+        u32::from_str_radix(&hex, 16).ok()
+    }
+
+    fn hex_escape(&mut self, len: usize) -> Option<char> {
+        char::from_u32(self.hex_unit(len)?)
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            if self.eat('}') {
+                return Some(Value::Object(map));
+            }
+            self.skip_space();
+            let key = match self.peek()? {
+                '"' | '\'' | '`' => self.string()?,
+                _ => self.identifier(),
+            };
+            if key.is_empty() || !self.eat(':') {
+                return None;
+            }
+            let value = self.value()?;
+            map.insert(key, value);
+            if !self.eat(',') && !matches!(self.peek(), Some('}')) {
+                return None;
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            if self.eat(']') {
+                return Some(Value::Array(items));
+            }
+            items.push(self.value()?);
+            if !self.eat(',') && !matches!(self.peek(), Some(']')) {
+                return None;
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+    Punct(char),
+}
+
+fn parse_powershell(text: &str) -> Result<Request, String> {
+    let tokens = ps_tokens(text);
+    let invoke = tokens
+        .iter()
+        .position(|t| {
+            matches!(t, Token::Word(w) if w.eq_ignore_ascii_case("Invoke-WebRequest")
+                || w.eq_ignore_ascii_case("Invoke-RestMethod")
+                || w.eq_ignore_ascii_case("iwr")
+                || w.eq_ignore_ascii_case("irm"))
+        })
+        .ok_or_else(|| "No Invoke-WebRequest or Invoke-RestMethod call found".to_string())?;
+
+    // Session setup before the call: user agent and cookies.
+    let mut user_agent = None;
+    let mut cookies = Vec::new();
+    for (i, token) in tokens[..invoke].iter().enumerate() {
+        let Token::Word(word) = token else {
+            continue;
+        };
+        if word.to_lowercase().ends_with(".useragent")
+            && tokens.get(i + 1) == Some(&Token::Punct('='))
+            && let Some(Token::Str(ua)) = tokens.get(i + 2)
+        {
+            user_agent = Some(ua.clone());
+        }
+        if word.eq_ignore_ascii_case("System.Net.Cookie")
+            && tokens.get(i + 1) == Some(&Token::Punct('('))
+            && let (Some(Token::Str(name)), Some(Token::Str(value))) =
+                (tokens.get(i + 2), tokens.get(i + 4))
+        {
+            cookies.push(format!("{}={}", name, value));
+        }
+    }
+
+    let mut method = String::new();
+    let mut url = None;
+    let mut headers = Vec::new();
+    let mut content_type = None;
+    let mut body = String::new();
+    let mut i = invoke + 1;
+    while i < tokens.len() {
+        let Token::Word(param) = &tokens[i] else {
+            i += 1;
+            continue;
+        };
+        i += 1;
+        match param.to_lowercase().as_str() {
+            "-uri" | "-url" => url = ps_text(tokens.get(i)),
+            "-method" => method = ps_text(tokens.get(i)).unwrap_or_default(),
+            "-contenttype" => content_type = ps_text(tokens.get(i)),
+            "-useragent" => user_agent = ps_text(tokens.get(i)),
+            "-headers" => {
+                let (pairs, next) = ps_hashtable(&tokens, i);
+                headers.extend(
+                    pairs
+                        .into_iter()
+                        .filter(|(k, _)| !PSEUDO_HEADERS.contains(&k.to_lowercase().as_str()))
+                        .map(|(k, v)| KeyValue::new(&k, &v)),
+                );
+                i = next;
+                continue;
+            }
+            "-body" => {
+                // Either a plain string or ([System.Text.Encoding]::UTF8.GetBytes("...")).
+                let mut depth = 0;
+                let mut j = i;
+                while let Some(token) = tokens.get(j) {
+                    match token {
+                        Token::Str(s) => {
+                            body = s.clone();
+                            break;
+                        }
+                        Token::Punct('(') => depth += 1,
+                        Token::Punct(')') if depth > 0 => depth -= 1,
+                        Token::Word(w) if depth == 0 && w.starts_with('-') => break,
+                        _ => {}
+                    }
+                    j += 1;
+                }
+            }
+            _ => continue,
+        }
+        i += 1;
+    }
+
+    let url = url.ok_or_else(|| "Missing -Uri".to_string())?;
+    let has =
+        |headers: &[KeyValue], name: &str| headers.iter().any(|h| h.key.eq_ignore_ascii_case(name));
+    if let Some(ua) = user_agent
+        && !has(&headers, "user-agent")
+    {
+        headers.push(KeyValue::new("User-Agent", &ua));
+    }
+    if !cookies.is_empty() && !has(&headers, "cookie") {
+        headers.push(KeyValue::new("Cookie", &cookies.join("; ")));
+    }
+    if let Some(ct) = content_type
+        && !has(&headers, "content-type")
+    {
+        headers.push(KeyValue::new("Content-Type", &ct));
+    }
+    Ok(build_request(&method, url, headers, body))
+}
+
+fn ps_text(token: Option<&Token>) -> Option<String> {
+    match token? {
+        Token::Str(s) | Token::Word(s) => Some(s.clone()),
+        Token::Punct(_) => None,
+    }
+}
+
+/// Parse `@{ "k"="v"; k2 = 'v2' }` starting at `start`; returns the pairs and
+/// the index after the closing brace.
+fn ps_hashtable(tokens: &[Token], start: usize) -> (Vec<(String, String)>, usize) {
+    let mut i = start;
+    if tokens.get(i) == Some(&Token::Word("@".to_string())) {
+        i += 1;
+    }
+    if tokens.get(i) != Some(&Token::Punct('{')) {
+        return (Vec::new(), start);
+    }
+    i += 1;
+    let mut pairs = Vec::new();
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Punct('}') => return (pairs, i + 1),
+            Token::Str(key) | Token::Word(key) if tokens.get(i + 1) == Some(&Token::Punct('=')) => {
+                if let Some(value) = ps_text(tokens.get(i + 2)) {
+                    pairs.push((key.clone(), value));
+                }
+                i += 3;
+            }
+            _ => i += 1,
+        }
+    }
+    (pairs, i)
+}
+
+fn ps_tokens(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            // A backtick before a line break continues the command.
+            '`' if chars.get(i + 1).is_some_and(|c| *c == '\n' || *c == '\r') => i += 1,
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' | '\'' => {
+                let (s, next) = ps_string(&chars, i);
+                tokens.push(Token::Str(s));
+                i = next;
+            }
+            '(' | ')' | '{' | '}' | '=' | ';' | ',' => {
+                tokens.push(Token::Punct(c));
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(
+                        chars[i],
+                        '"' | '\'' | '(' | ')' | '{' | '}' | '=' | ';' | ','
+                    )
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    tokens
+}
+
+/// A PowerShell string literal at `start`: backtick escapes and doubled quotes in
+/// "double", only doubled quotes in 'single'. Returns the text and the next index.
+fn ps_string(chars: &[char], start: usize) -> (String, usize) {
+    let quote = chars[start];
+    let mut out = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                out.push(quote);
+                i += 2;
+                continue;
+            }
+            return (out, i + 1);
+        }
+        if c == '`' && quote == '"' && i + 1 < chars.len() {
+            out.push(match chars[i + 1] {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                other => other,
+            });
+            i += 2;
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    (out, i)
+}
+
+fn parse_raw(text: &str) -> Result<Value, String> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let (head, body) = match (text.find("\r\n\r\n"), text.find("\n\n")) {
+        (Some(crlf), lf) if lf.is_none_or(|lf| crlf < lf) => (&text[..crlf], &text[crlf + 4..]),
+        (_, Some(lf)) => (&text[..lf], &text[lf + 2..]),
+        _ => (text, ""),
+    };
+    let mut lines = head.lines().map(|l| l.trim_end_matches('\r'));
+    let start = lines
+        .next()
+        .filter(|l| !l.trim().is_empty())
+        .ok_or("Message is empty")?;
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        if line.starts_with([' ', '\t']) {
+            // Obsolete line folding continues the previous header.
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid header line: {}", line))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+
+    let chunked = header("transfer-encoding").is_some_and(|v| v.to_lowercase().contains("chunked"));
+    let body = if chunked {
+        decode_chunked(body).ok_or("Invalid chunked body")?
+    } else {
+        let mut body = body.to_string();
+        if let Some(length) = header("content-length").and_then(|v| v.parse::<usize>().ok())
+            && length < body.len()
+        {
+            let mut end = length;
+            while !body.is_char_boundary(end) {
+                end += 1;
+            }
+            body.truncate(end);
+        }
+        body
+    };
+
+    let parts: Vec<&str> = start.split_whitespace().collect();
+    if start.starts_with("HTTP/") {
+        let status = parts
+            .get(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| format!("Invalid status line: {}", start))?;
+        let reason = start.splitn(3, ' ').nth(2).unwrap_or_default().trim();
+        let headers: Headers = headers.into_iter().collect();
+        return Ok(serde_json::json!({
+            "kind": "response",
+            "httpVersion": parts[0].trim_start_matches("HTTP/"),
+            "response": {
+                "statusCode": status,
+                "statusText": reason,
+                "headers": headers,
+                "body": body,
+            },
+        }));
+    }
+
+    let (method, target, version) = match parts[..] {
+        [method, target] => (method, target, "1.0"),
+        [method, target, version] if version.starts_with("HTTP/") => {
+            (method, target, version.trim_start_matches("HTTP/"))
+        }
+        _ => return Err(format!("Invalid request line: {}", start)),
+    };
+    if !method.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid method: {}", method));
+    }
+    let url = if target.contains("://") {
+        target.to_string()
+    } else {
+        let path = if target == "*" { "" } else { target };
+        match header("host") {
+            Some(host) if method.eq_ignore_ascii_case("CONNECT") => format!("https://{}", host),
+            Some(host) => format!("{}://{}{}", raw_scheme(host), host, path),
+            None if method.eq_ignore_ascii_case("CONNECT") => format!("https://{}", target),
+            None => format!("{{{{baseUrl}}}}{}", path),
+        }
+    };
+    let request_headers = headers
+        .iter()
+        .filter(|(k, _)| {
+            !["host", "content-length", "transfer-encoding"]
+                .iter()
+                .any(|h| k.eq_ignore_ascii_case(h))
+        })
+        .map(|(k, v)| KeyValue::new(k, v))
+        .collect();
+    let request = build_request(method, url, request_headers, body);
+    Ok(serde_json::json!({
+        "kind": "request",
+        "httpVersion": version,
+        "request": request,
+    }))
+}
+
+/// Plain http for local and non-default ports, which is how captures of
+/// development servers usually look; https otherwise.
+fn raw_scheme(host: &str) -> &'static str {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => (name, Some(port)),
+        _ => (host, None),
+    };
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    let local = name == "localhost"
+        || name.ends_with(".localhost")
+        || name.parse::<std::net::IpAddr>().is_ok();
+    match port {
+        Some("443") => "https",
+        Some(_) => "http",
+        None if local => "http",
+        None => "https",
+    }
+}
+
+/// Join the chunks of a chunked body: `<hex size>[;ext]` lines followed by that
+/// many bytes, up to a zero-size chunk. Trailers are ignored.
+fn decode_chunked(body: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = body;
+    loop {
+        let (size_line, after) = rest.split_once('\n')?;
+        let size = size_line.trim_end_matches('\r').split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        if size == 0 {
+            return Some(out);
+        }
+        out.push_str(after.get(..size)?);
+        rest = after[size..]
+            .strip_prefix("\r\n")
+            .or_else(|| after[size..].strip_prefix('\n'))
+            .unwrap_or(&after[size..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: String) -> Value {
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_fetch_snippet() {
+        let snippet = r#"fetch("https://api.example.com/users?page=2", {
+  "headers": {
+    "accept": "application/json",
+    "content-type": "application/json",
+  },
+  "referrer": "https://app.example.com/",
+  "referrerPolicy": "strict-origin-when-cross-origin",
+  "body": "{\"name\":\"Ann \u00e9\"}",
+  "method": "POST",
+  "mode": "cors",
+  "credentials": "include"
+});"#;
+        let request = parse(parse_fetch_snippet(snippet, None));
+        assert_eq!(request["method"], "POST");
+        assert_eq!(request["url"], "https://api.example.com/users?page=2");
+        assert_eq!(request["headers"][1]["key"], "content-type");
+        assert_eq!(request["headers"][2]["key"], "Referer");
+        assert_eq!(request["body"], r#"{"name":"Ann é"}"#);
+        assert_eq!(request["bodyType"], "json");
+
+        let node = parse(parse_fetch_snippet(
+            "await fetch('https://x.io/ping', { method: 'get', body: null, headers: [['X-A', 1]] })",
+            None,
+        ));
+        assert_eq!(node["method"], "GET");
+        assert_eq!(node["bodyType"], "none");
+        assert_eq!(node["headers"][0]["value"], "1");
+
+        let stringified = parse(parse_fetch_snippet(
+            "fetch(`/a`, {body: JSON.stringify({a: [1, true]})})",
+            None,
+        ));
+        assert_eq!(stringified["method"], "POST");
+        assert_eq!(stringified["body"], r#"{"a":[1,true]}"#);
+        assert!(parse_fetch_snippet("curl x", None).contains("error"));
+    }
+
+    #[test]
+    fn test_parse_powershell_snippet() {
+        let snippet = r#"$session = New-Object Microsoft.PowerShell.Commands.WebRequestSession
+$session.UserAgent = "Mozilla/5.0 Test"
+$session.Cookies.Add((New-Object System.Net.Cookie("sid", "abc123", "/", "api.example.com")))
+Invoke-WebRequest -UseBasicParsing -Uri "https://api.example.com/items" `
+-Method "PUT" `
+-WebSession $session `
+-Headers @{
+"authority"="api.example.com"
+  "method"="PUT"
+  "accept"="*/*"
+  "x-note"="it's ""quoted"""
+} `
+-ContentType "application/json" `
+-Body ([System.Text.Encoding]::UTF8.GetBytes("{`"id`":1}"))"#;
+        let request = parse(parse_powershell_snippet(snippet, None));
+        assert_eq!(request["method"], "PUT");
+        assert_eq!(request["url"], "https://api.example.com/items");
+        let headers: Vec<(String, String)> = request["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| {
+                (
+                    h["key"].as_str().unwrap().to_string(),
+                    h["value"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("accept", "*/*"),
+            ("x-note", r#"it's "quoted""#),
+            ("User-Agent", "Mozilla/5.0 Test"),
+            ("Cookie", "sid=abc123"),
+            ("Content-Type", "application/json"),
+        ];
+        assert_eq!(
+            headers,
+            expected
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(request["body"], r#"{"id":1}"#);
+        assert_eq!(request["bodyType"], "json");
+
+        let simple = parse(parse_powershell_snippet(
+            "Invoke-RestMethod -Uri 'https://x.io/a' -Body 'a=1'",
+            None,
+        ));
+        assert_eq!(simple["method"], "POST");
+        assert_eq!(simple["bodyType"], "raw");
+        assert!(parse_powershell_snippet("Get-Item x", None).contains("error"));
+    }
+
+    #[test]
+    fn test_requests_from_urls_list() {
+        let text = "# smoke endpoints\nhttps://api.x.io/health\n/users, /users?page=2\nGET /users\nnot-a-url\n";
+        let out = parse(requests_from_urls(
+            text,
+            r#"{"baseUrl": "https://api.x.io/", "folderName": "Smoke", "headers": {"Accept": "application/json"}}"#,
+            None,
+        ));
+        let folder = &out["folder"];
+        assert_eq!(folder["name"], "Smoke");
+        let requests = folder["requests"].as_array().unwrap();
+        let names: Vec<&str> = requests
+            .iter()
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["health", "users", "users (2)"]);
+        assert_eq!(requests[2]["url"], "https://api.x.io/users?page=2");
+        assert_eq!(requests[0]["method"], "GET");
+        assert_eq!(requests[0]["headers"][0]["key"], "Accept");
+        assert_eq!(out["skipped"], serde_json::json!(["/users", "not-a-url"]));
+        assert!(requests_from_urls("", "[1]", None).contains("error"));
+    }
+
+    #[test]
+    fn test_requests_from_urls_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://www.example.com/</loc><lastmod>2024-01-01</lastmod></url>
+  <url><loc> https://www.example.com/search?q=a&amp;page=2 </loc></url>
+  <url><loc><![CDATA[https://www.example.com/about/]]></loc></url>
+</urlset>"#;
+        let out = parse(requests_from_urls(xml, "", None));
+        assert_eq!(out["folder"]["name"], "www.example.com");
+        let requests = out["folder"]["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0]["name"], "www.example.com");
+        assert_eq!(
+            requests[1]["url"],
+            "https://www.example.com/search?q=a&page=2"
+        );
+        assert_eq!(requests[2]["name"], "about");
+    }
+
+    #[test]
+    fn test_parse_raw_http_request() {
+        let raw = "\r\nPOST /api/users?page=2 HTTP/1.1\r\nHost: api.example.com\r\nContent-Type: application/json\r\nX-Long: a\r\n  b\r\nContent-Length: 9\r\n\r\n{\"a\": 1}\ntrailing junk";
+        let out = parse(parse_raw_http(raw));
+        assert_eq!(out["kind"], "request");
+        assert_eq!(out["httpVersion"], "1.1");
+        let request = &out["request"];
+        assert_eq!(request["method"], "POST");
+        assert_eq!(request["url"], "https://api.example.com/api/users?page=2");
+        assert_eq!(request["body"], "{\"a\": 1}\n");
+        let headers = request["headers"].as_array().unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[1]["value"], "a b");
+
+        let local = parse(parse_raw_http(
+            "GET /health HTTP/1.1\nHost: localhost:8080\n\n",
+        ));
+        assert_eq!(local["request"]["url"], "http://localhost:8080/health");
+        let absolute = parse(parse_raw_http("GET http://proxy.test/x HTTP/1.1\n"));
+        assert_eq!(absolute["request"]["url"], "http://proxy.test/x");
+        let bare = parse(parse_raw_http("DELETE /items/1 HTTP/1.1"));
+        assert_eq!(bare["request"]["url"], "{{baseUrl}}/items/1");
+
+        assert!(parse(parse_raw_http("  \n"))["error"].is_string());
+        assert!(parse(parse_raw_http("not a request line at all"))["error"].is_string());
+        assert!(parse(parse_raw_http("GET / HTTP/1.1\nbad header\n\n"))["error"].is_string());
+    }
+
+    #[test]
+    fn test_parse_raw_http_response() {
+        let raw = "HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nX-Trailer: t\r\n\r\n";
+        let out = parse(parse_raw_http(raw));
+        assert_eq!(out["kind"], "response");
+        let response = &out["response"];
+        assert_eq!(response["statusCode"], 404);
+        assert_eq!(response["statusText"], "Not Found");
+        assert_eq!(response["body"], "hello world");
+        assert_eq!(response["headers"]["Set-Cookie"][1], "b=2");
+
+        let bad = parse(parse_raw_http(
+            "HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\nzz\nx",
+        ));
+        assert!(bad["error"].is_string());
+        assert!(parse(parse_raw_http("HTTP/2 abc"))["error"].is_string());
+    }
+}
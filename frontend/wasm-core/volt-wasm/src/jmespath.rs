@@ -0,0 +1,993 @@
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use wasm_bindgen::prelude::*;
+
+use crate::get_value_type;
+
+/// Expressions nested deeper than this are rejected.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Quoted(String),
+    Literal(Value),
+    Number(i64),
+    Op(&'static str),
+}
+
+/// Punctuation, longest first so `[?` is not read as `[`.
+const OPERATORS: &[&str] = &[
+    "[?", "[]", "||", "&&", "==", "!=", "<=", ">=", ".", "*", "[", "]", "{", "}", "(", ")", ",",
+    ":", "|", "<", ">", "!", "@", "&",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|d: char| !(d.is_ascii_alphanumeric() || d == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            rest = &rest[len..];
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|d: char| d.is_ascii_digit()))
+        {
+            let len = 1 + rest[1..]
+                .find(|d: char| !d.is_ascii_digit())
+                .unwrap_or(rest.len() - 1);
+            let n = rest[..len]
+                .parse()
+                .map_err(|_| format!("invalid number \"{}\"", &rest[..len]))?;
+            tokens.push(Token::Number(n));
+            rest = &rest[len..];
+        } else if matches!(c, '"' | '\'' | '`') {
+            let end = delimited(rest, c)?;
+            let inner = &rest[1..end];
+            tokens.push(match c {
+                // Quoted identifiers are JSON strings.
+                '"' => Token::Quoted(
+                    serde_json::from_str(&rest[..=end])
+                        .map_err(|_| format!("invalid quoted identifier {}", &rest[..=end]))?,
+                ),
+                '\'' => Token::Literal(Value::String(inner.replace("\\'", "'"))),
+                _ => Token::Literal(
+                    serde_json::from_str(&inner.replace("\\`", "`"))
+                        .map_err(|_| format!("invalid JSON literal `{}`", inner))?,
+                ),
+            });
+            rest = &rest[end + 1..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected \"{}\"", c));
+        }
+    }
+}
+
+/// Byte offset of the quote closing the string that starts `text`; a backslash
+/// escapes the next character.
+fn delimited(text: &str, quote: char) -> Result<usize, String> {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == quote => return Ok(i),
+            _ => {}
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// How tightly a token binds as an infix operator.
+fn power(token: Option<&Token>) -> u8 {
+    match token {
+        Some(Token::Op(op)) => match *op {
+            "|" => 1,
+            "||" => 2,
+            "&&" => 3,
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => 5,
+            "[]" => 9,
+            "*" => 20,
+            "[?" => 21,
+            "." => 40,
+            "!" => 45,
+            "{" => 50,
+            "[" => 55,
+            "(" => 60,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// Syntax tree of an expression.
+#[derive(Debug)]
+enum Node {
+    Current,
+    Literal(Value),
+    Field(String),
+    /// Evaluate the right side against the result of the left side.
+    Sub(Box<Node>, Box<Node>),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    /// Evaluate the right side against each element of the left side's array.
+    Projection(Box<Node>, Box<Node>),
+    /// Like `Projection`, over the values of an object.
+    ValueProjection(Box<Node>, Box<Node>),
+    /// Left side, elements for which the condition holds, right side.
+    Filter(Box<Node>, Box<Node>, Box<Node>),
+    Flatten(Box<Node>),
+    List(Vec<Node>),
+    Hash(Vec<(String, Node)>),
+    Not(Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Compare(&'static str, Box<Node>, Box<Node>),
+    Function(String, Vec<Node>),
+    /// `&expr`, only meaningful as a function argument.
+    ExpRef(Box<Node>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected \"{}\"", op))
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.peek().cloned().ok_or("unexpected end of expression")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("expression nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    /// Each infix operator wraps the tree built so far one level deeper, so it
+    /// counts towards the depth too: `a.b.c...` is as deep as it is long.
+    fn expression(&mut self, bp: u8) -> Result<Node, String> {
+        let depth = self.depth;
+        self.enter()?;
+        let token = self.next()?;
+        let mut left = self.prefix(token)?;
+        while bp < power(self.peek()) {
+            self.enter()?;
+            let token = self.next()?;
+            left = self.infix(token, left)?;
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn prefix(&mut self, token: Token) -> Result<Node, String> {
+        Ok(match token {
+            Token::Literal(value) => Node::Literal(value),
+            Token::Ident(name) => Node::Field(name),
+            Token::Quoted(_) if self.peek_op() == Some("(") => {
+                return Err("a quoted identifier cannot name a function".to_string());
+            }
+            Token::Quoted(name) => Node::Field(name),
+            Token::Op("@") => Node::Current,
+            Token::Op("*") => {
+                Node::ValueProjection(Box::new(Node::Current), Box::new(self.projection_rhs(20)?))
+            }
+            Token::Op("[?") => self.filter(Node::Current)?,
+            Token::Op("[]") => Node::Projection(
+                Box::new(Node::Flatten(Box::new(Node::Current))),
+                Box::new(self.projection_rhs(9)?),
+            ),
+            Token::Op("[") => match self.peek() {
+                Some(Token::Number(_) | Token::Op(":")) => {
+                    let index = self.index()?;
+                    self.project_slice(Node::Current, index)?
+                }
+                Some(Token::Op("*")) if self.tokens.get(self.pos + 1) == Some(&Token::Op("]")) => {
+                    self.pos += 2;
+                    Node::Projection(Box::new(Node::Current), Box::new(self.projection_rhs(20)?))
+                }
+                _ => self.list()?,
+            },
+            Token::Op("{") => self.hash()?,
+            Token::Op("&") => Node::ExpRef(Box::new(self.expression(0)?)),
+            Token::Op("!") => Node::Not(Box::new(self.expression(45)?)),
+            Token::Op("(") => {
+                let inner = self.expression(0)?;
+                self.expect(")")?;
+                inner
+            }
+            Token::Op(op) => return Err(format!("unexpected \"{}\"", op)),
+            Token::Number(n) => return Err(format!("unexpected number {}", n)),
+        })
+    }
+
+    fn infix(&mut self, token: Token, left: Node) -> Result<Node, String> {
+        let left = Box::new(left);
+        Ok(match token {
+            Token::Op(".") if self.peek_op() == Some("*") => {
+                self.pos += 1;
+                Node::ValueProjection(left, Box::new(self.projection_rhs(40)?))
+            }
+            Token::Op(".") => Node::Sub(left, Box::new(self.dot_rhs(40)?)),
+            Token::Op("|") => Node::Sub(left, Box::new(self.expression(1)?)),
+            Token::Op("||") => Node::Or(left, Box::new(self.expression(2)?)),
+            Token::Op("&&") => Node::And(left, Box::new(self.expression(3)?)),
+            Token::Op(op @ ("==" | "!=" | "<" | "<=" | ">" | ">=")) => {
+                Node::Compare(op, left, Box::new(self.expression(5)?))
+            }
+            Token::Op("(") => {
+                let Node::Field(name) = *left else {
+                    return Err("only a name can be called as a function".to_string());
+                };
+                let mut args = Vec::new();
+                if self.peek_op() != Some(")") {
+                    loop {
+                        args.push(self.expression(0)?);
+                        if self.peek_op() != Some(",") {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                self.expect(")")?;
+                Node::Function(name, args)
+            }
+            Token::Op("[?") => self.filter(*left)?,
+            Token::Op("[]") => Node::Projection(
+                Box::new(Node::Flatten(left)),
+                Box::new(self.projection_rhs(9)?),
+            ),
+            Token::Op("[") => match self.peek() {
+                Some(Token::Number(_) | Token::Op(":")) => {
+                    let index = self.index()?;
+                    self.project_slice(*left, index)?
+                }
+                _ => {
+                    self.expect("*")?;
+                    self.expect("]")?;
+                    Node::Projection(left, Box::new(self.projection_rhs(20)?))
+                }
+            },
+            Token::Op(op) => return Err(format!("unexpected \"{}\"", op)),
+            _ => return Err("expected an operator".to_string()),
+        })
+    }
+
+    /// `[n]`, `[start:stop:step]`, after the `[`.
+    fn index(&mut self) -> Result<Node, String> {
+        let mut parts = [None; 3];
+        let mut part = 0;
+        loop {
+            match self.next()? {
+                Token::Op("]") => break,
+                Token::Op(":") if part < 2 => part += 1,
+                Token::Number(n) if parts[part].is_none() => parts[part] = Some(n),
+                _ => return Err("invalid index or slice".to_string()),
+            }
+        }
+        match parts {
+            [Some(n), None, None] if part == 0 => Ok(Node::Index(n)),
+            [_, _, Some(0)] => Err("slice step cannot be 0".to_string()),
+            [start, stop, step] if part > 0 => Ok(Node::Slice(start, stop, step)),
+            _ => Err("invalid index or slice".to_string()),
+        }
+    }
+
+    /// A slice projects what follows it; an index does not.
+    fn project_slice(&mut self, left: Node, index: Node) -> Result<Node, String> {
+        let sliced = matches!(index, Node::Slice(..));
+        let node = Node::Sub(Box::new(left), Box::new(index));
+        if !sliced {
+            return Ok(node);
+        }
+        Ok(Node::Projection(
+            Box::new(node),
+            Box::new(self.projection_rhs(20)?),
+        ))
+    }
+
+    /// `[?condition]` after the `[?`, projecting what follows.
+    fn filter(&mut self, left: Node) -> Result<Node, String> {
+        let condition = self.expression(0)?;
+        self.expect("]")?;
+        Ok(Node::Filter(
+            Box::new(left),
+            Box::new(condition),
+            Box::new(self.projection_rhs(21)?),
+        ))
+    }
+
+    /// What a projection applies to each element: nothing (`@`) when the next
+    /// token binds loosely, otherwise the rest of the chain.
+    fn projection_rhs(&mut self, bp: u8) -> Result<Node, String> {
+        if power(self.peek()) < 10 {
+            return Ok(Node::Current);
+        }
+        match self.peek_op() {
+            Some("[" | "[?") => self.expression(bp),
+            Some(".") => {
+                self.pos += 1;
+                self.dot_rhs(bp)
+            }
+            Some(op) => Err(format!("unexpected \"{}\"", op)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    fn dot_rhs(&mut self, bp: u8) -> Result<Node, String> {
+        match self.peek() {
+            Some(Token::Ident(_) | Token::Quoted(_) | Token::Op("*")) => self.expression(bp),
+            Some(Token::Op("[")) => {
+                self.pos += 1;
+                self.list()
+            }
+            Some(Token::Op("{")) => {
+                self.pos += 1;
+                self.hash()
+            }
+            _ => Err("expected a name, \"[\" or \"{\" after \".\"".to_string()),
+        }
+    }
+
+    /// `[a, b]` after the `[`.
+    fn list(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        loop {
+            items.push(self.expression(0)?);
+            if self.peek_op() != Some(",") {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.expect("]")?;
+        Ok(Node::List(items))
+    }
+
+    /// `{key: a, other: b}` after the `{`.
+    fn hash(&mut self) -> Result<Node, String> {
+        let mut entries = Vec::new();
+        loop {
+            let key = match self.next()? {
+                Token::Ident(key) | Token::Quoted(key) => key,
+                _ => return Err("expected a key in \"{...}\"".to_string()),
+            };
+            self.expect(":")?;
+            entries.push((key, self.expression(0)?));
+            if self.peek_op() != Some(",") {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.expect("}")?;
+        Ok(Node::Hash(entries))
+    }
+}
+
+fn parse(text: &str) -> Result<Node, String> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+        depth: 0,
+    };
+    let node = parser.expression(0)?;
+    match parser.peek() {
+        None => Ok(node),
+        Some(_) => Err("unexpected text after expression".to_string()),
+    }
+}
+
+/// Evaluate a JMESPath expression against `data`. Missing keys and indices give
+/// null rather than an error; syntax and function type errors are errors.
+pub(crate) fn search(data: &Value, expression: &str) -> Result<Value, String> {
+    eval(&parse(expression)?, data)
+}
+
+/// False, null, "" and empty arrays or objects are false; everything else is true.
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        Value::Number(_) => true,
+    }
+}
+
+/// Deep equality where 1 and 1.0 are the same number.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| same(a, b))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| same(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+/// A number as JSON; whole numbers without a decimal point.
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn slice(items: &[Value], start: Option<i64>, stop: Option<i64>, step: Option<i64>) -> Vec<Value> {
+    let len = items.len() as i64;
+    let step = step.unwrap_or(1);
+    let clamp = |i: i64| {
+        if i < 0 {
+            (i + len).max(if step < 0 { -1 } else { 0 })
+        } else {
+            i.min(if step < 0 { len - 1 } else { len })
+        }
+    };
+    let mut i = start.map_or(if step < 0 { len - 1 } else { 0 }, clamp);
+    let stop = stop.map_or(if step < 0 { -1 } else { len }, clamp);
+    let mut out = Vec::new();
+    while (step > 0 && i < stop) || (step < 0 && i > stop) {
+        out.push(items[i as usize].clone());
+        i += step;
+    }
+    out
+}
+
+/// Apply `right` to each item, dropping null results.
+fn project<'v>(items: impl Iterator<Item = &'v Value>, right: &Node) -> Result<Value, String> {
+    let mut out = Vec::new();
+    for item in items {
+        let value = eval(right, item)?;
+        if !value.is_null() {
+            out.push(value);
+        }
+    }
+    Ok(Value::Array(out))
+}
+
+fn eval(node: &Node, data: &Value) -> Result<Value, String> {
+    Ok(match node {
+        Node::Current => data.clone(),
+        Node::Literal(value) => value.clone(),
+        Node::Field(name) => data.get(name).cloned().unwrap_or(Value::Null),
+        Node::Sub(left, right) => eval(right, &eval(left, data)?)?,
+        Node::Index(i) => match data {
+            Value::Array(items) => {
+                let i = if *i < 0 { items.len() as i64 + i } else { *i };
+                usize::try_from(i)
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            }
+            _ => Value::Null,
+        },
+        Node::Slice(start, stop, step) => match data {
+            Value::Array(items) => Value::Array(slice(items, *start, *stop, *step)),
+            _ => Value::Null,
+        },
+        Node::Projection(left, right) => match eval(left, data)? {
+            Value::Array(items) => project(items.iter(), right)?,
+            _ => Value::Null,
+        },
+        Node::ValueProjection(left, right) => match eval(left, data)? {
+            Value::Object(map) => project(map.values(), right)?,
+            _ => Value::Null,
+        },
+        Node::Filter(left, condition, right) => match eval(left, data)? {
+            Value::Array(items) => {
+                let mut kept = Vec::new();
+                for item in items {
+                    if truthy(&eval(condition, &item)?) {
+                        kept.push(item);
+                    }
+                }
+                project(kept.iter(), right)?
+            }
+            _ => Value::Null,
+        },
+        Node::Flatten(inner) => match eval(inner, data)? {
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .flat_map(|item| match item {
+                        Value::Array(nested) => nested,
+                        other => vec![other],
+                    })
+                    .collect(),
+            ),
+            _ => Value::Null,
+        },
+        Node::List(_) | Node::Hash(_) if data.is_null() => Value::Null,
+        Node::List(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| eval(item, data))
+                .collect::<Result<_, _>>()?,
+        ),
+        Node::Hash(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), eval(item, data)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        Node::Not(inner) => Value::Bool(!truthy(&eval(inner, data)?)),
+        Node::Or(left, right) => {
+            let left = eval(left, data)?;
+            if truthy(&left) {
+                left
+            } else {
+                eval(right, data)?
+            }
+        }
+        Node::And(left, right) => {
+            let left = eval(left, data)?;
+            if truthy(&left) {
+                eval(right, data)?
+            } else {
+                left
+            }
+        }
+        Node::Compare(op, left, right) => {
+            let (left, right) = (eval(left, data)?, eval(right, data)?);
+            match *op {
+                "==" => Value::Bool(same(&left, &right)),
+                "!=" => Value::Bool(!same(&left, &right)),
+                // Ordering is only defined for numbers.
+                _ => match (left.as_f64(), right.as_f64()) {
+                    (Some(a), Some(b)) => Value::Bool(match *op {
+                        "<" => a < b,
+                        "<=" => a <= b,
+                        ">" => a > b,
+                        _ => a >= b,
+                    }),
+                    _ => Value::Null,
+                },
+            }
+        }
+        Node::Function(name, args) => call(name, args, data)?,
+        Node::ExpRef(_) => return Err("&expression can only be a function argument".to_string()),
+    })
+}
+
+/// A value that can be sorted or compared by `max`, `min` and `sort`.
+#[derive(PartialEq, PartialOrd)]
+enum Key {
+    Num(f64),
+    Str(String),
+}
+
+/// Keys of one kind, all numbers or all strings.
+fn keys(name: &str, values: impl Iterator<Item = Value>) -> Result<Vec<Key>, String> {
+    let keys: Vec<Key> = values
+        .map(|v| match v {
+            Value::Number(n) => Ok(Key::Num(n.as_f64().unwrap_or(f64::NAN))),
+            Value::String(s) => Ok(Key::Str(s)),
+            other => Err(format!(
+                "{}() expects numbers or strings, got {}",
+                name,
+                get_value_type(&other)
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    let numbers = keys.iter().filter(|k| matches!(k, Key::Num(_))).count();
+    if numbers != 0 && numbers != keys.len() {
+        return Err(format!("{}() expects all numbers or all strings", name));
+    }
+    Ok(keys)
+}
+
+fn compare(a: &Key, b: &Key) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+fn call(name: &str, args: &[Node], data: &Value) -> Result<Value, String> {
+    let expected = |n: usize| {
+        format!(
+            "{}() takes {} argument{}",
+            name,
+            n,
+            if n == 1 { "" } else { "s" }
+        )
+    };
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(expected(n))
+        }
+    };
+    let arg = |i: usize| eval(&args[i], data);
+    let kind = |kind: &str, value: &Value| {
+        format!("{}() expects {}, got {}", name, kind, get_value_type(value))
+    };
+    let num = |i: usize| -> Result<f64, String> {
+        let value = arg(i)?;
+        value.as_f64().ok_or_else(|| kind("a number", &value))
+    };
+    let text = |i: usize| -> Result<String, String> {
+        match arg(i)? {
+            Value::String(s) => Ok(s),
+            other => Err(kind("a string", &other)),
+        }
+    };
+    let array = |i: usize| -> Result<Vec<Value>, String> {
+        match arg(i)? {
+            Value::Array(items) => Ok(items),
+            other => Err(kind("an array", &other)),
+        }
+    };
+    let object = |i: usize| -> Result<Map<String, Value>, String> {
+        match arg(i)? {
+            Value::Object(map) => Ok(map),
+            other => Err(kind("an object", &other)),
+        }
+    };
+    let expref = |i: usize| match &args[i] {
+        Node::ExpRef(inner) => Ok(&**inner),
+        _ => Err(format!(
+            "{}() expects &expression as argument {}",
+            name,
+            i + 1
+        )),
+    };
+    // Sort by the key of each item: the item itself or an expression of it.
+    let sorted = |items: Vec<Value>, by: Option<&Node>| -> Result<Vec<(Key, Value)>, String> {
+        let values = items
+            .iter()
+            .map(|item| by.map_or(Ok(item.clone()), |by| eval(by, item)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut pairs: Vec<(Key, Value)> = keys(name, values.into_iter())?
+            .into_iter()
+            .zip(items)
+            .collect();
+        pairs.sort_by(|a, b| compare(&a.0, &b.0));
+        Ok(pairs)
+    };
+
+    Ok(match name {
+        "abs" => {
+            arity(1)?;
+            number(num(0)?.abs())
+        }
+        "ceil" => {
+            arity(1)?;
+            number(num(0)?.ceil())
+        }
+        "floor" => {
+            arity(1)?;
+            number(num(0)?.floor())
+        }
+        "sum" | "avg" => {
+            arity(1)?;
+            let items = array(0)?;
+            let mut total = 0.0;
+            for item in &items {
+                total += item
+                    .as_f64()
+                    .ok_or_else(|| kind("an array of numbers", item))?;
+            }
+            match name {
+                "avg" if items.is_empty() => Value::Null,
+                "avg" => number(total / items.len() as f64),
+                _ => number(total),
+            }
+        }
+        "contains" => {
+            arity(2)?;
+            let search = arg(1)?;
+            match arg(0)? {
+                Value::Array(items) => Value::Bool(items.iter().any(|item| same(item, &search))),
+                Value::String(s) => Value::Bool(search.as_str().is_some_and(|t| s.contains(t))),
+                other => return Err(kind("an array or string", &other)),
+            }
+        }
+        "starts_with" => {
+            arity(2)?;
+            Value::Bool(text(0)?.starts_with(&text(1)?))
+        }
+        "ends_with" => {
+            arity(2)?;
+            Value::Bool(text(0)?.ends_with(&text(1)?))
+        }
+        "join" => {
+            arity(2)?;
+            let glue = text(0)?;
+            let parts = array(1)?
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(s) => Ok(s),
+                    other => Err(kind("an array of strings", &other)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::String(parts.join(&glue))
+        }
+        "keys" => {
+            arity(1)?;
+            object(0)?
+                .into_iter()
+                .map(|(k, _)| Value::String(k))
+                .collect()
+        }
+        "values" => {
+            arity(1)?;
+            object(0)?.into_iter().map(|(_, v)| v).collect()
+        }
+        "length" => {
+            arity(1)?;
+            match arg(0)? {
+                Value::String(s) => Value::from(s.chars().count()),
+                Value::Array(items) => Value::from(items.len()),
+                Value::Object(map) => Value::from(map.len()),
+                other => return Err(kind("a string, array or object", &other)),
+            }
+        }
+        "map" => {
+            arity(2)?;
+            let by = expref(0)?;
+            array(1)?
+                .iter()
+                .map(|item| eval(by, item))
+                .collect::<Result<_, _>>()?
+        }
+        "max" | "min" => {
+            arity(1)?;
+            let mut pairs = sorted(array(0)?, None)?;
+            let pick = if name == "max" {
+                pairs.pop()
+            } else {
+                pairs.into_iter().next()
+            };
+            pick.map_or(Value::Null, |(_, v)| v)
+        }
+        "max_by" | "min_by" => {
+            arity(2)?;
+            let mut pairs = sorted(array(0)?, Some(expref(1)?))?;
+            let pick = if name == "max_by" {
+                pairs.pop()
+            } else {
+                pairs.into_iter().next()
+            };
+            pick.map_or(Value::Null, |(_, v)| v)
+        }
+        "sort" => {
+            arity(1)?;
+            sorted(array(0)?, None)?
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect()
+        }
+        "sort_by" => {
+            arity(2)?;
+            sorted(array(0)?, Some(expref(1)?))?
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect()
+        }
+        "merge" => {
+            if args.is_empty() {
+                return Err(format!("{}() takes at least 1 argument", name));
+            }
+            let mut merged = Map::new();
+            for i in 0..args.len() {
+                merged.extend(object(i)?);
+            }
+            Value::Object(merged)
+        }
+        "not_null" => {
+            if args.is_empty() {
+                return Err(format!("{}() takes at least 1 argument", name));
+            }
+            for i in 0..args.len() {
+                let value = arg(i)?;
+                if !value.is_null() {
+                    return Ok(value);
+                }
+            }
+            Value::Null
+        }
+        "reverse" => {
+            arity(1)?;
+            match arg(0)? {
+                Value::String(s) => Value::String(s.chars().rev().collect()),
+                Value::Array(items) => items.into_iter().rev().collect(),
+                other => return Err(kind("an array or string", &other)),
+            }
+        }
+        "to_array" => {
+            arity(1)?;
+            match arg(0)? {
+                Value::Array(items) => Value::Array(items),
+                other => Value::Array(vec![other]),
+            }
+        }
+        "to_string" => {
+            arity(1)?;
+            match arg(0)? {
+                Value::String(s) => Value::String(s),
+                other => Value::String(other.to_string()),
+            }
+        }
+        "to_number" => {
+            arity(1)?;
+            match arg(0)? {
+                Value::Number(n) => Value::Number(n),
+                Value::String(s) => s.trim().parse().map_or(Value::Null, number),
+                _ => Value::Null,
+            }
+        }
+        "type" => {
+            arity(1)?;
+            Value::from(get_value_type(&arg(0)?))
+        }
+        _ => return Err(format!("unknown function {}()", name)),
+    })
+}
+
+/// Query JSON with a JMESPath expression, e.g. `people[?age > `30`].name`,
+/// `locations[*].{city: name, state: state} | sort_by(@, &city)` or
+/// `length(items)`. Supports the JMESPath specification: projections, filters,
+/// slices, flattening, pipes, multi-select lists and hashes, literals and the
+/// built-in functions.
+/// Returns JSON {result} (null when nothing matched) or {error} for invalid JSON,
+/// a syntax error or a function called with the wrong types.
+#[wasm_bindgen]
+pub fn json_query_jmespath(json_str: &str, expression: &str) -> String {
+    let data: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid JSON: {}", e) }).to_string();
+        }
+    };
+    match search(&data, expression) {
+        Ok(result) => serde_json::json!({ "result": result }).to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn query(data: &Value, expression: &str) -> Value {
+        search(data, expression).unwrap_or_else(|e| panic!("{}: {}", expression, e))
+    }
+
+    #[test]
+    fn test_paths_projections_and_slices() {
+        let data = json!({
+            "people": [
+                {"name": "Ann", "age": 34, "tags": ["a", "b"]},
+                {"name": "Bo", "age": 25, "tags": ["c"]},
+                {"name": "Cy", "age": 41},
+            ],
+            "ops": {"a": {"n": 1}, "b": {"n": 2}},
+            "weird key": 1,
+        });
+        assert_eq!(query(&data, "people[0].name"), "Ann");
+        assert_eq!(query(&data, "people[-1].age"), 41);
+        assert_eq!(query(&data, "people[*].name"), json!(["Ann", "Bo", "Cy"]));
+        assert_eq!(query(&data, "people[:2].age"), json!([34, 25]));
+        assert_eq!(query(&data, "people[::-2].name"), json!(["Cy", "Ann"]));
+        assert_eq!(query(&data, "people[].tags[]"), json!(["a", "b", "c"]));
+        assert_eq!(query(&data, "people[*].tags[0]"), json!(["a", "c"]));
+        assert_eq!(query(&data, "ops.*.n"), json!([1, 2]));
+        assert_eq!(query(&data, "\"weird key\""), 1);
+        assert_eq!(query(&data, "people[9].name"), Value::Null);
+        assert_eq!(query(&data, "missing.deeper"), Value::Null);
+        assert_eq!(
+            query(&data, "people[?age > `30`].name"),
+            json!(["Ann", "Cy"])
+        );
+        assert_eq!(
+            query(&data, "people[?name == 'Bo' || !tags].age"),
+            json!([25, 41])
+        );
+        assert_eq!(
+            query(&data, "people[0].{n: name, first: tags[0]}"),
+            json!({"n": "Ann", "first": "a"})
+        );
+        assert_eq!(query(&data, "people[1].[name, age]"), json!(["Bo", 25]));
+        assert_eq!(query(&data, "people[*].name | [0]"), "Ann");
+    }
+
+    #[test]
+    fn test_functions() {
+        let data = json!({"items": [
+            {"id": 3, "name": "c", "price": 1.5},
+            {"id": 1, "name": "a", "price": 4},
+            {"id": 2, "name": "b", "price": 2.5},
+        ], "tags": ["x", "y"]});
+        assert_eq!(query(&data, "length(items)"), 3);
+        assert_eq!(
+            query(&data, "sort_by(items, &id)[*].name"),
+            json!(["a", "b", "c"])
+        );
+        assert_eq!(query(&data, "max_by(items, &price).id"), 1);
+        assert_eq!(query(&data, "sum(items[*].price)"), 8);
+        assert_eq!(query(&data, "avg(items[*].id)"), 2);
+        assert_eq!(query(&data, "join(', ', tags)"), "x, y");
+        assert_eq!(query(&data, "contains(tags, 'y')"), true);
+        assert_eq!(
+            query(&data, "map(&to_string(id), items)"),
+            json!(["3", "1", "2"])
+        );
+        assert_eq!(query(&data, "not_null(missing, tags[1])"), "y");
+        assert_eq!(
+            query(&data, "sort(items[*].name) | reverse(@)"),
+            json!(["c", "b", "a"])
+        );
+        assert_eq!(query(&data, "type(items[0].price)"), "number");
+        assert!(search(&data, "sum(tags)").unwrap_err().contains("numbers"));
+        assert!(search(&data, "nope(items)").is_err());
+        assert!(search(&data, "length(items, tags)").is_err());
+    }
+
+    #[test]
+    fn test_json_query_jmespath_errors() {
+        let out: Value =
+            serde_json::from_str(&json_query_jmespath(r#"{"a": [1]}"#, "a[0]")).unwrap();
+        assert_eq!(out["result"], 1);
+        for expression in ["a[", "a.", "a[0:1:0]", "`{bad`", "'open", "a b"] {
+            let out: Value =
+                serde_json::from_str(&json_query_jmespath(r#"{"a": 1}"#, expression)).unwrap();
+            assert!(out["error"].is_string(), "{}", expression);
+        }
+        let out: Value = serde_json::from_str(&json_query_jmespath("{", "a")).unwrap();
+        assert!(out["error"].is_string());
+    }
+
+    #[test]
+    fn test_long_chains_are_rejected() {
+        let data = json!({ "x": { "x": 1 } });
+        assert_eq!(query(&data, "x.x"), 1);
+        for chain in [
+            format!("a{}", ".x".repeat(2000)),
+            format!("a{}", " | x".repeat(2000)),
+            format!("a{}", "[*]".repeat(2000)),
+        ] {
+            let err = search(&data, &chain).unwrap_err();
+            assert!(err.contains("nested too deeply"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::json_scan::{Position, ScanError};
+
+/// Nesting allowed before giving up, matching serde_json's own limit.
+const MAX_DEPTH: usize = 128;
+
+/// Converts JSON5 text to strict JSON text, noting which relaxations it used.
+struct Relaxed<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+    depth: usize,
+    out: String,
+    /// Kinds of non-JSON syntax met, in the order first seen.
+    fixes: Vec<&'static str>,
+}
+
+fn is_line_break(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '$' || c == '_'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '$' || c == '_'
+}
+
+impl Relaxed<'_> {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn error(&self, message: String) -> ScanError {
+        ScanError {
+            message,
+            position: self.position(),
+        }
+    }
+
+    fn fix(&mut self, kind: &'static str) {
+        if !self.fixes.contains(&kind) {
+            self.fixes.push(kind);
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn unexpected(&self, wanted: &str) -> ScanError {
+        match self.peek() {
+            Some(c) => self.error(format!("Expected {} but found '{}'", wanted, c)),
+            None => self.error(format!("Expected {} but reached end of input", wanted)),
+        }
+    }
+
+    /// Skip whitespace (including JSON5's extra kinds) and comments.
+    fn skip(&mut self) -> Result<(), ScanError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() || c == '\u{feff}' => {
+                    self.bump();
+                }
+                Some('/') if self.src[self.pos..].starts_with("//") => {
+                    self.fix("comments");
+                    while self.peek().is_some_and(|c| !is_line_break(c)) {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.src[self.pos..].starts_with("/*") => {
+                    self.fix("comments");
+                    let start = self.position();
+                    self.bump();
+                    self.bump();
+                    loop {
+                        if self.src[self.pos..].starts_with("*/") {
+                            self.bump();
+                            self.bump();
+                            break;
+                        }
+                        if self.bump().is_none() {
+                            return Err(ScanError {
+                                message: "Unterminated comment".to_string(),
+                                position: start,
+                            });
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<(), ScanError> {
+        match self.peek() {
+            Some('{') => self.nested(|p| p.object()),
+            Some('[') => self.nested(|p| p.array()),
+            Some(quote @ ('"' | '\'')) => self.string(quote),
+            Some('-' | '+' | '.' | '0'..='9') => self.number(),
+            Some(c) if is_identifier_start(c) => {
+                let start = self.position();
+                let word = self.identifier();
+                match word.as_str() {
+                    "true" | "false" | "null" => {
+                        self.out.push_str(&word);
+                        Ok(())
+                    }
+                    "Infinity" | "NaN" => Err(ScanError {
+                        message: format!("{} has no JSON equivalent", word),
+                        position: start,
+                    }),
+                    _ => Err(ScanError {
+                        message: format!("Unexpected '{}'", word),
+                        position: start,
+                    }),
+                }
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<(), ScanError>,
+    ) -> Result<(), ScanError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Nesting too deep".to_string()));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Members (or elements) up to `close`, allowing a trailing comma.
+    fn members(
+        &mut self,
+        close: char,
+        mut member: impl FnMut(&mut Self) -> Result<(), ScanError>,
+    ) -> Result<(), ScanError> {
+        self.bump();
+        self.skip()?;
+        let mut first = true;
+        while self.peek() != Some(close) {
+            if !first {
+                self.out.push(',');
+            }
+            first = false;
+            member(self)?;
+            self.skip()?;
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                    self.skip()?;
+                    if self.peek() == Some(close) {
+                        self.fix("trailingCommas");
+                    }
+                }
+                Some(c) if c == close => {}
+                _ => return Err(self.unexpected(&format!("',' or '{}'", close))),
+            }
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn object(&mut self) -> Result<(), ScanError> {
+        self.out.push('{');
+        self.members('}', |p| {
+            match p.peek() {
+                Some(quote @ ('"' | '\'')) => p.string(quote)?,
+                Some(c) if is_identifier_start(c) => {
+                    p.fix("unquotedKeys");
+                    let key = p.identifier();
+                    p.out.push_str(&Value::from(key).to_string());
+                }
+                _ => return Err(p.unexpected("a key")),
+            }
+            p.skip()?;
+            if p.peek() != Some(':') {
+                return Err(p.unexpected("':'"));
+            }
+            p.bump();
+            p.out.push(':');
+            p.skip()?;
+            p.value()
+        })?;
+        self.out.push('}');
+        Ok(())
+    }
+
+    fn array(&mut self) -> Result<(), ScanError> {
+        self.out.push('[');
+        self.members(']', |p| p.value())?;
+        self.out.push(']');
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_identifier_part) {
+            self.bump();
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    fn string(&mut self, quote: char) -> Result<(), ScanError> {
+        let start = self.position();
+        if quote == '\'' {
+            self.fix("singleQuotes");
+        }
+        self.bump();
+        self.out.push('"');
+        loop {
+            let Some(c) = self.bump() else {
+                return Err(ScanError {
+                    message: "Unterminated string".to_string(),
+                    position: start,
+                });
+            };
+            match c {
+                c if c == quote => break,
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.escape()?,
+                c if is_line_break(c) && c != '\u{2028}' && c != '\u{2029}' => {
+                    return Err(ScanError {
+                        message: "Unterminated string".to_string(),
+                        position: start,
+                    });
+                }
+                c if (c as u32) < 0x20 => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+        Ok(())
+    }
+
+    fn escape(&mut self) -> Result<(), ScanError> {
+        let Some(c) = self.bump() else {
+            return Err(self.error("Unterminated string".to_string()));
+        };
+        match c {
+            '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => {
+                self.out.push('\\');
+                self.out.push(c);
+            }
+            'u' => {
+                let hex = self.hex_digits(4)?;
+                self.out.push_str("\\u");
+                self.out.push_str(&hex);
+            }
+            other => {
+                self.fix("escapes");
+                match other {
+                    'x' => {
+                        let hex = self.hex_digits(2)?;
+                        self.out.push_str(&format!("\\u00{}", hex));
+                    }
+                    'v' => self.out.push_str("\\u000b"),
+                    '0' if !self.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                        self.out.push_str("\\u0000")
+                    }
+                    // A line continuation.
+                    '\r' => {
+                        if self.peek() == Some('\n') {
+                            self.bump();
+                        }
+                    }
+                    '\n' | '\u{2028}' | '\u{2029}' => {}
+                    '1'..='9' | '0' => {
+                        return Err(self.error(format!("Invalid escape '\\{}'", other)));
+                    }
+                    // Any other character stands for itself ('\'' included).
+                    c if (c as u32) < 0x20 => self.out.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => self.out.push(c),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn hex_digits(&mut self, count: usize) -> Result<String, ScanError> {
+        let mut hex = String::with_capacity(count);
+        for _ in 0..count {
+            match self.peek() {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    self.bump();
+                    hex.push(c);
+                }
+                _ => return Err(self.unexpected("a hex digit")),
+            }
+        }
+        Ok(hex)
+    }
+
+    fn number(&mut self) -> Result<(), ScanError> {
+        let start = self.position();
+        let negative = match self.peek() {
+            Some('-') => {
+                self.bump();
+                true
+            }
+            Some('+') => {
+                self.fix("numbers");
+                self.bump();
+                false
+            }
+            _ => false,
+        };
+        if self.peek().is_some_and(is_identifier_start) {
+            let word = self.identifier();
+            return Err(ScanError {
+                message: match word.as_str() {
+                    "Infinity" | "NaN" => format!("{} has no JSON equivalent", word),
+                    _ => format!("Unexpected '{}'", word),
+                },
+                position: start,
+            });
+        }
+        let sign = if negative { "-" } else { "" };
+        let rest = &self.src[self.pos..];
+        if rest.starts_with("0x") || rest.starts_with("0X") {
+            self.fix("numbers");
+            self.bump();
+            self.bump();
+            let digits = self.identifier();
+            let value = u128::from_str_radix(&digits, 16).map_err(|_| ScanError {
+                message: format!("Invalid hexadecimal number '0x{}'", digits),
+                position: start,
+            })?;
+            self.out.push_str(&format!("{}{}", sign, value));
+            return Ok(());
+        }
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while p.peek().is_some_and(|c| c.is_ascii_digit()) {
+                p.bump();
+            }
+            p.src[from..p.pos].to_string()
+        };
+        let int = digits(self);
+        let mut frac = None;
+        if self.peek() == Some('.') {
+            self.bump();
+            frac = Some(digits(self));
+        }
+        if int.is_empty() && frac.as_deref().is_none_or(str::is_empty) {
+            return Err(ScanError {
+                message: "Invalid number".to_string(),
+                position: start,
+            });
+        }
+        // JSON wants digits on both sides of the point and no leading zeros.
+        let trimmed = match int.trim_start_matches('0') {
+            "" => "0",
+            t => t,
+        };
+        if trimmed != int || frac.as_deref() == Some("") {
+            self.fix("numbers");
+        }
+        self.out.push_str(sign);
+        self.out.push_str(trimmed);
+        if let Some(frac) = frac.filter(|f| !f.is_empty()) {
+            self.out.push('.');
+            self.out.push_str(&frac);
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.bump();
+            self.out.push('e');
+            if let Some(c @ ('+' | '-')) = self.peek() {
+                self.bump();
+                self.out.push(c);
+            }
+            let exponent = digits(self);
+            if exponent.is_empty() {
+                return Err(self.unexpected("an exponent"));
+            }
+            self.out.push_str(&exponent);
+        }
+        Ok(())
+    }
+}
+
+/// Parse JSON5 text into strict JSON text (compact), with the kinds of
+/// relaxed syntax it contained.
+pub(crate) fn to_strict(text: &str) -> Result<(String, Vec<&'static str>), ScanError> {
+    let mut parser = Relaxed {
+        src: text,
+        pos: 0,
+        line: 1,
+        column: 1,
+        depth: 0,
+        out: String::with_capacity(text.len()),
+        fixes: Vec::new(),
+    };
+    parser.skip()?;
+    parser.value()?;
+    parser.skip()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected("end of input"));
+    }
+    Ok((parser.out, parser.fixes))
+}
+
+fn error_json(e: &ScanError) -> Value {
+    serde_json::json!({
+        "error": e.message,
+        "line": e.position.line,
+        "column": e.position.column,
+    })
+}
+
+/// Parse a body written in relaxed, JSON5-style syntax: comments, trailing
+/// commas, single-quoted strings, unquoted keys, hex numbers, leading or
+/// trailing decimal points, '+' signs and JavaScript string escapes.
+/// Returns JSON {json, fixes} where json is the equivalent strict JSON
+/// (pretty-printed) and fixes lists the relaxations found ("comments",
+/// "trailingCommas", "singleQuotes", "unquotedKeys", "numbers", "escapes");
+/// or {error, line, column} if the text is not valid JSON5 either, or uses
+/// Infinity or NaN.
+#[wasm_bindgen]
+pub fn json_parse_relaxed(text: &str) -> String {
+    let (strict, fixes) = match to_strict(text) {
+        Ok(parsed) => parsed,
+        Err(e) => return error_json(&e).to_string(),
+    };
+    let json = serde_json::from_str::<Value>(&strict)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or(strict);
+    serde_json::json!({ "json": json, "fixes": fixes }).to_string()
+}
+
+/// `json_validate` with details: where strict parsing fails, and whether
+/// `json_parse_relaxed` can turn the text into valid JSON.
+/// Returns JSON {valid, fixable} plus {error, line, column} when not valid.
+#[wasm_bindgen]
+pub fn json_validate_relaxed(text: &str) -> String {
+    let e = match serde_json::from_str::<Value>(text) {
+        Ok(_) => return serde_json::json!({ "valid": true, "fixable": false }).to_string(),
+        Err(e) => e,
+    };
+    serde_json::json!({
+        "valid": false,
+        "fixable": to_strict(text).is_ok(),
+        "error": e.to_string(),
+        "line": e.line(),
+        "column": e.column(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict(text: &str) -> String {
+        to_strict(text)
+            .unwrap_or_else(|e| panic!("{}", e.message))
+            .0
+    }
+
+    #[test]
+    fn test_to_strict() {
+        let text = r#"// config
+        {
+            name: 'O\'Brien "Ob"', /* inline */
+            hex: 0xFF, half: .5, whole: 5., plus: +1, neg: -0x10, exp: 1.5E+3,
+            list: [1, 2, 3,],
+            $id: 'line \
+continued\x41\v',
+        }"#;
+        assert_eq!(
+            strict(text),
+            r#"{"name":"O'Brien \"Ob\"","hex":255,"half":0.5,"whole":5,"plus":1,"neg":-16,"exp":1.5e+3,"list":[1,2,3],"$id":"line continued\u0041\u000b"}"#
+        );
+        assert_eq!(
+            to_strict(text).unwrap().1,
+            vec![
+                "comments",
+                "unquotedKeys",
+                "singleQuotes",
+                "escapes",
+                "numbers",
+                "trailingCommas"
+            ]
+        );
+        // Strict JSON passes through unchanged.
+        let json = r#"{"a":[1,-2.5e-3,"é\n",true,null],"b":{}}"#;
+        assert_eq!(to_strict(json).unwrap(), (json.to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_to_strict_errors() {
+        let error = |text: &str| {
+            let e = to_strict(text).unwrap_err();
+            (e.message, e.position.line, e.position.column)
+        };
+        assert_eq!(
+            error("{a: NaN}"),
+            ("NaN has no JSON equivalent".to_string(), 1, 5)
+        );
+        assert_eq!(
+            error("[1,\n 2 3]"),
+            ("Expected ',' or ']' but found '3'".to_string(), 2, 4)
+        );
+        assert_eq!(error("'abc"), ("Unterminated string".to_string(), 1, 1));
+        assert_eq!(error("[1,,]").0, "Expected a value but found ','");
+        assert_eq!(error("/* x").0, "Unterminated comment");
+        assert_eq!(error(&"[".repeat(200)).0, "Nesting too deep");
+    }
+
+    #[test]
+    fn test_json_parse_and_validate_relaxed() {
+        let out: Value = serde_json::from_str(&json_parse_relaxed("{a: 1, // }")).unwrap();
+        assert_eq!(out["error"], "Expected a key but reached end of input");
+        let out: Value = serde_json::from_str(&json_parse_relaxed("{a: [1,],}")).unwrap();
+        assert_eq!(out["json"], "{\n  \"a\": [\n    1\n  ]\n}");
+        assert_eq!(
+            out["fixes"],
+            serde_json::json!(["unquotedKeys", "trailingCommas"])
+        );
+
+        let check =
+            |text: &str| -> Value { serde_json::from_str(&json_validate_relaxed(text)).unwrap() };
+        assert_eq!(
+            check("[1]"),
+            serde_json::json!({"valid": true, "fixable": false})
+        );
+        let out = check("[1,]");
+        assert_eq!(
+            (out["valid"].clone(), out["fixable"].clone()),
+            (false.into(), true.into())
+        );
+        assert_eq!(out["line"], 1);
+        assert_eq!(check("[1 2]")["fixable"], false);
+    }
+}
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+use serde_json::{Number, Value};
+use wasm_bindgen::prelude::*;
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct CanonicalOptions {
+    /// RFC 8785 JSON Canonicalization Scheme: numbers as IEEE 754 doubles printed
+    /// the way JavaScript does, keys sorted by UTF-16 code units, no whitespace.
+    jcs: bool,
+    /// Indent by two spaces (ignored in JCS mode).
+    pretty: bool,
+}
+
+/// A decimal number as its significant digits (no leading or trailing zeros;
+/// empty for zero) and the position of the decimal point relative to them:
+/// 12.5 → ("125", 2), 0.007 → ("7", -2).
+struct Decimal {
+    negative: bool,
+    digits: String,
+    point: i64,
+}
+
+impl Decimal {
+    /// Parse a JSON number literal, or Rust's `{:e}` output.
+    fn parse(text: &str) -> Option<Decimal> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+            Some((m, e)) => (m, e.trim_start_matches('+').parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let all = format!("{}{}", int, frac);
+        let leading = all.len() - all.trim_start_matches('0').len();
+        let digits = all.trim_matches('0').to_string();
+        let point = (int.len() as i64 - leading as i64).checked_add(exponent)?;
+        Some(Decimal {
+            negative,
+            digits,
+            point,
+        })
+    }
+
+    /// Lay the digits out the way ECMAScript's Number::toString does: plain
+    /// notation for 1e-7 < |x| < 1e21, exponent notation otherwise.
+    fn render(&self) -> String {
+        if self.digits.is_empty() {
+            return "0".to_string();
+        }
+        let (digits, k, n) = (&self.digits, self.digits.len() as i64, self.point);
+        let body = if k <= n && n <= 21 {
+            format!("{}{}", digits, "0".repeat((n - k) as usize))
+        } else if 0 < n && n <= 21 {
+            format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+        } else if -6 < n && n <= 0 {
+            format!("0.{}{}", "0".repeat(-n as usize), digits)
+        } else {
+            let sign = if n - 1 < 0 { '-' } else { '+' };
+            let fraction = if k > 1 {
+                format!(".{}", &digits[1..])
+            } else {
+                String::new()
+            };
+            format!("{}{}e{}{}", &digits[..1], fraction, sign, (n - 1).abs())
+        };
+        if self.negative {
+            format!("-{}", body)
+        } else {
+            body
+        }
+    }
+}
+
+/// Numbers keep every digit they were written with; only the notation is made
+/// uniform (1.0 → 1, 1E2 → 100, 0.50 → 0.5). In JCS mode they go through f64,
+/// rounded correctly from the literal as RFC 8785 requires.
+fn number(n: &Number, jcs: bool) -> Result<String, String> {
+    let text = if jcs {
+        match n.to_string().parse::<f64>().ok() {
+            Some(f) if f.is_finite() => format!("{:e}", f),
+            _ => return Err(format!("Number {} is out of range for JCS", n)),
+        }
+    } else {
+        n.to_string()
+    };
+    Decimal::parse(&text)
+        .map(|d| d.render())
+        .ok_or_else(|| format!("Number {} is out of range", n))
+}
+
+struct Writer {
+    options: CanonicalOptions,
+    out: String,
+}
+
+impl Writer {
+    fn newline(&mut self, depth: usize) {
+        if self.options.pretty && !self.options.jcs {
+            self.out.push('\n');
+            self.out.push_str(&"  ".repeat(depth));
+        }
+    }
+
+    fn write(&mut self, value: &Value, depth: usize) -> Result<(), String> {
+        match value {
+            Value::Null | Value::Bool(_) | Value::String(_) => {
+                // serde_json escapes strings exactly as RFC 8785 requires.
+                self.out.push_str(&value.to_string());
+            }
+            Value::Number(n) => self.out.push_str(&number(n, self.options.jcs)?),
+            Value::Array(items) => {
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.write(item, depth + 1)?;
+                }
+                if !items.is_empty() {
+                    self.newline(depth);
+                }
+                self.out.push(']');
+            }
+            Value::Object(map) => {
+                let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+                if self.options.jcs {
+                    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                } else {
+                    entries.sort_by_key(|(key, _)| *key);
+                }
+                self.out.push('{');
+                for (i, (key, item)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.out.push_str(&Value::from(key.as_str()).to_string());
+                    self.out.push(':');
+                    if self.options.pretty && !self.options.jcs {
+                        self.out.push(' ');
+                    }
+                    self.write(item, depth + 1)?;
+                }
+                if !entries.is_empty() {
+                    self.newline(depth);
+                }
+                self.out.push('}');
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic JSON text for snapshot comparisons and signatures: object keys
+/// sorted, no insignificant whitespace (unless pretty), and numbers in one
+/// notation without losing digits.
+/// options_json: {jcs, pretty}; jcs follows RFC 8785 exactly (numbers become
+/// IEEE 754 doubles, keys sort by UTF-16 code units, always compact).
+/// Returns JSON {canonical} or {error} for invalid JSON or, in JCS mode, a
+/// number outside the double range.
+#[wasm_bindgen]
+pub fn json_canonicalize(json_str: &str, options_json: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid JSON: {}", e)),
+    };
+    let options: CanonicalOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let mut writer = Writer {
+        options,
+        out: String::with_capacity(json_str.len()),
+    };
+    match writer.write(&value, 0) {
+        Ok(()) => serde_json::json!({ "canonical": writer.out }).to_string(),
+        Err(e) => error(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(json: &str, options: &str) -> String {
+        let out: Value = serde_json::from_str(&json_canonicalize(json, options)).unwrap();
+        out["canonical"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{}", out))
+            .to_string()
+    }
+
+    #[test]
+    fn test_json_canonicalize() {
+        assert_eq!(
+            canonical(
+                r#"{"b": [1.0, 1E2, 0.50, -0, 1e-7], "a": {"y": null, "x": "é\n"}}"#,
+                "{}"
+            ),
+            r#"{"a":{"x":"é\n","y":null},"b":[1,100,0.5,0,1e-7]}"#
+        );
+        #[cfg(feature = "precise_numbers")]
+        assert_eq!(
+            canonical("12345678901234567890.10", "{}"),
+            "12345678901234567890.1"
+        );
+        assert_eq!(
+            canonical(r#"{"b": [], "a": [{}, 2]}"#, r#"{"pretty": true}"#),
+            "{\n  \"a\": [\n    {},\n    2\n  ],\n  \"b\": []\n}"
+        );
+        assert!(
+            serde_json::from_str::<Value>(&json_canonicalize("{", "{}")).unwrap()["error"]
+                .is_string()
+        );
+    }
+
+    #[test]
+    fn test_json_canonicalize_jcs() {
+        let jcs = r#"{"jcs": true, "pretty": true}"#;
+        // Examples from RFC 8785 section 3.2.2 and appendix B.
+        assert_eq!(
+            canonical(
+                r#"{"numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001, 1e21, 1e-7, 0.000001, -0, 9007199254740993]}"#,
+                jcs
+            ),
+            r#"{"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27,1e+21,1e-7,0.000001,0,9007199254740992]}"#
+        );
+        assert_eq!(
+            canonical(
+                r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7}"#,
+                jcs
+            ),
+            "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":5,\"\u{fb33}\":3}"
+        );
+        let out: Value = serde_json::from_str(&json_canonicalize("1e400", jcs)).unwrap();
+        assert!(out["error"].is_string());
+        // Without precise numbers 1e400 is already rejected while parsing.
+        #[cfg(feature = "precise_numbers")]
+        assert!(
+            out["error"]
+                .as_str()
+                .unwrap()
+                .ends_with("out of range for JCS")
+        );
+    }
+}
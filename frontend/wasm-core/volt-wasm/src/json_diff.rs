@@ -0,0 +1,499 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{Cancelled, cancelled_json, checkpoint};
+use crate::compare::{CompareOptions, child_path, json_matches, wildcard_path};
+use crate::openapi::escape_pointer;
+
+/// Arrays are aligned element by element (longest common subsequence) up to
+/// this many element comparisons; larger arrays are compared by position.
+const MAX_ALIGN_CELLS: usize = 4_000_000;
+/// Values in the summary are cut to this many characters.
+const SUMMARY_VALUE_LEN: usize = 60;
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct DiffOptions {
+    /// Arrays are compared as multisets: reordering is not a change.
+    ignore_array_order: bool,
+    /// Paths not compared, in the `a.b[0].c` syntax with `[*]` for any index.
+    ignore: Vec<String>,
+}
+
+/// Numbers compare by value, as in `json_compare`.
+fn same(a: &Value, b: &Value) -> bool {
+    json_matches(a, b, &CompareOptions::default())
+}
+
+/// One step of aligning two arrays.
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    /// Equal elements a[i] and b[j].
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Align two arrays so that as many equal elements as possible stay in place.
+fn align(a: &[Value], b: &[Value]) -> Result<Vec<Step>, Cancelled> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| same(x, y)).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| same(x, y))
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut steps: Vec<Step> = (0..prefix).map(|i| Step::Keep(i, i)).collect();
+    let (n, m) = (a_mid.len(), b_mid.len());
+    if n.checked_mul(m)
+        .is_some_and(|cells| cells <= MAX_ALIGN_CELLS)
+    {
+        // lengths[i][j]: longest common subsequence of a_mid[i..] and b_mid[j..].
+        let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            checkpoint()?;
+            for j in (0..m).rev() {
+                lengths[i][j] = if same(&a_mid[i], &b_mid[j]) {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && same(&a_mid[i], &b_mid[j]) {
+                steps.push(Step::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lengths[i][j + 1] >= lengths[i + 1][j]) {
+                steps.push(Step::Insert(prefix + j));
+                j += 1;
+            } else {
+                steps.push(Step::Delete(prefix + i));
+                i += 1;
+            }
+        }
+    } else {
+        steps.extend((0..n).map(|i| Step::Delete(prefix + i)));
+        steps.extend((0..m).map(|j| Step::Insert(prefix + j)));
+    }
+    steps.extend((0..suffix).map(|k| Step::Keep(a.len() - suffix + k, b.len() - suffix + k)));
+    Ok(steps)
+}
+
+/// Which element of `a` a position of `b` comes from while patching an array.
+#[derive(Clone, Copy, PartialEq)]
+enum Slot {
+    Old(usize),
+    New(usize),
+}
+
+struct Differ<'o> {
+    options: &'o DiffOptions,
+    patch: Vec<Value>,
+    summary: Vec<String>,
+    /// Set once the abort handle fires; no further changes are looked for.
+    cancelled: bool,
+}
+
+impl Differ<'_> {
+    fn ignored(&self, path: &str) -> bool {
+        let generic = wildcard_path(path);
+        self.options
+            .ignore
+            .iter()
+            .any(|p| p == path || *p == generic)
+    }
+
+    fn op(&mut self, op: &str, pointer: &str, value: Option<&Value>, from: Option<&str>) {
+        let mut entry = serde_json::json!({ "op": op, "path": pointer });
+        if let Some(from) = from {
+            entry["from"] = Value::from(from);
+        }
+        if let Some(value) = value {
+            entry["value"] = value.clone();
+        }
+        self.patch.push(entry);
+    }
+
+    fn add(&mut self, pointer: &str, value: &Value) {
+        self.summary
+            .push(format!("Added {}: {}", display(pointer), short(value)));
+        self.op("add", pointer, Some(value), None);
+    }
+
+    fn remove(&mut self, pointer: &str, old: &Value) {
+        self.summary
+            .push(format!("Removed {} (was {})", display(pointer), short(old)));
+        self.op("remove", pointer, None, None);
+    }
+
+    fn moved(&mut self, from: &str, pointer: &str) {
+        self.summary
+            .push(format!("Moved {} to {}", display(from), display(pointer)));
+        self.op("move", pointer, None, Some(from));
+    }
+
+    /// Changes turning `a` into `b`, at `pointer` (JSON Pointer into the patched
+    /// document) and `path` (`a.b[0]` syntax, for ignore rules).
+    fn diff(&mut self, a: &Value, b: &Value, pointer: &str, path: &str) {
+        if self.cancelled || checkpoint().is_err() {
+            self.cancelled = true;
+            return;
+        }
+        if self.ignored(path) || same(a, b) {
+            return;
+        }
+        match (a, b) {
+            (Value::Object(a), Value::Object(b)) => self.objects(a, b, pointer, path),
+            (Value::Array(a), Value::Array(b)) if self.options.ignore_array_order => {
+                self.unordered(a, b, pointer, path)
+            }
+            (Value::Array(a), Value::Array(b)) => self.arrays(a, b, pointer, path),
+            _ => {
+                self.summary.push(format!(
+                    "Changed {}: {} → {}",
+                    display(pointer),
+                    short(a),
+                    short(b)
+                ));
+                self.op("replace", pointer, Some(b), None);
+            }
+        }
+    }
+
+    fn objects(
+        &mut self,
+        a: &Map<String, Value>,
+        b: &Map<String, Value>,
+        pointer: &str,
+        path: &str,
+    ) {
+        let at = |key: &str| format!("{}/{}", pointer, escape_pointer(key));
+        let mut removed: Vec<&String> = a
+            .keys()
+            .filter(|k| !b.contains_key(*k) && !self.ignored(&child_path(path, k)))
+            .collect();
+        let mut added: Vec<&String> = b
+            .keys()
+            .filter(|k| !a.contains_key(*k) && !self.ignored(&child_path(path, k)))
+            .collect();
+        for (key, old) in a.iter().filter(|(k, _)| b.contains_key(*k)) {
+            self.diff(old, &b[key], &at(key), &child_path(path, key));
+        }
+        // A value that disappeared under one key and appeared under another was renamed.
+        let mut i = 0;
+        while i < removed.len() {
+            let from = removed[i];
+            match added.iter().position(|to| same(&a[from], &b[*to])) {
+                Some(j) => {
+                    let to = added.remove(j);
+                    removed.remove(i);
+                    self.moved(&at(from), &at(to));
+                }
+                None => i += 1,
+            }
+        }
+        for key in removed {
+            self.remove(&at(key), &a[key]);
+        }
+        for key in added {
+            self.add(&at(key), &b[key]);
+        }
+    }
+
+    fn arrays(&mut self, a: &[Value], b: &[Value], pointer: &str, path: &str) {
+        let Ok(steps) = align(a, b) else {
+            self.cancelled = true;
+            return;
+        };
+        let mut deleted: Vec<usize> = Vec::new();
+        let mut inserted: Vec<usize> = Vec::new();
+        for step in &steps {
+            match *step {
+                Step::Delete(i) => deleted.push(i),
+                Step::Insert(j) => inserted.push(j),
+                Step::Keep(..) => {}
+            }
+        }
+        // Where each element of `b` comes from: kept or moved elements of `a`,
+        // elements of `a` changed in place, or new values.
+        let mut target: Vec<Slot> = vec![Slot::New(0); b.len()];
+        let mut changed: Vec<(usize, usize)> = Vec::new();
+        for step in &steps {
+            if let Step::Keep(i, j) = *step {
+                target[j] = Slot::Old(i);
+            }
+        }
+        // An equal value deleted in one place and inserted in another moved; then
+        // the remaining ones of the same type pair up, in order, as changes.
+        let kind = std::mem::discriminant::<Value>;
+        for moved in [true, false] {
+            let mut k = 0;
+            while k < inserted.len() {
+                let j = inserted[k];
+                let found = deleted.iter().position(|&i| {
+                    if moved {
+                        same(&a[i], &b[j])
+                    } else {
+                        kind(&a[i]) == kind(&b[j])
+                    }
+                });
+                match found {
+                    Some(d) => {
+                        let i = deleted.remove(d);
+                        target[j] = Slot::Old(i);
+                        if !moved {
+                            changed.push((i, j));
+                        }
+                        inserted.remove(k);
+                    }
+                    None => k += 1,
+                }
+            }
+        }
+        for &j in &inserted {
+            target[j] = Slot::New(j);
+        }
+
+        let mut current: Vec<Slot> = (0..a.len()).map(Slot::Old).collect();
+        for &i in deleted.iter().rev() {
+            let at = current.iter().position(|s| *s == Slot::Old(i)).unwrap_or(i);
+            current.remove(at);
+            self.remove(&format!("{}/{}", pointer, at), &a[i]);
+        }
+        for (t, slot) in target.iter().enumerate() {
+            let here = format!("{}/{}", pointer, t);
+            if current.get(t) != Some(slot) {
+                match *slot {
+                    Slot::Old(_) => {
+                        let from = current.iter().position(|s| s == slot).unwrap_or(t);
+                        current.remove(from);
+                        current.insert(t, *slot);
+                        self.moved(&format!("{}/{}", pointer, from), &here);
+                    }
+                    Slot::New(j) => {
+                        current.insert(t, *slot);
+                        if !self.ignored(&format!("{}[{}]", path, t)) {
+                            self.add(&here, &b[j]);
+                        }
+                    }
+                }
+            }
+            if let Slot::Old(i) = *slot
+                && let Some(&(_, j)) = changed.iter().find(|(c, _)| *c == i)
+            {
+                self.diff(&a[i], &b[j], &here, &format!("{}[{}]", path, t));
+            }
+        }
+    }
+
+    /// Arrays as multisets: unmatched elements of `a` change into unmatched
+    /// elements of `b` in order; the rest are removed or appended.
+    fn unordered(&mut self, a: &[Value], b: &[Value], pointer: &str, path: &str) {
+        let mut used = vec![false; a.len()];
+        let mut extra: Vec<usize> = Vec::new();
+        for (j, value) in b.iter().enumerate() {
+            if checkpoint().is_err() {
+                self.cancelled = true;
+                return;
+            }
+            match (0..a.len()).find(|&i| !used[i] && same(&a[i], value)) {
+                Some(i) => used[i] = true,
+                None => extra.push(j),
+            }
+        }
+        let missing: Vec<usize> = (0..a.len()).filter(|&i| !used[i]).collect();
+        let pairs = missing.len().min(extra.len());
+        for (&i, &j) in missing.iter().zip(&extra).take(pairs) {
+            self.diff(
+                &a[i],
+                &b[j],
+                &format!("{}/{}", pointer, i),
+                &format!("{}[{}]", path, i),
+            );
+        }
+        for &i in missing[pairs..].iter().rev() {
+            if !self.ignored(&format!("{}[{}]", path, i)) {
+                self.remove(&format!("{}/{}", pointer, i), &a[i]);
+            }
+        }
+        for &j in &extra[pairs..] {
+            self.summary.push(format!(
+                "Added {}: {}",
+                display(&format!("{}/{}", pointer, j)),
+                short(&b[j])
+            ));
+            self.op("add", &format!("{}/-", pointer), Some(&b[j]), None);
+        }
+    }
+}
+
+fn display(pointer: &str) -> &str {
+    if pointer.is_empty() { "/" } else { pointer }
+}
+
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > SUMMARY_VALUE_LEN {
+        format!(
+            "{}…",
+            text.chars().take(SUMMARY_VALUE_LEN).collect::<String>()
+        )
+    } else {
+        text
+    }
+}
+
+/// Compare two JSON documents structurally, e.g. a response across environments
+/// or against an earlier run from history.
+/// options_json: {ignoreArrayOrder, ignore: [path]}; paths use the `a.b[0].c`
+/// syntax with `[*]` matching any index, and ignoring a path ignores everything
+/// under it. Numbers compare by value and object key order is not a change.
+/// Returns JSON {equal, patch, summary: [line], counts: {add, remove, replace,
+/// move}} where patch is an RFC 6902 JSON Patch that turns a into b (ignored
+/// paths aside); or {error} if either document is invalid.
+#[wasm_bindgen]
+pub fn json_diff(a_json: &str, b_json: &str, options_json: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let a: Value = match serde_json::from_str(a_json) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid JSON (a): {}", e)),
+    };
+    let b: Value = match serde_json::from_str(b_json) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid JSON (b): {}", e)),
+    };
+    let options: DiffOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let _timer = crate::logging::timer("json_diff");
+    let mut differ = Differ {
+        options: &options,
+        patch: Vec::new(),
+        summary: Vec::new(),
+        cancelled: false,
+    };
+    differ.diff(&a, &b, "", "");
+    if differ.cancelled {
+        return cancelled_json();
+    }
+    let count = |op: &str| differ.patch.iter().filter(|p| p["op"] == op).count();
+    serde_json::json!({
+        "equal": differ.patch.is_empty(),
+        "counts": {
+            "add": count("add"),
+            "remove": count("remove"),
+            "replace": count("replace"),
+            "move": count("move"),
+        },
+        "patch": differ.patch,
+        "summary": differ.summary,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(a: &str, b: &str) -> Value {
+        let out: Value = serde_json::from_str(&json_diff(a, b, "{}")).unwrap();
+        let (a, b): (Value, Value) = (
+            serde_json::from_str(a).unwrap(),
+            serde_json::from_str(b).unwrap(),
+        );
+        let patched = crate::json_patch::apply_patch(&a, &out["patch"]).unwrap();
+        assert!(same(&patched, &b), "{}", out["patch"]);
+        out
+    }
+
+    #[test]
+    fn test_json_diff_objects() {
+        let out = roundtrip(
+            r#"{"id": 1, "name": "Ann", "old": true, "nested": {"x": 1, "y": [1]}, "a/b": 2}"#,
+            r#"{"id": 1.0, "name": "Bo", "renamed": true, "nested": {"x": 1, "y": [1, 2]}, "a/b": 3}"#,
+        );
+        assert_eq!(out["equal"], false);
+        assert_eq!(
+            out["patch"],
+            serde_json::json!([
+                {"op": "replace", "path": "/name", "value": "Bo"},
+                {"op": "add", "path": "/nested/y/1", "value": 2},
+                {"op": "replace", "path": "/a~1b", "value": 3},
+                {"op": "move", "path": "/renamed", "from": "/old"},
+            ])
+        );
+        assert_eq!(out["summary"][0], "Changed /name: \"Ann\" → \"Bo\"");
+        assert_eq!(out["counts"]["replace"], 2);
+        assert_eq!(roundtrip("[1]", r#"{"a": 1}"#)["patch"][0]["path"], "");
+        assert_eq!(roundtrip(r#"{"a": [1]}"#, r#"{"a": [1.0]}"#)["equal"], true);
+    }
+
+    #[test]
+    fn test_json_diff_arrays() {
+        let out = roundtrip("[1, 2, 3, 4, 5]", "[1, 3, 4, 5]");
+        assert_eq!(
+            out["patch"],
+            serde_json::json!([{"op": "remove", "path": "/1"}])
+        );
+        let out = roundtrip("[1, 2, 3, 4]", "[4, 1, 2, 3]");
+        assert_eq!(
+            out["patch"],
+            serde_json::json!([{"op": "move", "path": "/0", "from": "/3"}])
+        );
+        let out = roundtrip(
+            r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, 7]"#,
+            r#"[0, {"id": 1, "v": "z"}, {"id": 2, "v": "b"}]"#,
+        );
+        assert_eq!(
+            out["patch"],
+            serde_json::json!([
+                {"op": "move", "path": "/0", "from": "/2"},
+                {"op": "replace", "path": "/0", "value": 0},
+                {"op": "replace", "path": "/1/v", "value": "z"},
+            ])
+        );
+        roundtrip("[1, 2, 3, 4, 5, 6]", "[6, 5, 9, 4, 3, 2, 1, 8]");
+        roundtrip(r#"["a", "b", ["c"]]"#, r#"[["c", "d"], "a", "x"]"#);
+    }
+
+    #[test]
+    fn test_json_diff_options() {
+        let diff = |a: &str, b: &str, options: &str| -> Value {
+            serde_json::from_str(&json_diff(a, b, options)).unwrap()
+        };
+        let unordered = r#"{"ignoreArrayOrder": true}"#;
+        assert_eq!(diff("[1, 2, 2]", "[2, 1, 2]", unordered)["equal"], true);
+        let out = diff(r#"{"t": [1, 2, 3]}"#, r#"{"t": [3, 4, 1, 5]}"#, unordered);
+        assert_eq!(
+            out["patch"],
+            serde_json::json!([
+                {"op": "replace", "path": "/t/1", "value": 4},
+                {"op": "add", "path": "/t/-", "value": 5},
+            ])
+        );
+        let ignore = r#"{"ignore": ["meta", "items[*].at"]}"#;
+        let out = diff(
+            r#"{"meta": {"t": 1}, "items": [{"id": 1, "at": 5}]}"#,
+            r#"{"items": [{"id": 1, "at": 6}]}"#,
+            ignore,
+        );
+        assert_eq!(out["equal"], true);
+        assert!(diff("{", "{}", "")["error"].is_string());
+    }
+
+    #[test]
+    fn test_json_diff_stops_when_cancelled() {
+        let handle = crate::cancel::AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(
+            json_diff(r#"{"a": 1}"#, r#"{"a": 2}"#, "{}"),
+            cancelled_json()
+        );
+        handle.deactivate();
+        assert!(json_diff(r#"{"a": 1}"#, r#"{"a": 2}"#, "{}").contains("replace"));
+    }
+}
@@ -0,0 +1,219 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Characters of the error line shown around the error position.
+const SNIPPET_WIDTH: usize = 80;
+
+/// Byte offset of the start of each line.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Byte offset of the error: serde_json counts columns in bytes and points at
+/// the offending byte, or at the last byte read when the input ended early.
+fn error_offset(text: &str, e: &serde_json::Error, starts: &[usize]) -> usize {
+    if e.classify() == serde_json::error::Category::Eof {
+        return text.len();
+    }
+    let start = starts.get(e.line().saturating_sub(1)).copied().unwrap_or(0);
+    let mut offset = (start + e.column().saturating_sub(1)).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// 1-based line and character column of a byte offset.
+fn line_column(text: &str, starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = starts.partition_point(|&s| s <= offset);
+    let start = starts[line - 1];
+    (line, text[start..offset].chars().count() + 1)
+}
+
+/// The innermost bracket still open at the end of the text, by byte offset.
+fn unclosed(text: &str) -> Option<usize> {
+    let mut stack = Vec::new();
+    let (mut in_string, mut escaped) = (false, false);
+    for (i, b) in text.bytes().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            _ if in_string => {}
+            b'{' | b'[' => stack.push(i),
+            b'}' | b']' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    stack.pop()
+}
+
+/// Short description of a parse error, looking at the text where it happened.
+fn describe(text: &str, e: &serde_json::Error, offset: usize) -> &'static str {
+    let message = e.to_string();
+    let at = text[offset..].chars().next();
+    let before = text[..offset].trim_end().chars().last();
+    match message.split(" at line ").next().unwrap_or("") {
+        "trailing comma" => "trailing comma",
+        "key must be a string" => match at {
+            Some('\'') => "single-quoted key",
+            Some(c) if c.is_alphabetic() || c == '_' || c == '$' => "unquoted key",
+            Some('}') if before == Some(',') => "trailing comma",
+            _ => "key must be a string",
+        },
+        "EOF while parsing an object" => "missing closing brace",
+        "EOF while parsing a list" => "missing closing bracket",
+        "EOF while parsing a string" => "unterminated string",
+        "EOF while parsing a value" if text.trim().is_empty() => "empty input",
+        "EOF while parsing a value" => "missing value",
+        "expected `:`" => "missing colon",
+        "expected `,` or `}`" if at == Some(']') => "mismatched bracket",
+        "expected `,` or `]`" if at == Some('}') => "mismatched brace",
+        "expected `,` or `}`" | "expected `,` or `]`" => "missing comma",
+        "expected value" => match at {
+            Some('\'') => "single-quoted string",
+            Some('/') => "comment",
+            Some(']' | '}') if before == Some(',') => "trailing comma",
+            Some(c) if c.is_alphabetic() => "invalid literal",
+            _ => "unexpected character",
+        },
+        "expected ident" => "invalid literal",
+        "invalid number" | "number out of range" => "invalid number",
+        "invalid escape"
+        | "invalid unicode code point"
+        | "lone leading surrogate in hex escape" => "invalid escape",
+        m if m.starts_with("control character") => "unescaped control character",
+        "trailing characters" => "unexpected text after the value",
+        _ => "invalid JSON",
+    }
+}
+
+/// The error's line, cut to a window around the error, with the error's
+/// 1-based character column within it.
+fn snippet(text: &str, starts: &[usize], line: usize, column: usize) -> (String, usize) {
+    let start = starts[line - 1];
+    let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+    let chars: Vec<char> = text[start..end].trim_end_matches('\r').chars().collect();
+    let from = column
+        .saturating_sub(SNIPPET_WIDTH / 2)
+        .min(chars.len().saturating_sub(SNIPPET_WIDTH));
+    let to = (from + SNIPPET_WIDTH).min(chars.len());
+    (chars[from..to].iter().collect(), column - from)
+}
+
+/// Validate JSON and, when invalid, say exactly where and what is wrong so
+/// the body editor can underline it.
+/// Returns JSON {valid: true}, or {valid: false, line, column, offset,
+/// description, message, snippet, snippetColumn, fixable, openedLine?,
+/// openedColumn?}. line and column are 1-based (column in characters), offset
+/// is in bytes; description is short ("trailing comma", "unquoted key",
+/// "missing closing brace", ...) and message is the parser's own; snippet is
+/// the error line (up to 80 characters around the error). Trailing commas
+/// point at the comma, and missing closing brackets report where the unclosed
+/// one was opened. fixable: `json_parse_relaxed` can repair the text.
+#[wasm_bindgen]
+pub fn json_validate_detailed(json_str: &str) -> String {
+    let e = match serde_json::from_str::<Value>(json_str) {
+        Ok(_) => return serde_json::json!({ "valid": true }).to_string(),
+        Err(e) => e,
+    };
+    let starts = line_starts(json_str);
+    let mut offset = error_offset(json_str, &e, &starts);
+    let description = describe(json_str, &e, offset);
+    if description == "trailing comma"
+        && let Some(comma) = json_str[..offset].rfind(',')
+    {
+        offset = comma;
+    }
+    let (line, column) = line_column(json_str, &starts, offset);
+    let (snippet, snippet_column) = snippet(json_str, &starts, line, column);
+    let message = e.to_string();
+    let mut result = serde_json::json!({
+        "valid": false,
+        "line": line,
+        "column": column,
+        "offset": offset,
+        "description": description,
+        "message": message.split(" at line ").next().unwrap_or(&message),
+        "snippet": snippet,
+        "snippetColumn": snippet_column,
+        "fixable": crate::json5::to_strict(json_str).is_ok(),
+    });
+    if matches!(
+        description,
+        "missing closing brace" | "missing closing bracket"
+    ) && let Some(opened) = unclosed(json_str)
+    {
+        let (line, column) = line_column(json_str, &starts, opened);
+        result["openedLine"] = line.into();
+        result["openedColumn"] = column.into();
+    }
+    result.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(text: &str) -> Value {
+        serde_json::from_str(&json_validate_detailed(text)).unwrap()
+    }
+
+    #[test]
+    fn test_json_validate_detailed() {
+        assert_eq!(detail(r#"{"a": [1]}"#), serde_json::json!({"valid": true}));
+
+        let out = detail("{\n  \"é\": 1,\n  \"b\": 2,\n}");
+        assert_eq!(out["description"], "trailing comma");
+        assert_eq!(
+            (out["line"].clone(), out["column"].clone()),
+            (3.into(), 9.into())
+        );
+        assert_eq!(out["offset"], 21);
+        assert_eq!(out["snippet"], "  \"b\": 2,");
+        assert_eq!(out["snippetColumn"], 9);
+        assert_eq!(out["fixable"], true);
+
+        let out = detail("{\"a\": {\"b\": [1, 2]\n");
+        assert_eq!(out["description"], "missing closing brace");
+        assert_eq!(
+            (out["line"].clone(), out["column"].clone()),
+            (2.into(), 1.into())
+        );
+        assert_eq!(
+            (out["openedLine"].clone(), out["openedColumn"].clone()),
+            (1.into(), 7.into())
+        );
+
+        for (text, description) in [
+            ("{a: 1}", "unquoted key"),
+            ("{'a': 1}", "single-quoted key"),
+            ("[1 2]", "missing comma"),
+            ("{\"a\" 1}", "missing colon"),
+            ("[1}", "mismatched brace"),
+            ("['x']", "single-quoted string"),
+            ("// c\n{}", "comment"),
+            ("[NaN]", "invalid literal"),
+            ("[01]", "invalid number"),
+            ("\"ab", "unterminated string"),
+            ("{} x", "unexpected text after the value"),
+            ("  ", "empty input"),
+        ] {
+            assert_eq!(detail(text)["description"], description, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_snippet_window() {
+        let text = format!("[{}x]", "1, ".repeat(100));
+        let out = detail(&text);
+        let snippet = out["snippet"].as_str().unwrap();
+        assert_eq!(snippet.chars().count(), SNIPPET_WIDTH);
+        let column = out["snippetColumn"].as_u64().unwrap() as usize;
+        assert_eq!(snippet.chars().nth(column - 1), Some('x'));
+    }
+}
@@ -0,0 +1,314 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::compare::{CompareOptions, json_matches};
+
+/// Split a JSON Pointer (RFC 6901) into unescaped reference tokens.
+fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("Invalid pointer: {}", pointer));
+    };
+    rest.split('/')
+        .map(|token| {
+            let mut out = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => out.push('~'),
+                        Some('1') => out.push('/'),
+                        _ => return Err(format!("Invalid escape in pointer: {}", pointer)),
+                    },
+                    c => out.push(c),
+                }
+            }
+            Ok(out)
+        })
+        .collect()
+}
+
+/// An array index token: digits without leading zeros, below `len`.
+fn index(token: &str, len: usize, pointer: &str) -> Result<usize, String> {
+    let digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return Err(format!("Invalid array index in {}", pointer));
+    }
+    match token.parse() {
+        Ok(i) if i < len => Ok(i),
+        _ => Err(format!("Index out of bounds: {}", pointer)),
+    }
+}
+
+fn get_mut<'v>(
+    mut doc: &'v mut Value,
+    tokens: &[String],
+    pointer: &str,
+) -> Result<&'v mut Value, String> {
+    for token in tokens {
+        doc = match doc {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => {
+                let i = index(token, items.len(), pointer)?;
+                items.get_mut(i)
+            }
+            _ => None,
+        }
+        .ok_or_else(|| format!("Path not found: {}", pointer))?;
+    }
+    Ok(doc)
+}
+
+fn get<'v>(doc: &'v mut Value, pointer: &str) -> Result<&'v mut Value, String> {
+    get_mut(doc, &tokens(pointer)?, pointer)
+}
+
+fn add(doc: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    let tokens = tokens(pointer)?;
+    let Some((last, head)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match get_mut(doc, head, pointer)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(items) => {
+            let i = match last.as_str() {
+                "-" => items.len(),
+                token => index(token, items.len() + 1, pointer)?,
+            };
+            items.insert(i, value);
+        }
+        _ => return Err(format!("Path not found: {}", pointer)),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, pointer: &str) -> Result<Value, String> {
+    let tokens = tokens(pointer)?;
+    let Some((last, head)) = tokens.split_last() else {
+        return Err("Cannot remove the whole document".to_string());
+    };
+    match get_mut(doc, head, pointer)? {
+        Value::Object(map) => map.shift_remove(last),
+        Value::Array(items) => {
+            let i = index(last, items.len(), pointer)?;
+            Some(items.remove(i))
+        }
+        _ => None,
+    }
+    .ok_or_else(|| format!("Path not found: {}", pointer))
+}
+
+/// Apply one operation of a JSON Patch.
+fn apply_op(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let member = |name: &str| -> Result<&str, String> {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Missing \"{}\"", name))
+    };
+    let value = || -> Result<Value, String> {
+        op.get("value")
+            .cloned()
+            .ok_or_else(|| "Missing \"value\"".to_string())
+    };
+    let path = member("path")?;
+    match member("op")? {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            *get(doc, path)? = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = member("from")?;
+            if from == path {
+                return get(doc, from).map(drop);
+            }
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(format!("Cannot move {} into itself", from));
+            }
+            let moved = remove(doc, from)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let copied = get(doc, member("from")?)?.clone();
+            add(doc, path, copied)
+        }
+        "test" => {
+            if json_matches(get(doc, path)?, &value()?, &CompareOptions::default()) {
+                Ok(())
+            } else {
+                Err(format!("Test failed at {}", path))
+            }
+        }
+        other => Err(format!("Unknown op: {}", other)),
+    }
+}
+
+/// Apply a JSON Patch (RFC 6902). The patch is atomic: on error `doc` is left as
+/// it was and the index of the failing operation is returned.
+pub(crate) fn apply_patch(doc: &Value, patch: &Value) -> Result<Value, (usize, String)> {
+    let Some(ops) = patch.as_array() else {
+        return Err((0, "Patch must be an array of operations".to_string()));
+    };
+    let mut out = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_op(&mut out, op).map_err(|e| (i, e))?;
+    }
+    Ok(out)
+}
+
+/// Apply a JSON Merge Patch (RFC 7386): objects merge recursively, null removes
+/// a member and anything else replaces the target.
+pub(crate) fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(map) = target else {
+        return;
+    };
+    for (key, value) in members {
+        if value.is_null() {
+            map.shift_remove(key);
+        } else {
+            merge_patch(map.entry(key.as_str()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn parse(json: &str, what: &str) -> Result<Value, String> {
+    serde_json::from_str(json).map_err(|e| {
+        serde_json::json!({ "error": format!("Invalid JSON ({}): {}", what, e) }).to_string()
+    })
+}
+
+/// Apply an RFC 6902 JSON Patch ([{op, path, value?, from?}], as produced by
+/// `json_diff`) to a document, e.g. to derive a mock body from a captured one.
+/// Returns JSON {result}, or {error, index} naming the first failing operation;
+/// a failing `test` op aborts the whole patch.
+#[wasm_bindgen]
+pub fn json_apply_patch(doc_json: &str, patch_json: &str) -> String {
+    let (doc, patch) = match (parse(doc_json, "document"), parse(patch_json, "patch")) {
+        (Ok(doc), Ok(patch)) => (doc, patch),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    match apply_patch(&doc, &patch) {
+        Ok(result) => serde_json::json!({ "result": result }).to_string(),
+        Err((index, e)) => serde_json::json!({ "error": e, "index": index }).to_string(),
+    }
+}
+
+/// Apply an RFC 7386 JSON Merge Patch to a document.
+/// Returns JSON {result} or {error} if either input is invalid.
+#[wasm_bindgen]
+pub fn json_merge_patch(doc_json: &str, patch_json: &str) -> String {
+    let (mut doc, patch) = match (parse(doc_json, "document"), parse(patch_json, "patch")) {
+        (Ok(doc), Ok(patch)) => (doc, patch),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    merge_patch(&mut doc, &patch);
+    serde_json::json!({ "result": doc }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patched(doc: Value, patch: Value) -> Result<Value, (usize, String)> {
+        apply_patch(&doc, &patch)
+    }
+
+    #[test]
+    fn test_json_apply_patch() {
+        let doc = json!({"a": {"b": [1, 2]}, "c~d": 1, "e/f": 2});
+        let out = patched(
+            doc.clone(),
+            json!([
+                {"op": "add", "path": "/a/b/1", "value": 9},
+                {"op": "add", "path": "/a/b/-", "value": 3},
+                {"op": "remove", "path": "/c~0d"},
+                {"op": "replace", "path": "/e~1f", "value": null},
+                {"op": "copy", "from": "/a/b", "path": "/g"},
+                {"op": "move", "from": "/a/b/0", "path": "/h"},
+                {"op": "test", "path": "/g/1", "value": 9.0},
+            ]),
+        );
+        assert_eq!(
+            out.unwrap(),
+            json!({"a": {"b": [9, 2, 3]}, "e/f": null, "g": [1, 9, 2, 3], "h": 1})
+        );
+        assert_eq!(
+            patched(doc.clone(), json!([{"op": "add", "path": "", "value": 1}])),
+            Ok(json!(1))
+        );
+
+        let err = |patch: Value| patched(doc.clone(), patch).unwrap_err();
+        assert_eq!(
+            err(json!([{"op": "test", "path": "", "value": {}}, {"op": "x"}])).0,
+            0
+        );
+        assert_eq!(
+            err(json!([{"op": "remove", "path": "/a"}, {"op": "remove", "path": "/a"}])),
+            (1, "Path not found: /a".to_string())
+        );
+        assert!(
+            err(json!([{"op": "add", "path": "/a/b/01", "value": 0}]))
+                .1
+                .contains("index")
+        );
+        assert!(
+            err(json!([{"op": "add", "path": "/a/b/3", "value": 0}]))
+                .1
+                .contains("bounds")
+        );
+        assert!(
+            err(json!([{"op": "move", "from": "/a", "path": "/a/x"}]))
+                .1
+                .contains("itself")
+        );
+        assert!(
+            err(json!([{"op": "add", "path": "/x"}]))
+                .1
+                .contains("value")
+        );
+
+        let out: Value = serde_json::from_str(&json_apply_patch(
+            r#"{"n": 1}"#,
+            r#"[{"op": "test", "path": "/n", "value": 2}]"#,
+        ))
+        .unwrap();
+        assert_eq!(out["index"], 0);
+        assert_eq!(out["error"], "Test failed at /n");
+    }
+
+    #[test]
+    fn test_json_merge_patch() {
+        let merge = |doc: &str, patch: &str| -> Value {
+            serde_json::from_str::<Value>(&json_merge_patch(doc, patch)).unwrap()["result"].clone()
+        };
+        assert_eq!(
+            merge(
+                r#"{"title": "Hi", "author": {"name": "A", "mail": "a@x"}, "tags": ["x"]}"#,
+                r#"{"title": "Hey", "author": {"mail": null}, "tags": ["y"], "n": {"a": null}}"#,
+            ),
+            json!({"title": "Hey", "author": {"name": "A"}, "tags": ["y"], "n": {}})
+        );
+        assert_eq!(merge(r#"[1]"#, r#"{"a": 1}"#), json!({"a": 1}));
+        assert_eq!(merge(r#"{"a": 1}"#, "null"), Value::Null);
+        assert!(
+            serde_json::from_str::<Value>(&json_merge_patch("{", "{}")).unwrap()["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid JSON (document)")
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::{get_json_path, get_value_type};
+
+const MAX_SUGGESTIONS: usize = 50;
+const PREVIEW_LEN: usize = 40;
+/// Nodes listed by `json_flatten_tree` when the caller sets no limit.
+const MAX_TREE_NODES: usize = 100_000;
+
+/// Suggest completions for a partially typed JSON path (dot/`[n]` syntax, as used
+/// by assertions and `json_extract`).
+/// "data.us" completes keys of `data` starting with "us"; "data." lists all keys;
+/// "items[" or "items[1" lists indices of the `items` array.
+/// Returns JSON array of {path, label, kind: "key"|"index", type, preview}.
+#[wasm_bindgen]
+pub fn suggest_json_paths(body_json: &str, partial_path: &str) -> String {
+    let body: Value = match serde_json::from_str(body_json) {
+        Ok(v) => v,
+        Err(_) => return "[]".to_string(),
+    };
+    let suggestions = suggest(&body, partial_path.trim());
+    serde_json::to_string(&suggestions).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn suggest(body: &Value, partial: &str) -> Vec<Value> {
+    let (parent_path, last) = match partial.rfind('.') {
+        Some(i) => (&partial[..i], &partial[i + 1..]),
+        None => ("", partial),
+    };
+
+    // "key[" / "key[12" → indices of that array.
+    if let Some(open) = last.find('[') {
+        let key = &last[..open];
+        let digits = last[open + 1..].trim_end_matches(']');
+        if key.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Vec::new();
+        }
+        let array_path = join(parent_path, key);
+        let found = get_json_path(body, &array_path);
+        let Some(Value::Array(items)) = found.as_deref() else {
+            return Vec::new();
+        };
+        return items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i.to_string().starts_with(digits))
+            .take(MAX_SUGGESTIONS)
+            .map(|(i, v)| {
+                entry(
+                    format!("{}[{}]", array_path, i),
+                    format!("[{}]", i),
+                    "index",
+                    v,
+                )
+            })
+            .collect();
+    }
+
+    let found = get_json_path(body, parent_path);
+    let Some(Value::Object(map)) = found.as_deref() else {
+        return Vec::new();
+    };
+    let prefix = last.to_lowercase();
+    // Keys containing path syntax need the quoted form, so never suggest them.
+    let addressable = |k: &&String| !k.contains('.') && !k.contains('[');
+    let (mut exact, mut folded): (Vec<_>, Vec<_>) = (Vec::new(), Vec::new());
+    for (key, value) in map.iter().filter(|(k, _)| addressable(k)) {
+        if key.starts_with(last) {
+            exact.push((key, value));
+        } else if key.to_lowercase().starts_with(&prefix) {
+            folded.push((key, value));
+        }
+    }
+    exact
+        .into_iter()
+        .chain(folded)
+        .take(MAX_SUGGESTIONS)
+        .map(|(k, v)| entry(join(parent_path, k), k.clone(), "key", v))
+        .collect()
+}
+
+pub(crate) fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn entry(path: String, label: String, kind: &str, value: &Value) -> Value {
+    serde_json::json!({
+        "path": path,
+        "label": label,
+        "kind": kind,
+        "type": get_value_type(value),
+        "preview": preview(value),
+    })
+}
+
+fn preview(value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        other => {
+            let s = other.to_string();
+            if s.chars().count() > PREVIEW_LEN {
+                format!("{}…", s.chars().take(PREVIEW_LEN).collect::<String>())
+            } else {
+                s
+            }
+        }
+    }
+}
+
+/// Flatten a JSON document into the rows of a tree viewer, in display order
+/// (each node followed by its descendants), so a virtual-scrolling view can
+/// render any slice without parsing the body itself.
+/// max_depth: deepest level listed, the root being 0; 0 lists every level.
+/// Nodes below the limit are not listed but still counted in `children`.
+/// max_nodes: most rows returned; 0 means 100000.
+/// Returns JSON {nodes: [{path, key, type, preview, children, depth}], count,
+/// truncated} where key is the member name, the array index, or null for the
+/// root, and paths use the `a.b[0]` syntax of `json_extract`; or {error}.
+#[wasm_bindgen]
+pub fn json_flatten_tree(json_str: &str, max_depth: usize, max_nodes: usize) -> String {
+    let body: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid JSON: {}", e) }).to_string();
+        }
+    };
+    let _timer = crate::logging::timer("json_flatten_tree");
+    let max_nodes = if max_nodes == 0 {
+        MAX_TREE_NODES
+    } else {
+        max_nodes
+    };
+    let mut nodes = Vec::new();
+    let mut truncated = false;
+    // Depth-first with an explicit stack, children pushed in reverse.
+    let mut stack: Vec<(String, Value, &Value, usize)> =
+        vec![(String::new(), Value::Null, &body, 0)];
+    while let Some((path, key, value, depth)) = stack.pop() {
+        if nodes.len() == max_nodes {
+            truncated = true;
+            break;
+        }
+        let children = match value {
+            Value::Object(map) => map.len(),
+            Value::Array(items) => items.len(),
+            _ => 0,
+        };
+        if max_depth == 0 || depth < max_depth {
+            match value {
+                Value::Object(map) => {
+                    for (k, v) in map.iter().rev() {
+                        stack.push((join(&path, k), Value::from(k.as_str()), v, depth + 1));
+                    }
+                }
+                Value::Array(items) => {
+                    for (i, v) in items.iter().enumerate().rev() {
+                        stack.push((format!("{}[{}]", path, i), Value::from(i), v, depth + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        nodes.push(serde_json::json!({
+            "path": path,
+            "key": key,
+            "type": get_value_type(value),
+            "preview": preview(value),
+            "children": children,
+            "depth": depth,
+        }));
+    }
+    serde_json::json!({
+        "count": nodes.len(),
+        "nodes": nodes,
+        "truncated": truncated,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{"data":{"users":[{"id":1,"name":"Ann"},{"id":2}],"userCount":2,"Uptime":"5d","a.b":1},"ok":true}"#;
+
+    fn paths(partial: &str) -> Vec<String> {
+        let out: Vec<Value> = serde_json::from_str(&suggest_json_paths(BODY, partial)).unwrap();
+        out.iter()
+            .map(|s| s["path"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_suggest_json_paths_keys() {
+        assert_eq!(paths(""), vec!["data", "ok"]);
+        assert_eq!(
+            paths("data."),
+            vec!["data.users", "data.userCount", "data.Uptime"]
+        );
+        assert_eq!(
+            paths("data.u"),
+            vec!["data.users", "data.userCount", "data.Uptime"]
+        );
+        assert_eq!(paths("data.users"), vec!["data.users"]);
+        assert!(paths("missing.").is_empty());
+    }
+
+    #[test]
+    fn test_suggest_json_paths_indices() {
+        assert_eq!(paths("data.users["), vec!["data.users[0]", "data.users[1]"]);
+        assert_eq!(paths("data.users[1"), vec!["data.users[1]"]);
+        assert_eq!(
+            paths("data.users[0]."),
+            vec!["data.users[0].id", "data.users[0].name"]
+        );
+    }
+
+    #[test]
+    fn test_suggest_json_paths_types_and_previews() {
+        let out: Vec<Value> = serde_json::from_str(&suggest_json_paths(BODY, "data.")).unwrap();
+        assert_eq!(out[0]["type"], "array");
+        assert_eq!(out[0]["preview"], "[2 items]");
+        assert_eq!(out[1]["type"], "number");
+        assert_eq!(out[2]["preview"], "\"5d\"");
+        assert_eq!(suggest_json_paths("nope", ""), "[]");
+    }
+
+    #[test]
+    fn test_json_flatten_tree() {
+        let tree = |depth, nodes| -> Value {
+            serde_json::from_str(&json_flatten_tree(BODY, depth, nodes)).unwrap()
+        };
+        let out = tree(0, 0);
+        let paths: Vec<&str> = out["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "",
+                "data",
+                "data.users",
+                "data.users[0]",
+                "data.users[0].id",
+                "data.users[0].name",
+                "data.users[1]",
+                "data.users[1].id",
+                "data.userCount",
+                "data.Uptime",
+                "data.a.b",
+                "ok",
+            ]
+        );
+        let users = &out["nodes"][2];
+        assert_eq!(users["key"], "users");
+        assert_eq!(users["children"], 2);
+        assert_eq!(users["depth"], 2);
+        assert_eq!(out["nodes"][3]["key"], 0);
+        assert_eq!(out["nodes"][0]["key"], Value::Null);
+        assert_eq!(out["truncated"], false);
+
+        let out = tree(1, 0);
+        assert_eq!(out["count"], 3);
+        assert_eq!(out["nodes"][1]["children"], 4);
+        let out = tree(0, 4);
+        assert_eq!(out["count"], 4);
+        assert_eq!(out["truncated"], true);
+        assert!(json_flatten_tree("{", 0, 0).contains("\"error\""));
+    }
+}
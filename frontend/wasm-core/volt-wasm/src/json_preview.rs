@@ -0,0 +1,152 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+/// Key of the entry standing in for the members cut from an object.
+const MORE_KEY: &str = "…";
+
+fn more(count: usize) -> String {
+    format!("…(+{} more)", count)
+}
+
+/// Bytes of `text` as a JSON string literal.
+fn string_size(text: &str) -> usize {
+    Value::from(text).to_string().len()
+}
+
+struct Preview {
+    /// Bytes left for the output; `usize::MAX` for no limit.
+    remaining: usize,
+    max_array_items: usize,
+    max_string_len: usize,
+}
+
+impl Preview {
+    fn spend(&mut self, bytes: usize) {
+        self.remaining = self.remaining.saturating_sub(bytes);
+    }
+
+    /// A string cut to the string limit, or to what is left of the budget.
+    fn string(&mut self, text: &str) -> Value {
+        let limit = self.max_string_len.min(self.remaining);
+        let cut = text.char_indices().nth(limit).map(|(i, _)| i);
+        let value = match cut {
+            Some(i) => format!("{}{}", &text[..i], more(text[i..].chars().count())),
+            None => text.to_string(),
+        };
+        self.spend(string_size(&value));
+        Value::String(value)
+    }
+
+    /// Children are kept in order until a limit is hit; the rest collapse
+    /// into one marker, so every kept node is at its original path.
+    fn value(&mut self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => self.string(s),
+            Value::Array(items) => {
+                self.spend(2);
+                let mut out = Vec::new();
+                for (i, item) in items.iter().enumerate() {
+                    if i == self.max_array_items || self.remaining == 0 {
+                        let marker = more(items.len() - i);
+                        self.spend(string_size(&marker) + 1);
+                        out.push(Value::String(marker));
+                        break;
+                    }
+                    out.push(self.value(item));
+                    self.spend(1);
+                }
+                Value::Array(out)
+            }
+            Value::Object(map) => {
+                self.spend(2);
+                let mut out = Map::new();
+                for (i, (key, item)) in map.iter().enumerate() {
+                    if self.remaining == 0 {
+                        let marker = more(map.len() - i);
+                        self.spend(string_size(MORE_KEY) + string_size(&marker) + 2);
+                        out.insert(MORE_KEY.to_string(), Value::String(marker));
+                        break;
+                    }
+                    self.spend(string_size(key) + 2);
+                    out.insert(key.clone(), self.value(item));
+                }
+                Value::Object(out)
+            }
+            other => {
+                self.spend(other.to_string().len());
+                other.clone()
+            }
+        }
+    }
+}
+
+/// A truncated but valid copy of a JSON document for an instant preview of a
+/// giant response. Arrays keep their first `max_array_items` items, strings
+/// their first `max_string_len` characters, and once about `max_bytes` of
+/// output is written the remaining members of each open array or object are
+/// dropped. What was cut is marked in place: "…(+N more)" ends a cut string,
+/// is the last item of a cut array, and is the value of a "…" key in a cut
+/// object. Kept values stay at their original paths, so nodes can be expanded
+/// with `json_extract`. A limit of 0 means no limit.
+/// Returns compact JSON, or an empty string for invalid JSON.
+#[wasm_bindgen]
+pub fn json_preview(
+    json_str: &str,
+    max_bytes: usize,
+    max_array_items: usize,
+    max_string_len: usize,
+) -> String {
+    let _timer = crate::logging::timer("json_preview");
+    let Ok(value) = serde_json::from_str::<Value>(json_str) else {
+        return String::new();
+    };
+    let unlimited = |n: usize| if n == 0 { usize::MAX } else { n };
+    let mut preview = Preview {
+        remaining: unlimited(max_bytes),
+        max_array_items: unlimited(max_array_items),
+        max_string_len: unlimited(max_string_len),
+    };
+    preview.value(&value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_preview_limits() {
+        let doc = r#"{"items": [1, 2, 3, 4, 5], "name": "abcdefghij", "nested": {"list": ["x", "y", "z"]}}"#;
+        assert_eq!(
+            json_preview(doc, 0, 2, 4),
+            r#"{"items":[1,2,"…(+3 more)"],"name":"abcd…(+6 more)","nested":{"list":["x","y","…(+1 more)"]}}"#
+        );
+        assert_eq!(
+            json_preview(doc, 0, 0, 0),
+            serde_json::from_str::<Value>(doc).unwrap().to_string()
+        );
+        assert_eq!(json_preview("[1,", 100, 0, 0), "");
+    }
+
+    #[test]
+    fn test_json_preview_byte_budget() {
+        let rows: Vec<Value> = (0..10_000)
+            .map(|i| serde_json::json!({"id": i, "text": "x".repeat(100)}))
+            .collect();
+        let doc = serde_json::json!({"total": 10_000, "rows": rows, "after": true}).to_string();
+        let preview = json_preview(&doc, 1_000, 0, 0);
+        assert!(preview.len() < 1_200, "{}", preview.len());
+        let value: Value = serde_json::from_str(&preview).unwrap();
+        assert_eq!(value["total"], 10_000);
+        assert_eq!(value["rows"][0]["id"], 0);
+        let rows = value["rows"].as_array().unwrap();
+        assert_eq!(
+            rows.last().unwrap(),
+            &Value::from(more(10_000 - (rows.len() - 1)))
+        );
+        assert_eq!(value[MORE_KEY], "…(+1 more)");
+
+        // A long string is cut to the budget too.
+        let preview = json_preview(&Value::from("é".repeat(5_000)).to_string(), 100, 0, 0);
+        assert!(preview.starts_with(&format!("\"{}…(+4900 more)", "é".repeat(100))));
+    }
+}
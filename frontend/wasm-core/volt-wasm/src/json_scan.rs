@@ -0,0 +1,419 @@
+use wasm_bindgen::prelude::*;
+
+use crate::logging;
+
+/// Deepest nesting of arrays and objects accepted, as in `serde_json`.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// 1-based line and column in the source text, counted in characters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct ScanError {
+    pub message: String,
+    pub position: Position,
+}
+
+/// A value in the source. `key` and `object` are set for object members;
+/// `object` identifies the enclosing object so sibling keys can be grouped.
+#[derive(Debug)]
+pub(crate) struct Node {
+    pub path: String,
+    pub key: Option<String>,
+    pub object: Option<usize>,
+    /// Where the key starts for object members, otherwise where the value starts.
+    pub position: Position,
+}
+
+/// Index every value in a JSON document with its path and source position.
+/// Unlike `serde_json`, repeated keys are all reported rather than collapsed.
+/// Paths use the `a.b[0]` syntax of `json_extract`.
+pub(crate) fn index(src: &str) -> Result<Vec<Node>, ScanError> {
+    let mut scanner = Scanner {
+        bytes: src.as_bytes(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        depth: 0,
+        objects: 0,
+        nodes: Vec::new(),
+    };
+    scanner.skip_ws();
+    let start = scanner.position();
+    scanner.nodes.push(Node {
+        path: String::new(),
+        key: None,
+        object: None,
+        position: start,
+    });
+    scanner.value("")?;
+    scanner.skip_ws();
+    if scanner.pos < scanner.bytes.len() {
+        return Err(scanner.error("Unexpected data after JSON value"));
+    }
+    Ok(scanner.nodes)
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    column: usize,
+    /// Arrays and objects currently open.
+    depth: usize,
+    objects: usize,
+    nodes: Vec<Node>,
+}
+
+impl Scanner<'_> {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn error(&self, message: &str) -> ScanError {
+        ScanError {
+            message: message.to_string(),
+            position: self.position(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if b & 0xC0 != 0x80 {
+            // Count characters, not UTF-8 continuation bytes.
+            self.column += 1;
+        }
+        Some(b)
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), ScanError> {
+        if self.peek() == Some(b) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", b as char)))
+        }
+    }
+
+    fn unexpected(&self, wanted: &str) -> ScanError {
+        match self.peek() {
+            Some(_) => {
+                let rest = String::from_utf8_lossy(&self.bytes[self.pos..]);
+                let found = rest.chars().next().unwrap_or('?');
+                self.error(&format!("Expected {} but found '{}'", wanted, found))
+            }
+            None => self.error(&format!("Expected {} but reached end of input", wanted)),
+        }
+    }
+
+    fn value(&mut self, path: &str) -> Result<(), ScanError> {
+        match self.peek() {
+            Some(open @ (b'{' | b'[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("Recursion limit exceeded"));
+                }
+                self.depth += 1;
+                let result = if open == b'{' {
+                    self.object(path)
+                } else {
+                    self.array(path)
+                };
+                self.depth -= 1;
+                result
+            }
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'-' | b'0'..=b'9') => {
+                while matches!(
+                    self.peek(),
+                    Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                ) {
+                    self.bump();
+                }
+                Ok(())
+            }
+            Some(b't') => self.literal("true"),
+            Some(b'f') => self.literal("false"),
+            Some(b'n') => self.literal("null"),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn literal(&mut self, word: &str) -> Result<(), ScanError> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            for _ in 0..word.len() {
+                self.bump();
+            }
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", word)))
+        }
+    }
+
+    fn object(&mut self, path: &str) -> Result<(), ScanError> {
+        self.expect(b'{')?;
+        let id = self.objects;
+        self.objects += 1;
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.bump();
+            return Ok(());
+        }
+        loop {
+            self.skip_ws();
+            let at = self.position();
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected("a string key"));
+            }
+            let key = self.string()?;
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            self.nodes.push(Node {
+                path: child.clone(),
+                key: Some(key),
+                object: Some(id),
+                position: at,
+            });
+            self.skip_ws();
+            self.expect(b':')?;
+            self.skip_ws();
+            self.value(&child)?;
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => {
+                    self.bump();
+                }
+                Some(b'}') => {
+                    self.bump();
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected("',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, path: &str) -> Result<(), ScanError> {
+        self.expect(b'[')?;
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.bump();
+            return Ok(());
+        }
+        let mut i = 0;
+        loop {
+            self.skip_ws();
+            let child = format!("{}[{}]", path, i);
+            self.nodes.push(Node {
+                path: child.clone(),
+                key: None,
+                object: None,
+                position: self.position(),
+            });
+            self.value(&child)?;
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => {
+                    self.bump();
+                    i += 1;
+                }
+                Some(b']') => {
+                    self.bump();
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected("',' or ']'")),
+            }
+        }
+    }
+
+    /// Parse a string literal and return its decoded contents.
+    fn string(&mut self) -> Result<String, ScanError> {
+        self.expect(b'"')?;
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("Unterminated string")),
+                Some(b'"') => break,
+                Some(b'\\') => match self.bump() {
+                    Some(b'"') => out.push(b'"'),
+                    Some(b'\\') => out.push(b'\\'),
+                    Some(b'/') => out.push(b'/'),
+                    Some(b'b') => out.push(0x08),
+                    Some(b'f') => out.push(0x0C),
+                    Some(b'n') => out.push(b'\n'),
+                    Some(b'r') => out.push(b'\r'),
+                    Some(b't') => out.push(b'\t'),
+                    Some(b'u') => {
+                        let c = self.unicode_escape()?;
+                        out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    _ => return Err(self.error("Invalid escape sequence")),
+                },
+                Some(b) if b < 0x20 => return Err(self.error("Control character in string")),
+                Some(b) => out.push(b),
+            }
+        }
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    fn hex4(&mut self) -> Result<u32, ScanError> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let d = self
+                .bump()
+                .and_then(|b| (b as char).to_digit(16))
+                .ok_or_else(|| self.error("Invalid \\u escape"))?;
+            n = n * 16 + d;
+        }
+        Ok(n)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ScanError> {
+        let high = self.hex4()?;
+        if (0xD800..0xDC00).contains(&high) && self.bytes[self.pos..].starts_with(b"\\u") {
+            self.bump();
+            self.bump();
+            let low = self.hex4()?;
+            let combined = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+            return Ok(char::from_u32(combined).unwrap_or('\u{FFFD}'));
+        }
+        Ok(char::from_u32(high).unwrap_or('\u{FFFD}'))
+    }
+}
+
+/// A repeated key: where it appears again and where it was first defined.
+pub(crate) struct DuplicateKey<'a> {
+    pub node: &'a Node,
+    pub first: Position,
+}
+
+pub(crate) fn duplicate_keys(nodes: &[Node]) -> Vec<DuplicateKey<'_>> {
+    let mut seen: std::collections::HashMap<(usize, &str), Position> =
+        std::collections::HashMap::new();
+    let mut out = Vec::new();
+    for node in nodes {
+        if let (Some(object), Some(key)) = (node.object, node.key.as_deref()) {
+            match seen.get(&(object, key)) {
+                Some(first) => out.push(DuplicateKey {
+                    node,
+                    first: *first,
+                }),
+                None => {
+                    seen.insert((object, key), node.position);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Find object keys that appear more than once in the same object
+/// (serde_json and JSON.parse silently keep only the last one).
+/// Returns JSON array of {path, key, line, column, firstLine, firstColumn},
+/// or [] if the input is not valid JSON.
+#[wasm_bindgen]
+pub fn json_find_duplicate_keys(json_str: &str) -> String {
+    let nodes = match index(json_str) {
+        Ok(n) => n,
+        Err(e) => {
+            logging::debug("json_find_duplicate_keys", || {
+                format!(
+                    "invalid JSON at line {}, column {}: {}",
+                    e.position.line, e.position.column, e.message
+                )
+            });
+            return "[]".to_string();
+        }
+    };
+    let list: Vec<serde_json::Value> = duplicate_keys(&nodes)
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "path": d.node.path,
+                "key": d.node.key,
+                "line": d.node.position.line,
+                "column": d.node.position.column,
+                "firstLine": d.first.line,
+                "firstColumn": d.first.column,
+            })
+        })
+        .collect();
+    serde_json::to_string(&list).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_index_paths_and_positions() {
+        let nodes = index("{\n  \"a\": [1, {\"b\": true}],\n  \"é\": null\n}").unwrap();
+        let paths: Vec<&str> = nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["", "a", "a[0]", "a[1]", "a[1].b", "é"]);
+        assert_eq!(nodes[1].position, Position { line: 2, column: 3 });
+        assert_eq!(
+            nodes[4].position,
+            Position {
+                line: 2,
+                column: 13
+            }
+        );
+        assert_eq!(nodes[5].position, Position { line: 3, column: 3 });
+    }
+
+    #[test]
+    fn test_index_errors() {
+        let err = index("{\"a\": 1,\n \"b\" 2}").unwrap_err();
+        assert_eq!(err.position, Position { line: 2, column: 6 });
+        assert!(err.message.contains("Expected ':'"));
+        assert!(index("[1] x").is_err());
+        assert!(index("\"open").is_err());
+
+        let deep = |n: usize| format!("{}{}", "[".repeat(n), "]".repeat(n));
+        assert!(index(&deep(MAX_DEPTH)).is_ok());
+        let err = index(&deep(5000)).unwrap_err();
+        assert_eq!(err.message, "Recursion limit exceeded");
+        assert_eq!(err.position.column, MAX_DEPTH + 1);
+        assert_eq!(json_find_duplicate_keys(&deep(5000)), "[]");
+    }
+
+    #[test]
+    fn test_json_find_duplicate_keys() {
+        let src = "{\n  \"id\": 1,\n  \"user\": {\"name\": \"a\", \"n\\u0061me\": \"b\"},\n  \"id\": 2\n}";
+        let out: Vec<Value> = serde_json::from_str(&json_find_duplicate_keys(src)).unwrap();
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0]["path"], "user.name");
+        assert_eq!(out[0]["line"], 3);
+        assert_eq!(out[0]["firstColumn"], 12);
+        assert_eq!(out[1]["path"], "id");
+        assert_eq!(out[1]["line"], 4);
+        assert_eq!(out[1]["firstLine"], 2);
+
+        // Same key in different objects is fine.
+        assert_eq!(json_find_duplicate_keys(r#"[{"a":1},{"a":2}]"#), "[]");
+        assert_eq!(json_find_duplicate_keys("not json"), "[]");
+    }
+}
@@ -0,0 +1,259 @@
+use regex_lite::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{cancelled_json, checkpoint};
+use crate::get_value_type;
+use crate::json_paths::join;
+use crate::regex_tester::Utf16Offsets;
+
+/// Characters of a matched key or value shown in its preview.
+const PREVIEW_WIDTH: usize = 80;
+/// Characters kept before the first match in a preview.
+const PREVIEW_CONTEXT: usize = 20;
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SearchOptions {
+    /// Treat the query as a regular expression instead of plain text.
+    regex: bool,
+    case_sensitive: bool,
+    /// Search object keys.
+    keys: bool,
+    /// Search scalar values (numbers, booleans and null as written in JSON).
+    values: bool,
+    max_results: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            regex: false,
+            case_sensitive: false,
+            keys: true,
+            values: true,
+            max_results: 100,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Hit {
+    path: String,
+    /// "key" or "value".
+    kind: &'static str,
+    /// Type of the value at the path.
+    #[serde(rename = "type")]
+    value_type: &'static str,
+    /// Part of the key or value around the first match.
+    preview: String,
+    /// Where the preview starts in the key or value.
+    preview_start: usize,
+    /// [start, end) of each match in the key or value.
+    matches: Vec<[usize; 2]>,
+}
+
+struct Search {
+    re: Regex,
+    options: SearchOptions,
+    hits: Vec<Hit>,
+    truncated: bool,
+    cancelled: bool,
+}
+
+impl Search {
+    /// Record a hit if `text` matches; false once the result limit is reached.
+    fn check(&mut self, path: &str, kind: &'static str, value: &Value, text: &str) -> bool {
+        let ranges: Vec<(usize, usize)> = self
+            .re
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        let Some(&(first, _)) = ranges.first() else {
+            return true;
+        };
+        if self.hits.len() == self.options.max_results {
+            self.truncated = true;
+            return false;
+        }
+        let start = text[..first]
+            .char_indices()
+            .rev()
+            .nth(PREVIEW_CONTEXT - 1)
+            .map_or(0, |(i, _)| i);
+        let end = text[start..]
+            .char_indices()
+            .nth(PREVIEW_WIDTH)
+            .map_or(text.len(), |(i, _)| start + i);
+        let mut offsets = Utf16Offsets::new(text);
+        let preview_start = offsets.at(start);
+        let matches = ranges
+            .into_iter()
+            .map(|(s, e)| [offsets.at(s), offsets.at(e)])
+            .collect();
+        self.hits.push(Hit {
+            path: path.to_string(),
+            kind,
+            value_type: get_value_type(value),
+            preview: text[start..end].to_string(),
+            preview_start,
+            matches,
+        });
+        true
+    }
+
+    /// Search keys and values in document order; false once the limit is
+    /// reached or the search is cancelled.
+    fn walk(&mut self, path: &str, value: &Value) -> bool {
+        if checkpoint().is_err() {
+            self.cancelled = true;
+            return false;
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map {
+                    let child = join(path, key);
+                    if self.options.keys && !self.check(&child, "key", item, key) {
+                        return false;
+                    }
+                    if !self.walk(&child, item) {
+                        return false;
+                    }
+                }
+                true
+            }
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .all(|(i, item)| self.walk(&format!("{}[{}]", path, i), item)),
+            Value::String(s) => !self.options.values || self.check(path, "value", value, s),
+            other => !self.options.values || self.check(path, "value", other, &other.to_string()),
+        }
+    }
+}
+
+/// Find keys and values matching a query, for "find in response" on large
+/// bodies. Paths use the `a.b[0]` syntax of `json_extract`.
+/// options_json: {regex (false), caseSensitive (false), keys (true), values
+/// (true), maxResults (100)}.
+/// Returns JSON {results: [{path, kind: "key" | "value", type, preview,
+/// previewStart, matches: [[start, end]]}], count, truncated} where offsets
+/// are UTF-16 code units into the key or value, and the preview is up to 80
+/// characters around the first match; or {error} for invalid JSON or an
+/// invalid pattern.
+#[wasm_bindgen]
+pub fn json_search(json_str: &str, query: &str, options_json: &str) -> String {
+    let _timer = crate::logging::timer("json_search");
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let options: SearchOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex_lite::escape(query)
+    };
+    let re = match RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+    {
+        Ok(re) => re,
+        Err(e) => return error(format!("Invalid pattern: {}", e)),
+    };
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid JSON: {}", e)),
+    };
+    let mut search = Search {
+        re,
+        options,
+        hits: Vec::new(),
+        truncated: false,
+        cancelled: false,
+    };
+    if !query.is_empty() {
+        search.walk("", &value);
+    }
+    if search.cancelled {
+        return cancelled_json();
+    }
+    serde_json::json!({
+        "results": search.hits,
+        "count": search.hits.len(),
+        "truncated": search.truncated,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn search(json: &str, query: &str, options: &str) -> Value {
+        serde_json::from_str(&json_search(json, query, options)).unwrap()
+    }
+
+    #[test]
+    fn test_json_search() {
+        let body = r#"{"user": {"name": "Ann User", "id": 1234, "tags": ["admin", "users"]}, "count": 12}"#;
+        let out = search(body, "USER", "{}");
+        assert_eq!(
+            out["results"],
+            json!([
+                {"path": "user", "kind": "key", "type": "object", "preview": "user", "previewStart": 0, "matches": [[0, 4]]},
+                {"path": "user.name", "kind": "value", "type": "string", "preview": "Ann User", "previewStart": 0, "matches": [[4, 8]]},
+                {"path": "user.tags[1]", "kind": "value", "type": "string", "preview": "users", "previewStart": 0, "matches": [[0, 4]]},
+            ])
+        );
+        assert_eq!(out["truncated"], false);
+
+        let out = search(body, "^12", r#"{"regex": true, "keys": false}"#);
+        let paths: Vec<&str> = out["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["user.id", "count"]);
+
+        assert_eq!(
+            search(body, "USER", r#"{"caseSensitive": true}"#)["count"],
+            0
+        );
+        let out = search(body, "u", r#"{"maxResults": 2}"#);
+        assert_eq!(
+            (out["count"].clone(), out["truncated"].clone()),
+            (json!(2), json!(true))
+        );
+        assert!(search(body, "(", r#"{"regex": true}"#)["error"].is_string());
+        assert!(search("{", "a", "")["error"].is_string());
+    }
+
+    #[test]
+    fn test_search_preview_window() {
+        let long = format!("{}needle{}", "é".repeat(100), "x".repeat(100));
+        let out = search(
+            &json!({ "text": long }).to_string(),
+            "needle",
+            r#"{"keys": false}"#,
+        );
+        let hit = &out["results"][0];
+        assert_eq!(hit["matches"], json!([[100, 106]]));
+        assert_eq!(hit["previewStart"], 100 - PREVIEW_CONTEXT);
+        let preview = hit["preview"].as_str().unwrap();
+        assert_eq!(preview.chars().count(), PREVIEW_WIDTH);
+        assert!(preview.starts_with(&"é".repeat(PREVIEW_CONTEXT)));
+    }
+
+    #[test]
+    fn test_json_search_stops_when_cancelled() {
+        let handle = crate::cancel::AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(json_search(r#"{"a": "x"}"#, "x", "{}"), cancelled_json());
+        handle.deactivate();
+        assert_eq!(search(r#"{"a": "x"}"#, "x", "{}")["count"], 1);
+    }
+}
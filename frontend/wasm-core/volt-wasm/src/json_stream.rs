@@ -0,0 +1,644 @@
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{Cancelled, cancelled_json, checkpoint};
+use crate::json_scan::{MAX_DEPTH, Position, ScanError};
+
+/// Bodies larger than this are summarised and formatted from the token stream
+/// instead of being parsed into a `Value` first.
+pub(crate) const STREAMING_THRESHOLD: usize = 8 * 1024 * 1024;
+
+/// Formatted output is handed over in pieces of about this many bytes unless
+/// the caller asks for another size.
+const DEFAULT_CHUNK: usize = 64 * 1024;
+
+/// Tokens walked between checks of the abort handle.
+const CHECKPOINT_INTERVAL: usize = 4096;
+
+/// One step through a JSON document. Keys and values are the source text as
+/// written, strings with their quotes.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Event<'a> {
+    /// `{` or `[`.
+    Open(u8),
+    /// `}` or `]`.
+    Close(u8),
+    Key(&'a str),
+    Value(&'a str),
+}
+
+/// What the grammar allows next.
+#[derive(Clone, Copy, PartialEq)]
+enum Want {
+    Value,
+    /// A value or `]` right after `[`.
+    FirstValue,
+    Key,
+    /// A key or `}` right after `{`.
+    FirstKey,
+    Colon,
+    /// `,` or the end of the enclosing container.
+    Next,
+    Done,
+}
+
+/// Why a walk ended before the end of the document.
+#[derive(Debug)]
+pub(crate) enum Stop {
+    Invalid(ScanError),
+    /// The active abort handle fired.
+    Cancelled,
+}
+
+impl From<ScanError> for Stop {
+    fn from(e: ScanError) -> Self {
+        Stop::Invalid(e)
+    }
+}
+
+/// Validate a JSON document and report it as events, in order. Containers are
+/// tracked on an explicit stack and no tree is built, so nesting is limited
+/// only by `max_depth`.
+pub(crate) fn walk(
+    src: &str,
+    max_depth: usize,
+    visit: &mut dyn FnMut(Event<'_>),
+) -> Result<(), Stop> {
+    let bytes = src.as_bytes();
+    let mut stack: Vec<u8> = Vec::new();
+    let mut want = Want::Value;
+    let mut pos = 0;
+    let mut steps: usize = 0;
+    loop {
+        steps += 1;
+        if steps.is_multiple_of(CHECKPOINT_INTERVAL) {
+            checkpoint().map_err(|Cancelled| Stop::Cancelled)?;
+        }
+        while matches!(bytes.get(pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            pos += 1;
+        }
+        let Some(&b) = bytes.get(pos) else {
+            break;
+        };
+        let after = |stack: &[u8]| {
+            if stack.is_empty() {
+                Want::Done
+            } else {
+                Want::Next
+            }
+        };
+        match want {
+            Want::Done => return Err(error(src, pos, "Unexpected data after JSON value").into()),
+            Want::Colon if b == b':' => {
+                pos += 1;
+                want = Want::Value;
+            }
+            Want::Colon => return Err(unexpected(src, pos, "':'").into()),
+            Want::Next => match (b, stack.last()) {
+                (b',', Some(b'{')) => {
+                    pos += 1;
+                    want = Want::Key;
+                }
+                (b',', _) => {
+                    pos += 1;
+                    want = Want::Value;
+                }
+                (b'}', Some(b'{')) | (b']', Some(b'[')) => {
+                    pos += 1;
+                    stack.pop();
+                    visit(Event::Close(b));
+                    want = after(&stack);
+                }
+                (_, Some(b'{')) => return Err(unexpected(src, pos, "',' or '}'").into()),
+                _ => return Err(unexpected(src, pos, "',' or ']'").into()),
+            },
+            Want::FirstKey if b == b'}' => {
+                pos += 1;
+                stack.pop();
+                visit(Event::Close(b));
+                want = after(&stack);
+            }
+            Want::FirstKey | Want::Key if b == b'"' => {
+                let end = string_end(src, pos)?;
+                visit(Event::Key(&src[pos..end]));
+                pos = end;
+                want = Want::Colon;
+            }
+            Want::FirstKey | Want::Key => return Err(unexpected(src, pos, "a string key").into()),
+            Want::FirstValue if b == b']' => {
+                pos += 1;
+                stack.pop();
+                visit(Event::Close(b));
+                want = after(&stack);
+            }
+            Want::FirstValue | Want::Value => match b {
+                b'{' | b'[' => {
+                    if stack.len() == max_depth {
+                        return Err(error(src, pos, "Recursion limit exceeded").into());
+                    }
+                    pos += 1;
+                    stack.push(b);
+                    visit(Event::Open(b));
+                    want = if b == b'{' {
+                        Want::FirstKey
+                    } else {
+                        Want::FirstValue
+                    };
+                }
+                _ => {
+                    let end = match b {
+                        b'"' => string_end(src, pos)?,
+                        b'-' | b'0'..=b'9' => number_end(src, pos)?,
+                        b't' => literal_end(src, pos, "true")?,
+                        b'f' => literal_end(src, pos, "false")?,
+                        b'n' => literal_end(src, pos, "null")?,
+                        _ => return Err(unexpected(src, pos, "a value").into()),
+                    };
+                    visit(Event::Value(&src[pos..end]));
+                    pos = end;
+                    want = after(&stack);
+                }
+            },
+        }
+    }
+    match want {
+        Want::Done => Ok(()),
+        Want::Colon => Err(unexpected(src, pos, "':'").into()),
+        Want::FirstKey | Want::Key => Err(unexpected(src, pos, "a string key").into()),
+        Want::Next => Err(error(src, pos, "Unexpected end of input inside a container").into()),
+        Want::Value | Want::FirstValue => Err(unexpected(src, pos, "a value").into()),
+    }
+}
+
+fn position(src: &str, pos: usize) -> Position {
+    let before = &src.as_bytes()[..pos];
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    Position {
+        line: 1 + before.iter().filter(|b| **b == b'\n').count(),
+        // Count characters, not UTF-8 continuation bytes.
+        column: 1 + before[line_start..]
+            .iter()
+            .filter(|b| **b & 0xC0 != 0x80)
+            .count(),
+    }
+}
+
+fn error(src: &str, pos: usize, message: &str) -> ScanError {
+    ScanError {
+        message: message.to_string(),
+        position: position(src, pos),
+    }
+}
+
+fn unexpected(src: &str, pos: usize, wanted: &str) -> ScanError {
+    match src.get(pos..).and_then(|rest| rest.chars().next()) {
+        Some(found) => error(
+            src,
+            pos,
+            &format!("Expected {} but found '{}'", wanted, found),
+        ),
+        None => error(
+            src,
+            pos,
+            &format!("Expected {} but reached end of input", wanted),
+        ),
+    }
+}
+
+/// End of the string literal starting at `start`, after its closing quote.
+fn string_end(src: &str, start: usize) -> Result<usize, ScanError> {
+    let bytes = src.as_bytes();
+    let mut pos = start + 1;
+    loop {
+        match bytes.get(pos) {
+            None => return Err(error(src, start, "Unterminated string")),
+            Some(b'"') => return Ok(pos + 1),
+            Some(b'\\') => match bytes.get(pos + 1) {
+                Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => pos += 2,
+                Some(b'u')
+                    if bytes
+                        .get(pos + 2..pos + 6)
+                        .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) =>
+                {
+                    pos += 6
+                }
+                Some(b'u') => return Err(error(src, pos, "Invalid \\u escape")),
+                _ => return Err(error(src, pos, "Invalid escape sequence")),
+            },
+            Some(b) if *b < 0x20 => return Err(error(src, pos, "Control character in string")),
+            Some(_) => pos += 1,
+        }
+    }
+}
+
+/// End of the number starting at `start`: `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`.
+fn number_end(src: &str, start: usize) -> Result<usize, ScanError> {
+    let bytes = src.as_bytes();
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let mut pos = start + usize::from(bytes[start] == b'-');
+    match digits(pos) {
+        0 => return Err(unexpected(src, pos, "a digit")),
+        n if n > 1 && bytes[pos] == b'0' => {
+            return Err(error(src, pos, "Leading zeros are not allowed"));
+        }
+        n => pos += n,
+    }
+    if bytes.get(pos) == Some(&b'.') {
+        match digits(pos + 1) {
+            0 => return Err(unexpected(src, pos + 1, "a digit")),
+            n => pos += 1 + n,
+        }
+    }
+    if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+        pos += 1;
+        if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        match digits(pos) {
+            0 => return Err(unexpected(src, pos, "a digit")),
+            n => pos += n,
+        }
+    }
+    Ok(pos)
+}
+
+fn literal_end(src: &str, start: usize, word: &str) -> Result<usize, ScanError> {
+    if src.as_bytes()[start..].starts_with(word.as_bytes()) {
+        Ok(start + word.len())
+    } else {
+        Err(unexpected(src, start, &format!("'{}'", word)))
+    }
+}
+
+/// Summary of a document, as reported by `json_info`.
+#[derive(Default)]
+pub(crate) struct Stats {
+    /// First byte of the top-level value.
+    root: u8,
+    depth: usize,
+    /// Members of the top-level object, items of the top-level array.
+    keys: usize,
+    length: usize,
+    total_keys: usize,
+    total_values: usize,
+}
+
+pub(crate) fn stats(src: &str) -> Result<Stats, Stop> {
+    let mut stats = Stats::default();
+    let mut level = 0;
+    walk(src, usize::MAX, &mut |event| match event {
+        Event::Open(b) => {
+            if level == 0 {
+                stats.root = b;
+            } else if level == 1 && stats.root == b'[' {
+                stats.length += 1;
+            }
+            stats.total_values += 1;
+            level += 1;
+            stats.depth = stats.depth.max(level);
+        }
+        Event::Close(_) => level -= 1,
+        Event::Key(_) => {
+            if level == 1 {
+                stats.keys += 1;
+            }
+            stats.total_keys += 1;
+        }
+        Event::Value(raw) => {
+            if level == 0 {
+                stats.root = raw.as_bytes()[0];
+            } else if level == 1 && stats.root == b'[' {
+                stats.length += 1;
+            }
+            stats.total_values += 1;
+        }
+    })?;
+    Ok(stats)
+}
+
+impl Stats {
+    fn value_type(&self) -> &'static str {
+        match self.root {
+            b'{' => "object",
+            b'[' => "array",
+            b'"' => "string",
+            b't' | b'f' => "boolean",
+            b'n' => "null",
+            _ => "number",
+        }
+    }
+}
+
+/// Pretty-prints events as `serde_json::to_string_pretty` would, with keys,
+/// strings and numbers kept exactly as written.
+struct Pretty {
+    out: String,
+    depth: usize,
+    /// Nothing has been written in the innermost container yet.
+    first: bool,
+    /// A key was just written; its value follows on the same line.
+    after_key: bool,
+}
+
+impl Pretty {
+    fn new(capacity: usize) -> Self {
+        Pretty {
+            out: String::with_capacity(capacity),
+            depth: 0,
+            first: false,
+            after_key: false,
+        }
+    }
+
+    /// Start a new line for the next member, unless it follows its key.
+    fn member(&mut self) {
+        if self.after_key {
+            self.after_key = false;
+            return;
+        }
+        if self.depth == 0 {
+            return;
+        }
+        if !self.first {
+            self.out.push(',');
+        }
+        self.newline();
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Open(b) => {
+                self.member();
+                self.out.push(b as char);
+                self.depth += 1;
+                self.first = true;
+            }
+            Event::Close(b) => {
+                self.depth -= 1;
+                if !self.first {
+                    self.newline();
+                }
+                self.out.push(b as char);
+                self.first = false;
+            }
+            Event::Key(raw) => {
+                self.member();
+                self.out.push_str(raw);
+                self.out.push_str(": ");
+                self.after_key = true;
+                self.first = false;
+            }
+            Event::Value(raw) => {
+                self.member();
+                self.out.push_str(raw);
+                self.first = false;
+            }
+        }
+    }
+}
+
+/// Pretty-print without building a tree. Used by `json_format` for large bodies.
+/// Nesting is limited as when parsing, since indentation grows with depth.
+pub(crate) fn pretty(src: &str) -> Result<String, Stop> {
+    let mut pretty = Pretty::new(src.len() + src.len() / 2);
+    walk(src, MAX_DEPTH, &mut |event| pretty.event(event))?;
+    Ok(pretty.out)
+}
+
+/// Totals of a chunked format.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Formatted {
+    pub bytes: usize,
+    pub lines: usize,
+    pub chunks: usize,
+}
+
+/// Pretty-print, passing the output to `sink` in pieces of at least
+/// `chunk_size` bytes (the last may be shorter) as it is produced. Pieces
+/// handed over before an error is found are part of an incomplete document.
+pub(crate) fn format_chunked(
+    src: &str,
+    chunk_size: usize,
+    sink: &mut dyn FnMut(&str),
+) -> Result<Formatted, Stop> {
+    let mut pretty = Pretty::new(chunk_size.min(src.len()) + 64);
+    let mut totals = Formatted::default();
+    let mut flush = |out: &mut String, totals: &mut Formatted| {
+        if out.is_empty() {
+            return;
+        }
+        totals.bytes += out.len();
+        totals.lines += out.bytes().filter(|b| *b == b'\n').count();
+        totals.chunks += 1;
+        sink(out);
+        out.clear();
+    };
+    walk(src, MAX_DEPTH, &mut |event| {
+        pretty.event(event);
+        if pretty.out.len() >= chunk_size {
+            flush(&mut pretty.out, &mut totals);
+        }
+    })?;
+    flush(&mut pretty.out, &mut totals);
+    totals.lines += 1;
+    Ok(totals)
+}
+
+fn error_json(size: usize, e: &ScanError) -> serde_json::Value {
+    serde_json::json!({
+        "valid": false,
+        "size": size,
+        "error": e.message,
+        "line": e.position.line,
+        "column": e.position.column,
+    })
+}
+
+/// `json_info` computed from the token stream, for bodies too large to parse
+/// into memory; `json_info` switches to it above 8 MiB. Also counts every
+/// key and value in the document.
+/// Returns JSON {valid, size, type, depth, keys, length, totalKeys, totalValues},
+/// or {valid: false, size, error, line, column}.
+#[wasm_bindgen]
+pub fn json_info_streaming(json_str: &str) -> String {
+    let _timer = crate::logging::timer("json_info_streaming");
+    match stats(json_str) {
+        Ok(stats) => serde_json::json!({
+            "valid": true,
+            "size": json_str.len(),
+            "type": stats.value_type(),
+            "depth": stats.depth,
+            "keys": stats.keys,
+            "length": stats.length,
+            "totalKeys": stats.total_keys,
+            "totalValues": stats.total_values,
+        }),
+        Err(Stop::Invalid(e)) => error_json(json_str.len(), &e),
+        Err(Stop::Cancelled) => return cancelled_json(),
+    }
+    .to_string()
+}
+
+/// Pretty-print JSON of any size without parsing it into a tree, in the layout
+/// of `json_format` but with keys, strings and numbers exactly as written and
+/// repeated keys kept. With `on_chunk`, the output is passed to it as strings
+/// of about chunk_size bytes (64 KiB when 0) while formatting, so the whole
+/// result is never held at once; without it the result is returned as text.
+/// Nesting deeper than 128 levels is an error, as in `json_format`.
+/// Returns JSON {valid, bytes, lines, chunks, text?} or {valid: false, size,
+/// error, line, column}; chunks already delivered before an error are incomplete.
+#[wasm_bindgen]
+pub fn json_format_chunked(
+    json_str: &str,
+    chunk_size: usize,
+    on_chunk: Option<js_sys::Function>,
+) -> String {
+    let _timer = crate::logging::timer("json_format_chunked");
+    let chunk_size = if chunk_size == 0 {
+        DEFAULT_CHUNK
+    } else {
+        chunk_size
+    };
+    let mut text = String::new();
+    let result = format_chunked(json_str, chunk_size, &mut |chunk| match &on_chunk {
+        // A throwing callback must not abort formatting.
+        Some(callback) => {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(chunk));
+        }
+        None => text.push_str(chunk),
+    });
+    match result {
+        Ok(totals) => {
+            let mut out = serde_json::json!({
+                "valid": true,
+                "bytes": totals.bytes,
+                "lines": totals.lines,
+                "chunks": totals.chunks,
+            });
+            if on_chunk.is_none() {
+                out["text"] = serde_json::Value::String(text);
+            }
+            out.to_string()
+        }
+        Err(Stop::Invalid(e)) => error_json(json_str.len(), &e).to_string(),
+        Err(Stop::Cancelled) => cancelled_json(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_pretty_matches_serde() {
+        let src = r#" {"a": [1, -2.5, {"b": null, "c": []}], "d": {}, "e": "x\"y",
+            "f": [[true, false]], "g": {"h": {"i": "é"}}} "#;
+        let expected =
+            serde_json::to_string_pretty(&serde_json::from_str::<Value>(src).unwrap()).unwrap();
+        assert_eq!(pretty(src).unwrap(), expected);
+        assert_eq!(pretty(" 42 ").unwrap(), "42");
+        assert_eq!(pretty("[]").unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_walk_errors() {
+        for (src, message) in [
+            ("", "Expected a value but reached end of input"),
+            ("{\"a\" 1}", "Expected ':' but found '1'"),
+            ("[1,]", "Expected a value but found ']'"),
+            ("{\"a\": 1,}", "Expected a string key but found '}'"),
+            ("[1 2]", "Expected ',' or ']' but found '2'"),
+            ("[1}", "Expected ',' or ']' but found '}'"),
+            ("01", "Leading zeros are not allowed"),
+            ("1.", "Expected a digit but reached end of input"),
+            ("\"a\\x\"", "Invalid escape sequence"),
+            ("[\"open", "Unterminated string"),
+            ("[1", "Unexpected end of input inside a container"),
+            ("{} {}", "Unexpected data after JSON value"),
+            ("tru", "Expected 'true' but found 't'"),
+        ] {
+            let Err(Stop::Invalid(e)) = stats(src) else {
+                panic!("{} parsed", src);
+            };
+            assert_eq!(e.message, message, "{}", src);
+        }
+        let Err(Stop::Invalid(e)) = stats("{\n  \"é\": [1,\n  x]}") else {
+            panic!("parsed");
+        };
+        assert_eq!(e.position, Position { line: 3, column: 3 });
+    }
+
+    #[test]
+    fn test_stats_and_chunks() {
+        let info: Value = serde_json::from_str(&json_info_streaming(
+            r#"{"a": [1, 2, {"b": [3]}], "c": "d", "c": null}"#,
+        ))
+        .unwrap();
+        assert_eq!(info["type"], "object");
+        assert_eq!(info["depth"], 4);
+        assert_eq!(info["keys"], 3);
+        assert_eq!(info["totalKeys"], 4);
+        assert_eq!(info["totalValues"], 9);
+        let info: Value = serde_json::from_str(&json_info_streaming("[1, [2, 3], 4]")).unwrap();
+        assert_eq!(info["length"], 3);
+        let info: Value = serde_json::from_str(&json_info_streaming("[1,")).unwrap();
+        assert_eq!(info["valid"], false);
+        assert_eq!(info["column"], 4);
+
+        // Deep nesting is no problem without recursion.
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert_eq!(stats(&deep).unwrap().depth, 100_000);
+        // Formatting it would need quadratic indentation, so it is refused.
+        let out: Value = serde_json::from_str(&json_format_chunked(&deep, 0, None)).unwrap();
+        assert_eq!(out["valid"], false);
+        assert_eq!(out["error"], "Recursion limit exceeded");
+        assert_eq!(out["column"], MAX_DEPTH + 1);
+        let limit = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(pretty(&limit).is_ok());
+
+        let src: String = format!(
+            "[{}]",
+            (0..1000)
+                .map(|i| format!("{{\"id\": {}}}", i))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let mut joined = String::new();
+        let totals = format_chunked(&src, 1024, &mut |chunk| joined.push_str(chunk)).unwrap();
+        assert_eq!(joined, pretty(&src).unwrap());
+        assert_eq!(totals.bytes, joined.len());
+        assert_eq!(totals.lines, joined.lines().count());
+        assert!(totals.chunks > 10);
+
+        let out: Value = serde_json::from_str(&json_format_chunked("[1]", 0, None)).unwrap();
+        assert_eq!(out["text"], "[\n  1\n]");
+        assert_eq!(out["chunks"], 1);
+    }
+
+    #[test]
+    fn test_streaming_stops_when_cancelled() {
+        let src = format!("[{}0]", "0,".repeat(10_000));
+        let handle = crate::cancel::AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(json_info_streaming(&src), cancelled_json());
+        assert_eq!(json_format_chunked(&src, 0, None), cancelled_json());
+        handle.deactivate();
+        assert!(json_info_streaming(&src).contains("\"valid\":true"));
+    }
+}
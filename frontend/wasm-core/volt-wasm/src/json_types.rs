@@ -0,0 +1,372 @@
+use std::collections::{HashSet, VecDeque};
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::naming::{humanize, singular};
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Everything seen at one place in a sample: the types, and for arrays and
+/// objects the merged shape of their contents.
+#[derive(Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    /// Numbers with a fraction or exponent.
+    number: bool,
+    string: bool,
+    /// Merged shape of the elements of every array seen here.
+    items: Option<Box<Shape>>,
+    /// Objects seen here.
+    objects: usize,
+    /// Each field, with the number of those objects that had it.
+    fields: IndexMap<String, (Shape, usize)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_i64() || n.is_u64() => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::String(_) => self.string = true,
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_default();
+                for item in items {
+                    shape.add(item);
+                }
+            }
+            Value::Object(map) => {
+                self.objects += 1;
+                for (key, value) in map {
+                    let (shape, count) = self.fields.entry(key.clone()).or_default();
+                    shape.add(value);
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    /// Fields missing from some of the objects are optional.
+    fn required(&self, count: usize) -> bool {
+        count == self.objects
+    }
+
+    fn to_schema(&self) -> Map<String, Value> {
+        let mut types = Vec::new();
+        let seen = [
+            ("null", self.null),
+            ("boolean", self.boolean),
+            ("integer", self.integer && !self.number),
+            ("number", self.number),
+            ("string", self.string),
+            ("array", self.items.is_some()),
+            ("object", self.objects > 0),
+        ];
+        for (name, _) in seen.iter().filter(|(_, seen)| *seen) {
+            types.push(Value::from(*name));
+        }
+        let mut schema = Map::new();
+        match types.len() {
+            0 => {}
+            1 => {
+                schema.insert("type".to_string(), types.remove(0));
+            }
+            _ => {
+                schema.insert("type".to_string(), Value::Array(types));
+            }
+        }
+        if let Some(items) = &self.items {
+            schema.insert("items".to_string(), Value::Object(items.to_schema()));
+        }
+        if self.objects > 0 {
+            let properties: Map<String, Value> = self
+                .fields
+                .iter()
+                .map(|(key, (shape, _))| (key.clone(), Value::Object(shape.to_schema())))
+                .collect();
+            let required: Vec<&String> = self
+                .fields
+                .iter()
+                .filter(|(_, (_, count))| self.required(*count))
+                .map(|(key, _)| key)
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), serde_json::json!(required));
+            }
+        }
+        schema
+    }
+}
+
+fn parse_sample(json_str: &str) -> Option<Shape> {
+    let value: Value = serde_json::from_str(json_str).ok()?;
+    let mut shape = Shape::default();
+    shape.add(&value);
+    Some(shape)
+}
+
+/// "user profiles" → "UserProfiles"; "Item" when nothing usable is left.
+fn pascal(words: &str) -> String {
+    let mut out = String::new();
+    for word in words.split(' ') {
+        let mut chars = word.chars().filter(|c| c.is_ascii_alphanumeric());
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars);
+        }
+    }
+    match out.chars().next() {
+        None => "Item".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("T{}", out),
+        Some(_) => out,
+    }
+}
+
+/// Words naming the elements of an array named `words`: "users" → "user".
+fn item_words(words: &str) -> String {
+    let one = singular(words);
+    if one == words {
+        format!("{} item", words)
+    } else {
+        one
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Emits one interface per object shape, named after the field holding it.
+struct Emitter<'a> {
+    names: HashSet<String>,
+    pending: VecDeque<(String, &'a Shape)>,
+}
+
+impl<'a> Emitter<'a> {
+    /// A type name not used yet, from lowercase words.
+    fn claim(&mut self, words: &str) -> String {
+        let base = pascal(words);
+        let mut name = base.clone();
+        let mut n = 2;
+        while !self.names.insert(name.clone()) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
+    fn type_of(&mut self, shape: &'a Shape, words: &str) -> String {
+        let parts = self.union(shape, words);
+        if parts.is_empty() {
+            return "unknown".to_string();
+        }
+        parts.join(" | ")
+    }
+
+    /// The members of the union type for a shape.
+    fn union(&mut self, shape: &'a Shape, words: &str) -> Vec<String> {
+        let mut parts = Vec::new();
+        if shape.string {
+            parts.push("string".to_string());
+        }
+        if shape.integer || shape.number {
+            parts.push("number".to_string());
+        }
+        if shape.boolean {
+            parts.push("boolean".to_string());
+        }
+        if shape.objects > 0 {
+            if shape.fields.is_empty() {
+                parts.push("Record<string, unknown>".to_string());
+            } else {
+                let name = self.claim(words);
+                self.pending.push_back((name.clone(), shape));
+                parts.push(name);
+            }
+        }
+        if let Some(items) = &shape.items {
+            let item = self.union(items, &item_words(words));
+            parts.push(match item.len() {
+                0 => "unknown[]".to_string(),
+                1 => format!("{}[]", item[0]),
+                _ => format!("({})[]", item.join(" | ")),
+            });
+        }
+        if shape.null {
+            parts.push("null".to_string());
+        }
+        parts
+    }
+
+    fn interface(&mut self, name: &str, shape: &'a Shape) -> String {
+        let mut out = format!("export interface {} {{\n", name);
+        for (key, (field, count)) in &shape.fields {
+            let words = humanize(&key.replace('.', " "));
+            let ty = self.type_of(field, &words);
+            let key = if is_identifier(key) {
+                key.clone()
+            } else {
+                Value::from(key.as_str()).to_string()
+            };
+            let optional = if shape.required(*count) { "" } else { "?" };
+            out.push_str(&format!("  {}{}: {};\n", key, optional, ty));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Infer a JSON Schema (2020-12) from a sample body. Array elements are merged
+/// into one `items` schema; object fields missing from some of the merged
+/// objects are left out of `required`, and fields seen as null get a "null" type.
+/// Returns the schema as JSON, or an empty string for invalid JSON.
+#[wasm_bindgen]
+pub fn json_infer_schema(json_str: &str) -> String {
+    let Some(shape) = parse_sample(json_str) else {
+        return String::new();
+    };
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), Value::from(SCHEMA_DIALECT));
+    schema.extend(shape.to_schema());
+    Value::Object(schema).to_string()
+}
+
+/// Generate TypeScript declarations for a sample body: an interface per object
+/// shape, named after its field (array elements get the singular: `users` →
+/// `User`), with array elements merged, fields missing from some objects marked
+/// optional (`?`) and fields seen as null typed `T | null`.
+/// root_name: name of the top-level type ("Root" when empty).
+/// Returns the declarations, or an empty string for invalid JSON.
+#[wasm_bindgen]
+pub fn json_to_typescript(json_str: &str, root_name: &str) -> String {
+    let Some(shape) = parse_sample(json_str) else {
+        return String::new();
+    };
+    let root_words = match humanize(root_name.trim()) {
+        words if words.is_empty() => "root".to_string(),
+        words => words,
+    };
+    let mut emitter = Emitter {
+        names: HashSet::new(),
+        pending: VecDeque::new(),
+    };
+    let mut sections = Vec::new();
+    let only_object = shape.objects > 0
+        && !shape.fields.is_empty()
+        && shape.items.is_none()
+        && !(shape.null || shape.boolean || shape.integer || shape.number || shape.string);
+    let name = emitter.claim(&root_words);
+    if only_object {
+        emitter.pending.push_back((name, &shape));
+    } else {
+        let ty = emitter.type_of(&shape, &root_words);
+        sections.push(format!("export type {} = {};\n", name, ty));
+    }
+    while let Some((name, shape)) = emitter.pending.pop_front() {
+        let interface = emitter.interface(&name, shape);
+        sections.push(interface);
+    }
+    sections.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "total": 2,
+        "users": [
+            {"id": 1, "name": "Ann", "email": null, "tags": ["a"], "address": {"city": "Oslo"}},
+            {"id": 2, "name": "Bo", "email": "b@x.io", "score": 1.5, "tags": []}
+        ],
+        "meta": {},
+        "next-page": null
+    }"#;
+
+    #[test]
+    fn test_json_infer_schema() {
+        let schema: Value = serde_json::from_str(&json_infer_schema(SAMPLE)).unwrap();
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["total", "users", "meta", "next-page"])
+        );
+        let user = &schema["properties"]["users"]["items"];
+        assert_eq!(user["type"], "object");
+        assert_eq!(
+            user["required"],
+            serde_json::json!(["id", "name", "email", "tags"])
+        );
+        assert_eq!(
+            user["properties"]["email"]["type"],
+            serde_json::json!(["null", "string"])
+        );
+        assert_eq!(user["properties"]["score"]["type"], "number");
+        assert_eq!(user["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(schema["properties"]["next-page"]["type"], "null");
+        assert_eq!(
+            json_infer_schema("[1, 2.5]"),
+            format!(
+                r#"{{"$schema":"{}","type":"array","items":{{"type":"number"}}}}"#,
+                SCHEMA_DIALECT
+            )
+        );
+        assert_eq!(json_infer_schema("{"), "");
+
+        // The inferred schema accepts the sample it came from.
+        let (errors, _) =
+            crate::schema::validate(&serde_json::from_str(SAMPLE).unwrap(), &schema).unwrap();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_json_to_typescript() {
+        assert_eq!(
+            json_to_typescript(SAMPLE, "users response"),
+            "export interface UsersResponse {
+  total: number;
+  users: User[];
+  meta: Record<string, unknown>;
+  \"next-page\": null;
+}
+
+export interface User {
+  id: number;
+  name: string;
+  email: string | null;
+  tags: string[];
+  address?: Address;
+  score?: number;
+}
+
+export interface Address {
+  city: string;
+}
+"
+        );
+        assert_eq!(
+            json_to_typescript(r#"[{"id": 1}, {"id": "x", "items": [[1], ["a"]]}]"#, ""),
+            "export type Root = RootItem[];
+
+export interface RootItem {
+  id: string | number;
+  items?: (string | number)[][];
+}
+"
+        );
+        assert_eq!(
+            json_to_typescript("null", "Empty"),
+            "export type Empty = null;\n"
+        );
+    }
+}
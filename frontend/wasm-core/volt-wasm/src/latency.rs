@@ -0,0 +1,269 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::mocks::looks_like_id;
+use crate::split_url;
+
+const HOUR_MS: f64 = 3_600_000.0;
+const DAY_MS: f64 = 24.0 * HOUR_MS;
+/// Samples needed in both windows before a regression is reported.
+const MIN_WINDOW_SAMPLES: usize = 5;
+/// Current/baseline median ratio that counts as a regression.
+const REGRESSION_RATIO: f64 = 1.2;
+/// Buckets further than this many scaled MADs from the typical median are anomalies.
+const ANOMALY_MADS: f64 = 3.5;
+
+#[derive(Deserialize)]
+struct Entry {
+    #[serde(default)]
+    timestamp: f64,
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    url: String,
+    #[serde(rename = "durationMs", alias = "responseTime", alias = "timing")]
+    duration_ms: f64,
+}
+
+/// Per-endpoint latency time series from local history.
+/// entries_json: array of {timestamp, method, url, durationMs}.
+/// bucket: "hour" or "day" (default).
+/// Endpoints group by method and path, with id-like segments folded into {id}.
+/// The current window (last 24 hours for "hour", last 7 days for "day", ending at
+/// the newest entry) is compared with the baseline window before it (7 and 28 days).
+/// Returns JSON [{endpoint, count, series: [{start, count, median, p95, anomaly}],
+/// baseline: {count, median, p95}, current: {...}, changePct, regression}],
+/// sorted by largest slowdown first.
+#[wasm_bindgen]
+pub fn latency_trends(entries_json: &str, bucket: &str) -> String {
+    let entries: Vec<Value> = serde_json::from_str(entries_json).unwrap_or_default();
+    let entries: Vec<Entry> = entries
+        .into_iter()
+        .filter_map(|e| serde_json::from_value(e).ok())
+        .filter(|e: &Entry| e.duration_ms.is_finite() && e.duration_ms >= 0.0)
+        .collect();
+    let (size, current_len, baseline_len) = if bucket == "hour" {
+        (HOUR_MS, DAY_MS, 7.0 * DAY_MS)
+    } else {
+        (DAY_MS, 7.0 * DAY_MS, 28.0 * DAY_MS)
+    };
+    let _timer = crate::logging::timer("latency_trends");
+
+    let Some(latest) = entries.iter().map(|e| e.timestamp).reduce(f64::max) else {
+        return "[]".to_string();
+    };
+    let current_start = latest - current_len;
+    let baseline_start = current_start - baseline_len;
+
+    let mut groups: IndexMap<String, Vec<&Entry>> = IndexMap::new();
+    for e in &entries {
+        groups.entry(endpoint(e)).or_default().push(e);
+    }
+
+    let mut trends: Vec<(f64, Value)> = groups
+        .into_iter()
+        .map(|(name, mut group)| {
+            group.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+            let window = |from: f64, to: f64| -> Vec<f64> {
+                group
+                    .iter()
+                    .filter(|e| e.timestamp > from && e.timestamp <= to)
+                    .map(|e| e.duration_ms)
+                    .collect()
+            };
+            let current = window(current_start, latest);
+            let baseline = window(baseline_start, current_start);
+            let (cur, base) = (summary(&current), summary(&baseline));
+
+            let enough =
+                current.len() >= MIN_WINDOW_SAMPLES && baseline.len() >= MIN_WINDOW_SAMPLES;
+            let change = (enough && cur.median > 0.0 && base.median > 0.0)
+                .then(|| (cur.median - base.median) / base.median * 100.0);
+            let regression = change.is_some_and(|c| c >= (REGRESSION_RATIO - 1.0) * 100.0);
+
+            let series = series(&group, size);
+            let trend = serde_json::json!({
+                "endpoint": name,
+                "count": group.len(),
+                "series": series,
+                "baseline": base.to_json(),
+                "current": cur.to_json(),
+                "changePct": change.map(round2),
+                "regression": regression,
+            });
+            (change.unwrap_or(f64::NEG_INFINITY), trend)
+        })
+        .collect();
+
+    trends.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let trends: Vec<Value> = trends.into_iter().map(|(_, t)| t).collect();
+    serde_json::to_string(&trends).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn endpoint(e: &Entry) -> String {
+    let (_, path, _) = split_url(&e.url);
+    let path: Vec<&str> = path
+        .split('/')
+        .map(|s| {
+            if !s.is_empty() && looks_like_id(s) {
+                "{id}"
+            } else {
+                s
+            }
+        })
+        .collect();
+    let path = path.join("/");
+    format!(
+        "{} {}",
+        e.method.to_uppercase(),
+        if path.is_empty() { "/" } else { &path }
+    )
+}
+
+struct Summary {
+    count: usize,
+    median: f64,
+    p95: f64,
+}
+
+impl Summary {
+    fn to_json(&self) -> Value {
+        if self.count == 0 {
+            return serde_json::json!({ "count": 0, "median": null, "p95": null });
+        }
+        serde_json::json!({
+            "count": self.count,
+            "median": round2(self.median),
+            "p95": round2(self.p95),
+        })
+    }
+}
+
+fn summary(values: &[f64]) -> Summary {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Summary {
+        count: sorted.len(),
+        median: percentile(&sorted, 50.0),
+        p95: percentile(&sorted, 95.0),
+    }
+}
+
+/// Linear-interpolated percentile of sorted values; NaN when empty.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => f64::NAN,
+        1 => sorted[0],
+        n => {
+            let rank = p / 100.0 * (n - 1) as f64;
+            let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+            sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+        }
+    }
+}
+
+/// Bucketed medians/p95s (entries sorted by time), with buckets whose median
+/// is far from the endpoint's typical bucket median flagged as anomalies.
+fn series(group: &[&Entry], size: f64) -> Vec<Value> {
+    let mut buckets: Vec<(f64, Vec<f64>)> = Vec::new();
+    for e in group {
+        let start = (e.timestamp / size).floor() * size;
+        match buckets.last_mut() {
+            Some((s, values)) if *s == start => values.push(e.duration_ms),
+            _ => buckets.push((start, vec![e.duration_ms])),
+        }
+    }
+    let summaries: Vec<(f64, Summary)> = buckets
+        .iter()
+        .map(|(start, values)| (*start, summary(values)))
+        .collect();
+
+    let mut medians: Vec<f64> = summaries.iter().map(|(_, s)| s.median).collect();
+    medians.sort_by(f64::total_cmp);
+    let typical = percentile(&medians, 50.0);
+    let mut deviations: Vec<f64> = medians.iter().map(|m| (m - typical).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    // 1.4826 scales the MAD to a standard deviation for normal data.
+    let mad = percentile(&deviations, 50.0) * 1.4826;
+
+    summaries
+        .into_iter()
+        .map(|(start, s)| {
+            let anomaly =
+                medians.len() >= 3 && mad > 0.0 && (s.median - typical).abs() / mad > ANOMALY_MADS;
+            serde_json::json!({
+                "start": start,
+                "count": s.count,
+                "median": round2(s.median),
+                "p95": round2(s.p95),
+                "anomaly": anomaly,
+            })
+        })
+        .collect()
+}
+
+fn round2(n: f64) -> f64 {
+    (n * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: f64, url: &str, ms: f64) -> Value {
+        serde_json::json!({"timestamp": day * DAY_MS, "method": "GET", "url": url, "durationMs": ms})
+    }
+
+    #[test]
+    fn test_latency_trends_regression() {
+        let mut entries = Vec::new();
+        for day in 0..35 {
+            let slow = day >= 28;
+            let ms = if slow { 150.0 } else { 100.0 } + (day % 3) as f64;
+            entries.push(entry(
+                day as f64 + 0.5,
+                &format!("https://x.io/users/{}", day),
+                ms,
+            ));
+            entries.push(entry(day as f64 + 0.6, "https://x.io/health", 10.0));
+        }
+        let trends: Value =
+            serde_json::from_str(&latency_trends(&Value::from(entries).to_string(), "day"))
+                .unwrap();
+        assert_eq!(trends[0]["endpoint"], "GET /users/{id}");
+        assert_eq!(trends[0]["regression"], true);
+        assert_eq!(trends[0]["count"], 35);
+        assert_eq!(trends[0]["series"].as_array().unwrap().len(), 35);
+        assert_eq!(trends[0]["baseline"]["median"], 101.0);
+        let change = trends[0]["changePct"].as_f64().unwrap();
+        assert!(change > 45.0 && change < 55.0);
+        assert_eq!(trends[1]["endpoint"], "GET /health");
+        assert_eq!(trends[1]["regression"], false);
+        assert_eq!(trends[1]["changePct"], 0.0);
+    }
+
+    #[test]
+    fn test_latency_trends_anomalies_and_hours() {
+        let mut entries: Vec<Value> = (0..24)
+            .map(|h| entry(h as f64 / 24.0, "/a", 50.0 + (h % 2) as f64 * 2.0))
+            .collect();
+        entries[10]["durationMs"] = 900.0.into();
+        let trends: Value =
+            serde_json::from_str(&latency_trends(&Value::from(entries).to_string(), "hour"))
+                .unwrap();
+        let series = trends[0]["series"].as_array().unwrap();
+        assert_eq!(series.len(), 24);
+        let flagged: Vec<usize> = series
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b["anomaly"] == true)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(flagged, vec![10]);
+        // Too little history for a baseline comparison.
+        assert_eq!(trends[0]["changePct"], Value::Null);
+        assert_eq!(latency_trends("nope", "day"), "[]");
+    }
+}
@@ -1,9 +1,92 @@
 use regex_lite::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use wasm_bindgen::prelude::*;
 
+use messages::Message;
+use model::Headers;
+use progress::Progress;
+
+mod bundle;
+mod cancel;
+mod capabilities;
+mod certs;
+mod chaos;
+mod codegen;
+mod collection_binary;
+mod collection_diff;
+mod collection_merge;
+mod compare;
+mod data_uri;
+mod datafile;
+mod datagen;
+mod deprecation;
+mod docs;
+mod entropy;
+mod environments;
+mod expr;
+mod graphql;
+mod graphql_ws;
+mod grpc_web;
+mod har;
+mod highlight;
+mod idempotency;
+mod import;
+mod jmespath;
+mod json5;
+mod json_canonical;
+mod json_diff;
+mod json_errors;
+mod json_patch;
+mod json_paths;
+mod json_preview;
+mod json_scan;
+mod json_search;
+mod json_stream;
+mod json_types;
+mod jwt;
+mod latency;
+mod load_stats;
+mod logging;
+mod messages;
+mod mocks;
+mod naming;
+mod model;
+mod openapi;
+mod path_stats;
+mod preflight;
+mod progress;
+mod proto_text;
+mod rate_limit;
+mod redact;
+mod regex_tester;
+mod request_merge;
+mod retention;
+mod rng;
+mod run_results;
+mod schedule;
+mod schema;
+mod sniff;
+mod socketio;
+mod stomp;
+mod tabular;
+mod text_diff;
+mod thresholds;
+mod template;
+mod tokens;
+mod totp;
+mod trace;
+mod variable_index;
+mod variables;
+#[cfg(feature = "vault")]
+mod vault;
+mod xml;
+mod yaml;
+mod zip;
+
 // Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -11,7 +94,114 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
+/// Milliseconds since the Unix epoch, from the JS clock when running as WASM.
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// (year, month, day) for a count of days since 1970-01-01 (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date; inverse of `civil_from_days`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Milliseconds since the epoch for an ISO 8601 date or date-time such as "2024-02-29",
+/// "2024-02-29T10:00:00.250Z" or "2024-02-29 10:00+02:00". A missing offset means UTC.
+fn parse_iso_datetime(text: &str) -> Option<f64> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(
+            r"^(\d{4})-(\d{2})-(\d{2})(?:[Tt ](\d{2}):(\d{2})(?::(\d{2})(?:[.,](\d+))?)?(?:([Zz])|([+-])(\d{2}):?(\d{2}))?)?$",
+        )
+        .unwrap()
+    });
+    let caps = re.captures(text.trim())?;
+    let num = |i: usize| {
+        caps.get(i)
+            .map_or(0, |m| m.as_str().parse::<i64>().unwrap_or(0))
+    };
+    let (month, day, hour, minute, second) = (num(2), num(3), num(4), num(5), num(6));
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+    let fraction = caps.get(7).map_or(0.0, |m| {
+        format!("0.{}", m.as_str()).parse::<f64>().unwrap_or(0.0)
+    });
+    let mut seconds =
+        days_from_civil(num(1), month, day) * 86_400 + hour * 3600 + minute * 60 + second;
+    if let Some(sign) = caps.get(9) {
+        let offset = num(10) * 3600 + num(11) * 60;
+        let east = sign.as_str() == "+";
+        seconds -= if east { offset } else { -offset };
+    }
+    Some(seconds as f64 * 1000.0 + (fraction * 1000.0).round())
+}
+
+/// Milliseconds since the epoch for an HTTP-date (RFC 9110 §5.6.7), e.g.
+/// "Wed, 21 Oct 2015 07:28:00 GMT"; the obsolete RFC 850 form is accepted too.
+fn parse_http_date(text: &str) -> Option<f64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let (_, rest) = text.trim().split_once(", ")?;
+    let parts: Vec<&str> = rest.split([' ', '-']).filter(|p| !p.is_empty()).collect();
+    let [day, month, year, time, ..] = parts[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| month.eq_ignore_ascii_case(m))? as i64 + 1;
+    let year: i64 = match year.parse().ok()? {
+        y @ 0..=69 => 2000 + y,
+        y @ 70..=99 => 1900 + y,
+        y => y,
+    };
+    let day: i64 = day.parse().ok()?;
+    parse_iso_datetime(&format!("{:04}-{:02}-{:02}T{}Z", year, month, day, time))
+}
+
 /// Substitutes {{variable}} patterns in a string with values from the provided map.
+/// Values may contain {{variables}} themselves and are resolved in turn; variables
+/// that refer back to themselves are left as written (see `find_variable_cycles`).
+/// Built-in `{{$name}}` variables such as `{{$uuid}}` or `{{$randomInt 1 10}}` are
+/// generated for each occurrence (see `list_dynamic_variables`). `{{name:-x}}` and
+/// `{{name | default:"x"}}` fall back to x when the variable is missing or empty, and
+/// `{{name | base64 | lower}}` runs the value through filters (see `list_template_filters`).
+/// Expressions such as `{{ userId + 1 }}` or `{{ n > 1 ? "s" : "" }}` are evaluated
+/// (see `evaluate_expression`). `{{#if name}}...{{else}}...{{/if}}` keeps one branch:
+/// the first when the variable (or expression) is not empty, "false" or "0".
+/// `\{{` is a literal `{{` and is written out without the backslash.
 /// Returns the substituted string.
 #[wasm_bindgen]
 pub fn substitute_variables(text: &str, variables_json: &str) -> String {
@@ -21,64 +211,68 @@ pub fn substitute_variables(text: &str, variables_json: &str) -> String {
 
     let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
         Ok(v) => v,
-        Err(_) => return text.to_string(),
+        Err(e) => {
+            logging::warn("substitute_variables", || format!("invalid variables JSON: {}", e));
+            return text.to_string();
+        }
     };
 
-    if variables.is_empty() {
-        return text.to_string();
-    }
-
-    let re = Regex::new(r"\{\{([^}]+)\}\}").unwrap();
-    re.replace_all(text, |caps: &regex_lite::Captures| {
-        let var_name = caps.get(1).unwrap().as_str().trim();
-        variables
-            .get(var_name)
-            .cloned()
-            .unwrap_or_else(|| caps.get(0).unwrap().as_str().to_string())
-    })
-    .to_string()
+    let mut resolver = variables::Resolver::new(&variables);
+    let result = resolver.substitute(text);
+    resolver.warn_cycles("substitute_variables");
+    result
 }
 
-/// Batch substitute variables in multiple strings at once.
+/// Batch substitute variables in multiple strings at once, resolving nested and
+/// built-in variables as `substitute_variables` does.
+/// progress: optional callback invoked with {done, total, stage}.
 /// Returns JSON array of substituted strings.
 #[wasm_bindgen]
-pub fn substitute_variables_batch(texts_json: &str, variables_json: &str) -> String {
+pub fn substitute_variables_batch(
+    texts_json: &str,
+    variables_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
     let texts: Vec<String> = match serde_json::from_str(texts_json) {
         Ok(t) => t,
-        Err(_) => return "[]".to_string(),
+        Err(e) => {
+            logging::warn("substitute_variables_batch", || format!("invalid texts JSON: {}", e));
+            return "[]".to_string();
+        }
     };
 
     let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
         Ok(v) => v,
-        Err(_) => return serde_json::to_string(&texts).unwrap_or_else(|_| "[]".to_string()),
+        Err(e) => {
+            logging::warn("substitute_variables_batch", || {
+                format!("invalid variables JSON, returning texts unchanged: {}", e)
+            });
+            return serde_json::to_string(&texts).unwrap_or_else(|_| "[]".to_string());
+        }
     };
+    let _timer = logging::timer("substitute_variables_batch");
 
-    if variables.is_empty() {
-        return serde_json::to_string(&texts).unwrap_or_else(|_| "[]".to_string());
-    }
-
-    let re = Regex::new(r"\{\{([^}]+)\}\}").unwrap();
-    let results: Vec<String> = texts
+    let mut resolver = variables::Resolver::new(&variables);
+    let mut progress = Progress::new(progress, "substitute", texts.len());
+    let results: Result<Vec<String>, cancel::Cancelled> = texts
         .iter()
-        .map(|text| {
-            if !text.contains("{{") {
-                return text.clone();
-            }
-            re.replace_all(text, |caps: &regex_lite::Captures| {
-                let var_name = caps.get(1).unwrap().as_str().trim();
-                variables
-                    .get(var_name)
-                    .cloned()
-                    .unwrap_or_else(|| caps.get(0).unwrap().as_str().to_string())
-            })
-            .to_string()
+        .enumerate()
+        .map(|(i, text)| {
+            cancel::checkpoint()?;
+            progress.tick(i + 1);
+            Ok(resolver.substitute(text))
         })
         .collect();
+    resolver.warn_cycles("substitute_variables_batch");
 
-    serde_json::to_string(&results).unwrap_or_else(|_| "[]".to_string())
+    match results {
+        Ok(results) => serde_json::to_string(&results).unwrap_or_else(|_| "[]".to_string()),
+        Err(cancel::Cancelled) => cancel::cancelled_json(),
+    }
 }
 
-/// Find all variable names used in a string.
+/// Find all variable names used in a string, skipping escaped `\{{` braces. Inline defaults (`{{name:-x}}`,
+/// `{{name | default:"x"}}`) are not part of the name.
 /// Returns JSON array of variable names.
 #[wasm_bindgen]
 pub fn find_variables(text: &str) -> String {
@@ -86,36 +280,46 @@ pub fn find_variables(text: &str) -> String {
         return "[]".to_string();
     }
 
-    let re = Regex::new(r"\{\{([^}]+)\}\}").unwrap();
     let mut vars: Vec<String> = Vec::new();
 
-    for caps in re.captures_iter(text) {
-        let var_name = caps.get(1).unwrap().as_str().trim().to_string();
-        if !vars.contains(&var_name) {
-            vars.push(var_name);
+    for caps in variables::placeholder_regex().captures_iter(text) {
+        if variables::is_escaped(text, caps.get(0).unwrap().start()) {
+            continue;
+        }
+        // An expression such as {{ price * quantity }} uses each of its variables.
+        for var_name in variables::placeholder_variables(&caps[1]) {
+            if !vars.contains(&var_name) {
+                vars.push(var_name);
+            }
         }
     }
 
     serde_json::to_string(&vars).unwrap_or_else(|_| "[]".to_string())
 }
 
-/// Check if a string contains any {{variable}} patterns.
+/// Check if a string contains any {{variable}} patterns; escaped `\{{` braces do not count.
 #[wasm_bindgen]
 pub fn has_variables(text: &str) -> bool {
     if text.is_empty() {
         return false;
     }
-    let re = Regex::new(r"\{\{[^}]+\}\}").unwrap();
-    re.is_match(text)
+    variables::placeholder_regex()
+        .find_iter(text)
+        .any(|m| !variables::is_escaped(text, m.start()))
 }
 
 /// Extract a value from JSON using dot notation path (e.g., "data.users[0].name").
 /// Returns the extracted value as a JSON string, or "undefined" if not found.
+/// @deprecated Use `json_extract_v2`, which tells a missing path, invalid JSON and
+/// a null value apart.
 #[wasm_bindgen]
 pub fn json_extract(json_str: &str, path: &str) -> String {
     let value: Value = match serde_json::from_str(json_str) {
         Ok(v) => v,
-        Err(_) => return "undefined".to_string(),
+        Err(e) => {
+            logging::debug("json_extract", || format!("body is not valid JSON: {}", e));
+            return "undefined".to_string();
+        }
     };
 
     match get_json_path(&value, path) {
@@ -124,6 +328,26 @@ pub fn json_extract(json_str: &str, path: &str) -> String {
     }
 }
 
+/// Extract a value from JSON using dot notation path (e.g., "data.users[0].name").
+/// Also accepts `items[-1]`, `items[*].id`, `items[1:5]` and `data["a.b"]`;
+/// wildcards and slices give an array of the matches.
+/// Returns JSON {found: true, value} when the path exists (value may be null),
+/// {found: false} when it does not, or {found: false, error} for invalid JSON.
+#[wasm_bindgen]
+pub fn json_extract_v2(json_str: &str, path: &str) -> String {
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "found": false, "error": format!("Invalid JSON: {}", e) })
+                .to_string();
+        }
+    };
+    match get_json_path(&value, path) {
+        Some(v) => serde_json::json!({ "found": true, "value": v }).to_string(),
+        None => serde_json::json!({ "found": false }).to_string(),
+    }
+}
+
 /// Extract multiple values from JSON at once.
 /// paths_json is a JSON array of paths.
 /// Returns JSON object mapping paths to extracted values.
@@ -131,18 +355,28 @@ pub fn json_extract(json_str: &str, path: &str) -> String {
 pub fn json_extract_batch(json_str: &str, paths_json: &str) -> String {
     let value: Value = match serde_json::from_str(json_str) {
         Ok(v) => v,
-        Err(_) => return "{}".to_string(),
+        Err(e) => {
+            logging::debug("json_extract_batch", || format!("body is not valid JSON: {}", e));
+            return "{}".to_string();
+        }
     };
 
     let paths: Vec<String> = match serde_json::from_str(paths_json) {
         Ok(p) => p,
-        Err(_) => return "{}".to_string(),
+        Err(e) => {
+            logging::warn("json_extract_batch", || format!("invalid paths JSON: {}", e));
+            return "{}".to_string();
+        }
     };
 
-    let mut results: HashMap<String, Value> = HashMap::new();
+    // Keys come back in the order the paths were requested.
+    let mut results = serde_json::Map::new();
     for path in paths {
+        if cancel::checkpoint().is_err() {
+            return cancel::cancelled_json();
+        }
         if let Some(v) = get_json_path(&value, &path) {
-            results.insert(path, v.clone());
+            results.entry(path).or_insert_with(|| v.into_owned());
         }
     }
 
@@ -150,8 +384,17 @@ pub fn json_extract_batch(json_str: &str, paths_json: &str) -> String {
 }
 
 /// Format/pretty-print JSON string.
+/// Bodies over 8 MiB are formatted from the token stream (see
+/// `json_format_chunked`) rather than parsed into a tree first.
 #[wasm_bindgen]
 pub fn json_format(json_str: &str) -> String {
+    if json_str.len() > json_stream::STREAMING_THRESHOLD {
+        return match json_stream::pretty(json_str) {
+            Ok(pretty) => pretty,
+            Err(json_stream::Stop::Cancelled) => cancel::cancelled_json(),
+            Err(json_stream::Stop::Invalid(_)) => json_str.to_string(),
+        };
+    }
     match serde_json::from_str::<Value>(json_str) {
         Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| json_str.to_string()),
         Err(_) => json_str.to_string(),
@@ -168,14 +411,20 @@ pub fn json_minify(json_str: &str) -> String {
 }
 
 /// Validate if a string is valid JSON.
+/// `json_validate_detailed` says where and why invalid text fails, and
+/// `json_validate_relaxed` whether it is fixable JSON5.
 #[wasm_bindgen]
 pub fn json_validate(json_str: &str) -> bool {
     serde_json::from_str::<Value>(json_str).is_ok()
 }
 
 /// Get JSON size info (for large response handling).
+/// Bodies over 8 MiB are measured by `json_info_streaming` instead.
 #[wasm_bindgen]
 pub fn json_info(json_str: &str) -> String {
+    if json_str.len() > json_stream::STREAMING_THRESHOLD {
+        return json_stream::json_info_streaming(json_str);
+    }
     let value: Value = match serde_json::from_str(json_str) {
         Ok(v) => v,
         Err(_) => {
@@ -218,32 +467,110 @@ fn get_json_depth(value: &Value) -> usize {
     }
 }
 
-fn get_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
-    if path.is_empty() {
-        return Some(value);
-    }
-
-    let mut current = value;
-    let parts: Vec<&str> = path.split('.').collect();
+/// One step of a dot path.
+enum PathStep {
+    Key(String),
+    /// `[n]`; negative indices count from the end.
+    Index(i64),
+    /// `[start:end]`; either bound may be left out or negative.
+    Slice(Option<i64>, Option<i64>),
+    /// `[*]`: every item of an array or value of an object.
+    All,
+}
 
-    for part in parts {
-        // Check for array index: key[0]
-        let array_re = Regex::new(r"^(.+)\[(\d+)\]$").unwrap();
-        if let Some(caps) = array_re.captures(part) {
-            let key = caps.get(1).unwrap().as_str();
-            let index: usize = caps.get(2).unwrap().as_str().parse().ok()?;
+/// Split a dot path into steps, or None if it is malformed.
+fn parse_json_path(path: &str) -> Option<Vec<PathStep>> {
+    let bound = |text: &str| match text.trim() {
+        "" => Some(None),
+        n => n.parse().ok().map(Some),
+    };
+    let mut steps = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            if let Some(quote) = inner.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                let quoted = &inner[1..];
+                let end = quoted.find(quote)?;
+                steps.push(PathStep::Key(quoted[..end].to_string()));
+                rest = quoted[end + 1..].strip_prefix(']')?;
+                continue;
+            }
+            let end = inner.find(']')?;
+            let index = inner[..end].trim();
+            steps.push(match index.split_once(':') {
+                _ if index == "*" => PathStep::All,
+                Some((start, end)) => PathStep::Slice(bound(start)?, bound(end)?),
+                None => PathStep::Index(index.parse().ok()?),
+            });
+            rest = &inner[end + 1..];
+            continue;
+        }
+        if !steps.is_empty() {
+            rest = rest.strip_prefix('.')?;
+        }
+        let end = rest.find(['.', '[']).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        steps.push(PathStep::Key(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+    Some(steps)
+}
 
-            current = current.get(key)?;
-            current = current.get(index)?;
+/// Resolve a dot path such as `data.users[0].name`. Indices may be negative
+/// (`items[-1]`), `[*]` matches every item or value, `[1:5]` is a slice, and
+/// keys containing dots or brackets can be quoted (`data["a.b"]`). Paths with
+/// a wildcard or slice give an array of everything they match.
+fn get_json_path<'a>(value: &'a Value, path: &str) -> Option<Cow<'a, Value>> {
+    if path.is_empty() {
+        return Some(Cow::Borrowed(value));
+    }
+    let steps = parse_json_path(path)?;
+    let position = |n: i64, len: usize| {
+        if n < 0 {
+            len.checked_sub(n.unsigned_abs() as usize)
         } else {
-            current = current.get(part)?;
+            Some(n as usize)
         }
+    };
+    let mut current = vec![value];
+    let mut multiple = false;
+    for step in &steps {
+        let mut next = Vec::new();
+        for v in current {
+            match (step, v) {
+                (PathStep::Key(key), Value::Object(map)) => next.extend(map.get(key)),
+                (PathStep::Index(n), Value::Array(items)) => {
+                    next.extend(position(*n, items.len()).and_then(|i| items.get(i)));
+                }
+                (PathStep::Slice(start, end), Value::Array(items)) => {
+                    let clamp = |n: i64| position(n, items.len()).unwrap_or(0).min(items.len());
+                    let start = start.map_or(0, clamp);
+                    let end = end.map_or(items.len(), clamp);
+                    next.extend(items.get(start..end.max(start)).into_iter().flatten());
+                }
+                (PathStep::All, Value::Array(items)) => next.extend(items),
+                (PathStep::All, Value::Object(map)) => next.extend(map.values()),
+                _ => {}
+            }
+        }
+        multiple |= matches!(step, PathStep::Slice(..) | PathStep::All);
+        if next.is_empty() && !multiple {
+            return None;
+        }
+        current = next;
+    }
+    if multiple {
+        Some(Cow::Owned(Value::Array(
+            current.into_iter().cloned().collect(),
+        )))
+    } else {
+        current.pop().map(Cow::Borrowed)
     }
-
-    Some(current)
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 struct Assertion {
     id: String,
     #[serde(rename = "type")]
@@ -252,6 +579,32 @@ struct Assertion {
     operator: String,
     expected: String,
     enabled: bool,
+    /// Matching mode for `bodyJson` equals/notEquals.
+    #[serde(default, skip_serializing_if = "compare::CompareOptions::is_exact")]
+    compare: compare::CompareOptions,
+    /// Syntax of `property` for `bodyJson`.
+    #[serde(
+        default,
+        rename = "queryLanguage",
+        skip_serializing_if = "QueryLanguage::is_path"
+    )]
+    query_language: QueryLanguage,
+}
+
+/// How a `bodyJson` assertion's property selects a value: a dot path such as
+/// `data.users[0].name`, or a JMESPath expression.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum QueryLanguage {
+    #[default]
+    Path,
+    JmesPath,
+}
+
+impl QueryLanguage {
+    fn is_path(&self) -> bool {
+        *self == QueryLanguage::Path
+    }
 }
 
 #[derive(Serialize)]
@@ -260,14 +613,31 @@ struct AssertionResult {
     assertion_id: String,
     passed: bool,
     actual: String,
+    /// Default English rendering of `code` + `params`.
     message: String,
+    /// Stable message code for localized rendering (see `render_message`).
+    code: String,
+    params: serde_json::Map<String, Value>,
+}
+
+impl AssertionResult {
+    fn new(assertion: &Assertion, passed: bool, actual: String, message: Message) -> Self {
+        AssertionResult {
+            assertion_id: assertion.id.clone(),
+            passed,
+            actual,
+            message: message.render(),
+            code: message.code.to_string(),
+            params: message.params_json(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct ResponseData {
     #[serde(rename = "statusCode")]
     status_code: i32,
-    headers: HashMap<String, String>,
+    headers: Headers,
     body: String,
     #[serde(rename = "timingMs")]
     timing_ms: i64,
@@ -276,26 +646,54 @@ struct ResponseData {
 /// Run all assertions against response data.
 /// assertions_json: JSON array of assertion objects
 /// response_json: JSON object with statusCode, headers, body, timingMs
+/// progress: optional callback invoked with {done, total, stage}.
 /// Returns JSON array of assertion results.
 #[wasm_bindgen]
-pub fn run_assertions(assertions_json: &str, response_json: &str) -> String {
+pub fn run_assertions(
+    assertions_json: &str,
+    response_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
     let assertions: Vec<Assertion> = match serde_json::from_str(assertions_json) {
         Ok(a) => a,
-        Err(_) => return "[]".to_string(),
+        Err(e) => {
+            logging::warn("run_assertions", || format!("invalid assertions JSON: {}", e));
+            return "[]".to_string();
+        }
     };
 
     let response: ResponseData = match serde_json::from_str(response_json) {
         Ok(r) => r,
-        Err(_) => return "[]".to_string(),
+        Err(e) => {
+            logging::warn("run_assertions", || format!("invalid response JSON: {}", e));
+            return "[]".to_string();
+        }
+    };
+    let _timer = logging::timer("run_assertions");
+
+    // Parse body JSON once for all assertions, and only if one of them needs it.
+    let needs_json = assertions.iter().any(|a| {
+        a.enabled
+            && matches!(
+                a.assertion_type.as_str(),
+                "bodyJson" | "bodySchema" | "noDuplicateKeys"
+            )
+    });
+    let body_json: Option<Value> = if needs_json {
+        serde_json::from_str(&response.body).ok()
+    } else {
+        None
     };
 
-    // Parse body JSON once for all assertions
-    let body_json: Option<Value> = serde_json::from_str(&response.body).ok();
-
-    let results: Vec<AssertionResult> = assertions
-        .iter()
-        .map(|a| run_single_assertion(a, &response, &body_json))
-        .collect();
+    let mut progress = Progress::new(progress, "assertions", assertions.len());
+    let mut results: Vec<AssertionResult> = Vec::with_capacity(assertions.len());
+    for a in &assertions {
+        if cancel::checkpoint().is_err() {
+            return cancel::cancelled_json();
+        }
+        results.push(run_single_assertion(a, &response, &body_json));
+        progress.tick(results.len());
+    }
 
     serde_json::to_string(&results).unwrap_or_else(|_| "[]".to_string())
 }
@@ -306,12 +704,12 @@ fn run_single_assertion(
     body_json: &Option<Value>,
 ) -> AssertionResult {
     if !assertion.enabled {
-        return AssertionResult {
-            assertion_id: assertion.id.clone(),
-            passed: true,
-            actual: String::new(),
-            message: "Skipped (disabled)".to_string(),
-        };
+        return AssertionResult::new(
+            assertion,
+            true,
+            String::new(),
+            Message::new("assertion.skipped"),
+        );
     }
 
     match assertion.assertion_type.as_str() {
@@ -319,95 +717,106 @@ fn run_single_assertion(
         "responseTime" => run_response_time_assertion(assertion, response.timing_ms),
         "bodyContains" => run_body_contains_assertion(assertion, &response.body),
         "bodyJson" => run_body_json_assertion(assertion, body_json),
+        "bodySchema" => run_body_schema_assertion(assertion, body_json),
+        "noDuplicateKeys" => {
+            run_no_duplicate_keys_assertion(assertion, &response.body, body_json)
+        }
         "headerExists" => run_header_exists_assertion(assertion, &response.headers),
         "headerEquals" => run_header_equals_assertion(assertion, &response.headers),
-        _ => AssertionResult {
-            assertion_id: assertion.id.clone(),
-            passed: false,
-            actual: String::new(),
-            message: format!("Unknown assertion type: {}", assertion.assertion_type),
-        },
+        _ => AssertionResult::new(
+            assertion,
+            false,
+            String::new(),
+            Message::new("assertion.unknown_type").with("type", &assertion.assertion_type),
+        ),
     }
 }
 
+fn unknown_operator(assertion: &Assertion) -> (bool, Message) {
+    (
+        false,
+        Message::new("assertion.unknown_operator").with("operator", &assertion.operator),
+    )
+}
+
 fn run_status_assertion(assertion: &Assertion, status_code: i32) -> AssertionResult {
     let expected: i32 = assertion.expected.parse().unwrap_or(0);
     let actual = status_code.to_string();
+    let msg = |code| {
+        Message::new(code)
+            .with("expected", expected)
+            .with("actual", status_code)
+    };
 
     let (passed, message) = match assertion.operator.as_str() {
         "equals" => (
             status_code == expected,
             if status_code == expected {
-                format!("Status code is {}", status_code)
+                msg("status.is")
             } else {
-                format!("Expected {}, got {}", expected, status_code)
+                msg("assertion.expected")
             },
         ),
         "notEquals" => (
             status_code != expected,
             if status_code != expected {
-                format!("Status code is not {}", expected)
+                msg("status.is_not")
             } else {
-                format!("Expected not {}, got {}", expected, status_code)
+                msg("assertion.expected_not")
             },
         ),
         "lessThan" => (
             status_code < expected,
             if status_code < expected {
-                format!("Status code {} < {}", status_code, expected)
+                msg("status.less_than")
             } else {
-                format!("Expected < {}, got {}", expected, status_code)
+                msg("status.expected_less_than")
             },
         ),
         "greaterThan" => (
             status_code > expected,
             if status_code > expected {
-                format!("Status code {} > {}", status_code, expected)
+                msg("status.greater_than")
             } else {
-                format!("Expected > {}, got {}", expected, status_code)
+                msg("status.expected_greater_than")
             },
         ),
-        _ => (false, format!("Unknown operator: {}", assertion.operator)),
+        _ => unknown_operator(assertion),
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
 fn run_response_time_assertion(assertion: &Assertion, timing_ms: i64) -> AssertionResult {
     let expected: i64 = assertion.expected.parse().unwrap_or(0);
     let actual = format!("{}ms", timing_ms);
+    let msg = |code| {
+        Message::new(code)
+            .with("expected", expected)
+            .with("actual", timing_ms)
+    };
 
     let (passed, message) = match assertion.operator.as_str() {
         "lessThan" => (
             timing_ms < expected,
             if timing_ms < expected {
-                format!("Response time {}ms < {}ms", timing_ms, expected)
+                msg("response_time.less_than")
             } else {
-                format!("Expected < {}ms, got {}ms", expected, timing_ms)
+                msg("response_time.expected_less_than")
             },
         ),
         "greaterThan" => (
             timing_ms > expected,
             if timing_ms > expected {
-                format!("Response time {}ms > {}ms", timing_ms, expected)
+                msg("response_time.greater_than")
             } else {
-                format!("Expected > {}ms, got {}ms", expected, timing_ms)
+                msg("response_time.expected_greater_than")
             },
         ),
-        _ => (false, format!("Unknown operator: {}", assertion.operator)),
+        _ => unknown_operator(assertion),
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
 fn run_body_contains_assertion(assertion: &Assertion, body: &str) -> AssertionResult {
@@ -416,109 +825,131 @@ fn run_body_contains_assertion(assertion: &Assertion, body: &str) -> AssertionRe
     } else {
         body.to_string()
     };
+    let contains = || body.contains(&assertion.expected);
+    let msg = |code| Message::new(code).with("expected", &assertion.expected);
+    let pattern_msg = |code| Message::new(code).with("pattern", &assertion.expected);
 
     let (passed, message) = match assertion.operator.as_str() {
         "contains" => (
-            body.contains(&assertion.expected),
-            if body.contains(&assertion.expected) {
-                format!("Body contains \"{}\"", assertion.expected)
+            contains(),
+            if contains() {
+                msg("body.contains")
             } else {
-                format!("Body does not contain \"{}\"", assertion.expected)
+                msg("body.not_contains")
             },
         ),
         "notContains" => (
-            !body.contains(&assertion.expected),
-            if !body.contains(&assertion.expected) {
-                format!("Body does not contain \"{}\"", assertion.expected)
+            !contains(),
+            if !contains() {
+                msg("body.not_contains")
             } else {
-                format!("Body contains \"{}\"", assertion.expected)
+                msg("body.contains")
             },
         ),
-        "matches" => match Regex::new(&assertion.expected) {
+        "matches" => match regex_tester::cached_regex(&assertion.expected) {
             Ok(re) => (
                 re.is_match(body),
                 if re.is_match(body) {
-                    format!("Body matches pattern \"{}\"", assertion.expected)
+                    pattern_msg("body.matches")
                 } else {
-                    format!("Body does not match pattern \"{}\"", assertion.expected)
+                    pattern_msg("body.not_matches")
                 },
             ),
-            Err(_) => (
-                false,
-                format!("Invalid regex pattern: {}", assertion.expected),
-            ),
+            Err(_) => (false, pattern_msg("assertion.invalid_regex")),
         },
-        _ => (false, format!("Unknown operator: {}", assertion.operator)),
+        _ => unknown_operator(assertion),
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
 fn run_body_json_assertion(assertion: &Assertion, body_json: &Option<Value>) -> AssertionResult {
     let body_json = match body_json {
         Some(v) => v,
         None => {
-            return AssertionResult {
-                assertion_id: assertion.id.clone(),
-                passed: false,
-                actual: "Invalid JSON".to_string(),
-                message: "Response body is not valid JSON".to_string(),
-            };
+            return AssertionResult::new(
+                assertion,
+                false,
+                "Invalid JSON".to_string(),
+                Message::new("json.invalid_body"),
+            );
         }
     };
 
-    let value = get_json_path(body_json, &assertion.property);
+    let (extracted, queried);
+    let value = match assertion.query_language {
+        QueryLanguage::Path => {
+            extracted = get_json_path(body_json, &assertion.property);
+            extracted.as_deref()
+        }
+        // JMESPath gives null for anything missing, so null counts as not found.
+        QueryLanguage::JmesPath => match jmespath::search(body_json, &assertion.property) {
+            Ok(v) => {
+                queried = v;
+                Some(&queried).filter(|v| !v.is_null())
+            }
+            Err(e) => {
+                return AssertionResult::new(
+                    assertion,
+                    false,
+                    String::new(),
+                    Message::new("assertion.invalid_query")
+                        .with("query", &assertion.property)
+                        .with("error", e),
+                );
+            }
+        },
+    };
     let actual = match value {
         Some(v) => serde_json::to_string(v).unwrap_or_else(|_| "undefined".to_string()),
         None => "undefined".to_string(),
     };
+    let msg = |code| {
+        Message::new(code)
+            .with("property", &assertion.property)
+            .with("expected", &assertion.expected)
+            .with("actual", &actual)
+    };
 
     let (passed, message) = match assertion.operator.as_str() {
         "exists" => (
             value.is_some(),
             if value.is_some() {
-                format!("Property \"{}\" exists", assertion.property)
+                msg("json.exists")
             } else {
-                format!("Property \"{}\" does not exist", assertion.property)
+                msg("json.not_exists")
             },
         ),
         "notExists" => (
             value.is_none(),
             if value.is_none() {
-                format!("Property \"{}\" does not exist", assertion.property)
+                msg("json.not_exists")
             } else {
-                format!("Property \"{}\" exists", assertion.property)
+                msg("json.exists")
             },
         ),
         "equals" => {
             let expected: Value = serde_json::from_str(&assertion.expected).unwrap_or(Value::Null);
-            let eq = value.is_some_and(|v| v == &expected);
+            let eq = value.is_some_and(|v| compare::json_matches(v, &expected, &assertion.compare));
             (
                 eq,
                 if eq {
-                    format!("{} equals {}", assertion.property, assertion.expected)
+                    msg("json.equals")
                 } else {
-                    format!("Expected {}, got {}", assertion.expected, actual)
+                    msg("assertion.expected")
                 },
             )
         }
         "notEquals" => {
             let expected: Value = serde_json::from_str(&assertion.expected).unwrap_or(Value::Null);
-            let neq = value.is_none_or(|v| v != &expected);
+            let neq =
+                value.is_none_or(|v| !compare::json_matches(v, &expected, &assertion.compare));
             (
                 neq,
                 if neq {
-                    format!(
-                        "{} does not equal {}",
-                        assertion.property, assertion.expected
-                    )
+                    msg("json.not_equals")
                 } else {
-                    format!("Expected not {}, got {}", assertion.expected, actual)
+                    msg("assertion.expected_not")
                 },
             )
         }
@@ -527,125 +958,183 @@ fn run_body_json_assertion(assertion: &Assertion, body_json: &Option<Value>) ->
             (
                 contains,
                 if contains {
-                    format!(
-                        "{} contains \"{}\"",
-                        assertion.property, assertion.expected
-                    )
+                    msg("json.contains")
                 } else {
-                    format!(
-                        "{} does not contain \"{}\"",
-                        assertion.property, assertion.expected
-                    )
+                    msg("json.not_contains")
                 },
             )
         }
-        _ => (false, format!("Unknown operator: {}", assertion.operator)),
+        _ => unknown_operator(assertion),
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
-fn run_header_exists_assertion(
+fn run_no_duplicate_keys_assertion(
     assertion: &Assertion,
-    headers: &HashMap<String, String>,
+    body: &str,
+    body_json: &Option<Value>,
 ) -> AssertionResult {
-    let header_name = assertion.property.to_lowercase();
-    let exists = headers.keys().any(|k| k.to_lowercase() == header_name);
+    // Only index bodies serde accepted, so input nested too deeply is never scanned.
+    let nodes = match body_json.as_ref().map(|_| json_scan::index(body)) {
+        Some(Ok(n)) => n,
+        _ => {
+            return AssertionResult::new(
+                assertion,
+                false,
+                "Invalid JSON".to_string(),
+                Message::new("json.invalid_body"),
+            );
+        }
+    };
+    let duplicates: Vec<String> = json_scan::duplicate_keys(&nodes)
+        .iter()
+        .map(|d| {
+            format!(
+                "{} (line {}, column {})",
+                d.node.path, d.node.position.line, d.node.position.column
+            )
+        })
+        .collect();
+    if duplicates.is_empty() {
+        AssertionResult::new(
+            assertion,
+            true,
+            String::new(),
+            Message::new("json.no_duplicate_keys"),
+        )
+    } else {
+        let actual = duplicates.join(", ");
+        AssertionResult::new(
+            assertion,
+            false,
+            actual.clone(),
+            Message::new("json.duplicate_keys").with("keys", actual),
+        )
+    }
+}
+
+/// `expected` holds the JSON Schema the whole body must satisfy.
+fn run_body_schema_assertion(assertion: &Assertion, body_json: &Option<Value>) -> AssertionResult {
+    let Some(body_json) = body_json else {
+        return AssertionResult::new(
+            assertion,
+            false,
+            "Invalid JSON".to_string(),
+            Message::new("json.invalid_body"),
+        );
+    };
+    let result = serde_json::from_str::<Value>(&assertion.expected)
+        .map_err(|e| e.to_string())
+        .and_then(|schema| schema::validate(body_json, &schema));
+    match result {
+        Err(e) => AssertionResult::new(
+            assertion,
+            false,
+            String::new(),
+            Message::new("json.invalid_schema").with("error", e),
+        ),
+        Ok((violations, _)) if violations.is_empty() => AssertionResult::new(
+            assertion,
+            true,
+            String::new(),
+            Message::new("json.schema_valid"),
+        ),
+        Ok((violations, _)) => {
+            let actual = violations
+                .iter()
+                .map(|v| format!("{}: {}", v.instance_path, v.message))
+                .collect::<Vec<_>>()
+                .join("; ");
+            AssertionResult::new(
+                assertion,
+                false,
+                actual,
+                Message::new("json.schema_violations").with("count", violations.len()),
+            )
+        }
+    }
+}
+
+fn run_header_exists_assertion(assertion: &Assertion, headers: &Headers) -> AssertionResult {
+    let exists = headers.contains(&assertion.property);
     let actual = if exists { "exists" } else { "not found" }.to_string();
+    let msg = |code| Message::new(code).with("header", &assertion.property);
 
     let (passed, message) = match assertion.operator.as_str() {
         "exists" => (
             exists,
             if exists {
-                format!("Header \"{}\" exists", assertion.property)
+                msg("header.exists")
             } else {
-                format!("Header \"{}\" not found", assertion.property)
+                msg("header.not_found")
             },
         ),
         "notExists" => (
             !exists,
             if !exists {
-                format!("Header \"{}\" does not exist", assertion.property)
+                msg("header.not_exists")
             } else {
-                format!("Header \"{}\" exists", assertion.property)
+                msg("header.exists")
             },
         ),
-        _ => (false, format!("Unknown operator: {}", assertion.operator)),
+        _ => unknown_operator(assertion),
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
-fn run_header_equals_assertion(
-    assertion: &Assertion,
-    headers: &HashMap<String, String>,
-) -> AssertionResult {
-    let header_name = assertion.property.to_lowercase();
-    let header_value = headers
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == header_name)
-        .map(|(_, v)| v.clone());
+fn run_header_equals_assertion(assertion: &Assertion, headers: &Headers) -> AssertionResult {
+    // A repeated header matches when one of its values, or all of them combined,
+    // equals the expected value.
+    let header_value = headers.combined(&assertion.property);
+    let equal = header_value.as_deref() == Some(assertion.expected.as_str())
+        || headers
+            .get_all(&assertion.property)
+            .any(|v| v == assertion.expected);
 
     let actual = header_value
         .clone()
         .unwrap_or_else(|| "not found".to_string());
+    let msg = |code| {
+        Message::new(code)
+            .with("header", &assertion.property)
+            .with("expected", &assertion.expected)
+            .with("actual", &actual)
+    };
 
     let (passed, message) = match &header_value {
-        None => (false, format!("Header \"{}\" not found", assertion.property)),
+        None => (false, msg("header.not_found")),
         Some(value) => match assertion.operator.as_str() {
             "equals" => (
-                value == &assertion.expected,
-                if value == &assertion.expected {
-                    format!(
-                        "Header \"{}\" equals \"{}\"",
-                        assertion.property, assertion.expected
-                    )
+                equal,
+                if equal {
+                    msg("header.equals")
                 } else {
-                    format!("Expected \"{}\", got \"{}\"", assertion.expected, value)
+                    msg("header.expected")
                 },
             ),
             "notEquals" => (
-                value != &assertion.expected,
-                if value != &assertion.expected {
-                    format!(
-                        "Header \"{}\" does not equal \"{}\"",
-                        assertion.property, assertion.expected
-                    )
+                !equal,
+                if !equal {
+                    msg("header.not_equals")
                 } else {
-                    format!("Expected not \"{}\", got \"{}\"", assertion.expected, value)
+                    msg("header.expected_not")
                 },
             ),
             "contains" => (
                 value.contains(&assertion.expected),
                 if value.contains(&assertion.expected) {
-                    format!(
-                        "Header \"{}\" contains \"{}\"",
-                        assertion.property, assertion.expected
-                    )
+                    msg("header.contains")
                 } else {
-                    format!("Header does not contain \"{}\"", assertion.expected)
+                    msg("header.not_contains")
                 },
             ),
-            _ => (false, format!("Unknown operator: {}", assertion.operator)),
+            _ => unknown_operator(assertion),
         },
     };
 
-    AssertionResult {
-        assertion_id: assertion.id.clone(),
-        passed,
-        actual,
-        message,
-    }
+    AssertionResult::new(assertion, passed, actual, message)
 }
 
 /// Parse query parameters from a URL string.
@@ -661,7 +1150,17 @@ pub fn parse_query_params(url: &str) -> String {
         return "[]".to_string();
     }
 
-    let pairs: Vec<Value> = query
+    let pairs: Vec<Value> = query_pairs(query)
+        .into_iter()
+        .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+        .collect();
+
+    serde_json::to_string(&pairs).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Decode a raw query string ("a=1&b=2") into key/value pairs, in order.
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
         .split('&')
         .filter(|s| !s.is_empty())
         .map(|pair| {
@@ -669,14 +1168,37 @@ pub fn parse_query_params(url: &str) -> String {
                 Some(pos) => (&pair[..pos], &pair[pos + 1..]),
                 None => (pair, ""),
             };
-            serde_json::json!({
-                "key": percent_decode(key),
-                "value": percent_decode(value),
-            })
+            (percent_decode(key), percent_decode(value))
         })
-        .collect();
+        .collect()
+}
 
-    serde_json::to_string(&pairs).unwrap_or_else(|_| "[]".to_string())
+/// Split a URL into (origin, path, query), dropping any fragment.
+/// The origin is "scheme://host[:port]" (or a leading "{{var}}"/host segment
+/// when there is no scheme); path and query may be empty.
+fn split_url(url: &str) -> (&str, &str, &str) {
+    let url = url.trim();
+    let url = match url.find('#') {
+        Some(pos) => &url[..pos],
+        None => url,
+    };
+    let (before_query, query) = match url.find('?') {
+        Some(pos) => (&url[..pos], &url[pos + 1..]),
+        None => (url, ""),
+    };
+    let authority_start = match before_query.find("://") {
+        Some(pos) => pos + 3,
+        None if before_query.starts_with('/') => return ("", before_query, query),
+        None => 0,
+    };
+    match before_query[authority_start..].find('/') {
+        Some(pos) => (
+            &before_query[..authority_start + pos],
+            &before_query[authority_start + pos..],
+            query,
+        ),
+        None => (before_query, "", query),
+    }
 }
 
 /// Build a URL with percent-encoded query parameters.
@@ -693,7 +1215,10 @@ pub fn build_url_with_params(base_url: &str, params_json: &str) -> String {
 
     let params: Vec<Param> = match serde_json::from_str(params_json) {
         Ok(p) => p,
-        Err(_) => return base_url.to_string(),
+        Err(e) => {
+            logging::warn("build_url_with_params", || format!("invalid params JSON: {}", e));
+            return base_url.to_string();
+        }
     };
 
     let enabled: Vec<&Param> = params
@@ -732,7 +1257,10 @@ pub fn encode_form_data(pairs_json: &str) -> String {
 
     let pairs: Vec<Pair> = match serde_json::from_str(pairs_json) {
         Ok(p) => p,
-        Err(_) => return String::new(),
+        Err(e) => {
+            logging::warn("encode_form_data", || format!("invalid pairs JSON: {}", e));
+            return String::new();
+        }
     };
 
     pairs
@@ -752,19 +1280,24 @@ pub fn build_basic_auth(username: &str, password: &str) -> String {
 }
 
 /// Parse Set-Cookie response headers into structured cookie objects.
-/// headers_json: JSON object of response headers (key → value).
+/// headers_json: response headers as a JSON object (key → value, or key → [values]
+/// for repeated headers) or a list of {name, value}. A value holding several
+/// cookies separated by newlines is split.
 /// Returns JSON array of {name, value, path?, domain?, expires?, maxAge?, secure?, httpOnly?, sameSite?}.
 #[wasm_bindgen]
 pub fn parse_cookies(headers_json: &str) -> String {
-    let headers: HashMap<String, String> = match serde_json::from_str(headers_json) {
+    let headers: Headers = match serde_json::from_str(headers_json) {
         Ok(h) => h,
-        Err(_) => return "[]".to_string(),
+        Err(e) => {
+            logging::warn("parse_cookies", || format!("invalid headers JSON: {}", e));
+            return "[]".to_string();
+        }
     };
 
     let cookies: Vec<Value> = headers
-        .iter()
-        .filter(|(k, _)| k.to_lowercase() == "set-cookie")
-        .filter_map(|(_, v)| parse_single_cookie(v))
+        .get_all("set-cookie")
+        .flat_map(str::lines)
+        .filter_map(parse_single_cookie)
         .collect();
 
     serde_json::to_string(&cookies).unwrap_or_else(|_| "[]".to_string())
@@ -789,12 +1322,12 @@ fn percent_decode(s: &str) -> String {
     let mut i = 0;
     while i < bytes.len() {
         if bytes[i] == b'%' && i + 2 < bytes.len() {
-            if let Ok(hex_str) = std::str::from_utf8(&bytes[i + 1..i + 3]) {
-                if let Ok(byte) = u8::from_str_radix(hex_str, 16) {
-                    out.push(byte as char);
-                    i += 3;
-                    continue;
-                }
+            if let Ok(hex_str) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                && let Ok(byte) = u8::from_str_radix(hex_str, 16)
+            {
+                out.push(byte as char);
+                i += 3;
+                continue;
             }
         } else if bytes[i] == b'+' {
             out.push(' ');
@@ -808,8 +1341,15 @@ fn percent_decode(s: &str) -> String {
 }
 
 fn base64_encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    base64_encode_into(&mut out, input);
+    out
+}
+
+/// Append the base64 encoding of `input` to `out`, for callers that build a larger
+/// string around it without an intermediate copy.
+fn base64_encode_into(out: &mut String, input: &[u8]) {
     const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut out = String::with_capacity((input.len() + 2) / 3 * 4);
     for chunk in input.chunks(3) {
         let b0 = chunk[0] as u32;
         let b1 = if chunk.len() > 1 { chunk[1] as u32 } else { 0 };
@@ -820,7 +1360,39 @@ fn base64_encode(input: &[u8]) -> String {
         out.push(if chunk.len() > 1 { CHARS[((n >> 6) & 0x3F) as usize] as char } else { '=' });
         out.push(if chunk.len() > 2 { CHARS[(n & 0x3F) as usize] as char } else { '=' });
     }
-    out
+}
+
+/// Lowercase hex encoding.
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode standard or URL-safe base64, with or without padding.
+/// Whitespace is ignored; returns None on any other invalid character.
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buf: u32 = 0;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None,
+        };
+        buf = (buf << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
 }
 
 fn parse_single_cookie(set_cookie: &str) -> Option<Value> {
@@ -894,6 +1466,19 @@ mod tests {
         assert_eq!(vars, vec!["baseUrl", "userId", "token"]);
     }
 
+    #[test]
+    fn test_hyphenated_variable_names() {
+        let vars: Vec<String> =
+            serde_json::from_str(&find_variables("{{x-api-key}} {{api-version}}")).unwrap();
+        assert_eq!(vars, vec!["x-api-key", "api-version"]);
+        let values = r#"{"x-api-key":"secret","api-version":"2","x":"7","api":"1","key":"2"}"#;
+        assert_eq!(
+            substitute_variables("{{x-api-key}}/v{{api-version}}", values),
+            "secret/v2"
+        );
+        assert_eq!(substitute_variables("{{x - key}}", values), "5");
+    }
+
     #[test]
     fn test_json_extract() {
         let json = r#"{"data":{"users":[{"name":"John"}]}}"#;
@@ -901,6 +1486,194 @@ mod tests {
         assert_eq!(result, "\"John\"");
     }
 
+    #[test]
+    fn test_json_extract_v2() {
+        let json = r#"{"a":null,"b":"undefined","c":[1]}"#;
+        assert_eq!(json_extract_v2(json, "a"), r#"{"found":true,"value":null}"#);
+        assert_eq!(
+            json_extract_v2(json, "b"),
+            r#"{"found":true,"value":"undefined"}"#
+        );
+        assert_eq!(json_extract_v2(json, "c[1]"), r#"{"found":false}"#);
+        let out: Value = serde_json::from_str(&json_extract_v2("{", "a")).unwrap();
+        assert_eq!(out["found"], false);
+        assert!(out["error"].as_str().unwrap().starts_with("Invalid JSON"));
+    }
+
+    #[test]
+    fn test_json_extract_path_syntax() {
+        let json =
+            r#"{"items":[{"id":1},{"id":2},{"name":"x"},{"id":4}],"data":{"a.b":[[1,2],[3]]}}"#;
+        assert_eq!(json_extract(json, "items[-1].id"), "4");
+        assert_eq!(json_extract(json, "items[*].id"), "[1,2,4]");
+        assert_eq!(
+            json_extract(json, "items[1:3]"),
+            r#"[{"id":2},{"name":"x"}]"#
+        );
+        assert_eq!(json_extract(json, "items[-2:].id"), "[4]");
+        assert_eq!(json_extract(json, r#"data["a.b"][0][1]"#), "2");
+        assert_eq!(json_extract(json, "data['a.b'][*][0]"), "[1,3]");
+        assert_eq!(json_extract(json, "data[*][5:]"), "[]");
+        assert_eq!(json_extract_v2(json, "items[-5]"), r#"{"found":false}"#);
+        for malformed in ["items[", "items[x]", "data[\"a.b]", "a..b", "items[0]id"] {
+            assert_eq!(json_extract_v2(json, malformed), r#"{"found":false}"#);
+        }
+    }
+
+    #[test]
+    fn test_json_extract_batch_preserves_path_order() {
+        let json = r#"{"b":2,"a":1,"c":{"z":true}}"#;
+        let result = json_extract_batch(json, r#"["c.z","missing","b","a"]"#);
+        assert_eq!(result, r#"{"c.z":true,"b":2,"a":1}"#);
+    }
+
+    #[cfg(feature = "precise_numbers")]
+    #[test]
+    fn test_json_preserves_big_numbers() {
+        let body = r#"{"id":9007199254740993,"price":19.990,"big":123456789012345678901234567890}"#;
+        assert_eq!(json_minify(body), body);
+        assert!(json_format(body).contains("\"id\": 9007199254740993"));
+        assert!(json_format(body).contains("\"price\": 19.990"));
+        assert_eq!(json_extract(body, "id"), "9007199254740993");
+        assert_eq!(json_extract(body, "big"), "123456789012345678901234567890");
+        assert_eq!(
+            json_extract_batch(body, r#"["price","id"]"#),
+            r#"{"price":19.990,"id":9007199254740993}"#
+        );
+        assert!(!compare::json_compare(
+            "123456789012345678901234567891",
+            "123456789012345678901234567890",
+            "{}"
+        ));
+    }
+
+    #[test]
+    fn test_no_duplicate_keys_assertion() {
+        let assertions = r#"[{"id":"d","type":"noDuplicateKeys","property":"","operator":"","expected":"","enabled":true}]"#;
+        let dup = r#"{"statusCode":200,"headers":{},"body":"{\"a\":1,\"a\":2}","timingMs":1}"#;
+        let results: Vec<Value> =
+            serde_json::from_str(&run_assertions(assertions, dup, None)).unwrap();
+        assert_eq!(results[0]["passed"], false);
+        assert_eq!(results[0]["code"], "json.duplicate_keys");
+        assert_eq!(results[0]["actual"], "a (line 1, column 8)");
+
+        let ok = r#"{"statusCode":200,"headers":{},"body":"{\"a\":1}","timingMs":1}"#;
+        let results: Vec<Value> =
+            serde_json::from_str(&run_assertions(assertions, ok, None)).unwrap();
+        assert_eq!(results[0]["passed"], true);
+        // A body nested too deeply fails the assertion instead of overflowing the stack.
+        let deep = serde_json::json!({"statusCode": 200, "headers": {}, "body": "[".repeat(5000), "timingMs": 1});
+        let results: Vec<Value> =
+            serde_json::from_str(&run_assertions(assertions, &deep.to_string(), None)).unwrap();
+        assert_eq!(results[0]["code"], "json.invalid_body");
+    }
+
+    #[test]
+    fn test_body_schema_assertion() {
+        let assertion = |schema: &str| {
+            serde_json::json!([{"id": "s", "type": "bodySchema", "property": "", "operator": "",
+                "expected": schema, "enabled": true}])
+            .to_string()
+        };
+        let response = r#"{"statusCode":200,"headers":{},"body":"{\"id\":\"7\",\"tags\":[]}","timingMs":1}"#;
+        let run = |schema: &str| -> Value {
+            let results: Vec<Value> =
+                serde_json::from_str(&run_assertions(&assertion(schema), response, None)).unwrap();
+            results[0].clone()
+        };
+        let result = run(r#"{"type": "object", "properties": {"id": {"type": "integer"}}, "required": ["name"]}"#);
+        assert_eq!(result["passed"], false);
+        assert_eq!(result["code"], "json.schema_violations");
+        assert_eq!(result["message"], "Schema violations: 2");
+        assert_eq!(
+            result["actual"],
+            ": Missing required property \"name\"; /id: Expected integer, got string"
+        );
+        assert_eq!(run(r#"{"properties": {"tags": {"maxItems": 0}}}"#)["passed"], true);
+        assert_eq!(run(r#"{"properties": {"id": {"pattern": "("}}}"#)["code"], "json.invalid_schema");
+    }
+
+    #[test]
+    fn test_body_json_equals_compare_options() {
+        let response = r#"{"statusCode":200,"headers":{},"body":"{\"tags\":[\"b\",\"a\"],\"extra\":1}","timingMs":1}"#;
+        let exact = r#"[{"id":"1","type":"bodyJson","property":"","operator":"equals","expected":"{\"tags\":[\"a\",\"b\"]}","enabled":true}]"#;
+        let loose = r#"[{"id":"1","type":"bodyJson","property":"","operator":"equals","expected":"{\"tags\":[\"a\",\"b\"]}","enabled":true,
+            "compare":{"ignoreArrayOrder":true,"ignoreExtraKeys":true}}]"#;
+        let passed = |a: &str| {
+            let results: Vec<Value> =
+                serde_json::from_str(&run_assertions(a, response, None)).unwrap();
+            results[0]["passed"].as_bool().unwrap()
+        };
+        assert!(!passed(exact));
+        assert!(passed(loose));
+    }
+
+    #[test]
+    fn test_body_json_jmespath_assertions() {
+        let response = r#"{"statusCode":200,"headers":{},"body":"{\"users\":[{\"name\":\"Ann\",\"age\":34},{\"name\":\"Bo\",\"age\":25}]}","timingMs":1}"#;
+        let assertions = r#"[
+            {"id":"1","type":"bodyJson","property":"users[?age > `30`].name","operator":"equals","expected":"[\"Ann\"]","enabled":true,"queryLanguage":"jmespath"},
+            {"id":"2","type":"bodyJson","property":"users[?age > `90`] | [0]","operator":"notExists","expected":"","enabled":true,"queryLanguage":"jmespath"},
+            {"id":"3","type":"bodyJson","property":"users[","operator":"exists","expected":"","enabled":true,"queryLanguage":"jmespath"},
+            {"id":"4","type":"bodyJson","property":"users[1].name","operator":"equals","expected":"\"Bo\"","enabled":true}
+        ]"#;
+        let results: Vec<Value> =
+            serde_json::from_str(&run_assertions(assertions, response, None)).unwrap();
+        let passed: Vec<bool> = results.iter().map(|r| r["passed"] == true).collect();
+        assert_eq!(passed, [true, true, false, true]);
+        assert_eq!(results[2]["code"], "assertion.invalid_query");
+    }
+
+    #[test]
+    fn test_json_minify_preserves_key_order() {
+        assert_eq!(json_minify(r#"{ "z": 1, "a": [ 2 ] }"#), r#"{"z":1,"a":[2]}"#);
+    }
+
+    #[test]
+    fn test_parse_cookies_in_header_order() {
+        let headers = r#"{"Set-Cookie":"b=2; Path=/","set-cookie":"a=1; Secure"}"#;
+        let cookies: Vec<Value> = serde_json::from_str(&parse_cookies(headers)).unwrap();
+        assert_eq!(cookies[0]["name"], "b");
+        assert_eq!(cookies[1]["name"], "a");
+        assert_eq!(cookies[1]["secure"], true);
+    }
+
+    #[test]
+    fn test_parse_cookies_repeated_headers() {
+        let map = r#"{"Set-Cookie":["a=1; Path=/","b=2; HttpOnly"],"Vary":"Accept"}"#;
+        let cookies: Vec<Value> = serde_json::from_str(&parse_cookies(map)).unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(cookies[1]["httpOnly"], true);
+
+        let har =
+            r#"[{"name":"set-cookie","value":"a=1"},{"name":"Set-Cookie","value":"b=2\nc=3"}]"#;
+        let cookies: Vec<Value> = serde_json::from_str(&parse_cookies(har)).unwrap();
+        let names: Vec<&str> = cookies
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_header_assertions_with_repeated_values() {
+        let assertions = r#"[
+            {"id":"1","type":"headerEquals","property":"vary","operator":"equals","expected":"Origin","enabled":true},
+            {"id":"2","type":"headerEquals","property":"Vary","operator":"equals","expected":"Accept, Origin","enabled":true},
+            {"id":"3","type":"headerEquals","property":"Vary","operator":"notEquals","expected":"Accept","enabled":true},
+            {"id":"4","type":"headerExists","property":"x-trace","operator":"notExists","expected":"","enabled":true}
+        ]"#;
+        let response = r#"{"statusCode":200,"headers":[["Vary","Accept"],["Vary","Origin"]],"body":"","timingMs":1}"#;
+        let results: Vec<Value> =
+            serde_json::from_str(&run_assertions(assertions, response, None)).unwrap();
+        let passed: Vec<bool> = results
+            .iter()
+            .map(|r| r["passed"].as_bool().unwrap())
+            .collect();
+        assert_eq!(passed, [true, true, false, true]);
+        assert_eq!(results[0]["actual"], "Accept, Origin");
+    }
+
     #[test]
     fn test_json_format() {
         let json = r#"{"name":"John","age":30}"#;
@@ -921,6 +1694,24 @@ mod tests {
         assert!(!has_variables("no variables"));
     }
 
+    #[test]
+    fn test_run_assertions_message_codes() {
+        let assertions = r#"[
+            {"id":"1","type":"status","property":"","operator":"equals","expected":"200","enabled":true},
+            {"id":"2","type":"headerEquals","property":"X-Mode","operator":"equals","expected":"a","enabled":true},
+            {"id":"3","type":"bodyJson","property":"id","operator":"exists","expected":"","enabled":false}
+        ]"#;
+        let response = r#"{"statusCode":404,"headers":{"x-mode":"b"},"body":"{}","timingMs":5}"#;
+        let results: Vec<Value> = serde_json::from_str(&run_assertions(assertions, response, None)).unwrap();
+        assert_eq!(results[0]["code"], "assertion.expected");
+        assert_eq!(results[0]["params"]["actual"], "404");
+        assert_eq!(results[0]["message"], "Expected 200, got 404");
+        assert_eq!(results[1]["code"], "header.expected");
+        assert_eq!(results[1]["message"], "Expected \"a\", got \"b\"");
+        assert_eq!(results[2]["code"], "assertion.skipped");
+        assert_eq!(results[2]["passed"], true);
+    }
+
     #[test]
     fn test_parse_query_params() {
         let result = parse_query_params("https://api.example.com/users?name=John&age=30");
@@ -962,6 +1753,55 @@ mod tests {
         assert_eq!(base64_encode(b"hello"), "aGVsbG8=");
     }
 
+    #[test]
+    fn test_civil_date_conversions() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in [-800_000, -1, 0, 59, 365, 11_016, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_parse_iso_datetime() {
+        assert_eq!(parse_iso_datetime("1970-01-02"), Some(86_400_000.0));
+        assert_eq!(
+            parse_iso_datetime("2024-02-29T10:00:00.250Z"),
+            Some(1_709_200_800_250.0)
+        );
+        assert_eq!(
+            parse_iso_datetime("2024-02-29 12:00+02:00"),
+            parse_iso_datetime("2024-02-29T10:00:00Z")
+        );
+        assert_eq!(parse_iso_datetime("2024-13-01"), None);
+        assert_eq!(parse_iso_datetime("yesterday"), None);
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            parse_iso_datetime("2015-10-21T07:28:00Z")
+        );
+        assert_eq!(
+            parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            parse_iso_datetime("1994-11-06T08:49:37Z")
+        );
+        assert_eq!(parse_http_date("120"), None);
+    }
+
+    #[test]
+    fn test_base64_decode() {
+        assert_eq!(base64_decode("dXNlcjpwYXNz").unwrap(), b"user:pass");
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(base64_decode("-_8").unwrap(), vec![0xfb, 0xff]);
+        assert!(base64_decode("not*base64").is_none());
+    }
+
     #[test]
     fn test_percent_encode() {
         assert_eq!(percent_encode("hello world"), "hello+world");
@@ -0,0 +1,327 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, VecDeque};
+use wasm_bindgen::prelude::*;
+
+/// Histogram bucket growth factor: percentiles are accurate to within 1%.
+const BUCKET_GROWTH: f64 = 1.01;
+/// Percentiles reported in every snapshot.
+const PERCENTILES: [f64; 6] = [50.0, 75.0, 90.0, 95.0, 99.0, 99.9];
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Sample {
+    /// Milliseconds since the epoch when the request finished.
+    timestamp: Option<f64>,
+    duration_ms: f64,
+    status: Option<u16>,
+    /// Any truthy value (or a message) marks the sample as failed.
+    error: Value,
+    bytes: Option<u64>,
+}
+
+impl Sample {
+    /// k6 semantics: transport errors and HTTP status >= 400 are failures.
+    fn failed(&self) -> bool {
+        let flagged = match &self.error {
+            Value::Null | Value::Bool(false) => false,
+            Value::String(s) => !s.is_empty(),
+            _ => true,
+        };
+        flagged || matches!(self.status, Some(0) | Some(400..))
+    }
+}
+
+#[derive(Default, Clone)]
+struct Window {
+    start_ms: f64,
+    count: u64,
+    errors: u64,
+    total_ms: f64,
+}
+
+/// Streaming aggregate of load-test samples with bounded memory.
+/// Durations go into a log-bucketed histogram (HDR-style, ±1%), so any number
+/// of samples can be recorded while percentiles stay cheap to read.
+#[wasm_bindgen]
+pub struct StatsAccumulator {
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+    errors: u64,
+    sum_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+    bytes: u64,
+    statuses: BTreeMap<u16, u64>,
+    first_ms: Option<f64>,
+    last_ms: Option<f64>,
+    window_ms: f64,
+    max_windows: usize,
+    windows: VecDeque<Window>,
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> Self {
+        StatsAccumulator::with_window(1000.0, 60)
+    }
+}
+
+#[wasm_bindgen]
+impl StatsAccumulator {
+    /// Accumulator with one-second throughput windows, keeping the last 60.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> StatsAccumulator {
+        StatsAccumulator::default()
+    }
+
+    /// Accumulator with custom throughput windows.
+    pub fn with_window(window_ms: f64, max_windows: usize) -> StatsAccumulator {
+        StatsAccumulator {
+            buckets: BTreeMap::new(),
+            count: 0,
+            errors: 0,
+            sum_ms: 0.0,
+            min_ms: f64::INFINITY,
+            max_ms: 0.0,
+            bytes: 0,
+            statuses: BTreeMap::new(),
+            first_ms: None,
+            last_ms: None,
+            window_ms: if window_ms > 0.0 { window_ms } else { 1000.0 },
+            max_windows: max_windows.max(1),
+            windows: VecDeque::new(),
+        }
+    }
+
+    /// Record one sample: {timestamp, durationMs, status, error, bytes}.
+    /// `timestamp` defaults to now. Returns false if the sample is not valid JSON.
+    pub fn record(&mut self, sample_json: &str) -> bool {
+        match serde_json::from_str::<Sample>(sample_json) {
+            Ok(s) => {
+                self.add(&s);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Record a JSON array of samples. Returns how many were recorded.
+    pub fn record_batch(&mut self, samples_json: &str) -> usize {
+        let samples: Vec<Sample> = serde_json::from_str(samples_json).unwrap_or_default();
+        for s in &samples {
+            self.add(s);
+        }
+        samples.len()
+    }
+
+    /// Duration at the given percentile (0–100), or NaN if nothing was recorded.
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * self.count as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (&bucket, &n) in &self.buckets {
+            seen += n;
+            if seen >= rank {
+                return bucket_value(bucket).clamp(self.min_ms, self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+
+    /// Current aggregate as JSON {count, errors, errorRate, minMs, maxMs, meanMs,
+    /// percentiles: {p50, p75, p90, p95, p99, p99.9}, rps, bytes, statusCodes,
+    /// windows: [{start, count, errors, meanMs, rps}]}.
+    pub fn snapshot(&self) -> String {
+        let mut percentiles = Map::new();
+        for p in PERCENTILES {
+            percentiles.insert(format!("p{}", p), number(self.percentile(p)));
+        }
+        let elapsed_s = match (self.first_ms, self.last_ms) {
+            (Some(a), Some(b)) if b > a => (b - a) / 1000.0,
+            _ => 0.0,
+        };
+        let statuses: Map<String, Value> = self
+            .statuses
+            .iter()
+            .map(|(s, n)| (s.to_string(), Value::from(*n)))
+            .collect();
+        let windows: Vec<Value> = self
+            .windows
+            .iter()
+            .map(|w| {
+                serde_json::json!({
+                    "start": w.start_ms,
+                    "count": w.count,
+                    "errors": w.errors,
+                    "meanMs": number(if w.count > 0 { w.total_ms / w.count as f64 } else { f64::NAN }),
+                    "rps": w.count as f64 * 1000.0 / self.window_ms,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "count": self.count,
+            "errors": self.errors,
+            "errorRate": if self.count > 0 { self.errors as f64 / self.count as f64 } else { 0.0 },
+            "minMs": number(if self.count > 0 { self.min_ms } else { f64::NAN }),
+            "maxMs": number(if self.count > 0 { self.max_ms } else { f64::NAN }),
+            "meanMs": number(if self.count > 0 { self.sum_ms / self.count as f64 } else { f64::NAN }),
+            "percentiles": percentiles,
+            "rps": number(if elapsed_s > 0.0 { self.count as f64 / elapsed_s } else { f64::NAN }),
+            "bytes": self.bytes,
+            "statusCodes": statuses,
+            "windows": windows,
+        })
+        .to_string()
+    }
+
+    /// Forget all samples (window settings are kept).
+    pub fn reset(&mut self) {
+        *self = StatsAccumulator::with_window(self.window_ms, self.max_windows);
+    }
+}
+
+impl StatsAccumulator {
+    fn add(&mut self, s: &Sample) {
+        let duration = if s.duration_ms.is_finite() {
+            s.duration_ms.max(0.0)
+        } else {
+            0.0
+        };
+        let failed = s.failed();
+        *self.buckets.entry(bucket_index(duration)).or_insert(0) += 1;
+        self.count += 1;
+        self.sum_ms += duration;
+        self.min_ms = self.min_ms.min(duration);
+        self.max_ms = self.max_ms.max(duration);
+        if failed {
+            self.errors += 1;
+        }
+        self.bytes += s.bytes.unwrap_or(0);
+        if let Some(status) = s.status {
+            *self.statuses.entry(status).or_insert(0) += 1;
+        }
+
+        let ts = s.timestamp.unwrap_or_else(crate::now_ms);
+        self.first_ms = Some(self.first_ms.map_or(ts, |f| f.min(ts)));
+        self.last_ms = Some(self.last_ms.map_or(ts, |l| l.max(ts)));
+
+        let start = (ts / self.window_ms).floor() * self.window_ms;
+        let pos = self.windows.iter().position(|w| w.start_ms == start);
+        let window = match pos {
+            Some(i) => &mut self.windows[i],
+            None => {
+                // Samples older than the retained windows only count in the totals.
+                if self.windows.len() == self.max_windows
+                    && self.windows.front().is_some_and(|w| start < w.start_ms)
+                {
+                    return;
+                }
+                let at = self.windows.partition_point(|w| w.start_ms < start);
+                self.windows.insert(
+                    at,
+                    Window {
+                        start_ms: start,
+                        ..Window::default()
+                    },
+                );
+                if self.windows.len() > self.max_windows {
+                    self.windows.pop_front();
+                    &mut self.windows[at - 1]
+                } else {
+                    &mut self.windows[at]
+                }
+            }
+        };
+        window.count += 1;
+        window.total_ms += duration;
+        if failed {
+            window.errors += 1;
+        }
+    }
+}
+
+/// Sub-millisecond durations share bucket 0; above that buckets grow by 1%.
+fn bucket_index(ms: f64) -> i32 {
+    if ms < 1.0 {
+        0
+    } else {
+        (ms.ln() / BUCKET_GROWTH.ln()).floor() as i32 + 1
+    }
+}
+
+/// Representative value (geometric midpoint) of a bucket.
+fn bucket_value(index: i32) -> f64 {
+    if index == 0 {
+        return 0.5;
+    }
+    let low = BUCKET_GROWTH.powi(index - 1);
+    low * BUCKET_GROWTH.sqrt()
+}
+
+/// NaN/infinite values become null, since JSON has no representation for them.
+fn number(n: f64) -> Value {
+    serde_json::Number::from_f64(n)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_accumulator_percentiles() {
+        let mut acc = StatsAccumulator::new();
+        let samples: Vec<Value> = (1..=1000)
+            .map(
+                |i| serde_json::json!({"timestamp": 1_000_000 + i, "durationMs": i, "status": 200}),
+            )
+            .collect();
+        assert_eq!(acc.record_batch(&Value::Array(samples).to_string()), 1000);
+        let within = |actual: f64, expected: f64| (actual - expected).abs() / expected <= 0.01;
+        assert!(within(acc.percentile(50.0), 500.0));
+        assert!(within(acc.percentile(95.0), 950.0));
+        assert!(within(acc.percentile(99.0), 990.0));
+        assert_eq!(acc.percentile(100.0), 1000.0);
+
+        let snap: Value = serde_json::from_str(&acc.snapshot()).unwrap();
+        assert_eq!(snap["count"], 1000);
+        assert_eq!(snap["minMs"], 1.0);
+        assert_eq!(snap["meanMs"], 500.5);
+        assert!(within(
+            snap["percentiles"]["p99.9"].as_f64().unwrap(),
+            999.0
+        ));
+        assert_eq!(snap["statusCodes"]["200"], 1000);
+    }
+
+    #[test]
+    fn test_stats_accumulator_errors_and_windows() {
+        let mut acc = StatsAccumulator::with_window(1000.0, 2);
+        assert!(acc.record(r#"{"timestamp":0,"durationMs":10,"status":200}"#));
+        assert!(acc.record(r#"{"timestamp":500,"durationMs":20,"status":500}"#));
+        assert!(acc.record(r#"{"timestamp":1500,"durationMs":30,"error":"ECONNRESET"}"#));
+        assert!(acc.record(r#"{"timestamp":2500,"durationMs":40,"status":201}"#));
+        assert!(!acc.record("nope"));
+
+        let snap: Value = serde_json::from_str(&acc.snapshot()).unwrap();
+        assert_eq!(snap["errors"], 2);
+        assert_eq!(snap["errorRate"], 0.5);
+        assert_eq!(snap["rps"], 1.6);
+        let windows = snap["windows"].as_array().unwrap();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0]["start"], 1000.0);
+        assert_eq!(windows[0]["errors"], 1);
+        assert_eq!(windows[1]["meanMs"], 40.0);
+
+        acc.reset();
+        let empty: Value = serde_json::from_str(&acc.snapshot()).unwrap();
+        assert_eq!(empty["count"], 0);
+        assert_eq!(empty["percentiles"]["p95"], Value::Null);
+    }
+}
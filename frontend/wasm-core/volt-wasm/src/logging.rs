@@ -0,0 +1,182 @@
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub(crate) enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+thread_local! {
+    /// Maximum level that is emitted; 0 = off. Defaults to errors only.
+    static MAX_LEVEL: Cell<u8> = const { Cell::new(Level::Error as u8) };
+    static CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(s: &str);
+}
+
+/// Set the most verbose level that is reported: "off", "error", "warn",
+/// "info", "debug" or "trace". Returns false (and leaves the level unchanged)
+/// for an unknown level.
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> bool {
+    let value = match level.trim().to_lowercase().as_str() {
+        "off" | "none" => 0,
+        "error" => Level::Error as u8,
+        "warn" | "warning" => Level::Warn as u8,
+        "info" => Level::Info as u8,
+        "debug" => Level::Debug as u8,
+        "trace" => Level::Trace as u8,
+        _ => return false,
+    };
+    MAX_LEVEL.with(|l| l.set(value));
+    true
+}
+
+/// Route log events to a callback instead of the console. The callback is
+/// invoked with {level, target, message, durationMs?}; pass null/undefined
+/// to go back to console output.
+#[wasm_bindgen]
+pub fn set_log_callback(callback: Option<js_sys::Function>) {
+    CALLBACK.with(|c| *c.borrow_mut() = callback);
+}
+
+pub(crate) fn enabled(level: Level) -> bool {
+    MAX_LEVEL.with(|l| level as u8 <= l.get())
+}
+
+fn emit(level: Level, target: &str, message: &str, duration_ms: Option<f64>) {
+    let delivered = CALLBACK.with(|c| {
+        let callback = c.borrow();
+        let Some(callback) = callback.as_ref() else {
+            return false;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"level".into(), &level.as_str().into());
+        let _ = js_sys::Reflect::set(&event, &"target".into(), &target.into());
+        let _ = js_sys::Reflect::set(&event, &"message".into(), &message.into());
+        if let Some(ms) = duration_ms {
+            let _ = js_sys::Reflect::set(&event, &"durationMs".into(), &ms.into());
+        }
+        let _ = callback.call1(&JsValue::NULL, &event);
+        true
+    });
+    if delivered {
+        return;
+    }
+
+    let line = format!("[volt-wasm] {}: {}", target, message);
+    #[cfg(target_arch = "wasm32")]
+    match level {
+        Level::Error => console_error(&line),
+        Level::Warn => console_warn(&line),
+        Level::Info => console_info(&line),
+        Level::Debug | Level::Trace => console_debug(&line),
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{} {}", level.as_str(), line);
+}
+
+fn log(level: Level, target: &str, message: impl FnOnce() -> String) {
+    if enabled(level) {
+        emit(level, target, &message(), None);
+    }
+}
+
+pub(crate) fn warn(target: &str, message: impl FnOnce() -> String) {
+    log(Level::Warn, target, message);
+}
+
+pub(crate) fn debug(target: &str, message: impl FnOnce() -> String) {
+    log(Level::Debug, target, message);
+}
+
+/// Logs the wall time of a scope at debug level when dropped.
+pub(crate) struct Timer {
+    target: &'static str,
+    started: Option<f64>,
+}
+
+pub(crate) fn timer(target: &'static str) -> Timer {
+    Timer {
+        target,
+        started: enabled(Level::Debug).then(crate::now_ms),
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(started) = self.started {
+            let elapsed = crate::now_ms() - started;
+            emit(
+                Level::Debug,
+                self.target,
+                &format!("finished in {:.2}ms", elapsed),
+                Some(elapsed),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_log_level() {
+        assert!(enabled(Level::Error));
+        assert!(!enabled(Level::Warn));
+        assert!(set_log_level("DEBUG"));
+        assert!(enabled(Level::Debug));
+        assert!(!enabled(Level::Trace));
+        assert!(!set_log_level("verbose"));
+        assert!(enabled(Level::Debug));
+        assert!(set_log_level("off"));
+        assert!(!enabled(Level::Error));
+    }
+
+    #[test]
+    fn test_timer_only_runs_when_debug_enabled() {
+        set_log_level("warn");
+        assert!(timer("test").started.is_none());
+        set_log_level("debug");
+        assert!(timer("test").started.is_some());
+    }
+
+    #[test]
+    fn test_message_not_built_when_disabled() {
+        set_log_level("error");
+        let mut built = false;
+        warn("test", || {
+            built = true;
+            String::new()
+        });
+        assert!(!built);
+    }
+}
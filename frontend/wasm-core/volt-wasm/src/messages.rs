@@ -0,0 +1,465 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// A stable message code plus the parameters needed to render it.
+pub(crate) struct Message {
+    pub code: &'static str,
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub(crate) fn new(code: &'static str) -> Self {
+        Message {
+            code,
+            params: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    /// Default English rendering.
+    pub(crate) fn render(&self) -> String {
+        let params: HashMap<&str, &str> =
+            self.params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        render(self.code, &params, "en")
+    }
+
+    pub(crate) fn params_json(&self) -> serde_json::Map<String, Value> {
+        self.params
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.clone())))
+            .collect()
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("assertion.skipped", "Skipped (disabled)"),
+    ("assertion.unknown_type", "Unknown assertion type: {type}"),
+    ("assertion.unknown_operator", "Unknown operator: {operator}"),
+    (
+        "assertion.invalid_regex",
+        "Invalid regex pattern: {pattern}",
+    ),
+    (
+        "assertion.invalid_query",
+        "Invalid query \"{query}\": {error}",
+    ),
+    ("assertion.expected", "Expected {expected}, got {actual}"),
+    (
+        "assertion.expected_not",
+        "Expected not {expected}, got {actual}",
+    ),
+    ("status.is", "Status code is {actual}"),
+    ("status.is_not", "Status code is not {expected}"),
+    ("status.less_than", "Status code {actual} < {expected}"),
+    ("status.greater_than", "Status code {actual} > {expected}"),
+    (
+        "status.expected_less_than",
+        "Expected < {expected}, got {actual}",
+    ),
+    (
+        "status.expected_greater_than",
+        "Expected > {expected}, got {actual}",
+    ),
+    (
+        "response_time.less_than",
+        "Response time {actual}ms < {expected}ms",
+    ),
+    (
+        "response_time.greater_than",
+        "Response time {actual}ms > {expected}ms",
+    ),
+    (
+        "response_time.expected_less_than",
+        "Expected < {expected}ms, got {actual}ms",
+    ),
+    (
+        "response_time.expected_greater_than",
+        "Expected > {expected}ms, got {actual}ms",
+    ),
+    ("body.contains", "Body contains \"{expected}\""),
+    ("body.not_contains", "Body does not contain \"{expected}\""),
+    ("body.matches", "Body matches pattern \"{pattern}\""),
+    (
+        "body.not_matches",
+        "Body does not match pattern \"{pattern}\"",
+    ),
+    ("json.invalid_body", "Response body is not valid JSON"),
+    ("json.exists", "Property \"{property}\" exists"),
+    ("json.not_exists", "Property \"{property}\" does not exist"),
+    ("json.equals", "{property} equals {expected}"),
+    ("json.not_equals", "{property} does not equal {expected}"),
+    ("json.contains", "{property} contains \"{expected}\""),
+    (
+        "json.not_contains",
+        "{property} does not contain \"{expected}\"",
+    ),
+    ("json.no_duplicate_keys", "Body has no duplicate keys"),
+    ("json.duplicate_keys", "Duplicate keys: {keys}"),
+    ("json.schema_valid", "Body matches the schema"),
+    ("json.schema_violations", "Schema violations: {count}"),
+    ("json.invalid_schema", "Invalid schema: {error}"),
+    ("header.exists", "Header \"{header}\" exists"),
+    ("header.not_found", "Header \"{header}\" not found"),
+    ("header.not_exists", "Header \"{header}\" does not exist"),
+    ("header.equals", "Header \"{header}\" equals \"{expected}\""),
+    (
+        "header.not_equals",
+        "Header \"{header}\" does not equal \"{expected}\"",
+    ),
+    (
+        "header.contains",
+        "Header \"{header}\" contains \"{expected}\"",
+    ),
+    (
+        "header.not_contains",
+        "Header does not contain \"{expected}\"",
+    ),
+    (
+        "header.expected",
+        "Expected \"{expected}\", got \"{actual}\"",
+    ),
+    (
+        "header.expected_not",
+        "Expected not \"{expected}\", got \"{actual}\"",
+    ),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("assertion.skipped", "Omitida (desactivada)"),
+    (
+        "assertion.unknown_type",
+        "Tipo de aserción desconocido: {type}",
+    ),
+    (
+        "assertion.unknown_operator",
+        "Operador desconocido: {operator}",
+    ),
+    (
+        "assertion.invalid_regex",
+        "Expresión regular no válida: {pattern}",
+    ),
+    (
+        "assertion.invalid_query",
+        "Consulta no válida \"{query}\": {error}",
+    ),
+    (
+        "assertion.expected",
+        "Se esperaba {expected}, se obtuvo {actual}",
+    ),
+    (
+        "assertion.expected_not",
+        "No se esperaba {expected}, se obtuvo {actual}",
+    ),
+    ("status.is", "El código de estado es {actual}"),
+    ("status.is_not", "El código de estado no es {expected}"),
+    ("status.less_than", "Código de estado {actual} < {expected}"),
+    (
+        "status.greater_than",
+        "Código de estado {actual} > {expected}",
+    ),
+    (
+        "status.expected_less_than",
+        "Se esperaba < {expected}, se obtuvo {actual}",
+    ),
+    (
+        "status.expected_greater_than",
+        "Se esperaba > {expected}, se obtuvo {actual}",
+    ),
+    (
+        "response_time.less_than",
+        "Tiempo de respuesta {actual}ms < {expected}ms",
+    ),
+    (
+        "response_time.greater_than",
+        "Tiempo de respuesta {actual}ms > {expected}ms",
+    ),
+    (
+        "response_time.expected_less_than",
+        "Se esperaba < {expected}ms, se obtuvo {actual}ms",
+    ),
+    (
+        "response_time.expected_greater_than",
+        "Se esperaba > {expected}ms, se obtuvo {actual}ms",
+    ),
+    ("body.contains", "El cuerpo contiene \"{expected}\""),
+    ("body.not_contains", "El cuerpo no contiene \"{expected}\""),
+    (
+        "body.matches",
+        "El cuerpo coincide con el patrón \"{pattern}\"",
+    ),
+    (
+        "body.not_matches",
+        "El cuerpo no coincide con el patrón \"{pattern}\"",
+    ),
+    (
+        "json.invalid_body",
+        "El cuerpo de la respuesta no es JSON válido",
+    ),
+    ("json.exists", "La propiedad \"{property}\" existe"),
+    ("json.not_exists", "La propiedad \"{property}\" no existe"),
+    ("json.equals", "{property} es igual a {expected}"),
+    ("json.not_equals", "{property} no es igual a {expected}"),
+    ("json.contains", "{property} contiene \"{expected}\""),
+    ("json.not_contains", "{property} no contiene \"{expected}\""),
+    (
+        "json.no_duplicate_keys",
+        "El cuerpo no tiene claves duplicadas",
+    ),
+    ("json.duplicate_keys", "Claves duplicadas: {keys}"),
+    ("json.schema_valid", "El cuerpo cumple el esquema"),
+    (
+        "json.schema_violations",
+        "Infracciones del esquema: {count}",
+    ),
+    ("json.invalid_schema", "Esquema no válido: {error}"),
+    ("header.exists", "La cabecera \"{header}\" existe"),
+    (
+        "header.not_found",
+        "No se encontró la cabecera \"{header}\"",
+    ),
+    ("header.not_exists", "La cabecera \"{header}\" no existe"),
+    (
+        "header.equals",
+        "La cabecera \"{header}\" es igual a \"{expected}\"",
+    ),
+    (
+        "header.not_equals",
+        "La cabecera \"{header}\" no es igual a \"{expected}\"",
+    ),
+    (
+        "header.contains",
+        "La cabecera \"{header}\" contiene \"{expected}\"",
+    ),
+    (
+        "header.not_contains",
+        "La cabecera no contiene \"{expected}\"",
+    ),
+    (
+        "header.expected",
+        "Se esperaba \"{expected}\", se obtuvo \"{actual}\"",
+    ),
+    (
+        "header.expected_not",
+        "No se esperaba \"{expected}\", se obtuvo \"{actual}\"",
+    ),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("assertion.skipped", "Übersprungen (deaktiviert)"),
+    (
+        "assertion.unknown_type",
+        "Unbekannter Assertion-Typ: {type}",
+    ),
+    (
+        "assertion.unknown_operator",
+        "Unbekannter Operator: {operator}",
+    ),
+    (
+        "assertion.invalid_regex",
+        "Ungültiges Regex-Muster: {pattern}",
+    ),
+    (
+        "assertion.invalid_query",
+        "Ungültige Abfrage \"{query}\": {error}",
+    ),
+    (
+        "assertion.expected",
+        "Erwartet {expected}, erhalten {actual}",
+    ),
+    (
+        "assertion.expected_not",
+        "Erwartet nicht {expected}, erhalten {actual}",
+    ),
+    ("status.is", "Statuscode ist {actual}"),
+    ("status.is_not", "Statuscode ist nicht {expected}"),
+    ("status.less_than", "Statuscode {actual} < {expected}"),
+    ("status.greater_than", "Statuscode {actual} > {expected}"),
+    (
+        "status.expected_less_than",
+        "Erwartet < {expected}, erhalten {actual}",
+    ),
+    (
+        "status.expected_greater_than",
+        "Erwartet > {expected}, erhalten {actual}",
+    ),
+    (
+        "response_time.less_than",
+        "Antwortzeit {actual}ms < {expected}ms",
+    ),
+    (
+        "response_time.greater_than",
+        "Antwortzeit {actual}ms > {expected}ms",
+    ),
+    (
+        "response_time.expected_less_than",
+        "Erwartet < {expected}ms, erhalten {actual}ms",
+    ),
+    (
+        "response_time.expected_greater_than",
+        "Erwartet > {expected}ms, erhalten {actual}ms",
+    ),
+    ("body.contains", "Body enthält \"{expected}\""),
+    ("body.not_contains", "Body enthält nicht \"{expected}\""),
+    ("body.matches", "Body entspricht dem Muster \"{pattern}\""),
+    (
+        "body.not_matches",
+        "Body entspricht nicht dem Muster \"{pattern}\"",
+    ),
+    ("json.invalid_body", "Antwort-Body ist kein gültiges JSON"),
+    ("json.exists", "Eigenschaft \"{property}\" existiert"),
+    (
+        "json.not_exists",
+        "Eigenschaft \"{property}\" existiert nicht",
+    ),
+    ("json.equals", "{property} ist gleich {expected}"),
+    ("json.not_equals", "{property} ist ungleich {expected}"),
+    ("json.contains", "{property} enthält \"{expected}\""),
+    (
+        "json.not_contains",
+        "{property} enthält nicht \"{expected}\"",
+    ),
+    (
+        "json.no_duplicate_keys",
+        "Body enthält keine doppelten Schlüssel",
+    ),
+    ("json.duplicate_keys", "Doppelte Schlüssel: {keys}"),
+    ("json.schema_valid", "Body entspricht dem Schema"),
+    ("json.schema_violations", "Schemaverletzungen: {count}"),
+    ("json.invalid_schema", "Ungültiges Schema: {error}"),
+    ("header.exists", "Header \"{header}\" existiert"),
+    ("header.not_found", "Header \"{header}\" nicht gefunden"),
+    ("header.not_exists", "Header \"{header}\" existiert nicht"),
+    (
+        "header.equals",
+        "Header \"{header}\" ist gleich \"{expected}\"",
+    ),
+    (
+        "header.not_equals",
+        "Header \"{header}\" ist ungleich \"{expected}\"",
+    ),
+    (
+        "header.contains",
+        "Header \"{header}\" enthält \"{expected}\"",
+    ),
+    ("header.not_contains", "Header enthält nicht \"{expected}\""),
+    (
+        "header.expected",
+        "Erwartet \"{expected}\", erhalten \"{actual}\"",
+    ),
+    (
+        "header.expected_not",
+        "Erwartet nicht \"{expected}\", erhalten \"{actual}\"",
+    ),
+];
+
+/// Catalog for a locale tag, falling back from "es-MX" to "es" to English.
+fn catalog(locale: &str) -> &'static [(&'static str, &'static str)] {
+    let lang = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    match lang.as_str() {
+        "es" => ES,
+        "de" => DE,
+        _ => EN,
+    }
+}
+
+fn lookup(code: &str, locale: &str) -> Option<&'static str> {
+    let find = |cat: &'static [(&'static str, &'static str)]| {
+        cat.iter().find(|(c, _)| *c == code).map(|(_, t)| *t)
+    };
+    find(catalog(locale)).or_else(|| find(EN))
+}
+
+fn render(code: &str, params: &HashMap<&str, &str>, locale: &str) -> String {
+    let Some(template) = lookup(code, locale) else {
+        return code.to_string();
+    };
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(len) => {
+                let name = &rest[start + 1..start + len];
+                match params.get(name) {
+                    Some(v) => out.push_str(v),
+                    None => out.push_str(&rest[start..=start + len]),
+                }
+                rest = &rest[start + len + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Render a message code with its parameters in the given locale.
+/// params_json: JSON object of parameter name → value (as returned in assertion results).
+/// Unknown locales fall back to English; unknown codes are returned unchanged.
+#[wasm_bindgen]
+pub fn render_message(code: &str, params_json: &str, locale: &str) -> String {
+    let params: serde_json::Map<String, Value> =
+        serde_json::from_str(params_json).unwrap_or_default();
+    let params: HashMap<String, String> = params
+        .into_iter()
+        .map(|(k, v)| {
+            let s = match v {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            (k, s)
+        })
+        .collect();
+    let borrowed: HashMap<&str, &str> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    render(code, &borrowed, locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_message_locales() {
+        let params = r#"{"expected":"200","actual":404}"#;
+        assert_eq!(
+            render_message("assertion.expected", params, "en"),
+            "Expected 200, got 404"
+        );
+        assert_eq!(
+            render_message("assertion.expected", params, "es-MX"),
+            "Se esperaba 200, se obtuvo 404"
+        );
+        assert_eq!(
+            render_message("assertion.expected", params, "xx"),
+            "Expected 200, got 404"
+        );
+    }
+
+    #[test]
+    fn test_render_message_unknown_code_and_missing_param() {
+        assert_eq!(render_message("nope.code", "{}", "en"), "nope.code");
+        assert_eq!(
+            render_message("status.is", "{}", "en"),
+            "Status code is {actual}"
+        );
+    }
+
+    #[test]
+    fn test_catalogs_cover_every_code() {
+        for (code, _) in EN {
+            assert!(ES.iter().any(|(c, _)| c == code), "es missing {}", code);
+            assert!(DE.iter().any(|(c, _)| c == code), "de missing {}", code);
+        }
+    }
+}
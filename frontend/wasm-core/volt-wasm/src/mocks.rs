@@ -0,0 +1,607 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{cancelled_json, checkpoint};
+use crate::model::Headers;
+use crate::progress::Progress;
+use crate::{query_pairs, split_url};
+
+/// Response headers that describe one particular exchange rather than the
+/// endpoint, so they are never copied into a mock template.
+const VOLATILE_HEADERS: &[&str] = &[
+    "date",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "set-cookie",
+    "age",
+    "etag",
+    "last-modified",
+    "x-request-id",
+    "x-amzn-requestid",
+    "cf-ray",
+];
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    body: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RecordedResponse {
+    #[serde(rename = "statusCode")]
+    status_code: i32,
+    headers: Headers,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct RecordedEntry {
+    request: RecordedRequest,
+    #[serde(default)]
+    response: RecordedResponse,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct MockOptions {
+    /// Distinct literal values at the same path position before the position
+    /// is generalized into a parameter.
+    #[serde(rename = "paramThreshold")]
+    param_threshold: usize,
+    #[serde(rename = "includeHeaders")]
+    include_headers: bool,
+    /// Which request parts may discriminate between variants: "query", "body".
+    #[serde(rename = "varyBy")]
+    vary_by: Vec<String>,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        MockOptions {
+            param_threshold: 3,
+            include_headers: true,
+            vary_by: vec!["query".to_string(), "body".to_string()],
+        }
+    }
+}
+
+/// A recorded exchange reduced to the parts that matter for clustering.
+struct Sample {
+    method: String,
+    segments: Vec<String>,
+    query: Vec<(String, String)>,
+    body: Option<Value>,
+    response: RecordedResponse,
+    /// Status and response body structure; only samples that agree on it may
+    /// have a literal path position generalized into a parameter.
+    shape: String,
+}
+
+/// Convert recorded request/response pairs into mock definitions.
+/// entries_json: JSON array of {request: {method, url, body}, response: {statusCode, headers, body}}.
+/// options_json: optional {paramThreshold, includeHeaders, varyBy}.
+/// progress: optional callback invoked with {done, total, stage}.
+/// Returns JSON {mocks: [{id, method, pathTemplate, pathPattern, params, sampleCount, variants}], skipped}.
+#[wasm_bindgen]
+pub fn history_to_mocks(
+    entries_json: &str,
+    options_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
+    let entries: Vec<Value> = match serde_json::from_str(entries_json) {
+        Ok(e) => e,
+        Err(_) => return r#"{"mocks":[],"skipped":0}"#.to_string(),
+    };
+    let options: MockOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let _timer = crate::logging::timer("history_to_mocks");
+
+    let mut skipped = 0;
+    let samples: Vec<Sample> = entries
+        .into_iter()
+        .filter_map(|e| {
+            let sample = serde_json::from_value::<RecordedEntry>(e)
+                .ok()
+                .and_then(to_sample);
+            if sample.is_none() {
+                skipped += 1;
+            }
+            sample
+        })
+        .collect();
+
+    let templates = build_templates(&samples, options.param_threshold.max(2));
+
+    // Group samples by (method, template), keeping first-appearance order.
+    let mut order: Vec<(String, Vec<String>)> = Vec::new();
+    let mut groups: HashMap<(String, Vec<String>), Vec<&Sample>> = HashMap::new();
+    for (sample, template) in samples.iter().zip(templates) {
+        let key = (sample.method.clone(), template);
+        if !groups.contains_key(&key) {
+            order.push(key.clone());
+        }
+        groups.entry(key).or_default().push(sample);
+    }
+
+    let mut progress = Progress::new(progress, "mocks", order.len());
+    let mut mocks: Vec<Value> = Vec::with_capacity(order.len());
+    for key in order {
+        if checkpoint().is_err() {
+            return cancelled_json();
+        }
+        mocks.push(build_mock(&key.0, &key.1, &groups[&key], &options));
+        progress.tick(mocks.len());
+    }
+
+    serde_json::to_string(&serde_json::json!({ "mocks": mocks, "skipped": skipped }))
+        .unwrap_or_else(|_| r#"{"mocks":[],"skipped":0}"#.to_string())
+}
+
+fn to_sample(entry: RecordedEntry) -> Option<Sample> {
+    let method = entry.request.method.trim().to_uppercase();
+    if method.is_empty() || entry.request.url.trim().is_empty() {
+        return None;
+    }
+    let (_, path, query) = split_url(&entry.request.url);
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+    let shape = match serde_json::from_str::<Value>(&entry.response.body) {
+        Ok(body) => format!("{} {}", entry.response.status_code, body_shape(&body, 0)),
+        Err(_) => format!("{} text", entry.response.status_code),
+    };
+    Some(Sample {
+        method,
+        segments,
+        query: query_pairs(query),
+        body: serde_json::from_str(&entry.request.body).ok(),
+        response: entry.response,
+        shape,
+    })
+}
+
+/// Structure of a JSON body: object keys and value types, and the shape of
+/// the first array element, down to a few levels.
+fn body_shape(value: &Value, depth: usize) -> String {
+    match value {
+        Value::Object(_) | Value::Array(_) if depth >= 3 => "_".to_string(),
+        Value::Object(map) => {
+            let mut fields: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}:{}", k, body_shape(v, depth + 1)))
+                .collect();
+            fields.sort();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => match items.first() {
+            Some(first) => format!("[{}]", body_shape(first, depth + 1)),
+            None => "[]".to_string(),
+        },
+        Value::String(_) => "string".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Null => "null".to_string(),
+    }
+}
+
+/// Returns true for path segments that are almost certainly identifiers:
+/// numbers, UUIDs, long hex strings and long mixed letter/digit tokens.
+pub(crate) fn looks_like_id(segment: &str) -> bool {
+    if segment.chars().all(|c| c.is_ascii_digit()) {
+        return true;
+    }
+    let hex_or_dash = segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    if hex_or_dash && segment.len() >= 8 && segment.chars().any(|c| c.is_ascii_digit()) {
+        return true;
+    }
+    segment.len() >= 16
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && segment.chars().any(|c| c.is_ascii_digit())
+        && segment.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Placeholder marker used while clustering; replaced by named params later.
+const PARAM: &str = "\u{0}";
+
+/// Computes a path template for each sample. Id-like segments become
+/// parameters immediately; literal positions become parameters once enough
+/// distinct values are seen under an otherwise identical template, among
+/// samples with the same status and response shape.
+fn build_templates(samples: &[Sample], threshold: usize) -> Vec<Vec<String>> {
+    let mut templates: Vec<Vec<String>> = samples
+        .iter()
+        .map(|s| {
+            s.segments
+                .iter()
+                .map(|seg| {
+                    if looks_like_id(seg) {
+                        PARAM.to_string()
+                    } else {
+                        seg.clone()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    loop {
+        let mut changed = false;
+        let max_len = templates.iter().map(|t| t.len()).max().unwrap_or(0);
+        for pos in 0..max_len {
+            // Key = method + response shape + template with this position blanked out.
+            let mut distinct: HashMap<(&str, &str, Vec<String>), Vec<String>> = HashMap::new();
+            for (sample, template) in samples.iter().zip(&templates) {
+                if pos >= template.len() || template[pos] == PARAM {
+                    continue;
+                }
+                let mut masked = template.clone();
+                masked[pos] = PARAM.to_string();
+                let values = distinct
+                    .entry((&sample.method, &sample.shape, masked))
+                    .or_default();
+                if !values.contains(&template[pos]) {
+                    values.push(template[pos].clone());
+                }
+            }
+            for (sample, template) in samples.iter().zip(templates.iter_mut()) {
+                if pos >= template.len() || template[pos] == PARAM {
+                    continue;
+                }
+                let mut masked = template.clone();
+                masked[pos] = PARAM.to_string();
+                if distinct
+                    .get(&(
+                        sample.method.as_str(),
+                        sample.shape.as_str(),
+                        masked.clone(),
+                    ))
+                    .is_some_and(|v| v.len() >= threshold)
+                {
+                    *template = masked;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return templates;
+        }
+    }
+}
+
+/// Derive a readable parameter name from the preceding literal segment,
+/// e.g. "users/{id}" becomes "userId".
+fn param_names(template: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (i, seg) in template.iter().enumerate() {
+        if seg != PARAM {
+            continue;
+        }
+        let base = template[..i]
+            .iter()
+            .rev()
+            .find(|s| *s != PARAM)
+            .map(|prev| {
+                let word: String = prev
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .collect::<String>()
+                    .to_lowercase();
+                let word = word.strip_suffix('s').unwrap_or(&word).to_string();
+                format!("{}Id", word)
+            })
+            .unwrap_or_else(|| "id".to_string());
+        let mut name = base.clone();
+        let mut n = 2;
+        while names.contains(&name) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+fn build_mock(
+    method: &str,
+    template: &[String],
+    group: &[&Sample],
+    options: &MockOptions,
+) -> Value {
+    let params = param_names(template);
+    let mut names = params.iter();
+    let mut path_template = String::new();
+    let mut path_pattern = String::from("^");
+    for seg in template {
+        path_template.push('/');
+        path_pattern.push('/');
+        if seg == PARAM {
+            path_template.push_str(&format!("{{{}}}", names.next().unwrap()));
+            path_pattern.push_str("([^/]+)");
+        } else {
+            path_template.push_str(seg);
+            path_pattern.push_str(&regex_escape(seg));
+        }
+    }
+    if template.is_empty() {
+        path_template.push('/');
+        path_pattern.push('/');
+    }
+    path_pattern.push('$');
+
+    let vary_query = options.vary_by.iter().any(|v| v == "query");
+    let vary_body = options.vary_by.iter().any(|v| v == "body");
+
+    // Request parts whose values differ between samples discriminate variants.
+    let mut query_keys: Vec<String> = Vec::new();
+    if vary_query {
+        for sample in group {
+            for (k, _) in &sample.query {
+                if !query_keys.contains(k) && distinct_count(group, |s| query_value(s, k)) > 1 {
+                    query_keys.push(k.clone());
+                }
+            }
+        }
+    }
+    let mut body_keys: Vec<String> = Vec::new();
+    if vary_body {
+        for sample in group {
+            if let Some(Value::Object(map)) = &sample.body {
+                for (k, v) in map {
+                    if !body_keys.contains(k)
+                        && !v.is_object()
+                        && !v.is_array()
+                        && distinct_count(group, |s| body_value(s, k)) > 1
+                    {
+                        body_keys.push(k.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // One variant per distinct match key; later samples win so the mock
+    // reflects the most recent recorded behaviour.
+    let mut variant_order: Vec<String> = Vec::new();
+    let mut variants: HashMap<String, (Value, &Sample, usize)> = HashMap::new();
+    for sample in group {
+        let mut query_match = serde_json::Map::new();
+        for k in &query_keys {
+            if let Some(v) = query_value(sample, k) {
+                query_match.insert(k.clone(), Value::String(v));
+            }
+        }
+        let mut body_match = serde_json::Map::new();
+        for k in &body_keys {
+            if let Some(Value::Object(map)) = &sample.body
+                && let Some(v) = map.get(k)
+            {
+                body_match.insert(k.clone(), v.clone());
+            }
+        }
+        let matcher = serde_json::json!({ "query": query_match, "body": body_match });
+        let key = matcher.to_string();
+        match variants.get_mut(&key) {
+            Some(existing) => {
+                existing.1 = sample;
+                existing.2 += 1;
+            }
+            None => {
+                variant_order.push(key.clone());
+                variants.insert(key, (matcher, sample, 1));
+            }
+        }
+    }
+
+    let default_key = variant_order
+        .iter()
+        .max_by_key(|k| {
+            (
+                variants[*k].2,
+                std::cmp::Reverse(variant_order.iter().position(|o| o == *k)),
+            )
+        })
+        .cloned();
+
+    let variant_values: Vec<Value> = variant_order
+        .iter()
+        .map(|key| {
+            let (matcher, sample, count) = &variants[key];
+            let headers: Headers = sample
+                .response
+                .headers
+                .iter()
+                .filter(|(k, _)| {
+                    options.include_headers
+                        && !VOLATILE_HEADERS.contains(&k.to_lowercase().as_str())
+                })
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            serde_json::json!({
+                "match": matcher,
+                "isDefault": default_key.as_deref() == Some(key.as_str()),
+                "sampleCount": count,
+                "response": {
+                    "statusCode": sample.response.status_code,
+                    "headers": headers,
+                    "body": sample.response.body,
+                }
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "id": format!("{} {}", method, path_template),
+        "method": method,
+        "pathTemplate": path_template,
+        "pathPattern": path_pattern,
+        "params": params,
+        "sampleCount": group.len(),
+        "variants": variant_values,
+    })
+}
+
+fn query_value(sample: &Sample, key: &str) -> Option<String> {
+    sample
+        .query
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.clone())
+}
+
+fn body_value(sample: &Sample, key: &str) -> Option<String> {
+    match &sample.body {
+        Some(Value::Object(map)) => map.get(key).map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+fn distinct_count(group: &[&Sample], f: impl Fn(&Sample) -> Option<String>) -> usize {
+    let mut seen: Vec<Option<String>> = Vec::new();
+    for sample in group {
+        let v = f(sample);
+        if !seen.contains(&v) {
+            seen.push(v);
+        }
+    }
+    seen.len()
+}
+
+fn regex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(method: &str, url: &str, body: &str, status: i32, resp: &str) -> Value {
+        serde_json::json!({
+            "request": { "method": method, "url": url, "body": body },
+            "response": {
+                "statusCode": status,
+                "headers": { "Content-Type": "application/json", "Date": "Mon" },
+                "body": resp
+            }
+        })
+    }
+
+    fn run(entries: Vec<Value>, options: &str) -> Value {
+        let json = serde_json::to_string(&entries).unwrap();
+        serde_json::from_str(&history_to_mocks(&json, options, None)).unwrap()
+    }
+
+    #[test]
+    fn test_history_to_mocks_extracts_id_params() {
+        let result = run(
+            vec![
+                entry(
+                    "GET",
+                    "https://api.example.com/users/1",
+                    "",
+                    200,
+                    r#"{"id":1}"#,
+                ),
+                entry(
+                    "GET",
+                    "https://api.example.com/users/2",
+                    "",
+                    200,
+                    r#"{"id":2}"#,
+                ),
+                entry("GET", "https://api.example.com/users", "", 200, "[]"),
+            ],
+            "{}",
+        );
+        let mocks = result["mocks"].as_array().unwrap();
+        assert_eq!(mocks.len(), 2);
+        assert_eq!(mocks[0]["pathTemplate"], "/users/{userId}");
+        assert_eq!(mocks[0]["pathPattern"], "^/users/([^/]+)$");
+        assert_eq!(mocks[0]["sampleCount"], 2);
+        assert_eq!(mocks[1]["pathTemplate"], "/users");
+        let headers = &mocks[0]["variants"][0]["response"]["headers"];
+        assert_eq!(headers["Content-Type"], "application/json");
+        assert!(headers.get("Date").is_none());
+    }
+
+    #[test]
+    fn test_history_to_mocks_generalizes_repeated_literals() {
+        let result = run(
+            vec![
+                entry("GET", "/repos/alice/settings", "", 200, "a"),
+                entry("GET", "/repos/bob/settings", "", 200, "b"),
+                entry("GET", "/repos/carol/settings", "", 200, "c"),
+            ],
+            "{}",
+        );
+        let mocks = result["mocks"].as_array().unwrap();
+        assert_eq!(mocks.len(), 1);
+        assert_eq!(mocks[0]["pathTemplate"], "/repos/{repoId}/settings");
+    }
+
+    #[test]
+    fn test_history_to_mocks_keeps_distinct_resources_apart() {
+        let result = run(
+            vec![
+                entry("GET", "/api/users", "", 200, r#"[{"id":1,"name":"Ann"}]"#),
+                entry("GET", "/api/orders", "", 200, r#"[{"id":7,"total":9.5}]"#),
+                entry("GET", "/api/products", "", 200, r#"[{"sku":"a1"}]"#),
+            ],
+            "{}",
+        );
+        let templates: Vec<&Value> = result["mocks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| &m["pathTemplate"])
+            .collect();
+        assert_eq!(templates, ["/api/users", "/api/orders", "/api/products"]);
+    }
+
+    #[test]
+    fn test_history_to_mocks_variants_by_query_and_body() {
+        let result = run(
+            vec![
+                entry("GET", "/items?page=1", "", 200, "p1"),
+                entry("GET", "/items?page=2", "", 200, "p2"),
+                entry("GET", "/items?page=1", "", 200, "p1-new"),
+                entry("POST", "/login", r#"{"user":"a"}"#, 200, "ok"),
+                entry("POST", "/login", r#"{"user":"b"}"#, 401, "denied"),
+            ],
+            "{}",
+        );
+        let mocks = result["mocks"].as_array().unwrap();
+        let items = &mocks[0]["variants"];
+        assert_eq!(items.as_array().unwrap().len(), 2);
+        assert_eq!(items[0]["match"]["query"]["page"], "1");
+        assert_eq!(items[0]["response"]["body"], "p1-new");
+        assert_eq!(items[0]["isDefault"], true);
+        let login = &mocks[1]["variants"];
+        assert_eq!(login[1]["match"]["body"]["user"], "b");
+        assert_eq!(login[1]["response"]["statusCode"], 401);
+    }
+
+    #[test]
+    fn test_history_to_mocks_skips_invalid_entries() {
+        let result = run(vec![serde_json::json!({ "foo": 1 })], "");
+        assert_eq!(result["mocks"].as_array().unwrap().len(), 0);
+        assert_eq!(result["skipped"], 1);
+    }
+}
@@ -0,0 +1,533 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::Assertion;
+
+/// A key/value row as edited in the UI (headers, query params, form fields).
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub(crate) struct KeyValue {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl KeyValue {
+    pub(crate) fn new(key: &str, value: &str) -> Self {
+        KeyValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Accepts both the saved-request shape ({"Name": "value"}) and the editor
+/// shape ([{key, value, enabled}]) for header/param lists.
+pub(crate) fn deserialize_pairs<'de, D>(deserializer: D) -> Result<Vec<KeyValue>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    map_or_list(deserializer, KeyValue::new)
+}
+
+fn map_or_list<'de, D, T>(
+    deserializer: D,
+    from_pair: fn(&str, &str) -> T,
+) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Object(map) => Ok(map
+            .into_iter()
+            .map(|(k, v)| match v {
+                Value::String(s) => from_pair(&k, &s),
+                other => from_pair(&k, &other.to_string()),
+            })
+            .collect()),
+        Value::Array(items) => Ok(items
+            .into_iter()
+            .filter_map(|item| serde_json::from_value(item).ok())
+            .collect()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Response headers in the order they arrived. A name may repeat (Set-Cookie,
+/// Vary, Link), so this is a list rather than a map; lookups ignore case.
+/// Deserializes from {"Name": "value"}, {"Name": ["v1", "v2"]}, HAR-style
+/// [{name, value}], editor-style [{key, value}] or [[name, value]]; serializes
+/// back to an object with repeated names as arrays.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Headers(Vec<(String, String)>);
+
+impl Headers {
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Every value sent under `name`, in order.
+    pub(crate) fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+    }
+
+    /// The first value sent under `name`.
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// All values of `name` as one field, joined with ", " (RFC 9110 §5.3).
+    pub(crate) fn combined(&self, name: &str) -> Option<String> {
+        let values: Vec<&str> = self.get_all(name).collect();
+        (!values.is_empty()).then(|| values.join(", "))
+    }
+}
+
+impl FromIterator<(String, String)> for Headers {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Headers(iter.into_iter().collect())
+    }
+}
+
+impl<'de> Deserialize<'de> for Headers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = |v: Value| match v {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        let mut pairs = Vec::new();
+        match Value::deserialize(deserializer)? {
+            Value::Object(map) => {
+                for (name, value) in map {
+                    match value {
+                        Value::Array(values) => {
+                            pairs.extend(values.into_iter().map(|v| (name.clone(), text(v))))
+                        }
+                        value => pairs.push((name, text(value))),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    match item {
+                        Value::Array(pair) => {
+                            let mut pair = pair.into_iter().map(text);
+                            if let (Some(name), value) = (pair.next(), pair.next()) {
+                                pairs.push((name, value.unwrap_or_default()));
+                            }
+                        }
+                        Value::Object(mut row) => {
+                            if row.get("enabled") == Some(&Value::Bool(false)) {
+                                continue;
+                            }
+                            let name = row.remove("name").or_else(|| row.remove("key"));
+                            if let Some(name) = name {
+                                let value = row.remove("value").map(text).unwrap_or_default();
+                                pairs.push((text(name), value));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(Headers(pairs))
+    }
+}
+
+impl Serialize for Headers {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serde_json::Map::new();
+        for (name, value) in &self.0 {
+            let key = map
+                .keys()
+                .find(|k| k.eq_ignore_ascii_case(name))
+                .cloned()
+                .unwrap_or_else(|| name.clone());
+            match map.get_mut(&key) {
+                Some(Value::Array(values)) => values.push(Value::String(value.clone())),
+                Some(first) => *first = Value::Array(vec![first.take(), value.clone().into()]),
+                None => {
+                    map.insert(key, Value::String(value.clone()));
+                }
+            }
+        }
+        map.serialize(serializer)
+    }
+}
+
+/// An environment variable. `secret` values are masked in reports.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub(crate) struct Variable {
+    pub key: String,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub value: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub secret: bool,
+    #[serde(
+        default,
+        rename = "type",
+        skip_serializing_if = "VariableType::is_string"
+    )]
+    pub kind: VariableType,
+}
+
+/// What a variable's value stands for where a JSON value is expected: strings
+/// are quoted, the others are inserted as written.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum VariableType {
+    Number,
+    Boolean,
+    Json,
+    #[default]
+    #[serde(other)]
+    String,
+}
+
+impl VariableType {
+    fn is_string(&self) -> bool {
+        *self == VariableType::String
+    }
+
+    /// The type a JSON value would be saved as.
+    pub(crate) fn of(value: &Value) -> Self {
+        match value {
+            Value::String(_) => VariableType::String,
+            Value::Number(_) => VariableType::Number,
+            Value::Bool(_) => VariableType::Boolean,
+            _ => VariableType::Json,
+        }
+    }
+
+    /// `text` as a JSON value of this type; text that does not fit the type
+    /// stays a string.
+    pub(crate) fn value(&self, text: &str) -> Value {
+        let parsed = match self {
+            VariableType::String => None,
+            VariableType::Number => serde_json::from_str(text.trim())
+                .ok()
+                .filter(Value::is_number),
+            VariableType::Boolean => match text.trim().to_ascii_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            VariableType::Json => serde_json::from_str(text).ok(),
+        };
+        parsed.unwrap_or_else(|| Value::String(text.to_string()))
+    }
+}
+
+impl Variable {
+    pub(crate) fn new(key: &str, value: &str) -> Self {
+        Variable {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            secret: false,
+            kind: VariableType::String,
+        }
+    }
+}
+
+/// Variable values are text; numbers and booleans are kept as written and
+/// objects or arrays as JSON, so typed values saved by the UI still load.
+fn deserialize_text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+fn deserialize_variables<'de, D>(deserializer: D) -> Result<Vec<Variable>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // In the map shape the JSON type of each value is its variable type.
+    match Value::deserialize(deserializer)? {
+        Value::Object(map) => Ok(map
+            .into_iter()
+            .map(|(k, v)| {
+                let kind = VariableType::of(&v);
+                let value = match v {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                Variable {
+                    kind,
+                    ..Variable::new(&k, &value)
+                }
+            })
+            .collect()),
+        other => map_or_list(other, Variable::new).map_err(serde::de::Error::custom),
+    }
+}
+
+/// An environment export ({name, variables}); variables may also be a plain map.
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct Environment {
+    pub id: String,
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_variables")]
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Auth {
+    #[serde(rename = "type")]
+    pub auth_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_location: Option<String>,
+}
+
+/// A saved response attached to a request for documentation purposes.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Example {
+    pub name: String,
+    pub request_body: String,
+    pub status_code: i32,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    pub headers: Vec<KeyValue>,
+    pub body: String,
+}
+
+/// A request as stored in a collection. Field names follow the frontend's
+/// request tab, with `requestBody` accepted as an alias for `body`.
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct Request {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub method: String,
+    pub url: String,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    pub headers: Vec<KeyValue>,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    pub query_params: Vec<KeyValue>,
+    #[serde(alias = "requestBody")]
+    pub body: String,
+    pub body_type: String,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    pub form_data: Vec<KeyValue>,
+    pub auth: Option<Auth>,
+    pub assertions: Vec<Assertion>,
+    pub examples: Vec<Example>,
+}
+
+impl Request {
+    /// Every enabled text that may contain {{variables}}, labelled with its field
+    /// ("url", "headers", "queryParams", "auth", "body", "formData").
+    pub(crate) fn templated_fields(&self) -> Vec<(&'static str, String)> {
+        let mut out = vec![("url", self.url.clone())];
+        for h in self.headers.iter().filter(|h| h.enabled) {
+            out.push(("headers", format!("{} {}", h.key, h.value)));
+        }
+        for p in self.query_params.iter().filter(|p| p.enabled) {
+            out.push(("queryParams", format!("{} {}", p.key, p.value)));
+        }
+        if let Some(auth) = &self.auth {
+            for value in [
+                &auth.username,
+                &auth.password,
+                &auth.token,
+                &auth.api_key_value,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                out.push(("auth", value.clone()));
+            }
+        }
+        out.push(("body", self.body.clone()));
+        for f in self.form_data.iter().filter(|f| f.enabled) {
+            out.push(("formData", f.value.clone()));
+        }
+        out
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct Folder {
+    pub id: String,
+    pub name: String,
+    pub requests: Vec<Request>,
+    pub folders: Vec<Folder>,
+}
+
+/// A collection export: top-level requests plus optional nested folders.
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct Collection {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub requests: Vec<Request>,
+    pub folders: Vec<Folder>,
+}
+
+impl Collection {
+    /// All requests depth-first, each with the names of its enclosing folders.
+    pub(crate) fn all_requests(&self) -> Vec<(Vec<&str>, &Request)> {
+        fn walk<'a>(
+            folder: &'a Folder,
+            path: &mut Vec<&'a str>,
+            out: &mut Vec<(Vec<&'a str>, &'a Request)>,
+        ) {
+            path.push(&folder.name);
+            for r in &folder.requests {
+                out.push((path.clone(), r));
+            }
+            for f in &folder.folders {
+                walk(f, path, out);
+            }
+            path.pop();
+        }
+
+        let mut out: Vec<(Vec<&str>, &Request)> =
+            self.requests.iter().map(|r| (Vec::new(), r)).collect();
+        let mut path = Vec::new();
+        for f in &self.folders {
+            walk(f, &mut path, &mut out);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_accepts_header_map_and_array() {
+        let saved: Request =
+            serde_json::from_str(r#"{"method":"GET","headers":{"Accept":"*/*"}}"#).unwrap();
+        assert_eq!(saved.headers, vec![KeyValue::new("Accept", "*/*")]);
+
+        let tab: Request = serde_json::from_str(
+            r#"{"headers":[{"key":"Accept","value":"*/*","enabled":false}],"requestBody":"x"}"#,
+        )
+        .unwrap();
+        assert!(!tab.headers[0].enabled);
+        assert_eq!(tab.body, "x");
+    }
+
+    #[test]
+    fn test_headers_keep_repeated_values() {
+        let map: Headers =
+            serde_json::from_str(r#"{"Set-Cookie":["a=1","b=2"],"Content-Length":12}"#).unwrap();
+        assert_eq!(
+            map.get_all("set-cookie").collect::<Vec<_>>(),
+            ["a=1", "b=2"]
+        );
+        assert_eq!(map.get("content-length"), Some("12"));
+        assert_eq!(map.combined("Set-Cookie").as_deref(), Some("a=1, b=2"));
+        assert!(!map.contains("vary"));
+
+        let list: Headers = serde_json::from_str(
+            r#"[{"name":"Vary","value":"Accept"},{"key":"vary","value":"Origin"},
+                {"key":"X-Off","value":"1","enabled":false},["Link","<a>"]]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&list).unwrap(),
+            serde_json::json!({"Vary": ["Accept", "Origin"], "Link": "<a>"})
+        );
+    }
+
+    #[test]
+    fn test_environment_accepts_map_and_list() {
+        let map: Environment =
+            serde_json::from_str(r#"{"name":"dev","variables":{"host":"localhost"}}"#).unwrap();
+        assert_eq!(map.variables, vec![Variable::new("host", "localhost")]);
+
+        let list: Environment = serde_json::from_str(
+            r#"{"variables":[{"key":"token","value":"x","secret":true},{"key":"off","enabled":false}]}"#,
+        )
+        .unwrap();
+        assert!(list.variables[0].secret);
+        assert!(!list.variables[1].enabled);
+    }
+
+    #[test]
+    fn test_variable_types() {
+        let env: Environment = serde_json::from_str(
+            r#"{"variables":[{"key":"n","value":"42","type":"number"},{"key":"b","value":true,"type":"boolean"},
+                {"key":"bad","value":"x","type":"number"},{"key":"s","value":"1","type":"other"}]}"#,
+        )
+        .unwrap();
+        let typed: Vec<Value> = env
+            .variables
+            .iter()
+            .map(|v| v.kind.value(&v.value))
+            .collect();
+        assert_eq!(
+            typed,
+            serde_json::json!([42, true, "x", "1"]).as_array().unwrap()[..]
+        );
+
+        let map: Environment =
+            serde_json::from_str(r#"{"variables":{"n":1.5,"o":{"a":1},"s":"t"}}"#).unwrap();
+        assert_eq!(map.variables[0].kind, VariableType::Number);
+        assert_eq!(map.variables[1].value, r#"{"a":1}"#);
+        assert_eq!(
+            map.variables[1].kind.value(&map.variables[1].value),
+            serde_json::json!({"a": 1})
+        );
+        assert_eq!(
+            serde_json::to_value(&map.variables[2]).unwrap()["type"],
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_collection_all_requests_walks_folders() {
+        let c: Collection = serde_json::from_str(
+            r#"{"name":"c","requests":[{"name":"a"}],
+                "folders":[{"name":"f","requests":[{"name":"b"}],"folders":[{"name":"g","requests":[{"name":"c"}]}]}]}"#,
+        )
+        .unwrap();
+        let all = c.all_requests();
+        let names: Vec<&str> = all.iter().map(|(_, r)| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(all[2].0, vec!["f", "g"]);
+    }
+}
@@ -0,0 +1,458 @@
+use indexmap::IndexMap;
+use wasm_bindgen::prelude::*;
+
+use crate::import::unique_name;
+use crate::mocks::looks_like_id;
+use crate::model::{Folder, Request};
+use crate::split_url;
+
+/// Path segments that only say "this is an API", skipped before the resource.
+const PREFIXES: &[&str] = &["api", "rest", "services"];
+
+/// Verb-like last segments ("/users/{id}/activate") named after the action.
+const ACTIONS: &[&str] = &[
+    "activate",
+    "approve",
+    "archive",
+    "authorize",
+    "batch",
+    "cancel",
+    "confirm",
+    "count",
+    "deactivate",
+    "disable",
+    "enable",
+    "export",
+    "import",
+    "invite",
+    "login",
+    "logout",
+    "publish",
+    "refresh",
+    "register",
+    "reject",
+    "reset",
+    "restore",
+    "revoke",
+    "search",
+    "send",
+    "signin",
+    "signout",
+    "signup",
+    "subscribe",
+    "sync",
+    "unpublish",
+    "unsubscribe",
+    "upload",
+    "validate",
+    "verify",
+];
+
+/// Actions that apply to a whole collection: "/users/search" → "Search users".
+const COLLECTION_ACTIONS: &[&str] = &["batch", "count", "export", "import", "search", "sync"];
+
+/// Groups smaller than this stay as loose requests instead of getting a folder.
+const MIN_FOLDER_SIZE: usize = 2;
+
+const MAX_FOLDER_DEPTH: usize = 3;
+
+enum Segment {
+    Literal(String),
+    /// A path parameter, with its name ("id" for bare identifiers).
+    Param(String),
+}
+
+/// Suggest a readable name for an unnamed request from its method and URL path,
+/// e.g. "Get user by id", "List posts for user", "Create order", "Activate user".
+/// Version and "api" prefixes are skipped; `{id}`, `:id`, `{{id}}` and id-like
+/// segments (numbers, UUIDs, hashes) are treated as parameters.
+#[wasm_bindgen]
+pub fn suggest_request_name(method: &str, url: &str) -> String {
+    let segments = segments(url);
+    let method = method.trim().to_uppercase();
+    let Some(last) = segments
+        .iter()
+        .rposition(|s| matches!(s, Segment::Literal(_)))
+    else {
+        let params = param_names(&segments);
+        let target = if params.is_empty() { "root" } else { "item" };
+        return sentence(&with_params(
+            &format!("{} {}", verb(&method), target),
+            &params,
+        ));
+    };
+    let Segment::Literal(resource) = &segments[last] else {
+        unreachable!()
+    };
+    let trailing = param_names(&segments[last + 1..]);
+    // The resource this one is nested under: "users" in /users/{id}/posts.
+    let parent = segments[..last]
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, s)| match s {
+            Segment::Literal(name) if matches!(segments.get(i + 1), Some(Segment::Param(_))) => {
+                Some(singular(&humanize(name)))
+            }
+            _ => None,
+        });
+    let direct_parent = match last.checked_sub(1).map(|i| &segments[i]) {
+        Some(Segment::Literal(name)) => Some(humanize(name)),
+        _ => None,
+    };
+
+    let action = resource.to_lowercase().replace(['-', '_'], "");
+    if ACTIONS.contains(&action.as_str()) && trailing.is_empty() {
+        let target = if COLLECTION_ACTIONS.contains(&action.as_str()) {
+            direct_parent.or(parent)
+        } else {
+            parent
+        };
+        return sentence(&match target {
+            Some(target) => format!("{} {}", humanize(resource), target),
+            None => humanize(resource),
+        });
+    }
+
+    let plural = humanize(resource);
+    let one = singular(&plural);
+    let name = match (method.as_str(), trailing.is_empty()) {
+        ("GET", false) => with_params(&format!("Get {}", one), &trailing),
+        ("GET", true) if one == plural => format!("Get {}", plural),
+        ("GET", true) => with_context(format!("List {}", plural), &parent),
+        ("POST", true) => with_context(format!("Create {}", one), &parent),
+        ("PUT" | "PATCH" | "POST", _) => format!("Update {}", one),
+        ("DELETE", false) => format!("Delete {}", one),
+        ("DELETE", true) => with_context(format!("Delete {}", plural), &parent),
+        _ => format!(
+            "{} {}",
+            verb(&method),
+            if trailing.is_empty() { &plural } else { &one }
+        ),
+    };
+    sentence(&name)
+}
+
+/// Group requests into a folder tree that follows their URL paths: one folder
+/// per top-level resource ("Users", "Orders"), with sub-folders for nested
+/// resources that have several requests. Requests to several hosts are grouped
+/// by host first. Unnamed requests get a name from `suggest_request_name`.
+/// requests_json: JSON array of requests.
+/// Returns JSON {requests: [...], folders: [{id, name, requests, folders}]} where
+/// requests are the ones left at the top level; or {error}.
+#[wasm_bindgen]
+pub fn suggest_folder_structure(requests_json: &str) -> String {
+    let requests: Vec<Request> = match serde_json::from_str(requests_json) {
+        Ok(requests) => requests,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid requests: {}", e) }).to_string();
+        }
+    };
+
+    let mut by_host: IndexMap<String, Vec<(Vec<String>, Request)>> = IndexMap::new();
+    for mut request in requests {
+        if request.name.trim().is_empty() {
+            request.name = suggest_request_name(&request.method, &request.url);
+        }
+        let (origin, _, _) = split_url(&request.url);
+        let host = origin.split("://").nth(1).unwrap_or(origin).to_string();
+        let path = segments(&request.url)
+            .into_iter()
+            .filter_map(|s| match s {
+                Segment::Literal(name) => Some(name),
+                Segment::Param(_) => None,
+            })
+            .collect();
+        by_host.entry(host).or_default().push((path, request));
+    }
+
+    let (requests, folders) = if by_host.len() > 1 {
+        let folders = by_host
+            .into_iter()
+            .map(|(host, items)| {
+                let (requests, folders) = group(items, 0);
+                Folder {
+                    name: if host.is_empty() {
+                        "Other".to_string()
+                    } else {
+                        host
+                    },
+                    requests: dedupe_names(requests),
+                    folders,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        (Vec::new(), folders)
+    } else {
+        group(by_host.into_values().next().unwrap_or_default(), 0)
+    };
+    serde_json::json!({ "requests": dedupe_names(requests), "folders": folders }).to_string()
+}
+
+/// Split items on their first path segment; groups of at least MIN_FOLDER_SIZE
+/// become folders (nested up to MAX_FOLDER_DEPTH), the rest stay at this level.
+/// Below the top level a group holding every item is not split off again.
+fn group(items: Vec<(Vec<String>, Request)>, depth: usize) -> (Vec<Request>, Vec<Folder>) {
+    let total = items.len();
+    let mut loose = Vec::new();
+    let mut groups: IndexMap<String, Vec<(Vec<String>, Request)>> = IndexMap::new();
+    for (path, request) in items {
+        match path.split_first() {
+            Some((first, rest)) => groups
+                .entry(first.to_lowercase())
+                .or_default()
+                .push((rest.to_vec(), request)),
+            None => loose.push(request),
+        }
+    }
+
+    let mut folders = Vec::new();
+    for (key, members) in groups {
+        let nest = depth < MAX_FOLDER_DEPTH && (depth == 0 || members.len() < total);
+        if members.len() < MIN_FOLDER_SIZE || !nest {
+            loose.extend(members.into_iter().map(|(_, r)| r));
+            continue;
+        }
+        let (requests, subfolders) = group(members, depth + 1);
+        folders.push(Folder {
+            name: sentence(&humanize(&key)),
+            requests: dedupe_names(requests),
+            folders: subfolders,
+            ..Default::default()
+        });
+    }
+    (loose, folders)
+}
+
+fn dedupe_names(requests: Vec<Request>) -> Vec<Request> {
+    let mut used = Vec::new();
+    requests
+        .into_iter()
+        .map(|mut r| {
+            r.name = unique_name(r.name, &mut used);
+            r
+        })
+        .collect()
+}
+
+/// Path segments after any "api"/"v1" prefix, with parameters recognised.
+fn segments(url: &str) -> Vec<Segment> {
+    let (_, path, _) = split_url(url);
+    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let version = |s: &str| {
+        s.strip_prefix(['v', 'V'])
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit() || c == '.'))
+    };
+    let skip = segments
+        .iter()
+        .take_while(|s| PREFIXES.contains(&s.to_lowercase().as_str()) || version(s))
+        .count();
+    segments.drain(..skip);
+    segments
+        .into_iter()
+        .map(|s| {
+            let inner = s
+                .strip_prefix("{{")
+                .and_then(|s| s.strip_suffix("}}"))
+                .or_else(|| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+                .or_else(|| s.strip_prefix('<').and_then(|s| s.strip_suffix('>')))
+                .or_else(|| s.strip_prefix(':'));
+            match inner {
+                Some(name) => Segment::Param(param_name(name)),
+                None if looks_like_id(s) => Segment::Param("id".to_string()),
+                None => Segment::Literal(crate::percent_decode(s)),
+            }
+        })
+        .collect()
+}
+
+/// "userId", "user_id" and "id" → "id"; other names are humanized.
+fn param_name(name: &str) -> String {
+    let words = humanize(name);
+    if words.split(' ').next_back() == Some("id") {
+        "id".to_string()
+    } else {
+        words
+    }
+}
+
+fn param_names(segments: &[Segment]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in segments {
+        if let Segment::Param(name) = segment
+            && !names.contains(name)
+        {
+            names.push(name.clone());
+        }
+    }
+    names
+}
+
+fn with_params(name: &str, params: &[String]) -> String {
+    if params.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} by {}", name, params.join(" and "))
+    }
+}
+
+fn with_context(name: String, parent: &Option<String>) -> String {
+    match parent {
+        Some(parent) => format!("{} for {}", name, parent),
+        None => name,
+    }
+}
+
+fn verb(method: &str) -> String {
+    match method {
+        "GET" => "Get".to_string(),
+        "HEAD" => "Check".to_string(),
+        "OPTIONS" => "Options for".to_string(),
+        other => sentence(&other.to_lowercase()),
+    }
+}
+
+/// "userProfiles", "user-profiles", "user_profiles.json" → "user profiles".
+pub(crate) fn humanize(text: &str) -> String {
+    let text = text.split('.').next().unwrap_or(text);
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in text.chars() {
+        if c == '-' || c == '_' || c == ' ' || c == '+' {
+            if !out.ends_with(' ') && !out.is_empty() {
+                out.push(' ');
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            out.push(' ');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        out.extend(c.to_lowercase());
+    }
+    out.trim_end().to_string()
+}
+
+/// English singular of the last word, for the common regular plurals.
+pub(crate) fn singular(words: &str) -> String {
+    let (head, last) = match words.rsplit_once(' ') {
+        Some((head, last)) => (format!("{} ", head), last),
+        None => (String::new(), words),
+    };
+    let one = if let Some(stem) = last.strip_suffix("ies").filter(|s| s.len() > 1) {
+        format!("{}y", stem)
+    } else if ["sses", "shes", "ches", "xes", "zes"]
+        .iter()
+        .any(|suffix| last.ends_with(suffix))
+    {
+        last[..last.len() - 2].to_string()
+    } else if last.len() > 3
+        && last.ends_with('s')
+        && !["ss", "us", "is"].iter().any(|s| last.ends_with(s))
+    {
+        last[..last.len() - 1].to_string()
+    } else {
+        last.to_string()
+    };
+    format!("{}{}", head, one)
+}
+
+fn sentence(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_suggest_request_name() {
+        for (method, url, name) in [
+            ("GET", "https://api.x.io/api/v1/users", "List users"),
+            ("get", "https://api.x.io/users/42", "Get user by id"),
+            (
+                "GET",
+                "{{baseUrl}}/users/{userId}/posts",
+                "List posts for user",
+            ),
+            ("GET", "/repos/:owner/:repo", "Get repo by owner and repo"),
+            ("POST", "/users", "Create user"),
+            ("PATCH", "/users/:id", "Update user"),
+            (
+                "DELETE",
+                "https://x.io/categories/{{categoryId}}",
+                "Delete category",
+            ),
+            ("POST", "/users/123/activate", "Activate user"),
+            ("POST", "/v2/auth/login", "Login"),
+            ("GET", "/users/search?q=x", "Search users"),
+            ("GET", "/me", "Get me"),
+            ("GET", "/health-check", "Get health check"),
+            ("GET", "/user-addresses", "List user addresses"),
+            ("HEAD", "/status", "Check status"),
+            ("GET", "https://x.io/", "Get root"),
+        ] {
+            assert_eq!(
+                suggest_request_name(method, url),
+                name,
+                "{} {}",
+                method,
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_suggest_folder_structure() {
+        let requests = serde_json::json!([
+            {"method": "GET", "url": "https://api.x.io/v1/users"},
+            {"method": "POST", "url": "https://api.x.io/v1/users"},
+            {"method": "GET", "url": "https://api.x.io/v1/users/1"},
+            {"method": "GET", "url": "https://api.x.io/v1/users/1/orders"},
+            {"method": "GET", "url": "https://api.x.io/v1/users/2/orders"},
+            {"name": "Ping", "method": "GET", "url": "https://api.x.io/v1/ping"},
+            {"method": "GET", "url": "https://api.x.io/v1/"},
+        ]);
+        let out: Value =
+            serde_json::from_str(&suggest_folder_structure(&requests.to_string())).unwrap();
+        let loose: Vec<&str> = out["requests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(loose, ["Get root", "Ping"]);
+        let users = &out["folders"][0];
+        assert_eq!(users["name"], "Users");
+        assert_eq!(users["requests"].as_array().unwrap().len(), 3);
+        assert_eq!(users["folders"][0]["name"], "Orders");
+        assert_eq!(
+            users["folders"][0]["requests"][0]["name"],
+            "List orders for user"
+        );
+        assert_eq!(
+            users["folders"][0]["requests"][1]["name"],
+            "List orders for user (2)"
+        );
+
+        let hosts = serde_json::json!([
+            {"method": "GET", "url": "https://a.io/items"},
+            {"method": "GET", "url": "https://b.io/items"},
+        ]);
+        let out: Value =
+            serde_json::from_str(&suggest_folder_structure(&hosts.to_string())).unwrap();
+        assert_eq!(out["folders"][0]["name"], "a.io");
+        assert_eq!(out["folders"][1]["requests"][0]["name"], "List items");
+        assert!(
+            serde_json::from_str::<Value>(&suggest_folder_structure("{}")).unwrap()["error"]
+                .is_string()
+        );
+    }
+}
@@ -0,0 +1,780 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+#[derive(Serialize)]
+struct Problem {
+    rule: &'static str,
+    severity: &'static str,
+    path: String,
+    message: String,
+}
+
+#[derive(Default)]
+struct Linter {
+    problems: Vec<Problem>,
+}
+
+impl Linter {
+    fn report(&mut self, rule: &'static str, severity: &'static str, path: &str, message: String) {
+        self.problems.push(Problem {
+            rule,
+            severity,
+            path: path.to_string(),
+            message,
+        });
+    }
+}
+
+/// Lint an OpenAPI 3.x (or Swagger 2.0) document given as JSON.
+/// Rules: openapi-version, operation-operationId, operation-operationId-unique,
+/// operation-success-response, path-params, invalid-ref, response-description,
+/// unused-component.
+/// Returns JSON {valid, problems: [{rule, severity, path, message}], counts: {error, warning, info}}.
+/// `path` is a JSON Pointer into the document; `valid` is false when any error was found.
+#[wasm_bindgen]
+pub fn lint_openapi(spec: &str) -> String {
+    let doc: Value = match serde_json::from_str(spec) {
+        Ok(v) => v,
+        Err(e) => {
+            let mut linter = Linter::default();
+            linter.report(
+                "parse",
+                "error",
+                "",
+                format!("Spec is not valid JSON: {}", e),
+            );
+            return finish(linter);
+        }
+    };
+
+    let _timer = crate::logging::timer("lint_openapi");
+    let mut linter = Linter::default();
+    if doc.get("openapi").and_then(Value::as_str).is_none()
+        && doc.get("swagger").and_then(Value::as_str).is_none()
+    {
+        linter.report(
+            "openapi-version",
+            "error",
+            "",
+            "Document has no \"openapi\" (or \"swagger\") version field".to_string(),
+        );
+    }
+
+    lint_operations(&doc, &mut linter);
+
+    let mut refs: Vec<(String, String)> = Vec::new();
+    collect_refs(&doc, "", &mut refs);
+    for (location, target) in &refs {
+        match target.strip_prefix('#') {
+            Some(pointer) => {
+                if resolve_pointer(&doc, pointer).is_none() {
+                    linter.report(
+                        "invalid-ref",
+                        "error",
+                        location,
+                        format!("$ref \"{}\" does not resolve", target),
+                    );
+                }
+            }
+            None => linter.report(
+                "invalid-ref",
+                "info",
+                location,
+                format!("External $ref \"{}\" was not checked", target),
+            ),
+        }
+    }
+
+    lint_unused_components(&doc, &refs, &mut linter);
+    finish(linter)
+}
+
+fn finish(linter: Linter) -> String {
+    let count = |sev: &str| linter.problems.iter().filter(|p| p.severity == sev).count();
+    let result = serde_json::json!({
+        "valid": count("error") == 0,
+        "problems": linter.problems,
+        "counts": {
+            "error": count("error"),
+            "warning": count("warning"),
+            "info": count("info"),
+        }
+    });
+    serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Escape a single JSON Pointer reference token (RFC 6901).
+pub(crate) fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Resolve a JSON Pointer ("/components/schemas/User") against a document.
+pub(crate) fn resolve_pointer<'a>(doc: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(doc);
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+    let mut current = doc;
+    for raw in pointer[1..].split('/') {
+        let token = crate::percent_decode(raw)
+            .replace("~1", "/")
+            .replace("~0", "~");
+        current = match current {
+            Value::Object(map) => map.get(&token)?,
+            Value::Array(arr) => arr.get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn collect_refs(value: &Value, path: &str, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(target)) = map.get("$ref") {
+                out.push((format!("{}/$ref", path), target.clone()));
+            }
+            for (k, v) in map {
+                collect_refs(v, &format!("{}/{}", path, escape_pointer(k)), out);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, v) in arr.iter().enumerate() {
+                collect_refs(v, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lint_operations(doc: &Value, linter: &mut Linter) {
+    let Some(paths) = doc.get("paths").and_then(Value::as_object) else {
+        return;
+    };
+    let mut operation_ids: Vec<(String, String)> = Vec::new();
+
+    for (path, item) in paths {
+        let item_ptr = format!("/paths/{}", escape_pointer(path));
+        let template_params: Vec<String> = path
+            .split('{')
+            .skip(1)
+            .filter_map(|s| s.split('}').next())
+            .map(|s| s.to_string())
+            .collect();
+        let shared_params = item.get("parameters");
+
+        for method in METHODS {
+            let Some(op) = item.get(*method) else {
+                continue;
+            };
+            let op_ptr = format!("{}/{}", item_ptr, method);
+            let label = format!("{} {}", method.to_uppercase(), path);
+
+            match op.get("operationId").and_then(Value::as_str) {
+                Some(id) if !id.trim().is_empty() => {
+                    if let Some((_, first)) = operation_ids.iter().find(|(i, _)| i == id) {
+                        linter.report(
+                            "operation-operationId-unique",
+                            "error",
+                            &format!("{}/operationId", op_ptr),
+                            format!("operationId \"{}\" is already used by {}", id, first),
+                        );
+                    } else {
+                        operation_ids.push((id.to_string(), label.clone()));
+                    }
+                }
+                _ => linter.report(
+                    "operation-operationId",
+                    "warning",
+                    &op_ptr,
+                    format!("{} has no operationId", label),
+                ),
+            }
+
+            // Path template parameters must be declared as `in: path`.
+            let declared = |params: Option<&Value>, name: &str| {
+                params.and_then(Value::as_array).is_some_and(|list| {
+                    list.iter().any(|p| {
+                        let p = match p.get("$ref").and_then(Value::as_str) {
+                            Some(r) => r
+                                .strip_prefix('#')
+                                .and_then(|ptr| resolve_pointer(doc, ptr))
+                                .unwrap_or(p),
+                            None => p,
+                        };
+                        p.get("in").and_then(Value::as_str) == Some("path")
+                            && p.get("name").and_then(Value::as_str) == Some(name)
+                    })
+                })
+            };
+            for name in &template_params {
+                if !declared(op.get("parameters"), name) && !declared(shared_params, name) {
+                    linter.report(
+                        "path-params",
+                        "error",
+                        &op_ptr,
+                        format!("{} does not declare path parameter \"{}\"", label, name),
+                    );
+                }
+            }
+
+            match op.get("responses").and_then(Value::as_object) {
+                None => linter.report(
+                    "operation-success-response",
+                    "warning",
+                    &op_ptr,
+                    format!("{} has no responses", label),
+                ),
+                Some(responses) => {
+                    if !responses
+                        .keys()
+                        .any(|code| code.starts_with('2') || code.starts_with('3'))
+                    {
+                        linter.report(
+                            "operation-success-response",
+                            "warning",
+                            &format!("{}/responses", op_ptr),
+                            format!("{} has no 2xx or 3xx response", label),
+                        );
+                    }
+                    for (code, response) in responses {
+                        if response.get("$ref").is_some() {
+                            continue;
+                        }
+                        let has_description = response
+                            .get("description")
+                            .and_then(Value::as_str)
+                            .is_some_and(|d| !d.trim().is_empty());
+                        if !has_description {
+                            linter.report(
+                                "response-description",
+                                "error",
+                                &format!("{}/responses/{}", op_ptr, escape_pointer(code)),
+                                format!("Response {} of {} has no description", code, label),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn lint_unused_components(doc: &Value, refs: &[(String, String)], linter: &mut Linter) {
+    let mut sections: Vec<(String, &serde_json::Map<String, Value>)> = Vec::new();
+    if let Some(components) = doc.get("components").and_then(Value::as_object) {
+        for (name, section) in components {
+            if name == "securitySchemes" {
+                continue;
+            }
+            if let Some(map) = section.as_object() {
+                sections.push((format!("/components/{}", escape_pointer(name)), map));
+            }
+        }
+    }
+    // Swagger 2.0 keeps reusable objects at the top level.
+    for name in ["definitions", "parameters", "responses"] {
+        if doc.get("swagger").is_some()
+            && let Some(map) = doc.get(name).and_then(Value::as_object)
+        {
+            sections.push((format!("/{}", name), map));
+        }
+    }
+
+    for (prefix, map) in sections {
+        for name in map.keys() {
+            let pointer = format!("{}/{}", prefix, escape_pointer(name));
+            let target = format!("#{}", pointer);
+            let used = refs
+                .iter()
+                .any(|(_, r)| r == &target || r.starts_with(&format!("{}/", target)));
+            if !used {
+                linter.report(
+                    "unused-component",
+                    "warning",
+                    &pointer,
+                    format!("Component \"{}\" is never referenced", name),
+                );
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct ExampleOptions {
+    /// Emit properties that are not listed in `required`.
+    #[serde(rename = "includeOptional")]
+    include_optional: bool,
+    /// Nesting depth (objects and arrays) after which generation stops with null.
+    #[serde(rename = "maxDepth")]
+    max_depth: usize,
+    /// Prefer `example`, `examples` and `default` over synthesized values.
+    #[serde(rename = "useExamples")]
+    use_examples: bool,
+}
+
+impl Default for ExampleOptions {
+    fn default() -> Self {
+        ExampleOptions {
+            include_optional: true,
+            max_depth: 8,
+            use_examples: true,
+        }
+    }
+}
+
+struct ExampleBuilder<'a> {
+    doc: &'a Value,
+    options: ExampleOptions,
+    /// $ref targets currently being expanded, for cycle detection.
+    stack: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// Generate an example body for a single schema of an OpenAPI document.
+/// schema_ref: "#/components/schemas/User", or a bare component name ("User")
+/// looked up under components/schemas (or Swagger 2.0 definitions).
+/// options_json: optional {includeOptional, maxDepth, useExamples}.
+/// Follows $ref chains, merges allOf, takes the first oneOf/anyOf branch and
+/// uses string formats (uuid, date-time, email, ...) for realistic values.
+/// Returns JSON {example, warnings: [...]} or {error}.
+#[wasm_bindgen]
+pub fn openapi_example_body(spec: &str, schema_ref: &str, options_json: &str) -> String {
+    let doc: Value = match serde_json::from_str(spec) {
+        Ok(v) => v,
+        Err(e) => return error_json(&format!("Spec is not valid JSON: {}", e)),
+    };
+    let options: ExampleOptions = serde_json::from_str(options_json).unwrap_or_default();
+
+    let target = schema_ref.trim();
+    let pointer = match target.strip_prefix('#') {
+        Some(p) => p.to_string(),
+        None if target.starts_with('/') => target.to_string(),
+        None => {
+            let name = escape_pointer(target);
+            if doc.get("swagger").is_some() {
+                format!("/definitions/{}", name)
+            } else {
+                format!("/components/schemas/{}", name)
+            }
+        }
+    };
+    if resolve_pointer(&doc, &pointer).is_none() {
+        return error_json(&format!("Schema \"{}\" not found", schema_ref));
+    }
+
+    let mut builder = ExampleBuilder {
+        doc: &doc,
+        options,
+        stack: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let root = serde_json::json!({ "$ref": format!("#{}", pointer) });
+    let example = builder.build(&root, 0, None);
+    serde_json::to_string(&serde_json::json!({
+        "example": example,
+        "warnings": builder.warnings,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+impl ExampleBuilder<'_> {
+    fn build(&mut self, schema: &Value, depth: usize, name: Option<&str>) -> Value {
+        if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+            return self.build_ref(target, depth, name);
+        }
+        let Some(obj) = schema.as_object() else {
+            return Value::Null;
+        };
+
+        if self.options.use_examples {
+            if let Some(example) = obj.get("example") {
+                return example.clone();
+            }
+            if let Some(first) = obj
+                .get("examples")
+                .and_then(Value::as_array)
+                .and_then(|e| e.first())
+            {
+                return first.clone();
+            }
+            if let Some(default) = obj.get("default") {
+                return default.clone();
+            }
+        }
+        if let Some(constant) = obj.get("const") {
+            return constant.clone();
+        }
+        if let Some(first) = obj
+            .get("enum")
+            .and_then(Value::as_array)
+            .and_then(|e| e.iter().find(|v| !v.is_null()).or(e.first()))
+        {
+            return first.clone();
+        }
+
+        if let Some(parts) = obj.get("allOf").and_then(Value::as_array) {
+            return self.build_all_of(obj, parts, depth, name);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = obj.get(key).and_then(Value::as_array).and_then(|branches| {
+                branches
+                    .iter()
+                    .find(|b| b.get("type").and_then(Value::as_str) != Some("null"))
+            }) {
+                return self.build(first, depth, name);
+            }
+        }
+
+        match schema_type(obj) {
+            "object" => self.build_object(obj, depth),
+            "array" => self.build_array(obj, depth, name),
+            "string" => string_example(obj),
+            "integer" => Value::from(number_example(obj).round() as i64),
+            "number" => serde_json::Number::from_f64(number_example(obj))
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            "boolean" => Value::Bool(true),
+            _ => Value::Null,
+        }
+    }
+
+    fn build_ref(&mut self, target: &str, depth: usize, name: Option<&str>) -> Value {
+        let Some(pointer) = target.strip_prefix('#') else {
+            self.warnings
+                .push(format!("External $ref \"{}\" was not followed", target));
+            return Value::Null;
+        };
+        if self.stack.iter().any(|t| t == target) {
+            self.warnings
+                .push(format!("Recursive $ref \"{}\" was cut off", target));
+            return Value::Null;
+        }
+        let Some(resolved) = resolve_pointer(self.doc, pointer) else {
+            self.warnings
+                .push(format!("$ref \"{}\" does not resolve", target));
+            return Value::Null;
+        };
+        self.stack.push(target.to_string());
+        let value = self.build(resolved, depth, name);
+        self.stack.pop();
+        value
+    }
+
+    /// Merge the examples of every allOf branch (and any sibling properties).
+    fn build_all_of(
+        &mut self,
+        obj: &Map<String, Value>,
+        parts: &[Value],
+        depth: usize,
+        name: Option<&str>,
+    ) -> Value {
+        let mut merged: Option<Value> = None;
+        let mut siblings = obj.clone();
+        siblings.remove("allOf");
+        let own = siblings.contains_key("properties").then(|| Value::Object(siblings));
+        for part in parts.iter().chain(own.as_ref()) {
+            let value = self.build(part, depth, name);
+            merged = match (merged, value) {
+                (Some(Value::Object(mut acc)), Value::Object(next)) => {
+                    acc.extend(next);
+                    Some(Value::Object(acc))
+                }
+                (Some(prev), Value::Null) => Some(prev),
+                (_, next) => Some(next),
+            };
+        }
+        merged.unwrap_or(Value::Null)
+    }
+
+    fn build_object(&mut self, obj: &Map<String, Value>, depth: usize) -> Value {
+        if depth >= self.options.max_depth {
+            return Value::Null;
+        }
+        let required: Vec<&str> = obj
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let mut out = Map::new();
+        if let Some(properties) = obj.get("properties").and_then(Value::as_object) {
+            for (key, prop) in properties {
+                if !self.options.include_optional && !required.contains(&key.as_str()) {
+                    continue;
+                }
+                if prop.get("readOnly") == Some(&Value::Bool(true)) {
+                    continue;
+                }
+                out.insert(key.clone(), self.build(prop, depth + 1, Some(key)));
+            }
+        } else if let Some(extra) = obj.get("additionalProperties").filter(|a| a.is_object()) {
+            out.insert("key".to_string(), self.build(extra, depth + 1, None));
+        }
+        Value::Object(out)
+    }
+
+    fn build_array(&mut self, obj: &Map<String, Value>, depth: usize, name: Option<&str>) -> Value {
+        if depth >= self.options.max_depth {
+            return Value::Array(Vec::new());
+        }
+        let Some(items) = obj.get("items") else {
+            return Value::Array(Vec::new());
+        };
+        let count = obj
+            .get("minItems")
+            .and_then(Value::as_u64)
+            .unwrap_or(1)
+            .clamp(1, 10) as usize;
+        let item = self.build(items, depth + 1, name);
+        Value::Array(vec![item; count])
+    }
+}
+
+/// The schema's type, inferred from its keywords when `type` is absent.
+/// OpenAPI 3.1 type arrays resolve to their first non-null entry.
+fn schema_type(obj: &Map<String, Value>) -> &str {
+    match obj.get("type") {
+        Some(Value::String(t)) => t,
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if obj.contains_key("properties") || obj.contains_key("additionalProperties") => "object",
+        _ if obj.contains_key("items") => "array",
+        _ => "",
+    }
+}
+
+fn string_example(obj: &Map<String, Value>) -> Value {
+    let format = obj.get("format").and_then(Value::as_str).unwrap_or("");
+    let sample = match format {
+        "date-time" => "2024-01-15T09:30:00Z",
+        "date" => "2024-01-15",
+        "time" => "09:30:00",
+        "email" => "user@example.com",
+        "uri" | "url" | "uri-reference" => "https://example.com",
+        "hostname" => "example.com",
+        "ipv4" => "192.0.2.1",
+        "ipv6" => "2001:db8::1",
+        "uuid" => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+        "byte" => "ZXhhbXBsZQ==",
+        "binary" => "",
+        "password" => "********",
+        _ => "string",
+    };
+    let mut text = sample.to_string();
+    if format.is_empty() {
+        let min = obj.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let max = obj
+            .get("maxLength")
+            .and_then(Value::as_u64)
+            .map(|m| m as usize);
+        while text.len() < min {
+            text.push('x');
+        }
+        if let Some(max) = max {
+            text.truncate(max);
+        }
+    }
+    Value::String(text)
+}
+
+/// A value inside [minimum, maximum], honouring exclusive bounds in both the
+/// 3.0 (boolean) and 3.1 (numeric) forms.
+fn number_example(obj: &Map<String, Value>) -> f64 {
+    let bound = |inclusive: &str, exclusive: &str, step: f64| -> Option<f64> {
+        match obj.get(exclusive) {
+            Some(Value::Number(n)) => n.as_f64().map(|n| n + step),
+            Some(Value::Bool(true)) => obj.get(inclusive).and_then(Value::as_f64).map(|n| n + step),
+            _ => obj.get(inclusive).and_then(Value::as_f64),
+        }
+    };
+    let min = bound("minimum", "exclusiveMinimum", 1.0);
+    let max = bound("maximum", "exclusiveMaximum", -1.0);
+    match (min, max) {
+        (Some(min), _) => min,
+        (None, Some(max)) if max < 0.0 => max,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(spec: &Value) -> Value {
+        serde_json::from_str(&lint_openapi(&spec.to_string())).unwrap()
+    }
+
+    fn rules(result: &Value) -> Vec<String> {
+        result["problems"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["rule"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_openapi_clean_spec() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/users/{id}": {
+                    "parameters": [{"$ref": "#/components/parameters/Id"}],
+                    "get": {
+                        "operationId": "getUser",
+                        "responses": {"200": {"description": "OK", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}}}}
+                    }
+                }
+            },
+            "components": {
+                "schemas": {"User": {"type": "object"}},
+                "parameters": {"Id": {"name": "id", "in": "path", "required": true}}
+            }
+        });
+        let result = lint(&spec);
+        assert_eq!(result["valid"], true);
+        assert!(rules(&result).is_empty());
+    }
+
+    #[test]
+    fn test_lint_openapi_reports_problems() {
+        let spec = serde_json::json!({
+            "openapi": "3.1.0",
+            "paths": {
+                "/a/{id}": {
+                    "get": {"operationId": "dup", "responses": {"404": {}}},
+                    "post": {"operationId": "dup", "responses": {"201": {"description": "x", "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Missing"}}}}}}
+                },
+                "/b": {"delete": {"responses": {"204": {"description": "gone"}}}}
+            },
+            "components": {"schemas": {"Unused": {"type": "string"}}}
+        });
+        let result = lint(&spec);
+        assert_eq!(result["valid"], false);
+        let r = rules(&result);
+        assert!(r.contains(&"operation-operationId-unique".to_string()));
+        assert!(r.contains(&"operation-operationId".to_string()));
+        assert!(r.contains(&"operation-success-response".to_string()));
+        assert!(r.contains(&"response-description".to_string()));
+        assert!(r.contains(&"path-params".to_string()));
+        assert!(r.contains(&"invalid-ref".to_string()));
+        assert!(r.contains(&"unused-component".to_string()));
+        let unused = result["problems"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["rule"] == "unused-component")
+            .unwrap();
+        assert_eq!(unused["path"], "/components/schemas/Unused");
+    }
+
+    #[test]
+    fn test_resolve_pointer_escapes() {
+        let doc = serde_json::json!({"paths": {"/a/b": {"get": [1, 2]}}});
+        assert_eq!(
+            resolve_pointer(&doc, "/paths/~1a~1b/get/1"),
+            Some(&Value::from(2))
+        );
+        assert_eq!(resolve_pointer(&doc, "/paths/missing"), None);
+    }
+
+    fn example(spec: &Value, schema_ref: &str, options: &str) -> Value {
+        serde_json::from_str(&openapi_example_body(
+            &spec.to_string(),
+            schema_ref,
+            options,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_openapi_example_body_refs_and_formats() {
+        let spec = serde_json::json!({
+            "openapi": "3.1.0",
+            "components": {"schemas": {
+                "Base": {"type": "object", "required": ["id"], "properties": {
+                    "id": {"type": "string", "format": "uuid"},
+                    "createdAt": {"type": "string", "format": "date-time", "readOnly": true}
+                }},
+                "User": {"allOf": [{"$ref": "#/components/schemas/Base"}, {"type": "object", "properties": {
+                    "email": {"type": "string", "format": "email"},
+                    "age": {"type": "integer", "minimum": 18},
+                    "role": {"type": "string", "enum": ["admin", "member"]},
+                    "nickname": {"type": ["null", "string"], "example": "jd"},
+                    "pet": {"oneOf": [{"$ref": "#/components/schemas/Pet"}, {"type": "string"}]},
+                    "tags": {"type": "array", "minItems": 2, "items": {"type": "string", "minLength": 8}}
+                }}]},
+                "Pet": {"type": "object", "properties": {"name": {"type": "string"}}}
+            }}
+        });
+        let result = example(&spec, "User", "");
+        assert_eq!(
+            result["example"],
+            serde_json::json!({
+                "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                "email": "user@example.com",
+                "age": 18,
+                "role": "admin",
+                "nickname": "jd",
+                "pet": {"name": "string"},
+                "tags": ["stringxx", "stringxx"]
+            })
+        );
+        assert!(result["warnings"].as_array().unwrap().is_empty());
+
+        let required = example(
+            &spec,
+            "#/components/schemas/Base",
+            r#"{"includeOptional":false}"#,
+        );
+        assert_eq!(
+            required["example"],
+            serde_json::json!({"id": "3fa85f64-5717-4562-b3fc-2c963f66afa6"})
+        );
+    }
+
+    #[test]
+    fn test_openapi_example_body_cycles_and_errors() {
+        let spec = serde_json::json!({
+            "swagger": "2.0",
+            "definitions": {"Node": {"type": "object", "properties": {
+                "value": {"type": "number", "exclusiveMinimum": true, "minimum": 0},
+                "next": {"$ref": "#/definitions/Node"}
+            }}}
+        });
+        let result = example(&spec, "Node", "");
+        assert_eq!(
+            result["example"],
+            serde_json::json!({"value": 1.0, "next": null})
+        );
+        assert_eq!(
+            result["warnings"][0],
+            "Recursive $ref \"#/definitions/Node\" was cut off"
+        );
+        assert_eq!(
+            example(&spec, "Missing", "")["error"],
+            "Schema \"Missing\" not found"
+        );
+    }
+
+    #[test]
+    fn test_lint_openapi_invalid_json() {
+        let result: Value = serde_json::from_str(&lint_openapi("openapi: 3")).unwrap();
+        assert_eq!(result["problems"][0]["rule"], "parse");
+    }
+}
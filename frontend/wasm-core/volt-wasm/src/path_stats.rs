@@ -0,0 +1,490 @@
+use indexmap::IndexMap;
+use regex_lite::Regex;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+use crate::{get_json_path, get_value_type};
+
+/// Array items beyond this index are not analyzed individually.
+const MAX_ARRAY_ITEMS: usize = 50;
+const TOP_VALUES: usize = 5;
+
+/// Statistics for one JSON path across many responses of the same endpoint.
+/// responses_json: array whose items are raw body strings, {body: "..."} response
+/// objects, or already-parsed bodies.
+/// Returns JSON {path, samples, present, missing, distinct, changeRate, classification,
+/// types: {type: count}, min, max, topValues: [{value, count}]}.
+#[wasm_bindgen]
+pub fn path_stats(responses_json: &str, path: &str) -> String {
+    let bodies = parse_bodies(responses_json);
+    let found: Vec<_> = bodies.iter().map(|b| get_json_path(b, path)).collect();
+    let values: Vec<Option<&Value>> = found.iter().map(|v| v.as_deref()).collect();
+    let stats = FieldStats::from_values(&values);
+
+    let mut types: Map<String, Value> = Map::new();
+    for v in values.iter().flatten() {
+        let count = types.entry(get_value_type(v)).or_insert(Value::from(0));
+        *count = Value::from(count.as_u64().unwrap_or(0) + 1);
+    }
+    let numbers: Vec<f64> = values.iter().flatten().filter_map(|v| v.as_f64()).collect();
+    let min = numbers.iter().copied().reduce(f64::min);
+    let max = numbers.iter().copied().reduce(f64::max);
+
+    let mut counts: Vec<(&Value, usize)> = Vec::new();
+    for v in values.iter().flatten() {
+        match counts.iter_mut().find(|(k, _)| k == v) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((v, 1)),
+        }
+    }
+    counts.sort_by_key(|c| std::cmp::Reverse(c.1));
+    let top: Vec<Value> = counts
+        .into_iter()
+        .take(TOP_VALUES)
+        .map(|(value, count)| serde_json::json!({ "value": value, "count": count }))
+        .collect();
+
+    serde_json::json!({
+        "path": path,
+        "samples": values.len(),
+        "present": stats.present,
+        "missing": values.len() - stats.present,
+        "distinct": stats.distinct,
+        "changeRate": stats.change_rate,
+        "classification": stats.classification(values.len()),
+        "types": types,
+        "min": min,
+        "max": max,
+        "topValues": top,
+    })
+    .to_string()
+}
+
+/// Classify every leaf field across many responses of the same endpoint.
+/// Fields that change on every run (timestamps, generated ids) are "volatile" and
+/// returned in `suggestedIgnore` for snapshot comparisons.
+/// Returns JSON {samples, fields: [{path, classification, changeRate, distinct, present, kind}],
+/// suggestedIgnore: [path]}. kind is "timestamp", "uuid", "id" or "value".
+#[wasm_bindgen]
+pub fn detect_flaky_fields(responses_json: &str) -> String {
+    let bodies = parse_bodies(responses_json);
+
+    // Paths in first-seen order, so output follows the body layout.
+    let mut order: Vec<String> = Vec::new();
+    let mut per_path: HashMap<String, Vec<Option<&Value>>> = HashMap::new();
+    for (i, body) in bodies.iter().enumerate() {
+        let mut leaves = Vec::new();
+        collect_leaves(body, String::new(), &mut leaves);
+        for (path, value) in leaves {
+            let slot = per_path.entry(path.clone()).or_insert_with(|| {
+                order.push(path);
+                Vec::new()
+            });
+            slot.resize(i, None);
+            slot.push(Some(value));
+        }
+    }
+
+    let mut fields = Vec::new();
+    let mut ignore = Vec::new();
+    for path in &order {
+        let mut values = per_path.remove(path).unwrap_or_default();
+        values.resize(bodies.len(), None);
+        let stats = FieldStats::from_values(&values);
+        let classification = stats.classification(bodies.len());
+        if classification == "volatile" {
+            ignore.push(path.clone());
+        }
+        fields.push(serde_json::json!({
+            "path": path,
+            "classification": classification,
+            "changeRate": stats.change_rate,
+            "distinct": stats.distinct,
+            "present": stats.present,
+            "kind": field_kind(path, &values),
+        }));
+    }
+
+    serde_json::json!({
+        "samples": bodies.len(),
+        "fields": fields,
+        "suggestedIgnore": ignore,
+    })
+    .to_string()
+}
+
+/// Compare the shape of two sets of responses from the same endpoint, e.g. last
+/// week's history against today's runs. Both arguments accept the same items as
+/// `path_stats`. Array elements are merged under `path[]`.
+/// Returns JSON {oldSamples, newSamples, drifted, changes: [{path, change, before, after}]}
+/// where change is "added", "removed", "retyped" or "nullability". before/after hold
+/// {types, nullable} for added/removed fields, the type lists for "retyped" and the
+/// nullable flags for "nullability". Fields below an added or removed parent are
+/// not listed separately.
+#[wasm_bindgen]
+pub fn detect_schema_drift(old_samples_json: &str, new_samples_json: &str) -> String {
+    let old_bodies = parse_bodies(old_samples_json);
+    let new_bodies = parse_bodies(new_samples_json);
+    let before = infer_shapes(&old_bodies);
+    let after = infer_shapes(&new_bodies);
+
+    let mut changes = Vec::new();
+    let mut gone: HashSet<&str> = HashSet::new();
+    for (path, old) in &before {
+        if after.contains_key(path) {
+            continue;
+        }
+        gone.insert(path);
+        if !parent_path(path).is_some_and(|p| gone.contains(p)) {
+            changes.push(drift(path, "removed", old.to_json(), Value::Null));
+        }
+    }
+
+    let mut added: HashSet<&str> = HashSet::new();
+    for (path, new) in &after {
+        let Some(old) = before.get(path) else {
+            added.insert(path);
+            if !parent_path(path).is_some_and(|p| added.contains(p)) {
+                changes.push(drift(path, "added", Value::Null, new.to_json()));
+            }
+            continue;
+        };
+        // A field only ever seen as null has no type to compare against.
+        if !old.types.is_empty() && !new.types.is_empty() && old.types != new.types {
+            changes.push(drift(
+                path,
+                "retyped",
+                Value::from(old.types.clone()),
+                Value::from(new.types.clone()),
+            ));
+        }
+        if old.nullable != new.nullable {
+            changes.push(drift(
+                path,
+                "nullability",
+                Value::from(old.nullable),
+                Value::from(new.nullable),
+            ));
+        }
+    }
+
+    serde_json::json!({
+        "oldSamples": old_bodies.len(),
+        "newSamples": new_bodies.len(),
+        "drifted": !changes.is_empty(),
+        "changes": changes,
+    })
+    .to_string()
+}
+
+fn parse_bodies(responses_json: &str) -> Vec<Value> {
+    let items: Vec<Value> = serde_json::from_str(responses_json).unwrap_or_default();
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            Value::String(s) => serde_json::from_str(&s).ok(),
+            Value::Object(ref map) if map.get("body").is_some_and(Value::is_string) => {
+                serde_json::from_str(map["body"].as_str().unwrap_or_default()).ok()
+            }
+            other => Some(other),
+        })
+        .collect()
+}
+
+/// Leaf values with paths in the `a.b[0].c` syntax understood by `get_json_path`.
+/// Arrays directly inside arrays (or at the root) cannot be addressed and are skipped.
+fn collect_leaves<'a>(value: &'a Value, path: String, out: &mut Vec<(String, &'a Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                if k.contains('.') || k.contains('[') {
+                    continue;
+                }
+                let child = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", path, k)
+                };
+                collect_leaves(v, child, out);
+            }
+        }
+        Value::Array(items) => {
+            if path.is_empty() || path.ends_with(']') {
+                return;
+            }
+            for (i, v) in items.iter().take(MAX_ARRAY_ITEMS).enumerate() {
+                collect_leaves(v, format!("{}[{}]", path, i), out);
+            }
+        }
+        _ if !path.is_empty() => out.push((path, value)),
+        _ => {}
+    }
+}
+
+/// Types and nullability observed for one field across a set of bodies.
+#[derive(Default)]
+struct Shape {
+    /// Non-null types, sorted so sets compare regardless of sample order.
+    types: Vec<&'static str>,
+    nullable: bool,
+}
+
+impl Shape {
+    fn to_json(&self) -> Value {
+        serde_json::json!({ "types": self.types, "nullable": self.nullable })
+    }
+}
+
+fn infer_shapes(bodies: &[Value]) -> IndexMap<String, Shape> {
+    let mut shapes = IndexMap::new();
+    for body in bodies {
+        record_shape(body, String::new(), &mut shapes);
+    }
+    for shape in shapes.values_mut() {
+        shape.types.sort_unstable();
+    }
+    shapes
+}
+
+fn record_shape(value: &Value, path: String, shapes: &mut IndexMap<String, Shape>) {
+    if !path.is_empty() {
+        let shape = shapes.entry(path.clone()).or_default();
+        match get_value_type(value) {
+            "null" => shape.nullable = true,
+            t if !shape.types.contains(&t) => shape.types.push(t),
+            _ => {}
+        }
+    }
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let child = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", path, k)
+                };
+                record_shape(v, child, shapes);
+            }
+        }
+        Value::Array(items) => {
+            for v in items.iter().take(MAX_ARRAY_ITEMS) {
+                record_shape(v, format!("{}[]", path), shapes);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// "a.b" → "a", "a[]" → "a", "a" → None.
+fn parent_path(path: &str) -> Option<&str> {
+    if let Some(p) = path.strip_suffix("[]") {
+        return (!p.is_empty()).then_some(p);
+    }
+    path.rfind('.').map(|i| &path[..i])
+}
+
+fn drift(path: &str, change: &str, before: Value, after: Value) -> Value {
+    serde_json::json!({ "path": path, "change": change, "before": before, "after": after })
+}
+
+struct FieldStats {
+    present: usize,
+    distinct: usize,
+    /// Fraction of consecutive present/present pairs whose value changed.
+    change_rate: f64,
+}
+
+impl FieldStats {
+    fn from_values(values: &[Option<&Value>]) -> Self {
+        let present: Vec<&Value> = values.iter().flatten().copied().collect();
+        let mut seen: Vec<&Value> = Vec::new();
+        for v in &present {
+            if !seen.contains(v) {
+                seen.push(v);
+            }
+        }
+        let pairs: Vec<bool> = values
+            .windows(2)
+            .filter_map(|w| match (w[0], w[1]) {
+                (Some(a), Some(b)) => Some(a != b),
+                _ => None,
+            })
+            .collect();
+        let change_rate = if pairs.is_empty() {
+            0.0
+        } else {
+            pairs.iter().filter(|c| **c).count() as f64 / pairs.len() as f64
+        };
+        FieldStats {
+            present: present.len(),
+            distinct: seen.len(),
+            change_rate,
+        }
+    }
+
+    fn classification(&self, samples: usize) -> &'static str {
+        if self.present == 0 {
+            "absent"
+        } else if self.present < 2 {
+            "insufficient"
+        } else if self.distinct == self.present && self.change_rate == 1.0 {
+            "volatile"
+        } else if self.distinct > 1 || self.present < samples {
+            "varying"
+        } else {
+            "stable"
+        }
+    }
+}
+
+fn field_kind(path: &str, values: &[Option<&Value>]) -> &'static str {
+    static UUID: OnceLock<Regex> = OnceLock::new();
+    static ISO: OnceLock<Regex> = OnceLock::new();
+    let uuid = UUID.get_or_init(|| {
+        Regex::new(r"(?i)^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap()
+    });
+    let iso = ISO.get_or_init(|| Regex::new(r"^\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}").unwrap());
+    // Last key of the path, without any index: "items[0].createdAt" → "createdAt".
+    let key = path.rsplit('.').next().unwrap_or(path);
+    let key = key.split('[').next().unwrap_or(key);
+    let lower = key.to_lowercase();
+    let timey_key = ["time", "date", "_at"].iter().any(|k| lower.contains(k))
+        || (key.ends_with("At") && key.len() > 2);
+
+    let present: Vec<&Value> = values.iter().flatten().copied().collect();
+    let all = |f: &dyn Fn(&Value) -> bool| !present.is_empty() && present.iter().all(|v| f(v));
+
+    if all(&|v| v.as_str().is_some_and(|s| uuid.is_match(s))) {
+        "uuid"
+    } else if all(&|v| v.as_str().is_some_and(|s| iso.is_match(s)))
+        || (timey_key && all(&|v| v.as_f64().is_some_and(is_epoch)))
+    {
+        "timestamp"
+    } else if lower == "id" || lower.ends_with("_id") || (key.ends_with("Id") && key.len() > 2) {
+        "id"
+    } else {
+        "value"
+    }
+}
+
+/// Plausible Unix time in seconds or milliseconds (2001–2286).
+fn is_epoch(n: f64) -> bool {
+    (1e9..1e10).contains(&n) || (1e12..1e13).contains(&n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSES: &str = r#"[
+        "{\"id\":\"0b6f3c2e-1d2a-4c5b-9e7f-111111111111\",\"status\":\"ok\",\"createdAt\":\"2024-01-01T10:00:00Z\",\"count\":3,\"items\":[{\"userId\":1}]}",
+        {"body": "{\"id\":\"0b6f3c2e-1d2a-4c5b-9e7f-222222222222\",\"status\":\"ok\",\"createdAt\":\"2024-01-01T10:00:05Z\",\"count\":3,\"items\":[{\"userId\":1}]}"},
+        {"id":"0b6f3c2e-1d2a-4c5b-9e7f-333333333333","status":"ok","createdAt":"2024-01-01T10:00:09Z","count":4,"items":[{"userId":1}]}
+    ]"#;
+
+    #[test]
+    fn test_path_stats() {
+        let stats: Value = serde_json::from_str(&path_stats(RESPONSES, "count")).unwrap();
+        assert_eq!(stats["samples"], 3);
+        assert_eq!(stats["present"], 3);
+        assert_eq!(stats["distinct"], 2);
+        assert_eq!(stats["changeRate"], 0.5);
+        assert_eq!(stats["classification"], "varying");
+        assert_eq!(stats["min"], 3.0);
+        assert_eq!(stats["max"], 4.0);
+        assert_eq!(stats["topValues"][0]["value"], 3);
+        assert_eq!(stats["topValues"][0]["count"], 2);
+        assert_eq!(stats["types"]["number"], 3);
+
+        let missing: Value = serde_json::from_str(&path_stats(RESPONSES, "nope")).unwrap();
+        assert_eq!(missing["classification"], "absent");
+        assert_eq!(missing["missing"], 3);
+    }
+
+    #[test]
+    fn test_detect_flaky_fields() {
+        let out: Value = serde_json::from_str(&detect_flaky_fields(RESPONSES)).unwrap();
+        assert_eq!(out["samples"], 3);
+        assert_eq!(
+            out["suggestedIgnore"],
+            serde_json::json!(["id", "createdAt"])
+        );
+        let field = |p: &str| {
+            out["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|f| f["path"] == p)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(field("id")["kind"], "uuid");
+        assert_eq!(field("createdAt")["kind"], "timestamp");
+        assert_eq!(field("status")["classification"], "stable");
+        assert_eq!(field("items[0].userId")["classification"], "stable");
+        assert_eq!(field("items[0].userId")["kind"], "id");
+    }
+
+    #[test]
+    fn test_detect_flaky_fields_epoch_and_invalid() {
+        let out: Value = serde_json::from_str(&detect_flaky_fields(
+            r#"[{"updated_at":1700000000},{"updated_at":1700000100}]"#,
+        ))
+        .unwrap();
+        assert_eq!(out["fields"][0]["kind"], "timestamp");
+        assert_eq!(out["fields"][0]["classification"], "volatile");
+
+        let empty: Value = serde_json::from_str(&detect_flaky_fields("nope")).unwrap();
+        assert_eq!(empty["samples"], 0);
+    }
+
+    #[test]
+    fn test_detect_schema_drift() {
+        let old = r#"[
+            {"id": 1, "name": "Ada", "email": null, "tags": ["a"], "meta": {"v": 1}},
+            {"id": 2, "name": "Bob", "email": "b@x.io", "tags": [], "meta": {"v": 1}}
+        ]"#;
+        let new = r#"[
+            {"id": "3", "name": "Cy", "email": "c@x.io", "tags": [1], "avatar": {"url": "u", "size": 2}},
+            {"id": "4", "name": null, "email": "d@x.io", "tags": [2], "avatar": null}
+        ]"#;
+        let out: Value = serde_json::from_str(&detect_schema_drift(old, new)).unwrap();
+        assert_eq!(out["drifted"], true);
+        let changes: Vec<(String, String)> = out["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["path"].as_str().unwrap().into(),
+                    c["change"].as_str().unwrap().into(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("meta", "removed"),
+            ("id", "retyped"),
+            ("name", "nullability"),
+            ("email", "nullability"),
+            ("tags[]", "retyped"),
+            ("avatar", "added"),
+        ];
+        assert_eq!(
+            changes,
+            expected.map(|(p, c)| (p.to_string(), c.to_string()))
+        );
+        assert_eq!(out["changes"][1]["before"], serde_json::json!(["number"]));
+        assert_eq!(out["changes"][1]["after"], serde_json::json!(["string"]));
+        assert_eq!(out["changes"][3]["after"], false);
+        assert_eq!(
+            out["changes"][5]["after"],
+            serde_json::json!({"types": ["object"], "nullable": true})
+        );
+
+        let same: Value = serde_json::from_str(&detect_schema_drift(old, old)).unwrap();
+        assert_eq!(same["drifted"], false);
+        assert_eq!(same["oldSamples"], 2);
+    }
+}
@@ -0,0 +1,438 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::json_scan;
+use crate::model::Request;
+use crate::{find_variables, split_url};
+
+#[derive(Serialize)]
+struct Issue {
+    code: &'static str,
+    field: String,
+    message: String,
+}
+
+#[derive(Serialize, Default)]
+struct Validation {
+    valid: bool,
+    errors: Vec<Issue>,
+    warnings: Vec<Issue>,
+}
+
+impl Validation {
+    fn error(&mut self, code: &'static str, field: &str, message: String) {
+        self.errors.push(Issue {
+            code,
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn warn(&mut self, code: &'static str, field: &str, message: String) {
+        self.warnings.push(Issue {
+            code,
+            field: field.to_string(),
+            message,
+        });
+    }
+}
+
+/// Validate a fully-resolved request right before it is sent.
+/// request_json: request object {method, url, headers, queryParams, body, bodyType, formData, auth}.
+/// Returns JSON {valid, errors: [{code, field, message}], warnings: [...]}.
+/// Errors should block sending; warnings are advisory.
+#[wasm_bindgen]
+pub fn validate_request(request_json: &str) -> String {
+    let request: Request = match serde_json::from_str(request_json) {
+        Ok(r) => r,
+        Err(e) => {
+            let mut v = Validation::default();
+            v.error("invalid_request", "", format!("Invalid request: {}", e));
+            return serde_json::to_string(&v).unwrap_or_else(|_| "{}".to_string());
+        }
+    };
+
+    let mut v = Validation::default();
+    check_url(&request, &mut v);
+    check_headers(&request, &mut v);
+    check_body(&request, &mut v);
+    check_auth(&request, &mut v);
+    check_unresolved(&request, &mut v);
+
+    v.valid = v.errors.is_empty();
+    serde_json::to_string(&v).unwrap_or_else(|_| "{}".to_string())
+}
+
+fn check_url(request: &Request, v: &mut Validation) {
+    let url = request.url.trim();
+    if url.is_empty() {
+        v.error("empty_url", "url", "URL is empty".to_string());
+        return;
+    }
+    if crate::has_variables(url) {
+        // Reported by check_unresolved; structure can't be judged yet.
+        return;
+    }
+    let Some(scheme_end) = url.find("://") else {
+        v.error(
+            "malformed_url",
+            "url",
+            format!("URL \"{}\" is missing a scheme (http:// or https://)", url),
+        );
+        return;
+    };
+    let scheme = url[..scheme_end].to_lowercase();
+    if scheme != "http" && scheme != "https" {
+        v.error(
+            "unsupported_scheme",
+            "url",
+            format!("Unsupported URL scheme \"{}\"", scheme),
+        );
+    }
+    let (origin, path, _) = split_url(url);
+    let authority = &origin[scheme_end + 3..];
+    let host = authority.rsplit('@').next().unwrap_or("");
+    let host_only = if host.starts_with('[') {
+        host.split(']').next().unwrap_or("").trim_start_matches('[')
+    } else {
+        host.split(':').next().unwrap_or("")
+    };
+    if host_only.is_empty() {
+        v.error("malformed_url", "url", "URL has no host".to_string());
+    }
+    if let Some(port) = host.strip_prefix(&format!("{}:", host_only))
+        && port.parse::<u16>().is_err()
+    {
+        v.error("malformed_url", "url", format!("Invalid port \"{}\"", port));
+    }
+    if url.chars().any(|c| c.is_whitespace()) {
+        v.error(
+            "malformed_url",
+            "url",
+            "URL contains unencoded whitespace".to_string(),
+        );
+    }
+    if path.contains("//") {
+        v.warn(
+            "double_slash",
+            "url",
+            "URL path contains an empty segment (\"//\")".to_string(),
+        );
+    }
+}
+
+/// RFC 7230 token characters.
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+fn check_headers(request: &Request, v: &mut Validation) {
+    let mut seen: Vec<(String, String)> = Vec::new();
+    for h in request.headers.iter().filter(|h| h.enabled) {
+        let name = h.key.trim();
+        if name.is_empty() {
+            if !h.value.is_empty() {
+                v.warn(
+                    "empty_header_name",
+                    "headers",
+                    format!(
+                        "Header value \"{}\" has no name and will be ignored",
+                        h.value
+                    ),
+                );
+            }
+            continue;
+        }
+        if !name.chars().all(is_token_char) {
+            v.error(
+                "invalid_header_name",
+                "headers",
+                format!("Header name \"{}\" contains invalid characters", name),
+            );
+        }
+        if h.value.contains('\r') || h.value.contains('\n') || h.value.contains('\0') {
+            v.error(
+                "invalid_header_value",
+                "headers",
+                format!("Header \"{}\" value contains control characters", name),
+            );
+        } else if !h.value.is_ascii() {
+            v.warn(
+                "non_ascii_header_value",
+                "headers",
+                format!("Header \"{}\" value contains non-ASCII characters", name),
+            );
+        }
+        let lower = name.to_lowercase();
+        if let Some((_, prev)) = seen.iter().find(|(n, _)| *n == lower)
+            && prev != &h.value
+            && (lower == "content-type" || lower == "authorization" || lower == "host")
+        {
+            v.warn(
+                "duplicate_header",
+                "headers",
+                format!(
+                    "Header \"{}\" is set more than once with different values",
+                    name
+                ),
+            );
+        }
+        seen.push((lower, h.value.clone()));
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|h| h.enabled && h.key.trim().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn has_body(request: &Request) -> bool {
+    match request.body_type.as_str() {
+        "none" => false,
+        "form-data" => request
+            .form_data
+            .iter()
+            .any(|f| f.enabled && !f.key.trim().is_empty()),
+        _ => !request.body.trim().is_empty(),
+    }
+}
+
+fn check_body(request: &Request, v: &mut Validation) {
+    if !has_body(request) {
+        return;
+    }
+    let method = request.method.to_uppercase();
+    if method == "GET" || method == "HEAD" {
+        v.warn(
+            "body_with_get",
+            "body",
+            format!(
+                "{} requests with a body are ignored or rejected by many servers",
+                method
+            ),
+        );
+    }
+    // form-data bodies get their Content-Type from the transport.
+    if request.body_type != "form-data" && header(request, "content-type").is_none() {
+        v.warn(
+            "missing_content_type",
+            "headers",
+            "Request has a body but no Content-Type header".to_string(),
+        );
+    }
+    if request.body_type == "json" && !crate::has_variables(&request.body) {
+        if let Some(ct) = header(request, "content-type")
+            && !ct.to_lowercase().contains("json")
+        {
+            v.warn(
+                "content_type_mismatch",
+                "headers",
+                format!("Body type is JSON but Content-Type is \"{}\"", ct),
+            );
+        }
+        if serde_json::from_str::<serde_json::Value>(&request.body).is_err() {
+            v.warn(
+                "invalid_json_body",
+                "body",
+                "Body type is JSON but the body is not valid JSON".to_string(),
+            );
+        } else if let Ok(nodes) = json_scan::index(&request.body) {
+            for d in json_scan::duplicate_keys(&nodes) {
+                v.warn(
+                    "duplicate_json_key",
+                    "body",
+                    format!(
+                        "Key \"{}\" is repeated at line {}, column {}; only the last value is kept",
+                        d.node.path, d.node.position.line, d.node.position.column
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn check_auth(request: &Request, v: &mut Validation) {
+    let Some(auth) = &request.auth else {
+        return;
+    };
+    match auth.auth_type.as_str() {
+        "basic" | "bearer" => {
+            if header(request, "authorization").is_some() {
+                v.warn(
+                    "conflicting_auth",
+                    "auth",
+                    format!(
+                        "{} auth is configured but an Authorization header is also set",
+                        auth.auth_type
+                    ),
+                );
+            }
+            if auth.auth_type == "bearer" && auth.token.as_deref().unwrap_or("").trim().is_empty() {
+                v.error(
+                    "missing_credentials",
+                    "auth",
+                    "Bearer token is empty".to_string(),
+                );
+            }
+            if auth.auth_type == "basic" && auth.username.as_deref().unwrap_or("").is_empty() {
+                v.warn(
+                    "missing_credentials",
+                    "auth",
+                    "Basic auth username is empty".to_string(),
+                );
+            }
+        }
+        "apikey" => {
+            let name = auth.api_key_name.as_deref().unwrap_or("").trim();
+            if name.is_empty() {
+                v.error(
+                    "missing_credentials",
+                    "auth",
+                    "API key name is empty".to_string(),
+                );
+                return;
+            }
+            let in_query = auth.api_key_location.as_deref() == Some("query");
+            let clash = if in_query {
+                request
+                    .query_params
+                    .iter()
+                    .any(|p| p.enabled && p.key.trim() == name)
+            } else {
+                header(request, name).is_some()
+            };
+            if clash {
+                v.warn(
+                    "conflicting_auth",
+                    "auth",
+                    format!(
+                        "API key \"{}\" is also set as a {}",
+                        name,
+                        if in_query {
+                            "query parameter"
+                        } else {
+                            "header"
+                        }
+                    ),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_unresolved(request: &Request, v: &mut Validation) {
+    let mut report = |field: &str, text: &str, blocking: bool| {
+        let vars: Vec<String> = serde_json::from_str(&find_variables(text)).unwrap_or_default();
+        for var in vars {
+            let message = format!("Unresolved variable {{{{{}}}}} in {}", var, field);
+            if blocking {
+                v.error("unresolved_variable", field, message);
+            } else {
+                v.warn("unresolved_variable", field, message);
+            }
+        }
+    };
+    // Unresolved variables in the body are warnings; everywhere else they block.
+    for (field, text) in request.templated_fields() {
+        report(field, &text, !matches!(field, "body" | "formData"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn validate(request: &str) -> Value {
+        serde_json::from_str(&validate_request(request)).unwrap()
+    }
+
+    fn codes(v: &Value, kind: &str) -> Vec<String> {
+        v[kind]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["code"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_request_clean() {
+        let v = validate(
+            r#"{"method":"POST","url":"https://api.example.com/users","bodyType":"json",
+                "headers":{"Content-Type":"application/json"},"body":"{\"a\":1}"}"#,
+        );
+        assert_eq!(v["valid"], true);
+        assert!(v["warnings"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_request_body_warnings() {
+        let v = validate(r#"{"method":"GET","url":"https://x.io/a","bodyType":"raw","body":"hi"}"#);
+        assert_eq!(v["valid"], true);
+        assert_eq!(
+            codes(&v, "warnings"),
+            vec!["body_with_get", "missing_content_type"]
+        );
+    }
+
+    #[test]
+    fn test_validate_request_duplicate_json_key() {
+        let v = validate(
+            r#"{"method":"POST","url":"https://x.io/a","bodyType":"json",
+                "headers":{"Content-Type":"application/json"},"body":"{\"a\":1,\"a\":2}"}"#,
+        );
+        assert_eq!(codes(&v, "warnings"), vec!["duplicate_json_key"]);
+        assert!(
+            v["warnings"][0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("line 1, column 8")
+        );
+
+        let body = format!("{}{}", "[".repeat(5000), "]".repeat(5000));
+        let v = validate(
+            &serde_json::json!({"method": "POST", "url": "https://x.io/a", "bodyType": "json",
+                "headers": {"Content-Type": "application/json"}, "body": body})
+            .to_string(),
+        );
+        assert_eq!(codes(&v, "warnings"), vec!["invalid_json_body"]);
+    }
+
+    #[test]
+    fn test_validate_request_blocking_errors() {
+        let v = validate(
+            r#"{"method":"GET","url":"ftp//bad url","headers":[{"key":"Bad Name","value":"x"},{"key":"X-A","value":"a\r\nb"}]}"#,
+        );
+        assert_eq!(v["valid"], false);
+        assert_eq!(
+            codes(&v, "errors"),
+            vec![
+                "malformed_url",
+                "invalid_header_name",
+                "invalid_header_value"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_request_unresolved_and_auth() {
+        let v = validate(
+            r#"{"method":"POST","url":"{{baseUrl}}/users","headers":{"Authorization":"Bearer x","Content-Type":"text/plain"},
+                "auth":{"type":"bearer","token":"abc"},"body":"{{payload}}"}"#,
+        );
+        assert_eq!(v["valid"], false);
+        assert_eq!(
+            v["errors"][0]["message"],
+            "Unresolved variable {{baseUrl}} in url"
+        );
+        assert_eq!(
+            codes(&v, "warnings"),
+            vec!["conflicting_auth", "unresolved_variable"]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use wasm_bindgen::prelude::*;
+
+/// Reports `{done, total, stage}` to an optional JS callback, throttled to
+/// roughly one call per percent so callbacks never dominate the work itself.
+pub(crate) struct Progress {
+    callback: Option<js_sys::Function>,
+    stage: &'static str,
+    total: usize,
+    last_reported: Option<usize>,
+}
+
+impl Progress {
+    pub(crate) fn new(
+        callback: Option<js_sys::Function>,
+        stage: &'static str,
+        total: usize,
+    ) -> Self {
+        Progress {
+            callback,
+            stage,
+            total,
+            last_reported: None,
+        }
+    }
+
+    /// Start a new stage, resetting the counters.
+    pub(crate) fn stage(&mut self, stage: &'static str, total: usize) {
+        self.stage = stage;
+        self.total = total;
+        self.last_reported = None;
+        self.tick(0);
+    }
+
+    pub(crate) fn tick(&mut self, done: usize) {
+        if self.callback.is_none() {
+            return;
+        }
+        let step = (self.total / 100).max(1);
+        let due = match self.last_reported {
+            None => true,
+            Some(last) => done >= self.total || done >= last + step,
+        };
+        if !due || self.last_reported == Some(done) {
+            return;
+        }
+        self.last_reported = Some(done);
+        self.report(done);
+    }
+
+    fn report(&self, done: usize) {
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"done".into(), &JsValue::from(done as f64));
+        let _ = js_sys::Reflect::set(&event, &"total".into(), &JsValue::from(self.total as f64));
+        let _ = js_sys::Reflect::set(&event, &"stage".into(), &JsValue::from_str(self.stage));
+        // A throwing callback must not abort the operation it observes.
+        let _ = callback.call1(&JsValue::NULL, &event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_without_callback_is_noop() {
+        let mut progress = Progress::new(None, "work", 10);
+        for i in 0..=10 {
+            progress.tick(i);
+        }
+        progress.stage("next", 5);
+        assert_eq!(progress.last_reported, None);
+    }
+}
@@ -0,0 +1,797 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, base64_encode};
+
+/// Message and enum definitions used to interpret text-format payloads.
+/// {"messages": {"pkg.User": {"fields": [{name, type, repeated, keyType, valueType, jsonName}]}},
+///  "enums": {"pkg.Status": {"ACTIVE": 1}}}
+/// `type` is a scalar type ("int32", "string", ...), "map", or the name of a
+/// message/enum in the registry. Names may be fully qualified or short.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Registry {
+    messages: HashMap<String, MessageDef>,
+    enums: HashMap<String, HashMap<String, i64>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct MessageDef {
+    fields: Vec<FieldDef>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+struct FieldDef {
+    name: String,
+    #[serde(rename = "type")]
+    field_type: String,
+    repeated: bool,
+    key_type: String,
+    value_type: String,
+    json_name: Option<String>,
+}
+
+impl FieldDef {
+    /// proto3 JSON name: lowerCamelCase unless overridden.
+    fn json_name(&self) -> String {
+        if let Some(n) = &self.json_name {
+            return n.clone();
+        }
+        let mut out = String::new();
+        let mut upper = false;
+        for c in self.name.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                out.extend(c.to_uppercase());
+                upper = false;
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+enum Kind<'a> {
+    Scalar(&'a str),
+    Enum(&'a HashMap<String, i64>),
+    Message(&'a MessageDef),
+}
+
+impl Registry {
+    fn find<'a, T>(map: &'a HashMap<String, T>, name: &str) -> Option<&'a T> {
+        let name = name.trim_start_matches('.');
+        map.get(name).or_else(|| {
+            let suffix = format!(".{}", name);
+            map.iter()
+                .find(|(k, _)| k.ends_with(&suffix))
+                .map(|(_, v)| v)
+        })
+    }
+
+    fn kind(&self, type_name: &str) -> Result<Kind<'_>, String> {
+        const SCALARS: [&str; 15] = [
+            "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+            "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+        ];
+        if let Some(s) = SCALARS.iter().find(|s| **s == type_name) {
+            return Ok(Kind::Scalar(s));
+        }
+        if let Some(m) = Registry::find(&self.messages, type_name) {
+            return Ok(Kind::Message(m));
+        }
+        if let Some(e) = Registry::find(&self.enums, type_name) {
+            return Ok(Kind::Enum(e));
+        }
+        Err(format!("Unknown type \"{}\"", type_name))
+    }
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Convert a protobuf text-format message to its proto3 JSON form.
+/// registry_json: see `Registry`; type_name: the message type of `text`.
+/// Returns the JSON object, or {error} with a line number on parse failure.
+#[wasm_bindgen]
+pub fn proto_text_to_json(registry_json: &str, type_name: &str, text: &str) -> String {
+    let registry: Registry = match serde_json::from_str(registry_json) {
+        Ok(r) => r,
+        Err(e) => return error_json(&format!("Invalid registry: {}", e)),
+    };
+    let def = match registry.kind(type_name) {
+        Ok(Kind::Message(m)) => m,
+        Ok(_) => return error_json(&format!("\"{}\" is not a message type", type_name)),
+        Err(e) => return error_json(&e),
+    };
+    let mut parser = TextParser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+        registry: &registry,
+    };
+    match parser.message(def, None) {
+        Ok(map) => Value::Object(map).to_string(),
+        Err(e) => error_json(&format!("Line {}: {}", parser.line, e)),
+    }
+}
+
+/// Convert a proto3 JSON message to protobuf text format (two-space indent).
+/// Returns the text, or {error} if the JSON does not fit the type.
+#[wasm_bindgen]
+pub fn json_to_proto_text(registry_json: &str, type_name: &str, json: &str) -> String {
+    let registry: Registry = match serde_json::from_str(registry_json) {
+        Ok(r) => r,
+        Err(e) => return error_json(&format!("Invalid registry: {}", e)),
+    };
+    let value: Value = match serde_json::from_str(json) {
+        Ok(v) => v,
+        Err(e) => return error_json(&format!("Invalid JSON: {}", e)),
+    };
+    let def = match registry.kind(type_name) {
+        Ok(Kind::Message(m)) => m,
+        Ok(_) => return error_json(&format!("\"{}\" is not a message type", type_name)),
+        Err(e) => return error_json(&e),
+    };
+    let mut out = String::new();
+    match write_message(&registry, def, &value, 0, &mut out) {
+        Ok(()) => out,
+        Err(e) => error_json(&e),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Text → JSON
+
+enum Token {
+    Ident(String),
+    Str(Vec<u8>),
+    Num(String),
+}
+
+struct TextParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    registry: &'a Registry,
+}
+
+impl TextParser<'_> {
+    fn skip_ws(&mut self) {
+        while let Some(&c) = self.chars.get(self.pos) {
+            if c == '#' {
+                while self.chars.get(self.pos).is_some_and(|c| *c != '\n') {
+                    self.pos += 1;
+                }
+            } else if c.is_whitespace() {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        self.skip_ws();
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(match self.chars.get(self.pos) {
+                Some(c) => format!("Expected a field name but found '{}'", c),
+                None => "Expected a field name but reached end of input".to_string(),
+            });
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn scalar_token(&mut self) -> Result<Token, String> {
+        match self.peek() {
+            Some('"' | '\'') => {
+                // Adjacent string literals are concatenated.
+                let mut bytes = Vec::new();
+                while let Some(q @ ('"' | '\'')) = self.peek() {
+                    self.pos += 1;
+                    self.string_body(q, &mut bytes)?;
+                }
+                Ok(Token::Str(bytes))
+            }
+            Some(c) if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+                {
+                    // A sign only continues a number right after an exponent.
+                    let c = self.chars[self.pos];
+                    if matches!(c, '+' | '-') && !matches!(self.chars[self.pos - 1], 'e' | 'E') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(Token::Num(self.chars[start..self.pos].iter().collect()))
+            }
+            Some(_) => Ok(Token::Ident(self.ident()?)),
+            None => Err("Expected a value but reached end of input".to_string()),
+        }
+    }
+
+    fn string_body(&mut self, quote: char, out: &mut Vec<u8>) -> Result<(), String> {
+        loop {
+            let c = *self.chars.get(self.pos).ok_or("Unterminated string")?;
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(()),
+                '\n' => return Err("Newline in string".to_string()),
+                '\\' => {
+                    let e = *self.chars.get(self.pos).ok_or("Unterminated string")?;
+                    self.pos += 1;
+                    match e {
+                        'n' => out.push(b'\n'),
+                        'r' => out.push(b'\r'),
+                        't' => out.push(b'\t'),
+                        'a' => out.push(0x07),
+                        'b' => out.push(0x08),
+                        'f' => out.push(0x0C),
+                        'v' => out.push(0x0B),
+                        '0'..='7' => {
+                            let mut n = e.to_digit(8).unwrap_or(0);
+                            for _ in 0..2 {
+                                match self.chars.get(self.pos).and_then(|c| c.to_digit(8)) {
+                                    Some(d) => {
+                                        n = n * 8 + d;
+                                        self.pos += 1;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            out.push(n as u8);
+                        }
+                        'x' => {
+                            let mut n = 0;
+                            let mut digits = 0;
+                            while digits < 2 {
+                                match self.chars.get(self.pos).and_then(|c| c.to_digit(16)) {
+                                    Some(d) => {
+                                        n = n * 16 + d;
+                                        self.pos += 1;
+                                        digits += 1;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            if digits == 0 {
+                                return Err("Invalid \\x escape".to_string());
+                            }
+                            out.push(n as u8);
+                        }
+                        'u' | 'U' => {
+                            let len = if e == 'u' { 4 } else { 8 };
+                            let hex: String = self
+                                .chars
+                                .get(self.pos..self.pos + len)
+                                .ok_or("Invalid unicode escape")?
+                                .iter()
+                                .collect();
+                            self.pos += len;
+                            let ch = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or("Invalid unicode escape")?;
+                            out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                        }
+                        other => out.extend_from_slice(other.encode_utf8(&mut [0; 4]).as_bytes()),
+                    }
+                }
+                c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+
+    fn message(
+        &mut self,
+        def: &MessageDef,
+        close: Option<char>,
+    ) -> Result<Map<String, Value>, String> {
+        let mut map = Map::new();
+        loop {
+            match (self.peek(), close) {
+                (None, None) => return Ok(map),
+                (None, Some(c)) => return Err(format!("Missing closing '{}'", c)),
+                (Some(c), Some(end)) if c == end => {
+                    self.pos += 1;
+                    return Ok(map);
+                }
+                _ => {}
+            }
+            if self.peek() == Some('[') {
+                return Err("Extensions and Any expansions are not supported".to_string());
+            }
+            let name = self.ident()?;
+            let field = def
+                .fields
+                .iter()
+                .find(|f| f.name == name || f.json_name() == name)
+                .ok_or_else(|| format!("Unknown field \"{}\"", name))?
+                .clone();
+            let colon = self.eat(':');
+            self.field_values(&field, colon, &mut map)?;
+            // Optional separators.
+            if !self.eat(',') {
+                self.eat(';');
+            }
+        }
+    }
+
+    fn field_values(
+        &mut self,
+        field: &FieldDef,
+        colon: bool,
+        map: &mut Map<String, Value>,
+    ) -> Result<(), String> {
+        let key = field.json_name();
+        let mut values = Vec::new();
+        if self.eat('[') {
+            if !self.eat(']') {
+                loop {
+                    values.push(self.field_value(field, true)?);
+                    if self.eat(']') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return Err("Expected ',' or ']' in list".to_string());
+                    }
+                }
+            }
+        } else {
+            values.push(self.field_value(field, colon)?);
+        }
+
+        if field.field_type == "map" {
+            let entry = map.entry(key).or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(m) = entry {
+                for v in values {
+                    if let Value::Array(pair) = v {
+                        let k = match &pair[0] {
+                            Value::String(s) => s.clone(),
+                            Value::Null => String::new(),
+                            other => other.to_string(),
+                        };
+                        m.insert(k, pair[1].clone());
+                    }
+                }
+            }
+        } else if field.repeated {
+            let entry = map.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(items) = entry {
+                items.extend(values);
+            }
+        } else if let Some(v) = values.pop() {
+            map.insert(key, v);
+        }
+        Ok(())
+    }
+
+    fn field_value(&mut self, field: &FieldDef, colon: bool) -> Result<Value, String> {
+        if field.field_type == "map" {
+            let close = self.open_brace()?;
+            let entry = MessageDef {
+                fields: vec![
+                    FieldDef {
+                        name: "key".to_string(),
+                        field_type: field.key_type.clone(),
+                        ..FieldDef::default()
+                    },
+                    FieldDef {
+                        name: "value".to_string(),
+                        field_type: field.value_type.clone(),
+                        ..FieldDef::default()
+                    },
+                ],
+            };
+            let mut m = self.message(&entry, Some(close))?;
+            let default_value = match self.registry.kind(&field.value_type)? {
+                Kind::Message(_) => Value::Object(Map::new()),
+                _ => Value::Null,
+            };
+            return Ok(Value::Array(vec![
+                m.remove("key").unwrap_or(Value::Null),
+                m.remove("value").unwrap_or(default_value),
+            ]));
+        }
+        match self.registry.kind(&field.field_type)? {
+            Kind::Message(def) => {
+                let close = self.open_brace()?;
+                Ok(Value::Object(self.message(def, Some(close))?))
+            }
+            Kind::Enum(values) => {
+                if !colon {
+                    return Err(format!("Expected ':' after \"{}\"", field.name));
+                }
+                match self.scalar_token()? {
+                    Token::Ident(name) if values.contains_key(&name) => Ok(Value::from(name)),
+                    Token::Num(n) => {
+                        let n = parse_int(&n)?;
+                        Ok(values
+                            .iter()
+                            .find(|(_, v)| i128::from(**v) == n)
+                            .map(|(k, _)| Value::from(k.clone()))
+                            .unwrap_or(Value::from(n as i64)))
+                    }
+                    Token::Ident(name) => Err(format!("Unknown enum value \"{}\"", name)),
+                    Token::Str(_) => Err("Enum values are not quoted".to_string()),
+                }
+            }
+            Kind::Scalar(t) => {
+                if !colon {
+                    return Err(format!("Expected ':' after \"{}\"", field.name));
+                }
+                let token = self.scalar_token()?;
+                scalar_to_json(t, token)
+            }
+        }
+    }
+
+    fn open_brace(&mut self) -> Result<char, String> {
+        if self.eat('{') {
+            Ok('}')
+        } else if self.eat('<') {
+            Ok('>')
+        } else {
+            Err("Expected '{' to start a message".to_string())
+        }
+    }
+}
+
+fn parse_int(s: &str) -> Result<i128, String> {
+    let (neg, digits) = match s.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, s),
+    };
+    let n = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i128::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i128::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse::<i128>()
+    }
+    .map_err(|_| format!("Invalid integer \"{}\"", s))?;
+    Ok(if neg { -n } else { n })
+}
+
+fn scalar_to_json(t: &str, token: Token) -> Result<Value, String> {
+    let text = |token: &Token| match token {
+        Token::Ident(s) | Token::Num(s) => s.clone(),
+        Token::Str(b) => String::from_utf8_lossy(b).into_owned(),
+    };
+    match (t, token) {
+        ("string", Token::Str(b)) => String::from_utf8(b)
+            .map(Value::from)
+            .map_err(|_| "String field is not valid UTF-8".to_string()),
+        ("bytes", Token::Str(b)) => Ok(Value::from(base64_encode(&b))),
+        ("string" | "bytes", other) => {
+            Err(format!("Expected a quoted string, found {}", text(&other)))
+        }
+        ("bool", Token::Ident(s) | Token::Num(s)) => match s.as_str() {
+            "true" | "True" | "t" | "1" => Ok(Value::Bool(true)),
+            "false" | "False" | "f" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("Invalid bool \"{}\"", s)),
+        },
+        ("float" | "double", Token::Ident(s) | Token::Num(s)) => {
+            let lower = s.to_lowercase();
+            let (neg, body) = match lower.strip_prefix('-') {
+                Some(b) => (true, b.to_string()),
+                None => (false, lower.clone()),
+            };
+            match body.as_str() {
+                "inf" | "infinity" => Ok(Value::from(if neg { "-Infinity" } else { "Infinity" })),
+                "nan" => Ok(Value::from("NaN")),
+                _ => {
+                    let trimmed = lower.trim_end_matches('f');
+                    let n: f64 = trimmed
+                        .parse()
+                        .or_else(|_| parse_int(trimmed).map(|n| n as f64))
+                        .map_err(|_| format!("Invalid number \"{}\"", s))?;
+                    serde_json::Number::from_f64(n)
+                        .map(Value::Number)
+                        .ok_or_else(|| format!("Invalid number \"{}\"", s))
+                }
+            }
+        }
+        (t, Token::Num(s)) => {
+            let n = parse_int(&s)?;
+            let (min, max): (i128, i128) = match t {
+                "int32" | "sint32" | "sfixed32" => (i32::MIN.into(), i32::MAX.into()),
+                "uint32" | "fixed32" => (0, u32::MAX.into()),
+                "int64" | "sint64" | "sfixed64" => (i64::MIN.into(), i64::MAX.into()),
+                _ => (0, u64::MAX.into()),
+            };
+            if n < min || n > max {
+                return Err(format!("{} is out of range for {}", s, t));
+            }
+            // proto3 JSON writes 64-bit integers as strings.
+            if t.ends_with("64") {
+                Ok(Value::from(n.to_string()))
+            } else {
+                Ok(Value::from(n as i64))
+            }
+        }
+        (t, other) => Err(format!("Invalid {} value {}", t, text(&other))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON → text
+
+fn write_message(
+    registry: &Registry,
+    def: &MessageDef,
+    value: &Value,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), String> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| format!("Expected a JSON object, found {}", value))?;
+    for key in obj.keys() {
+        if !def
+            .fields
+            .iter()
+            .any(|f| f.json_name() == *key || f.name == *key)
+        {
+            return Err(format!("Unknown field \"{}\"", key));
+        }
+    }
+    let indent = "  ".repeat(depth);
+    for field in &def.fields {
+        let Some(v) = obj.get(&field.json_name()).or_else(|| obj.get(&field.name)) else {
+            continue;
+        };
+        if v.is_null() {
+            continue;
+        }
+        if field.field_type == "map" {
+            let entries = v
+                .as_object()
+                .ok_or_else(|| format!("Field \"{}\" must be an object", field.name))?;
+            for (k, mv) in entries {
+                out.push_str(&format!("{}{} {{\n", indent, field.name));
+                let key = write_scalar_or_enum(registry, &field.key_type, &Value::from(k.clone()))?;
+                out.push_str(&format!("{}  key: {}\n", indent, key));
+                write_field(registry, "value", &field.value_type, mv, depth + 1, out)?;
+                out.push_str(&format!("{}}}\n", indent));
+            }
+        } else if field.repeated {
+            let items = v
+                .as_array()
+                .ok_or_else(|| format!("Field \"{}\" must be an array", field.name))?;
+            for item in items {
+                write_field(registry, &field.name, &field.field_type, item, depth, out)?;
+            }
+        } else {
+            write_field(registry, &field.name, &field.field_type, v, depth, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_field(
+    registry: &Registry,
+    name: &str,
+    type_name: &str,
+    value: &Value,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), String> {
+    let indent = "  ".repeat(depth);
+    if let Kind::Message(def) = registry.kind(type_name)? {
+        out.push_str(&format!("{}{} {{\n", indent, name));
+        write_message(registry, def, value, depth + 1, out)?;
+        out.push_str(&format!("{}}}\n", indent));
+    } else {
+        let text = write_scalar_or_enum(registry, type_name, value)?;
+        out.push_str(&format!("{}{}: {}\n", indent, name, text));
+    }
+    Ok(())
+}
+
+fn write_scalar_or_enum(
+    registry: &Registry,
+    type_name: &str,
+    value: &Value,
+) -> Result<String, String> {
+    let bad = || format!("Invalid {} value {}", type_name, value);
+    match registry.kind(type_name)? {
+        Kind::Message(_) => Err(bad()),
+        Kind::Enum(values) => match value {
+            Value::String(s) if values.contains_key(s) => Ok(s.clone()),
+            Value::Number(n) => {
+                let n = n.as_i64().ok_or_else(bad)?;
+                Ok(values
+                    .iter()
+                    .find(|(_, v)| **v == n)
+                    .map(|(k, _)| k.clone())
+                    .unwrap_or(n.to_string()))
+            }
+            _ => Err(bad()),
+        },
+        Kind::Scalar("string") => value.as_str().map(quote).ok_or_else(bad),
+        Kind::Scalar("bytes") => value
+            .as_str()
+            .and_then(base64_decode)
+            .map(|b| quote_bytes(&b))
+            .ok_or_else(bad),
+        Kind::Scalar("bool") => match value {
+            Value::Bool(b) => Ok(b.to_string()),
+            Value::String(s) if s == "true" || s == "false" => Ok(s.clone()),
+            _ => Err(bad()),
+        },
+        Kind::Scalar("float" | "double") => match value {
+            Value::Number(n) => Ok(n.to_string()),
+            Value::String(s) => match s.as_str() {
+                "NaN" => Ok("nan".to_string()),
+                "Infinity" => Ok("inf".to_string()),
+                "-Infinity" => Ok("-inf".to_string()),
+                other => other
+                    .parse::<f64>()
+                    .map(|_| other.to_string())
+                    .map_err(|_| bad()),
+            },
+            _ => Err(bad()),
+        },
+        Kind::Scalar(_) => match value {
+            // Integers may arrive as numbers or (for 64-bit) as strings.
+            Value::Number(n) if n.as_i64().is_some() || n.as_u64().is_some() => Ok(n.to_string()),
+            Value::String(s) if parse_int(s).is_ok() => Ok(s.clone()),
+            _ => Err(bad()),
+        },
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\{:03o}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn quote_bytes(bytes: &[u8]) -> String {
+    let mut out = String::from("\"");
+    for &b in bytes {
+        match b {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            b'\n' => out.push_str("\\n"),
+            0x20..=0x7E => out.push(b as char),
+            _ => out.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"{
+        "messages": {
+            "example.User": {"fields": [
+                {"name": "id", "type": "int64"},
+                {"name": "display_name", "type": "string"},
+                {"name": "age", "type": "int32"},
+                {"name": "score", "type": "double"},
+                {"name": "active", "type": "bool"},
+                {"name": "status", "type": "Status"},
+                {"name": "tags", "type": "string", "repeated": true},
+                {"name": "address", "type": "example.Address"},
+                {"name": "labels", "type": "map", "keyType": "string", "valueType": "int32"},
+                {"name": "avatar", "type": "bytes"}
+            ]},
+            "example.Address": {"fields": [{"name": "city", "type": "string"}]}
+        },
+        "enums": {"example.Status": {"ACTIVE": 1, "BANNED": 2}}
+    }"#;
+
+    const TEXT: &str = r#"
+        # a user
+        id: 9007199254740993
+        display_name: "Ann \"A\"" ' Lee'
+        age: 0x1F
+        score: 1.5f
+        active: t
+        status: BANNED
+        tags: "a"
+        tags: ["b", "c"]
+        address < city: "Oslo" >
+        labels { key: "x" value: 1 }
+        labels: { key: "y", value: 2 };
+        avatar: "\001\377"
+    "#;
+
+    #[test]
+    fn test_proto_text_to_json() {
+        let out: Value =
+            serde_json::from_str(&proto_text_to_json(REGISTRY, "example.User", TEXT)).unwrap();
+        assert_eq!(out["id"], "9007199254740993");
+        assert_eq!(out["displayName"], "Ann \"A\" Lee");
+        assert_eq!(out["age"], 31);
+        assert_eq!(out["score"], 1.5);
+        assert_eq!(out["active"], true);
+        assert_eq!(out["status"], "BANNED");
+        assert_eq!(out["tags"], serde_json::json!(["a", "b", "c"]));
+        assert_eq!(out["address"]["city"], "Oslo");
+        assert_eq!(out["labels"], serde_json::json!({"x": 1, "y": 2}));
+        assert_eq!(out["avatar"], "Af8=");
+    }
+
+    #[test]
+    fn test_proto_text_errors() {
+        let unknown = proto_text_to_json(REGISTRY, "example.User", "id: 1\nnope: 2");
+        assert!(unknown.contains("Line 2: Unknown field \\\"nope\\\""));
+        let range = proto_text_to_json(REGISTRY, "example.User", "age: 3000000000");
+        assert!(range.contains("out of range"));
+        assert!(proto_text_to_json(REGISTRY, "example.Nope", "").contains("Unknown type"));
+        assert!(
+            proto_text_to_json(REGISTRY, "example.User", "address { city: \"x\"")
+                .contains("Missing closing")
+        );
+    }
+
+    #[test]
+    fn test_json_to_proto_text_round_trip() {
+        let json = proto_text_to_json(REGISTRY, "example.User", TEXT);
+        let text = json_to_proto_text(REGISTRY, "User", &json);
+        assert!(text.starts_with("id: 9007199254740993\ndisplay_name: \"Ann \\\"A\\\" Lee\"\n"));
+        assert!(text.contains("tags: \"c\"\naddress {\n  city: \"Oslo\"\n}\n"));
+        assert!(text.contains("labels {\n  key: \"x\"\n  value: 1\n}\n"));
+        assert!(text.contains("avatar: \"\\001\\377\"\n"));
+        assert_eq!(proto_text_to_json(REGISTRY, "example.User", &text), json);
+
+        let bad = json_to_proto_text(REGISTRY, "example.User", r#"{"age":"old"}"#);
+        assert!(bad.contains("Invalid int32 value"));
+    }
+}
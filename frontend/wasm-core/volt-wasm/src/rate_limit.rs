@@ -0,0 +1,260 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::model::Headers;
+use crate::parse_http_date;
+
+/// Header prefixes for the limit/remaining/reset triple, most specific first:
+/// the IETF draft, the common X- form (GitHub, Twitter) and its hyphenated variant.
+const PREFIXES: &[&str] = &["ratelimit-", "x-ratelimit-", "x-rate-limit-"];
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RateLimit {
+    limit: Option<f64>,
+    remaining: Option<f64>,
+    used: Option<f64>,
+    /// When the quota resets, in ms since the epoch.
+    reset_at: Option<f64>,
+    /// From `Retry-After`, in ms.
+    retry_after_ms: Option<f64>,
+    /// How long to wait before the next request, in ms (0 when there is no need).
+    wait_ms: f64,
+    /// The quota is used up (remaining is 0) or the server asked to back off.
+    exhausted: bool,
+    /// False when the server said a retry will not help (`Stripe-Should-Retry: false`).
+    should_retry: bool,
+    /// Window length in seconds, from `RateLimit-Policy` or `X-RateLimit-Window`.
+    window_seconds: Option<f64>,
+    /// Which quota the headers describe (GitHub's `X-RateLimit-Resource`).
+    resource: Option<String>,
+    /// Header names the result was read from.
+    sources: Vec<String>,
+}
+
+/// Read rate-limit state from response headers.
+/// headers_json: JSON object of response headers (key → value or [values]).
+/// now_ms: reference time in ms; defaults to the current time.
+/// Understands `Retry-After` (seconds or HTTP-date), `X-RateLimit-*` and
+/// `X-Rate-Limit-*` (reset as epoch seconds/ms or seconds to go), the IETF draft
+/// `RateLimit-*`, `RateLimit` and `RateLimit-Policy` headers, GitHub's `-Used` and
+/// `-Resource`, OpenAI-style `-Reset-Requests: 6m0s` durations, Discord's
+/// `-Reset-After` and Stripe's `Stripe-Should-Retry`.
+/// Returns JSON {limit, remaining, used, resetAt, retryAfterMs, waitMs, exhausted,
+/// shouldRetry, windowSeconds, resource, sources}; unknown values are null.
+#[wasm_bindgen]
+pub fn parse_rate_limit(headers_json: &str, now_ms: Option<f64>) -> String {
+    let headers: Headers = serde_json::from_str(headers_json).unwrap_or_default();
+    // The first value wins when a header is repeated.
+    let mut by_name: IndexMap<String, &str> = IndexMap::new();
+    for (name, value) in headers.iter() {
+        by_name.entry(name.to_lowercase()).or_insert(value.trim());
+    }
+    let now = now_ms.unwrap_or_else(crate::now_ms);
+    serde_json::to_string(&read(&by_name, now)).unwrap_or_else(|_| "{}".to_string())
+}
+
+fn read(headers: &IndexMap<String, &str>, now: f64) -> RateLimit {
+    let mut out = RateLimit {
+        should_retry: true,
+        ..Default::default()
+    };
+    let mut sources = Vec::new();
+    let mut header = |name: &str| {
+        let value = headers.get(name).copied()?;
+        sources.push(name.to_string());
+        Some(value)
+    };
+
+    if let Some(value) = header("retry-after") {
+        out.retry_after_ms = match value.parse::<f64>() {
+            Ok(seconds) => Some(seconds.max(0.0) * 1000.0),
+            Err(_) => parse_http_date(value).map(|at| (at - now).max(0.0)),
+        };
+    }
+
+    let mut reset_ms = None;
+    for prefix in PREFIXES {
+        let field = |name: &str| format!("{}{}", prefix, name);
+        let number = |v: Option<&str>| v.and_then(|v| v.parse::<f64>().ok());
+        out.limit = out.limit.or(number(header(&field("limit"))));
+        out.remaining = out.remaining.or(number(header(&field("remaining"))));
+        out.used = out.used.or(number(header(&field("used"))));
+        if let Some(value) = header(&field("reset")) {
+            reset_ms = reset_ms.or(reset_time(value, now, *prefix == "ratelimit-"));
+        }
+        for name in ["reset-after", "reset-requests"] {
+            if let Some(value) = header(&field(name)) {
+                reset_ms = reset_ms.or(duration_ms(value).map(|ms| now + ms));
+            }
+        }
+        out.window_seconds = out.window_seconds.or(number(header(&field("window"))));
+        if out.resource.is_none() {
+            out.resource = header(&field("resource")).map(str::to_string);
+        }
+    }
+
+    // Structured draft headers: `RateLimit: "default";r=50;t=30` (or the older
+    // `limit=100, remaining=50, reset=30`) and `RateLimit-Policy: "default";q=100;w=60`.
+    if let Some(value) = header("ratelimit") {
+        let params = parameters(value);
+        let get = |names: &[&str]| names.iter().find_map(|n| params.get(*n).copied());
+        out.remaining = out.remaining.or(get(&["r", "remaining"]));
+        out.limit = out.limit.or(get(&["limit"]));
+        reset_ms = reset_ms.or(get(&["t", "reset"]).map(|s| now + s * 1000.0));
+    }
+    if let Some(value) = header("ratelimit-policy") {
+        let params = parameters(value);
+        out.limit = out.limit.or(params.get("q").copied()).or_else(|| {
+            // Draft-03 form: "100;w=60".
+            value.split(';').next()?.trim().parse().ok()
+        });
+        out.window_seconds = out.window_seconds.or(params.get("w").copied());
+    }
+    if header("stripe-should-retry").is_some_and(|v| v.eq_ignore_ascii_case("false")) {
+        out.should_retry = false;
+    }
+
+    out.sources = sources;
+
+    if out.used.is_none()
+        && let (Some(limit), Some(remaining)) = (out.limit, out.remaining)
+    {
+        out.used = Some((limit - remaining).max(0.0));
+    }
+    out.reset_at = reset_ms;
+    out.exhausted = out.remaining == Some(0.0) || out.retry_after_ms.is_some();
+    out.wait_ms = match (out.retry_after_ms, reset_ms) {
+        (Some(ms), _) => ms,
+        (None, Some(at)) if out.remaining == Some(0.0) => (at - now).max(0.0),
+        _ => 0.0,
+    };
+    out
+}
+
+/// A reset value: epoch seconds or ms when it looks like one, otherwise seconds
+/// from now. The IETF draft always uses seconds from now.
+fn reset_time(value: &str, now: f64, always_delta: bool) -> Option<f64> {
+    let n: f64 = match value.parse() {
+        Ok(n) => n,
+        Err(_) => return parse_http_date(value),
+    };
+    Some(if always_delta || n < 1e9 {
+        now + n * 1000.0
+    } else if n < 1e12 {
+        n * 1000.0
+    } else {
+        n
+    })
+}
+
+/// "1.5" (seconds), "20ms", "6m0s", "1h2m3.5s" → milliseconds.
+fn duration_ms(value: &str) -> Option<f64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(seconds * 1000.0);
+    }
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let n: f64 = number.parse().ok()?;
+        number.clear();
+        total += n * match c {
+            'h' => 3_600_000.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                1.0
+            }
+            'm' => 60_000.0,
+            's' => 1000.0,
+            _ => return None,
+        };
+    }
+    number.is_empty().then_some(total)
+}
+
+/// Numeric `key=value` parameters of a structured header, split on ';' and ','.
+fn parameters(value: &str) -> IndexMap<String, f64> {
+    value
+        .split([';', ','])
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((
+                k.trim().to_lowercase(),
+                v.trim().trim_matches('"').parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const NOW: f64 = 1_700_000_000_000.0;
+
+    fn parse(headers: &str) -> Value {
+        serde_json::from_str(&parse_rate_limit(headers, Some(NOW))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rate_limit_github_and_retry_after() {
+        let github = parse(
+            r#"{"X-RateLimit-Limit": "5000", "X-RateLimit-Remaining": "0",
+                "X-RateLimit-Used": "5000", "X-RateLimit-Reset": "1700000060",
+                "X-RateLimit-Resource": "core"}"#,
+        );
+        assert_eq!(github["limit"], 5000.0);
+        assert_eq!(github["remaining"], 0.0);
+        assert_eq!(github["resetAt"], 1_700_000_060_000.0);
+        assert_eq!(github["waitMs"], 60_000.0);
+        assert_eq!(github["exhausted"], true);
+        assert_eq!(github["resource"], "core");
+
+        let retry = parse(r#"{"Retry-After": "Tue, 14 Nov 2023 22:14:20 GMT"}"#);
+        assert_eq!(retry["retryAfterMs"], 60_000.0);
+        let retry = parse(r#"{"retry-after": "30", "Stripe-Should-Retry": "false"}"#);
+        assert_eq!(retry["waitMs"], 30_000.0);
+        assert_eq!(retry["shouldRetry"], false);
+        assert_eq!(
+            retry["sources"],
+            serde_json::json!(["retry-after", "stripe-should-retry"])
+        );
+
+        let none = parse("{}");
+        assert_eq!(none["waitMs"], 0.0);
+        assert_eq!(none["exhausted"], false);
+        assert!(none["limit"].is_null());
+    }
+
+    #[test]
+    fn test_parse_rate_limit_ietf_draft_and_durations() {
+        let draft = parse(
+            r#"{"RateLimit-Limit": "100", "RateLimit-Remaining": "40", "RateLimit-Reset": "30"}"#,
+        );
+        assert_eq!(draft["resetAt"], NOW + 30_000.0);
+        assert_eq!(draft["used"], 60.0);
+        assert_eq!(draft["waitMs"], 0.0);
+
+        let structured = parse(
+            r#"{"RateLimit": "\"default\";r=0;t=12", "RateLimit-Policy": "\"default\";q=100;w=60"}"#,
+        );
+        assert_eq!(structured["remaining"], 0.0);
+        assert_eq!(structured["limit"], 100.0);
+        assert_eq!(structured["windowSeconds"], 60.0);
+        assert_eq!(structured["waitMs"], 12_000.0);
+
+        let openai = parse(
+            r#"{"x-ratelimit-limit-requests": "60", "x-ratelimit-remaining": "0", "x-ratelimit-reset-requests": "1m30.5s"}"#,
+        );
+        assert_eq!(openai["waitMs"], 90_500.0);
+        assert_eq!(duration_ms("250ms"), Some(250.0));
+        assert_eq!(duration_ms("soon"), None);
+    }
+}
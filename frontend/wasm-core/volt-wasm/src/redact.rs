@@ -0,0 +1,203 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::har::{normalize, segments};
+use crate::json_paths::join;
+
+const MASK: &str = "[REDACTED]";
+
+/// Key-name patterns used when none are given (compared lower-cased, without
+/// '-'/'_', anywhere in the key).
+const DEFAULT_PATTERNS: &[&str] = &[
+    "authorization",
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "apikey",
+    "cookie",
+    "ssn",
+];
+
+struct Redactor {
+    patterns: Vec<String>,
+    /// Paths of the redacted values, in the order they were redacted.
+    redacted: Vec<String>,
+}
+
+impl Redactor {
+    fn record(&mut self, path: String) {
+        if !self.redacted.contains(&path) {
+            self.redacted.push(path);
+        }
+    }
+
+    /// Mask the values at a path with `*` wildcards.
+    fn path(&mut self, value: &mut Value, segments: &[String], at: &str) {
+        let Some((first, rest)) = segments.split_first() else {
+            mask(value);
+            self.record(at.to_string());
+            return;
+        };
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    if first == "*" || first == key {
+                        self.path(item, rest, &join(at, key));
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    if first == "*" || first.parse() == Ok(i) {
+                        self.path(item, rest, &format!("{}[{}]", at, i));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mask the values of every key that matches a pattern.
+    fn keys(&mut self, value: &mut Value, at: &str) {
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    let name = normalize(key);
+                    let child = join(at, key);
+                    if self.patterns.iter().any(|p| name.contains(p.as_str())) {
+                        mask(item);
+                        self.record(child);
+                    } else {
+                        self.keys(item, &child);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    self.keys(item, &format!("{}[{}]", at, i));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Replace every value under `value` except null with the mask, keeping
+/// objects and arrays as they are.
+fn mask(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.values_mut().for_each(mask),
+        Value::Array(items) => items.iter_mut().for_each(mask),
+        Value::Null => {}
+        other => *other = Value::from(MASK),
+    }
+}
+
+/// Mask sensitive values in a JSON document before it is shared, e.g. in a
+/// session export. Values are replaced with "[REDACTED]" (inside objects and
+/// arrays, each value is, so the structure stays the same; null is kept).
+/// paths_json: JSON array of paths in the `a.b[0]` syntax of `json_extract`,
+/// with `*` for any key or index and an optional leading "$"
+/// ("$.users[*].email").
+/// patterns_json: JSON array of key names; any key containing one, ignoring
+/// case, '-' and '_', is masked wherever it appears ("token" covers
+/// "access_token" and "refreshToken"). When patterns_json is empty or not an
+/// array, the defaults are used: authorization, password, passwd, secret, token, apikey,
+/// cookie and ssn; pass [] to match paths only.
+/// Returns JSON {json, redacted: [path]} or {error} for invalid JSON.
+#[wasm_bindgen]
+pub fn json_redact(json_str: &str, paths_json: &str, patterns_json: &str) -> String {
+    let mut value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid JSON: {}", e) }).to_string();
+        }
+    };
+    let paths: Vec<String> = serde_json::from_str(paths_json).unwrap_or_default();
+    let patterns: Vec<String> = serde_json::from_str(patterns_json)
+        .unwrap_or_else(|_| DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect());
+    let mut redactor = Redactor {
+        patterns: patterns
+            .iter()
+            .map(|p| normalize(p))
+            .filter(|p| !p.is_empty())
+            .collect(),
+        redacted: Vec::new(),
+    };
+    for path in &paths {
+        redactor.path(&mut value, &segments(path), "");
+    }
+    redactor.keys(&mut value, "");
+    serde_json::json!({
+        "json": value.to_string(),
+        "redacted": redactor.redacted,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn redact(doc: Value, paths: &str, patterns: &str) -> (Value, Value) {
+        let out: Value =
+            serde_json::from_str(&json_redact(&doc.to_string(), paths, patterns)).unwrap();
+        let json = serde_json::from_str(out["json"].as_str().unwrap()).unwrap();
+        (json, out["redacted"].clone())
+    }
+
+    #[test]
+    fn test_json_redact_default_patterns() {
+        let doc = json!({
+            "user": {"name": "Ann", "Password": "hunter2", "ssn": 123456789, "email": "a@x.io"},
+            "headers": {"Authorization": "Bearer x", "X-Api-Key": ["k1", "k2"]},
+            "access_token": null,
+            "tokens": [{"refresh": "r"}],
+        });
+        let (json, redacted) = redact(doc, "", "");
+        assert_eq!(
+            json,
+            json!({
+                "user": {"name": "Ann", "Password": "[REDACTED]", "ssn": "[REDACTED]", "email": "a@x.io"},
+                "headers": {"Authorization": "[REDACTED]", "X-Api-Key": ["[REDACTED]", "[REDACTED]"]},
+                "access_token": null,
+                "tokens": [{"refresh": "[REDACTED]"}],
+            })
+        );
+        assert_eq!(
+            redacted,
+            json!([
+                "user.Password",
+                "user.ssn",
+                "headers.Authorization",
+                "headers.X-Api-Key",
+                "access_token",
+                "tokens"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_json_redact_paths() {
+        let doc = json!({"users": [{"email": "a@x.io", "pin": 1}, {"email": "b@x.io", "pin": 2}], "card": "4111"});
+        let (json, redacted) = redact(
+            doc,
+            r#"["$.users[*].email", "users[1].pin", "missing.x"]"#,
+            r#"["card"]"#,
+        );
+        assert_eq!(
+            json,
+            json!({"users": [{"email": "[REDACTED]", "pin": 1}, {"email": "[REDACTED]", "pin": "[REDACTED]"}], "card": "[REDACTED]"})
+        );
+        assert_eq!(
+            redacted,
+            json!(["users[0].email", "users[1].email", "users[1].pin", "card"])
+        );
+        let (json, redacted) = redact(json!({"token": "t"}), "[]", "[]");
+        assert_eq!((json, redacted), (json!({"token": "t"}), json!([])));
+        let out: Value = serde_json::from_str(&json_redact("{", "", "")).unwrap();
+        assert!(out["error"].is_string());
+    }
+}
@@ -0,0 +1,249 @@
+use regex_lite::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Matches reported before the result is marked truncated.
+const MAX_MATCHES: usize = 1000;
+
+#[derive(Serialize)]
+struct Group {
+    index: usize,
+    name: Option<String>,
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct Match {
+    start: usize,
+    end: usize,
+    text: String,
+    /// Indexed capture groups (1-based); null where a group did not participate.
+    groups: Vec<Option<Group>>,
+    /// Named groups that participated, by name.
+    named: serde_json::Map<String, Value>,
+}
+
+/// Test a regex against sample text, as `matches` assertions and extraction rules would.
+/// flags: any of "i" (case-insensitive), "m" (multi-line), "s" (dot matches newline),
+/// "x" (ignore whitespace), "U" (swap greed), "g" (all matches instead of the first).
+/// Returns JSON {valid, error: {message, position}, groupNames, matches: [{start, end, text,
+/// groups: [{index, name, start, end, text} | null], named}], count, truncated}.
+/// Offsets are UTF-16 code units so they index JavaScript strings directly; `position`
+/// is the best-effort pattern offset of a compile error, or null.
+#[wasm_bindgen]
+pub fn regex_test(pattern: &str, flags: &str, text: &str) -> String {
+    if let Some(flag) = flags.chars().find(|c| !"imsxUg".contains(*c)) {
+        return invalid(format!("Unknown flag \"{}\"", flag), None);
+    }
+    let re = match compile(pattern, flags) {
+        Ok(re) => re,
+        Err(e) => {
+            let message = e.to_string();
+            let position = error_position(pattern, flags, &message);
+            return invalid(message, position);
+        }
+    };
+    let global = flags.contains('g');
+
+    let _timer = crate::logging::timer("regex_test");
+    let names: Vec<Option<&str>> = re.capture_names().skip(1).collect();
+    let mut offsets = Utf16Offsets::new(text);
+    let mut matches: Vec<Match> = Vec::new();
+    let mut truncated = false;
+    for caps in re.captures_iter(text) {
+        if matches.len() == MAX_MATCHES {
+            truncated = true;
+            break;
+        }
+        let whole = caps.get(0).expect("group 0 always participates");
+        let start = offsets.at(whole.start());
+        let mut named = serde_json::Map::new();
+        let groups = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let m = caps.get(i + 1)?;
+                if let Some(name) = name {
+                    named.insert(name.to_string(), Value::from(m.as_str()));
+                }
+                Some(Group {
+                    index: i + 1,
+                    name: name.map(str::to_string),
+                    start: offsets.at(m.start()),
+                    end: offsets.at(m.end()),
+                    text: m.as_str().to_string(),
+                })
+            })
+            .collect();
+        matches.push(Match {
+            start,
+            end: offsets.at(whole.end()),
+            text: whole.as_str().to_string(),
+            groups,
+            named,
+        });
+        if !global {
+            break;
+        }
+    }
+
+    serde_json::to_string(&serde_json::json!({
+        "valid": true,
+        "groupNames": names,
+        "count": matches.len(),
+        "matches": matches,
+        "truncated": truncated,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+fn compile(pattern: &str, flags: &str) -> Result<Regex, regex_lite::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .dot_matches_new_line(flags.contains('s'))
+        .ignore_whitespace(flags.contains('x'))
+        .swap_greed(flags.contains('U'))
+        .build()
+}
+
+/// User patterns kept compiled; the cache is emptied when it would grow past this.
+const MAX_CACHED: usize = 128;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Compile a user-supplied pattern (assertions, mock matchers, golden-file rules),
+/// reusing the compiled regex when the same pattern was seen before.
+pub(crate) fn cached_regex(pattern: &str) -> Result<Regex, regex_lite::Error> {
+    if let Some(re) = CACHE.with(|c| c.borrow().get(pattern).cloned()) {
+        return Ok(re);
+    }
+    let re = Regex::new(pattern)?;
+    CACHE.with(|c| {
+        let mut cache = c.borrow_mut();
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        cache.insert(pattern.to_string(), re.clone());
+    });
+    Ok(re)
+}
+
+fn invalid(message: String, position: Option<usize>) -> String {
+    serde_json::json!({
+        "valid": false,
+        "error": {"message": message, "position": position},
+        "groupNames": [],
+        "count": 0,
+        "matches": [],
+        "truncated": false,
+    })
+    .to_string()
+}
+
+/// regex-lite errors carry no location, so find the shortest pattern prefix that
+/// fails with the same message: for "(ab" that is the unclosed "(" and for "ab)"
+/// the stray ")". Returns the UTF-16 offset of the last character of that prefix.
+fn error_position(pattern: &str, flags: &str, message: &str) -> Option<usize> {
+    let mut utf16 = 0;
+    for (i, c) in pattern.char_indices() {
+        let prefix = &pattern[..i + c.len_utf8()];
+        if compile(prefix, flags).is_err_and(|e| e.to_string() == message) {
+            return Some(utf16);
+        }
+        utf16 += c.len_utf16();
+    }
+    None
+}
+
+/// Converts increasing byte offsets into UTF-16 offsets without rescanning.
+pub(crate) struct Utf16Offsets<'a> {
+    text: &'a str,
+    byte: usize,
+    utf16: usize,
+}
+
+impl<'a> Utf16Offsets<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Utf16Offsets {
+            text,
+            byte: 0,
+            utf16: 0,
+        }
+    }
+
+    pub(crate) fn at(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            self.byte = 0;
+            self.utf16 = 0;
+        }
+        self.utf16 += self.text[self.byte..byte].encode_utf16().count();
+        self.byte = byte;
+        self.utf16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(pattern: &str, flags: &str, text: &str) -> Value {
+        serde_json::from_str(&regex_test(pattern, flags, text)).unwrap()
+    }
+
+    #[test]
+    fn test_regex_test_groups() {
+        let result = run(r"(?P<key>\w+)=(\d+)?", "g", "a=1 b= c=3");
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["groupNames"], serde_json::json!(["key", null]));
+        assert_eq!(result["count"], 3);
+        let first = &result["matches"][0];
+        assert_eq!(first["start"], 0);
+        assert_eq!(first["end"], 3);
+        assert_eq!(first["named"], serde_json::json!({"key": "a"}));
+        assert_eq!(first["groups"][1]["text"], "1");
+        assert_eq!(first["groups"][1]["start"], 2);
+        assert_eq!(result["matches"][1]["groups"][1], Value::Null);
+
+        let single = run("B", "i", "abcb");
+        assert_eq!(single["count"], 1);
+        assert_eq!(single["matches"][0]["start"], 1);
+    }
+
+    #[test]
+    fn test_regex_test_utf16_offsets() {
+        let result = run("b+", "g", "😀a bb");
+        assert_eq!(result["matches"][0]["start"], 4);
+        assert_eq!(result["matches"][0]["end"], 6);
+    }
+
+    #[test]
+    fn test_regex_test_compile_errors() {
+        let unclosed = run("ab(cd", "", "");
+        assert_eq!(unclosed["valid"], false);
+        assert_eq!(unclosed["error"]["position"], 2);
+        assert_eq!(run("ab)", "", "")["error"]["position"], 2);
+        assert_eq!(run("x[a-", "", "")["error"]["position"], 3);
+        assert_eq!(run("a", "q", "")["error"]["message"], "Unknown flag \"q\"");
+    }
+
+    #[test]
+    fn test_cached_regex() {
+        let first = cached_regex("^item-[0-9]+$").unwrap();
+        let again = cached_regex("^item-[0-9]+$").unwrap();
+        assert!(first.is_match("item-7") && again.is_match("item-7"));
+        assert_eq!(CACHE.with(|c| c.borrow().len()), 1);
+        assert!(cached_regex("(").is_err());
+        for i in 0..MAX_CACHED + 1 {
+            cached_regex(&format!("x{}", i)).unwrap();
+        }
+        assert!(CACHE.with(|c| c.borrow().len()) <= MAX_CACHED);
+    }
+}
@@ -0,0 +1,296 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::model::{KeyValue, Request};
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Strategy {
+    /// Overrides win; repeated query params replace the base ones by name.
+    Override,
+    /// Like `Override`, but query params are appended so keys can repeat.
+    Append,
+    /// Overrides are shared defaults (collection or folder level): they only
+    /// fill in what the request does not set itself.
+    Defaults,
+}
+
+/// Apply a partial request on top of a base request.
+/// Used both for "duplicate with changes" and for applying collection-level
+/// defaults (shared headers, auth) to a request.
+/// overrides_json: any subset of the request fields; fields that are absent are kept.
+/// strategy: "override" (default), "append" or "defaults".
+/// - scalar fields and `auth` are replaced (for "defaults": only when the request's
+///   own value is empty, or its auth is missing or of type "inherit");
+/// - headers and form fields are replaced by name, case-insensitively for headers,
+///   with new names appended;
+/// - query params are replaced by name, or appended for "append";
+/// - JSON object bodies are deep merged, with `null` in an override removing a key;
+///   other bodies are replaced;
+/// - assertions are appended, skipping ids the request already has.
+///
+/// Returns the merged request JSON, or {error}.
+#[wasm_bindgen]
+pub fn merge_request(base_request_json: &str, overrides_json: &str, strategy: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let strategy = match strategy {
+        "" | "override" => Strategy::Override,
+        "append" => Strategy::Append,
+        "defaults" => Strategy::Defaults,
+        other => return error(format!("Unknown strategy: {}", other)),
+    };
+    let mut base: Request = match serde_json::from_str(base_request_json) {
+        Ok(r) => r,
+        Err(e) => return error(format!("Invalid request: {}", e)),
+    };
+    let (Ok(Value::Object(present)), Ok(overrides)) = (
+        serde_json::from_str::<Value>(overrides_json),
+        serde_json::from_str::<Request>(overrides_json),
+    ) else {
+        return error("Invalid overrides: expected a request object".to_string());
+    };
+
+    apply(&mut base, overrides, &present, strategy);
+    serde_json::to_string(&base).unwrap_or_else(|e| error(e.to_string()))
+}
+
+/// Merge `overrides` into `base`; `present` holds the override's JSON keys, so
+/// fields it leaves out are kept.
+pub(crate) fn apply(
+    base: &mut Request,
+    overrides: Request,
+    present: &Map<String, Value>,
+    strategy: Strategy,
+) {
+    let has = |field: &str| present.contains_key(field);
+    let defaults = strategy == Strategy::Defaults;
+    let scalars = [
+        ("name", &mut base.name, overrides.name),
+        ("description", &mut base.description, overrides.description),
+        ("method", &mut base.method, overrides.method),
+        ("url", &mut base.url, overrides.url),
+        ("bodyType", &mut base.body_type, overrides.body_type),
+    ];
+    for (field, target, value) in scalars {
+        if has(field) && (!defaults || target.is_empty()) {
+            *target = value;
+        }
+    }
+
+    if has("headers") {
+        merge_pairs(&mut base.headers, overrides.headers, true, defaults);
+    }
+    if has("queryParams") {
+        if strategy == Strategy::Append {
+            base.query_params.extend(overrides.query_params);
+        } else {
+            merge_pairs(
+                &mut base.query_params,
+                overrides.query_params,
+                false,
+                defaults,
+            );
+        }
+    }
+    if has("formData") {
+        merge_pairs(&mut base.form_data, overrides.form_data, false, defaults);
+    }
+
+    if has("body") || has("requestBody") {
+        base.body = merge_body(&base.body, &overrides.body, defaults);
+    }
+
+    if has("auth") {
+        let inherits = base
+            .auth
+            .as_ref()
+            .is_none_or(|a| a.auth_type.is_empty() || a.auth_type == "inherit");
+        if !defaults || inherits {
+            base.auth = overrides.auth;
+        }
+    }
+
+    for assertion in overrides.assertions {
+        if assertion.id.is_empty() || !base.assertions.iter().any(|a| a.id == assertion.id) {
+            base.assertions.push(assertion);
+        }
+    }
+}
+
+/// Replace rows by key, keeping the base order and appending new keys. With
+/// `keep_existing`, rows the base already has are left alone.
+fn merge_pairs(
+    base: &mut Vec<KeyValue>,
+    overrides: Vec<KeyValue>,
+    ignore_case: bool,
+    keep_existing: bool,
+) {
+    let same = |a: &str, b: &str| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    };
+    // Keys already taken from the overrides: further rows with them are
+    // appended, so repeated params replace a base param as a group.
+    let mut replaced: Vec<String> = Vec::new();
+    for row in overrides {
+        if replaced.iter().any(|k| same(k, &row.key)) {
+            base.push(row);
+            continue;
+        }
+        match base.iter().position(|b| same(&b.key, &row.key)) {
+            Some(_) if keep_existing => {}
+            Some(i) => {
+                let key = row.key.clone();
+                base[i] = row;
+                let mut first = true;
+                base.retain(|b| !same(&b.key, &key) || std::mem::take(&mut first));
+                replaced.push(key);
+            }
+            None => {
+                replaced.push(row.key.clone());
+                base.push(row);
+            }
+        }
+    }
+}
+
+fn merge_body(base: &str, overrides: &str, defaults: bool) -> String {
+    match (
+        serde_json::from_str::<Value>(base),
+        serde_json::from_str::<Value>(overrides),
+    ) {
+        (Ok(mut target @ Value::Object(_)), Ok(patch @ Value::Object(_))) => {
+            if defaults {
+                fill_missing(&mut target, &patch);
+            } else {
+                merge_patch(&mut target, &patch);
+            }
+            serde_json::to_string_pretty(&target).unwrap_or_else(|_| base.to_string())
+        }
+        _ if defaults && !base.trim().is_empty() => base.to_string(),
+        _ => overrides.to_string(),
+    }
+}
+
+/// JSON Merge Patch (RFC 7386): objects merge recursively, `null` removes a key.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let (Value::Object(t), Value::Object(p)) = (&mut *target, patch) else {
+        *target = patch.clone();
+        return;
+    };
+    for (k, v) in p {
+        if v.is_null() {
+            t.remove(k);
+        } else {
+            merge_patch(t.entry(k.clone()).or_insert(Value::Null), v);
+        }
+    }
+}
+
+/// Add keys from `defaults` that `target` lacks, recursing into shared objects.
+fn fill_missing(target: &mut Value, defaults: &Value) {
+    if let (Value::Object(t), Value::Object(d)) = (target, defaults) {
+        for (k, v) in d {
+            match t.get_mut(k) {
+                Some(existing) => fill_missing(existing, v),
+                None => {
+                    t.insert(k.clone(), v.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"{
+        "id": "r1",
+        "name": "Create user",
+        "method": "POST",
+        "url": "https://api.x.io/users",
+        "headers": [
+            {"key": "Accept", "value": "application/json", "enabled": true},
+            {"key": "X-Trace", "value": "1", "enabled": true}
+        ],
+        "queryParams": {"tag": "a"},
+        "body": "{\"name\": \"Ada\", \"address\": {\"city\": \"London\", \"zip\": \"N1\"}, \"admin\": false}",
+        "bodyType": "json",
+        "auth": {"type": "inherit"}
+    }"#;
+
+    fn merged(overrides: &str, strategy: &str) -> Value {
+        serde_json::from_str(&merge_request(BASE, overrides, strategy)).unwrap()
+    }
+
+    #[test]
+    fn test_merge_request_override() {
+        let out = merged(
+            r#"{
+                "name": "Create admin",
+                "headers": {"accept": "text/plain", "X-Admin": "yes"},
+                "queryParams": {"tag": "b"},
+                "body": "{\"address\": {\"city\": \"Paris\"}, \"admin\": true, \"name\": null}"
+            }"#,
+            "override",
+        );
+        assert_eq!(out["name"], "Create admin");
+        assert_eq!(out["method"], "POST");
+        let headers: Vec<(&str, &str)> = out["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| (h["key"].as_str().unwrap(), h["value"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            headers,
+            [
+                ("accept", "text/plain"),
+                ("X-Trace", "1"),
+                ("X-Admin", "yes")
+            ]
+        );
+        assert_eq!(out["queryParams"].as_array().unwrap().len(), 1);
+        assert_eq!(out["queryParams"][0]["value"], "b");
+        let body: Value = serde_json::from_str(out["body"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"address": {"city": "Paris", "zip": "N1"}, "admin": true})
+        );
+
+        let appended = merged(r#"{"queryParams": {"tag": "b"}}"#, "append");
+        assert_eq!(appended["queryParams"].as_array().unwrap().len(), 2);
+        let repeated = merged(
+            r#"{"queryParams": [{"key": "tag", "value": "b"}, {"key": "tag", "value": "c"}]}"#,
+            "override",
+        );
+        assert_eq!(repeated["queryParams"][1]["value"], "c");
+        assert_eq!(repeated["queryParams"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_merge_request_defaults() {
+        let out = merged(
+            r#"{
+                "method": "GET",
+                "headers": {"Accept": "*/*", "User-Agent": "volt"},
+                "body": "{\"admin\": true, \"address\": {\"country\": \"UK\"}}",
+                "auth": {"type": "bearer", "token": "{{token}}"}
+            }"#,
+            "defaults",
+        );
+        assert_eq!(out["method"], "POST");
+        assert_eq!(out["headers"][0]["value"], "application/json");
+        assert_eq!(out["headers"][2]["key"], "User-Agent");
+        assert_eq!(out["auth"]["type"], "bearer");
+        let body: Value = serde_json::from_str(out["body"].as_str().unwrap()).unwrap();
+        assert_eq!(body["admin"], false);
+        assert_eq!(body["address"]["country"], "UK");
+
+        assert!(merge_request(BASE, "{}", "rebase").contains("Unknown strategy"));
+        assert!(merge_request(BASE, "[]", "").contains("Invalid overrides"));
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::split_url;
+
+const DAY_MS: f64 = 86_400_000.0;
+
+#[derive(Deserialize)]
+struct EntryMeta {
+    id: String,
+    #[serde(default)]
+    timestamp: f64,
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    url: String,
+    /// Stored size in bytes (request + response).
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Policy {
+    #[serde(rename = "maxAgeDays")]
+    max_age_days: Option<f64>,
+    /// Newest entries kept per endpoint (method + URL without query).
+    #[serde(rename = "maxPerEndpoint")]
+    max_per_endpoint: Option<usize>,
+    #[serde(rename = "maxEntries")]
+    max_entries: Option<usize>,
+    #[serde(rename = "maxTotalBytes")]
+    max_total_bytes: Option<u64>,
+    /// Reference time in ms; defaults to the current time.
+    now: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct Deletion {
+    id: String,
+    rule: &'static str,
+}
+
+/// Decide which history entries to prune.
+/// entries_meta_json: array of {id, timestamp, method, url, size, pinned}.
+/// policy_json: {maxAgeDays, maxPerEndpoint, maxEntries, maxTotalBytes, now}; omitted rules don't apply.
+/// Rules run in that order, each over the entries the previous ones kept; pinned
+/// entries are never deleted and don't count towards the per-endpoint or entry
+/// limits, though their size does count towards the byte budget.
+/// Returns JSON {delete: [id], reasons: [{id, rule}], kept, keptBytes, freedBytes}
+/// where rule is "age", "endpointLimit", "entryLimit" or "sizeBudget".
+#[wasm_bindgen]
+pub fn apply_retention(entries_meta_json: &str, policy_json: &str) -> String {
+    let entries: Vec<EntryMeta> = serde_json::from_str(entries_meta_json).unwrap_or_default();
+    let policy: Policy = serde_json::from_str(policy_json).unwrap_or_default();
+    let now = policy.now.unwrap_or_else(crate::now_ms);
+
+    // Newest first, so "keep the first N" keeps the most recent.
+    let mut order: Vec<&EntryMeta> = entries.iter().collect();
+    order.sort_by(|a, b| b.timestamp.total_cmp(&a.timestamp));
+
+    let mut reasons: HashMap<&str, &'static str> = HashMap::new();
+    let alive =
+        |reasons: &HashMap<&str, &'static str>, e: &EntryMeta| !reasons.contains_key(e.id.as_str());
+
+    if let Some(days) = policy.max_age_days {
+        let cutoff = now - days * DAY_MS;
+        for e in order.iter().filter(|e| !e.pinned && e.timestamp < cutoff) {
+            reasons.insert(&e.id, "age");
+        }
+    }
+
+    if let Some(limit) = policy.max_per_endpoint {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for e in &order {
+            if e.pinned || !alive(&reasons, e) {
+                continue;
+            }
+            let count = seen.entry(endpoint(e)).or_default();
+            *count += 1;
+            if *count > limit {
+                reasons.insert(&e.id, "endpointLimit");
+            }
+        }
+    }
+
+    if let Some(limit) = policy.max_entries {
+        let unpinned = order.iter().filter(|e| !e.pinned && alive(&reasons, e));
+        for e in unpinned.skip(limit).collect::<Vec<_>>() {
+            reasons.insert(&e.id, "entryLimit");
+        }
+    }
+
+    if let Some(budget) = policy.max_total_bytes {
+        let mut total: u64 = order
+            .iter()
+            .filter(|e| alive(&reasons, e))
+            .map(|e| e.size)
+            .sum();
+        for e in order.iter().rev() {
+            if total <= budget {
+                break;
+            }
+            if !e.pinned && alive(&reasons, e) {
+                reasons.insert(&e.id, "sizeBudget");
+                total -= e.size;
+            }
+        }
+    }
+
+    // Report deletions oldest first, in a stable order.
+    let deletions: Vec<Deletion> = order
+        .iter()
+        .rev()
+        .filter_map(|e| {
+            reasons.get(e.id.as_str()).map(|rule| Deletion {
+                id: e.id.clone(),
+                rule,
+            })
+        })
+        .collect();
+    let freed: u64 = order
+        .iter()
+        .filter(|e| !alive(&reasons, e))
+        .map(|e| e.size)
+        .sum();
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    serde_json::to_string(&serde_json::json!({
+        "delete": deletions.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(),
+        "reasons": deletions,
+        "kept": entries.len() - deletions.len(),
+        "keptBytes": total - freed,
+        "freedBytes": freed,
+    }))
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+fn endpoint(e: &EntryMeta) -> String {
+    let (origin, path, _) = split_url(e.url.trim());
+    format!("{} {}{}", e.method.to_uppercase(), origin, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn entry(id: &str, days_ago: f64, url: &str, size: u64, pinned: bool) -> Value {
+        serde_json::json!({
+            "id": id, "timestamp": 100.0 * DAY_MS - days_ago * DAY_MS,
+            "method": "get", "url": url, "size": size, "pinned": pinned
+        })
+    }
+
+    fn run(entries: &[Value], policy: Value) -> Value {
+        let mut policy = policy;
+        policy["now"] = Value::from(100.0 * DAY_MS);
+        serde_json::from_str(&apply_retention(
+            &Value::from(entries.to_vec()).to_string(),
+            &policy.to_string(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_retention_age_and_endpoint() {
+        let entries = [
+            entry("old", 40.0, "https://x.io/a", 10, false),
+            entry("old-pinned", 50.0, "https://x.io/a", 10, true),
+            entry("a1", 1.0, "https://x.io/a?page=1", 10, false),
+            entry("a2", 2.0, "https://x.io/a?page=2", 10, false),
+            entry("a3", 3.0, "https://x.io/a", 10, false),
+            entry("b1", 3.0, "https://x.io/b", 10, false),
+        ];
+        let result = run(
+            &entries,
+            serde_json::json!({"maxAgeDays": 30, "maxPerEndpoint": 2}),
+        );
+        assert_eq!(result["delete"], serde_json::json!(["old", "a3"]));
+        assert_eq!(result["reasons"][0]["rule"], "age");
+        assert_eq!(result["reasons"][1]["rule"], "endpointLimit");
+        assert_eq!(result["kept"], 4);
+        assert_eq!(result["freedBytes"], 20);
+    }
+
+    #[test]
+    fn test_apply_retention_size_budget_and_entry_limit() {
+        let entries = [
+            entry("new", 1.0, "/a", 100, false),
+            entry("mid", 2.0, "/b", 100, false),
+            entry("pin", 3.0, "/c", 100, true),
+            entry("older", 4.0, "/d", 100, false),
+            entry("oldest", 5.0, "/e", 100, false),
+        ];
+        let result = run(&entries, serde_json::json!({"maxTotalBytes": 250}));
+        assert_eq!(
+            result["delete"],
+            serde_json::json!(["oldest", "older", "mid"])
+        );
+        assert_eq!(result["keptBytes"], 200);
+
+        let result = run(&entries, serde_json::json!({"maxEntries": 1}));
+        assert_eq!(
+            result["delete"],
+            serde_json::json!(["oldest", "older", "mid"])
+        );
+        assert_eq!(result["reasons"][0]["rule"], "entryLimit");
+        assert_eq!(
+            run(&entries, serde_json::json!({}))["delete"],
+            serde_json::json!([])
+        );
+    }
+}
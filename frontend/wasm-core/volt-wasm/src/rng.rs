@@ -0,0 +1,69 @@
+/// Small deterministic PRNG (SplitMix64). Not cryptographically secure; used
+/// wherever a run must be reproducible from a seed.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform integer in [min, max] (inclusive). Returns min when max < min.
+    pub(crate) fn range(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        // The span can exceed i64; the full range takes any u64 as is.
+        let offset = match (max as i128 - min as i128) as u64 {
+            u64::MAX => self.next_u64(),
+            span => self.next_u64() % (span + 1),
+        };
+        min.wrapping_add(offset as i64)
+    }
+
+    /// Returns true with the given probability (clamped to [0, 1]).
+    pub(crate) fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_rng_range_bounds() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let n = rng.range(-3, 3);
+            assert!((-3..=3).contains(&n));
+        }
+        assert_eq!(rng.range(5, 5), 5);
+        for _ in 0..100 {
+            assert!(rng.range(-1, i64::MAX) >= -1);
+            assert!(rng.range(i64::MAX - 1, i64::MAX) >= i64::MAX - 1);
+            rng.range(i64::MIN, i64::MAX);
+        }
+    }
+}
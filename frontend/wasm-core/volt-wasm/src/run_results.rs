@@ -0,0 +1,351 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::model::{KeyValue, deserialize_pairs};
+
+const POSTMAN_SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Run {
+    #[serde(rename = "collectionId")]
+    collection_id: String,
+    name: String,
+    #[serde(rename = "startedAt")]
+    started_at: f64,
+    #[serde(rename = "finishedAt")]
+    finished_at: f64,
+    results: Vec<Execution>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Execution {
+    #[serde(alias = "requestId")]
+    id: String,
+    name: String,
+    method: String,
+    url: String,
+    /// Zero-based iteration this execution belongs to.
+    iteration: usize,
+    #[serde(deserialize_with = "deserialize_pairs")]
+    headers: Vec<KeyValue>,
+    status: u16,
+    #[serde(rename = "statusText")]
+    status_text: String,
+    #[serde(rename = "durationMs")]
+    duration_ms: f64,
+    #[serde(rename = "responseSize")]
+    response_size: u64,
+    #[serde(rename = "responseHeaders", deserialize_with = "deserialize_pairs")]
+    response_headers: Vec<KeyValue>,
+    /// Transport failure (no response), if any.
+    error: Option<String>,
+    #[serde(alias = "assertionResults")]
+    assertions: Vec<AssertionOutcome>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AssertionOutcome {
+    #[serde(rename = "assertionId")]
+    assertion_id: String,
+    name: String,
+    passed: bool,
+    skipped: bool,
+    message: String,
+}
+
+impl AssertionOutcome {
+    fn label(&self) -> &str {
+        [&self.name, &self.message, &self.assertion_id]
+            .into_iter()
+            .find(|s| !s.is_empty())
+            .map(String::as_str)
+            .unwrap_or("assertion")
+    }
+}
+
+/// Convert a collection run into another tool's result format.
+/// run_json: {collectionId, name, startedAt, finishedAt, results: [{id, name, method,
+/// url, iteration, headers, status, statusText, durationMs, responseSize,
+/// responseHeaders, error, assertions: [{assertionId, name, passed, skipped, message}]}]}
+/// where `assertions` takes the output of `run_assertions` as is.
+/// format: "newman" for the structure of Newman's JSON reporter
+/// ({collection, run: {stats, timings, executions, transfers, failures, error}}).
+/// Returns the converted JSON, or {error}.
+#[wasm_bindgen]
+pub fn export_run_results(run_json: &str, format: &str) -> String {
+    let run: Run = match serde_json::from_str(run_json) {
+        Ok(r) => r,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid run: {}", e) }).to_string();
+        }
+    };
+    match format {
+        "newman" => newman(&run).to_string(),
+        other => {
+            serde_json::json!({ "error": format!("Unsupported format: {}", other) }).to_string()
+        }
+    }
+}
+
+fn newman(run: &Run) -> Value {
+    let length = run.results.len();
+    let iterations = run
+        .results
+        .iter()
+        .map(|e| e.iteration + 1)
+        .max()
+        .unwrap_or(0);
+    let mut executions = Vec::new();
+    let mut failures = Vec::new();
+    let mut items: IndexMap<&str, Value> = IndexMap::new();
+
+    for (position, e) in run.results.iter().enumerate() {
+        let source = serde_json::json!({ "id": e.id, "name": e.name });
+        let cursor = serde_json::json!({
+            "position": position,
+            "iteration": e.iteration,
+            "length": length,
+            "cycles": iterations,
+            "empty": false,
+            "eof": position + 1 == length,
+            "bof": position == 0,
+            "cr": false,
+            "ref": e.id,
+            "httpRequestId": format!("{}-{}", e.id, position),
+        });
+        let request = serde_json::json!({
+            "url": e.url,
+            "method": e.method.to_uppercase(),
+            "header": pairs(&e.headers),
+        });
+        items.entry(&e.id).or_insert_with(
+            || serde_json::json!({ "id": e.id, "name": e.name, "request": request }),
+        );
+
+        let mut assertions = Vec::new();
+        for (index, a) in e.assertions.iter().enumerate() {
+            let mut entry = serde_json::json!({ "assertion": a.label(), "skipped": a.skipped });
+            if !a.passed && !a.skipped {
+                let error = serde_json::json!({
+                    "name": "AssertionError",
+                    "index": index,
+                    "test": a.label(),
+                    "message": a.message,
+                    "stack": format!("AssertionError: {}", a.message),
+                });
+                entry["error"] = error.clone();
+                failures.push(serde_json::json!({
+                    "error": error,
+                    "at": format!("assertion:{} in test-script", index),
+                    "source": source,
+                    "parent": { "id": run.collection_id, "name": run.name },
+                    "cursor": cursor,
+                }));
+            }
+            assertions.push(entry);
+        }
+
+        let mut execution = serde_json::json!({
+            "id": format!("{}-{}", e.id, position),
+            "cursor": cursor,
+            "item": source,
+            "request": request,
+            "assertions": assertions,
+        });
+        match &e.error {
+            Some(message) => {
+                let error = serde_json::json!({ "name": "Error", "message": message });
+                execution["requestError"] = error.clone();
+                failures.push(serde_json::json!({
+                    "error": error,
+                    "at": "request",
+                    "source": source,
+                    "parent": { "id": run.collection_id, "name": run.name },
+                    "cursor": cursor,
+                }));
+            }
+            None => {
+                execution["response"] = serde_json::json!({
+                    "id": format!("{}-{}-response", e.id, position),
+                    "status": e.status_text,
+                    "code": e.status,
+                    "header": pairs(&e.response_headers),
+                    "responseTime": e.duration_ms,
+                    "responseSize": e.response_size,
+                });
+            }
+        }
+        executions.push(execution);
+    }
+
+    let answered: Vec<f64> = run
+        .results
+        .iter()
+        .filter(|e| e.error.is_none())
+        .map(|e| e.duration_ms)
+        .collect();
+    let average = if answered.is_empty() {
+        0.0
+    } else {
+        answered.iter().sum::<f64>() / answered.len() as f64
+    };
+    let sd = if answered.is_empty() {
+        0.0
+    } else {
+        (answered.iter().map(|d| (d - average).powi(2)).sum::<f64>() / answered.len() as f64).sqrt()
+    };
+
+    let all_assertions = run.results.iter().flat_map(|e| &e.assertions);
+    let assertion_failures = all_assertions
+        .clone()
+        .filter(|a| !a.passed && !a.skipped)
+        .count();
+    let failed_iterations = (0..iterations)
+        .filter(|i| {
+            run.results.iter().any(|e| {
+                e.iteration == *i
+                    && (e.error.is_some() || e.assertions.iter().any(|a| !a.passed && !a.skipped))
+            })
+        })
+        .count();
+    let zero = serde_json::json!({ "total": 0, "pending": 0, "failed": 0 });
+    let stat = |total: usize, failed: usize| serde_json::json!({ "total": total, "pending": 0, "failed": failed });
+
+    serde_json::json!({
+        "collection": {
+            "info": {
+                "_postman_id": run.collection_id,
+                "name": run.name,
+                "schema": POSTMAN_SCHEMA,
+            },
+            "item": items.into_values().collect::<Vec<_>>(),
+        },
+        "run": {
+            "stats": {
+                "iterations": stat(iterations, failed_iterations),
+                "items": stat(length, 0),
+                "scripts": zero,
+                "prerequests": stat(length, 0),
+                "requests": stat(length, run.results.iter().filter(|e| e.error.is_some()).count()),
+                "tests": stat(length, 0),
+                "assertions": stat(all_assertions.count(), assertion_failures),
+                "testScripts": zero,
+                "prerequestScripts": zero,
+            },
+            "timings": {
+                "responseAverage": average,
+                "responseMin": answered.iter().copied().reduce(f64::min).unwrap_or(0.0),
+                "responseMax": answered.iter().copied().reduce(f64::max).unwrap_or(0.0),
+                "responseSd": sd,
+                "dnsAverage": 0,
+                "dnsMin": 0,
+                "dnsMax": 0,
+                "dnsSd": 0,
+                "firstByteAverage": 0,
+                "firstByteMin": 0,
+                "firstByteMax": 0,
+                "firstByteSd": 0,
+                "started": run.started_at,
+                "completed": run.finished_at,
+            },
+            "executions": executions,
+            "transfers": {
+                "responseTotal": run.results.iter().map(|e| e.response_size).sum::<u64>(),
+            },
+            "failures": failures,
+            "error": null,
+        },
+    })
+}
+
+fn pairs(list: &[KeyValue]) -> Vec<Value> {
+    list.iter()
+        .filter(|kv| kv.enabled)
+        .map(|kv| serde_json::json!({ "key": kv.key, "value": kv.value }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = r#"{
+        "collectionId": "c1",
+        "name": "Smoke",
+        "startedAt": 1000,
+        "finishedAt": 2500,
+        "results": [
+            {
+                "id": "r1", "name": "List users", "method": "get", "url": "https://x.io/users",
+                "headers": {"Accept": "application/json"},
+                "status": 200, "statusText": "OK", "durationMs": 100, "responseSize": 512,
+                "assertions": [
+                    {"assertionId": "a1", "passed": true, "message": "Status is 200"},
+                    {"assertionId": "a2", "passed": false, "message": "Expected 3 items, got 2"}
+                ]
+            },
+            {
+                "id": "r2", "name": "Health", "method": "GET", "url": "https://x.io/health",
+                "error": "connect ECONNREFUSED"
+            },
+            {
+                "id": "r1", "name": "List users", "method": "GET", "url": "https://x.io/users",
+                "iteration": 1, "status": 200, "statusText": "OK", "durationMs": 300, "responseSize": 256
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_export_run_results_newman() {
+        let report: Value = serde_json::from_str(&export_run_results(RUN, "newman")).unwrap();
+        assert_eq!(report["collection"]["info"]["name"], "Smoke");
+        assert_eq!(report["collection"]["item"].as_array().unwrap().len(), 2);
+        let stats = &report["run"]["stats"];
+        assert_eq!(
+            stats["iterations"],
+            serde_json::json!({"total": 2, "pending": 0, "failed": 1})
+        );
+        assert_eq!(stats["requests"]["failed"], 1);
+        assert_eq!(stats["assertions"]["total"], 2);
+        assert_eq!(stats["assertions"]["failed"], 1);
+
+        let timings = &report["run"]["timings"];
+        assert_eq!(timings["responseAverage"], 200.0);
+        assert_eq!(timings["responseMin"], 100.0);
+        assert_eq!(timings["responseSd"], 100.0);
+        assert_eq!(timings["completed"], 2500.0);
+        assert_eq!(report["run"]["transfers"]["responseTotal"], 768);
+
+        let first = &report["run"]["executions"][0];
+        assert_eq!(first["request"]["method"], "GET");
+        assert_eq!(first["request"]["header"][0]["key"], "Accept");
+        assert_eq!(first["response"]["code"], 200);
+        assert_eq!(
+            first["assertions"][1]["assertion"],
+            "Expected 3 items, got 2"
+        );
+        assert_eq!(first["cursor"]["bof"], true);
+        assert!(report["run"]["executions"][1].get("response").is_none());
+
+        let failures = report["run"]["failures"].as_array().unwrap();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0]["error"]["name"], "AssertionError");
+        assert_eq!(failures[0]["at"], "assertion:1 in test-script");
+        assert_eq!(failures[1]["at"], "request");
+        assert_eq!(failures[1]["source"]["name"], "Health");
+    }
+
+    #[test]
+    fn test_export_run_results_errors() {
+        assert!(export_run_results(RUN, "junit").contains("Unsupported format"));
+        assert!(export_run_results("nope", "newman").contains("Invalid run"));
+        let empty: Value = serde_json::from_str(&export_run_results("{}", "newman")).unwrap();
+        assert_eq!(empty["run"]["stats"]["iterations"]["total"], 0);
+        assert_eq!(empty["run"]["timings"]["responseAverage"], 0.0);
+    }
+}
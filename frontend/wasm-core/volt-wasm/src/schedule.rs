@@ -0,0 +1,386 @@
+use wasm_bindgen::prelude::*;
+
+use crate::civil_from_days;
+
+const MINUTE_MS: i64 = 60_000;
+const DAY_MS: i64 = 86_400_000;
+
+/// The range of a JavaScript Date, ±100,000,000 days around the epoch.
+const MAX_TIME_MS: f64 = 8.64e15;
+
+/// Upper bound on occurrences per call, so a UI slider cannot hang the worker.
+const MAX_OCCURRENCES: usize = 1000;
+
+/// Cron expressions are searched this far ahead; "30 0 31 2 *" never fires.
+const SEARCH_DAYS: i64 = 366 * 5;
+
+const MONTHS: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+enum Schedule {
+    Interval(i64),
+    Cron(Cron),
+}
+
+/// A five-field cron expression as bit sets of allowed values.
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day-of-month or day-of-week was "*"; when both are restricted a day
+    /// matching either one fires, as in Vixie cron.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Validate a monitor schedule.
+/// expr: a five-field cron expression ("*/15 9-17 * * MON-FRI"), a macro
+/// (@hourly, @daily, @midnight, @weekly, @monthly, @yearly, @annually) or an
+/// interval ("every 15m", "every 2 hours", "30s").
+/// Returns JSON {valid, kind: "cron"|"interval", intervalMs, fields:
+/// {minute, hour, dayOfMonth, month, dayOfWeek}} with the expanded values of each
+/// cron field, or {valid: false, error}.
+#[wasm_bindgen]
+pub fn parse_schedule(expr: &str) -> String {
+    match parse(expr) {
+        Ok(Schedule::Interval(ms)) => serde_json::json!({
+            "valid": true,
+            "kind": "interval",
+            "intervalMs": ms,
+        }),
+        Ok(Schedule::Cron(cron)) => serde_json::json!({
+            "valid": true,
+            "kind": "cron",
+            "fields": {
+                "minute": values(cron.minutes),
+                "hour": values(cron.hours),
+                "dayOfMonth": values(cron.days),
+                "month": values(cron.months),
+                "dayOfWeek": values(cron.weekdays),
+            },
+        }),
+        Err(error) => serde_json::json!({ "valid": false, "error": error }),
+    }
+    .to_string()
+}
+
+/// The next `n` times (ms since the epoch, UTC) the schedule fires after `from`.
+/// Intervals are aligned to the epoch, so "every 15m" fires at :00, :15, :30
+/// and :45. n is capped at 1000.
+/// Returns JSON {occurrences: [ms]} or {occurrences: [], error}, also when
+/// `from` is not a finite time within the range of a JavaScript Date.
+#[wasm_bindgen]
+pub fn next_occurrences(expr: &str, from: f64, n: u32) -> String {
+    let n = (n as usize).min(MAX_OCCURRENCES);
+    if !from.is_finite() || from.abs() > MAX_TIME_MS {
+        return serde_json::json!({
+            "occurrences": [],
+            "error": format!("Start time {} is out of range", from),
+        })
+        .to_string();
+    }
+    match parse(expr) {
+        Ok(schedule) => {
+            let mut out = Vec::with_capacity(n);
+            let mut at = from.floor() as i64;
+            while out.len() < n {
+                let Some(next) = next_after(&schedule, at) else {
+                    break;
+                };
+                out.push(next);
+                at = next;
+            }
+            serde_json::json!({ "occurrences": out })
+        }
+        Err(error) => serde_json::json!({ "occurrences": [], "error": error }),
+    }
+    .to_string()
+}
+
+fn parse(expr: &str) -> Result<Schedule, String> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        return Err("Schedule is empty".to_string());
+    }
+    let lower = expr.to_lowercase();
+    let cron = match lower.as_str() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        _ if lower.starts_with('@') => return Err(format!("Unknown macro {}", expr)),
+        _ => expr,
+    };
+    if let Some(ms) = parse_interval(&lower) {
+        return ms.map(Schedule::Interval);
+    }
+    parse_cron(cron).map(Schedule::Cron)
+}
+
+/// "every 15m", "every 2 hours", "90s", "every day". None when the text does not
+/// look like an interval at all.
+fn parse_interval(text: &str) -> Option<Result<i64, String>> {
+    let rest = text.strip_prefix("every").map(str::trim_start);
+    let body = rest.unwrap_or(text);
+    let split = body
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(body.len());
+    let (number, unit) = body.split_at(split);
+    let unit = unit.trim();
+    if rest.is_none() && (number.is_empty() || unit.is_empty() || unit.contains(' ')) {
+        return None;
+    }
+    let count: f64 = if number.is_empty() {
+        1.0
+    } else {
+        match number.parse() {
+            Ok(n) => n,
+            Err(_) => return Some(Err(format!("Invalid interval \"{}\"", text))),
+        }
+    };
+    let unit_ms = match unit {
+        "ms" => 1.0,
+        "s" => 1000.0,
+        _ => match unit.trim_end_matches('s') {
+            "sec" | "second" => 1000.0,
+            "m" | "min" | "minute" => 60_000.0,
+            "h" | "hr" | "hour" => 3_600_000.0,
+            "d" | "day" => 86_400_000.0,
+            "w" | "wk" | "week" => 604_800_000.0,
+            _ if rest.is_none() => return None,
+            _ => return Some(Err(format!("Unknown interval unit \"{}\"", unit))),
+        },
+    };
+    let ms = (count * unit_ms).round() as i64;
+    Some(if ms < 1000 {
+        Err("Interval must be at least 1 second".to_string())
+    } else {
+        Ok(ms)
+    })
+}
+
+fn parse_cron(expr: &str) -> Result<Cron, String> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let [minute, hour, day, month, weekday] = fields[..] else {
+        return Err(format!(
+            "Expected 5 cron fields (minute hour day-of-month month day-of-week), got {}",
+            fields.len()
+        ));
+    };
+    let mut weekdays = parse_field(weekday, "day of week", 0, 7, WEEKDAYS)?;
+    // 7 is another name for Sunday.
+    if weekdays & (1 << 7) != 0 {
+        weekdays = (weekdays | 1) & !(1 << 7);
+    }
+    Ok(Cron {
+        minutes: parse_field(minute, "minute", 0, 59, &[])?,
+        hours: parse_field(hour, "hour", 0, 23, &[])?,
+        days: parse_field(day, "day of month", 1, 31, &[])?,
+        months: parse_field(month, "month", 1, 12, MONTHS)?,
+        weekdays,
+        any_day: matches!(day, "*" | "?"),
+        any_weekday: matches!(weekday, "*" | "?"),
+    })
+}
+
+/// One cron field: lists of values, ranges and steps ("1,5-10/2,*/15").
+/// `names` are case-insensitive aliases for `min`, `min + 1`, ...
+fn parse_field(
+    field: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let upper = text.to_uppercase();
+        let n = match names.iter().position(|name| *name == upper) {
+            Some(i) => min + i as u32,
+            None => text
+                .parse()
+                .map_err(|_| format!("{}: invalid value \"{}\"", label, text))?,
+        };
+        if n < min || n > max {
+            return Err(format!("{}: {} is out of range {}-{}", label, n, min, max));
+        }
+        Ok(n)
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("{}: invalid step \"{}\"", label, step)),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" | "?" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // "5/15" runs from 5 to the end of the range.
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("{}: range {} is backwards", label, range));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+fn values(bits: u64) -> Vec<u32> {
+    (0..64).filter(|n| bits & (1 << n) != 0).collect()
+}
+
+fn next_after(schedule: &Schedule, after: i64) -> Option<i64> {
+    match schedule {
+        Schedule::Interval(ms) => after.div_euclid(*ms).checked_add(1)?.checked_mul(*ms),
+        Schedule::Cron(cron) => cron.next_after(after),
+    }
+}
+
+impl Cron {
+    fn day_matches(&self, days: i64) -> bool {
+        let (_, _, day) = civil_from_days(days);
+        let weekday = (days + 4).rem_euclid(7);
+        let by_day = self.days & (1 << day) != 0;
+        let by_weekday = self.weekdays & (1 << weekday) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => by_day || by_weekday,
+            _ => by_day && by_weekday,
+        }
+    }
+
+    /// First matching minute strictly after `after`, walking day by day and then
+    /// through the hours and minutes of a matching day.
+    fn next_after(&self, after: i64) -> Option<i64> {
+        let start = after
+            .div_euclid(MINUTE_MS)
+            .checked_add(1)?
+            .checked_mul(MINUTE_MS)?;
+        let first_day = start.div_euclid(DAY_MS);
+        for days in first_day..first_day.checked_add(SEARCH_DAYS)? {
+            let (_, month, _) = civil_from_days(days);
+            if self.months & (1 << month) == 0 || !self.day_matches(days) {
+                continue;
+            }
+            let from_minute = if days == first_day {
+                (start - days * DAY_MS) / MINUTE_MS
+            } else {
+                0
+            };
+            let minute = (from_minute..1440)
+                .find(|m| self.hours & (1 << (m / 60)) != 0 && self.minutes & (1 << (m % 60)) != 0);
+            if let Some(minute) = minute {
+                return days.checked_mul(DAY_MS)?.checked_add(minute * MINUTE_MS);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// 2024-01-01T00:00:00Z, a Monday.
+    const NOW: f64 = 1_704_067_200_000.0;
+
+    fn next(expr: &str, n: u32) -> Vec<i64> {
+        let out: Value = serde_json::from_str(&next_occurrences(expr, NOW, n)).unwrap();
+        out["occurrences"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| (v.as_i64().unwrap() - NOW as i64) / MINUTE_MS)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let cron: Value =
+            serde_json::from_str(&parse_schedule("*/20 9-17/4 * JAN,jun mon-FRI")).unwrap();
+        assert_eq!(cron["kind"], "cron");
+        assert_eq!(cron["fields"]["minute"], serde_json::json!([0, 20, 40]));
+        assert_eq!(cron["fields"]["hour"], serde_json::json!([9, 13, 17]));
+        assert_eq!(cron["fields"]["month"], serde_json::json!([1, 6]));
+        assert_eq!(
+            cron["fields"]["dayOfWeek"],
+            serde_json::json!([1, 2, 3, 4, 5])
+        );
+        let sunday: Value = serde_json::from_str(&parse_schedule("0 0 * * 7")).unwrap();
+        assert_eq!(sunday["fields"]["dayOfWeek"], serde_json::json!([0]));
+
+        let interval: Value = serde_json::from_str(&parse_schedule("every 15m")).unwrap();
+        assert_eq!(interval["intervalMs"], 900_000);
+        assert_eq!(parse_interval("every 2 hours"), Some(Ok(7_200_000)));
+        assert_eq!(parse_interval("90s"), Some(Ok(90_000)));
+        assert_eq!(parse_interval("every day"), Some(Ok(86_400_000)));
+        assert_eq!(parse_interval("every 1500ms"), Some(Ok(1500)));
+        assert_eq!(parse_interval("5"), None);
+
+        for (expr, error) in [
+            ("", "Schedule is empty"),
+            ("* * *", "Expected 5 cron fields"),
+            ("60 * * * *", "minute: 60 is out of range 0-59"),
+            ("* * * FOO *", "month: invalid value \"FOO\""),
+            ("*/0 * * * *", "minute: invalid step"),
+            ("every 5 fortnights", "Unknown interval unit"),
+            ("every 0.5s", "Interval must be at least 1 second"),
+            ("@reboot", "Unknown macro"),
+        ] {
+            let out: Value = serde_json::from_str(&parse_schedule(expr)).unwrap();
+            assert_eq!(out["valid"], false, "{}", expr);
+            assert!(
+                out["error"].as_str().unwrap().starts_with(error),
+                "{}: {}",
+                expr,
+                out
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_occurrences() {
+        assert_eq!(next("every 15m", 3), [15, 30, 45]);
+        assert_eq!(next("30 9 * * MON-FRI", 2), [570, 570 + 1440]);
+        assert_eq!(next("@hourly", 2), [60, 120]);
+        // Day 15 or any Friday (Jan 5th); both restricted means either matches.
+        assert_eq!(next("0 0 15 * 5", 2), [4 * 1440, 11 * 1440]);
+        // Feb 29 2024 is the first leap day after NOW.
+        assert_eq!(next("0 12 29 2 *", 1), [59 * 1440 + 720]);
+        assert!(next("0 0 31 2 *", 1).is_empty());
+        let out: Value = serde_json::from_str(&next_occurrences("nope", NOW, 3)).unwrap();
+        assert!(out["error"].is_string());
+        assert_eq!(next("* * * * *", 5000).len(), MAX_OCCURRENCES);
+    }
+
+    #[test]
+    fn test_next_occurrences_rejects_out_of_range_start() {
+        for from in [-1e300, 1e300, f64::NAN, f64::INFINITY, -8.7e15] {
+            for expr in ["* * * * *", "every 15m"] {
+                let out: Value = serde_json::from_str(&next_occurrences(expr, from, 3)).unwrap();
+                assert_eq!(out["occurrences"], serde_json::json!([]));
+                assert!(out["error"].as_str().unwrap().contains("out of range"));
+            }
+        }
+        assert_eq!(next_after(&Schedule::Interval(1000), i64::MAX), None);
+        let cron = parse("* * * * *").unwrap();
+        assert_eq!(next_after(&cron, i64::MAX - 1), None);
+        let out: Value =
+            serde_json::from_str(&next_occurrences("0 0 * * *", -MAX_TIME_MS, 1)).unwrap();
+        assert_eq!(out["occurrences"].as_array().unwrap().len(), 1);
+    }
+}
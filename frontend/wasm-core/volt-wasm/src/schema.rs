@@ -0,0 +1,871 @@
+use std::collections::HashMap;
+
+use regex_lite::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::compare::{CompareOptions, json_matches};
+use crate::openapi::{escape_pointer, resolve_pointer};
+
+/// Nested schema applications (through `$ref` included) before giving up, so
+/// that a recursive schema cannot exhaust the stack on a deep instance.
+/// A `$ref` loop that does not consume the instance is caught before that.
+const MAX_DEPTH: usize = 128;
+/// Violations reported before the rest are dropped.
+const MAX_ERRORS: usize = 1000;
+
+/// One place where the instance does not satisfy the schema.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Violation {
+    /// JSON Pointer to the offending value ("" for the document itself).
+    pub(crate) instance_path: String,
+    /// JSON Pointer to the failing keyword in the schema.
+    pub(crate) schema_path: String,
+    pub(crate) keyword: String,
+    pub(crate) message: String,
+}
+
+struct Validator<'s> {
+    root: &'s Value,
+    regexes: HashMap<&'s str, Regex>,
+    errors: Vec<Violation>,
+    truncated: bool,
+    /// `$ref` targets being applied, with the instance each is applied to; the
+    /// same pair again means the schema loops without consuming the instance.
+    refs: Vec<(*const Value, *const Value)>,
+}
+
+fn same(a: &Value, b: &Value) -> bool {
+    json_matches(a, b, &CompareOptions::default())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if is_integer(n) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// 1.0 is an integer too.
+fn is_integer(n: &serde_json::Number) -> bool {
+    n.is_i64()
+        || n.is_u64()
+        || n.as_f64()
+            .is_some_and(|f| f.is_finite() && f.fract() == 0.0)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("number", Value::Number(_)) => true,
+        ("integer", Value::Number(n)) => is_integer(n),
+        _ => type_name(value) == name,
+    }
+}
+
+fn child(path: &str, token: &str) -> String {
+    format!("{}/{}", path, escape_pointer(token))
+}
+
+/// Short rendering of a value for messages.
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(40) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// Find the subschema with the given `$id`, or the given `$anchor` when
+/// `anchor` is set.
+fn find<'s>(schema: &'s Value, name: &str, anchor: bool) -> Option<&'s Value> {
+    let key = if anchor { "$anchor" } else { "$id" };
+    match schema {
+        Value::Object(map) => {
+            if map.get(key).and_then(Value::as_str) == Some(name) {
+                return Some(schema);
+            }
+            map.values().find_map(|v| find(v, name, anchor))
+        }
+        Value::Array(items) => items.iter().find_map(|v| find(v, name, anchor)),
+        _ => None,
+    }
+}
+
+/// Checks for the formats worth asserting on besides ipv4 and ipv6; others are
+/// annotations only.
+const FORMATS: &[(&str, &str)] = &[
+    ("date", r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])$"),
+    (
+        "time",
+        r"^([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)(\.\d+)?([zZ]|[+-]([01]\d|2[0-3]):[0-5]\d)$",
+    ),
+    (
+        "date-time",
+        r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])[tT ]([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)(\.\d+)?([zZ]|[+-]([01]\d|2[0-3]):[0-5]\d)$",
+    ),
+    ("email", r"^[^\s@]+@[^\s@]+\.[^\s@]+$"),
+    (
+        "uuid",
+        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+    ),
+    ("uri", r"^[A-Za-z][A-Za-z0-9+.-]*:\S*$"),
+];
+
+impl<'s> Validator<'s> {
+    /// Report a violation of `keyword` in the schema at `schema_path`.
+    fn fail(&mut self, instance_path: &str, schema_path: &str, keyword: &str, message: String) {
+        let schema_path = format!("{}/{}", schema_path, keyword);
+        self.report(instance_path, schema_path, keyword, message);
+    }
+
+    fn report(&mut self, instance_path: &str, schema_path: String, keyword: &str, message: String) {
+        if self.errors.len() >= MAX_ERRORS {
+            self.truncated = true;
+            return;
+        }
+        self.errors.push(Violation {
+            instance_path: instance_path.to_string(),
+            schema_path,
+            keyword: keyword.to_string(),
+            message,
+        });
+    }
+
+    fn regex(&mut self, pattern: &'s str) -> Result<&Regex, String> {
+        if !self.regexes.contains_key(pattern) {
+            let regex = Regex::new(pattern)
+                .map_err(|e| format!("Invalid pattern \"{}\": {}", pattern, e))?;
+            self.regexes.insert(pattern, regex);
+        }
+        Ok(&self.regexes[pattern])
+    }
+
+    /// Whether the instance satisfies the schema, without reporting anything.
+    fn passes(
+        &mut self,
+        schema: &'s Value,
+        instance: &Value,
+        schema_path: &str,
+        depth: usize,
+    ) -> Result<bool, String> {
+        let saved = std::mem::take(&mut self.errors);
+        let truncated = self.truncated;
+        self.check(schema, instance, "", schema_path, depth)?;
+        let failed = !self.errors.is_empty();
+        self.errors = saved;
+        self.truncated = truncated;
+        Ok(!failed)
+    }
+
+    fn resolve(&self, reference: &str) -> Result<&'s Value, String> {
+        let (base, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let document = match base {
+            "" => Some(self.root),
+            id => find(self.root, id, false),
+        };
+        let target = document.and_then(|doc| match fragment {
+            "" => Some(doc),
+            f if f.starts_with('/') => resolve_pointer(doc, f),
+            anchor => find(doc, anchor, true).or_else(|| find(doc, &format!("#{}", anchor), false)),
+        });
+        target.ok_or_else(|| format!("Unresolvable $ref: {}", reference))
+    }
+
+    fn check(
+        &mut self,
+        schema: &'s Value,
+        instance: &Value,
+        path: &str,
+        schema_path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("Schema recursion too deep at {}", schema_path));
+        }
+        let obj = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => {
+                let message = "No value is allowed here".to_string();
+                self.report(path, schema_path.to_string(), "false", message);
+                return Ok(());
+            }
+            Value::Object(obj) => obj,
+            _ => {
+                return Err(format!(
+                    "Schema at \"{}\" must be an object or a boolean",
+                    schema_path
+                ));
+            }
+        };
+        let at = |keyword: &str| format!("{}/{}", schema_path, keyword);
+
+        if let Some(reference) = obj.get("$ref") {
+            let reference = reference.as_str().ok_or("$ref must be a string")?;
+            let target = self.resolve(reference)?;
+            let key = (target as *const Value, instance as *const Value);
+            if self.refs.contains(&key) {
+                return Err(format!("Circular $ref {} at {}", reference, at("$ref")));
+            }
+            self.refs.push(key);
+            let checked = self.check(target, instance, path, &at("$ref"), depth + 1);
+            self.refs.pop();
+            checked?;
+        }
+        if let Some(types) = obj.get("type") {
+            let names: Vec<&str> = match types {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => return Err(format!("Invalid type at \"{}\"", schema_path)),
+            };
+            if !names.iter().any(|name| has_type(instance, name)) {
+                let message = format!(
+                    "Expected {}, got {}",
+                    names.join(" or "),
+                    type_name(instance)
+                );
+                self.fail(path, schema_path, "type", message);
+            }
+        }
+        if let Some(options) = obj.get("enum") {
+            let options = options.as_array().ok_or("enum must be an array")?;
+            if !options.iter().any(|o| same(o, instance)) {
+                let message = format!("{} is not one of the allowed values", short(instance));
+                self.fail(path, schema_path, "enum", message);
+            }
+        }
+        if let Some(expected) = obj.get("const")
+            && !same(expected, instance)
+        {
+            let message = format!("Expected {}, got {}", short(expected), short(instance));
+            self.fail(path, schema_path, "const", message);
+        }
+
+        match instance {
+            Value::Number(n) => {
+                self.number(obj, n.as_f64().unwrap_or(f64::NAN), path, schema_path)?
+            }
+            Value::String(s) => self.string(obj, s, path, schema_path)?,
+            Value::Array(items) => self.array(obj, items, path, schema_path, depth)?,
+            Value::Object(map) => self.object(obj, instance, map, path, schema_path, depth)?,
+            _ => {}
+        }
+
+        if let Some(all) = obj.get("allOf") {
+            let all = all.as_array().ok_or("allOf must be an array")?;
+            for (i, sub) in all.iter().enumerate() {
+                self.check(
+                    sub,
+                    instance,
+                    path,
+                    &child(&at("allOf"), &i.to_string()),
+                    depth + 1,
+                )?;
+            }
+        }
+        if let Some(any) = obj.get("anyOf") {
+            let any = any.as_array().ok_or("anyOf must be an array")?;
+            let mut matched = false;
+            for (i, sub) in any.iter().enumerate() {
+                if self.passes(
+                    sub,
+                    instance,
+                    &child(&at("anyOf"), &i.to_string()),
+                    depth + 1,
+                )? {
+                    matched = true;
+                    break;
+                }
+            }
+            if !matched {
+                let message = "Value does not match any of the anyOf schemas".to_string();
+                self.fail(path, schema_path, "anyOf", message);
+            }
+        }
+        if let Some(one) = obj.get("oneOf") {
+            let one = one.as_array().ok_or("oneOf must be an array")?;
+            let mut matched = 0;
+            for (i, sub) in one.iter().enumerate() {
+                if self.passes(
+                    sub,
+                    instance,
+                    &child(&at("oneOf"), &i.to_string()),
+                    depth + 1,
+                )? {
+                    matched += 1;
+                }
+            }
+            if matched != 1 {
+                let message = format!("Value matches {} of the oneOf schemas, expected 1", matched);
+                self.fail(path, schema_path, "oneOf", message);
+            }
+        }
+        if let Some(not) = obj.get("not")
+            && self.passes(not, instance, &at("not"), depth + 1)?
+        {
+            let message = "Value must not match the \"not\" schema".to_string();
+            self.fail(path, schema_path, "not", message);
+        }
+        if let Some(condition) = obj.get("if") {
+            let branch = if self.passes(condition, instance, &at("if"), depth + 1)? {
+                "then"
+            } else {
+                "else"
+            };
+            if let Some(sub) = obj.get(branch) {
+                self.check(sub, instance, path, &at(branch), depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn number(
+        &mut self,
+        obj: &'s Map<String, Value>,
+        n: f64,
+        path: &str,
+        schema_path: &str,
+    ) -> Result<(), String> {
+        let limit = |keyword: &str| -> Result<Option<f64>, String> {
+            match obj.get(keyword) {
+                None => Ok(None),
+                Some(Value::Number(limit)) => Ok(limit.as_f64()),
+                // Draft-04 boolean exclusive bounds are not supported.
+                Some(Value::Bool(_)) if keyword.starts_with("exclusive") => Ok(None),
+                Some(_) => Err(format!("{} must be a number", keyword)),
+            }
+        };
+        let bounds = [
+            ("minimum", ">="),
+            ("maximum", "<="),
+            ("exclusiveMinimum", ">"),
+            ("exclusiveMaximum", "<"),
+        ];
+        for (keyword, op) in bounds {
+            let Some(l) = limit(keyword)? else {
+                continue;
+            };
+            let ok = match op {
+                ">=" => n >= l,
+                "<=" => n <= l,
+                ">" => n > l,
+                _ => n < l,
+            };
+            if !ok {
+                self.fail(
+                    path,
+                    schema_path,
+                    keyword,
+                    format!("Expected a value {} {}, got {}", op, l, n),
+                );
+            }
+        }
+        if let Some(m) = limit("multipleOf")?.filter(|m| *m > 0.0) {
+            let quotient = n / m;
+            if (quotient - quotient.round()).abs() > 1e-9 * quotient.abs().max(1.0) {
+                self.fail(
+                    path,
+                    schema_path,
+                    "multipleOf",
+                    format!("{} is not a multiple of {}", n, m),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn string(
+        &mut self,
+        obj: &'s Map<String, Value>,
+        s: &str,
+        path: &str,
+        schema_path: &str,
+    ) -> Result<(), String> {
+        let len = s.chars().count();
+        if let Some(min) = obj.get("minLength").and_then(Value::as_u64)
+            && (len as u64) < min
+        {
+            self.fail(
+                path,
+                schema_path,
+                "minLength",
+                format!("Expected at least {} characters, got {}", min, len),
+            );
+        }
+        if let Some(max) = obj.get("maxLength").and_then(Value::as_u64)
+            && len as u64 > max
+        {
+            self.fail(
+                path,
+                schema_path,
+                "maxLength",
+                format!("Expected at most {} characters, got {}", max, len),
+            );
+        }
+        if let Some(pattern) = obj.get("pattern") {
+            let pattern = pattern.as_str().ok_or("pattern must be a string")?;
+            if !self.regex(pattern)?.is_match(s) {
+                self.fail(
+                    path,
+                    schema_path,
+                    "pattern",
+                    format!("Does not match pattern \"{}\"", pattern),
+                );
+            }
+        }
+        if let Some(format) = obj.get("format").and_then(Value::as_str) {
+            let valid = match format {
+                "ipv4" => Some(s.parse::<std::net::Ipv4Addr>().is_ok()),
+                "ipv6" => Some(s.parse::<std::net::Ipv6Addr>().is_ok()),
+                _ => match FORMATS.iter().find(|(name, _)| *name == format) {
+                    Some((_, pattern)) => Some(self.regex(pattern)?.is_match(s)),
+                    None => None,
+                },
+            };
+            if valid == Some(false) {
+                self.fail(
+                    path,
+                    schema_path,
+                    "format",
+                    format!("Not a valid {}", format),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn array(
+        &mut self,
+        obj: &'s Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        schema_path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        let at = |keyword: &str| format!("{}/{}", schema_path, keyword);
+        // 2020-12 prefixItems + items, or draft-07 tuple items + additionalItems.
+        let (prefix, prefix_keyword, rest, rest_keyword) =
+            match (obj.get("prefixItems"), obj.get("items")) {
+                (Some(Value::Array(prefix)), rest) => {
+                    (prefix.as_slice(), "prefixItems", rest, "items")
+                }
+                (_, Some(Value::Array(tuple))) => (
+                    tuple.as_slice(),
+                    "items",
+                    obj.get("additionalItems"),
+                    "additionalItems",
+                ),
+                (_, rest) => (&[][..], "prefixItems", rest, "items"),
+            };
+        for (i, (sub, item)) in prefix.iter().zip(items).enumerate() {
+            let index = i.to_string();
+            self.check(
+                sub,
+                item,
+                &child(path, &index),
+                &child(&at(prefix_keyword), &index),
+                depth + 1,
+            )?;
+        }
+        if let Some(sub) = rest {
+            for (i, item) in items.iter().enumerate().skip(prefix.len()) {
+                self.check(
+                    sub,
+                    item,
+                    &child(path, &i.to_string()),
+                    &at(rest_keyword),
+                    depth + 1,
+                )?;
+            }
+        }
+        if let Some(min) = obj.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            self.fail(
+                path,
+                schema_path,
+                "minItems",
+                format!("Expected at least {} items, got {}", min, items.len()),
+            );
+        }
+        if let Some(max) = obj.get("maxItems").and_then(Value::as_u64)
+            && items.len() as u64 > max
+        {
+            self.fail(
+                path,
+                schema_path,
+                "maxItems",
+                format!("Expected at most {} items, got {}", max, items.len()),
+            );
+        }
+        if obj.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = (0..items.len())
+                .find_map(|j| (0..j).find(|&i| same(&items[i], &items[j])).map(|i| (i, j)));
+            if let Some((i, j)) = duplicate {
+                self.fail(
+                    path,
+                    schema_path,
+                    "uniqueItems",
+                    format!("Items {} and {} are equal", i, j),
+                );
+            }
+        }
+        if let Some(contains) = obj.get("contains") {
+            let mut count = 0u64;
+            for item in items {
+                if self.passes(contains, item, &at("contains"), depth + 1)? {
+                    count += 1;
+                }
+            }
+            let min = obj.get("minContains").and_then(Value::as_u64).unwrap_or(1);
+            if count < min {
+                let keyword = if obj.contains_key("minContains") {
+                    "minContains"
+                } else {
+                    "contains"
+                };
+                self.fail(
+                    path,
+                    schema_path,
+                    keyword,
+                    format!("Expected at least {} matching items, got {}", min, count),
+                );
+            }
+            if let Some(max) = obj.get("maxContains").and_then(Value::as_u64)
+                && count > max
+            {
+                self.fail(
+                    path,
+                    schema_path,
+                    "maxContains",
+                    format!("Expected at most {} matching items, got {}", max, count),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn object(
+        &mut self,
+        obj: &'s Map<String, Value>,
+        instance: &Value,
+        map: &Map<String, Value>,
+        path: &str,
+        schema_path: &str,
+        depth: usize,
+    ) -> Result<(), String> {
+        let at = |keyword: &str| format!("{}/{}", schema_path, keyword);
+        if let Some(required) = obj.get("required") {
+            let required = required.as_array().ok_or("required must be an array")?;
+            for name in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(name) {
+                    self.fail(
+                        path,
+                        schema_path,
+                        "required",
+                        format!("Missing required property \"{}\"", name),
+                    );
+                }
+            }
+        }
+        let properties = obj.get("properties").and_then(Value::as_object);
+        let patterns = obj.get("patternProperties").and_then(Value::as_object);
+        for (key, value) in map {
+            let value_path = child(path, key);
+            let mut matched = false;
+            if let Some(sub) = properties.and_then(|p| p.get(key)) {
+                matched = true;
+                self.check(
+                    sub,
+                    value,
+                    &value_path,
+                    &child(&at("properties"), key),
+                    depth + 1,
+                )?;
+            }
+            for (pattern, sub) in patterns.into_iter().flatten() {
+                if self.regex(pattern)?.is_match(key) {
+                    matched = true;
+                    self.check(
+                        sub,
+                        value,
+                        &value_path,
+                        &child(&at("patternProperties"), pattern),
+                        depth + 1,
+                    )?;
+                }
+            }
+            match obj.get("additionalProperties") {
+                Some(Value::Bool(false)) if !matched => {
+                    let message = format!("Unexpected property \"{}\"", key);
+                    self.fail(&value_path, schema_path, "additionalProperties", message);
+                }
+                Some(sub) if !matched => {
+                    self.check(
+                        sub,
+                        value,
+                        &value_path,
+                        &at("additionalProperties"),
+                        depth + 1,
+                    )?;
+                }
+                _ => {}
+            }
+            if let Some(names) = obj.get("propertyNames") {
+                let name = Value::String(key.clone());
+                self.check(names, &name, &value_path, &at("propertyNames"), depth + 1)?;
+            }
+        }
+        if let Some(min) = obj.get("minProperties").and_then(Value::as_u64)
+            && (map.len() as u64) < min
+        {
+            self.fail(
+                path,
+                schema_path,
+                "minProperties",
+                format!("Expected at least {} properties, got {}", min, map.len()),
+            );
+        }
+        if let Some(max) = obj.get("maxProperties").and_then(Value::as_u64)
+            && map.len() as u64 > max
+        {
+            self.fail(
+                path,
+                schema_path,
+                "maxProperties",
+                format!("Expected at most {} properties, got {}", max, map.len()),
+            );
+        }
+        // Draft-07 `dependencies` holds both forms the 2020-12 keywords split up.
+        for keyword in ["dependentRequired", "dependentSchemas", "dependencies"] {
+            let Some(dependencies) = obj.get(keyword).and_then(Value::as_object) else {
+                continue;
+            };
+            for (key, dependency) in dependencies.iter().filter(|(k, _)| map.contains_key(*k)) {
+                match dependency {
+                    Value::Array(names) => {
+                        for name in names.iter().filter_map(Value::as_str) {
+                            if !map.contains_key(name) {
+                                let message = format!("Property \"{}\" requires \"{}\"", key, name);
+                                self.fail(path, schema_path, keyword, message);
+                            }
+                        }
+                    }
+                    sub => self.check(sub, instance, path, &child(&at(keyword), key), depth + 1)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validate an instance against a JSON Schema (draft-07 or 2020-12 core and
+/// validation keywords; `$ref` within the schema document). Err if the schema
+/// itself is invalid.
+pub(crate) fn validate(instance: &Value, schema: &Value) -> Result<(Vec<Violation>, bool), String> {
+    let mut validator = Validator {
+        root: schema,
+        regexes: HashMap::new(),
+        errors: Vec::new(),
+        truncated: false,
+        refs: Vec::new(),
+    };
+    validator.check(schema, instance, "", "#", 0)?;
+    Ok((validator.errors, validator.truncated))
+}
+
+/// Validate a JSON document against a JSON Schema (draft-07 or 2020-12).
+/// Supports type, enum, const, the numeric, string, array and object keywords,
+/// allOf/anyOf/oneOf/not, if/then/else, `$ref` to `#/...` pointers, `$anchor`s
+/// and `$id`s inside the schema, and the date, time, date-time, email, uuid,
+/// uri, ipv4 and ipv6 formats; unevaluated* keywords are ignored.
+/// Returns JSON {valid, errors: [{instancePath, schemaPath, keyword, message}],
+/// truncated}, or {error} if either input or the schema itself is invalid.
+#[wasm_bindgen]
+pub fn json_validate_schema(json_str: &str, schema_json: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let instance: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid JSON: {}", e)),
+    };
+    let schema: Value = match serde_json::from_str(schema_json) {
+        Ok(v) => v,
+        Err(e) => return error(format!("Invalid schema JSON: {}", e)),
+    };
+    let _timer = crate::logging::timer("json_validate_schema");
+    match validate(&instance, &schema) {
+        Ok((errors, truncated)) => serde_json::json!({
+            "valid": errors.is_empty(),
+            "errors": errors,
+            "truncated": truncated,
+        })
+        .to_string(),
+        Err(e) => error(format!("Invalid schema: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(instance: Value, schema: Value) -> Vec<(String, String)> {
+        let (errors, _) = validate(&instance, &schema).unwrap();
+        errors
+            .into_iter()
+            .map(|v| (v.instance_path, v.keyword))
+            .collect()
+    }
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(p, k)| (p.to_string(), k.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_keywords() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "name", "role"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "name": {"type": "string", "minLength": 2, "pattern": "^[A-Z]"},
+                "email": {"type": "string", "format": "email"},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "uniqueItems": true, "maxItems": 3},
+                "role": {"const": "admin"},
+            },
+            "additionalProperties": false,
+        });
+        let valid = json!({"id": 1.0, "name": "Ann", "email": "a@x.io", "tags": ["a", "b"], "role": "admin"});
+        assert!(errors(valid, schema.clone()).is_empty());
+        let invalid =
+            json!({"id": 0, "name": "a", "email": "nope", "tags": ["a", "c", "a"], "x/y": 1});
+        assert_eq!(
+            errors(invalid, schema),
+            pairs(&[
+                ("", "required"),
+                ("/id", "minimum"),
+                ("/name", "minLength"),
+                ("/name", "pattern"),
+                ("/email", "format"),
+                ("/tags/1", "enum"),
+                ("/tags", "uniqueItems"),
+                ("/x~1y", "additionalProperties"),
+            ])
+        );
+        assert_eq!(
+            errors(json!("1"), json!({"type": ["integer", "null"]})),
+            pairs(&[("", "type")])
+        );
+        assert_eq!(errors(json!(0.3), json!({"multipleOf": 0.1})), vec![]);
+        assert_eq!(
+            errors(
+                json!([1, "a"]),
+                json!({"prefixItems": [{"type": "integer"}], "items": false})
+            ),
+            pairs(&[("/1", "false")])
+        );
+        assert_eq!(
+            errors(
+                json!([1, "a"]),
+                json!({"items": [true], "additionalItems": {"type": "integer"}})
+            ),
+            pairs(&[("/1", "type")])
+        );
+        assert_eq!(
+            errors(json!([1, 2]), json!({"contains": {"const": 3}})),
+            pairs(&[("", "contains")])
+        );
+    }
+
+    #[test]
+    fn test_validate_combinators_and_refs() {
+        let schema = json!({
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+                    "required": ["v"],
+                },
+                "named": {"$anchor": "name", "type": "string"},
+            },
+            "type": "object",
+            "properties": {
+                "tree": {"$ref": "#/$defs/node"},
+                "label": {"$ref": "#name"},
+                "n": {"oneOf": [{"type": "integer"}, {"minimum": 0}]},
+                "kind": {"anyOf": [{"const": "a"}, {"const": "b"}]},
+                "x": {"not": {"type": "null"}},
+            },
+            "if": {"properties": {"kind": {"const": "a"}}},
+            "then": {"required": ["x"]},
+            "dependentRequired": {"label": ["kind"]},
+        });
+        let ok =
+            json!({"tree": {"v": 1, "children": [{"v": 2}]}, "label": "l", "n": -1, "kind": "b"});
+        assert!(errors(ok, schema.clone()).is_empty());
+        let bad =
+            json!({"tree": {"v": 1, "children": [{}]}, "label": 1, "n": 2, "kind": "a", "x": null});
+        assert_eq!(
+            errors(bad, schema.clone()),
+            pairs(&[
+                ("/tree/children/0", "required"),
+                ("/label", "type"),
+                ("/n", "oneOf"),
+                ("/x", "not"),
+            ])
+        );
+        assert_eq!(
+            errors(json!({"kind": "a"}), schema),
+            pairs(&[("", "required")])
+        );
+
+        let out: Value =
+            serde_json::from_str(&json_validate_schema("1", r##"{"$ref": "#/nope"}"##)).unwrap();
+        assert_eq!(out["error"], "Invalid schema: Unresolvable $ref: #/nope");
+        let out: Value =
+            serde_json::from_str(&json_validate_schema("1", r##"{"$ref": "#"}"##)).unwrap();
+        assert_eq!(
+            out["error"],
+            "Invalid schema: Circular $ref # at #/$ref/$ref"
+        );
+        let out: Value = serde_json::from_str(&json_validate_schema(
+            "[1]",
+            r#"{"items": {"type": "string"}}"#,
+        ))
+        .unwrap();
+        assert_eq!(out["valid"], false);
+        assert_eq!(out["errors"][0]["schemaPath"], "#/items/type");
+        assert_eq!(out["errors"][0]["message"], "Expected string, got integer");
+    }
+
+    #[test]
+    fn test_cyclic_refs() {
+        let cyclic = json!({
+            "$defs": {
+                "a": {"anyOf": [{"$ref": "#/$defs/b"}]},
+                "b": {"not": {"$ref": "#/$defs/a"}}
+            },
+            "properties": {"x": {"$ref": "#/$defs/a"}}
+        });
+        let err = validate(&json!({"x": 1}), &cyclic).unwrap_err();
+        assert!(err.starts_with("Circular $ref #/$defs/a at "), "{}", err);
+        // A recursive schema is fine while each step goes one level into the instance.
+        let tree = json!({
+            "$defs": {"node": {"type": "array", "items": {"$ref": "#/$defs/node"}}},
+            "$ref": "#/$defs/node"
+        });
+        let mut deep = json!([]);
+        for _ in 0..20 {
+            deep = json!([deep]);
+        }
+        assert!(validate(&deep, &tree).unwrap().0.is_empty());
+    }
+}
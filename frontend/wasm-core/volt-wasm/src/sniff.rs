@@ -0,0 +1,289 @@
+use wasm_bindgen::prelude::*;
+
+/// Only the start of a large body is inspected; JSON is parsed in full up to
+/// the same size.
+const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Lines looked at by the YAML and CSV heuristics.
+const SAMPLE_LINES: usize = 50;
+
+const HTML_TAGS: &[&str] = &[
+    "<html", "<head", "<body", "<div", "<p>", "<span", "<script", "<title", "<meta", "<a ",
+    "<table", "<br",
+];
+
+/// Guess the format of a response body so the viewer can pick a formatter when
+/// the Content-Type is wrong or missing.
+/// content_type: the declared Content-Type, used to settle ambiguous bodies
+/// (a short plain line is also valid YAML or CSV).
+/// Returns JSON {language, confidence, declared, mismatch} where language is
+/// "json", "xml", "html", "yaml", "csv", "plain" or "binary", confidence is
+/// 0–1, declared is the language the Content-Type names (or null), and
+/// mismatch is true when the body is clearly something else.
+#[wasm_bindgen]
+pub fn detect_body_language(bytes: &[u8], content_type: Option<String>) -> String {
+    let declared = content_type.as_deref().and_then(declared_language);
+    let (mut language, mut confidence) = detect(bytes);
+    if let Some(declared) = declared
+        && declared != language
+        && confidence < 0.6
+        && (declared != "binary" || bytes.is_empty())
+    {
+        // Weak evidence: trust the server.
+        language = declared;
+        confidence = 0.6;
+    }
+    serde_json::json!({
+        "language": language,
+        "confidence": (confidence * 100.0).round() / 100.0,
+        "declared": declared,
+        "mismatch": declared.is_some_and(|d| d != language),
+    })
+    .to_string()
+}
+
+fn detect(bytes: &[u8]) -> (&'static str, f64) {
+    if bytes.is_empty() {
+        return ("plain", 0.0);
+    }
+    match crate::data_uri::sniff(bytes) {
+        "image/svg+xml" => return ("xml", 0.95),
+        "application/octet-stream" => {}
+        _ => return ("binary", 1.0),
+    }
+    let sample = &bytes[..bytes.len().min(SAMPLE_BYTES)];
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        // A multi-byte character cut off by the sample boundary is fine.
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return ("binary", 0.9),
+    };
+    let controls = text
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\u{c}'))
+        .count();
+    if text.contains('\0') || controls * 20 > text.chars().count() {
+        return ("binary", 0.9);
+    }
+
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let lower: String = text.chars().take(1024).collect::<String>().to_lowercase();
+
+    if text.starts_with('{') || text.starts_with('[') {
+        let complete = bytes.len() <= SAMPLE_BYTES;
+        if complete && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() {
+            return ("json", 1.0);
+        }
+        // Cut off by the sample, or almost-JSON with a trailing comma.
+        if text.contains("\":") || text.starts_with("[{") || text.starts_with("[\"") {
+            return ("json", if complete { 0.7 } else { 0.9 });
+        }
+    }
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return ("html", 1.0);
+    }
+    if text.starts_with('<') {
+        if lower.starts_with("<?xml") {
+            return ("xml", 1.0);
+        }
+        let tags = HTML_TAGS.iter().filter(|t| lower.contains(*t)).count();
+        if tags >= 2 {
+            return ("html", 0.8);
+        }
+        if looks_like_xml(text) {
+            return ("xml", 0.85);
+        }
+        return ("html", 0.5);
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SAMPLE_LINES)
+        .collect();
+    let yaml = yaml_score(text, &lines);
+    let csv = csv_score(&lines);
+    match (yaml, csv) {
+        (y, c) if y >= c && y >= 0.6 => ("yaml", y),
+        (_, c) if c >= 0.6 => ("csv", c),
+        _ => ("plain", 0.5),
+    }
+}
+
+/// The declared type as one of the detected languages.
+fn declared_language(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next()?.trim().to_lowercase();
+    let (kind, subtype) = essence.split_once('/')?;
+    Some(match subtype {
+        "json" | "problem+json" => "json",
+        s if s.ends_with("+json") || s == "x-ndjson" || s == "ndjson" => "json",
+        "html" | "xhtml+xml" => "html",
+        "xml" => "xml",
+        s if s.ends_with("+xml") => "xml",
+        "yaml" | "x-yaml" => "yaml",
+        "csv" | "tab-separated-values" => "csv",
+        _ if kind == "text" => "plain",
+        _ if matches!(kind, "image" | "audio" | "video" | "font") => "binary",
+        "octet-stream" | "pdf" | "zip" | "gzip" | "protobuf" | "x-protobuf" | "grpc" => "binary",
+        _ => return None,
+    })
+}
+
+/// A root element that is closed again: `<a ...>...</a>` or `<a/>`.
+fn looks_like_xml(text: &str) -> bool {
+    let name: String = text[1..]
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, ':' | '-' | '_' | '.'))
+        .collect();
+    !name.is_empty() && (text.contains(&format!("</{}>", name)) || text.trim_end().ends_with("/>"))
+}
+
+/// Share of lines that read as YAML mappings, list items or comments. A single
+/// `key: value` line scores low: it is just as likely plain text.
+fn yaml_score(text: &str, lines: &[&str]) -> f64 {
+    let mapping = |line: &str| {
+        let line = line.trim_start().trim_start_matches("- ");
+        line.split_once(':').is_some_and(|(key, rest)| {
+            !key.is_empty()
+                && !key.contains(' ')
+                && !key.contains(['{', '<', '('])
+                && (rest.is_empty() || rest.starts_with(' '))
+        })
+    };
+    let document = text.starts_with("---");
+    if !document && !lines.iter().any(|l| mapping(l)) {
+        return 0.0;
+    }
+    if lines.len() < 2 {
+        return 0.4;
+    }
+    let yamlish = lines
+        .iter()
+        .filter(|l| {
+            let t = l.trim_start();
+            mapping(l) || t.starts_with("- ") || t == "-" || t.starts_with('#') || t == "---"
+        })
+        .count();
+    let share = yamlish as f64 / lines.len() as f64;
+    if share < 0.8 {
+        return 0.0;
+    }
+    (0.5 + share * 0.4 + if document { 0.1 } else { 0.0 }).min(0.95)
+}
+
+/// Consistency of the field count across lines for the likeliest delimiter.
+fn csv_score(lines: &[&str]) -> f64 {
+    if lines.len() < 2 {
+        return 0.0;
+    }
+    [',', ';', '\t', '|']
+        .iter()
+        .map(|&delimiter| {
+            let counts: Vec<usize> = lines.iter().map(|l| fields_in(l, delimiter)).collect();
+            let header = counts[0];
+            if header < 2 {
+                return 0.0;
+            }
+            let same = counts.iter().filter(|&&c| c == header).count();
+            let share = same as f64 / counts.len() as f64;
+            if share < 0.8 {
+                0.0
+            } else {
+                (0.5 + share * 0.3 + (lines.len().min(10) as f64) * 0.01).min(0.9)
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Fields on one line, ignoring delimiters inside double quotes.
+fn fields_in(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    let mut fields = 1;
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields += 1,
+            _ => {}
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn detect_as(body: &[u8], content_type: Option<&str>) -> Value {
+        serde_json::from_str(&detect_body_language(
+            body,
+            content_type.map(str::to_string),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detect_body_language() {
+        for (body, language) in [
+            (&b"\xef\xbb\xbf {\"a\": [1, 2]}"[..], "json"),
+            (b"[{\"a\": 1},]", "json"),
+            (b"<!DOCTYPE html><html><body>x</body></html>", "html"),
+            (b"<div><p>Hello</p><span>x</span></div>", "html"),
+            (b"<?xml version=\"1.0\"?><a/>", "xml"),
+            (b"<feed xmlns=\"x\"><entry/></feed>", "xml"),
+            (b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", "xml"),
+            (
+                b"openapi: 3.0.0\ninfo:\n  title: API\n  version: 1\npaths: {}\n",
+                "yaml",
+            ),
+            (b"---\n- a\n- b: 1\n", "yaml"),
+            (
+                b"id,name,email\n1,Ann,\"a@x.io\"\n2,\"Bo, Jr\",b@x.io\n",
+                "csv",
+            ),
+            (b"id\tname\n1\tAnn\n2\tBo\n", "csv"),
+            (b"Internal Server Error", "plain"),
+            (b"\x89PNG\r\n\x1a\n\x00\x00", "binary"),
+            (b"\x00\x01\x02\x03hello", "binary"),
+            (b"\xff\xfe\xfd invalid utf8", "binary"),
+        ] {
+            let out = detect_as(body, None);
+            assert_eq!(
+                out["language"],
+                language,
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+        }
+        assert_eq!(detect_as(b"{\"a\":1}", None)["confidence"], 1.0);
+    }
+
+    #[test]
+    fn test_detect_body_language_with_content_type() {
+        let wrong = detect_as(b"<html><body>502</body></html>", Some("application/json"));
+        assert_eq!(wrong["language"], "html");
+        assert_eq!(wrong["declared"], "json");
+        assert_eq!(wrong["mismatch"], true);
+
+        let vendor = detect_as(
+            b"{\"ok\":true}",
+            Some("application/vnd.api+json; charset=utf-8"),
+        );
+        assert_eq!(vendor["declared"], "json");
+        assert_eq!(vendor["mismatch"], false);
+
+        // A single line is ambiguous, so the declared type wins.
+        let yaml = detect_as(b"status: ok", Some("application/yaml"));
+        assert_eq!(yaml["language"], "yaml");
+        assert_eq!(yaml["confidence"], 0.6);
+        let csv = detect_as(b"a,b", Some("text/csv"));
+        assert_eq!(csv["language"], "csv");
+        assert_eq!(detect_as(b"", Some("image/png"))["language"], "binary");
+        assert_eq!(
+            detect_as(b"plain words", Some("image/png"))["language"],
+            "plain"
+        );
+    }
+}
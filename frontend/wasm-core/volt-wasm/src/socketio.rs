@@ -0,0 +1,352 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::base64_decode;
+
+const ENGINE_TYPES: [&str; 7] = [
+    "open", "close", "ping", "pong", "message", "upgrade", "noop",
+];
+const SOCKET_TYPES: [&str; 7] = [
+    "connect",
+    "disconnect",
+    "event",
+    "ack",
+    "connect_error",
+    "binary_event",
+    "binary_ack",
+];
+/// Record separator between packets in an HTTP long-polling payload.
+const PAYLOAD_SEPARATOR: char = '\u{1e}';
+
+/// Parse one Engine.IO (v4) packet.
+/// "b<base64>" packets (binary over a text transport) are reported with binary: true.
+/// Returns JSON {type, typeName, data, binary} or {error}.
+#[wasm_bindgen]
+pub fn engineio_parse(packet: &str) -> String {
+    match parse_engine(packet) {
+        Ok(v) => v.to_string(),
+        Err(e) => error_json(&e),
+    }
+}
+
+fn parse_engine(packet: &str) -> Result<Value, String> {
+    if let Some(b64) = packet.strip_prefix('b') {
+        if base64_decode(b64).is_none() {
+            return Err("Binary packet is not valid base64".to_string());
+        }
+        return Ok(serde_json::json!({
+            "type": 4, "typeName": "message", "data": b64, "binary": true,
+        }));
+    }
+    let mut chars = packet.chars();
+    let t = chars
+        .next()
+        .and_then(|c| c.to_digit(10))
+        .filter(|d| (*d as usize) < ENGINE_TYPES.len())
+        .ok_or_else(|| format!("Unknown Engine.IO packet type in \"{}\"", packet))?;
+    let data = chars.as_str();
+    // The open packet carries the handshake as JSON.
+    let data = if t == 0 {
+        serde_json::from_str(data).unwrap_or(Value::from(data))
+    } else {
+        Value::from(data)
+    };
+    Ok(serde_json::json!({
+        "type": t, "typeName": ENGINE_TYPES[t as usize], "data": data, "binary": false,
+    }))
+}
+
+/// Split an HTTP long-polling payload into its Engine.IO packets.
+/// Returns JSON array of parsed packets (as `engineio_parse`).
+#[wasm_bindgen]
+pub fn engineio_parse_payload(payload: &str) -> String {
+    let packets: Vec<Value> = payload
+        .split(PAYLOAD_SEPARATOR)
+        .filter(|p| !p.is_empty())
+        .map(|p| parse_engine(p).unwrap_or_else(|e| serde_json::json!({ "error": e })))
+        .collect();
+    serde_json::to_string(&packets).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Build an Engine.IO packet. packet_type is a name ("message", "ping", ...) or digit.
+/// Returns the packet text, or an empty string for an unknown type.
+#[wasm_bindgen]
+pub fn engineio_build(packet_type: &str, data: &str) -> String {
+    match type_index(packet_type, &ENGINE_TYPES) {
+        Some(t) => format!("{}{}", t, data),
+        None => String::new(),
+    }
+}
+
+/// Parse a Socket.IO (v5) packet, e.g. `2/chat,17["message",{"a":1}]`.
+/// Returns JSON {type, typeName, namespace, ackId, attachments, data, event, args} or {error}.
+/// `event`/`args` are filled for event packets; binary packets keep their
+/// {"_placeholder": true, "num": n} markers (see `socketio_attach_binary`).
+#[wasm_bindgen]
+pub fn socketio_parse(packet: &str) -> String {
+    match parse_socket(packet) {
+        Ok(v) => v.to_string(),
+        Err(e) => error_json(&e),
+    }
+}
+
+fn parse_socket(packet: &str) -> Result<Value, String> {
+    let bytes = packet.as_bytes();
+    let t = bytes
+        .first()
+        .and_then(|b| (*b as char).to_digit(10))
+        .filter(|d| (*d as usize) < SOCKET_TYPES.len())
+        .ok_or_else(|| format!("Unknown Socket.IO packet type in \"{}\"", packet))?
+        as usize;
+    let mut rest = &packet[1..];
+
+    let mut attachments = 0;
+    if t == 5 || t == 6 {
+        let (count, after) = rest
+            .split_once('-')
+            .ok_or("Binary packet is missing its attachment count")?;
+        attachments = count
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid attachment count \"{}\"", count))?;
+        rest = after;
+    }
+
+    let mut namespace = "/".to_string();
+    if rest.starts_with('/') {
+        let end = rest.find(',').unwrap_or(rest.len());
+        namespace = rest[..end].to_string();
+        rest = rest.get(end + 1..).unwrap_or_default();
+    }
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let ack_id: Option<u64> = rest[..digits].parse().ok();
+    rest = &rest[digits..];
+
+    let data: Value = if rest.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(rest).map_err(|e| format!("Invalid packet payload: {}", e))?
+    };
+
+    let (event, args) = match (t, &data) {
+        (2 | 5, Value::Array(items)) if !items.is_empty() => {
+            (items[0].clone(), Value::Array(items[1..].to_vec()))
+        }
+        _ => (Value::Null, Value::Null),
+    };
+
+    Ok(serde_json::json!({
+        "type": t,
+        "typeName": SOCKET_TYPES[t],
+        "namespace": namespace,
+        "ackId": ack_id,
+        "attachments": attachments,
+        "data": data,
+        "event": event,
+        "args": args,
+    }))
+}
+
+/// Build a Socket.IO packet.
+/// packet_json: {type (name or number), namespace, ackId, data} or, for events,
+/// {event, args} in place of data. The attachment count for binary packets is
+/// taken from the placeholders in the data.
+/// Returns the packet text, or an empty string if the packet is invalid.
+#[wasm_bindgen]
+pub fn socketio_build(packet_json: &str) -> String {
+    let packet: Map<String, Value> = match serde_json::from_str(packet_json) {
+        Ok(p) => p,
+        Err(_) => return String::new(),
+    };
+    let t = match packet.get("type") {
+        Some(Value::Number(n)) => n.as_u64().map(|n| n as usize),
+        Some(Value::String(s)) => type_index(s, &SOCKET_TYPES),
+        _ => packet.contains_key("event").then_some(2),
+    };
+    let Some(t) = t.filter(|t| *t < SOCKET_TYPES.len()) else {
+        return String::new();
+    };
+
+    let data = match (packet.get("data"), packet.get("event")) {
+        (Some(d), _) => d.clone(),
+        (None, Some(event)) => {
+            let mut items = vec![event.clone()];
+            if let Some(Value::Array(args)) = packet.get("args") {
+                items.extend(args.iter().cloned());
+            }
+            Value::Array(items)
+        }
+        _ => Value::Null,
+    };
+
+    let mut out = t.to_string();
+    if t == 5 || t == 6 {
+        out.push_str(&format!("{}-", count_placeholders(&data)));
+    }
+    if let Some(ns) = packet.get("namespace").and_then(Value::as_str)
+        && ns != "/"
+        && !ns.is_empty()
+    {
+        out.push_str(ns);
+        out.push(',');
+    }
+    if let Some(id) = packet.get("ackId").and_then(Value::as_u64) {
+        out.push_str(&id.to_string());
+    }
+    if !data.is_null() {
+        out.push_str(&data.to_string());
+    }
+    out
+}
+
+/// Decode a WebSocket text message from a Socket.IO server: the Engine.IO
+/// framing plus, for message packets, the Socket.IO packet inside it.
+/// Returns JSON {engine: {...}, socket: {...} | null}.
+#[wasm_bindgen]
+pub fn socketio_decode(ws_text: &str) -> String {
+    let engine = match parse_engine(ws_text) {
+        Ok(e) => e,
+        Err(e) => return error_json(&e),
+    };
+    let socket = match (&engine["typeName"], &engine["data"], &engine["binary"]) {
+        (Value::String(name), Value::String(data), Value::Bool(false)) if name == "message" => {
+            parse_socket(data).unwrap_or_else(|e| serde_json::json!({ "error": e }))
+        }
+        _ => Value::Null,
+    };
+    serde_json::json!({ "engine": engine, "socket": socket }).to_string()
+}
+
+/// Replace binary placeholders in a parsed packet's data with the received
+/// attachments. attachments_json: JSON array of base64 strings, in order.
+/// Each placeholder becomes {"base64": "..."}; missing attachments stay as placeholders.
+/// Returns the data JSON with attachments filled in.
+#[wasm_bindgen]
+pub fn socketio_attach_binary(data_json: &str, attachments_json: &str) -> String {
+    let mut data: Value = match serde_json::from_str(data_json) {
+        Ok(d) => d,
+        Err(_) => return "null".to_string(),
+    };
+    let attachments: Vec<String> = serde_json::from_str(attachments_json).unwrap_or_default();
+    fill_placeholders(&mut data, &attachments);
+    data.to_string()
+}
+
+fn placeholder_num(v: &Value) -> Option<usize> {
+    let obj = v.as_object()?;
+    if obj.get("_placeholder") != Some(&Value::Bool(true)) {
+        return None;
+    }
+    obj.get("num")?.as_u64().map(|n| n as usize)
+}
+
+fn count_placeholders(v: &Value) -> usize {
+    if placeholder_num(v).is_some() {
+        return 1;
+    }
+    match v {
+        Value::Array(items) => items.iter().map(count_placeholders).sum(),
+        Value::Object(map) => map.values().map(count_placeholders).sum(),
+        _ => 0,
+    }
+}
+
+fn fill_placeholders(v: &mut Value, attachments: &[String]) {
+    if let Some(b64) = placeholder_num(v).and_then(|n| attachments.get(n)) {
+        *v = serde_json::json!({ "base64": b64 });
+        return;
+    }
+    match v {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|i| fill_placeholders(i, attachments)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|i| fill_placeholders(i, attachments)),
+        _ => {}
+    }
+}
+
+fn type_index(name: &str, names: &[&str]) -> Option<usize> {
+    let name = name.trim();
+    match name.parse::<usize>() {
+        Ok(n) if n < names.len() => Some(n),
+        Ok(_) => None,
+        Err(_) => names.iter().position(|n| n.eq_ignore_ascii_case(name)),
+    }
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(s: &str) -> Value {
+        serde_json::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_engineio_packets() {
+        let open = json(&engineio_parse(r#"0{"sid":"abc","pingInterval":25000}"#));
+        assert_eq!(open["typeName"], "open");
+        assert_eq!(open["data"]["sid"], "abc");
+        assert_eq!(json(&engineio_parse("2"))["typeName"], "ping");
+        assert_eq!(json(&engineio_parse("bAQID"))["binary"], true);
+        assert!(json(&engineio_parse("9x"))["error"].is_string());
+
+        let payload = json(&engineio_parse_payload("4hello\u{1e}2\u{1e}bAQID"));
+        assert_eq!(payload.as_array().unwrap().len(), 3);
+        assert_eq!(payload[0]["data"], "hello");
+        assert_eq!(engineio_build("pong", "probe"), "3probe");
+        assert_eq!(engineio_build("bogus", ""), "");
+    }
+
+    #[test]
+    fn test_socketio_parse() {
+        let p = json(&socketio_parse(r#"2/chat,17["message",{"a":1}]"#));
+        assert_eq!(p["typeName"], "event");
+        assert_eq!(p["namespace"], "/chat");
+        assert_eq!(p["ackId"], 17);
+        assert_eq!(p["event"], "message");
+        assert_eq!(p["args"], json(r#"[{"a":1}]"#));
+
+        let bin = json(&socketio_parse(
+            r#"51-["upload",{"_placeholder":true,"num":0}]"#,
+        ));
+        assert_eq!(bin["typeName"], "binary_event");
+        assert_eq!(bin["attachments"], 1);
+        assert_eq!(bin["namespace"], "/");
+
+        let connect = json(&socketio_parse("0"));
+        assert_eq!(connect["typeName"], "connect");
+        assert_eq!(connect["data"], Value::Null);
+        assert!(json(&socketio_parse(r#"2["bad"#))["error"].is_string());
+    }
+
+    #[test]
+    fn test_socketio_build_and_decode() {
+        let text = socketio_build(r#"{"namespace":"/chat","ackId":3,"event":"hi","args":[1]}"#);
+        assert_eq!(text, r#"2/chat,3["hi",1]"#);
+        let bin = socketio_build(
+            r#"{"type":"binary_event","data":["f",{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}]}"#,
+        );
+        assert!(bin.starts_with("52-[\"f\""));
+
+        let decoded = json(&socketio_decode(&format!("4{}", text)));
+        assert_eq!(decoded["engine"]["typeName"], "message");
+        assert_eq!(decoded["socket"]["event"], "hi");
+        assert_eq!(json(&socketio_decode("3"))["socket"], Value::Null);
+    }
+
+    #[test]
+    fn test_socketio_attach_binary() {
+        let out = json(&socketio_attach_binary(
+            r#"["f",{"file":{"_placeholder":true,"num":0}}]"#,
+            r#"["AQID"]"#,
+        ));
+        assert_eq!(out[1]["file"]["base64"], "AQID");
+    }
+}
@@ -0,0 +1,227 @@
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+/// CONNECT and CONNECTED frames do not use header escaping (STOMP 1.2).
+fn escapes_headers(command: &str) -> bool {
+    command != "CONNECT" && command != "CONNECTED"
+}
+
+/// Parse one STOMP frame (as received in a WebSocket text message).
+/// Header values are unescaped; when a header repeats, the first value wins
+/// (all values are kept in `headerList`). The body is cut to `content-length`
+/// bytes when present, otherwise at the first NUL.
+/// Returns JSON {command, headers, headerList: [[name, value]], body}, {heartbeat: true}
+/// for an EOL-only frame, or {error}.
+#[wasm_bindgen]
+pub fn stomp_parse(frame_text: &str) -> String {
+    match parse(frame_text) {
+        Ok(v) => v.to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+fn parse(frame: &str) -> Result<Value, String> {
+    // Heart-beats are bare EOLs.
+    let frame = frame.trim_start_matches(['\r', '\n']);
+    if frame.is_empty() || frame == "\0" {
+        return Ok(serde_json::json!({ "heartbeat": true }));
+    }
+
+    let mut rest = frame;
+    let mut next_line = || -> Option<&str> {
+        let end = rest.find('\n')?;
+        let line = rest[..end].strip_suffix('\r').unwrap_or(&rest[..end]);
+        rest = &rest[end + 1..];
+        Some(line)
+    };
+
+    let command = next_line().ok_or("Frame has no command line")?.to_string();
+    if command.is_empty() || !command.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid command \"{}\"", command));
+    }
+    let unescape = escapes_headers(&command);
+
+    let mut headers = Map::new();
+    let mut list = Vec::new();
+    loop {
+        let line = next_line().ok_or("Frame ended before the blank line after headers")?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed header line \"{}\"", line))?;
+        let (name, value) = if unescape {
+            (unescape_header(name)?, unescape_header(value)?)
+        } else {
+            (name.to_string(), value.to_string())
+        };
+        headers
+            .entry(name.clone())
+            .or_insert_with(|| Value::from(value.clone()));
+        list.push(serde_json::json!([name, value]));
+    }
+
+    let body = match headers.get("content-length").and_then(Value::as_str) {
+        Some(len) => {
+            let len: usize = len
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid content-length \"{}\"", len))?;
+            let bytes = rest.as_bytes();
+            if bytes.len() < len {
+                return Err(format!(
+                    "Body is shorter than content-length ({} < {})",
+                    bytes.len(),
+                    len
+                ));
+            }
+            String::from_utf8_lossy(&bytes[..len]).into_owned()
+        }
+        None => rest.split('\0').next().unwrap_or_default().to_string(),
+    };
+
+    Ok(serde_json::json!({
+        "command": command,
+        "headers": headers,
+        "headerList": list,
+        "body": body,
+    }))
+}
+
+fn unescape_header(s: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('c') => out.push(':'),
+            Some('\\') => out.push('\\'),
+            other => {
+                return Err(format!(
+                    "Invalid header escape \"\\{}\"",
+                    other.map(String::from).unwrap_or_default()
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn escape_header(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace(':', "\\c")
+}
+
+/// Build a STOMP frame ready to send as a WebSocket text message.
+/// headers_json: JSON object or array of [name, value] pairs (order is kept).
+/// A `content-length` header is added for non-empty bodies unless one is given.
+/// Returns the frame text terminated by NUL, or an empty string for an invalid command.
+#[wasm_bindgen]
+pub fn stomp_build(command: &str, headers_json: &str, body: &str) -> String {
+    let command = command.trim().to_uppercase();
+    if command.is_empty() || !command.chars().all(|c| c.is_ascii_uppercase()) {
+        return String::new();
+    }
+    let headers: Vec<(String, String)> = match serde_json::from_str::<Value>(headers_json) {
+        Ok(Value::Object(map)) => map.into_iter().map(|(k, v)| (k, value_text(v))).collect(),
+        Ok(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Array(pair) if pair.len() == 2 => {
+                    let mut it = pair.into_iter();
+                    Some((value_text(it.next()?), value_text(it.next()?)))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let escape = escapes_headers(&command);
+    let mut frame = format!("{}\n", command);
+    for (name, value) in &headers {
+        if escape {
+            frame.push_str(&format!(
+                "{}:{}\n",
+                escape_header(name),
+                escape_header(value)
+            ));
+        } else {
+            frame.push_str(&format!("{}:{}\n", name, value));
+        }
+    }
+    if !body.is_empty() && !headers.iter().any(|(n, _)| n == "content-length") {
+        frame.push_str(&format!("content-length:{}\n", body.len()));
+    }
+    frame.push('\n');
+    frame.push_str(body);
+    frame.push('\0');
+    frame
+}
+
+fn value_text(v: Value) -> String {
+    match v {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(frame: &str) -> Value {
+        serde_json::from_str(&stomp_parse(frame)).unwrap()
+    }
+
+    #[test]
+    fn test_stomp_parse() {
+        let f = parsed(
+            "MESSAGE\r\ndestination:/topic/a\\cb\nfoo:1\nfoo:2\nsubscription:0\n\nhello\0\n",
+        );
+        assert_eq!(f["command"], "MESSAGE");
+        assert_eq!(f["headers"]["destination"], "/topic/a:b");
+        assert_eq!(f["headers"]["foo"], "1");
+        assert_eq!(f["headerList"][2], serde_json::json!(["foo", "2"]));
+        assert_eq!(f["body"], "hello");
+
+        let with_nul = parsed("SEND\ncontent-length:3\n\na\0b\0");
+        assert_eq!(with_nul["body"], "a\0b");
+
+        assert_eq!(parsed("\n")["heartbeat"], true);
+        assert!(parsed("SEND\nbad header\n\n\0")["error"].is_string());
+        assert!(parsed("SEND\nx:\\t\n\n\0")["error"].is_string());
+    }
+
+    #[test]
+    fn test_stomp_build() {
+        let frame = stomp_build(
+            "send",
+            r#"[["destination","/queue/a:b"],["receipt","r-1"]]"#,
+            "héllo",
+        );
+        assert_eq!(
+            frame,
+            "SEND\ndestination:/queue/a\\cb\nreceipt:r-1\ncontent-length:6\n\nhéllo\0"
+        );
+        let connect = stomp_build("CONNECT", r#"{"accept-version":"1.2","host":"a:b"}"#, "");
+        assert_eq!(connect, "CONNECT\naccept-version:1.2\nhost:a:b\n\n\0");
+        assert_eq!(stomp_build("", "{}", ""), "");
+    }
+
+    #[test]
+    fn test_stomp_round_trip() {
+        let frame = stomp_build("SEND", r#"{"x":"line1\nline2\\"}"#, "{\"a\":1}");
+        let f = parsed(&frame);
+        assert_eq!(f["headers"]["x"], "line1\nline2\\");
+        assert_eq!(f["body"], "{\"a\":1}");
+    }
+}
@@ -0,0 +1,370 @@
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::datafile::parse_csv;
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TabularOptions {
+    /// Field separator; the first character is used.
+    delimiter: String,
+    /// The first record holds the column names. Without it, rows are read as
+    /// arrays and written without a header line.
+    header: bool,
+    /// Dotted column names ("address.city", "tags.0") stand for nested
+    /// objects and arrays: nested values are flattened into them when
+    /// writing and rebuilt from them when reading. Otherwise nested values
+    /// are written as JSON text.
+    flatten: bool,
+    /// Read numbers and booleans as JSON values and empty fields as null;
+    /// otherwise every field is a string.
+    parse_values: bool,
+    /// Columns to write, in order; every key found, in order of first
+    /// appearance, when empty.
+    columns: Vec<String>,
+}
+
+impl Default for TabularOptions {
+    fn default() -> Self {
+        TabularOptions {
+            delimiter: ",".to_string(),
+            header: true,
+            flatten: true,
+            parse_values: false,
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl TabularOptions {
+    fn delimiter(&self) -> char {
+        self.delimiter.chars().next().unwrap_or(',')
+    }
+
+    fn field(&self, text: String) -> Value {
+        if self.parse_values {
+            match text.as_str() {
+                "" => return Value::Null,
+                "true" => return Value::Bool(true),
+                "false" => return Value::Bool(false),
+                _ => {
+                    if let Ok(n) = serde_json::from_str::<Number>(&text) {
+                        return Value::Number(n);
+                    }
+                }
+            }
+        }
+        Value::String(text)
+    }
+}
+
+/// Whether the path `parts` can be created in `map` without replacing a value.
+fn fits(map: &Map<String, Value>, parts: &[&str]) -> bool {
+    match parts.split_first() {
+        None => true,
+        Some((first, rest)) => match map.get(*first) {
+            None => true,
+            Some(Value::Object(inner)) => fits(inner, rest),
+            Some(_) => false,
+        },
+    }
+}
+
+/// Insert a value under a dotted column name; a column that clashes with a
+/// value already there keeps its dotted name.
+fn insert_path(map: &mut Map<String, Value>, column: &str, value: Value) {
+    let parts: Vec<&str> = column.split('.').collect();
+    let (last, parents) = parts.split_last().unwrap_or((&column, &[]));
+    if parents.is_empty() || parts.iter().any(|p| p.is_empty()) || !fits(map, parents) {
+        map.insert(column.to_string(), value);
+        return;
+    }
+    let mut target = map;
+    for part in parents {
+        let slot = target
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Value::Object(inner) = slot else {
+            return;
+        };
+        target = inner;
+    }
+    target.insert(last.to_string(), value);
+}
+
+/// Objects keyed "0", "1", ... in order become arrays.
+fn rebuild_arrays(value: Value) -> Value {
+    let Value::Object(map) = value else {
+        return value;
+    };
+    let map: Map<String, Value> = map
+        .into_iter()
+        .map(|(k, v)| (k, rebuild_arrays(v)))
+        .collect();
+    let indexed = map.keys().enumerate().all(|(i, k)| *k == i.to_string());
+    if indexed && !map.is_empty() {
+        Value::Array(map.into_iter().map(|(_, v)| v).collect())
+    } else {
+        Value::Object(map)
+    }
+}
+
+/// Flatten nested objects and arrays into dotted columns; empty ones stay
+/// whole.
+fn flatten_into(prefix: &str, value: &Value, out: &mut Map<String, Value>) {
+    let path = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                flatten_into(&path(key), item, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                flatten_into(&path(&i.to_string()), item, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn write_record(out: &mut String, fields: &[String], delimiter: char) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+/// Read CSV (or TSV, with delimiter "\t") into JSON: an array with one
+/// object per row keyed by the header's column names, or one array of fields
+/// per row without a header.
+/// options_json: {delimiter (","), header (true), flatten (true: "a.b"
+/// columns become nested objects, "a.0" arrays), parseValues (false)}.
+/// Returns JSON {json (pretty), columns, rowCount} or {error}; rows with a
+/// different number of fields than the header are an error.
+#[wasm_bindgen]
+pub fn csv_to_json(csv_str: &str, options_json: &str) -> String {
+    let _timer = crate::logging::timer("csv_to_json");
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let options: TabularOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let mut records = match parse_csv(csv_str, options.delimiter()) {
+        Ok(records) => records,
+        Err(e) => return error(format!("Invalid CSV: {}", e)),
+    };
+    let columns: Vec<String> = if options.header && !records.is_empty() {
+        records
+            .remove(0)
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut rows = Vec::new();
+    for (i, record) in records.into_iter().enumerate() {
+        if !options.header {
+            let fields = record.into_iter().map(|f| options.field(f)).collect();
+            rows.push(Value::Array(fields));
+            continue;
+        }
+        if record.len() != columns.len() {
+            return error(format!(
+                "Row {} has {} fields, expected {}",
+                i + 1,
+                record.len(),
+                columns.len()
+            ));
+        }
+        let mut row = Map::new();
+        for (column, field) in columns.iter().zip(record) {
+            let value = options.field(field);
+            if options.flatten {
+                insert_path(&mut row, column, value);
+            } else {
+                row.insert(column.clone(), value);
+            }
+        }
+        if options.flatten {
+            row = row
+                .into_iter()
+                .map(|(k, v)| (k, rebuild_arrays(v)))
+                .collect();
+        }
+        rows.push(Value::Object(row));
+    }
+    serde_json::json!({
+        "json": serde_json::to_string_pretty(&rows).unwrap_or_default(),
+        "columns": columns,
+        "rowCount": rows.len(),
+    })
+    .to_string()
+}
+
+/// Write JSON as CSV: an array of objects (or a single object) becomes one
+/// row per object, with nested values flattened into dotted columns
+/// ("address.city", "tags.0"); arrays of arrays are written as they are.
+/// Null is an empty field and fields are quoted only when they must be.
+/// options_json: {delimiter (","), header (true), flatten (true), columns
+/// (default: every key, in order of first appearance)}.
+/// Returns JSON {csv, columns} or {error}.
+#[wasm_bindgen]
+pub fn json_to_csv(json_str: &str, options_json: &str) -> String {
+    let error = |msg: String| serde_json::json!({ "error": msg }).to_string();
+    let options: TabularOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let items = match serde_json::from_str(json_str) {
+        Ok(Value::Array(items)) => items,
+        Ok(object @ Value::Object(_)) => vec![object],
+        Ok(_) => return error("JSON must be an array or an object".to_string()),
+        Err(e) => return error(format!("Invalid JSON: {}", e)),
+    };
+    let delimiter = options.delimiter();
+    let mut out = String::new();
+    if items.iter().all(Value::is_array) && options.columns.is_empty() {
+        for item in &items {
+            let fields: Vec<String> = item
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|v| cell(Some(v)))
+                .collect();
+            write_record(&mut out, &fields, delimiter);
+        }
+        return serde_json::json!({ "csv": out, "columns": [] }).to_string();
+    }
+    let rows: Vec<Map<String, Value>> = items
+        .iter()
+        .map(|item| match item {
+            Value::Object(map) if !options.flatten => map.clone(),
+            _ => {
+                let mut row = Map::new();
+                flatten_into("", item, &mut row);
+                row
+            }
+        })
+        .collect();
+    let columns = if options.columns.is_empty() {
+        let mut columns: Vec<String> = Vec::new();
+        for key in rows.iter().flat_map(|row| row.keys()) {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        columns
+    } else {
+        options.columns.clone()
+    };
+    if options.header {
+        write_record(&mut out, &columns, delimiter);
+    }
+    for row in &rows {
+        let fields: Vec<String> = columns.iter().map(|c| cell(row.get(c))).collect();
+        write_record(&mut out, &fields, delimiter);
+    }
+    serde_json::json!({ "csv": out, "columns": columns }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn read(csv: &str, options: &str) -> Value {
+        let out: Value = serde_json::from_str(&csv_to_json(csv, options)).unwrap();
+        serde_json::from_str(out["json"].as_str().unwrap_or_else(|| panic!("{}", out))).unwrap()
+    }
+
+    fn write(json: Value, options: &str) -> String {
+        let out: Value = serde_json::from_str(&json_to_csv(&json.to_string(), options)).unwrap();
+        out["csv"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{}", out))
+            .to_string()
+    }
+
+    #[test]
+    fn test_csv_to_json() {
+        let csv = "id,name,address.city,tags.0,tags.1\n1,\"Doe, Jane\",Oslo,a,\n2,Bo,,b,c\n";
+        assert_eq!(
+            read(csv, r#"{"parseValues": true}"#),
+            json!([
+                {"id": 1, "name": "Doe, Jane", "address": {"city": "Oslo"}, "tags": ["a", null]},
+                {"id": 2, "name": "Bo", "address": {"city": null}, "tags": ["b", "c"]},
+            ])
+        );
+        assert_eq!(
+            read("a.b\tc\n1\t2\n", r#"{"delimiter": "\t", "flatten": false}"#),
+            json!([{"a.b": "1", "c": "2"}])
+        );
+        assert_eq!(
+            read("1,x\n2,y\n", r#"{"header": false}"#),
+            json!([["1", "x"], ["2", "y"]])
+        );
+
+        let out: Value = serde_json::from_str(&csv_to_json("a,b\n1\n", "")).unwrap();
+        assert_eq!(out["error"], "Row 1 has 1 fields, expected 2");
+        let out: Value = serde_json::from_str(&csv_to_json("a\n\"x", "")).unwrap();
+        assert_eq!(
+            out["error"],
+            "Invalid CSV: line 2: unterminated quoted field"
+        );
+    }
+
+    #[test]
+    fn test_json_to_csv() {
+        let rows = json!([
+            {"id": 1, "user": {"name": "Ann \"A\"", "tags": ["x", "y"]}, "note": "a,b"},
+            {"id": 2, "user": {"name": "Bo", "tags": []}, "extra": null, "note": "line\nbreak"},
+        ]);
+        assert_eq!(
+            write(rows.clone(), "{}"),
+            "id,user.name,user.tags.0,user.tags.1,note,user.tags,extra\n\
+             1,\"Ann \"\"A\"\"\",x,y,\"a,b\",,\n\
+             2,Bo,,,\"line\nbreak\",[],\n"
+        );
+        assert_eq!(
+            write(
+                rows,
+                r#"{"flatten": false, "columns": ["id", "user"], "delimiter": ";"}"#
+            ),
+            "id;user\n1;\"{\"\"name\"\":\"\"Ann \\\"\"A\\\"\"\"\",\"\"tags\"\":[\"\"x\"\",\"\"y\"\"]}\"\n2;\"{\"\"name\"\":\"\"Bo\"\",\"\"tags\"\":[]}\"\n"
+        );
+        assert_eq!(write(json!([[1, "a"], [2, null]]), "{}"), "1,a\n2,\n");
+        let out: Value = serde_json::from_str(&json_to_csv("3", "")).unwrap();
+        assert!(out["error"].is_string());
+    }
+
+    #[test]
+    fn test_csv_json_roundtrip() {
+        let rows = json!([
+            {"id": "1", "address": {"city": "Oslo", "zip": "0150"}, "tags": ["a", "b"]},
+            {"id": "2", "address": {"city": "Bergen", "zip": "5003"}, "tags": ["c", "d"]},
+        ]);
+        assert_eq!(read(&write(rows.clone(), "{}"), "{}"), rows);
+    }
+}
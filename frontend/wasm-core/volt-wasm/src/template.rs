@@ -0,0 +1,183 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, base64_encode, hex_encode, percent_decode, percent_encode};
+
+/// Transforms a variable's value; the argument comes from `{{name | filter:arg}}`.
+/// An error leaves the placeholder as written.
+type Apply = fn(&str, Option<&str>) -> Result<String, String>;
+
+struct Filter {
+    name: &'static str,
+    description: &'static str,
+    apply: Apply,
+}
+
+/// Filters usable as `{{name | filter}}`, applied left to right. To add one,
+/// append an entry here; parsing and `list_template_filters` pick it up.
+const FILTERS: &[Filter] = &[
+    Filter {
+        name: "urlencode",
+        description: "Percent-encode for use in a URL (like encodeURIComponent)",
+        // percent_encode is form encoding; a literal "+" is already %2B.
+        apply: |v, _| Ok(percent_encode(v).replace('+', "%20")),
+    },
+    Filter {
+        name: "urldecode",
+        description: "Decode percent-encoding",
+        apply: |v, _| Ok(percent_decode(v)),
+    },
+    Filter {
+        name: "base64",
+        description: "Base64-encode the UTF-8 bytes",
+        apply: |v, _| Ok(base64_encode(v.as_bytes())),
+    },
+    Filter {
+        name: "base64decode",
+        description: "Decode standard or URL-safe base64 to UTF-8 text",
+        apply: |v, _| {
+            let bytes = base64_decode(v).ok_or("not valid base64")?;
+            String::from_utf8(bytes).map_err(|_| "decoded bytes are not UTF-8".to_string())
+        },
+    },
+    Filter {
+        name: "upper",
+        description: "Uppercase",
+        apply: |v, _| Ok(v.to_uppercase()),
+    },
+    Filter {
+        name: "lower",
+        description: "Lowercase",
+        apply: |v, _| Ok(v.to_lowercase()),
+    },
+    Filter {
+        name: "trim",
+        description: "Remove leading and trailing whitespace",
+        apply: |v, _| Ok(v.trim().to_string()),
+    },
+    Filter {
+        name: "jsonescape",
+        description: "Escape for use inside a JSON string",
+        apply: |v, _| {
+            let quoted = Value::from(v).to_string();
+            Ok(quoted[1..quoted.len() - 1].to_string())
+        },
+    },
+    Filter {
+        name: "sha256",
+        description: "SHA-256 as lowercase hex",
+        apply: |v, _| Ok(hex_encode(&Sha256::digest(v.as_bytes()))),
+    },
+    Filter {
+        name: "truncate",
+        description: "Keep the first n characters (truncate:n)",
+        apply: |v, arg| {
+            let n: usize = arg
+                .and_then(|a| a.parse().ok())
+                .ok_or("truncate needs a length")?;
+            Ok(v.chars().take(n).collect())
+        },
+    },
+];
+
+/// One `| filter[:arg]` step of a placeholder.
+pub(crate) struct Pipe {
+    pub name: String,
+    pub arg: Option<String>,
+}
+
+/// Split `filter:arg` (the argument may be quoted) into a pipe step.
+pub(crate) fn parse_pipe(text: &str) -> Pipe {
+    match text.trim().split_once(':') {
+        Some((name, arg)) => Pipe {
+            name: name.trim().to_string(),
+            arg: Some(unquote(arg.trim())),
+        },
+        None => Pipe {
+            name: text.trim().to_string(),
+            arg: None,
+        },
+    }
+}
+
+pub(crate) fn is_filter(name: &str) -> bool {
+    FILTERS.iter().any(|f| f.name == name)
+}
+
+/// Run the filters in order. Err names the filter that failed and why.
+pub(crate) fn apply_filters(value: String, pipes: &[Pipe]) -> Result<String, String> {
+    pipes.iter().try_fold(value, |value, pipe| {
+        let filter = FILTERS
+            .iter()
+            .find(|f| f.name == pipe.name)
+            .ok_or_else(|| format!("unknown filter \"{}\"", pipe.name))?;
+        (filter.apply)(&value, pipe.arg.as_deref()).map_err(|e| format!("{}: {}", pipe.name, e))
+    })
+}
+
+/// `"a \"b\""` or `'a'` → the text inside; anything else as written.
+pub(crate) fn unquote(arg: &str) -> String {
+    let quoted = arg.len() >= 2
+        && ((arg.starts_with('"') && arg.ends_with('"'))
+            || (arg.starts_with('\'') && arg.ends_with('\'')));
+    if !quoted {
+        return arg.to_string();
+    }
+    let mut out = String::new();
+    let mut chars = arg[1..arg.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// List the filters usable in `{{name | filter}}` placeholders, for autocomplete.
+/// `default:"x"` is always available as well.
+/// Returns JSON array of {name, description}.
+#[wasm_bindgen]
+pub fn list_template_filters() -> String {
+    let list: Vec<Value> = FILTERS
+        .iter()
+        .map(|f| serde_json::json!({ "name": f.name, "description": f.description }))
+        .collect();
+    Value::from(list).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(value: &str, pipes: &str) -> Result<String, String> {
+        let pipes: Vec<Pipe> = pipes.split('|').map(parse_pipe).collect();
+        apply_filters(value.to_string(), &pipes)
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(run("a b&c", "urlencode").unwrap(), "a%20b%26c");
+        assert_eq!(run("a%20b", "urldecode | upper").unwrap(), "A B");
+        assert_eq!(run("user:pass", "base64").unwrap(), "dXNlcjpwYXNz");
+        assert_eq!(run("dXNlcjpwYXNz", "base64decode").unwrap(), "user:pass");
+        assert_eq!(run("  Ann ", "trim | lower").unwrap(), "ann");
+        assert_eq!(
+            run("say \"hi\"\n", "jsonescape").unwrap(),
+            r#"say \"hi\"\n"#
+        );
+        assert_eq!(run("héllo", "truncate:'2'").unwrap(), "hé");
+        assert!(run("abc", "sha256").unwrap().starts_with("ba7816bf"));
+        assert!(
+            run("!!", "base64decode")
+                .unwrap_err()
+                .starts_with("base64decode:")
+        );
+        assert!(run("x", "shout").is_err());
+        assert!(is_filter("upper") && !is_filter("default"));
+
+        let listed: Value = serde_json::from_str(&list_template_filters()).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), FILTERS.len());
+    }
+}
@@ -0,0 +1,518 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{Cancelled, cancelled_json, checkpoint};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
+            Op::Equal => "equal",
+            Op::Delete => "delete",
+            Op::Insert => "insert",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Segment {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct Line {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "oldLine", skip_serializing_if = "Option::is_none")]
+    old_line: Option<usize>,
+    #[serde(rename = "newLine", skip_serializing_if = "Option::is_none")]
+    new_line: Option<usize>,
+    text: String,
+    /// Intra-line highlights for a changed line paired with its counterpart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    segments: Option<Vec<Segment>>,
+}
+
+#[derive(Serialize)]
+struct Hunk {
+    #[serde(rename = "oldStart")]
+    old_start: usize,
+    #[serde(rename = "oldLines")]
+    old_lines: usize,
+    #[serde(rename = "newStart")]
+    new_start: usize,
+    #[serde(rename = "newLines")]
+    new_lines: usize,
+    lines: Vec<Line>,
+}
+
+#[derive(Serialize, Default)]
+struct Stats {
+    added: usize,
+    removed: usize,
+    unchanged: usize,
+}
+
+/// Diff two texts (HTML, XML, plain bodies) with the Myers algorithm.
+/// mode: "line" (default) or "word".
+/// context: unchanged lines kept around each change in line mode; omit for the full text.
+/// Line mode returns JSON {identical, stats: {added, removed, unchanged},
+/// hunks: [{oldStart, oldLines, newStart, newLines, lines: [{type, oldLine, newLine, text, segments}]}]};
+/// replaced lines carry word-level `segments` [{type, text}].
+/// Word mode returns JSON {identical, stats, segments: [{type, text}]}, where stats count segments.
+/// identical compares the texts exactly, so a final newline or line ending that
+/// differs makes it false even when no line changed.
+#[wasm_bindgen]
+pub fn text_diff(a: &str, b: &str, mode: &str, context: Option<u32>) -> String {
+    let _timer = crate::logging::timer("text_diff");
+    let result = if mode == "word" {
+        word_diff(a, b)
+    } else {
+        line_diff(a, b, context.map(|c| c as usize))
+    };
+    match result {
+        Ok(result) => serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string()),
+        Err(Cancelled) => cancelled_json(),
+    }
+}
+
+fn word_diff(a: &str, b: &str) -> Result<serde_json::Value, Cancelled> {
+    let old = tokenize(a);
+    let new = tokenize(b);
+    let segments = segments(&old, &new)?;
+    let mut stats = Stats::default();
+    for s in &segments {
+        match s.kind {
+            "insert" => stats.added += 1,
+            "delete" => stats.removed += 1,
+            _ => stats.unchanged += 1,
+        }
+    }
+    Ok(serde_json::json!({
+        "identical": a == b,
+        "stats": stats,
+        "segments": segments,
+    }))
+}
+
+fn line_diff(a: &str, b: &str, context: Option<usize>) -> Result<serde_json::Value, Cancelled> {
+    let old: Vec<&str> = a.lines().collect();
+    let new: Vec<&str> = b.lines().collect();
+    let ops = diff(&old, &new)?;
+
+    let mut stats = Stats::default();
+    let mut lines: Vec<Line> = Vec::with_capacity(ops.len());
+    let (mut oi, mut ni) = (0, 0);
+    let mut i = 0;
+    while i < ops.len() {
+        if ops[i] == Op::Equal {
+            stats.unchanged += 1;
+            lines.push(Line {
+                kind: "equal",
+                old_line: Some(oi + 1),
+                new_line: Some(ni + 1),
+                text: old[oi].to_string(),
+                segments: None,
+            });
+            oi += 1;
+            ni += 1;
+            i += 1;
+            continue;
+        }
+        // A run of deletes followed by inserts is a replacement; pair the lines
+        // up so each pair gets intra-line highlights.
+        let deletes = ops[i..].iter().take_while(|o| **o == Op::Delete).count();
+        let inserts = ops[i + deletes..]
+            .iter()
+            .take_while(|o| **o == Op::Insert)
+            .count();
+        let paired = deletes.min(inserts);
+        for k in 0..deletes {
+            let segments = (k < paired)
+                .then(|| {
+                    let (from, to) = (tokenize(old[oi + k]), tokenize(new[ni + k]));
+                    segments(&from, &to)
+                        .map(|s| s.into_iter().filter(|s| s.kind != "insert").collect())
+                })
+                .transpose()?;
+            lines.push(Line {
+                kind: "delete",
+                old_line: Some(oi + k + 1),
+                new_line: None,
+                text: old[oi + k].to_string(),
+                segments,
+            });
+        }
+        for k in 0..inserts {
+            let segments = (k < paired)
+                .then(|| {
+                    let (from, to) = (tokenize(old[oi + k]), tokenize(new[ni + k]));
+                    segments(&from, &to)
+                        .map(|s| s.into_iter().filter(|s| s.kind != "delete").collect())
+                })
+                .transpose()?;
+            lines.push(Line {
+                kind: "insert",
+                old_line: None,
+                new_line: Some(ni + k + 1),
+                text: new[ni + k].to_string(),
+                segments,
+            });
+        }
+        stats.removed += deletes;
+        stats.added += inserts;
+        oi += deletes;
+        ni += inserts;
+        i += deletes + inserts;
+    }
+
+    Ok(serde_json::json!({
+        "identical": a == b,
+        "stats": stats,
+        "hunks": hunks(lines, context),
+    }))
+}
+
+/// Group lines into hunks, keeping `context` unchanged lines around each change.
+fn hunks(lines: Vec<Line>, context: Option<usize>) -> Vec<Hunk> {
+    let keep: Vec<bool> = match context {
+        None => vec![true; lines.len()],
+        Some(context) => {
+            let mut keep = vec![false; lines.len()];
+            for (i, line) in lines.iter().enumerate() {
+                if line.kind != "equal" {
+                    let start = i.saturating_sub(context);
+                    let end = (i + context + 1).min(lines.len());
+                    keep[start..end].iter_mut().for_each(|k| *k = true);
+                }
+            }
+            keep
+        }
+    };
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut current: Option<Hunk> = None;
+    // Line numbers the next kept line would start a hunk at.
+    let (mut old_pos, mut new_pos) = (1, 1);
+    for (line, kept) in lines.into_iter().zip(keep) {
+        let (old_step, new_step) = match line.kind {
+            "equal" => (1, 1),
+            "delete" => (1, 0),
+            _ => (0, 1),
+        };
+        if kept {
+            let hunk = current.get_or_insert_with(|| Hunk {
+                old_start: old_pos,
+                old_lines: 0,
+                new_start: new_pos,
+                new_lines: 0,
+                lines: Vec::new(),
+            });
+            hunk.old_lines += old_step;
+            hunk.new_lines += new_step;
+            hunk.lines.push(line);
+        } else if let Some(hunk) = current.take() {
+            hunks.push(hunk);
+        }
+        old_pos += old_step;
+        new_pos += new_step;
+    }
+    hunks.extend(current);
+    hunks
+}
+
+/// Split text into words, whitespace runs and single punctuation characters.
+fn tokenize(text: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<u8> = None;
+    for (i, c) in text.char_indices() {
+        let cls = class(c);
+        if let Some(p) = prev
+            && (p != cls || cls == 2)
+        {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        prev = Some(cls);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Diff two token lists into segments, merging adjacent tokens of the same type.
+fn segments(old: &[&str], new: &[&str]) -> Result<Vec<Segment>, Cancelled> {
+    let mut out: Vec<Segment> = Vec::new();
+    let (mut oi, mut ni) = (0, 0);
+    for op in diff(old, new)? {
+        let token = match op {
+            Op::Equal | Op::Delete => old[oi],
+            Op::Insert => new[ni],
+        };
+        match op {
+            Op::Equal => {
+                oi += 1;
+                ni += 1;
+            }
+            Op::Delete => oi += 1,
+            Op::Insert => ni += 1,
+        }
+        match out.last_mut() {
+            Some(last) if last.kind == op.name() => last.text.push_str(token),
+            _ => out.push(Segment {
+                kind: op.name(),
+                text: token.to_string(),
+            }),
+        }
+    }
+    Ok(out)
+}
+
+/// Myers O(ND) shortest edit script. Deletes are emitted before inserts
+/// within each change, and the common prefix/suffix is trimmed up front.
+/// Past `MAX_EDIT_COST` edits the region between them is replaced as a whole.
+fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Result<Vec<Op>, Cancelled> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = vec![Op::Equal; prefix];
+    ops.extend(myers(a_mid, b_mid)?);
+    ops.extend(std::iter::repeat_n(Op::Equal, suffix));
+    Ok(ops)
+}
+
+/// Edit cost beyond which `myers` stops looking for a minimal script and
+/// replaces the whole changed region, bounding time and the saved frontiers.
+const MAX_EDIT_COST: isize = 2_000;
+
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Result<Vec<Op>, Cancelled> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let replace_all = || {
+        let mut ops = vec![Op::Delete; n as usize];
+        ops.extend(std::iter::repeat_n(Op::Insert, m as usize));
+        ops
+    };
+    if n == 0 || m == 0 {
+        return Ok(replace_all());
+    }
+    let max = n + m;
+    let offset = max as usize;
+    let mut v = vec![0isize; 2 * offset + 2];
+    // Diagonals -d..=d of the frontier before each step, all the walk back reads.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        if d > MAX_EDIT_COST {
+            return Ok(replace_all());
+        }
+        checkpoint()?;
+        trace.push(v[(max - d) as usize..=(max + d) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + max) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the saved frontiers backwards to recover the edit script.
+    let mut ops = Vec::with_capacity((n + m) as usize);
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let idx = (k + d) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + d) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if x == prev_x {
+            ops.push(Op::Insert);
+        } else {
+            ops.push(Op::Delete);
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        ops.push(Op::Equal);
+        x -= 1;
+        y -= 1;
+    }
+    ops.reverse();
+    Ok(normalize(ops))
+}
+
+/// Reorder each run of changes so its deletes come before its inserts.
+fn normalize(ops: Vec<Op>) -> Vec<Op> {
+    let mut out = Vec::with_capacity(ops.len());
+    let mut i = 0;
+    while i < ops.len() {
+        if ops[i] == Op::Equal {
+            out.push(Op::Equal);
+            i += 1;
+            continue;
+        }
+        let run = ops[i..].iter().take_while(|o| **o != Op::Equal).count();
+        let deletes = ops[i..i + run].iter().filter(|o| **o == Op::Delete).count();
+        out.extend(std::iter::repeat_n(Op::Delete, deletes));
+        out.extend(std::iter::repeat_n(Op::Insert, run - deletes));
+        i += run;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn run(a: &str, b: &str, mode: &str, context: Option<u32>) -> Value {
+        serde_json::from_str(&text_diff(a, b, mode, context)).unwrap()
+    }
+
+    #[test]
+    fn test_myers_minimal_script() {
+        let a: Vec<char> = "ABCABBA".chars().collect();
+        let b: Vec<char> = "CBABAC".chars().collect();
+        let ops = diff(&a, &b).ok().unwrap();
+        let edits = ops.iter().filter(|o| **o != Op::Equal).count();
+        assert_eq!(edits, 5);
+        assert_eq!(ops.iter().filter(|o| **o != Op::Insert).count(), a.len());
+        assert_eq!(ops.iter().filter(|o| **o != Op::Delete).count(), b.len());
+    }
+
+    #[test]
+    fn test_myers_falls_back_past_max_cost() {
+        let a: Vec<String> = (0..5_000).map(|i| format!("old {}", i)).collect();
+        let b: Vec<String> = (0..5_000).map(|i| format!("new {}", i)).collect();
+        let ops = diff(&a, &b).ok().unwrap();
+        assert_eq!(ops[..5_000], [Op::Delete; 5_000]);
+        assert_eq!(ops[5_000..], [Op::Insert; 5_000]);
+
+        // Small edits inside a long text still get a minimal script.
+        let mut c = a.clone();
+        c[2_500] = "changed".to_string();
+        let ops = diff(&a, &c).ok().unwrap();
+        assert_eq!(ops.iter().filter(|o| **o != Op::Equal).count(), 2);
+    }
+
+    #[test]
+    fn test_text_diff_lines_with_highlights() {
+        let result = run(
+            "<ul>\n<li>one</li>\n<li>two</li>\n</ul>",
+            "<ul>\n<li>one</li>\n<li>three</li>\n<li>four</li>\n</ul>",
+            "line",
+            None,
+        );
+        assert_eq!(result["identical"], false);
+        assert_eq!(
+            result["stats"],
+            serde_json::json!({"added": 2, "removed": 1, "unchanged": 3})
+        );
+        let lines = result["hunks"][0]["lines"].as_array().unwrap();
+        assert_eq!(lines[2]["type"], "delete");
+        assert_eq!(lines[2]["oldLine"], 3);
+        assert_eq!(
+            lines[2]["segments"],
+            serde_json::json!([
+                {"type": "equal", "text": "<li>"},
+                {"type": "delete", "text": "two"},
+                {"type": "equal", "text": "</li>"}
+            ])
+        );
+        assert_eq!(lines[3]["segments"][1]["text"], "three");
+        assert!(lines[4].get("segments").is_none());
+
+        let newline = run("a\n", "a", "line", None);
+        assert_eq!(newline["identical"], false);
+        assert_eq!(newline["stats"]["unchanged"], 1);
+        assert_eq!(run("a\nb", "a\nb", "line", None)["identical"], true);
+    }
+
+    #[test]
+    fn test_text_diff_context_splits_hunks() {
+        let a = "a\nb\nc\nd\ne\nf\ng\nh";
+        let b = "A\nb\nc\nd\ne\nf\ng\nH";
+        let result = run(a, b, "line", Some(1));
+        let hunks = result["hunks"].as_array().unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0]["oldStart"], 1);
+        assert_eq!(hunks[0]["oldLines"], 2);
+        assert_eq!(hunks[1]["oldStart"], 7);
+        assert_eq!(hunks[1]["newLines"], 2);
+        assert_eq!(run(a, a, "line", Some(1))["hunks"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_text_diff_words() {
+        let result = run("the quick fox", "the slow fox!", "word", None);
+        assert_eq!(
+            result["segments"],
+            serde_json::json!([
+                {"type": "equal", "text": "the "},
+                {"type": "delete", "text": "quick"},
+                {"type": "insert", "text": "slow"},
+                {"type": "equal", "text": " fox"},
+                {"type": "insert", "text": "!"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_text_diff_stops_when_cancelled() {
+        let handle = crate::cancel::AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(text_diff("a\nb", "a\nc", "line", None), cancelled_json());
+        assert_eq!(text_diff("a b", "a c", "word", None), cancelled_json());
+        handle.deactivate();
+        assert_eq!(run("a\nb", "a\nc", "line", None)["stats"]["added"], 1);
+    }
+}
@@ -0,0 +1,208 @@
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThresholdSpec {
+    Expr(String),
+    Full {
+        expression: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+struct Threshold<'a> {
+    metric: &'a str,
+    operator: &'a str,
+    value: f64,
+}
+
+/// Evaluate k6-style thresholds against a `StatsAccumulator` snapshot.
+/// thresholds_json: array of expressions ("p95 < 500", "p(99.9) <= 2s",
+/// "error_rate < 0.01", "count > 1000") or {expression, name} objects.
+/// Metrics: p50…p99.9 / p(N), avg, med, min, max, count, errors, error_rate, rps.
+/// Returns JSON {passed, results: [{name, expression, metric, operator, threshold,
+/// actual, passed, margin, error}]}; margin is the headroom left (negative when failing).
+#[wasm_bindgen]
+pub fn evaluate_thresholds(thresholds_json: &str, stats_snapshot: &str) -> String {
+    let specs: Vec<ThresholdSpec> = match serde_json::from_str(thresholds_json) {
+        Ok(s) => s,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid thresholds: {}", e) })
+                .to_string();
+        }
+    };
+    let snapshot: Value = serde_json::from_str(stats_snapshot).unwrap_or(Value::Null);
+
+    let results: Vec<Value> = specs
+        .iter()
+        .map(|spec| {
+            let (expression, name) = match spec {
+                ThresholdSpec::Expr(e) => (e.as_str(), None),
+                ThresholdSpec::Full { expression, name } => (expression.as_str(), name.clone()),
+            };
+            evaluate_one(expression, name, &snapshot)
+        })
+        .collect();
+    let passed = results.iter().all(|r| r["passed"] == true);
+    serde_json::json!({ "passed": passed, "results": results }).to_string()
+}
+
+fn evaluate_one(expression: &str, name: Option<String>, snapshot: &Value) -> Value {
+    let failure = |error: String| {
+        serde_json::json!({
+            "name": name,
+            "expression": expression,
+            "passed": false,
+            "error": error,
+        })
+    };
+    let threshold = match parse(expression) {
+        Ok(t) => t,
+        Err(e) => return failure(e),
+    };
+    let actual = match metric_value(threshold.metric, snapshot) {
+        Ok(v) => v,
+        Err(e) => return failure(e),
+    };
+    let Some(actual) = actual else {
+        return failure(format!("No data for {}", threshold.metric));
+    };
+
+    let (passed, margin) = match threshold.operator {
+        "<" => (actual < threshold.value, threshold.value - actual),
+        "<=" => (actual <= threshold.value, threshold.value - actual),
+        ">" => (actual > threshold.value, actual - threshold.value),
+        ">=" => (actual >= threshold.value, actual - threshold.value),
+        "==" => (actual == threshold.value, -(actual - threshold.value).abs()),
+        _ => (actual != threshold.value, (actual - threshold.value).abs()),
+    };
+    serde_json::json!({
+        "name": name,
+        "expression": expression,
+        "metric": threshold.metric,
+        "operator": threshold.operator,
+        "threshold": threshold.value,
+        "actual": actual,
+        "passed": passed,
+        "margin": margin,
+        "error": null,
+    })
+}
+
+fn parse(expression: &str) -> Result<Threshold<'_>, String> {
+    let expr = expression.trim();
+    let op_start = expr
+        .find(['<', '>', '=', '!'])
+        .ok_or_else(|| format!("Missing comparison operator in \"{}\"", expr))?;
+    let rest = &expr[op_start..];
+    let op_len = if rest.len() > 1 && rest.as_bytes()[1] == b'=' {
+        2
+    } else {
+        1
+    };
+    let operator = &rest[..op_len];
+    if operator == "=" || operator == "!" {
+        return Err(format!("Invalid operator \"{}\"", operator));
+    }
+    let metric = expr[..op_start].trim();
+    if metric.is_empty() {
+        return Err(format!("Missing metric in \"{}\"", expr));
+    }
+
+    let raw = rest[op_len..].trim();
+    let (number, scale) = if let Some(n) = raw.strip_suffix("ms") {
+        (n, 1.0)
+    } else if let Some(n) = raw.strip_suffix('s') {
+        (n, 1000.0)
+    } else if let Some(n) = raw.strip_suffix('%') {
+        (n, 0.01)
+    } else {
+        (raw, 1.0)
+    };
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid threshold value \"{}\"", raw))?;
+    Ok(Threshold {
+        metric,
+        operator,
+        value: value * scale,
+    })
+}
+
+/// Look a metric up in the snapshot. Ok(None) means the metric is known but has no data.
+fn metric_value(metric: &str, snapshot: &Value) -> Result<Option<f64>, String> {
+    let percentile = |p: &str| {
+        let key = format!("p{}", p.trim());
+        match snapshot["percentiles"].get(&key) {
+            Some(v) => Ok(v.as_f64()),
+            None => Err(format!("Percentile {} is not in the snapshot", key)),
+        }
+    };
+    let field = |key: &str| Ok(snapshot.get(key).and_then(Value::as_f64));
+
+    let m = metric.to_lowercase();
+    if let Some(p) = m.strip_prefix("p(").and_then(|p| p.strip_suffix(')')) {
+        return percentile(p);
+    }
+    match m.as_str() {
+        "avg" | "mean" => field("meanMs"),
+        "med" | "median" => percentile("50"),
+        "min" => field("minMs"),
+        "max" => field("maxMs"),
+        "count" => field("count"),
+        "errors" => field("errors"),
+        "error_rate" | "rate" => field("errorRate"),
+        "rps" => field("rps"),
+        p if p.starts_with('p') && p[1..].parse::<f64>().is_ok() => percentile(&p[1..]),
+        _ => Err(format!("Unknown metric \"{}\"", metric)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = r#"{"count":1200,"errors":6,"errorRate":0.005,"minMs":3,"maxMs":900,
+        "meanMs":120,"percentiles":{"p50":100,"p95":480,"p99":700,"p99.9":880},"rps":40}"#;
+
+    fn eval(thresholds: &str) -> Value {
+        serde_json::from_str(&evaluate_thresholds(thresholds, SNAPSHOT)).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_thresholds_pass_and_margin() {
+        let out = eval(
+            r#"["p95 < 500", "p(99.9) <= 1s", "error_rate < 1%", "count > 1000", "med<=100"]"#,
+        );
+        assert_eq!(out["passed"], true);
+        assert_eq!(out["results"][0]["actual"], 480.0);
+        assert_eq!(out["results"][0]["margin"], 20.0);
+        assert_eq!(out["results"][1]["threshold"], 1000.0);
+        assert_eq!(out["results"][3]["margin"], 200.0);
+    }
+
+    #[test]
+    fn test_evaluate_thresholds_failures() {
+        let out = eval(
+            r#"[{"name":"latency","expression":"p99 < 500ms"}, "avg == 100", "p(97) < 1", "bogus > 1", "p95 500"]"#,
+        );
+        assert_eq!(out["passed"], false);
+        let results = out["results"].as_array().unwrap();
+        assert_eq!(results[0]["name"], "latency");
+        assert_eq!(results[0]["passed"], false);
+        assert_eq!(results[0]["margin"], -200.0);
+        assert_eq!(results[1]["passed"], false);
+        assert!(results[2]["error"].as_str().unwrap().contains("p97"));
+        assert!(
+            results[3]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Unknown metric")
+        );
+        assert!(results[4]["error"].as_str().unwrap().contains("operator"));
+    }
+}
@@ -0,0 +1,380 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, percent_encode};
+
+/// How to obtain a new token for a profile.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+struct RefreshConfig {
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    /// "basic" sends client credentials in an Authorization header,
+    /// "body" (default) sends them as form fields.
+    client_auth: String,
+    scope: String,
+}
+
+#[derive(Default, Clone)]
+struct TokenEntry {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    token_type: String,
+    scope: Option<String>,
+    expires_at_ms: Option<f64>,
+    expiry_source: Option<&'static str>,
+    config: RefreshConfig,
+}
+
+/// Stores OAuth/bearer tokens per auth profile and decides when to refresh.
+/// All times are milliseconds since the Unix epoch; `now_ms` arguments
+/// default to the current time when omitted.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct TokenManager {
+    profiles: HashMap<String, TokenEntry>,
+}
+
+#[wasm_bindgen]
+impl TokenManager {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TokenManager {
+        TokenManager::default()
+    }
+
+    /// Configure how a profile refreshes: {tokenUrl, clientId, clientSecret, clientAuth, scope}.
+    /// Returns false if the config is not valid JSON.
+    pub fn configure(&mut self, profile: &str, config_json: &str) -> bool {
+        match serde_json::from_str::<RefreshConfig>(config_json) {
+            Ok(config) => {
+                self.profiles.entry(profile.to_string()).or_default().config = config;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Store a token for a profile. Accepts an OAuth token response
+    /// ({access_token, refresh_token?, expires_in?, token_type?, scope?}) or a raw token string.
+    /// Expiry comes from `expires_in` when present, otherwise from the JWT `exp` claim.
+    /// Returns JSON {stored, expiresAt, expirySource} where expirySource is "expires_in", "jwt" or null.
+    pub fn store_token(&mut self, profile: &str, token_json: &str, now_ms: Option<f64>) -> String {
+        let now = now_ms.unwrap_or_else(crate::now_ms);
+        let response: Value = serde_json::from_str(token_json)
+            .unwrap_or_else(|_| Value::String(token_json.trim().to_string()));
+
+        let (access, refresh, token_type, scope, expires_in) = match &response {
+            Value::String(s) => (Some(s.clone()), None, None, None, None),
+            Value::Object(map) => {
+                let field = |a: &str, b: &str| {
+                    map.get(a)
+                        .or_else(|| map.get(b))
+                        .and_then(Value::as_str)
+                        .map(|s| s.to_string())
+                };
+                let expires_in = map
+                    .get("expires_in")
+                    .or_else(|| map.get("expiresIn"))
+                    .and_then(|v| v.as_f64().or_else(|| v.as_str()?.parse().ok()));
+                (
+                    field("access_token", "accessToken"),
+                    field("refresh_token", "refreshToken"),
+                    field("token_type", "tokenType"),
+                    map.get("scope")
+                        .and_then(Value::as_str)
+                        .map(|s| s.to_string()),
+                    expires_in,
+                )
+            }
+            _ => (None, None, None, None, None),
+        };
+
+        let Some(access) = access.filter(|a| !a.is_empty()) else {
+            return serde_json::json!({ "stored": false, "expiresAt": null, "expirySource": null })
+                .to_string();
+        };
+
+        let (expires_at, source) = match expires_in {
+            Some(secs) => (Some(now + secs * 1000.0), Some("expires_in")),
+            None => match jwt_expiry_ms(&access) {
+                Some(at) => (Some(at), Some("jwt")),
+                None => (None, None),
+            },
+        };
+
+        let entry = self.profiles.entry(profile.to_string()).or_default();
+        entry.access_token = Some(access);
+        // Servers may omit refresh_token on refresh, meaning "keep the old one".
+        if refresh.is_some() {
+            entry.refresh_token = refresh;
+        }
+        entry.token_type = token_type.unwrap_or_else(|| "Bearer".to_string());
+        entry.scope = scope;
+        entry.expires_at_ms = expires_at;
+        entry.expiry_source = source;
+
+        serde_json::json!({ "stored": true, "expiresAt": expires_at, "expirySource": source })
+            .to_string()
+    }
+
+    /// The current access token for a profile, if any.
+    pub fn get_token(&self, profile: &str) -> Option<String> {
+        self.profiles.get(profile)?.access_token.clone()
+    }
+
+    /// The value for an Authorization header, e.g. "Bearer abc".
+    pub fn authorization_header(&self, profile: &str) -> Option<String> {
+        let entry = self.profiles.get(profile)?;
+        let token = entry.access_token.as_ref()?;
+        let scheme = if entry.token_type.eq_ignore_ascii_case("bearer") {
+            "Bearer"
+        } else {
+            entry.token_type.as_str()
+        };
+        Some(format!("{} {}", scheme, token))
+    }
+
+    /// True when there is no token, or it expires within `skew_seconds`.
+    /// Tokens with unknown expiry are assumed valid.
+    pub fn needs_refresh(&self, profile: &str, skew_seconds: f64, now_ms: Option<f64>) -> bool {
+        let now = now_ms.unwrap_or_else(crate::now_ms);
+        match self.profiles.get(profile) {
+            Some(entry) if entry.access_token.is_some() => entry
+                .expires_at_ms
+                .is_some_and(|at| at - skew_seconds.max(0.0) * 1000.0 <= now),
+            _ => true,
+        }
+    }
+
+    /// Build the token endpoint request that renews a profile's token.
+    /// Uses the refresh_token grant when a refresh token is stored, otherwise
+    /// client_credentials. Returns JSON {method, url, headers, body, grantType}
+    /// or {error} when the profile cannot be refreshed.
+    pub fn build_refresh_request(&self, profile: &str) -> String {
+        let Some(entry) = self.profiles.get(profile) else {
+            return error_json(&format!("Unknown profile \"{}\"", profile));
+        };
+        let config = &entry.config;
+        if config.token_url.trim().is_empty() {
+            return error_json(&format!("Profile \"{}\" has no tokenUrl", profile));
+        }
+
+        let mut fields: Vec<(&str, &str)> = Vec::new();
+        let grant = match &entry.refresh_token {
+            Some(rt) => {
+                fields.push(("grant_type", "refresh_token"));
+                fields.push(("refresh_token", rt));
+                "refresh_token"
+            }
+            None if !config.client_id.is_empty() => {
+                fields.push(("grant_type", "client_credentials"));
+                "client_credentials"
+            }
+            None => {
+                return error_json(&format!(
+                    "Profile \"{}\" has no refresh token or client credentials",
+                    profile
+                ));
+            }
+        };
+        if !config.scope.is_empty() {
+            fields.push(("scope", &config.scope));
+        }
+
+        let mut headers = serde_json::Map::new();
+        headers.insert(
+            "Content-Type".to_string(),
+            Value::from("application/x-www-form-urlencoded"),
+        );
+        headers.insert("Accept".to_string(), Value::from("application/json"));
+        if config.client_auth == "basic" {
+            headers.insert(
+                "Authorization".to_string(),
+                Value::from(crate::build_basic_auth(
+                    &config.client_id,
+                    &config.client_secret,
+                )),
+            );
+        } else if !config.client_id.is_empty() {
+            fields.push(("client_id", &config.client_id));
+            if !config.client_secret.is_empty() {
+                fields.push(("client_secret", &config.client_secret));
+            }
+        }
+
+        let body = fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        serde_json::json!({
+            "method": "POST",
+            "url": config.token_url,
+            "headers": headers,
+            "body": body,
+            "grantType": grant,
+        })
+        .to_string()
+    }
+
+    /// Summary of all profiles without token values:
+    /// JSON array of {profile, hasToken, hasRefreshToken, expiresAt, expiresInSeconds, expirySource, scope}.
+    pub fn status(&self, now_ms: Option<f64>) -> String {
+        let now = now_ms.unwrap_or_else(crate::now_ms);
+        let mut names: Vec<&String> = self.profiles.keys().collect();
+        names.sort();
+        let list: Vec<Value> = names
+            .into_iter()
+            .map(|name| {
+                let e = &self.profiles[name];
+                serde_json::json!({
+                    "profile": name,
+                    "hasToken": e.access_token.is_some(),
+                    "hasRefreshToken": e.refresh_token.is_some(),
+                    "expiresAt": e.expires_at_ms,
+                    "expiresInSeconds": e.expires_at_ms.map(|at| ((at - now) / 1000.0).floor()),
+                    "expirySource": e.expiry_source,
+                    "scope": e.scope,
+                })
+            })
+            .collect();
+        serde_json::to_string(&list).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Forget the stored token (but keep the refresh configuration).
+    pub fn clear_token(&mut self, profile: &str) {
+        if let Some(entry) = self.profiles.get_mut(profile) {
+            let config = entry.config.clone();
+            *entry = TokenEntry {
+                config,
+                ..TokenEntry::default()
+            };
+        }
+    }
+
+    /// Remove a profile entirely.
+    pub fn remove(&mut self, profile: &str) {
+        self.profiles.remove(profile);
+    }
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Expiry of a JWT (`exp` claim, seconds) in milliseconds, if it is a JWT.
+pub(crate) fn jwt_expiry_ms(token: &str) -> Option<f64> {
+    let claims = jwt_claims(token)?;
+    claims.get("exp")?.as_f64().map(|exp| exp * 1000.0)
+}
+
+/// Decode (without verifying) the payload of a compact JWT.
+pub(crate) fn jwt_claims(token: &str) -> Option<Value> {
+    let mut parts = token.split('.');
+    let (_header, payload, _sig) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let bytes = base64_decode(payload)?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base64_encode;
+
+    fn jwt(claims: &str) -> String {
+        let enc = |s: &str| {
+            base64_encode(s.as_bytes())
+                .trim_end_matches('=')
+                .replace('+', "-")
+                .replace('/', "_")
+        };
+        format!("{}.{}.sig", enc(r#"{"alg":"none"}"#), enc(claims))
+    }
+
+    #[test]
+    fn test_token_manager_expires_in() {
+        let mut tm = TokenManager::new();
+        let stored: Value = serde_json::from_str(&tm.store_token(
+            "api",
+            r#"{"access_token":"abc","expires_in":3600,"refresh_token":"r1"}"#,
+            Some(1_000_000.0),
+        ))
+        .unwrap();
+        assert_eq!(stored["expirySource"], "expires_in");
+        assert_eq!(stored["expiresAt"], 4_600_000.0);
+        assert!(!tm.needs_refresh("api", 60.0, Some(1_000_000.0)));
+        assert!(tm.needs_refresh("api", 60.0, Some(4_550_000.0)));
+        assert!(tm.needs_refresh("other", 0.0, Some(0.0)));
+        assert_eq!(tm.authorization_header("api").unwrap(), "Bearer abc");
+    }
+
+    #[test]
+    fn test_token_manager_jwt_expiry() {
+        let mut tm = TokenManager::new();
+        let token = jwt(r#"{"sub":"1","exp":2000}"#);
+        let stored: Value =
+            serde_json::from_str(&tm.store_token("jwt", &token, Some(0.0))).unwrap();
+        assert_eq!(stored["expirySource"], "jwt");
+        assert_eq!(stored["expiresAt"], 2_000_000.0);
+        assert!(!tm.needs_refresh("jwt", 30.0, Some(1_000_000.0)));
+        assert!(tm.needs_refresh("jwt", 30.0, Some(1_980_000.0)));
+    }
+
+    #[test]
+    fn test_token_manager_refresh_request() {
+        let mut tm = TokenManager::new();
+        assert!(tm.configure(
+            "api",
+            r#"{"tokenUrl":"https://auth.example.com/token","clientId":"cid","clientSecret":"s e","scope":"read"}"#
+        ));
+        let cc: Value = serde_json::from_str(&tm.build_refresh_request("api")).unwrap();
+        assert_eq!(cc["grantType"], "client_credentials");
+        assert_eq!(
+            cc["body"],
+            "grant_type=client_credentials&scope=read&client_id=cid&client_secret=s+e"
+        );
+
+        tm.store_token(
+            "api",
+            r#"{"access_token":"a","refresh_token":"r/1"}"#,
+            Some(0.0),
+        );
+        // A later response without refresh_token keeps the previous one.
+        tm.store_token("api", r#"{"access_token":"b"}"#, Some(0.0));
+        let rt: Value = serde_json::from_str(&tm.build_refresh_request("api")).unwrap();
+        assert_eq!(rt["grantType"], "refresh_token");
+        assert!(
+            rt["body"]
+                .as_str()
+                .unwrap()
+                .starts_with("grant_type=refresh_token&refresh_token=r%2F1")
+        );
+        assert_eq!(
+            rt["headers"]["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+
+        let missing: Value = serde_json::from_str(&tm.build_refresh_request("nope")).unwrap();
+        assert!(missing["error"].is_string());
+    }
+
+    #[test]
+    fn test_token_manager_clear_and_status() {
+        let mut tm = TokenManager::new();
+        tm.store_token("a", "raw-token", Some(0.0));
+        let status: Value = serde_json::from_str(&tm.status(Some(0.0))).unwrap();
+        assert_eq!(status[0]["hasToken"], true);
+        assert_eq!(status[0]["expiresAt"], Value::Null);
+        tm.clear_token("a");
+        assert!(tm.get_token("a").is_none());
+        tm.remove("a");
+        assert_eq!(tm.status(Some(0.0)), "[]");
+    }
+}
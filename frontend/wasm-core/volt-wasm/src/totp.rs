@@ -0,0 +1,287 @@
+use sha2::{Digest, Sha256, Sha512};
+use wasm_bindgen::prelude::*;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// RFC 4648 Base32 with '=' padding, as used for TOTP secrets.
+#[wasm_bindgen]
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = buf.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < chars {
+                out.push(BASE32_ALPHABET[(bits >> (35 - i * 5)) as usize & 31] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode Base32, ignoring case, whitespace, '-' separators and padding, as
+/// authenticator apps display secrets ("JBSW Y3DP EHPK 3PXP").
+/// Returns undefined when the text contains other characters.
+#[wasm_bindgen]
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.chars() {
+        if c.is_whitespace() || c == '-' || c == '=' {
+            continue;
+        }
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Generate an RFC 6238 time-based one-time password.
+/// secret: the Base32 secret, or an `otpauth://totp/...` URI whose digits, period
+/// and algorithm are used unless given here.
+/// time_ms: the time to generate for; defaults to now.
+/// digits: 6 by default (1–10); period: 30 seconds by default;
+/// algorithm: "SHA1" (default), "SHA256" or "SHA512".
+/// Returns JSON {code, counter, period, remainingSeconds} or {error}.
+#[wasm_bindgen]
+pub fn totp(
+    secret: &str,
+    time_ms: Option<f64>,
+    digits: Option<u32>,
+    period: Option<u32>,
+    algorithm: &str,
+) -> String {
+    let error = |msg: &str| serde_json::json!({ "error": msg }).to_string();
+
+    let mut params = OtpParams::default();
+    let secret = secret.trim();
+    let secret = match secret.strip_prefix("otpauth://") {
+        Some(uri) => {
+            params = OtpParams::from_uri(uri);
+            params.secret.clone()
+        }
+        None => secret.to_string(),
+    };
+    let digits = digits.or(params.digits).unwrap_or(6);
+    let period = period.or(params.period).unwrap_or(30);
+    let algorithm = Some(algorithm.trim())
+        .filter(|a| !a.is_empty())
+        .or(params.algorithm.as_deref())
+        .unwrap_or("SHA1")
+        .to_uppercase()
+        .replace('-', "");
+
+    let Some(key) = base32_decode(&secret).filter(|k| !k.is_empty()) else {
+        return error("Secret is not valid Base32");
+    };
+    if !(1..=10).contains(&digits) {
+        return error("digits must be between 1 and 10");
+    }
+    if period == 0 {
+        return error("period must be at least 1 second");
+    }
+    let seconds = (time_ms.unwrap_or_else(crate::now_ms) / 1000.0)
+        .floor()
+        .max(0.0) as u64;
+    let counter = seconds / period as u64;
+    let Some(code) = hotp(&key, counter, digits, &algorithm) else {
+        return error(&format!("Unsupported algorithm: {}", algorithm));
+    };
+
+    serde_json::json!({
+        "code": code,
+        "counter": counter,
+        "period": period,
+        "remainingSeconds": period as u64 - seconds % period as u64,
+    })
+    .to_string()
+}
+
+/// RFC 4226 HOTP with dynamic truncation, zero-padded to `digits`.
+fn hotp(key: &[u8], counter: u64, digits: u32, algorithm: &str) -> Option<String> {
+    let message = counter.to_be_bytes();
+    let mac = match algorithm {
+        "SHA1" => hmac(sha1, 64, key, &message),
+        "SHA256" => hmac(|m| Sha256::digest(m).to_vec(), 64, key, &message),
+        "SHA512" => hmac(|m| Sha512::digest(m).to_vec(), 128, key, &message),
+        _ => return None,
+    };
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        mac[offset],
+        mac[offset + 1],
+        mac[offset + 2],
+        mac[offset + 3],
+    ]) & 0x7fff_ffff;
+    let code = binary as u64 % 10u64.pow(digits);
+    Some(format!("{:0width$}", code, width = digits as usize))
+}
+
+#[derive(Default)]
+struct OtpParams {
+    secret: String,
+    digits: Option<u32>,
+    period: Option<u32>,
+    algorithm: Option<String>,
+}
+
+impl OtpParams {
+    /// Query parameters of `totp/Issuer:account?secret=...&digits=8`.
+    fn from_uri(uri: &str) -> Self {
+        let mut params = OtpParams::default();
+        let query = uri.split_once('?').map_or("", |(_, q)| q);
+        for (key, value) in crate::query_pairs(query) {
+            match key.to_lowercase().as_str() {
+                "secret" => params.secret = value,
+                "digits" => params.digits = value.parse().ok(),
+                "period" => params.period = value.parse().ok(),
+                "algorithm" => params.algorithm = Some(value),
+                _ => {}
+            }
+        }
+        params
+    }
+}
+
+/// HMAC (RFC 2104) over any hash with the given block size.
+pub(crate) fn hmac(
+    hash: impl Fn(&[u8]) -> Vec<u8>,
+    block_size: usize,
+    key: &[u8],
+    message: &[u8],
+) -> Vec<u8> {
+    let mut block = if key.len() > block_size {
+        hash(key)
+    } else {
+        key.to_vec()
+    };
+    block.resize(block_size, 0);
+    let pad = |byte: u8| block.iter().map(move |b| b ^ byte);
+    let inner: Vec<u8> = pad(0x36).chain(message.iter().copied()).collect();
+    let outer: Vec<u8> = pad(0x5c).chain(hash(&inner)).collect();
+    hash(&outer)
+}
+
+/// SHA-1 (FIPS 180-4). Only for HMAC-SHA1, which TOTP still defaults to and
+/// which remains sound even though SHA-1 collisions are practical.
+fn sha1(message: &[u8]) -> Vec<u8> {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut data = message.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    for block in data.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    h.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn code(secret: &[u8], seconds: f64, algorithm: &str) -> String {
+        let out: Value = serde_json::from_str(&totp(
+            &base32_encode(secret),
+            Some(seconds * 1000.0),
+            Some(8),
+            None,
+            algorithm,
+        ))
+        .unwrap();
+        out["code"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI======");
+        assert_eq!(base32_encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(
+            base32_decode("mzxw 6ytb-oi======"),
+            Some(b"foobar".to_vec())
+        );
+        assert_eq!(base32_decode("MZXW6YQ"), Some(b"foob".to_vec()));
+        assert_eq!(base32_decode("MZ1W"), None);
+        assert_eq!(
+            crate::hex_encode(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let sha1_key = b"12345678901234567890";
+        let sha256_key = b"12345678901234567890123456789012";
+        let sha512_key = b"1234567890123456789012345678901234567890123456789012345678901234";
+        assert_eq!(code(sha1_key, 59.0, ""), "94287082");
+        assert_eq!(code(sha1_key, 1_111_111_109.0, "sha1"), "07081804");
+        assert_eq!(code(sha256_key, 59.0, "SHA256"), "46119246");
+        assert_eq!(code(sha512_key, 20_000_000_000.0, "SHA-512"), "47863826");
+    }
+
+    #[test]
+    fn test_totp_otpauth_uri_and_errors() {
+        let uri = format!(
+            "otpauth://totp/Volt:dev%40x.io?secret={}&issuer=Volt&digits=8&period=60",
+            base32_encode(b"12345678901234567890")
+        );
+        let out: Value = serde_json::from_str(&totp(&uri, Some(59_000.0), None, None, "")).unwrap();
+        assert_eq!(out["period"], 60);
+        assert_eq!(out["counter"], 0);
+        assert_eq!(out["remainingSeconds"], 1);
+        assert_eq!(out["code"].as_str().unwrap().len(), 8);
+
+        let bad: Value = serde_json::from_str(&totp("not base32!", None, None, None, "")).unwrap();
+        assert!(bad["error"].is_string());
+        let bad: Value =
+            serde_json::from_str(&totp("JBSWY3DPEHPK3PXP", None, None, None, "MD5")).unwrap();
+        assert_eq!(bad["error"], "Unsupported algorithm: MD5");
+    }
+}
@@ -0,0 +1,333 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::hex_encode;
+use crate::idempotency::random_bytes;
+use crate::model::Headers;
+
+/// tracestate may carry at most this many list members (W3C Trace Context §3.3.1).
+const MAX_TRACESTATE_MEMBERS: usize = 32;
+
+/// Headers servers commonly echo a request or correlation id in, used when the
+/// response has no trace headers.
+const CORRELATION_HEADERS: &[&str] = &[
+    "x-request-id",
+    "x-correlation-id",
+    "request-id",
+    "x-amzn-requestid",
+    "x-amzn-trace-id",
+    "cf-ray",
+];
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct TraceContext {
+    /// "w3c", "b3" (single header), "b3multi" (X-B3-* headers) or null.
+    format: Option<&'static str>,
+    trace_id: Option<String>,
+    span_id: Option<String>,
+    parent_span_id: Option<String>,
+    sampled: Option<bool>,
+    /// tracestate members in order, as [key, value] pairs.
+    tracestate: Vec<(String, String)>,
+    /// The trace id, or else the first correlation header found.
+    correlation_id: Option<String>,
+    /// Why trace headers that are present could not be used.
+    errors: Vec<String>,
+}
+
+/// Generate trace headers for an outgoing request.
+/// parent: a traceparent (or single `b3` header) from an earlier request or
+/// response; when valid, its trace id is kept and its span becomes the parent,
+/// so chained requests show up as one trace. Otherwise a new trace is started.
+/// sampled: the sampled flag; defaults to the parent's, or true.
+/// Returns JSON {traceparent, b3, traceId, spanId, parentSpanId, sampled}.
+#[wasm_bindgen]
+pub fn generate_traceparent(parent: Option<String>, sampled: Option<bool>) -> String {
+    let parent = parent.as_deref().and_then(|p| {
+        parse_traceparent(p)
+            .or_else(|_| parse_b3_single(p))
+            .ok()
+            .filter(|(trace, _, _)| trace.is_some())
+    });
+    let (trace_id, parent_span_id, parent_sampled) = match parent {
+        Some((Some((trace, span)), _, parent_sampled)) => (trace, Some(span), parent_sampled),
+        _ => (random_id(16), None, None),
+    };
+    let span_id = random_id(8);
+    let sampled = sampled.or(parent_sampled).unwrap_or(true);
+    serde_json::json!({
+        "traceparent": format!("00-{}-{}-{}", trace_id, span_id, if sampled { "01" } else { "00" }),
+        "b3": format!("{}-{}-{}", trace_id, span_id, if sampled { "1" } else { "0" }),
+        "traceId": trace_id,
+        "spanId": span_id,
+        "parentSpanId": parent_span_id,
+        "sampled": sampled,
+    })
+    .to_string()
+}
+
+/// Read the trace context from request or response headers.
+/// headers_json: JSON object of headers (key → value or [values]) or [{key, value}].
+/// W3C `traceparent`/`tracestate` are preferred; B3 (`b3`, then `X-B3-*`) is the
+/// fallback. 64-bit B3 trace ids are left-padded to 32 hex digits.
+/// Returns JSON {format, traceId, spanId, parentSpanId, sampled, tracestate,
+/// correlationId, errors}; format is null when no trace headers were usable.
+#[wasm_bindgen]
+pub fn parse_trace_headers(headers_json: &str) -> String {
+    let headers: Headers = serde_json::from_str(headers_json).unwrap_or_default();
+    serde_json::to_string(&read(&headers)).unwrap_or_else(|_| "{}".to_string())
+}
+
+fn read(headers: &Headers) -> TraceContext {
+    let mut out = TraceContext::default();
+    let found = |format, parsed: Result<Parsed, String>, out: &mut TraceContext| match parsed {
+        Ok((ids, parent, sampled)) => {
+            if let Some((trace, span)) = ids {
+                out.trace_id = Some(trace);
+                out.span_id = Some(span);
+            }
+            out.parent_span_id = parent;
+            out.sampled = sampled;
+            out.format = Some(format);
+            true
+        }
+        Err(e) => {
+            out.errors.push(e);
+            false
+        }
+    };
+
+    let w3c = headers
+        .get("traceparent")
+        .is_some_and(|value| found("w3c", parse_traceparent(value), &mut out));
+    if w3c {
+        out.tracestate = parse_tracestate(headers);
+    } else if let Some(value) = headers.get("b3") {
+        found("b3", parse_b3_single(value), &mut out);
+    }
+    if out.format.is_none() && headers.contains("x-b3-traceid") {
+        found("b3multi", parse_b3_multi(headers), &mut out);
+    }
+
+    out.correlation_id = out.trace_id.clone().or_else(|| {
+        CORRELATION_HEADERS
+            .iter()
+            .find_map(|name| headers.get(name))
+            .map(|v| v.trim().to_string())
+    });
+    out
+}
+
+/// (trace id and span id, parent span id, sampled). B3 may send only the
+/// sampling decision, without ids.
+type Parsed = (Option<(String, String)>, Option<String>, Option<bool>);
+
+/// `version-traceid-parentid-flags`. Later versions may append fields, which
+/// are ignored as the spec asks.
+fn parse_traceparent(value: &str) -> Result<Parsed, String> {
+    let value = value.trim();
+    let parts: Vec<&str> = value.split('-').collect();
+    let invalid = || format!("Invalid traceparent \"{}\"", value);
+    if parts.len() < 4 || !is_hex(parts[0], 2) || parts[0] == "ff" {
+        return Err(invalid());
+    }
+    if parts[0] == "00" && parts.len() != 4 {
+        return Err(invalid());
+    }
+    let (trace, span, flags) = (parts[1], parts[2], parts[3]);
+    if !is_id(trace, 32) || !is_id(span, 16) || !is_hex(flags, 2) {
+        return Err(invalid());
+    }
+    let flags = u8::from_str_radix(flags, 16).map_err(|_| invalid())?;
+    Ok((
+        Some((trace.to_ascii_lowercase(), span.to_ascii_lowercase())),
+        None,
+        Some(flags & 1 == 1),
+    ))
+}
+
+/// List members `key=value`, comma separated; invalid members are dropped and
+/// repeated headers are joined, as the spec asks.
+fn parse_tracestate(headers: &Headers) -> Vec<(String, String)> {
+    let Some(value) = headers.combined("tracestate") else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .filter_map(|member| {
+            let (key, value) = member.trim().split_once('=')?;
+            let valid_key = !key.is_empty()
+                && key.len() <= 256
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-*/@".contains(c));
+            let valid_value = !value.is_empty()
+                && value.len() <= 256
+                && value
+                    .chars()
+                    .all(|c| (' '..='~').contains(&c) && c != ',' && c != '=');
+            (valid_key && valid_value).then(|| (key.to_string(), value.to_string()))
+        })
+        .take(MAX_TRACESTATE_MEMBERS)
+        .collect()
+}
+
+/// `traceid-spanid[-sampled[-parentspanid]]`, or just the sampling state
+/// (`0`, `1` or `d` for debug).
+fn parse_b3_single(value: &str) -> Result<Parsed, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid b3 header \"{}\"", value);
+    let parts: Vec<&str> = value.split('-').collect();
+    if parts.len() == 1 {
+        return b3_sampled(parts[0])
+            .map(|s| (None, None, Some(s)))
+            .ok_or_else(invalid);
+    }
+    let trace = b3_trace_id(parts[0]).ok_or_else(invalid)?;
+    let span = parts[1];
+    if !is_id(span, 16) || parts.len() > 4 {
+        return Err(invalid());
+    }
+    let sampled = match parts.get(2) {
+        Some(s) => Some(b3_sampled(s).ok_or_else(invalid)?),
+        None => None,
+    };
+    let parent = match parts.get(3) {
+        Some(p) if is_id(p, 16) => Some(p.to_string()),
+        Some(_) => return Err(invalid()),
+        None => None,
+    };
+    Ok((Some((trace, span.to_ascii_lowercase())), parent, sampled))
+}
+
+fn parse_b3_multi(headers: &Headers) -> Result<Parsed, String> {
+    let get = |name| headers.get(name).map(str::trim);
+    let trace = get("x-b3-traceid")
+        .and_then(b3_trace_id)
+        .ok_or("Invalid X-B3-TraceId")?;
+    let span = get("x-b3-spanid")
+        .filter(|s| is_id(s, 16))
+        .ok_or("Invalid or missing X-B3-SpanId")?;
+    let parent = get("x-b3-parentspanid")
+        .filter(|s| is_id(s, 16))
+        .map(str::to_ascii_lowercase);
+    // Debug implies sampled.
+    let sampled = match get("x-b3-flags") {
+        Some("1") => Some(true),
+        _ => get("x-b3-sampled").and_then(b3_sampled),
+    };
+    Ok((Some((trace, span.to_ascii_lowercase())), parent, sampled))
+}
+
+fn b3_trace_id(id: &str) -> Option<String> {
+    match id.len() {
+        16 if is_id(id, 16) => Some(format!("{:0>32}", id.to_ascii_lowercase())),
+        32 if is_id(id, 32) => Some(id.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+fn b3_sampled(value: &str) -> Option<bool> {
+    match value {
+        "1" | "d" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Hex of the given length that is not all zeros (an invalid id in both formats).
+fn is_id(s: &str, len: usize) -> bool {
+    is_hex(s, len) && s.bytes().any(|b| b != b'0')
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// A random non-zero id of `len` bytes, as lowercase hex.
+fn random_id(len: usize) -> String {
+    loop {
+        let bytes = random_bytes();
+        let id = hex_encode(&bytes[..len]);
+        if is_id(&id, len * 2) {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn json(s: &str) -> Value {
+        serde_json::from_str(s).unwrap()
+    }
+
+    const TRACE: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    #[test]
+    fn test_generate_traceparent() {
+        let fresh = json(&generate_traceparent(None, None));
+        let traceparent = fresh["traceparent"].as_str().unwrap();
+        assert!(parse_traceparent(traceparent).is_ok(), "{}", traceparent);
+        assert!(traceparent.ends_with("-01"));
+        assert_eq!(fresh["parentSpanId"], Value::Null);
+        assert_ne!(
+            json(&generate_traceparent(None, None))["traceId"],
+            fresh["traceId"]
+        );
+
+        let parent = format!("00-{}-00f067aa0ba902b7-00", TRACE);
+        let child = json(&generate_traceparent(Some(parent), None));
+        assert_eq!(child["traceId"], TRACE);
+        assert_eq!(child["parentSpanId"], "00f067aa0ba902b7");
+        assert_eq!(child["sampled"], false);
+        assert_ne!(child["spanId"], "00f067aa0ba902b7");
+
+        let b3 = json(&generate_traceparent(
+            Some("80f198ee56343ba8-e457b5a2e4d86bd1-1".to_string()),
+            Some(false),
+        ));
+        assert_eq!(b3["traceId"], "000000000000000080f198ee56343ba8");
+        assert!(b3["b3"].as_str().unwrap().ends_with("-0"));
+        let bad = json(&generate_traceparent(Some("garbage".to_string()), None));
+        assert_eq!(bad["parentSpanId"], Value::Null);
+    }
+
+    #[test]
+    fn test_parse_trace_headers() {
+        let w3c = json(&parse_trace_headers(&format!(
+            r#"{{"Traceparent": "00-{}-00f067aa0ba902b7-01", "tracestate": ["rojo=00f067aa0ba902b7", "congo=t61rcWkgMzE, bad key=x"]}}"#,
+            TRACE
+        )));
+        assert_eq!(w3c["format"], "w3c");
+        assert_eq!(w3c["traceId"], TRACE);
+        assert_eq!(w3c["sampled"], true);
+        assert_eq!(w3c["correlationId"], TRACE);
+        assert_eq!(w3c["tracestate"].as_array().unwrap().len(), 2);
+        assert_eq!(w3c["tracestate"][1][0], "congo");
+
+        // An invalid traceparent falls back to B3.
+        let b3 = json(&parse_trace_headers(&format!(
+            r#"{{"traceparent": "00-{}-0000000000000000-01", "b3": "{}-e457b5a2e4d86bd1-d-05e3ac9a4f6e3b90"}}"#,
+            TRACE, TRACE
+        )));
+        assert_eq!(b3["format"], "b3");
+        assert_eq!(b3["parentSpanId"], "05e3ac9a4f6e3b90");
+        assert_eq!(b3["sampled"], true);
+        assert_eq!(b3["errors"].as_array().unwrap().len(), 1);
+
+        let multi = json(&parse_trace_headers(
+            r#"[{"key": "X-B3-TraceId", "value": "80F198EE56343BA8"}, {"key": "X-B3-SpanId", "value": "e457b5a2e4d86bd1"}, {"key": "X-B3-Sampled", "value": "0"}]"#,
+        ));
+        assert_eq!(multi["format"], "b3multi");
+        assert_eq!(multi["traceId"], "000000000000000080f198ee56343ba8");
+        assert_eq!(multi["sampled"], false);
+
+        let plain = json(&parse_trace_headers(r#"{"X-Request-Id": " req-42 "}"#));
+        assert_eq!(plain["format"], Value::Null);
+        assert_eq!(plain["correlationId"], "req-42");
+    }
+}
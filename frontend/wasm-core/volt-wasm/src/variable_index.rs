@@ -0,0 +1,435 @@
+use indexmap::IndexMap;
+use regex_lite::Regex;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+use crate::cancel::{cancelled_json, checkpoint};
+use crate::progress::Progress;
+use crate::variables::{
+    Block, is_escaped, parse_block, parse_placeholder, placeholder_regex, placeholder_variables,
+};
+
+/// Script API calls that read or write a variable by name, as found in
+/// collections imported from Postman: `pm.environment.get("token")`.
+fn script_access() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"\b(?:environment|variables|globals|collectionVariables|iterationData)\.(?:get|set|has|unset)\(\s*["']([^"']+)["']"#).unwrap()
+    })
+}
+
+/// Visit every text of a request that may use variables, labelled with its
+/// field: "method", "url", "headers", "queryParams", "auth", "body", "formData"
+/// or "script" (any field whose name contains "script", or Postman's "event").
+/// Disabled entries are included, so an index finds them and a rename keeps
+/// them consistent. Keys of {key: value} maps are visited too.
+pub(crate) fn visit_request_texts(
+    request: &mut Map<String, Value>,
+    visit: &mut impl FnMut(&'static str, &mut String),
+) {
+    for (key, value) in request.iter_mut() {
+        let field = match key.as_str() {
+            "method" => "method",
+            "url" => "url",
+            "headers" => "headers",
+            "queryParams" => "queryParams",
+            "auth" => "auth",
+            "body" | "requestBody" => "body",
+            "formData" => "formData",
+            "event" | "events" => "script",
+            k if k.to_lowercase().contains("script") => "script",
+            _ => continue,
+        };
+        let mut visit_field = |text: &mut String| visit(field, text);
+        match field {
+            "headers" | "queryParams" | "formData" => visit_pairs(value, &mut visit_field),
+            _ => visit_strings(value, &mut visit_field),
+        }
+    }
+}
+
+fn visit_strings(value: &mut Value, visit: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::String(s) => visit(s),
+        Value::Array(items) => items.iter_mut().for_each(|v| visit_strings(v, visit)),
+        Value::Object(map) => map.values_mut().for_each(|v| visit_strings(v, visit)),
+        _ => {}
+    }
+}
+
+fn visit_pairs(value: &mut Value, visit: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (mut key, mut v) in entries {
+                visit(&mut key);
+                visit_strings(&mut v, visit);
+                map.insert(key, v);
+            }
+        }
+        other => visit_strings(other, visit),
+    }
+}
+
+/// Requests of a collection (or folder) depth-first, with their folder names.
+pub(crate) fn visit_requests(
+    node: &mut Value,
+    folders: &mut Vec<String>,
+    visit: &mut impl FnMut(&[String], &mut Map<String, Value>),
+) {
+    let Value::Object(map) = node else {
+        return;
+    };
+    if let Some(Value::Array(requests)) = map.get_mut("requests") {
+        for request in requests.iter_mut().filter_map(Value::as_object_mut) {
+            visit(folders, request);
+        }
+    }
+    if let Some(Value::Array(children)) = map.get_mut("folders") {
+        for child in children.iter_mut() {
+            let name = child["name"].as_str().unwrap_or_default().to_string();
+            folders.push(name);
+            visit_requests(child, folders, visit);
+            folders.pop();
+        }
+    }
+}
+
+/// Names of the variables a text uses: `{{name}}` placeholders outside escaped
+/// braces, without built-ins, plus script accessors in scripts.
+fn names_in(text: &str, field: &str) -> Vec<String> {
+    let mut names: Vec<String> = placeholder_regex()
+        .captures_iter(text)
+        .filter(|caps| !is_escaped(text, caps.get(0).unwrap().start()))
+        .flat_map(|caps| placeholder_variables(&caps[1]))
+        .filter(|name| !name.starts_with('$') && !name.is_empty())
+        .collect();
+    if field == "script" {
+        names.extend(
+            script_access()
+                .captures_iter(text)
+                .map(|caps| caps[1].to_string()),
+        );
+    }
+    names
+}
+
+/// Index where each variable is used across a collection, for a "where is this
+/// used?" panel and before renaming.
+/// Looks at the method, URL, headers, query parameters, auth, body, form data
+/// and scripts of every request, including disabled entries; in scripts,
+/// `pm.environment.get("name")`-style accessors count as uses as well.
+/// Returns JSON {name: [{requestId, requestName, folders, field, count}]} with
+/// names in order of first use, or {error} if the collection is invalid.
+/// progress: optional callback invoked with {done, total, stage}.
+#[wasm_bindgen]
+pub fn find_variables_in_collection(
+    collection_json: &str,
+    progress: Option<js_sys::Function>,
+) -> String {
+    let mut collection: Value = match serde_json::from_str(collection_json) {
+        Ok(v @ Value::Object(_)) => v,
+        Ok(_) => return serde_json::json!({ "error": "Collection must be an object" }).to_string(),
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid collection: {}", e) })
+                .to_string();
+        }
+    };
+
+    let mut total = 0;
+    visit_requests(&mut collection, &mut Vec::new(), &mut |_, _| total += 1);
+    let mut progress = Progress::new(progress, "index", total);
+    let mut done = 0;
+
+    let mut index: IndexMap<String, Vec<Value>> = IndexMap::new();
+    let mut cancelled = false;
+    visit_requests(&mut collection, &mut Vec::new(), &mut |folders, request| {
+        if cancelled || checkpoint().is_err() {
+            cancelled = true;
+            return;
+        }
+        done += 1;
+        progress.tick(done);
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let name = request.get("name").cloned().unwrap_or(Value::Null);
+        // (variable, field) → count, in order of first use.
+        let mut uses: IndexMap<(String, &'static str), u32> = IndexMap::new();
+        visit_request_texts(request, &mut |field, text| {
+            if !text.contains("{{") && field != "script" {
+                return;
+            }
+            for var in names_in(text, field) {
+                *uses.entry((var, field)).or_default() += 1;
+            }
+        });
+        for ((var, field), count) in uses {
+            index.entry(var).or_default().push(serde_json::json!({
+                "requestId": id,
+                "requestName": name,
+                "folders": folders,
+                "field": field,
+                "count": count,
+            }));
+        }
+    });
+    if cancelled {
+        return cancelled_json();
+    }
+    serde_json::to_string(&index).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Rename a variable everywhere it is used in a collection or environment.
+/// Rewrites `{{old_name}}` placeholders (keeping any default or filters) in every
+/// text `find_variables_in_collection` looks at, script accessors such as
+/// `pm.environment.get("old_name")`, and, when the JSON has `variables` (an
+/// environment, or a collection with its own variables), the definition's key
+/// and placeholders inside other values. Escaped `\{{old_name}}` is left alone.
+/// Returns JSON {result, replacements: [{requestId, requestName, count}],
+/// variables, total} where result is the rewritten JSON, replacements lists
+/// requests that changed and variables counts changes to definitions; or {error}.
+#[wasm_bindgen]
+pub fn rename_variable(collection_json: &str, old_name: &str, new_name: &str) -> String {
+    let (old_name, new_name) = (old_name.trim(), new_name.trim());
+    if old_name.is_empty() || !valid_name(new_name) {
+        return serde_json::json!({ "error": format!("Invalid variable name \"{}\"", new_name) })
+            .to_string();
+    }
+    let mut root: Value = match serde_json::from_str(collection_json) {
+        Ok(v @ Value::Object(_)) => v,
+        Ok(_) => return serde_json::json!({ "error": "Collection must be an object" }).to_string(),
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid collection: {}", e) })
+                .to_string();
+        }
+    };
+    let rename = |field: &str, text: &mut String| -> usize {
+        let mut count = 0;
+        if text.contains("{{") {
+            let (renamed, n) = rename_in(text, old_name, new_name);
+            *text = renamed;
+            count += n;
+        }
+        if field == "script" {
+            let (renamed, n) = rename_accessors(text, old_name, new_name);
+            *text = renamed;
+            count += n;
+        }
+        count
+    };
+
+    let mut replacements = Vec::new();
+    let mut total = 0;
+    visit_requests(&mut root, &mut Vec::new(), &mut |_, request| {
+        let mut count = 0;
+        visit_request_texts(request, &mut |field, text| count += rename(field, text));
+        if count > 0 {
+            total += count;
+            replacements.push(serde_json::json!({
+                "requestId": request.get("id"),
+                "requestName": request.get("name"),
+                "count": count,
+            }));
+        }
+    });
+
+    let mut definitions = 0;
+    if let Some(variables) = root.get_mut("variables") {
+        match variables {
+            Value::Object(map) => {
+                let entries = std::mem::take(map);
+                for (mut key, mut value) in entries {
+                    if key.trim() == old_name {
+                        key = new_name.to_string();
+                        definitions += 1;
+                    }
+                    visit_strings(&mut value, &mut |text| definitions += rename("", text));
+                    map.insert(key, value);
+                }
+            }
+            Value::Array(list) => {
+                for var in list.iter_mut().filter_map(Value::as_object_mut) {
+                    if let Some(Value::String(key)) = var.get_mut("key")
+                        && key.trim() == old_name
+                    {
+                        *key = new_name.to_string();
+                        definitions += 1;
+                    }
+                    if let Some(Value::String(value)) = var.get_mut("value") {
+                        definitions += rename("", value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    serde_json::json!({
+        "result": root,
+        "replacements": replacements,
+        "variables": definitions,
+        "total": total + definitions,
+    })
+    .to_string()
+}
+
+/// A name that reads back as the same variable inside `{{ }}`.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('$')
+        && !name.contains(['{', '}', '|'])
+        && !name.contains(":-")
+}
+
+/// Replace the name part of matching placeholders; returns the text and count.
+fn rename_in(text: &str, old_name: &str, new_name: &str) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for caps in placeholder_regex().captures_iter(text) {
+        let expr = caps.get(1).unwrap();
+        let inner = expr.as_str();
+        // Where the name is written: the condition of an `{{#if}}`, else the start.
+        let (target, offset) = match parse_block(inner) {
+            Some(Block::If(condition)) => (condition, inner.rfind(condition).unwrap_or(0)),
+            Some(_) => continue,
+            None => (inner, inner.len() - inner.trim_start().len()),
+        };
+        if is_escaped(text, caps.get(0).unwrap().start())
+            || parse_placeholder(target).name != old_name
+        {
+            continue;
+        }
+        let name_start = expr.start() + offset;
+        out.push_str(&text[last..name_start]);
+        out.push_str(new_name);
+        last = name_start + old_name.len();
+        count += 1;
+    }
+    out.push_str(&text[last..]);
+    (out, count)
+}
+
+fn rename_accessors(text: &str, old_name: &str, new_name: &str) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for caps in script_access().captures_iter(text) {
+        let name = caps.get(1).unwrap();
+        if name.as_str() != old_name {
+            continue;
+        }
+        out.push_str(&text[last..name.start()]);
+        out.push_str(new_name);
+        last = name.end();
+        count += 1;
+    }
+    out.push_str(&text[last..]);
+    (out, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_variables_in_collection() {
+        let collection = r#"{
+            "name": "C",
+            "requests": [{
+                "id": "1", "name": "Login", "method": "POST",
+                "url": "{{baseUrl}}/login?x={{baseUrl}}",
+                "headers": [{"key": "X-Key", "value": "{{apiKey}}", "enabled": false}],
+                "auth": {"type": "bearer", "token": "{{token | trim}}"},
+                "body": {"user": "{{user:-ann}}", "literal": "\\{{notVar}}", "id": "{{$uuid}}"},
+                "testScript": "pm.environment.set('token', json.token); // {{baseUrl}}"
+            }],
+            "folders": [{"name": "Users", "folders": [{"name": "Admin", "requests": [{
+                "id": "2", "name": "List", "url": "{{baseUrl}}/users",
+                "queryParams": {"{{pageParam}}": "1"}
+            }]}]}]
+        }"#;
+        let index: Value =
+            serde_json::from_str(&find_variables_in_collection(collection, None)).unwrap();
+        let names: Vec<&String> = index.as_object().unwrap().keys().collect();
+        assert_eq!(names, ["baseUrl", "apiKey", "token", "user", "pageParam"]);
+
+        let base = index["baseUrl"].as_array().unwrap();
+        assert_eq!(base.len(), 3);
+        assert_eq!(base[0]["field"], "url");
+        assert_eq!(base[0]["count"], 2);
+        assert_eq!(base[1]["field"], "script");
+        assert_eq!(base[2]["requestId"], "2");
+        assert_eq!(base[2]["folders"], serde_json::json!(["Users", "Admin"]));
+        assert_eq!(index["token"][1]["field"], "script");
+        assert_eq!(index["pageParam"][0]["field"], "queryParams");
+        assert!(
+            serde_json::from_str::<Value>(&find_variables_in_collection("[]", None)).unwrap()["error"]
+                .is_string()
+        );
+    }
+
+    #[test]
+    fn test_rename_variable() {
+        let collection = r#"{
+            "requests": [{
+                "id": "1", "name": "A",
+                "url": "{{host}}/a?h={{ host | upper }}&d={{host:-x}}&e=\\{{host}}&o={{hostname}}",
+                "headers": {"X-{{host}}": "1"},
+                "testScript": "pm.environment.get(\"host\"); pm.variables.get('hostname')"
+            }, {"id": "2", "name": "B", "url": "/b"}],
+            "variables": {"host": "example.com", "url": "https://{{host}}"}
+        }"#;
+        let out: Value =
+            serde_json::from_str(&rename_variable(collection, "host", "apiHost")).unwrap();
+        let request = &out["result"]["requests"][0];
+        assert_eq!(
+            request["url"],
+            "{{apiHost}}/a?h={{ apiHost | upper }}&d={{apiHost:-x}}&e=\\{{host}}&o={{hostname}}"
+        );
+        assert_eq!(request["headers"]["X-{{apiHost}}"], "1");
+        assert_eq!(
+            request["testScript"],
+            "pm.environment.get(\"apiHost\"); pm.variables.get('hostname')"
+        );
+        assert_eq!(
+            out["replacements"],
+            serde_json::json!([{"requestId": "1", "requestName": "A", "count": 5}])
+        );
+        assert_eq!(out["result"]["variables"]["apiHost"], "example.com");
+        assert_eq!(out["result"]["variables"]["url"], "https://{{apiHost}}");
+        assert_eq!(out["variables"], 2);
+        assert_eq!(out["total"], 7);
+
+        assert_eq!(
+            rename_in("{{#if  host }}{{host}}{{/if}}{{#if hostname}}", "host", "h"),
+            ("{{#if  h }}{{h}}{{/if}}{{#if hostname}}".to_string(), 2)
+        );
+
+        let env: Value = serde_json::from_str(&rename_variable(
+            r#"{"name": "dev", "variables": [{"key": "host", "value": "x"}]}"#,
+            "host",
+            "apiHost",
+        ))
+        .unwrap();
+        assert_eq!(env["result"]["variables"][0]["key"], "apiHost");
+        for bad in ["", "a}}b", "$uuid", "a|b"] {
+            let out: Value = serde_json::from_str(&rename_variable("{}", "host", bad)).unwrap();
+            assert!(out["error"].is_string(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_find_variables_in_collection_stops_when_cancelled() {
+        let collection = r#"{"requests": [{"id": "1", "url": "{{host}}"}]}"#;
+        let handle = crate::cancel::AbortHandle::new();
+        handle.abort();
+        handle.activate();
+        assert_eq!(
+            find_variables_in_collection(collection, None),
+            cancelled_json()
+        );
+        handle.deactivate();
+        assert!(find_variables_in_collection(collection, None).contains("host"));
+    }
+}
@@ -0,0 +1,1498 @@
+use regex_lite::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+use crate::datagen;
+use crate::environments::MASK;
+use crate::expr::Expr;
+use crate::idempotency::uuid_v4;
+use crate::logging;
+use crate::model::{Environment, VariableType};
+use crate::rng::Rng;
+use crate::template::{Pipe, apply_filters, is_filter, parse_pipe};
+
+/// Longest chain of variables referring to variables that is followed; deeper
+/// references are left as placeholders.
+const MAX_DEPTH: usize = 32;
+
+/// Makes the value of a `{{$name arg ...}}` placeholder from its arguments, or
+/// None when the arguments are unusable (the placeholder is then left as written).
+type Generator = fn(&[&str], &mut Generate) -> Option<String>;
+
+/// State shared by the generators during one substitution.
+struct Generate {
+    now: f64,
+    rng: Rng,
+}
+
+struct DynamicVariable {
+    name: &'static str,
+    usage: &'static str,
+    description: &'static str,
+    generate: Generator,
+}
+
+/// Built-in variables made up at substitution time, each occurrence anew.
+/// A variable of the same name in the map takes precedence.
+const DYNAMIC_VARIABLES: &[DynamicVariable] = &[
+    DynamicVariable {
+        name: "uuid",
+        usage: "$uuid",
+        description: "Random UUID v4",
+        generate: |_, _| Some(uuid_v4()),
+    },
+    DynamicVariable {
+        name: "guid",
+        usage: "$guid",
+        description: "Random UUID v4 (alias of $uuid)",
+        generate: |_, _| Some(uuid_v4()),
+    },
+    DynamicVariable {
+        name: "randomUUID",
+        usage: "$randomUUID",
+        description: "Random UUID v4 (alias of $uuid)",
+        generate: |_, _| Some(uuid_v4()),
+    },
+    DynamicVariable {
+        name: "timestamp",
+        usage: "$timestamp",
+        description: "Current Unix time in seconds",
+        generate: |_, g| Some(((g.now / 1000.0).floor() as i64).to_string()),
+    },
+    DynamicVariable {
+        name: "timestampMs",
+        usage: "$timestampMs",
+        description: "Current Unix time in milliseconds",
+        generate: |_, g| Some((g.now.floor() as i64).to_string()),
+    },
+    DynamicVariable {
+        name: "isoTimestamp",
+        usage: "$isoTimestamp",
+        description: "Current time as ISO 8601 in UTC",
+        generate: |_, g| Some(iso_timestamp(g.now)),
+    },
+    DynamicVariable {
+        name: "randomInt",
+        usage: "$randomInt [min max]",
+        description: "Random integer between min and max inclusive (default 0 and 1000)",
+        generate: |args, g| {
+            let (min, max) = match args {
+                [] => (0, 1000),
+                [min, max] => (min.parse().ok()?, max.parse().ok()?),
+                _ => return None,
+            };
+            (min <= max).then(|| g.rng.range(min, max).to_string())
+        },
+    },
+    DynamicVariable {
+        name: "randomBoolean",
+        usage: "$randomBoolean",
+        description: "true or false",
+        generate: |_, g| Some(g.rng.chance(0.5).to_string()),
+    },
+    DynamicVariable {
+        name: "randomAlphaNumeric",
+        usage: "$randomAlphaNumeric [length]",
+        description: "Random letters and digits (default length 1)",
+        generate: |args, g| {
+            let len = match args {
+                [] => 1,
+                [len] => len.parse::<usize>().ok().filter(|n| *n <= 1024)?,
+                _ => return None,
+            };
+            Some(random_alphanumeric(&mut g.rng, len))
+        },
+    },
+    DynamicVariable {
+        name: "randomEmail",
+        usage: "$randomEmail",
+        description: "Random address at example.com",
+        generate: |_, g| {
+            Some(format!(
+                "{}@example.com",
+                random_alphanumeric(&mut g.rng, 10)
+            ))
+        },
+    },
+];
+
+/// `{{...}}` with the inner text as group 1; compiled once and shared.
+pub(crate) fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{([^}]+)\}\}").unwrap())
+}
+
+/// Whether the `{{` at `start` is written `\{{`, a literal pair of braces. An
+/// even run of backslashes is escaped backslashes, so `\\{{x}}` still substitutes.
+pub(crate) fn is_escaped(text: &str, start: usize) -> bool {
+    let backslashes = text[..start]
+        .bytes()
+        .rev()
+        .take_while(|b| *b == b'\\')
+        .count();
+    backslashes % 2 == 1
+}
+
+/// Copy text that is not a placeholder, turning `\{{` into `{{`.
+fn unescape_braces(text: &str, out: &mut String) {
+    let mut last = 0;
+    for (i, _) in text.match_indices("{{") {
+        if i >= last && is_escaped(text, i) {
+            out.push_str(&text[last..i - 1]);
+            last = i;
+        }
+    }
+    out.push_str(&text[last..]);
+}
+
+/// The parts of a placeholder's inner text.
+pub(crate) struct Placeholder<'t> {
+    pub name: &'t str,
+    /// Used when the variable is missing or empty, as in the shell:
+    /// `{{name | default:"x"}}` or `{{name:-x}}`.
+    pub default: Option<String>,
+    /// `{{name | filter | filter:arg}}` steps, applied in order to the value
+    /// (or the default) — see `template`.
+    pub filters: Vec<Pipe>,
+    /// Names an unknown filter; the placeholder is left as written.
+    pub invalid: bool,
+}
+
+pub(crate) fn parse_placeholder(expr: &str) -> Placeholder<'_> {
+    let expr = expr.trim();
+    if let Some((name, rest)) = expr.split_once('|') {
+        let mut placeholder = Placeholder {
+            name: name.trim(),
+            default: None,
+            filters: Vec::new(),
+            invalid: false,
+        };
+        for pipe in rest.split('|').map(parse_pipe) {
+            match pipe.name.as_str() {
+                "default" => placeholder.default = pipe.arg,
+                name if is_filter(name) => placeholder.filters.push(pipe),
+                _ => placeholder.invalid = true,
+            }
+        }
+        return placeholder;
+    }
+    let (name, default) = match expr.split_once(":-") {
+        Some((name, default)) => (name.trim(), Some(default.to_string())),
+        None => (expr, None),
+    };
+    Placeholder {
+        name,
+        default,
+        filters: Vec::new(),
+        invalid: false,
+    }
+}
+
+/// A `{{#if condition}}`, `{{else}}` or `{{/if}}` tag. Text between `#if` and
+/// `else` (or `/if`) is kept only when the condition holds, the rest otherwise.
+pub(crate) enum Block<'t> {
+    If(&'t str),
+    Else,
+    End,
+}
+
+pub(crate) fn parse_block(expr: &str) -> Option<Block<'_>> {
+    match expr.trim() {
+        "else" => Some(Block::Else),
+        "/if" => Some(Block::End),
+        expr => {
+            let condition = expr.strip_prefix("#if")?;
+            (condition.starts_with(char::is_whitespace) && !condition.trim().is_empty())
+                .then(|| Block::If(condition.trim()))
+        }
+    }
+}
+
+/// Whether every block tag among the placeholders pairs up. If not, none of
+/// them are treated as blocks and all are left as written.
+fn blocks_balanced(matches: &[(usize, usize, String)]) -> bool {
+    let mut depth = 0usize;
+    for (_, _, expr) in matches {
+        match parse_block(expr) {
+            Some(Block::If(_)) => depth += 1,
+            Some(Block::Else) if depth == 0 => return false,
+            Some(Block::End) => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        logging::warn("variables", || {
+            "{{#if}} without a matching {{/if}}".to_string()
+        });
+    }
+    depth == 0
+}
+
+/// The variables a placeholder's inner text refers to: each variable of an
+/// expression such as `a + b`, otherwise the one name. Block tags refer to
+/// the variables of their condition.
+pub(crate) fn placeholder_variables(expr: &str) -> Vec<String> {
+    match parse_block(expr) {
+        Some(Block::If(condition)) => return placeholder_variables(condition),
+        Some(_) => return Vec::new(),
+        None => {}
+    }
+    match Expr::parse_template(expr) {
+        Some(expr) => expr.variables(),
+        None => vec![parse_placeholder(expr).name.to_string()],
+    }
+}
+
+/// Substitutes `{{name}}` placeholders, resolving variables whose values
+/// contain placeholders themselves. Each variable is resolved once and reused,
+/// so one resolver should serve all texts substituted with the same variables.
+pub(crate) struct Resolver<'a> {
+    variables: &'a HashMap<String, String>,
+    resolved: HashMap<String, Option<String>>,
+    stack: Vec<String>,
+    cyclic: HashSet<String>,
+    cycles: Vec<Vec<String>>,
+    seen: Vec<(String, bool)>,
+    defaulted: Vec<String>,
+    spans: Vec<Span>,
+    skipped: Vec<(usize, usize)>,
+    generate: Generate,
+}
+
+/// Where a placeholder of the text passed to `substitute` was, by byte offset,
+/// and how it resolved. Placeholders inside variable values are not included.
+#[derive(Serialize)]
+pub(crate) struct Span {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    /// What the placeholder was replaced with; None when it was left as written.
+    #[serde(skip)]
+    pub value: Option<String>,
+    /// Resolved through an inline default.
+    pub defaulted: bool,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(variables: &'a HashMap<String, String>) -> Self {
+        Resolver {
+            variables,
+            resolved: HashMap::new(),
+            stack: Vec::new(),
+            cyclic: HashSet::new(),
+            cycles: Vec::new(),
+            seen: Vec::new(),
+            defaulted: Vec::new(),
+            spans: Vec::new(),
+            skipped: Vec::new(),
+            generate: Generate {
+                now: crate::now_ms(),
+                rng: datagen::seeded_rng(),
+            },
+        }
+    }
+
+    /// Replace every placeholder that can be resolved; the others stay as written.
+    pub(crate) fn substitute(&mut self, text: &str) -> String {
+        if !text.contains("{{") {
+            return text.to_string();
+        }
+        let matches: Vec<(usize, usize, String)> = placeholder_regex()
+            .captures_iter(text)
+            .map(|caps| {
+                let whole = caps.get(0).unwrap();
+                (whole.start(), whole.end(), caps[1].to_string())
+            })
+            .filter(|(start, _, _)| !is_escaped(text, *start))
+            .collect();
+        let blocks = blocks_balanced(&matches);
+        // Enclosing `{{#if}}` blocks: whether the condition held, and whether
+        // the `{{else}}` branch has been reached.
+        let mut open: Vec<(bool, bool)> = Vec::new();
+        let active = |open: &[(bool, bool)]| open.iter().all(|(held, in_else)| held != in_else);
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end, expr) in matches {
+            let skipping = !active(&open);
+            let gap = last;
+            if !skipping {
+                unescape_braces(&text[last..start], &mut out);
+            }
+            last = end;
+            if let Some(block) = parse_block(&expr).filter(|_| blocks) {
+                if self.stack.is_empty() {
+                    self.skipped.push((if skipping { gap } else { start }, end));
+                }
+                match block {
+                    Block::If(condition) => {
+                        let held = !skipping && self.condition(condition);
+                        open.push((held, false));
+                    }
+                    Block::Else => {
+                        if let Some(top) = open.last_mut() {
+                            top.1 = true;
+                        }
+                    }
+                    Block::End => {
+                        open.pop();
+                    }
+                }
+                continue;
+            }
+            if skipping {
+                if self.stack.is_empty() {
+                    self.skipped.push((gap, end));
+                }
+                continue;
+            }
+            if let Some(value) = self.evaluate(&expr) {
+                if self.stack.is_empty() {
+                    self.spans.push(Span {
+                        name: expr.trim().to_string(),
+                        start,
+                        end,
+                        value: Some(value.clone()),
+                        defaulted: false,
+                    });
+                }
+                out.push_str(&value);
+                continue;
+            }
+            let placeholder = parse_placeholder(&expr);
+            let name = placeholder.name;
+            let mut value = if placeholder.invalid {
+                None
+            } else {
+                self.lookup(name)
+            };
+            if !self.seen.iter().any(|(n, _)| n == name) {
+                self.seen.push((name.to_string(), value.is_some()));
+            }
+            let defaulted = !placeholder.invalid
+                && value.as_deref().is_none_or(str::is_empty)
+                && placeholder.default.is_some();
+            if defaulted {
+                if !self.defaulted.iter().any(|n| n == name) {
+                    self.defaulted.push(name.to_string());
+                }
+                value = placeholder.default;
+            }
+            if !placeholder.filters.is_empty() {
+                value = value.and_then(|v| {
+                    apply_filters(v, &placeholder.filters)
+                        .inspect_err(|e| {
+                            logging::warn("variables", || format!("{{{{{}}}}}: {}", expr.trim(), e))
+                        })
+                        .ok()
+                });
+            }
+            if self.stack.is_empty() {
+                self.spans.push(Span {
+                    name: name.to_string(),
+                    start,
+                    end,
+                    value: value.clone(),
+                    defaulted,
+                });
+            }
+            match value {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&text[start..end]),
+            }
+        }
+        unescape_braces(&text[last..], &mut out);
+        out
+    }
+
+    /// Placeholders of the texts substituted since the last call, in order.
+    pub(crate) fn take_spans(&mut self) -> Vec<Span> {
+        std::mem::take(&mut self.spans)
+    }
+
+    /// Byte ranges of the texts substituted since the last call that were left
+    /// out: block tags and branches whose condition did not hold. In order.
+    pub(crate) fn take_skipped(&mut self) -> Vec<(usize, usize)> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    /// `take_spans` and `take_skipped` together in text order: placeholders,
+    /// and ranges that were left out (None).
+    pub(crate) fn take_pieces(&mut self) -> Vec<(usize, usize, Option<Span>)> {
+        let mut pieces: Vec<(usize, usize, Option<Span>)> = self
+            .take_spans()
+            .into_iter()
+            .map(|span| (span.start, span.end, Some(span)))
+            .chain(self.take_skipped().into_iter().map(|(a, b)| (a, b, None)))
+            .collect();
+        pieces.sort_by_key(|piece| piece.0);
+        pieces
+    }
+
+    /// Loops found so far, each as the chain of names that leads back to its
+    /// start, e.g. ["a", "b", "a"].
+    pub(crate) fn cycles(&self) -> &[Vec<String>] {
+        &self.cycles
+    }
+
+    /// Every name looked up so far, nested ones included, in order of first use,
+    /// with whether it resolved.
+    pub(crate) fn seen(&self) -> &[(String, bool)] {
+        &self.seen
+    }
+
+    /// Names that were missing somewhere and fell back to an inline default.
+    pub(crate) fn defaulted(&self) -> &[String] {
+        &self.defaulted
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<String> {
+        if let Some(value) = self.resolved.get(name) {
+            return value.clone();
+        }
+        let Some(raw) = self.variables.get(name) else {
+            return name.strip_prefix('$').and_then(|expr| self.dynamic(expr));
+        };
+        if !raw.contains("{{") {
+            return Some(raw.clone());
+        }
+        if let Some(pos) = self.stack.iter().position(|n| n == name) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(name.to_string());
+            self.cyclic.extend(self.stack[pos..].iter().cloned());
+            self.cycles.push(cycle);
+            return None;
+        }
+        if self.stack.len() >= MAX_DEPTH {
+            logging::warn("variables", || {
+                format!("variable nesting deeper than {} at \"{}\"", MAX_DEPTH, name)
+            });
+            return None;
+        }
+
+        self.stack.push(name.to_string());
+        let value = self.substitute(raw);
+        self.stack.pop();
+        // A variable in a loop has no value; leave its placeholder visible.
+        let value = (!self.cyclic.contains(name)).then_some(value);
+        self.resolved.insert(name.to_string(), value.clone());
+        value
+    }
+
+    /// Whether a `{{#if condition}}` holds: a variable name or an expression whose
+    /// value is not empty, "false" or "0". A missing variable does not hold.
+    fn condition(&mut self, condition: &str) -> bool {
+        let value = self.evaluate(condition).or_else(|| {
+            let value = self.lookup(condition);
+            if !self.seen.iter().any(|(n, _)| n == condition) {
+                self.seen.push((condition.to_string(), value.is_some()));
+            }
+            value
+        });
+        value.is_some_and(|v| {
+            let v = v.trim();
+            !v.is_empty() && v != "false" && v != "0"
+        })
+    }
+
+    /// The value of an expression placeholder such as `{{ count + 1 }}`. None
+    /// when the text is a plain name (or a variable is named like it) or the
+    /// expression cannot be evaluated; it is then treated as a name.
+    fn evaluate(&mut self, text: &str) -> Option<String> {
+        if self.variables.contains_key(text.trim()) {
+            return None;
+        }
+        let expr = Expr::parse_template(text)?;
+        let value = expr
+            .eval(&mut |name| self.lookup(name))
+            .inspect_err(|e| {
+                logging::debug("variables", || format!("{{{{{}}}}}: {}", text.trim(), e))
+            })
+            .ok()?;
+        for name in expr.variables() {
+            if !self.seen.iter().any(|(n, _)| *n == name) {
+                self.seen.push((name, true));
+            }
+        }
+        Some(value.into_text())
+    }
+
+    fn dynamic(&mut self, expr: &str) -> Option<String> {
+        let mut parts = expr.split_whitespace();
+        let name = parts.next()?;
+        let args: Vec<&str> = parts.collect();
+        if let Some(variable) = DYNAMIC_VARIABLES.iter().find(|v| v.name == name) {
+            return (variable.generate)(&args, &mut self.generate);
+        }
+        // Fake data such as $randomFirstName takes no arguments.
+        let kind = datagen::template_kind(name).filter(|_| args.is_empty())?;
+        datagen::fake(&kind, &mut self.generate.rng)
+    }
+
+    /// Warn once about every loop found, naming the variables involved.
+    pub(crate) fn warn_cycles(&self, target: &str) {
+        for cycle in &self.cycles {
+            logging::warn(target, || {
+                format!("variables refer to each other: {}", cycle.join(" → "))
+            });
+        }
+    }
+}
+
+/// Find variables whose values refer back to themselves, directly or through
+/// other variables. Such variables are left unresolved by `substitute_variables`.
+/// variables_json: JSON object of name → value.
+/// Returns JSON array of loops, each the chain of names that leads back to its
+/// start, e.g. [["a", "b", "a"]].
+#[wasm_bindgen]
+pub fn find_variable_cycles(variables_json: &str) -> String {
+    let variables: HashMap<String, String> =
+        serde_json::from_str(variables_json).unwrap_or_default();
+    let mut names: Vec<&String> = variables.keys().collect();
+    names.sort();
+    let mut resolver = Resolver::new(&variables);
+    for name in names {
+        resolver.lookup(name);
+    }
+    serde_json::to_string(resolver.cycles()).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Substitute variables like `substitute_variables` and report every placeholder
+/// of the text, so problems can be highlighted before a request is sent.
+/// Returns JSON {text, resolved: [{name, start, end, defaulted}], unresolved:
+/// [{name, start, end, defaulted}], cycles}, with byte offsets into the input
+/// text and placeholders in text order; or {error} if the variables are invalid.
+#[wasm_bindgen]
+pub fn substitute_variables_detailed(text: &str, variables_json: &str) -> String {
+    let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid variables: {}", e) }).to_string();
+        }
+    };
+    let mut resolver = Resolver::new(&variables);
+    let result = resolver.substitute(text);
+    let (resolved, unresolved): (Vec<Span>, Vec<Span>) = resolver
+        .take_spans()
+        .into_iter()
+        .partition(|s| s.value.is_some());
+    serde_json::json!({
+        "text": result,
+        "resolved": resolved,
+        "unresolved": unresolved,
+        "cycles": resolver.cycles(),
+    })
+    .to_string()
+}
+
+/// Substitute variables like `substitute_variables` and also build a copy that is
+/// safe to show in previews, logs or screenshots: every placeholder whose value
+/// comes from a secret variable, directly or through other variables, filters or
+/// expressions, is shown as ••••••.
+/// secret_names_json: JSON array of the names of secret variables.
+/// Returns JSON {text, display, masked: [{name, start, end}]} with byte offsets of
+/// each mask into display; or {error}.
+#[wasm_bindgen]
+pub fn mask_secrets(text: &str, secret_names_json: &str, variables_json: &str) -> String {
+    let secrets: Vec<String> = match serde_json::from_str(secret_names_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid secret names: {}", e) })
+                .to_string();
+        }
+    };
+    let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid variables: {}", e) }).to_string();
+        }
+    };
+    let tainted = tainted_variables(&variables, secrets);
+
+    let mut resolver = Resolver::new(&variables);
+    let result = resolver.substitute(text);
+    let mut display = String::with_capacity(result.len());
+    let mut masked = Vec::new();
+    let mut last = 0;
+    for (start, end, span) in resolver.take_pieces() {
+        unescape_braces(&text[last..start], &mut display);
+        last = end;
+        let Some(span) = span else {
+            continue;
+        };
+        let inner = &text[span.start + 2..span.end - 2];
+        match span.value {
+            Some(_)
+                if placeholder_variables(inner)
+                    .iter()
+                    .any(|n| tainted.contains(n)) =>
+            {
+                let start = display.len();
+                display.push_str(MASK);
+                masked.push(serde_json::json!({
+                    "name": span.name,
+                    "start": start,
+                    "end": display.len(),
+                }));
+            }
+            Some(value) => display.push_str(&value),
+            None => display.push_str(&text[span.start..span.end]),
+        }
+    }
+    unescape_braces(&text[last..], &mut display);
+    resolver.warn_cycles("mask_secrets");
+    serde_json::json!({ "text": result, "display": display, "masked": masked }).to_string()
+}
+
+/// Substitute variables into a JSON body, inserting values by type: inside a
+/// string a value is escaped (`"id": "{{id}}"`), outside one it becomes a JSON
+/// value (`"id": {{id}}` gives `"id": 123`). A variable saved as a number,
+/// boolean or json is inserted as such; other values, and placeholders with
+/// filters, defaults or expressions, are inserted as written when they are a
+/// number, boolean, null, object or array, and quoted otherwise.
+/// variables_json: {name: value} where the JSON type of each value is its type,
+/// an environment, or a list of [{key, value, type, enabled}].
+/// Returns JSON {body, valid, parseError, unresolved: [name]} where valid tells
+/// whether the result is JSON, parseError says why not (null when valid) and
+/// unresolved placeholders are left as written; or {error}.
+#[wasm_bindgen]
+pub fn substitute_into_json(body_json: &str, variables_json: &str) -> String {
+    let environment: Result<Environment, _> = match serde_json::from_str(variables_json) {
+        Ok(Value::Object(map)) if !map.contains_key("variables") => {
+            serde_json::from_value(serde_json::json!({ "variables": map }))
+        }
+        Ok(Value::Array(list)) => serde_json::from_value(serde_json::json!({ "variables": list })),
+        Ok(other) => serde_json::from_value(other),
+        Err(e) => Err(e),
+    };
+    let environment = match environment {
+        Ok(env) => env,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid variables: {}", e) }).to_string();
+        }
+    };
+    let enabled = environment.variables.iter().filter(|v| v.enabled);
+    let variables: HashMap<String, String> = enabled
+        .clone()
+        .map(|v| (v.key.clone(), v.value.clone()))
+        .collect();
+    let kinds: HashMap<&str, VariableType> = enabled.map(|v| (v.key.as_str(), v.kind)).collect();
+
+    let mut resolver = Resolver::new(&variables);
+    resolver.substitute(body_json);
+    let mut body = String::with_capacity(body_json.len());
+    let mut unresolved: Vec<String> = Vec::new();
+    // Whether the output so far ends inside a JSON string, and after a backslash there.
+    let (mut quoted, mut escaped) = (false, false);
+    let mut last = 0;
+    for (start, end, span) in resolver.take_pieces() {
+        let from = body.len();
+        unescape_braces(&body_json[last..start], &mut body);
+        for c in body[from..].chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                _ => {}
+            }
+        }
+        last = end;
+        let Some(span) = span else {
+            continue;
+        };
+        let Some(value) = span.value else {
+            if !unresolved.contains(&span.name) {
+                unresolved.push(span.name);
+            }
+            body.push_str(&body_json[span.start..span.end]);
+            continue;
+        };
+        if quoted {
+            let escaped = Value::String(value).to_string();
+            body.push_str(&escaped[1..escaped.len() - 1]);
+            continue;
+        }
+        let inner = &body_json[span.start + 2..span.end - 2];
+        let placeholder = parse_placeholder(inner);
+        let plain = !span.defaulted
+            && placeholder.filters.is_empty()
+            && placeholder.default.is_none()
+            && Expr::parse_template(inner).is_none();
+        let typed = match kinds.get(placeholder.name) {
+            Some(kind) if plain && *kind != VariableType::String => kind.value(&value),
+            _ => {
+                serde_json::from_str(value.trim()).unwrap_or_else(|_| Value::String(value.clone()))
+            }
+        };
+        // Keep the value as written when it is already JSON (1.50 stays 1.50).
+        match typed {
+            Value::String(_) => body.push_str(&typed.to_string()),
+            _ if serde_json::from_str::<Value>(value.trim()).is_ok() => body.push_str(value.trim()),
+            _ => body.push_str(&typed.to_string()),
+        }
+    }
+    unescape_braces(&body_json[last..], &mut body);
+    resolver.warn_cycles("substitute_into_json");
+    let parse_error = serde_json::from_str::<Value>(&body)
+        .err()
+        .map(|e| e.to_string());
+    serde_json::json!({
+        "body": body,
+        "valid": parse_error.is_none(),
+        "parseError": parse_error,
+        "unresolved": unresolved,
+    })
+    .to_string()
+}
+
+/// The secret variables and every variable whose value refers to one, at any depth.
+fn tainted_variables(variables: &HashMap<String, String>, secrets: Vec<String>) -> HashSet<String> {
+    let re = placeholder_regex();
+    let refers: Vec<(&String, Vec<String>)> = variables
+        .iter()
+        .map(|(name, raw)| {
+            let names = re
+                .captures_iter(raw)
+                .filter(|caps| !is_escaped(raw, caps.get(0).unwrap().start()))
+                .flat_map(|caps| placeholder_variables(&caps[1]))
+                .collect();
+            (name, names)
+        })
+        .collect();
+    let mut tainted: HashSet<String> = secrets.into_iter().collect();
+    loop {
+        let before = tainted.len();
+        for (name, names) in &refers {
+            if names.iter().any(|n| tainted.contains(n)) {
+                tainted.insert(name.to_string());
+            }
+        }
+        if tainted.len() == before {
+            return tainted;
+        }
+    }
+}
+
+/// Substitute variables throughout a request in one call: method, url, header
+/// and query parameter names and values, auth fields, form-data fields and the
+/// body, which may be a string or any JSON value (its strings are substituted).
+/// request_json: a request as stored in a collection; headers, queryParams and
+/// formData may be [{key, value, enabled}] lists or {key: value} maps. Disabled
+/// entries and other fields are returned unchanged.
+/// Returns JSON {request, resolved: [name], unresolved: [{name, fields}], cycles}
+/// where fields are "method", "url", "headers", "queryParams", "auth", "body" or
+/// "formData"; or {error}.
+#[wasm_bindgen]
+pub fn substitute_request(request_json: &str, variables_json: &str) -> String {
+    let mut request = match serde_json::from_str::<Value>(request_json) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => return serde_json::json!({ "error": "Request must be an object" }).to_string(),
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid request: {}", e) }).to_string();
+        }
+    };
+    let variables: HashMap<String, String> = match serde_json::from_str(variables_json) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid variables: {}", e) }).to_string();
+        }
+    };
+
+    let mut resolver = Resolver::new(&variables);
+    let mut resolved: Vec<String> = Vec::new();
+    let mut unresolved: Vec<(String, Vec<&'static str>)> = Vec::new();
+    for (key, field) in [
+        ("method", "method"),
+        ("url", "url"),
+        ("headers", "headers"),
+        ("queryParams", "queryParams"),
+        ("auth", "auth"),
+        ("body", "body"),
+        ("requestBody", "body"),
+        ("formData", "formData"),
+    ] {
+        let Some(value) = request.get_mut(key) else {
+            continue;
+        };
+        let mut substitute = |text: &mut String| {
+            if !text.contains("{{") {
+                return;
+            }
+            *text = resolver.substitute(text);
+            for span in resolver.take_spans() {
+                if span.value.is_some() {
+                    if !resolved.contains(&span.name) {
+                        resolved.push(span.name);
+                    }
+                    continue;
+                }
+                match unresolved.iter_mut().find(|(name, _)| *name == span.name) {
+                    Some((_, fields)) if !fields.contains(&field) => fields.push(field),
+                    Some(_) => {}
+                    None => unresolved.push((span.name, vec![field])),
+                }
+            }
+        };
+        match field {
+            "headers" | "queryParams" | "formData" => substitute_pairs(value, &mut substitute),
+            // The auth type names the scheme; everything else is a credential.
+            "auth" => {
+                if let Value::Object(auth) = value {
+                    for (_, v) in auth.iter_mut().filter(|(k, _)| *k != "type") {
+                        substitute_strings(v, &mut substitute);
+                    }
+                }
+            }
+            _ => substitute_strings(value, &mut substitute),
+        }
+    }
+
+    let unresolved: Vec<Value> = unresolved
+        .into_iter()
+        .map(|(name, fields)| serde_json::json!({ "name": name, "fields": fields }))
+        .collect();
+    serde_json::json!({
+        "request": request,
+        "resolved": resolved,
+        "unresolved": unresolved,
+        "cycles": resolver.cycles(),
+    })
+    .to_string()
+}
+
+/// Every string inside a JSON value; object keys are left alone.
+fn substitute_strings(value: &mut Value, substitute: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::String(s) => substitute(s),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|v| substitute_strings(v, substitute)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|v| substitute_strings(v, substitute)),
+        _ => {}
+    }
+}
+
+/// Keys and values of a [{key, value, enabled}] list or a {key: value} map.
+/// Disabled entries and file form fields keep their text.
+fn substitute_pairs(value: &mut Value, substitute: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::Array(items) => {
+            for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                if item.get("enabled") == Some(&Value::Bool(false)) {
+                    continue;
+                }
+                let file = item.get("type").and_then(Value::as_str) == Some("file");
+                for key in ["key", "name", "value"] {
+                    if file && key == "value" {
+                        continue;
+                    }
+                    if let Some(Value::String(s)) = item.get_mut(key) {
+                        substitute(s);
+                    }
+                }
+            }
+        }
+        Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (mut key, mut v) in entries {
+                substitute(&mut key);
+                substitute_strings(&mut v, substitute);
+                map.insert(key, v);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Substitute variables from layered scopes and report where each value came from.
+/// scopes_json: array of {name, variables} (or plain {key: value} maps, named by
+/// their index) ordered from lowest to highest precedence, e.g. globals,
+/// environment, collection, request. Disabled variables are skipped. Nested and
+/// built-in variables resolve as in `substitute_variables`, with the same precedence.
+/// Returns JSON {result, variables: [{name, scope, value, secret}], unresolved,
+/// defaulted, cycles} where variables lists every name used, nested ones
+/// included, scope is null for unresolved names, "dynamic" for built-ins and
+/// "default" for names that fell back to an inline default (also listed in
+/// defaulted, so the UI can warn), and secret values are masked; or {error}.
+#[wasm_bindgen]
+pub fn resolve_variables_scoped(text: &str, scopes_json: &str) -> String {
+    let scopes: Vec<Value> = match serde_json::from_str(scopes_json) {
+        Ok(s) => s,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid scopes: {}", e) }).to_string();
+        }
+    };
+    let mut variables: HashMap<String, String> = HashMap::new();
+    let mut origin: HashMap<String, (String, bool)> = HashMap::new();
+    for (i, scope) in scopes.into_iter().enumerate() {
+        let scope = match scope {
+            Value::Object(map) if !map.contains_key("variables") => {
+                serde_json::json!({ "name": i.to_string(), "variables": map })
+            }
+            v => v,
+        };
+        let mut scope: Environment = match serde_json::from_value(scope) {
+            Ok(s) => s,
+            Err(e) => {
+                return serde_json::json!({ "error": format!("Invalid scope {}: {}", i, e) })
+                    .to_string();
+            }
+        };
+        if scope.name.is_empty() {
+            scope.name = i.to_string();
+        }
+        for var in scope
+            .variables
+            .into_iter()
+            .filter(|v| v.enabled && !v.key.trim().is_empty())
+        {
+            let key = var.key.trim().to_string();
+            origin.insert(key.clone(), (scope.name.clone(), var.secret));
+            variables.insert(key, var.value);
+        }
+    }
+
+    let mut resolver = Resolver::new(&variables);
+    let result = resolver.substitute(text);
+    let mut used = Vec::new();
+    let mut unresolved = Vec::new();
+    for (name, resolved) in resolver.seen() {
+        if !resolved && resolver.defaulted().contains(name) {
+            used.push(
+                serde_json::json!({ "name": name, "scope": "default", "value": null, "secret": false }),
+            );
+            continue;
+        }
+        if !resolved {
+            unresolved.push(name.clone());
+            used.push(
+                serde_json::json!({ "name": name, "scope": null, "value": null, "secret": false }),
+            );
+            continue;
+        }
+        let entry = match origin.get(name) {
+            Some((scope, secret)) => {
+                let value = if *secret { MASK } else { &variables[name] };
+                serde_json::json!({ "name": name, "scope": scope, "value": value, "secret": secret })
+            }
+            None => {
+                serde_json::json!({ "name": name, "scope": "dynamic", "value": null, "secret": false })
+            }
+        };
+        used.push(entry);
+    }
+    serde_json::json!({
+        "result": result,
+        "variables": used,
+        "unresolved": unresolved,
+        "defaulted": resolver.defaulted(),
+        "cycles": resolver.cycles(),
+    })
+    .to_string()
+}
+
+/// List the built-in `{{$name}}` variables for autocomplete, including the
+/// fake data kinds of `generate_fake` as `$randomFirstName` and so on.
+/// Returns JSON array of {name, usage, description}, with names including the `$`.
+#[wasm_bindgen]
+pub fn list_dynamic_variables() -> String {
+    let builtin = DYNAMIC_VARIABLES
+        .iter()
+        .map(|v| (v.name.to_string(), v.usage.to_string(), v.description));
+    let fake = datagen::template_names().map(|(name, description)| {
+        let usage = format!("${}", name);
+        (name, usage, description)
+    });
+    let list: Vec<_> = builtin
+        .chain(fake)
+        .map(|(name, usage, description)| {
+            serde_json::json!({
+                "name": format!("${}", name),
+                "usage": usage,
+                "description": description,
+            })
+        })
+        .collect();
+    Value::from(list).to_string()
+}
+
+fn random_alphanumeric(rng: &mut Rng, len: usize) -> String {
+    const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    (0..len)
+        .map(|_| CHARS[rng.range(0, CHARS.len() as i64 - 1) as usize] as char)
+        .collect()
+}
+
+fn iso_timestamp(ms: f64) -> String {
+    let ms = ms.floor() as i64;
+    let secs = ms.div_euclid(1000);
+    let (year, month, day) = crate::civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        ms.rem_euclid(1000)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_resolution() {
+        let variables = vars(&[
+            ("protocol", "https"),
+            ("host", "{{ sub }}.example.com"),
+            ("sub", "api"),
+            ("baseUrl", "{{protocol}}://{{host}}"),
+        ]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute("{{baseUrl}}/users/{{id}}"),
+            "https://api.example.com/users/{{id}}"
+        );
+        assert!(resolver.cycles().is_empty());
+    }
+
+    #[test]
+    fn test_cycles_are_reported() {
+        let variables = vars(&[
+            ("a", "x{{b}}"),
+            ("b", "{{a}}y"),
+            ("self", "{{self}}"),
+            ("ok", "{{c}}"),
+            ("c", "fine"),
+        ]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(resolver.substitute("{{a}}|{{ok}}"), "{{a}}|fine");
+        assert_eq!(resolver.cycles(), [vec!["a", "b", "a"]]);
+
+        let cycles: Vec<Vec<String>> = serde_json::from_str(&find_variable_cycles(
+            r#"{"a":"{{b}}","b":"{{a}}","self":"{{self}}","c":"1"}"#,
+        ))
+        .unwrap();
+        assert_eq!(cycles, [vec!["a", "b", "a"], vec!["self", "self"]]);
+    }
+
+    #[test]
+    fn test_dynamic_variables() {
+        let variables = vars(&[("$timestamp", "fixed"), ("id", "{{$uuid}}")]);
+        let mut resolver = Resolver::new(&variables);
+        resolver.generate.now = 1_709_208_000_250.0;
+        assert_eq!(resolver.substitute("{{$timestamp}}"), "fixed");
+        assert_eq!(
+            resolver.substitute("{{$timestampMs}} {{ $isoTimestamp }}"),
+            "1709208000250 2024-02-29T12:00:00.250Z"
+        );
+
+        let uuid = resolver.substitute("{{$uuid}}");
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert_ne!(resolver.substitute("{{$guid}}"), uuid);
+        // A variable holding a generator is generated once, then reused.
+        assert_eq!(resolver.substitute("{{id}}"), resolver.substitute("{{id}}"));
+
+        for _ in 0..20 {
+            let n: i64 = resolver.substitute("{{$randomInt 5 7}}").parse().unwrap();
+            assert!((5..=7).contains(&n));
+        }
+        let full = "{{$randomInt -9223372036854775808 9223372036854775807}}";
+        assert!(resolver.substitute(full).parse::<i64>().is_ok());
+        assert!(
+            resolver
+                .substitute("{{$randomEmail}}")
+                .ends_with("@example.com")
+        );
+        assert_eq!(resolver.substitute("{{$randomAlphaNumeric 8}}").len(), 8);
+        assert_eq!(
+            resolver.substitute("{{$randomInt 9 1}}"),
+            "{{$randomInt 9 1}}"
+        );
+        assert_eq!(resolver.substitute("{{$nope}}"), "{{$nope}}");
+
+        let listed: Value = serde_json::from_str(&list_dynamic_variables()).unwrap();
+        assert_eq!(listed[0]["name"], "$uuid");
+        assert!(
+            listed
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|v| v["name"] == "$randomCity")
+        );
+    }
+
+    #[test]
+    fn test_fake_data_is_reproducible_with_seed() {
+        let variables = HashMap::new();
+        let text = "{{$randomFirstName}} {{$randomCity}} {{$randomInt}} {{$randomCreditCard}}";
+        crate::datagen::set_random_seed(Some(42.0));
+        let first = Resolver::new(&variables).substitute(text);
+        let second = Resolver::new(&variables).substitute(text);
+        crate::datagen::set_random_seed(None);
+        assert_eq!(first, second);
+        assert!(!first.contains("{{"), "{}", first);
+        assert_eq!(
+            Resolver::new(&variables).substitute("{{$randomCity 2}} {{$randomness}}"),
+            "{{$randomCity 2}} {{$randomness}}"
+        );
+    }
+
+    #[test]
+    fn test_resolve_variables_scoped() {
+        let scopes = r#"[
+            {"name": "globals", "variables": {"host": "example.com", "protocol": "http"}},
+            {"name": "staging", "variables": [
+                {"key": "host", "value": "staging.example.com"},
+                {"key": "token", "value": "s3cret", "secret": true},
+                {"key": "protocol", "value": "ftp", "enabled": false}
+            ]},
+            {"baseUrl": "{{protocol}}://{{host}}"}
+        ]"#;
+        let out: Value = serde_json::from_str(&resolve_variables_scoped(
+            "{{baseUrl}}/x?t={{token}}&id={{$uuid}}&{{missing}}",
+            scopes,
+        ))
+        .unwrap();
+        let result = out["result"].as_str().unwrap();
+        assert!(result.starts_with("http://staging.example.com/x?t=s3cret&id="));
+        assert!(result.ends_with("&{{missing}}"));
+
+        let scope_of = |name: &str| {
+            out["variables"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|v| v["name"] == name)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(scope_of("baseUrl")["scope"], "2");
+        assert_eq!(scope_of("protocol")["scope"], "globals");
+        assert_eq!(scope_of("host")["value"], "staging.example.com");
+        assert_eq!(scope_of("token")["value"], MASK);
+        assert_eq!(scope_of("$uuid")["scope"], "dynamic");
+        assert_eq!(out["unresolved"], serde_json::json!(["missing"]));
+        assert!(
+            serde_json::from_str::<Value>(&resolve_variables_scoped("", "{}")).unwrap()["error"]
+                .is_string()
+        );
+    }
+
+    #[test]
+    fn test_inline_defaults() {
+        let variables = vars(&[("host", "api.example.com"), ("empty", "")]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute(
+                r#"{{host:-localhost}}:{{port:-8080}}/{{ v | default:"v\"1\"" }}/{{empty:-x}}/{{a|default:'b'}}"#
+            ),
+            r#"api.example.com:8080/v"1"/x/b"#
+        );
+        assert_eq!(resolver.defaulted(), ["port", "v", "empty", "a"]);
+        // Unknown pipes leave the placeholder alone.
+        assert_eq!(resolver.substitute("{{host | shout}}"), "{{host | shout}}");
+
+        let out: Value = serde_json::from_str(&resolve_variables_scoped(
+            "{{region:-eu}}",
+            r#"[{"other": "1"}]"#,
+        ))
+        .unwrap();
+        assert_eq!(out["result"], "eu");
+        assert_eq!(out["defaulted"], serde_json::json!(["region"]));
+        assert_eq!(out["variables"][0]["scope"], "default");
+        assert_eq!(out["unresolved"], serde_json::json!([]));
+        assert_eq!(
+            crate::find_variables("{{a:-1}} {{ b | default:\"2\" }}"),
+            r#"["a","b"]"#
+        );
+    }
+
+    #[test]
+    fn test_filters_in_placeholders() {
+        let variables = vars(&[("token", "user:pass"), ("name", " Ann Lee ")]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute("Basic {{token | base64}} {{ name | trim | urlencode | lower }}"),
+            "Basic dXNlcjpwYXNz ann%20lee"
+        );
+        assert_eq!(
+            resolver.substitute("{{missing | default:\"a b\" | upper}} {{missing | upper}}"),
+            "A B {{missing | upper}}"
+        );
+        assert_eq!(
+            resolver.substitute("{{token | base64decode}}"),
+            "{{token | base64decode}}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_variables_detailed() {
+        let out: Value = serde_json::from_str(&substitute_variables_detailed(
+            "é{{host}}/{{ missing }}/{{port:-80}}/{{a}}",
+            r#"{"host": "{{inner}}", "a": "{{b}}", "b": "{{a}}"}"#,
+        ))
+        .unwrap();
+        assert_eq!(out["text"], "é{{inner}}/{{ missing }}/80/{{a}}");
+        assert_eq!(
+            out["resolved"],
+            serde_json::json!([
+                {"name": "host", "start": 2, "end": 10, "defaulted": false},
+                {"name": "port", "start": 25, "end": 37, "defaulted": true},
+            ])
+        );
+        let unresolved: Vec<&str> = out["unresolved"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(unresolved, ["missing", "a"]);
+        assert_eq!(out["unresolved"][0]["start"], 11);
+        assert_eq!(out["cycles"][0], serde_json::json!(["a", "b", "a"]));
+        assert!(
+            serde_json::from_str::<Value>(&substitute_variables_detailed("x", "[1]")).unwrap()
+                ["error"]
+                .is_string()
+        );
+    }
+
+    #[test]
+    fn test_expressions_in_placeholders() {
+        let variables = vars(&[
+            ("userId", "41"),
+            ("price", "2.5"),
+            ("qty", "{{base}}"),
+            ("base", "4"),
+            ("api-key", "k"),
+        ]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute("/users/{{ userId + 1 }}?total={{price * qty}}"),
+            "/users/42?total=10"
+        );
+        assert_eq!(
+            resolver.substitute("{{ qty > 1 ? 'items' : 'item' }} {{api-key}}"),
+            "items k"
+        );
+        // An expression with a missing variable stays as written.
+        assert_eq!(
+            resolver.substitute("{{ missing + 1 }}"),
+            "{{ missing + 1 }}"
+        );
+        let spans = resolver.take_spans();
+        assert_eq!(spans[0].name, "userId + 1");
+        assert!(spans.last().unwrap().value.is_none());
+        assert!(resolver.seen().iter().any(|(n, ok)| n == "base" && *ok));
+    }
+
+    #[test]
+    fn test_mask_secrets() {
+        let variables = r#"{
+            "host": "api.example.com",
+            "token": "s3cr3t",
+            "auth": "Bearer {{token}}",
+            "user": "ann"
+        }"#;
+        let text =
+            "https://{{host}}/?u={{user}}&t={{token | base64}} {{auth}} {{missing}} \\{{token}}";
+        let out: Value =
+            serde_json::from_str(&mask_secrets(text, r#"["token"]"#, variables)).unwrap();
+        assert_eq!(
+            out["text"],
+            "https://api.example.com/?u=ann&t=czNjcjN0 Bearer s3cr3t {{missing}} {{token}}"
+        );
+        let display = out["display"].as_str().unwrap();
+        assert_eq!(
+            display,
+            format!("https://api.example.com/?u=ann&t={MASK} {MASK} {{{{missing}}}} {{{{token}}}}")
+        );
+        assert!(!display.contains("s3cr3t") && !display.contains("czNjcjN0"));
+        let masked = out["masked"].as_array().unwrap();
+        assert_eq!(masked.len(), 2);
+        assert_eq!(masked[1]["name"], "auth");
+        let (start, end) = (
+            masked[1]["start"].as_u64().unwrap() as usize,
+            masked[1]["end"].as_u64().unwrap() as usize,
+        );
+        assert_eq!(&display[start..end], MASK);
+
+        let text = "{{#if user}}u={{user}}{{else}}t={{token}}{{/if}};{{#if token}}{{token}}{{/if}}";
+        let out: Value =
+            serde_json::from_str(&mask_secrets(text, r#"["token"]"#, variables)).unwrap();
+        assert_eq!(out["text"], "u=ann;s3cr3t");
+        assert_eq!(out["display"], format!("u=ann;{MASK}"));
+
+        let out: Value = serde_json::from_str(&mask_secrets("x", "nope", "{}")).unwrap();
+        assert!(out["error"].is_string());
+    }
+
+    #[test]
+    fn test_substitute_into_json() {
+        let into = |body: &str, variables: &str| -> Value {
+            serde_json::from_str(&substitute_into_json(body, variables)).unwrap()
+        };
+        let body = r#"{"id": {{id}}, "label": "No. {{id}} {{name}}", "active": {{active}},
+            "tags": {{tags}}, "name": {{name}}, "next": {{id + 1}}, "zip": {{zip}}}"#;
+        let out = into(
+            body,
+            r#"{"id": 123, "name": "Ann \"A\"", "active": true, "tags": ["a"], "zip": "02139"}"#,
+        );
+        assert_eq!(out["valid"], true, "{}", out["parseError"]);
+        let parsed: Value = serde_json::from_str(out["body"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!({"id": 123, "label": "No. 123 Ann \"A\"", "active": true,
+                "tags": ["a"], "name": "Ann \"A\"", "next": 124, "zip": "02139"})
+        );
+
+        // Typed lists: numbers keep their formatting, values that do not fit their
+        // type are quoted, and disabled variables stay unresolved.
+        let out = into(
+            r#"{"a": {{a}}, "b": {{b}}, "c": {{c}}, "d": "{{d}}"}"#,
+            r#"[{"key": "a", "value": "1.50", "type": "number"}, {"key": "b", "value": "n/a",
+                "type": "number"}, {"key": "c", "value": "TRUE", "type": "boolean"},
+                {"key": "d", "value": "x", "enabled": false}]"#,
+        );
+        assert_eq!(
+            out["body"],
+            r#"{"a": 1.50, "b": "n/a", "c": true, "d": "{{d}}"}"#
+        );
+        assert_eq!(out["unresolved"], serde_json::json!(["d"]));
+        assert_eq!(out["valid"], true);
+
+        let out = into(r#"{"a": {{missing}}}"#, "{}");
+        assert_eq!(out["valid"], false);
+        assert!(out["parseError"].is_string());
+        assert!(into("{}", "nope")["error"].is_string());
+    }
+
+    #[test]
+    fn test_conditional_blocks() {
+        let variables = vars(&[
+            ("useAuth", "true"),
+            ("token", "abc"),
+            ("debug", "0"),
+            ("tier", "{{plan}}"),
+            ("plan", "pro"),
+            ("off", "false"),
+        ]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute("{{#if useAuth}}Authorization: Bearer {{token}}{{/if}}"),
+            "Authorization: Bearer abc"
+        );
+        assert_eq!(
+            resolver.substitute(
+                "a{{#if debug}}D{{else}}-{{#if missing}}M{{else}}{{tier}}{{/if}}{{/if}}b"
+            ),
+            "a-prob"
+        );
+        assert_eq!(
+            resolver.substitute("{{#if off}}x{{/if}}{{#if !off && tier == 'pro'}}y{{/if}}"),
+            "y"
+        );
+        // Skipped branches are not looked up.
+        assert!(!resolver.seen().iter().any(|(n, _)| n == "M"));
+        // Unbalanced tags are left as written.
+        assert_eq!(resolver.substitute("{{#if useAuth}}x"), "{{#if useAuth}}x");
+        assert_eq!(resolver.substitute("x{{/if}}"), "x{{/if}}");
+
+        let mut resolver = Resolver::new(&variables);
+        let text = "{{#if useAuth}}{{token}}{{/if}} {{missing}}";
+        assert_eq!(resolver.substitute(text), "abc {{missing}}");
+        let spans = resolver.take_spans();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(&text[spans[1].start..spans[1].end], "{{missing}}");
+        assert_eq!(placeholder_variables(" #if a && b "), ["a", "b"]);
+        assert!(placeholder_variables("/if").is_empty());
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let variables = vars(&[("name", "Ann"), ("tpl", "\\{{name}} is {{name}}")]);
+        let mut resolver = Resolver::new(&variables);
+        assert_eq!(
+            resolver.substitute(r"Hi \{{name}}, {{name}}! \\{{name}} \{{ open"),
+            r"Hi {{name}}, Ann! \\Ann {{ open"
+        );
+        assert_eq!(resolver.substitute("{{tpl}}"), "{{name}} is Ann");
+        let spans: Vec<String> = resolver.take_spans().into_iter().map(|s| s.name).collect();
+        assert_eq!(spans, ["name", "name", "tpl"]);
+        assert_eq!(crate::find_variables(r"\{{a}} {{b}}"), r#"["b"]"#);
+        assert!(!crate::has_variables(r"\{{a}}"));
+    }
+
+    #[test]
+    fn test_substitute_request() {
+        let request = r#"{
+            "id": "r1",
+            "method": "{{verb:-GET}}",
+            "url": "{{baseUrl}}/users/{{id}}",
+            "headers": [
+                {"key": "Authorization", "value": "Bearer {{token}}", "enabled": true},
+                {"key": "X-Off", "value": "{{off}}", "enabled": false}
+            ],
+            "queryParams": {"{{param}}": "{{id}}"},
+            "auth": {"type": "basic", "username": "{{user}}", "password": "{{pass}}"},
+            "body": {"query": "query { user(id: \"{{id}}\") }", "n": 1},
+            "formData": [
+                {"key": "note", "value": "{{missing}}"},
+                {"key": "file", "value": "{{path}}", "type": "file"}
+            ]
+        }"#;
+        let variables = r#"{"baseUrl": "https://x.io", "id": "7", "token": "t", "param": "q",
+            "user": "ann", "pass": "pw"}"#;
+        let out: Value = serde_json::from_str(&substitute_request(request, variables)).unwrap();
+        let r = &out["request"];
+        assert_eq!(r["id"], "r1");
+        assert_eq!(r["method"], "GET");
+        assert_eq!(r["url"], "https://x.io/users/7");
+        assert_eq!(r["headers"][0]["value"], "Bearer t");
+        assert_eq!(r["headers"][1]["value"], "{{off}}");
+        assert_eq!(r["queryParams"]["q"], "7");
+        assert_eq!(r["auth"]["type"], "basic");
+        assert_eq!(r["auth"]["password"], "pw");
+        assert_eq!(r["body"]["query"], "query { user(id: \"7\") }");
+        assert_eq!(r["formData"][1]["value"], "{{path}}");
+        assert_eq!(
+            out["unresolved"],
+            serde_json::json!([{"name": "missing", "fields": ["formData"]}])
+        );
+        assert_eq!(out["resolved"][1], "baseUrl");
+
+        let plain: Value = serde_json::from_str(&substitute_request(
+            r#"{"url": "{{a}}", "requestBody": "{{a}}{{b}}"}"#,
+            r#"{"a": "1"}"#,
+        ))
+        .unwrap();
+        assert_eq!(plain["request"]["requestBody"], "1{{b}}");
+        assert_eq!(
+            plain["unresolved"][0]["fields"],
+            serde_json::json!(["body"])
+        );
+        assert!(
+            serde_json::from_str::<Value>(&substitute_request("[]", "{}")).unwrap()["error"]
+                .is_string()
+        );
+    }
+}
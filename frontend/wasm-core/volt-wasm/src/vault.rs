@@ -0,0 +1,265 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::{base64_decode, base64_encode};
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Argon2id cost (OWASP minimum: 19 MiB, 2 passes, 1 lane).
+pub(crate) const DEFAULT_COST: KdfCost = KdfCost {
+    memory_kib: 19_456,
+    iterations: 2,
+    parallelism: 1,
+};
+
+/// Largest cost accepted from a blob, so a tampered one cannot exhaust memory
+/// or hang key derivation.
+const MAX_COST: KdfCost = KdfCost {
+    memory_kib: 1024 * 1024,
+    iterations: 10,
+    parallelism: 16,
+};
+
+#[derive(Clone, Copy)]
+pub(crate) struct KdfCost {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+/// Self-describing envelope so blobs stay decryptable if the defaults change.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultBlob {
+    v: u32,
+    kdf: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl VaultBlob {
+    /// Header fields authenticated along with the ciphertext.
+    fn associated_data(&self) -> Vec<u8> {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.v, self.kdf, self.memory_kib, self.iterations, self.parallelism, self.salt
+        )
+        .into_bytes()
+    }
+}
+
+/// Encrypt an environment with a passphrase.
+/// The key is derived with Argon2id from a random salt; data is sealed with AES-256-GCM.
+/// Returns the vault blob as JSON {v, kdf, memoryKib, iterations, parallelism, salt, nonce, ciphertext},
+/// or {error} if env_json is not valid JSON or the passphrase is empty.
+#[wasm_bindgen]
+pub fn vault_encrypt(env_json: &str, passphrase: &str) -> String {
+    match encrypt_with(env_json, passphrase, DEFAULT_COST) {
+        Ok(blob) => blob,
+        Err(e) => error_json(&e),
+    }
+}
+
+/// Decrypt a blob produced by `vault_encrypt`.
+/// Returns JSON {ok: true, plaintext} with the original environment JSON as a
+/// string, or {ok: false, error} on a wrong passphrase or corrupted blob.
+#[wasm_bindgen]
+pub fn vault_decrypt(blob: &str, passphrase: &str) -> String {
+    match decrypt(blob, passphrase) {
+        Ok(plain) => serde_json::json!({ "ok": true, "plaintext": plain }).to_string(),
+        Err(e) => serde_json::json!({ "ok": false, "error": e }).to_string(),
+    }
+}
+
+pub(crate) fn encrypt_with(
+    env_json: &str,
+    passphrase: &str,
+    cost: KdfCost,
+) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+    // Normalise and validate before sealing so a decrypted vault is always usable.
+    let env: Value =
+        serde_json::from_str(env_json).map_err(|e| format!("Invalid environment JSON: {}", e))?;
+    let plain = env.to_string();
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| format!("No secure random source: {}", e))?;
+    getrandom::getrandom(&mut nonce).map_err(|e| format!("No secure random source: {}", e))?;
+
+    let mut blob = VaultBlob {
+        v: VAULT_VERSION,
+        kdf: "argon2id".to_string(),
+        memory_kib: cost.memory_kib,
+        iterations: cost.iterations,
+        parallelism: cost.parallelism,
+        salt: base64_encode(&salt),
+        nonce: base64_encode(&nonce),
+        ciphertext: String::new(),
+    };
+    let cipher = derive_cipher(passphrase, &salt, cost)?;
+    let payload = Payload {
+        msg: plain.as_bytes(),
+        aad: &blob.associated_data(),
+    };
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| "Encryption failed".to_string())?;
+    blob.ciphertext = base64_encode(&ciphertext);
+    serde_json::to_string(&blob).map_err(|e| e.to_string())
+}
+
+pub(crate) fn decrypt(blob: &str, passphrase: &str) -> Result<String, String> {
+    let blob: VaultBlob =
+        serde_json::from_str(blob).map_err(|e| format!("Invalid vault blob: {}", e))?;
+    if blob.v != VAULT_VERSION || blob.kdf != "argon2id" {
+        return Err(format!(
+            "Unsupported vault format (v{}, {})",
+            blob.v, blob.kdf
+        ));
+    }
+    let decode = |field: &str, value: &str| {
+        base64_decode(value).ok_or_else(|| format!("Invalid vault blob: bad {}", field))
+    };
+    let salt = decode("salt", &blob.salt)?;
+    let nonce = decode("nonce", &blob.nonce)?;
+    let ciphertext = decode("ciphertext", &blob.ciphertext)?;
+    if salt.len() != SALT_LEN {
+        return Err("Invalid vault blob: bad salt".to_string());
+    }
+    if nonce.len() != NONCE_LEN {
+        return Err("Invalid vault blob: bad nonce".to_string());
+    }
+
+    let cost = KdfCost {
+        memory_kib: blob.memory_kib,
+        iterations: blob.iterations,
+        parallelism: blob.parallelism,
+    };
+    if cost.memory_kib > MAX_COST.memory_kib
+        || cost.iterations > MAX_COST.iterations
+        || cost.parallelism > MAX_COST.parallelism
+    {
+        return Err(format!(
+            "Unsupported key derivation cost (memoryKib {}, iterations {}, parallelism {})",
+            cost.memory_kib, cost.iterations, cost.parallelism
+        ));
+    }
+    let cipher = derive_cipher(passphrase, &salt, cost)?;
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: &blob.associated_data(),
+    };
+    let plain = cipher
+        .decrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| "Wrong passphrase or corrupted vault".to_string())?;
+    String::from_utf8(plain).map_err(|_| "Wrong passphrase or corrupted vault".to_string())
+}
+
+fn derive_cipher(passphrase: &str, salt: &[u8], cost: KdfCost) -> Result<Aes256Gcm, String> {
+    let params = Params::new(cost.memory_kib, cost.iterations, cost.parallelism, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters keep debug-mode tests fast; decrypt reads them from the blob.
+    const TEST_COST: KdfCost = KdfCost {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_vault_round_trip() {
+        let env = r#"{"name":"prod","variables":{"token":"s3cret"}}"#;
+        let blob = encrypt_with(env, "correct horse", TEST_COST).unwrap();
+        assert!(!blob.contains("s3cret"));
+        let parsed: Value = serde_json::from_str(&blob).unwrap();
+        assert_eq!(parsed["kdf"], "argon2id");
+        let out: Value = serde_json::from_str(&vault_decrypt(&blob, "correct horse")).unwrap();
+        assert_eq!(out["ok"], true);
+        assert_eq!(out["plaintext"], env);
+
+        // A vault holding an "error" key is still told apart from a failure.
+        let blob = encrypt_with(r#"{"error":"x"}"#, "pw", TEST_COST).unwrap();
+        let out: Value = serde_json::from_str(&vault_decrypt(&blob, "pw")).unwrap();
+        assert_eq!(out["ok"], true);
+        assert_eq!(out["plaintext"], r#"{"error":"x"}"#);
+    }
+
+    #[test]
+    fn test_vault_is_salted() {
+        let a = encrypt_with("{}", "pw", TEST_COST).unwrap();
+        let b = encrypt_with("{}", "pw", TEST_COST).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_vault_wrong_passphrase_and_tampering() {
+        let blob = encrypt_with(r#"{"a":1}"#, "pw", TEST_COST).unwrap();
+        let wrong: Value = serde_json::from_str(&vault_decrypt(&blob, "nope")).unwrap();
+        assert_eq!(wrong["ok"], false);
+        assert_eq!(wrong["error"], "Wrong passphrase or corrupted vault");
+
+        let mut tampered: VaultBlob = serde_json::from_str(&blob).unwrap();
+        let mut ct = base64_decode(&tampered.ciphertext).unwrap();
+        ct[0] ^= 1;
+        tampered.ciphertext = base64_encode(&ct);
+        let out = vault_decrypt(&serde_json::to_string(&tampered).unwrap(), "pw");
+        assert!(out.contains("Wrong passphrase"));
+
+        // The header is authenticated, so lowering the cost is detected too.
+        let mut tampered: VaultBlob = serde_json::from_str(&blob).unwrap();
+        tampered.memory_kib = 32;
+        let out = vault_decrypt(&serde_json::to_string(&tampered).unwrap(), "pw");
+        assert!(out.contains("Wrong passphrase"));
+    }
+
+    #[test]
+    fn test_vault_rejects_excessive_cost() {
+        let blob = encrypt_with("{}", "pw", TEST_COST).unwrap();
+        for (field, value) in [("memoryKib", 4_000_000_000u32), ("iterations", u32::MAX)] {
+            let mut tampered: Value = serde_json::from_str(&blob).unwrap();
+            tampered[field] = value.into();
+            let out = vault_decrypt(&tampered.to_string(), "pw");
+            assert!(out.contains("Unsupported key derivation cost"), "{}", out);
+        }
+    }
+
+    #[test]
+    fn test_vault_invalid_input() {
+        assert!(vault_encrypt("not json", "pw").contains("Invalid environment JSON"));
+        assert!(vault_encrypt("{}", "").contains("must not be empty"));
+        assert!(vault_decrypt("garbage", "pw").contains("Invalid vault blob"));
+        let mut short_salt: Value =
+            serde_json::from_str(&encrypt_with("{}", "pw", TEST_COST).unwrap()).unwrap();
+        short_salt["salt"] = base64_encode(b"salt").into();
+        let out: Value =
+            serde_json::from_str(&vault_decrypt(&short_salt.to_string(), "pw")).unwrap();
+        assert_eq!(out["error"], "Invalid vault blob: bad salt");
+    }
+}
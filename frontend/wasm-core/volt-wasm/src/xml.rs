@@ -0,0 +1,747 @@
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::json_scan::{Position, ScanError};
+
+/// Element nesting allowed before giving up, in either direction. The tree
+/// read by `Parser` is never deeper, so `to_json` needs no check of its own.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct XmlOptions {
+    /// Prefix marking attributes among an element's keys ("@id").
+    attribute_prefix: String,
+    /// Key for an element's text when it also has attributes or children.
+    text_key: String,
+    /// Leave attributes out when reading XML.
+    ignore_attributes: bool,
+    /// Drop namespace prefixes (`soap:Body` → `Body`) and `xmlns`
+    /// declarations when reading XML.
+    strip_namespaces: bool,
+    /// Element names read as arrays even when they occur once.
+    always_array: Vec<String>,
+    /// Read text and attribute values that are JSON numbers or booleans as
+    /// such; otherwise every value is a string.
+    parse_values: bool,
+    /// Namespaces declared on the root element when writing XML, by prefix
+    /// ("" for the default namespace).
+    namespaces: IndexMap<String, String>,
+    /// Root element when the JSON is not an object with a single key.
+    root_name: String,
+    /// Element for the items of a top-level or nested array.
+    item_name: String,
+    /// Indent by two spaces.
+    pretty: bool,
+    /// Start with an `<?xml ...?>` declaration.
+    declaration: bool,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        XmlOptions {
+            attribute_prefix: "@".to_string(),
+            text_key: "#text".to_string(),
+            ignore_attributes: false,
+            strip_namespaces: false,
+            always_array: Vec::new(),
+            parse_values: false,
+            namespaces: IndexMap::new(),
+            root_name: "root".to_string(),
+            item_name: "item".to_string(),
+            pretty: true,
+            declaration: false,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    /// Text and CDATA content, concatenated.
+    text: String,
+}
+
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '<' | '>' | '/' | '=' | '"' | '\'' | '&')
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> ScanError {
+        let before = &self.text[..self.pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ScanError {
+            message: message.into(),
+            position: Position {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+            },
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skip past `end`, failing with `what` when it never comes.
+    fn skip_past(&mut self, end: &str, what: &str) -> Result<&'a str, ScanError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let skipped = &self.text[self.pos..self.pos + i];
+                self.pos += i + end.len();
+                Ok(skipped)
+            }
+            None => Err(self.error(format!("Unterminated {}", what))),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ScanError> {
+        let rest = self.rest();
+        let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    /// Text with entity and character references decoded.
+    fn decode(&self, raw: &str, start: usize) -> Result<String, ScanError> {
+        let mut out = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(i) = rest.find('&') {
+            out.push_str(&rest[..i]);
+            let at = Parser {
+                pos: start + (raw.len() - rest.len()) + i,
+                ..*self
+            };
+            let Some(end) = rest[i..].find(';') else {
+                return Err(at.error("Unterminated entity reference"));
+            };
+            let entity = &rest[i + 1..i + end];
+            let decoded = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            match decoded {
+                Some(c) => out.push(c),
+                None => return Err(at.error(format!("Unknown entity '&{};'", entity))),
+            }
+            rest = &rest[i + end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Comments, processing instructions and a DOCTYPE outside the root.
+    fn skip_misc(&mut self) -> Result<(), ScanError> {
+        loop {
+            self.skip_space();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                // Entity declarations in an internal subset are not expanded.
+                let mut brackets = 0;
+                let len = rest.find(|c| {
+                    match c {
+                        '[' => brackets += 1,
+                        ']' => brackets -= 1,
+                        '>' if brackets == 0 => return true,
+                        _ => {}
+                    }
+                    false
+                });
+                match len {
+                    Some(len) => self.pos += len + 1,
+                    None => return Err(self.error("Unterminated DOCTYPE")),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element, ScanError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Nesting too deep"));
+        }
+        self.depth += 1;
+        self.pos += 1;
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        };
+        loop {
+            self.skip_space();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                self.depth -= 1;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            if self.rest().is_empty() {
+                return Err(self.error(format!("Unterminated tag <{}>", element.name)));
+            }
+            let attribute_at = self.pos;
+            let name = self.name()?;
+            self.skip_space();
+            if !self.rest().starts_with('=') {
+                return Err(self.error(format!("Expected '=' after attribute '{}'", name)));
+            }
+            self.pos += 1;
+            self.skip_space();
+            let quote = match self.rest().chars().next() {
+                Some(q @ ('"' | '\'')) => q,
+                _ => return Err(self.error("Expected a quoted attribute value")),
+            };
+            self.pos += 1;
+            let start = self.pos;
+            let raw = self.skip_past(&quote.to_string(), "attribute value")?;
+            let value = self.decode(raw, start)?;
+            if element.attributes.iter().any(|(n, _)| *n == name) {
+                self.pos = attribute_at;
+                return Err(self.error(format!("Duplicate attribute '{}'", name)));
+            }
+            element.attributes.push((name, value));
+        }
+        loop {
+            let start = self.pos;
+            let len = self.rest().find('<').unwrap_or(self.rest().len());
+            let raw = &self.text[start..start + len];
+            self.pos += len;
+            let text = self.decode(raw, start)?;
+            element.text.push_str(&text);
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(format!("Missing </{}>", element.name)));
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let cdata = self.skip_past("]]>", "CDATA section")?;
+                element.text.push_str(cdata);
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if rest.starts_with("</") {
+                let close_at = self.pos;
+                self.pos += 2;
+                let name = self.name()?;
+                self.skip_space();
+                if name != element.name || !self.rest().starts_with('>') {
+                    self.pos = close_at;
+                    return Err(self.error(format!("Expected </{}>", element.name)));
+                }
+                self.pos += 1;
+                self.depth -= 1;
+                return Ok(element);
+            } else {
+                let child = self.element()?;
+                element.children.push(child);
+            }
+        }
+    }
+
+    fn document(&mut self) -> Result<Element, ScanError> {
+        self.skip_misc()?;
+        if !self.rest().starts_with('<') {
+            return Err(self.error("Expected a root element"));
+        }
+        let root = self.element()?;
+        self.skip_misc()?;
+        if !self.rest().is_empty() {
+            return Err(self.error("Unexpected content after the root element"));
+        }
+        Ok(root)
+    }
+}
+
+impl XmlOptions {
+    fn local<'a>(&self, name: &'a str) -> &'a str {
+        match name.split_once(':') {
+            Some((_, local)) if self.strip_namespaces => local,
+            _ => name,
+        }
+    }
+
+    fn scalar(&self, text: &str) -> Value {
+        if self.parse_values {
+            match text {
+                "true" => return Value::Bool(true),
+                "false" => return Value::Bool(false),
+                _ => {
+                    if let Ok(n) = serde_json::from_str::<Number>(text) {
+                        return Value::Number(n);
+                    }
+                }
+            }
+        }
+        Value::String(text.to_string())
+    }
+
+    /// An element's content: its text alone when it has nothing else, null
+    /// when empty, otherwise an object of attributes, children and text.
+    fn to_json(&self, element: &Element) -> Value {
+        let text = element.text.trim();
+        let mut map = Map::new();
+        if !self.ignore_attributes {
+            for (name, value) in &element.attributes {
+                let declaration = name == "xmlns" || name.starts_with("xmlns:");
+                if declaration && self.strip_namespaces {
+                    continue;
+                }
+                let key = format!("{}{}", self.attribute_prefix, self.local(name));
+                map.insert(key, self.scalar(value));
+            }
+        }
+        for child in &element.children {
+            let name = self.local(&child.name);
+            let value = self.to_json(child);
+            match map.get_mut(name) {
+                Some(Value::Array(items)) => items.push(value),
+                Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                None if self.always_array.iter().any(|n| n == name) => {
+                    map.insert(name.to_string(), Value::Array(vec![value]));
+                }
+                None => {
+                    map.insert(name.to_string(), value);
+                }
+            }
+        }
+        if map.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                self.scalar(text)
+            };
+        }
+        if !text.is_empty() {
+            map.insert(self.text_key.clone(), self.scalar(text));
+        }
+        Value::Object(map)
+    }
+}
+
+/// Escape text for XML content or a double-quoted attribute value.
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A valid XML name from a JSON key: other characters become '_', and names
+/// that cannot start an element get a leading '_'.
+fn element_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => name,
+        _ => format!("_{}", name),
+    }
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+struct Writer<'a> {
+    options: &'a XmlOptions,
+    out: String,
+}
+
+impl Writer<'_> {
+    fn indent(&mut self, depth: usize) {
+        if self.options.pretty {
+            if !self.out.is_empty() {
+                self.out.push('\n');
+            }
+            self.out.push_str(&"  ".repeat(depth));
+        }
+    }
+
+    /// Arrays repeat the element once per item.
+    fn element(&mut self, name: &str, value: &Value, depth: usize) -> Result<(), String> {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    match item {
+                        Value::Array(_) => {
+                            let wrapped =
+                                serde_json::json!({ self.options.item_name.clone(): item });
+                            self.single(name, &wrapped, depth, &[])?;
+                        }
+                        _ => self.single(name, item, depth, &[])?,
+                    }
+                }
+                Ok(())
+            }
+            _ => self.single(name, value, depth, &[]),
+        }
+    }
+
+    fn single(
+        &mut self,
+        name: &str,
+        value: &Value,
+        depth: usize,
+        extra: &[(String, String)],
+    ) -> Result<(), String> {
+        if depth >= MAX_DEPTH {
+            return Err("Nesting too deep".to_string());
+        }
+        let name = element_name(name);
+        self.indent(depth);
+        self.out.push('<');
+        self.out.push_str(&name);
+        for (attribute, text) in extra {
+            self.out
+                .push_str(&format!(" {}=\"{}\"", attribute, xml_escape(text)));
+        }
+        let prefix = &self.options.attribute_prefix;
+        let (mut text, mut children) = (None, Vec::new());
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map {
+                    if *key == self.options.text_key {
+                        text = Some(text_of(item));
+                    } else if let Some(attribute) = key
+                        .strip_prefix(prefix.as_str())
+                        .filter(|_| !prefix.is_empty())
+                    {
+                        self.out.push_str(&format!(
+                            " {}=\"{}\"",
+                            element_name(attribute),
+                            xml_escape(&text_of(item))
+                        ));
+                    } else {
+                        children.push((key, item));
+                    }
+                }
+            }
+            Value::Null => {}
+            other => text = Some(text_of(other)),
+        }
+        if children.is_empty() && text.as_deref().is_none_or(str::is_empty) {
+            self.out.push_str("/>");
+            return Ok(());
+        }
+        self.out.push('>');
+        if let Some(text) = text {
+            self.out.push_str(&xml_escape(&text));
+        }
+        for (key, item) in &children {
+            self.element(key, item, depth + 1)?;
+        }
+        if !children.is_empty() {
+            self.indent(depth);
+        }
+        self.out.push_str(&format!("</{}>", name));
+        Ok(())
+    }
+}
+
+/// Convert JSON to XML for SOAP/XML request bodies. An object with a single
+/// key becomes the root element; anything else is wrapped in `rootName`.
+/// Keys starting with `attributePrefix` become attributes, `textKey` the
+/// element's text, arrays repeat their element, and null is an empty element.
+/// options_json: {attributePrefix ("@"), textKey ("#text"), namespaces
+/// ({prefix: uri} declared on the root), rootName ("root"), itemName
+/// ("item"), pretty (true), declaration (false)}.
+/// Returns JSON {xml} or {error}.
+#[wasm_bindgen]
+pub fn json_to_xml(json_str: &str, options_json: &str) -> String {
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid JSON: {}", e) }).to_string();
+        }
+    };
+    let options: XmlOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let namespaces: Vec<(String, String)> = options
+        .namespaces
+        .iter()
+        .map(|(prefix, uri)| match prefix.as_str() {
+            "" => ("xmlns".to_string(), uri.clone()),
+            _ => (format!("xmlns:{}", prefix), uri.clone()),
+        })
+        .collect();
+    let mut writer = Writer {
+        options: &options,
+        out: String::new(),
+    };
+    if options.declaration {
+        writer
+            .out
+            .push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    }
+    let written = match &value {
+        Value::Object(map)
+            if map.len() == 1
+                && map.values().all(|v| !v.is_array())
+                && map.keys().all(|k| *k != options.text_key) =>
+        {
+            let (name, item) = map
+                .iter()
+                .next()
+                .unwrap_or((&options.root_name, &Value::Null));
+            writer.single(name, item, 0, &namespaces)
+        }
+        Value::Array(_) => {
+            let wrapped = serde_json::json!({ options.item_name.clone(): value });
+            writer.single(&options.root_name, &wrapped, 0, &namespaces)
+        }
+        _ => writer.single(&options.root_name, &value, 0, &namespaces),
+    };
+    match written {
+        Ok(()) => serde_json::json!({ "xml": writer.out }).to_string(),
+        Err(e) => serde_json::json!({ "error": e }).to_string(),
+    }
+}
+
+/// Convert an XML document (e.g. a SOAP response) to pretty-printed JSON so
+/// the JSON assertions and extractors work on it. The result is
+/// {rootElement: content}: elements with only text become strings, empty
+/// ones null, and others objects of attributes (`attributePrefix` + name),
+/// child elements (repeated names become arrays) and text (`textKey`).
+/// Comments and processing instructions are dropped; only the predefined and
+/// numeric entities are expanded.
+/// options_json: {attributePrefix ("@"), textKey ("#text"), ignoreAttributes,
+/// stripNamespaces, alwaysArray (element names), parseValues}.
+/// Returns JSON {json} or {error, line, column}.
+#[wasm_bindgen]
+pub fn xml_to_json(xml_str: &str, options_json: &str) -> String {
+    let _timer = crate::logging::timer("xml_to_json");
+    let options: XmlOptions = serde_json::from_str(options_json).unwrap_or_default();
+    let mut parser = Parser {
+        text: xml_str.trim_start_matches('\u{feff}'),
+        pos: 0,
+        depth: 0,
+    };
+    match parser.document() {
+        Ok(root) => {
+            let name = options.local(&root.name);
+            let mut content = options.to_json(&root);
+            if options.always_array.iter().any(|n| n == name) {
+                content = Value::Array(vec![content]);
+            }
+            let json = serde_json::json!({ name: content });
+            serde_json::json!({
+                "json": serde_json::to_string_pretty(&json).unwrap_or_default(),
+            })
+            .to_string()
+        }
+        Err(e) => serde_json::json!({
+            "error": e.message,
+            "line": e.position.line,
+            "column": e.position.column,
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SOAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- response -->
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <m:GetPriceResponse xmlns:m="https://x.io/prices">
+      <m:Price currency="EUR">12.50</m:Price>
+      <m:Item id="1">A &amp; B</m:Item>
+      <m:Item id="2"><![CDATA[<raw>]]></m:Item>
+      <m:Note/>
+      <m:Stock>007</m:Stock>
+      <m:Available>true</m:Available>
+    </m:GetPriceResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+
+    fn to_json(xml: &str, options: &str) -> Value {
+        let out: Value = serde_json::from_str(&xml_to_json(xml, options)).unwrap();
+        serde_json::from_str(out["json"].as_str().unwrap_or_else(|| panic!("{}", out))).unwrap()
+    }
+
+    fn to_xml(json: Value, options: &str) -> String {
+        let out: Value = serde_json::from_str(&json_to_xml(&json.to_string(), options)).unwrap();
+        out["xml"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_xml_to_json() {
+        let out = to_json(SOAP, "{}");
+        let response = &out["soap:Envelope"]["soap:Body"]["m:GetPriceResponse"];
+        assert_eq!(
+            out["soap:Envelope"]["@xmlns:soap"],
+            "http://schemas.xmlsoap.org/soap/envelope/"
+        );
+        assert_eq!(
+            response["m:Price"],
+            json!({"@currency": "EUR", "#text": "12.50"})
+        );
+        assert_eq!(response["m:Item"][0]["#text"], "A & B");
+        assert_eq!(response["m:Item"][1]["#text"], "<raw>");
+        assert_eq!(response["m:Note"], Value::Null);
+
+        let out = to_json(
+            SOAP,
+            r#"{"stripNamespaces": true, "parseValues": true, "ignoreAttributes": true, "alwaysArray": ["Price"]}"#,
+        );
+        assert_eq!(
+            out,
+            json!({"Envelope": {"Body": {"GetPriceResponse": {
+                "Price": [serde_json::from_str::<Value>("12.50").unwrap()],
+                "Item": ["A & B", "<raw>"],
+                "Note": null,
+                "Stock": "007",
+                "Available": true,
+            }}}})
+        );
+        assert_eq!(
+            to_json(
+                "<a x='1'>t<b>&#65;&#x42;</b></a>",
+                r#"{"attributePrefix": "_", "textKey": "text"}"#
+            ),
+            json!({"a": {"_x": "1", "b": "AB", "text": "t"}})
+        );
+
+        let error = |xml: &str| {
+            let out: Value = serde_json::from_str(&xml_to_json(xml, "")).unwrap();
+            (
+                out["error"].as_str().unwrap().to_string(),
+                out["line"].clone(),
+                out["column"].clone(),
+            )
+        };
+        assert_eq!(
+            error("<a>\n  <b></c>\n</a>"),
+            ("Expected </b>".to_string(), json!(2), json!(6))
+        );
+        assert_eq!(error("<a x='1' x='2'/>").0, "Duplicate attribute 'x'");
+        assert_eq!(error("<a>&nbsp;</a>").0, "Unknown entity '&nbsp;'");
+        assert_eq!(
+            error("<a></a><b/>").0,
+            "Unexpected content after the root element"
+        );
+        assert_eq!(error("<a>").0, "Missing </a>");
+    }
+
+    #[test]
+    fn test_json_to_xml() {
+        let body = json!({"soap:Envelope": {
+            "soap:Body": {
+                "GetPrice": {"@currency": "EUR", "Item": ["A & B", {"@id": 2, "#text": "<c>"}], "Note": null, "Qty": 3},
+            },
+        }});
+        let options = r#"{"namespaces": {"soap": "http://schemas.xmlsoap.org/soap/envelope/"}, "declaration": true}"#;
+        assert_eq!(
+            to_xml(body, options),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetPrice currency="EUR">
+      <Item>A &amp; B</Item>
+      <Item id="2">&lt;c&gt;</Item>
+      <Note/>
+      <Qty>3</Qty>
+    </GetPrice>
+  </soap:Body>
+</soap:Envelope>"#
+        );
+        assert_eq!(
+            to_xml(
+                json!([1, [2, 3], {"first name": "x"}]),
+                r#"{"pretty": false}"#
+            ),
+            "<root><item>1</item><item><item>2</item><item>3</item></item><item><first_name>x</first_name></item></root>"
+        );
+        assert_eq!(
+            to_xml(
+                json!({"a": 1, "2xx": true}),
+                r#"{"rootName": "r", "pretty": false}"#
+            ),
+            "<r><a>1</a><_2xx>true</_2xx></r>"
+        );
+    }
+
+    #[test]
+    fn test_json_xml_roundtrip() {
+        let doc = json!({"order": {"@id": "7", "line": [{"sku": "a", "qty": "1"}, {"sku": "b", "qty": "2"}], "note": "x < y"}});
+        let xml = to_xml(doc.clone(), "{}");
+        assert_eq!(to_json(&xml, "{}"), doc);
+    }
+
+    #[test]
+    fn test_deep_nesting_is_an_error() {
+        let deep = format!("{}{}", "<a>".repeat(2000), "</a>".repeat(2000));
+        let out: Value = serde_json::from_str(&xml_to_json(&deep, "{}")).unwrap();
+        assert_eq!(out["error"], "Nesting too deep");
+        let ok = format!("{}{}", "<a>".repeat(100), "</a>".repeat(100));
+        assert!(to_json(&ok, "{}").is_object());
+
+        // serde_json stops at 128 levels while parsing, so build the value directly.
+        let mut value = Value::Null;
+        for _ in 0..200 {
+            value = json!({ "a": value });
+        }
+        let options = XmlOptions::default();
+        let mut writer = Writer {
+            options: &options,
+            out: String::new(),
+        };
+        assert_eq!(
+            writer.single("root", &value, 0, &[]),
+            Err("Nesting too deep".to_string())
+        );
+        let deep = format!("{}{}", r#"{"a":"#.repeat(2000), "}".repeat(2000));
+        let out: Value = serde_json::from_str(&json_to_xml(&deep, "{}")).unwrap();
+        assert!(out["error"].is_string());
+    }
+}
@@ -0,0 +1,1139 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::json_scan::{Position, ScanError};
+
+/// Nesting allowed before giving up.
+const MAX_DEPTH: usize = 128;
+/// Values that aliases may expand to in total, so a document of aliases to
+/// aliases cannot blow up memory.
+const MAX_ALIAS_NODES: usize = 1_000_000;
+
+/// Plain scalars that YAML 1.1 readers take as booleans; quoted when emitted.
+const LEGACY_BOOLEANS: &[&str] = &["y", "yes", "n", "no", "on", "off"];
+
+fn is_blank(c: Option<char>) -> bool {
+    matches!(c, None | Some(' ' | '\t' | '\n'))
+}
+
+fn is_flow_indicator(c: char) -> bool {
+    matches!(c, ',' | '[' | ']' | '{' | '}')
+}
+
+fn node_count(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(node_count).sum::<usize>(),
+        Value::Object(map) => 1 + map.values().map(node_count).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// A number written the way YAML's core schema reads one, as JSON.
+fn number(text: &str) -> Option<Number> {
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let negative = text.starts_with('-');
+    let radix = |digits: &str, radix: u32| {
+        let n = u64::from_str_radix(digits, radix).ok()?;
+        if negative {
+            let n = i64::try_from(n).ok()?;
+            Some(Number::from(-n))
+        } else {
+            Some(Number::from(n))
+        }
+    };
+    if let Some(hex) = unsigned.strip_prefix("0x") {
+        return radix(hex, 16);
+    }
+    if let Some(octal) = unsigned.strip_prefix("0o") {
+        return radix(octal, 8);
+    }
+    let mut chars = unsigned.chars().peekable();
+    let mut digits = 0;
+    while chars.next_if(char::is_ascii_digit).is_some() {
+        digits += 1;
+    }
+    if chars.next_if_eq(&'.').is_some() {
+        while chars.next_if(char::is_ascii_digit).is_some() {
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return None;
+    }
+    if chars.next_if(|c| matches!(c, 'e' | 'E')).is_some() {
+        chars.next_if(|c| matches!(c, '+' | '-'));
+        chars.next_if(char::is_ascii_digit)?;
+        while chars.next_if(char::is_ascii_digit).is_some() {}
+    }
+    if chars.next().is_some() {
+        return None;
+    }
+    // Keep every digit when the text is also a JSON number.
+    let json = text.strip_prefix('+').unwrap_or(text);
+    serde_json::from_str::<Number>(json)
+        .ok()
+        .or_else(|| json.parse::<f64>().ok().and_then(Number::from_f64))
+}
+
+/// A plain scalar's value under the YAML 1.2 core schema.
+fn resolve(text: &str) -> Result<Value, String> {
+    Ok(match text {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ => {
+            let lower = text.to_ascii_lowercase();
+            let unsigned = lower.trim_start_matches(['-', '+']);
+            if unsigned == ".inf" || lower == ".nan" {
+                return Err(format!("{} cannot be represented in JSON", text));
+            }
+            match number(text) {
+                Some(n) => Value::Number(n),
+                None => Value::String(text.to_string()),
+            }
+        }
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Chomp {
+    Clip,
+    Strip,
+    Keep,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    /// 0-based column of `pos`.
+    column: usize,
+    /// Where the current line starts.
+    line_start: usize,
+    depth: usize,
+    anchors: HashMap<String, Value>,
+    alias_nodes: usize,
+}
+
+impl Parser {
+    fn error(&self, message: impl Into<String>) -> ScanError {
+        ScanError {
+            message: message.into(),
+            position: Position {
+                line: self.line,
+                column: self.column + 1,
+            },
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+            self.line_start = self.pos;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn at_eol(&self) -> bool {
+        matches!(self.peek(), None | Some('\n'))
+    }
+
+    fn skip_space(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !self.at_eol() {
+                self.bump();
+            }
+        }
+    }
+
+    /// "---" or "..." at the start of a line.
+    fn at_document_marker(&self) -> bool {
+        self.column == 0
+            && matches!(
+                (self.peek(), self.peek_at(1), self.peek_at(2)),
+                (Some('-'), Some('-'), Some('-')) | (Some('.'), Some('.'), Some('.'))
+            )
+            && is_blank(self.peek_at(3))
+    }
+
+    /// Move to the next token, past spaces, comments and line breaks.
+    fn skip_to_content(&mut self) -> Result<(), ScanError> {
+        loop {
+            self.skip_space();
+            self.skip_comment();
+            if self.peek() != Some('\n') {
+                break;
+            }
+            self.bump();
+        }
+        if self.peek().is_some()
+            && self.chars[self.line_start..self.pos].contains(&'\t')
+            && self.chars[self.line_start..self.pos]
+                .iter()
+                .all(|c| *c == ' ' || *c == '\t')
+        {
+            return Err(self.error("Tabs are not allowed for indentation"));
+        }
+        Ok(())
+    }
+
+    /// After a value: only a comment may follow on its line.
+    fn end_of_value(&mut self) -> Result<(), ScanError> {
+        self.skip_space();
+        self.skip_comment();
+        let at_line_content = self.chars[self.line_start..self.pos]
+            .iter()
+            .all(|c| *c == ' ' || *c == '\t');
+        if self.at_eol() || at_line_content {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "Unexpected '{}' after a value",
+                self.peek().unwrap_or(' ')
+            )))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ScanError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("Nesting too deep"));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| !is_blank(Some(*c)) && !is_flow_indicator(*c))
+        {
+            name.push(c);
+            self.bump();
+        }
+        name
+    }
+
+    /// An anchor (`&name`) and/or a tag (`!tag`) before a node.
+    fn properties(&mut self) -> Result<(Option<String>, Option<String>), ScanError> {
+        let (mut anchor, mut tag) = (None, None);
+        loop {
+            match self.peek() {
+                Some('&') if anchor.is_none() => {
+                    self.bump();
+                    let name = self.name();
+                    if name.is_empty() {
+                        return Err(self.error("Expected an anchor name"));
+                    }
+                    anchor = Some(name);
+                }
+                Some('!') if tag.is_none() => tag = Some(self.name()),
+                _ => return Ok((anchor, tag)),
+            }
+            self.skip_space();
+        }
+    }
+
+    fn alias(&mut self) -> Result<Value, ScanError> {
+        self.bump();
+        let name = self.name();
+        let value = self
+            .anchors
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| self.error(format!("Unknown alias '*{}'", name)))?;
+        self.alias_nodes += node_count(&value);
+        if self.alias_nodes > MAX_ALIAS_NODES {
+            return Err(self.error("Aliases expand to too many values"));
+        }
+        Ok(value)
+    }
+
+    fn finish(&mut self, value: Value, anchor: Option<String>) -> Value {
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, value.clone());
+        }
+        value
+    }
+
+    fn scalar(&self, text: String, plain: bool, tag: Option<&str>) -> Result<Value, ScanError> {
+        if !plain || tag == Some("!!str") {
+            return Ok(Value::String(text));
+        }
+        resolve(&text).map_err(|e| self.error(e))
+    }
+
+    /// A node in block context; `parent` is the indentation of the collection
+    /// holding it (-1 at the root). `inline` when it follows a key on the
+    /// same line, where a block sequence or another key cannot start.
+    fn block_node(&mut self, parent: isize, inline: bool) -> Result<Value, ScanError> {
+        let start_line = self.line;
+        self.skip_to_content()?;
+        if self.peek().is_none() || self.at_document_marker() {
+            return Ok(Value::Null);
+        }
+        if self.line != start_line && self.column as isize <= parent {
+            return Ok(Value::Null);
+        }
+        let (anchor, tag) = self.properties()?;
+        if anchor.is_some() || tag.is_some() {
+            self.skip_comment();
+            if self.at_eol() {
+                let value = self.block_node(parent, false)?;
+                return Ok(self.finish(value, anchor));
+            }
+        }
+        self.enter()?;
+        let column = self.column;
+        let value = match self.peek() {
+            Some('-') if is_blank(self.peek_at(1)) => {
+                if inline {
+                    return Err(self.error("A block sequence cannot start on the line of its key"));
+                }
+                self.block_sequence(column)?
+            }
+            Some(indicator @ ('|' | '>')) => {
+                Value::String(self.block_scalar(parent, indicator == '>')?)
+            }
+            Some('[' | '{') => {
+                let value = self.flow_node()?;
+                self.skip_space();
+                if self.peek() == Some(':') {
+                    return Err(self.error("Flow collections as keys are not supported"));
+                }
+                value
+            }
+            Some('*') => self.alias()?,
+            Some('?') if is_blank(self.peek_at(1)) => {
+                return Err(self.error("Complex keys ('?') are not supported"));
+            }
+            _ => {
+                let key_line = self.line;
+                let (text, plain) = self.block_scalar_text(parent)?;
+                self.skip_space();
+                if self.peek() == Some(':') && is_blank(self.peek_at(1)) && self.line == key_line {
+                    if inline {
+                        return Err(
+                            self.error("Nested mappings cannot start on the line of their key")
+                        );
+                    }
+                    self.block_mapping(column, text)?
+                } else {
+                    self.scalar(text, plain, tag.as_deref())?
+                }
+            }
+        };
+        self.depth -= 1;
+        Ok(self.finish(value, anchor))
+    }
+
+    fn block_sequence(&mut self, indent: usize) -> Result<Value, ScanError> {
+        let mut items = Vec::new();
+        loop {
+            self.bump();
+            let item = self.block_node(indent as isize, false)?;
+            items.push(item);
+            self.end_of_value()?;
+            self.skip_to_content()?;
+            if self.peek().is_none() || self.at_document_marker() || self.column < indent {
+                break;
+            }
+            if self.column > indent {
+                return Err(self.error("Bad indentation of a sequence entry"));
+            }
+            if !(self.peek() == Some('-') && is_blank(self.peek_at(1))) {
+                break;
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn block_mapping(&mut self, indent: usize, first_key: String) -> Result<Value, ScanError> {
+        let mut map = Map::new();
+        let mut merges = Vec::new();
+        let mut key = first_key;
+        loop {
+            let key_position = self.error("");
+            self.bump();
+            let value = self.mapping_value(indent)?;
+            if key == "<<" {
+                merges.push(value);
+            } else if map.contains_key(&key) {
+                return Err(ScanError {
+                    message: format!("Duplicate key '{}'", key),
+                    ..key_position
+                });
+            } else {
+                map.insert(key, value);
+            }
+            self.end_of_value()?;
+            self.skip_to_content()?;
+            if self.peek().is_none() || self.at_document_marker() || self.column < indent {
+                break;
+            }
+            if self.column > indent {
+                return Err(self.error("Bad indentation of a mapping entry"));
+            }
+            if self.peek() == Some('-') && is_blank(self.peek_at(1)) {
+                return Err(self.error("Expected a key, found a sequence entry"));
+            }
+            key = self.mapping_key()?;
+        }
+        // Merged keys (`<<: *base`) never override the mapping's own.
+        for merge in merges {
+            let sources = match merge {
+                Value::Array(items) => items,
+                other => vec![other],
+            };
+            for source in sources {
+                let Value::Object(source) = source else {
+                    return Err(self.error("'<<' expects a mapping or a list of mappings"));
+                };
+                for (k, v) in source {
+                    map.entry(k).or_insert(v);
+                }
+            }
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn mapping_key(&mut self) -> Result<String, ScanError> {
+        let line = self.line;
+        let (key, _) = match self.peek() {
+            Some('"' | '\'') => self.quoted()?,
+            Some('?') if is_blank(self.peek_at(1)) => {
+                return Err(self.error("Complex keys ('?') are not supported"));
+            }
+            _ => (self.plain_line(false), true),
+        };
+        self.skip_space();
+        if self.peek() == Some(':') && is_blank(self.peek_at(1)) && self.line == line {
+            Ok(key)
+        } else {
+            Err(self.error("Expected ':' after a key"))
+        }
+    }
+
+    fn mapping_value(&mut self, indent: usize) -> Result<Value, ScanError> {
+        self.skip_space();
+        self.skip_comment();
+        if !self.at_eol() {
+            return self.block_node(indent as isize, true);
+        }
+        self.skip_to_content()?;
+        // A sequence may sit at its key's indentation.
+        if self.column == indent
+            && self.peek() == Some('-')
+            && is_blank(self.peek_at(1))
+            && !self.at_document_marker()
+        {
+            self.enter()?;
+            let value = self.block_sequence(indent)?;
+            self.depth -= 1;
+            return Ok(value);
+        }
+        if self.column <= indent {
+            return Ok(Value::Null);
+        }
+        self.block_node(indent as isize, false)
+    }
+
+    /// A plain or quoted scalar in block context, with whether it was plain.
+    fn block_scalar_text(&mut self, parent: isize) -> Result<(String, bool), ScanError> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.quoted();
+        }
+        let mut text = self.plain_line(false);
+        // Continuation lines fold into the scalar when indented past the parent.
+        loop {
+            self.skip_space();
+            if self.peek() != Some('\n') {
+                break;
+            }
+            let saved = (self.pos, self.line, self.column, self.line_start);
+            let mut breaks = 0;
+            while self.peek() == Some('\n') {
+                self.bump();
+                breaks += 1;
+                self.skip_space();
+            }
+            let continues = self.peek().is_some_and(|c| c != '#')
+                && self.column as isize > parent
+                && !self.at_document_marker()
+                && !(self.column == 0 && self.peek() == Some('-') && is_blank(self.peek_at(1)));
+            if !continues {
+                (self.pos, self.line, self.column, self.line_start) = saved;
+                break;
+            }
+            let line = self.plain_line(false);
+            if line.is_empty() {
+                (self.pos, self.line, self.column, self.line_start) = saved;
+                break;
+            }
+            if breaks == 1 {
+                text.push(' ');
+            } else {
+                text.push_str(&"\n".repeat(breaks - 1));
+            }
+            text.push_str(&line);
+        }
+        Ok((text, true))
+    }
+
+    /// Plain scalar text up to the end of the line, a comment or a key's ':'.
+    fn plain_line(&mut self, flow: bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            let next = self.peek_at(1);
+            let stop = match c {
+                '\n' => true,
+                ':' => is_blank(next) || (flow && next.is_some_and(is_flow_indicator)),
+                '#' => text.ends_with([' ', '\t']),
+                c if flow && is_flow_indicator(c) => true,
+                _ => false,
+            };
+            if stop {
+                break;
+            }
+            text.push(c);
+            self.bump();
+        }
+        text.trim_end().to_string()
+    }
+
+    /// A single- or double-quoted scalar; line breaks fold into spaces.
+    fn quoted(&mut self) -> Result<(String, bool), ScanError> {
+        let start = self.error("Unterminated quoted string");
+        let quote = self.bump().unwrap_or('"');
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.bump() else {
+                return Err(start);
+            };
+            match c {
+                '\'' if quote == '\'' && self.peek() == Some('\'') => {
+                    self.bump();
+                    text.push('\'');
+                }
+                c if c == quote => break,
+                '\\' if quote == '"' => {
+                    if self.peek() == Some('\n') {
+                        self.bump();
+                        self.skip_space();
+                        continue;
+                    }
+                    self.escape(&mut text)?;
+                }
+                '\n' => {
+                    let kept = text.trim_end_matches([' ', '\t']).len();
+                    text.truncate(kept);
+                    let mut breaks = 0;
+                    self.skip_space();
+                    while self.peek() == Some('\n') {
+                        self.bump();
+                        breaks += 1;
+                        self.skip_space();
+                    }
+                    if breaks == 0 {
+                        text.push(' ');
+                    } else {
+                        text.push_str(&"\n".repeat(breaks));
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        Ok((text, false))
+    }
+
+    fn escape(&mut self, text: &mut String) -> Result<(), ScanError> {
+        let c = self
+            .bump()
+            .ok_or_else(|| self.error("Unterminated quoted string"))?;
+        let hex = |parser: &mut Self, len: usize| -> Result<char, ScanError> {
+            let mut code = String::new();
+            for _ in 0..len {
+                match parser.bump() {
+                    Some(d) if d.is_ascii_hexdigit() => code.push(d),
+                    _ => return Err(parser.error("Invalid escape")),
+                }
+            }
+            u32::from_str_radix(&code, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| parser.error("Invalid escape"))
+        };
+        let out = match c {
+            '0' => '\0',
+            'a' => '\u{7}',
+            'b' => '\u{8}',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\u{b}',
+            'f' => '\u{c}',
+            'r' => '\r',
+            'e' => '\u{1b}',
+            ' ' => ' ',
+            '"' => '"',
+            '/' => '/',
+            '\\' => '\\',
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            'x' => hex(self, 2)?,
+            'u' => hex(self, 4)?,
+            'U' => hex(self, 8)?,
+            other => return Err(self.error(format!("Invalid escape '\\{}'", other))),
+        };
+        text.push(out);
+        Ok(())
+    }
+
+    /// A `|` literal or `>` folded block scalar.
+    fn block_scalar(&mut self, parent: isize, folded: bool) -> Result<String, ScanError> {
+        self.bump();
+        let mut chomp = Chomp::Clip;
+        let mut explicit = None;
+        for _ in 0..2 {
+            match self.peek() {
+                Some('-') => chomp = Chomp::Strip,
+                Some('+') => chomp = Chomp::Keep,
+                Some(d @ '1'..='9') => explicit = d.to_digit(10).map(|d| d as usize),
+                _ => break,
+            }
+            self.bump();
+        }
+        self.skip_space();
+        self.skip_comment();
+        if !self.at_eol() {
+            return Err(self.error("Unexpected text after a block scalar indicator"));
+        }
+        let base = (parent + 1).max(0) as usize;
+        let mut indent = explicit.map(|n| (parent.max(0) as usize) + n);
+        let mut lines: Vec<String> = Vec::new();
+        while self.peek() == Some('\n') {
+            let saved = (self.pos, self.line, self.column, self.line_start);
+            self.bump();
+            let mut spaces = 0;
+            while self.peek() == Some(' ') {
+                self.bump();
+                spaces += 1;
+            }
+            if self.at_eol() {
+                lines.push(String::new());
+                continue;
+            }
+            let want = *indent.get_or_insert(spaces.max(base));
+            if spaces < want || self.at_document_marker() {
+                (self.pos, self.line, self.column, self.line_start) = saved;
+                break;
+            }
+            let mut line = " ".repeat(spaces - want);
+            while !self.at_eol() {
+                line.push(self.bump().unwrap_or(' '));
+            }
+            lines.push(line);
+        }
+        // The line break ending the scalar is not part of the next node.
+        if self.peek() == Some('\n') {
+            self.bump();
+        }
+        let trailing = lines.iter().rev().take_while(|l| l.is_empty()).count();
+        let content = &lines[..lines.len() - trailing];
+        let mut text = String::new();
+        for (i, line) in content.iter().enumerate() {
+            if i > 0 {
+                let previous = &content[i - 1];
+                let fold = folded
+                    && !line.is_empty()
+                    && !previous.is_empty()
+                    && !line.starts_with([' ', '\t'])
+                    && !previous.starts_with([' ', '\t']);
+                if fold {
+                    text.push(' ');
+                } else if !(folded && previous.is_empty() && !line.starts_with([' ', '\t'])) {
+                    text.push('\n');
+                }
+            }
+            text.push_str(line);
+        }
+        match chomp {
+            Chomp::Strip => {}
+            Chomp::Clip if !content.is_empty() => text.push('\n'),
+            Chomp::Clip => {}
+            Chomp::Keep => text.push_str(&"\n".repeat(trailing + usize::from(!content.is_empty()))),
+        }
+        Ok(text)
+    }
+
+    fn skip_flow_space(&mut self) -> Result<(), ScanError> {
+        loop {
+            self.skip_space();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                None => return Err(self.error("Unterminated flow collection")),
+                Some(_) => return Ok(()),
+            }
+        }
+    }
+
+    fn flow_node(&mut self) -> Result<Value, ScanError> {
+        self.skip_flow_space()?;
+        let (anchor, tag) = self.properties()?;
+        self.enter()?;
+        let value = match self.peek() {
+            Some('[') => self.flow_sequence()?,
+            Some('{') => self.flow_mapping()?,
+            Some('*') => self.alias()?,
+            Some('"' | '\'') => Value::String(self.quoted()?.0),
+            _ => {
+                let text = self.plain_line(true);
+                self.scalar(text, true, tag.as_deref())?
+            }
+        };
+        self.depth -= 1;
+        Ok(self.finish(value, anchor))
+    }
+
+    fn flow_key(&mut self) -> Result<String, ScanError> {
+        self.skip_flow_space()?;
+        Ok(match self.peek() {
+            Some('"' | '\'') => self.quoted()?.0,
+            _ => self.plain_line(true),
+        })
+    }
+
+    fn flow_sequence(&mut self) -> Result<Value, ScanError> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_flow_space()?;
+            if self.peek() == Some(']') {
+                break;
+            }
+            let item = self.flow_node()?;
+            self.skip_flow_space()?;
+            // A single pair inside a sequence: [a: 1] is [{"a": 1}].
+            let item = if self.peek() == Some(':') {
+                self.bump();
+                let key = match item {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                self.skip_flow_space()?;
+                let value = if matches!(self.peek(), Some(',' | ']')) {
+                    Value::Null
+                } else {
+                    self.flow_node()?
+                };
+                self.skip_flow_space()?;
+                serde_json::json!({ key: value })
+            } else {
+                item
+            };
+            items.push(item);
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => break,
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+        self.bump();
+        Ok(Value::Array(items))
+    }
+
+    fn flow_mapping(&mut self) -> Result<Value, ScanError> {
+        self.bump();
+        let mut map = Map::new();
+        loop {
+            self.skip_flow_space()?;
+            if self.peek() == Some('}') {
+                break;
+            }
+            let key = self.flow_key()?;
+            self.skip_flow_space()?;
+            let value = if self.peek() == Some(':') {
+                self.bump();
+                self.skip_flow_space()?;
+                if matches!(self.peek(), Some(',' | '}')) {
+                    Value::Null
+                } else {
+                    self.flow_node()?
+                }
+            } else {
+                Value::Null
+            };
+            if map.contains_key(&key) {
+                return Err(self.error(format!("Duplicate key '{}'", key)));
+            }
+            map.insert(key, value);
+            self.skip_flow_space()?;
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => break,
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+        self.bump();
+        Ok(Value::Object(map))
+    }
+
+    fn document(&mut self) -> Result<Value, ScanError> {
+        self.skip_to_content()?;
+        while self.column == 0 && self.peek() == Some('%') {
+            while !self.at_eol() {
+                self.bump();
+            }
+            self.skip_to_content()?;
+        }
+        if self.at_document_marker() && self.peek() == Some('-') {
+            for _ in 0..3 {
+                self.bump();
+            }
+        }
+        let value = self.block_node(-1, false)?;
+        self.end_of_value()?;
+        self.skip_to_content()?;
+        // An end marker, or a start marker with no document after it.
+        if self.at_document_marker() {
+            let error = self.error("Multiple documents are not supported");
+            for _ in 0..3 {
+                self.bump();
+            }
+            self.skip_to_content()?;
+            if self.peek().is_some() {
+                return Err(error);
+            }
+        }
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(self.error(format!("Unexpected '{}'", c))),
+        }
+    }
+}
+
+/// Parse a single YAML document (block and flow styles, block scalars,
+/// anchors, aliases and `<<` merge keys) into JSON.
+pub(crate) fn parse(text: &str) -> Result<Value, ScanError> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 0,
+        line_start: 0,
+        depth: 0,
+        anchors: HashMap::new(),
+        alias_nodes: 0,
+    };
+    parser.document()
+}
+
+/// Whether a string can be written as a plain scalar and read back as the
+/// same string.
+fn plain_safe(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    !"-?:,[]{}#&*!|>'\"%@` \t".contains(first)
+        && !s.ends_with([' ', '\t', ':'])
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.chars().any(char::is_control)
+        && !LEGACY_BOOLEANS.contains(&s.to_ascii_lowercase().as_str())
+        && matches!(resolve(s), Ok(Value::String(_)))
+}
+
+fn write_string(out: &mut String, s: &str, indent: usize) {
+    if plain_safe(s) {
+        out.push_str(s);
+        return;
+    }
+    // Multi-line text reads best as a literal block.
+    let clip = s.ends_with('\n') && !s.ends_with("\n\n");
+    let block = s.contains('\n')
+        && (clip || !s.ends_with('\n'))
+        && !s.starts_with([' ', '\t', '\n'])
+        && !s.chars().any(|c| c.is_control() && c != '\n' && c != '\t');
+    if !block {
+        out.push_str(&Value::from(s).to_string());
+        return;
+    }
+    out.push_str(if clip { "|" } else { "|-" });
+    for line in s.strip_suffix('\n').unwrap_or(s).split('\n') {
+        out.push('\n');
+        if !line.is_empty() {
+            out.push_str(&" ".repeat(indent));
+            out.push_str(line);
+        }
+    }
+}
+
+/// Scalars and empty collections, which stay on their key's line.
+fn write_inline(out: &mut String, value: &Value, indent: usize) -> bool {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_string(out, s, indent),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        _ => return false,
+    }
+    true
+}
+
+/// Write a non-empty collection as a block, each line indented by `indent`.
+fn write_block(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map {
+                out.push_str(&pad);
+                if plain_safe(key) {
+                    out.push_str(key);
+                } else {
+                    out.push_str(&Value::from(key.as_str()).to_string());
+                }
+                out.push(':');
+                let mut inline = String::from(" ");
+                if write_inline(&mut inline, item, indent + 2) {
+                    out.push_str(&inline);
+                    out.push('\n');
+                } else {
+                    out.push('\n');
+                    write_block(out, item, indent + 2);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                out.push_str(&pad);
+                out.push('-');
+                let mut inline = String::from(" ");
+                if write_inline(&mut inline, item, indent + 2) {
+                    out.push_str(&inline);
+                    out.push('\n');
+                } else {
+                    // The nested block's first line goes after the dash.
+                    let mut nested = String::new();
+                    write_block(&mut nested, item, indent + 2);
+                    out.push(' ');
+                    out.push_str(&nested[indent + 2..]);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Convert JSON to YAML (block style, two-space indentation, keys in their
+/// original order). Strings are quoted only when they would otherwise read
+/// as something else; multi-line strings become literal blocks.
+/// Returns JSON {yaml} or {error}.
+#[wasm_bindgen]
+pub fn json_to_yaml(json_str: &str) -> String {
+    let value: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return serde_json::json!({ "error": format!("Invalid JSON: {}", e) }).to_string();
+        }
+    };
+    let mut yaml = String::new();
+    if write_inline(&mut yaml, &value, 2) {
+        yaml.push('\n');
+    } else {
+        write_block(&mut yaml, &value, 0);
+    }
+    serde_json::json!({ "yaml": yaml }).to_string()
+}
+
+/// Convert a YAML document (e.g. an OpenAPI spec or a request body) to
+/// pretty-printed JSON. Supports block and flow collections, plain, quoted
+/// and block (`|`, `>`) scalars, comments, anchors and aliases, and `<<`
+/// merge keys; scalars resolve with the YAML 1.2 core schema, and keys are
+/// always strings.
+/// Returns JSON {json} or {error, line, column}.
+#[wasm_bindgen]
+pub fn yaml_to_json(yaml_str: &str) -> String {
+    let _timer = crate::logging::timer("yaml_to_json");
+    match parse(yaml_str) {
+        Ok(value) => serde_json::json!({
+            "json": serde_json::to_string_pretty(&value).unwrap_or_default(),
+        })
+        .to_string(),
+        Err(e) => serde_json::json!({
+            "error": e.message,
+            "line": e.position.line,
+            "column": e.position.column,
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn yaml(text: &str) -> Value {
+        parse(text).unwrap_or_else(|e| {
+            panic!("{} at {}:{}", e.message, e.position.line, e.position.column)
+        })
+    }
+
+    #[test]
+    fn test_parse_block_yaml() {
+        let text = "\
+# An OpenAPI-ish document
+openapi: 3.0.0
+info:
+  title: Pets API   # trailing comment
+  version: '1.0'
+  description: >
+    Folded text
+    on two lines.
+
+    New paragraph.
+servers:
+- url: https://api.x.io/v1
+  variables: {region: {default: eu, enum: [eu, us]}}
+tags: [pets, 'store', \"a\\tb\"]
+limits:
+  max: 0x10
+  ratio: 1.5e3
+  big: 123456789012345678901234567890
+  none: ~
+  off: off
+  yes: true
+script: |-
+  line 1
+    indented
+  line 3
+plain: a long
+  wrapped value
+nested:
+  - - 1
+    - 2
+  - key: value
+    other: [1, {a: b}]
+";
+        assert_eq!(
+            yaml(text),
+            json!({
+                "openapi": "3.0.0",
+                "info": {
+                    "title": "Pets API",
+                    "version": "1.0",
+                    "description": "Folded text on two lines.\nNew paragraph.\n",
+                },
+                "servers": [{"url": "https://api.x.io/v1", "variables": {"region": {"default": "eu", "enum": ["eu", "us"]}}}],
+                "tags": ["pets", "store", "a\tb"],
+                "limits": {"max": 16, "ratio": serde_json::from_str::<Value>("1.5e3").unwrap(), "big": serde_json::from_str::<Value>("123456789012345678901234567890").unwrap(), "none": null, "off": "off", "yes": true},
+                "script": "line 1\n  indented\nline 3",
+                "plain": "a long wrapped value",
+                "nested": [[1, 2], {"key": "value", "other": [1, {"a": "b"}]}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_anchors_and_errors() {
+        let text = "base: &base {a: 1, b: 2}\nderived:\n  <<: *base\n  b: 3\nlist: [*base]\n---\n";
+        assert_eq!(
+            yaml(text),
+            json!({"base": {"a": 1, "b": 2}, "derived": {"b": 3, "a": 1}, "list": [{"a": 1, "b": 2}]})
+        );
+        assert_eq!(yaml("- 1\n- two\n-\n"), json!([1, "two", null]));
+        assert_eq!(
+            yaml("a:\n  b:\n  c: 1\n"),
+            json!({"a": {"b": null, "c": 1}})
+        );
+        assert_eq!(yaml("'it''s'"), json!("it's"));
+        assert_eq!(yaml(""), Value::Null);
+
+        let error = |text: &str| {
+            let e = parse(text).unwrap_err();
+            (e.message, e.position.line)
+        };
+        assert_eq!(
+            error("a: 1\n  b: 2\n"),
+            ("Unexpected ':' after a value".to_string(), 2)
+        );
+        assert_eq!(error("a: 1\na: 2\n"), ("Duplicate key 'a'".to_string(), 2));
+        assert_eq!(
+            error("a:\n\t- 1\n"),
+            ("Tabs are not allowed for indentation".to_string(), 2)
+        );
+        assert_eq!(
+            error("a: [1, 2\n"),
+            ("Unterminated flow collection".to_string(), 2)
+        );
+        assert_eq!(error("a: *nope\n").0, "Unknown alias '*nope'");
+        assert_eq!(
+            error("a: 1\n---\nb: 2\n").0,
+            "Multiple documents are not supported"
+        );
+        assert_eq!(error("x: .inf").0, ".inf cannot be represented in JSON");
+    }
+
+    #[test]
+    fn test_json_yaml_roundtrip() {
+        let doc = json!({
+            "name": "Volt",
+            "empty": "",
+            "tricky": ["true", "no", "1.5", "- a", "a: b", "#x", " pad", "null", "~"],
+            "multi": "line 1\nline 2\n",
+            "multiStrip": "a\n\nb",
+            "nested": [{"a": [1, 2], "b": {}}, [[]], [{"c": null}]],
+            "key: colon": 1,
+            "n": 1.25,
+        });
+        let out: Value = serde_json::from_str(&json_to_yaml(&doc.to_string())).unwrap();
+        let text = out["yaml"].as_str().unwrap();
+        assert!(text.starts_with("name: Volt\nempty: \"\"\ntricky:\n  - \"true\"\n  - \"no\"\n"));
+        assert!(text.contains("multi: |\n  line 1\n  line 2\n"));
+        assert!(text.contains("nested:\n  - a:\n      - 1\n      - 2\n    b: {}\n  - - []\n"));
+        assert_eq!(yaml(text), doc);
+
+        let out: Value = serde_json::from_str(&yaml_to_json("a:\n  - b: [1,\n    2]\n")).unwrap();
+        assert_eq!(
+            out["json"],
+            "{\n  \"a\": [\n    {\n      \"b\": [\n        1,\n        2\n      ]\n    }\n  ]\n}"
+        );
+        let out: Value = serde_json::from_str(&yaml_to_json("a: 'x\nb: 1")).unwrap();
+        assert_eq!(
+            (out["error"].clone(), out["line"].clone()),
+            (json!("Unterminated quoted string"), json!(1))
+        );
+    }
+}
@@ -0,0 +1,177 @@
+/// Minimal ZIP container support: stored (uncompressed) entries only, which is
+/// all the bundle format writes. Names are flagged as UTF-8.
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR: u32 = 0x0605_4b50;
+const UTF8_FLAG: u16 = 0x0800;
+const VERSION: u16 = 20;
+
+/// Build an archive from (name, data) entries, stamping them with `modified_ms`.
+pub(crate) fn write(entries: &[(String, Vec<u8>)], modified_ms: f64) -> Vec<u8> {
+    let (time, date) = dos_datetime(modified_ms);
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+
+        put_u32(&mut out, LOCAL_HEADER);
+        for v in [VERSION, UTF8_FLAG, 0, time, date] {
+            put_u16(&mut out, v);
+        }
+        for v in [crc, data.len() as u32, data.len() as u32] {
+            put_u32(&mut out, v);
+        }
+        put_u16(&mut out, name.len() as u16);
+        put_u16(&mut out, 0);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        put_u32(&mut central, CENTRAL_HEADER);
+        for v in [VERSION, VERSION, UTF8_FLAG, 0, time, date] {
+            put_u16(&mut central, v);
+        }
+        for v in [crc, data.len() as u32, data.len() as u32] {
+            put_u32(&mut central, v);
+        }
+        for v in [name.len() as u16, 0, 0, 0, 0] {
+            put_u16(&mut central, v);
+        }
+        put_u32(&mut central, 0);
+        put_u32(&mut central, offset);
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    put_u32(&mut out, END_OF_CENTRAL_DIR);
+    for v in [0, 0, entries.len() as u16, entries.len() as u16] {
+        put_u16(&mut out, v);
+    }
+    put_u32(&mut out, central.len() as u32);
+    put_u32(&mut out, central_offset);
+    put_u16(&mut out, 0);
+    out
+}
+
+/// Read every entry of an archive, verifying checksums.
+pub(crate) fn read(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let not_zip = || "Not a ZIP archive".to_string();
+    // The end record is 22 bytes plus an optional comment of up to 64 KiB.
+    let eocd = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + 0xFFFF)
+        .find(|&i| get_u32(bytes, i) == Some(END_OF_CENTRAL_DIR))
+        .ok_or_else(not_zip)?;
+    let count = get_u16(bytes, eocd + 10).ok_or_else(not_zip)? as usize;
+    let mut pos = get_u32(bytes, eocd + 16).ok_or_else(not_zip)? as usize;
+
+    let corrupt = |what: &str| format!("Corrupted archive: {}", what);
+    let mut entries = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        if get_u32(bytes, pos) != Some(CENTRAL_HEADER) {
+            return Err(corrupt("bad central directory"));
+        }
+        let field = |at: usize| get_u16(bytes, pos + at).ok_or_else(|| corrupt("truncated"));
+        let method = field(10)?;
+        let crc = get_u32(bytes, pos + 16).ok_or_else(|| corrupt("truncated"))?;
+        let size = get_u32(bytes, pos + 20).ok_or_else(|| corrupt("truncated"))? as usize;
+        let (name_len, extra_len, comment_len) = (
+            field(28)? as usize,
+            field(30)? as usize,
+            field(32)? as usize,
+        );
+        let local = get_u32(bytes, pos + 42).ok_or_else(|| corrupt("truncated"))? as usize;
+        let name = bytes
+            .get(pos + 46..pos + 46 + name_len)
+            .ok_or_else(|| corrupt("truncated"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        pos += 46 + name_len + extra_len + comment_len;
+
+        if method != 0 {
+            return Err(format!("Unsupported compression method for \"{}\"", name));
+        }
+        if get_u32(bytes, local) != Some(LOCAL_HEADER) {
+            return Err(corrupt("bad local header"));
+        }
+        let local_name = get_u16(bytes, local + 26).ok_or_else(|| corrupt("truncated"))?;
+        let local_extra = get_u16(bytes, local + 28).ok_or_else(|| corrupt("truncated"))?;
+        let start = local + 30 + local_name as usize + local_extra as usize;
+        let data = bytes
+            .get(start..start + size)
+            .ok_or_else(|| corrupt("truncated"))?;
+        if crc32(data) != crc {
+            return Err(corrupt(&format!("checksum mismatch in \"{}\"", name)));
+        }
+        entries.push((name, data.to_vec()));
+    }
+    Ok(entries)
+}
+
+/// CRC-32 (IEEE 802.3), as used by ZIP.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (!(crc & 1)).wrapping_add(1));
+        }
+    }
+    !crc
+}
+
+/// MS-DOS (time, date) for a Unix timestamp in ms, in UTC; clamped to 1980.
+fn dos_datetime(ms: f64) -> (u16, u16) {
+    let secs = (ms / 1000.0).max(315_532_800.0) as i64;
+    let (year, month, day) = crate::civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    let time = ((rem / 3600) << 11) | (((rem % 3600) / 60) << 5) | ((rem % 60) / 2);
+    let date = ((year - 1980).min(127) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+fn put_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn get_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn get_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_and_dos_datetime() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        // 2024-02-29T13:45:30Z
+        let (time, date) = dos_datetime(1_709_214_330_000.0);
+        assert_eq!(date, (44 << 9) | (2 << 5) | 29);
+        assert_eq!(time, (13 << 11) | (45 << 5) | 15);
+    }
+
+    #[test]
+    fn test_zip_round_trip_and_corruption() {
+        let entries = vec![
+            ("manifest.json".to_string(), b"{}".to_vec()),
+            ("collections/ü.json".to_string(), b"[1,2,3]".to_vec()),
+        ];
+        let bytes = write(&entries, 0.0);
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(read(&bytes).unwrap(), entries);
+
+        let mut corrupt = bytes.clone();
+        corrupt[30 + "manifest.json".len()] ^= 1;
+        assert!(read(&corrupt).unwrap_err().contains("checksum mismatch"));
+        assert_eq!(read(b"plain text").unwrap_err(), "Not a ZIP archive");
+    }
+}